@@ -0,0 +1,341 @@
+//! Merkle Core
+//!
+//! `core`-only leaf hashing and proof verification for merkle-distributor
+//! trees. The on-chain program and the builder (`merkle-tree`) both hash
+//! through this crate, and claim UIs run the same code in the browser via
+//! the `wasm` feature instead of re-implementing it.
+//!
+//! ## Overview
+//!
+//! - `leaf = H([0x00] || DOMAIN_SEPARATOR || distribution_id || recipient || amount_le)`
+//! - `node = H([0x01] || left || right)`
+//! - The `0x00` / `0x01` prefixes are only present under `HashScheme::V2`
+//! - `left` / `right` are the sorted pair (`ProofMode::Sorted`) or given by
+//!   the bits of the leaf index (`ProofMode::Positional`)
+//! - `H` is Keccak-256 or SHA-256 (`HashKind`)
+//!
+//! On Solana the hashes go through the Keccak / SHA-256 syscalls; elsewhere
+//! through `sha3` / `sha2`.
+//!
+//! ```ignore
+//! let leaf = compute_leaf(HashKind::Keccak, HashScheme::V1, &distribution_id, &recipient, amount);
+//! assert!(verify_proof(HashKind::Keccak, HashScheme::V1, ProofMode::Sorted, &proof, &root, leaf, index));
+//! ```
+
+#![no_std]
+
+// Only the multiproof queue allocates
+extern crate alloc;
+// The crate is built as a cdylib for wasm-pack, which needs std's panic
+// handler to link; the code itself only uses `core` and `alloc`.
+extern crate std;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use alloc::vec::Vec;
+
+/// Domain separator for leaf hashing (must match on-chain program)
+pub const DOMAIN_SEPARATOR: &[u8] = b"L33_MERKLE_V1";
+
+/// Domain separator for vesting leaves (unlock / expiry committed in the
+/// leaf). Unrelated to `HashScheme::V2`; the bytes predate the rename and
+/// are kept so existing tree files still verify.
+pub const VESTING_DOMAIN_SEPARATOR: &[u8] = b"L33_MERKLE_V2";
+
+/// Leaf hash prefix for `HashScheme::V2`
+pub const LEAF_PREFIX: u8 = 0x00;
+
+/// Internal node hash prefix for `HashScheme::V2`
+pub const NODE_PREFIX: u8 = 0x01;
+
+/// Maximum proof depth accepted on-chain
+pub const MAX_PROOF_LEN: usize = 20;
+
+/// Hash function for leaves and nodes (matches the on-chain `HashKind`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashKind {
+    /// Keccak-256 (default)
+    #[default]
+    Keccak = 0,
+    /// SHA-256
+    Sha256 = 1,
+}
+
+impl HashKind {
+    /// Kind stored as `byte` on-chain and in tree files
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(HashKind::Keccak),
+            1 => Some(HashKind::Sha256),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name used in JSON output (`keccak`, `sha256`)
+    pub fn name(self) -> &'static str {
+        match self {
+            HashKind::Keccak => "keccak",
+            HashKind::Sha256 => "sha256",
+        }
+    }
+
+    /// Hash the concatenation of `parts`
+    pub fn hashv(self, parts: &[&[u8]]) -> [u8; 32] {
+        match self {
+            HashKind::Keccak => keccak(parts),
+            HashKind::Sha256 => sha256(parts),
+        }
+    }
+}
+
+/// Leaf / node prefixing (matches the on-chain `HashScheme`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashScheme {
+    /// Unprefixed leaves and internal nodes (legacy, default)
+    #[default]
+    V1 = 0,
+    /// Leaves prefixed with `LEAF_PREFIX`, internal nodes with
+    /// `NODE_PREFIX`, so an internal node can never be presented as a leaf
+    V2 = 1,
+}
+
+impl HashScheme {
+    /// Scheme stored as `byte` on-chain
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(HashScheme::V1),
+            1 => Some(HashScheme::V2),
+            _ => None,
+        }
+    }
+}
+
+/// Sibling ordering used when verifying proofs (matches the on-chain
+/// `ProofMode`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofMode {
+    /// Siblings are sorted before hashing (default)
+    #[default]
+    Sorted = 0,
+    /// Bit `i` of the leaf index gives the node's position at level `i`
+    /// (0 = left, 1 = right)
+    Positional = 1,
+}
+
+impl ProofMode {
+    /// Mode stored as `byte` on-chain
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(ProofMode::Sorted),
+            1 => Some(ProofMode::Positional),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "solana")]
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    solana_keccak_hasher::hashv(parts).to_bytes()
+}
+
+#[cfg(target_os = "solana")]
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    solana_sha256_hasher::hashv(parts).to_bytes()
+}
+
+#[cfg(not(target_os = "solana"))]
+fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    digest::<sha3::Keccak256>(parts)
+}
+
+#[cfg(not(target_os = "solana"))]
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    digest::<sha2::Sha256>(parts)
+}
+
+#[cfg(not(target_os = "solana"))]
+fn digest<D: sha2::Digest>(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&hasher.finalize());
+    out
+}
+
+/// Compute leaf hash using domain separation
+pub fn compute_leaf(
+    kind: HashKind,
+    scheme: HashScheme,
+    distribution_id: &[u8; 32],
+    recipient: &[u8; 32],
+    amount: u64,
+) -> [u8; 32] {
+    let amount = amount.to_le_bytes();
+    match scheme {
+        HashScheme::V1 => kind.hashv(&[DOMAIN_SEPARATOR, distribution_id, recipient, &amount]),
+        HashScheme::V2 => kind.hashv(&[
+            &[LEAF_PREFIX],
+            DOMAIN_SEPARATOR,
+            distribution_id,
+            recipient,
+            &amount,
+        ]),
+    }
+}
+
+/// Compute a vesting leaf hash (unlock / expiry committed in the leaf)
+pub fn compute_vesting_leaf(
+    kind: HashKind,
+    distribution_id: &[u8; 32],
+    recipient: &[u8; 32],
+    amount: u64,
+    unlock_at: i64,
+    expires_at: i64,
+) -> [u8; 32] {
+    kind.hashv(&[
+        VESTING_DOMAIN_SEPARATOR,
+        distribution_id,
+        recipient,
+        &amount.to_le_bytes(),
+        &unlock_at.to_le_bytes(),
+        &expires_at.to_le_bytes(),
+    ])
+}
+
+/// Hash two nodes, sorting for determinism
+pub fn hash_pair(kind: HashKind, scheme: HashScheme, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    hash_ordered(kind, scheme, first, second)
+}
+
+/// Hash a left and right node in the given order
+pub fn hash_ordered(
+    kind: HashKind,
+    scheme: HashScheme,
+    left: &[u8; 32],
+    right: &[u8; 32],
+) -> [u8; 32] {
+    match scheme {
+        HashScheme::V1 => kind.hashv(&[left, right]),
+        HashScheme::V2 => kind.hashv(&[&[NODE_PREFIX], left, right]),
+    }
+}
+
+/// Verify a Merkle proof the same way the on-chain program does
+///
+/// `index` is only used in `ProofMode::Positional`, where it must address a
+/// leaf within the proof's depth so one leaf cannot be claimed under
+/// several indices.
+pub fn verify_proof(
+    kind: HashKind,
+    scheme: HashScheme,
+    mode: ProofMode,
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+    leaf: [u8; 32],
+    index: u64,
+) -> bool {
+    if proof.len() > MAX_PROOF_LEN {
+        return false;
+    }
+
+    let mut current = leaf;
+
+    match mode {
+        ProofMode::Sorted => {
+            for sibling in proof {
+                current = hash_pair(kind, scheme, &current, sibling);
+            }
+        }
+        ProofMode::Positional => {
+            if index >> proof.len() != 0 {
+                return false;
+            }
+            for (level, sibling) in proof.iter().enumerate() {
+                current = if (index >> level) & 1 == 0 {
+                    hash_ordered(kind, scheme, &current, sibling)
+                } else {
+                    hash_ordered(kind, scheme, sibling, &current)
+                };
+            }
+        }
+    }
+
+    current == *root
+}
+
+/// Verify a Merkle multiproof for several leaves of a sorted-pair tree
+///
+/// `leaves` must be in tree order. Each hash step takes its first input
+/// from the queue of leaves followed by computed hashes; `proof_flags[i]`
+/// says whether the second input comes from that queue too (`true`) or from
+/// `proof` (`false`).
+pub fn verify_multiproof(
+    kind: HashKind,
+    scheme: HashScheme,
+    proof: &[[u8; 32]],
+    proof_flags: &[bool],
+    root: &[u8; 32],
+    leaves: &[[u8; 32]],
+) -> bool {
+    process_multiproof(kind, scheme, proof, proof_flags, leaves)
+        .is_some_and(|computed| computed == *root)
+}
+
+/// Compute the root implied by a multiproof, or `None` if it is malformed
+fn process_multiproof(
+    kind: HashKind,
+    scheme: HashScheme,
+    proof: &[[u8; 32]],
+    proof_flags: &[bool],
+    leaves: &[[u8; 32]],
+) -> Option<[u8; 32]> {
+    if leaves.is_empty() || leaves.len() + proof.len() != proof_flags.len() + 1 {
+        return None;
+    }
+    if proof_flags.is_empty() {
+        return Some(leaves[0]);
+    }
+
+    let mut hashes: Vec<[u8; 32]> = Vec::with_capacity(proof_flags.len());
+    let mut leaf_pos = 0;
+    let mut hash_pos = 0;
+    let mut proof_pos = 0;
+
+    for &flag in proof_flags {
+        let a = next_multiproof_node(leaves, &hashes, &mut leaf_pos, &mut hash_pos)?;
+        let b = if flag {
+            next_multiproof_node(leaves, &hashes, &mut leaf_pos, &mut hash_pos)?
+        } else {
+            proof_pos += 1;
+            *proof.get(proof_pos - 1)?
+        };
+        hashes.push(hash_pair(kind, scheme, &a, &b));
+    }
+
+    // Every input must be consumed exactly once
+    if leaf_pos != leaves.len() || hash_pos != hashes.len() - 1 || proof_pos != proof.len() {
+        return None;
+    }
+
+    hashes.last().copied()
+}
+
+/// Pop the next node from the multiproof queue (leaves, then computed hashes)
+fn next_multiproof_node(
+    leaves: &[[u8; 32]],
+    hashes: &[[u8; 32]],
+    leaf_pos: &mut usize,
+    hash_pos: &mut usize,
+) -> Option<[u8; 32]> {
+    if *leaf_pos < leaves.len() {
+        *leaf_pos += 1;
+        Some(leaves[*leaf_pos - 1])
+    } else {
+        *hash_pos += 1;
+        hashes.get(*hash_pos - 1).copied()
+    }
+}
@@ -0,0 +1,122 @@
+//! JavaScript bindings (`wasm` feature)
+//!
+//! Hashes are `Uint8Array`s, amounts, timestamps and indices `bigint`s, and
+//! hash kinds, schemes and proof modes their on-chain byte (kind: 0 =
+//! Keccak, 1 = SHA-256; scheme: 0 = V1, 1 = V2; mode: 0 = sorted, 1 =
+//! positional). A proof is passed as its nodes concatenated into one
+//! `Uint8Array` of `32 * depth` bytes. Malformed input throws instead of
+//! failing verification.
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{HashKind, HashScheme, ProofMode};
+
+fn hash_kind(byte: u8) -> Result<HashKind, JsError> {
+    HashKind::from_u8(byte).ok_or_else(|| JsError::new(&format!("unknown hash kind {byte}")))
+}
+
+fn hash_scheme(byte: u8) -> Result<HashScheme, JsError> {
+    HashScheme::from_u8(byte).ok_or_else(|| JsError::new(&format!("unknown hash scheme {byte}")))
+}
+
+fn proof_mode(byte: u8) -> Result<ProofMode, JsError> {
+    ProofMode::from_u8(byte).ok_or_else(|| JsError::new(&format!("unknown proof mode {byte}")))
+}
+
+fn hash32(name: &str, bytes: &[u8]) -> Result<[u8; 32], JsError> {
+    bytes
+        .try_into()
+        .map_err(|_| JsError::new(&format!("{name} must be 32 bytes, got {}", bytes.len())))
+}
+
+/// Leaf hash of an allocation
+#[wasm_bindgen(js_name = computeLeaf)]
+pub fn compute_leaf(
+    hash_kind_byte: u8,
+    hash_scheme_byte: u8,
+    distribution_id: &[u8],
+    recipient: &[u8],
+    amount: u64,
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::compute_leaf(
+        hash_kind(hash_kind_byte)?,
+        hash_scheme(hash_scheme_byte)?,
+        &hash32("distributionId", distribution_id)?,
+        &hash32("recipient", recipient)?,
+        amount,
+    )
+    .to_vec())
+}
+
+/// Leaf hash of a vesting allocation (0 = no unlock / expiry)
+#[wasm_bindgen(js_name = computeVestingLeaf)]
+pub fn compute_vesting_leaf(
+    hash_kind_byte: u8,
+    distribution_id: &[u8],
+    recipient: &[u8],
+    amount: u64,
+    unlock_at: i64,
+    expires_at: i64,
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::compute_vesting_leaf(
+        hash_kind(hash_kind_byte)?,
+        &hash32("distributionId", distribution_id)?,
+        &hash32("recipient", recipient)?,
+        amount,
+        unlock_at,
+        expires_at,
+    )
+    .to_vec())
+}
+
+/// Sorted hash of two nodes
+#[wasm_bindgen(js_name = hashPair)]
+pub fn hash_pair(
+    hash_kind_byte: u8,
+    hash_scheme_byte: u8,
+    a: &[u8],
+    b: &[u8],
+) -> Result<Vec<u8>, JsError> {
+    Ok(crate::hash_pair(
+        hash_kind(hash_kind_byte)?,
+        hash_scheme(hash_scheme_byte)?,
+        &hash32("a", a)?,
+        &hash32("b", b)?,
+    )
+    .to_vec())
+}
+
+/// Whether `proof` (concatenated nodes) connects the leaf at `index` to
+/// `root`
+#[wasm_bindgen(js_name = verifyProof)]
+pub fn verify_proof(
+    hash_kind_byte: u8,
+    hash_scheme_byte: u8,
+    proof_mode_byte: u8,
+    root: &[u8],
+    leaf: &[u8],
+    proof: &[u8],
+    index: u64,
+) -> Result<bool, JsError> {
+    let nodes = proof.chunks_exact(32);
+    if !nodes.remainder().is_empty() {
+        return Err(JsError::new(&format!(
+            "proof length {} is not a multiple of 32",
+            proof.len()
+        )));
+    }
+    let proof: Vec<[u8; 32]> = nodes.map(|node| node.try_into().unwrap()).collect();
+
+    Ok(crate::verify_proof(
+        hash_kind(hash_kind_byte)?,
+        hash_scheme(hash_scheme_byte)?,
+        proof_mode(proof_mode_byte)?,
+        &proof,
+        &hash32("root", root)?,
+        hash32("leaf", leaf)?,
+        index,
+    ))
+}
@@ -0,0 +1,107 @@
+//! Anchor events (`emit!`) of the distributor
+//!
+//! `emit!` logs `Program data: <base64>` where the payload is the event's
+//! 8-byte discriminator, `sha256("event:<Name>")[..8]`, followed by its
+//! Borsh encoding.
+//!
+//! `emit_cpi!` carries the same payload in the data of an instruction the
+//! program invokes on itself, after the 8-byte `EVENT_IX_TAG`. Logs can be
+//! truncated by the runtime; inner instruction data cannot.
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+
+use crate::{DecodeError, ProgramEvent, TotalsVerifiedEvent};
+
+/// Instruction data prefix of Anchor's self-CPI event instruction
+/// (`anchor_lang::event::EVENT_IX_TAG_LE`)
+pub const EVENT_IX_TAG: [u8; 8] = 0x1d9a_cb51_2ea5_45e4u64.to_le_bytes();
+
+/// Anchor discriminator of the event named `name`
+pub fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("event:{name}"));
+    hash[..8].try_into().unwrap()
+}
+
+/// Decode one `Program data:` payload (base64, without the prefix)
+pub fn decode_base64(data: &str) -> Result<Option<ProgramEvent>, DecodeError> {
+    decode(&base64::engine::general_purpose::STANDARD.decode(data)?)
+}
+
+/// Decode the data of an instruction the program invoked on itself
+///
+/// `Ok(None)` for instructions that are not `emit_cpi!` events.
+pub fn decode_cpi(data: &[u8]) -> Result<Option<ProgramEvent>, DecodeError> {
+    match data.strip_prefix(EVENT_IX_TAG.as_slice()) {
+        Some(payload) => decode(payload),
+        None => Ok(None),
+    }
+}
+
+/// Decode an event payload (discriminator and Borsh data)
+///
+/// `Ok(None)` for discriminators of events the distributor does not emit.
+pub fn decode(data: &[u8]) -> Result<Option<ProgramEvent>, DecodeError> {
+    let Some((disc, body)) = data.split_first_chunk::<8>() else {
+        return Ok(None);
+    };
+    if *disc == discriminator("TotalsVerified") {
+        return decode_totals_verified(body).map(|e| Some(ProgramEvent::TotalsVerified(e)));
+    }
+    Ok(None)
+}
+
+/// Pubkey, seven u64s and a bool
+const TOTALS_VERIFIED_LEN: usize = 32 + 7 * 8 + 1;
+
+fn decode_totals_verified(body: &[u8]) -> Result<TotalsVerifiedEvent, DecodeError> {
+    const EVENT: &str = "TotalsVerified";
+    if body.len() != TOTALS_VERIFIED_LEN {
+        return Err(DecodeError::EventLength {
+            event: EVENT,
+            len: body.len(),
+            expected: TOTALS_VERIFIED_LEN,
+        });
+    }
+
+    let mut reader = Reader(body);
+    Ok(TotalsVerifiedEvent {
+        distribution: reader.pubkey(),
+        total_amount: reader.u64(),
+        claimed_amount: reader.u64(),
+        clawed_back_amount: reader.u64(),
+        escrowed_amount: reader.u64(),
+        vault_amount: reader.u64(),
+        num_recipients: reader.u64(),
+        num_claimed: reader.u64(),
+        ok: match reader.u8() {
+            0 => false,
+            1 => true,
+            byte => return Err(DecodeError::InvalidBool { event: EVENT, byte }),
+        },
+    })
+}
+
+/// Borsh reader over a body whose length was checked up front
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let (head, rest) = self.0.split_first_chunk::<N>().unwrap();
+        self.0 = rest;
+        *head
+    }
+
+    fn pubkey(&mut self) -> Pubkey {
+        Pubkey::new_from_array(self.take())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take())
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+}
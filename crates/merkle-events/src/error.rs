@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+/// Errors raised while decoding distributor logs
+///
+/// Lines the distributor did not write, and `msg!` lines that are not
+/// events (config and metadata updates), are skipped rather than rejected.
+/// These errors mean the program wrote an event this crate cannot read,
+/// which is a log format change to add to `LogFormat`, not data to drop.
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    #[error("unrecognized event message: {0:?}")]
+    UnrecognizedMessage(String),
+    #[error("invalid base64 in event data: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("{event} event data is {len} bytes, expected {expected}")]
+    EventLength {
+        event: &'static str,
+        len: usize,
+        expected: usize,
+    },
+    #[error("{event} event has invalid bool byte {byte}")]
+    InvalidBool { event: &'static str, byte: u8 },
+}
@@ -0,0 +1,157 @@
+//! Merkle Events
+//!
+//! Typed decoding of what `merkle_distributor` writes to transaction logs:
+//! the `msg!` lines of claim and admin instructions (`message`) and its
+//! Anchor events (`anchor`). The claim event indexer decodes through this
+//! crate via the `wasm` feature, so every consumer reads the program's logs
+//! the same way.
+//!
+//! ## Overview
+//!
+//! - `decode_transaction` returns the distributor's events in a
+//!   transaction, from its log messages and its self-CPI (`emit_cpi!`)
+//!   inner instructions, attributed to the instruction that invoked the
+//!   distributor: a top-level instruction, or an inner one for CPI
+//! - `decode_logs` does the same from log messages alone
+//! - `message::decode` parses one `Program log:` message, trying every
+//!   `LogFormat` the program has shipped
+//! - `anchor::decode` parses one event payload, from a `Program data:` line
+//!   or a self-CPI instruction
+//!
+//! ```ignore
+//! for decoded in decode_transaction(&log_messages, &inner_instructions, &program_id)? {
+//!     if let ProgramEvent::Claimed(claim) = decoded.event {
+//!         println!("{} claimed {}", claim.recipient, claim.amount);
+//!     }
+//! }
+//! ```
+
+pub mod anchor;
+pub mod error;
+pub mod logs;
+pub mod message;
+pub mod transaction;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::DecodeError;
+pub use logs::{decode_logs, DecodedEvent, Encoding};
+pub use message::LogFormat;
+pub use solana_pubkey::Pubkey;
+pub use transaction::{decode_transaction, InnerInstruction};
+
+/// `claim` / `claim_batch`: tokens transferred to the recipient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimedEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Leaf index in the Merkle tree
+    pub index: u64,
+}
+
+/// `claim_with_referral`: a claim that also paid the referrer a bonus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimedWithReferralEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub index: u64,
+    pub referrer: Pubkey,
+    pub bonus: u64,
+}
+
+/// `claim_to_escrow`: the claim was recorded and the tokens held in escrow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowedEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub index: u64,
+}
+
+/// `renounce_claim`: the recipient gave up the allocation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenouncedEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub index: u64,
+}
+
+/// `release_escrow`: escrowed tokens transferred to the recipient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowReleasedEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// `clawback`: vault tokens returned to the clawback destination
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClawbackEvent {
+    pub amount: u64,
+    /// Clawback destination token account (`None` for `LogFormat::V0`,
+    /// which returned the vault to the authority without logging where)
+    pub destination: Option<Pubkey>,
+    /// Vault balance left after the clawback
+    pub remaining: u64,
+}
+
+/// `set_operator`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperatorChangedEvent {
+    pub operator: Pubkey,
+}
+
+/// `sweep_dust`: residual vault dust sent to the dust collector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DustSweptEvent {
+    pub amount: u64,
+}
+
+/// `verify_totals` (Anchor event `TotalsVerified`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TotalsVerifiedEvent {
+    pub distribution: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub clawed_back_amount: u64,
+    pub escrowed_amount: u64,
+    pub vault_amount: u64,
+    pub num_recipients: u64,
+    pub num_claimed: u64,
+    /// Whether all invariants held
+    pub ok: bool,
+}
+
+/// An event of the distributor program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgramEvent {
+    Claimed(ClaimedEvent),
+    ClaimedWithReferral(ClaimedWithReferralEvent),
+    Escrowed(EscrowedEvent),
+    Renounced(RenouncedEvent),
+    EscrowReleased(EscrowReleasedEvent),
+    Clawback(ClawbackEvent),
+    Paused,
+    Unpaused,
+    OperatorChanged(OperatorChangedEvent),
+    DustSwept(DustSweptEvent),
+    TotalsVerified(TotalsVerifiedEvent),
+}
+
+impl ProgramEvent {
+    /// Snake-case name, as stored in `merkle_claim_events.kind` for the
+    /// kinds the indexer records
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProgramEvent::Claimed(_) => "claim",
+            ProgramEvent::ClaimedWithReferral(_) => "claim_referral",
+            ProgramEvent::Escrowed(_) => "escrow",
+            ProgramEvent::Renounced(_) => "renounce",
+            ProgramEvent::EscrowReleased(_) => "escrow_release",
+            ProgramEvent::Clawback(_) => "clawback",
+            ProgramEvent::Paused => "pause",
+            ProgramEvent::Unpaused => "unpause",
+            ProgramEvent::OperatorChanged(_) => "operator_change",
+            ProgramEvent::DustSwept(_) => "dust_sweep",
+            ProgramEvent::TotalsVerified(_) => "totals_verified",
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Transaction log walking
+//!
+//! Log messages are attributed to the program whose invocation is on top of
+//! the stack, tracked through `Program <id> invoke [n]` and
+//! `Program <id> success` / `failed` lines. Messages the distributor wrote
+//! are decoded at any depth. A distributor invoked through CPI logs under
+//! another program's top-level instruction, whose accounts do not identify
+//! the distribution, so its events also record which of that instruction's
+//! inner instructions invoked it. Every invocation below the top level is
+//! one inner instruction, logged in the order the transaction's inner
+//! instructions list them.
+
+use solana_pubkey::Pubkey;
+
+use crate::{anchor, message, DecodeError, LogFormat, ProgramEvent};
+
+const LOG_PREFIX: &str = "Program log: ";
+const DATA_PREFIX: &str = "Program data: ";
+
+/// How an event was written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// `msg!` line in the given format
+    Message(LogFormat),
+    /// Anchor `emit!`
+    Event,
+    /// Anchor `emit_cpi!`, decoded from inner instruction data
+    Cpi,
+}
+
+/// An event and where in the transaction it was emitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodedEvent {
+    /// Top-level instruction the event was emitted under
+    pub instruction_index: usize,
+    /// Position among that instruction's inner instructions of the CPI that
+    /// invoked the distributor, `None` when the top-level instruction is the
+    /// distributor's own. The first account of the instruction this points
+    /// to is the distribution.
+    pub inner_index: Option<usize>,
+    pub encoding: Encoding,
+    pub event: ProgramEvent,
+}
+
+/// Decode the distributor events in a transaction's log messages
+pub fn decode_logs<S: AsRef<str>>(
+    logs: &[S],
+    program_id: &Pubkey,
+) -> Result<Vec<DecodedEvent>, DecodeError> {
+    let program_id = program_id.to_string();
+    let mut events = Vec::new();
+    // Invoked programs, each with its inner instruction index (`None` at
+    // the top level)
+    let mut stack: Vec<(&str, Option<usize>)> = Vec::new();
+    let mut top_level: Option<usize> = None;
+    // Inner instructions invoked so far under the current top-level one
+    let mut inner_count = 0;
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some((program, depth)) = invoke(line) {
+            let inner_index = if depth == 1 {
+                top_level = Some(top_level.map_or(0, |i| i + 1));
+                inner_count = 0;
+                None
+            } else {
+                inner_count += 1;
+                Some(inner_count - 1)
+            };
+            stack.push((program, inner_index));
+            continue;
+        }
+        if is_exit(line) {
+            stack.pop();
+            continue;
+        }
+        let (Some(instruction_index), Some(&(program, inner_index))) = (top_level, stack.last())
+        else {
+            continue;
+        };
+        if program != program_id {
+            continue;
+        }
+
+        let decoded = if let Some(text) = line.strip_prefix(LOG_PREFIX) {
+            message::decode(text)?.map(|(format, event)| (Encoding::Message(format), event))
+        } else if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+            anchor::decode_base64(data)?.map(|event| (Encoding::Event, event))
+        } else {
+            None
+        };
+        if let Some((encoding, event)) = decoded {
+            events.push(DecodedEvent {
+                instruction_index,
+                inner_index,
+                encoding,
+                event,
+            });
+        }
+    }
+
+    Ok(events)
+}
+
+/// `Program <id> invoke [<depth>]`
+fn invoke(line: &str) -> Option<(&str, usize)> {
+    let rest = line.strip_prefix("Program ")?;
+    let (program, rest) = rest.split_once(" invoke [")?;
+    let depth = rest.strip_suffix(']')?.parse().ok()?;
+    Some((program, depth))
+}
+
+/// `Program <id> success` or `Program <id> failed: <error>`
+fn is_exit(line: &str) -> bool {
+    let Some(rest) = line.strip_prefix("Program ") else {
+        return false;
+    };
+    match rest.split_once(' ') {
+        Some((program, outcome)) => {
+            !program.ends_with(':') && (outcome == "success" || outcome.starts_with("failed"))
+        }
+        None => false,
+    }
+}
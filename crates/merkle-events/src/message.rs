@@ -0,0 +1,171 @@
+//! `msg!` lines of the distributor
+//!
+//! The text of a `msg!` line is not versioned on-chain, so every format the
+//! program has shipped is kept as a `LogFormat` and a message is tried
+//! against each, newest first. Backfills over transactions from an earlier
+//! program release keep decoding. Changing an event's `msg!` line in the
+//! program means adding a format here, not editing one.
+
+use crate::{
+    ClaimedEvent, ClaimedWithReferralEvent, ClawbackEvent, DecodeError, DustSweptEvent,
+    EscrowReleasedEvent, EscrowedEvent, OperatorChangedEvent, ProgramEvent, RenouncedEvent,
+};
+
+/// Prefixes of event messages across every format
+///
+/// A message with one of these prefixes that no format decodes is an error
+/// instead of being skipped, so a format change is noticed before claims go
+/// missing from the index.
+const EVENT_PREFIXES: &[&str] = &[
+    "Claimed: ",
+    "Claimed with referral: ",
+    "Escrowed: ",
+    "Renounced: ",
+    "Escrow released: ",
+    "Clawback: ",
+    "Distribution paused",
+    "Distribution unpaused",
+    "Operator set to: ",
+    "Dust swept: ",
+];
+
+/// A release's `msg!` event formats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum LogFormat {
+    /// The first release: `Clawback: <u64> tokens returned to authority`,
+    /// which drained the vault to the authority. Its other events are
+    /// written as in `V1`.
+    V0,
+    /// `Claimed: recipient=<pubkey>, amount=<u64>, index=<u64>` and siblings
+    V1,
+}
+
+impl LogFormat {
+    /// Every format, newest first
+    pub const ALL: &'static [LogFormat] = &[LogFormat::V1, LogFormat::V0];
+
+    /// Format the current program writes
+    pub const LATEST: LogFormat = LogFormat::V1;
+
+    /// Decode `message` as written in this format
+    pub fn decode(self, message: &str) -> Option<ProgramEvent> {
+        match self {
+            LogFormat::V0 => decode_v0(message),
+            LogFormat::V1 => decode_v1(message),
+        }
+    }
+}
+
+/// Decode one `Program log:` message (without the prefix)
+///
+/// `Ok(None)` for messages that are not events.
+pub fn decode(message: &str) -> Result<Option<(LogFormat, ProgramEvent)>, DecodeError> {
+    for &format in LogFormat::ALL {
+        if let Some(event) = format.decode(message) {
+            return Ok(Some((format, event)));
+        }
+    }
+    if EVENT_PREFIXES.iter().any(|p| message.starts_with(p)) {
+        return Err(DecodeError::UnrecognizedMessage(message.to_string()));
+    }
+    Ok(None)
+}
+
+fn decode_v0(message: &str) -> Option<ProgramEvent> {
+    // Only the clawback line differs from V1
+    let amount = message
+        .strip_prefix("Clawback: ")?
+        .strip_suffix(" tokens returned to authority")?;
+    Some(ProgramEvent::Clawback(ClawbackEvent {
+        amount: amount.parse().ok()?,
+        // The authority's token account was not logged
+        destination: None,
+        // The whole vault was returned
+        remaining: 0,
+    }))
+}
+
+fn decode_v1(message: &str) -> Option<ProgramEvent> {
+    if let Some(rest) = message.strip_prefix("Claimed: ") {
+        let [recipient, amount, index] = fields(rest, ["recipient", "amount", "index"])?;
+        return Some(ProgramEvent::Claimed(ClaimedEvent {
+            recipient: recipient.parse().ok()?,
+            amount: amount.parse().ok()?,
+            index: index.parse().ok()?,
+        }));
+    }
+    if let Some(rest) = message.strip_prefix("Claimed with referral: ") {
+        let [recipient, amount, index, referrer, bonus] =
+            fields(rest, ["recipient", "amount", "index", "referrer", "bonus"])?;
+        return Some(ProgramEvent::ClaimedWithReferral(
+            ClaimedWithReferralEvent {
+                recipient: recipient.parse().ok()?,
+                amount: amount.parse().ok()?,
+                index: index.parse().ok()?,
+                referrer: referrer.parse().ok()?,
+                bonus: bonus.parse().ok()?,
+            },
+        ));
+    }
+    if let Some(rest) = message.strip_prefix("Escrowed: ") {
+        let [recipient, amount, index] = fields(rest, ["recipient", "amount", "index"])?;
+        return Some(ProgramEvent::Escrowed(EscrowedEvent {
+            recipient: recipient.parse().ok()?,
+            amount: amount.parse().ok()?,
+            index: index.parse().ok()?,
+        }));
+    }
+    if let Some(rest) = message.strip_prefix("Renounced: ") {
+        let [recipient, amount, index] = fields(rest, ["recipient", "amount", "index"])?;
+        return Some(ProgramEvent::Renounced(RenouncedEvent {
+            recipient: recipient.parse().ok()?,
+            amount: amount.parse().ok()?,
+            index: index.parse().ok()?,
+        }));
+    }
+    if let Some(rest) = message.strip_prefix("Escrow released: ") {
+        let [recipient, amount] = fields(rest, ["recipient", "amount"])?;
+        return Some(ProgramEvent::EscrowReleased(EscrowReleasedEvent {
+            recipient: recipient.parse().ok()?,
+            amount: amount.parse().ok()?,
+        }));
+    }
+    if let Some(rest) = message.strip_prefix("Clawback: ") {
+        // "{amount} tokens returned to {destination}, {remaining} remaining"
+        let (amount, rest) = rest.split_once(" tokens returned to ")?;
+        let (destination, rest) = rest.split_once(", ")?;
+        let remaining = rest.strip_suffix(" remaining")?;
+        return Some(ProgramEvent::Clawback(ClawbackEvent {
+            amount: amount.parse().ok()?,
+            destination: Some(destination.parse().ok()?),
+            remaining: remaining.parse().ok()?,
+        }));
+    }
+    if let Some(operator) = message.strip_prefix("Operator set to: ") {
+        return Some(ProgramEvent::OperatorChanged(OperatorChangedEvent {
+            operator: operator.parse().ok()?,
+        }));
+    }
+    if let Some(rest) = message.strip_prefix("Dust swept: ") {
+        let amount = rest.strip_suffix(" tokens to collector")?;
+        return Some(ProgramEvent::DustSwept(DustSweptEvent {
+            amount: amount.parse().ok()?,
+        }));
+    }
+    match message {
+        "Distribution paused" => Some(ProgramEvent::Paused),
+        "Distribution unpaused" => Some(ProgramEvent::Unpaused),
+        _ => None,
+    }
+}
+
+/// Values of `key=value, key=value` pairs, which must be exactly `keys` in order
+fn fields<'a, const N: usize>(s: &'a str, keys: [&str; N]) -> Option<[&'a str; N]> {
+    let mut parts = s.split(", ");
+    let mut values = [""; N];
+    for (value, key) in values.iter_mut().zip(keys) {
+        *value = parts.next()?.strip_prefix(key)?.strip_prefix('=')?;
+    }
+    parts.next().is_none().then_some(values)
+}
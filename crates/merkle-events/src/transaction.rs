@@ -0,0 +1,86 @@
+//! Events from both encodings of a transaction
+//!
+//! A program on `emit_cpi!` writes its events into self-CPI instructions
+//! instead of (or, while migrating, as well as) its logs. For each
+//! distributor invocation, events found in its self-CPI instructions are
+//! authoritative and the invocation's logged events are dropped, so a
+//! release that writes both is not counted twice. Invocations without
+//! self-CPI events are decoded from their logs as before.
+
+use solana_pubkey::Pubkey;
+
+use crate::{anchor, decode_logs, DecodeError, DecodedEvent, Encoding};
+
+/// An instruction invoked through CPI, from the transaction's inner
+/// instructions
+#[derive(Clone, Copy, Debug)]
+pub struct InnerInstruction<'a> {
+    /// Top-level instruction it ran under
+    pub instruction_index: usize,
+    /// Position among that instruction's inner instructions
+    pub inner_index: usize,
+    pub program_id: Pubkey,
+    pub data: &'a [u8],
+    /// Invocation depth (2 = invoked by the top-level instruction), where
+    /// the source reports it
+    pub stack_height: Option<u32>,
+}
+
+/// Decode the distributor events in a transaction, from its log messages
+/// and its inner instructions
+///
+/// A self-CPI event belongs to the distributor invocation that made the
+/// self-CPI: the nearest earlier inner instruction one level up, or the
+/// top-level instruction at stack height 2. Without a stack height it is
+/// taken to be the top-level instruction. `inner_instructions` may be
+/// limited to the distributor's own. Events are ordered by top-level
+/// instruction, then by position within it.
+pub fn decode_transaction<S: AsRef<str>>(
+    logs: &[S],
+    inner_instructions: &[InnerInstruction],
+    program_id: &Pubkey,
+) -> Result<Vec<DecodedEvent>, DecodeError> {
+    let mut cpi_events = Vec::new();
+    for (position, ix) in inner_instructions.iter().enumerate() {
+        if ix.program_id != *program_id {
+            continue;
+        }
+        let Some(event) = anchor::decode_cpi(ix.data)? else {
+            continue;
+        };
+        let inner_index = match ix.stack_height {
+            Some(height) if height > 2 => {
+                let parent = inner_instructions[..position]
+                    .iter()
+                    .rev()
+                    .take_while(|p| p.instruction_index == ix.instruction_index)
+                    .find(|p| p.stack_height == Some(height - 1));
+                match parent {
+                    Some(parent) if parent.program_id == *program_id => Some(parent.inner_index),
+                    // No distributor invocation to attribute it to
+                    _ => continue,
+                }
+            }
+            _ => None,
+        };
+        cpi_events.push(DecodedEvent {
+            instruction_index: ix.instruction_index,
+            inner_index,
+            encoding: Encoding::Cpi,
+            event,
+        });
+    }
+
+    let mut events: Vec<DecodedEvent> = decode_logs(logs, program_id)?
+        .into_iter()
+        .filter(|e| {
+            !cpi_events.iter().any(|c| {
+                (c.instruction_index, c.inner_index) == (e.instruction_index, e.inner_index)
+            })
+        })
+        .collect();
+    events.extend(cpi_events);
+    // Stable: keeps each instruction's events in emission order
+    events.sort_by_key(|e| e.instruction_index);
+    Ok(events)
+}
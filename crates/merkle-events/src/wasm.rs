@@ -0,0 +1,172 @@
+//! JavaScript bindings (`wasm` feature)
+//!
+//! Built with `wasm-pack --target nodejs` for the claim event indexer
+//! (`src/indexers/claim-events.ts`). Events are returned as JSON: public
+//! keys are base58 and amounts decimal strings, since they do not fit a
+//! JavaScript number. Fields an event does not have are `null`.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{DecodedEvent, Encoding, InnerInstruction, ProgramEvent, Pubkey};
+
+/// Inner instruction as passed to `decodeTransaction`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsInnerInstruction {
+    instruction_index: usize,
+    inner_index: usize,
+    program_id: String,
+    /// base64
+    data: String,
+    stack_height: Option<u32>,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct JsEvent {
+    instruction_index: usize,
+    /// Inner instruction that invoked the distributor through CPI
+    inner_index: Option<usize>,
+    kind: &'static str,
+    /// `event`, `cpi` or `message:v1`
+    encoding: String,
+    recipient: Option<String>,
+    amount: Option<String>,
+    leaf_index: Option<u64>,
+    referrer: Option<String>,
+    bonus: Option<String>,
+    /// Clawback destination, or the new operator of `operator_change`
+    destination: Option<String>,
+    /// The full event, for kinds without a column above (`totals_verified`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    totals: Option<serde_json::Value>,
+}
+
+impl From<DecodedEvent> for JsEvent {
+    fn from(decoded: DecodedEvent) -> Self {
+        let mut js = JsEvent {
+            instruction_index: decoded.instruction_index,
+            inner_index: decoded.inner_index,
+            kind: decoded.event.kind(),
+            encoding: match decoded.encoding {
+                Encoding::Message(format) => format!("message:{format:?}").to_lowercase(),
+                Encoding::Event => "event".to_string(),
+                Encoding::Cpi => "cpi".to_string(),
+            },
+            ..Default::default()
+        };
+        let key = |k: Pubkey| Some(k.to_string());
+        let amount = |a: u64| Some(a.to_string());
+
+        match decoded.event {
+            ProgramEvent::Claimed(e) => {
+                js.recipient = key(e.recipient);
+                js.amount = amount(e.amount);
+                js.leaf_index = Some(e.index);
+            }
+            ProgramEvent::ClaimedWithReferral(e) => {
+                js.recipient = key(e.recipient);
+                js.amount = amount(e.amount);
+                js.leaf_index = Some(e.index);
+                js.referrer = key(e.referrer);
+                js.bonus = amount(e.bonus);
+            }
+            ProgramEvent::Escrowed(e) => {
+                js.recipient = key(e.recipient);
+                js.amount = amount(e.amount);
+                js.leaf_index = Some(e.index);
+            }
+            ProgramEvent::Renounced(e) => {
+                js.recipient = key(e.recipient);
+                js.amount = amount(e.amount);
+                js.leaf_index = Some(e.index);
+            }
+            ProgramEvent::EscrowReleased(e) => {
+                js.recipient = key(e.recipient);
+                js.amount = amount(e.amount);
+            }
+            ProgramEvent::Clawback(e) => {
+                js.amount = amount(e.amount);
+                js.destination = e.destination.map(|d| d.to_string());
+            }
+            ProgramEvent::OperatorChanged(e) => js.destination = key(e.operator),
+            ProgramEvent::DustSwept(e) => js.amount = amount(e.amount),
+            ProgramEvent::TotalsVerified(e) => {
+                js.totals = Some(serde_json::json!({
+                    "distribution": e.distribution.to_string(),
+                    "totalAmount": e.total_amount.to_string(),
+                    "claimedAmount": e.claimed_amount.to_string(),
+                    "clawedBackAmount": e.clawed_back_amount.to_string(),
+                    "escrowedAmount": e.escrowed_amount.to_string(),
+                    "vaultAmount": e.vault_amount.to_string(),
+                    "numRecipients": e.num_recipients,
+                    "numClaimed": e.num_claimed,
+                    "ok": e.ok,
+                }));
+            }
+            ProgramEvent::Paused | ProgramEvent::Unpaused => {}
+        }
+        js
+    }
+}
+
+fn pubkey(name: &str, value: &str) -> Result<Pubkey, JsError> {
+    value
+        .parse()
+        .map_err(|_| JsError::new(&format!("invalid {name} {value}")))
+}
+
+fn to_json(events: Vec<DecodedEvent>) -> Result<String, JsError> {
+    let events: Vec<JsEvent> = events.into_iter().map(JsEvent::from).collect();
+    Ok(serde_json::to_string(&events)?)
+}
+
+/// Decode the distributor events in a transaction's log messages, as a
+/// JSON array
+#[wasm_bindgen(js_name = decodeLogs)]
+pub fn decode_logs(logs: Vec<String>, program_id: &str) -> Result<String, JsError> {
+    let program_id = pubkey("program id", program_id)?;
+    to_json(crate::decode_logs(logs.as_slice(), &program_id)?)
+}
+
+/// Decode the distributor events in a transaction's log messages and inner
+/// instructions, as a JSON array
+///
+/// `inner_instructions` is a JSON array of
+/// `{ instructionIndex, innerIndex, programId, data (base64), stackHeight | null }`.
+#[wasm_bindgen(js_name = decodeTransaction)]
+pub fn decode_transaction(
+    logs: Vec<String>,
+    inner_instructions: &str,
+    program_id: &str,
+) -> Result<String, JsError> {
+    let program_id = pubkey("program id", program_id)?;
+    let parsed: Vec<JsInnerInstruction> = serde_json::from_str(inner_instructions)?;
+
+    let mut data = Vec::with_capacity(parsed.len());
+    let mut programs = Vec::with_capacity(parsed.len());
+    for ix in &parsed {
+        data.push(base64::engine::general_purpose::STANDARD.decode(&ix.data)?);
+        programs.push(pubkey("inner instruction program id", &ix.program_id)?);
+    }
+    let inner: Vec<InnerInstruction> = parsed
+        .iter()
+        .zip(&data)
+        .zip(programs)
+        .map(|((ix, data), program_id)| InnerInstruction {
+            instruction_index: ix.instruction_index,
+            inner_index: ix.inner_index,
+            program_id,
+            data,
+            stack_height: ix.stack_height,
+        })
+        .collect();
+
+    to_json(crate::decode_transaction(
+        logs.as_slice(),
+        &inner,
+        &program_id,
+    )?)
+}
@@ -0,0 +1,380 @@
+//! Decoding of program output: Anchor events against the program's own
+//! encoding, `msg!` lines against a transaction's log messages, self-CPI
+//! events alongside logged ones
+
+use anchor_lang::prelude::Pubkey as ChainPubkey;
+use anchor_lang::{Discriminator, Event};
+use merkle_distributor::TotalsVerified;
+use merkle_events::{
+    anchor, decode_logs, decode_transaction, ClaimedEvent, ClawbackEvent, DecodeError,
+    DecodedEvent, Encoding, InnerInstruction, LogFormat, ProgramEvent, Pubkey, TotalsVerifiedEvent,
+};
+
+fn key(seed: u8) -> Pubkey {
+    Pubkey::new_from_array([seed; 32])
+}
+
+#[test]
+fn totals_verified_discriminator_matches_program() {
+    assert_eq!(
+        anchor::discriminator("TotalsVerified").as_slice(),
+        TotalsVerified::DISCRIMINATOR
+    );
+}
+
+#[test]
+fn event_ix_tag_matches_anchor() {
+    assert_eq!(
+        anchor::EVENT_IX_TAG.as_slice(),
+        anchor_lang::event::EVENT_IX_TAG_LE
+    );
+}
+
+fn totals_verified(ok: bool) -> (TotalsVerified, ProgramEvent) {
+    let chain = TotalsVerified {
+        distribution: ChainPubkey::new_from_array([9; 32]),
+        total_amount: 1_000_000,
+        claimed_amount: 600_000,
+        clawed_back_amount: 100_000,
+        escrowed_amount: 50_000,
+        vault_amount: 300_000,
+        num_recipients: u64::MAX,
+        num_claimed: 42,
+        ok,
+    };
+    let decoded = ProgramEvent::TotalsVerified(TotalsVerifiedEvent {
+        distribution: key(9),
+        total_amount: 1_000_000,
+        claimed_amount: 600_000,
+        clawed_back_amount: 100_000,
+        escrowed_amount: 50_000,
+        vault_amount: 300_000,
+        num_recipients: u64::MAX,
+        num_claimed: 42,
+        ok,
+    });
+    (chain, decoded)
+}
+
+#[test]
+fn totals_verified_decodes_program_encoding() {
+    for ok in [true, false] {
+        let (event, expected) = totals_verified(ok);
+        assert_eq!(
+            anchor::decode(&event.data()).unwrap(),
+            Some(expected.clone())
+        );
+
+        let cpi_data = [anchor::EVENT_IX_TAG.as_slice(), event.data().as_slice()].concat();
+        assert_eq!(anchor::decode_cpi(&cpi_data).unwrap(), Some(expected));
+    }
+}
+
+#[test]
+fn decode_logs_attributes_events_to_top_level_distributor_instructions() {
+    let program = key(1);
+    let other = key(2);
+    let logs = [
+        format!("Program {other} invoke [1]"),
+        format!(
+            "Program log: Claimed: recipient={}, amount=5, index=0",
+            key(3)
+        ),
+        format!("Program {other} success"),
+        format!("Program {program} invoke [1]"),
+        "Program log: Instruction: Claim".to_string(),
+        format!("Program {other} invoke [2]"),
+        "Program log: Instruction: TransferChecked".to_string(),
+        format!("Program {other} success"),
+        format!(
+            "Program log: Claimed: recipient={}, amount=1500, index=7",
+            key(4)
+        ),
+        format!("Program {program} consumed 24000 of 200000 compute units"),
+        format!("Program {program} success"),
+        format!("Program {program} invoke [1]"),
+        format!(
+            "Program log: Clawback: 10 tokens returned to {}, 0 remaining",
+            key(5)
+        ),
+        format!("Program {program} success"),
+    ];
+
+    assert_eq!(
+        decode_logs(&logs, &program).unwrap(),
+        [
+            DecodedEvent {
+                instruction_index: 1,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Claimed(ClaimedEvent {
+                    recipient: key(4),
+                    amount: 1500,
+                    index: 7,
+                }),
+            },
+            DecodedEvent {
+                instruction_index: 2,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Clawback(ClawbackEvent {
+                    amount: 10,
+                    destination: Some(key(5)),
+                    remaining: 0,
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn decode_logs_attributes_cpi_events_to_the_invoking_inner_instruction() {
+    let program = key(1);
+    let router = key(2);
+    let token = key(6);
+    let logs = [
+        format!("Program {router} invoke [1]"),
+        format!("Program {token} invoke [2]"),
+        format!("Program {token} success"),
+        format!("Program {program} invoke [2]"),
+        "Program log: Instruction: Claim".to_string(),
+        format!("Program {token} invoke [3]"),
+        format!("Program {token} success"),
+        format!(
+            "Program log: Claimed: recipient={}, amount=1500, index=7",
+            key(4)
+        ),
+        format!("Program {program} success"),
+        format!("Program {router} success"),
+        format!("Program {program} invoke [1]"),
+        "Program log: Distribution paused".to_string(),
+        format!("Program {program} success"),
+    ];
+
+    // The router's instruction does not name the distribution; the claim
+    // is attributed to its second inner instruction, the distributor CPI
+    assert_eq!(
+        decode_logs(&logs, &program).unwrap(),
+        [
+            DecodedEvent {
+                instruction_index: 0,
+                inner_index: Some(1),
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Claimed(ClaimedEvent {
+                    recipient: key(4),
+                    amount: 1500,
+                    index: 7,
+                }),
+            },
+            DecodedEvent {
+                instruction_index: 1,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Paused,
+            },
+        ]
+    );
+}
+
+#[test]
+fn claim_batch_yields_one_event_per_claim() {
+    let program = key(1);
+    let logs = [
+        format!("Program {program} invoke [1]"),
+        "Program log: Instruction: ClaimBatch".to_string(),
+        format!(
+            "Program log: Claimed: recipient={}, amount=100, index=3",
+            key(3)
+        ),
+        format!(
+            "Program log: Claimed: recipient={}, amount=200, index=4",
+            key(3)
+        ),
+        format!("Program {program} success"),
+    ];
+
+    // Both claims come from instruction 0; the indexer keys them apart by
+    // their position in the transaction
+    assert_eq!(
+        decode_logs(&logs, &program).unwrap(),
+        [
+            DecodedEvent {
+                instruction_index: 0,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Claimed(ClaimedEvent {
+                    recipient: key(3),
+                    amount: 100,
+                    index: 3,
+                }),
+            },
+            DecodedEvent {
+                instruction_index: 0,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Claimed(ClaimedEvent {
+                    recipient: key(3),
+                    amount: 200,
+                    index: 4,
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn baseline_clawback_message_decodes() {
+    let program = key(1);
+    // As written by the first program release (`msg!("Clawback: {} tokens
+    // returned to authority", remaining)`)
+    let logs = [
+        format!("Program {program} invoke [1]"),
+        "Program log: Instruction: Clawback".to_string(),
+        "Program log: Clawback: 250000 tokens returned to authority".to_string(),
+        format!("Program {program} success"),
+    ];
+
+    assert_eq!(
+        decode_logs(&logs, &program).unwrap(),
+        [DecodedEvent {
+            instruction_index: 0,
+            inner_index: None,
+            encoding: Encoding::Message(LogFormat::V0),
+            event: ProgramEvent::Clawback(ClawbackEvent {
+                amount: 250_000,
+                destination: None,
+                remaining: 0,
+            }),
+        }]
+    );
+}
+
+#[test]
+fn unrecognized_event_message_is_an_error() {
+    let program = key(1);
+    let logs = [
+        format!("Program {program} invoke [1]"),
+        format!("Program log: Claimed: recipient={}, amount=5", key(3)),
+        format!("Program {program} success"),
+    ];
+
+    assert!(matches!(
+        decode_logs(&logs, &program),
+        Err(DecodeError::UnrecognizedMessage(_))
+    ));
+}
+
+#[test]
+fn cpi_events_replace_logged_events_of_their_instruction() {
+    use base64::Engine;
+
+    let program = key(1);
+    let (event, expected) = totals_verified(true);
+    let data = event.data();
+    let logged = base64::engine::general_purpose::STANDARD.encode(&data);
+    let cpi_data = [anchor::EVENT_IX_TAG.as_slice(), data.as_slice()].concat();
+
+    let logs = [
+        format!("Program {program} invoke [1]"),
+        format!("Program data: {logged}"),
+        format!("Program {program} invoke [2]"),
+        format!("Program {program} success"),
+        format!("Program {program} success"),
+        format!("Program {program} invoke [1]"),
+        format!(
+            "Program log: Claimed: recipient={}, amount=5, index=3",
+            key(3)
+        ),
+        format!("Program {program} success"),
+    ];
+    let inner = [
+        InnerInstruction {
+            instruction_index: 0,
+            inner_index: 0,
+            program_id: program,
+            data: &cpi_data,
+            stack_height: Some(2),
+        },
+        // No distributor invocation one level up to belong to
+        InnerInstruction {
+            instruction_index: 1,
+            inner_index: 0,
+            program_id: program,
+            data: &cpi_data,
+            stack_height: Some(3),
+        },
+    ];
+
+    assert_eq!(
+        decode_transaction(&logs, &inner, &program).unwrap(),
+        [
+            DecodedEvent {
+                instruction_index: 0,
+                inner_index: None,
+                encoding: Encoding::Cpi,
+                event: expected,
+            },
+            DecodedEvent {
+                instruction_index: 1,
+                inner_index: None,
+                encoding: Encoding::Message(LogFormat::V1),
+                event: ProgramEvent::Claimed(ClaimedEvent {
+                    recipient: key(3),
+                    amount: 5,
+                    index: 3,
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn cpi_events_of_a_distributor_invoked_through_cpi_belong_to_its_invocation() {
+    use base64::Engine;
+
+    let program = key(1);
+    let router = key(2);
+    let (event, expected) = totals_verified(false);
+    let data = event.data();
+    let logged = base64::engine::general_purpose::STANDARD.encode(&data);
+    let cpi_data = [anchor::EVENT_IX_TAG.as_slice(), data.as_slice()].concat();
+
+    let logs = [
+        format!("Program {router} invoke [1]"),
+        format!("Program {program} invoke [2]"),
+        format!("Program data: {logged}"),
+        format!("Program {program} invoke [3]"),
+        format!("Program {program} success"),
+        format!("Program {program} success"),
+        format!("Program {router} success"),
+    ];
+    // Limited to the distributor's own, as the indexer passes them
+    let inner = [
+        InnerInstruction {
+            instruction_index: 0,
+            inner_index: 0,
+            program_id: program,
+            data: &[],
+            stack_height: Some(2),
+        },
+        InnerInstruction {
+            instruction_index: 0,
+            inner_index: 1,
+            program_id: program,
+            data: &cpi_data,
+            stack_height: Some(3),
+        },
+    ];
+
+    // The logged copy is dropped; the event belongs to the router's first
+    // inner instruction, the distributor invocation
+    assert_eq!(
+        decode_transaction(&logs, &inner, &program).unwrap(),
+        [DecodedEvent {
+            instruction_index: 0,
+            inner_index: Some(0),
+            encoding: Encoding::Cpi,
+            event: expected,
+        }]
+    );
+}
@@ -0,0 +1,101 @@
+//! Reward allocations: the payouts of `reward_payouts_preview`
+//!
+//! Payouts are selected exactly as `export-reward-csv` selects them
+//! (positive amounts, ignored wallets excluded, largest first), so anything
+//! built from them matches a build from that CSV.
+
+use futures_util::{Stream, StreamExt};
+use sqlx::PgExecutor;
+
+use crate::column::{units, unsigned, Columns};
+use crate::error::StoreError;
+
+const TOTALS_SQL: &str = "
+    SELECT
+      COUNT(*) AS num_recipients,
+      COALESCE(SUM(payout_amount), 0)::TEXT AS total_amount,
+      COUNT(DISTINCT mint) AS num_mints,
+      MIN(mint) AS mint,
+      MIN(window_id) AS window_id
+    FROM reward_payouts_preview
+    WHERE reward_id = $1
+      AND payout_amount > 0
+      AND NOT (wallet = ANY($2))
+";
+
+const PAYOUTS_SQL: &str = "
+    SELECT wallet, payout_amount::TEXT AS amount
+    FROM reward_payouts_preview
+    WHERE reward_id = $1
+      AND payout_amount > 0
+      AND NOT (wallet = ANY($2))
+    ORDER BY payout_amount DESC, wallet
+";
+
+/// Aggregate of a reward's payouts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardTotals {
+    pub num_recipients: u64,
+    pub total_amount: u64,
+    /// Distinct mints paid out; a distribution needs exactly one
+    pub num_mints: u64,
+    /// Smallest mint and window, `None` without payouts
+    pub mint: Option<String>,
+    pub window_id: Option<String>,
+}
+
+impl RewardTotals {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            num_recipients: unsigned(row, "num_recipients")?,
+            total_amount: units(row, "total_amount")?,
+            num_mints: unsigned(row, "num_mints")?,
+            mint: row.optional_string("mint")?,
+            window_id: row.optional_string("window_id")?,
+        })
+    }
+}
+
+/// One wallet's payout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payout {
+    pub wallet: String,
+    pub amount: u64,
+}
+
+impl Payout {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            wallet: row.string("wallet")?,
+            amount: units(row, "amount")?,
+        })
+    }
+}
+
+/// Count, total, mints and window of a reward's payouts, excluding
+/// `ignored_wallets`
+pub async fn reward_totals<'e>(
+    executor: impl PgExecutor<'e>,
+    reward_id: &str,
+    ignored_wallets: &[String],
+) -> Result<RewardTotals, StoreError> {
+    let row = sqlx::query(TOTALS_SQL)
+        .bind(reward_id)
+        .bind(ignored_wallets)
+        .fetch_one(executor)
+        .await?;
+    RewardTotals::from_row(&row)
+}
+
+/// Stream a reward's payouts, largest first, through a cursor
+pub fn reward_payouts<'e>(
+    executor: impl PgExecutor<'e> + 'e,
+    reward_id: &'e str,
+    ignored_wallets: &'e [String],
+) -> impl Stream<Item = Result<Payout, StoreError>> + 'e {
+    sqlx::query(PAYOUTS_SQL)
+        .bind(reward_id)
+        .bind(ignored_wallets)
+        .fetch(executor)
+        .map(|row| Payout::from_row(&row?))
+}
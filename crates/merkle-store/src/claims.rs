@@ -0,0 +1,256 @@
+//! `merkle_claims`: relayer claim tracking
+//!
+//! One row per leaf, seeded from the artifact and updated on every
+//! submission attempt, as the TypeScript relayer does.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sqlx::PgExecutor;
+
+use crate::column::{integer, parsed, units, unsigned, Columns};
+use crate::error::StoreError;
+
+const INSERT_SQL: &str = "
+    INSERT INTO merkle_claims (distribution_id, leaf_index, wallet, amount, status)
+    SELECT $1, leaf_index, wallet, amount::NUMERIC, 'pending'
+    FROM UNNEST($2::INTEGER[], $3::TEXT[], $4::TEXT[]) AS c(leaf_index, wallet, amount)
+    ON CONFLICT (distribution_id, leaf_index) DO NOTHING
+";
+
+const SELECT_SQL: &str = "
+    SELECT
+      distribution_id,
+      leaf_index::BIGINT AS leaf_index,
+      wallet,
+      amount::TEXT AS amount,
+      status,
+      tx_signature,
+      attempts::BIGINT AS attempts,
+      EXTRACT(EPOCH FROM last_attempt)::BIGINT AS last_attempt,
+      EXTRACT(EPOCH FROM confirmed_at)::BIGINT AS confirmed_at,
+      error_message
+    FROM merkle_claims
+";
+
+const RECORD_ATTEMPT_SQL: &str = "
+    UPDATE merkle_claims
+    SET
+      status = $3,
+      tx_signature = COALESCE($4, tx_signature),
+      attempts = attempts + 1,
+      last_attempt = NOW(),
+      confirmed_at = CASE WHEN $3 = 'confirmed' THEN NOW() ELSE confirmed_at END,
+      error_message = $5
+    WHERE distribution_id = $1 AND leaf_index = $2
+";
+
+const UNCONFIRMED_SQL: &str = "
+    SELECT COUNT(*) AS unconfirmed
+    FROM merkle_claims
+    WHERE distribution_id = $1
+      AND status <> 'confirmed'
+";
+
+/// Relayer state of a claim (`merkle_claims.status`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClaimStatus {
+    Pending,
+    Submitted,
+    Confirmed,
+    Failed,
+}
+
+impl ClaimStatus {
+    pub const ALL: [Self; 4] = [
+        Self::Pending,
+        Self::Submitted,
+        Self::Confirmed,
+        Self::Failed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Submitted => "submitted",
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for ClaimStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown claim status `{s}`"))
+    }
+}
+
+impl fmt::Display for ClaimStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A leaf to track, from the distribution's artifact
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewClaim {
+    pub leaf_index: u64,
+    pub wallet: String,
+    pub amount: u64,
+}
+
+/// A `merkle_claims` row
+///
+/// Timestamps are unix seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimRecord {
+    pub distribution_id: String,
+    pub leaf_index: u64,
+    pub wallet: String,
+    pub amount: u64,
+    pub status: ClaimStatus,
+    pub tx_signature: Option<String>,
+    pub attempts: u64,
+    pub last_attempt: Option<i64>,
+    pub confirmed_at: Option<i64>,
+    pub error_message: Option<String>,
+}
+
+impl ClaimRecord {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            distribution_id: row.string("distribution_id")?,
+            leaf_index: unsigned(row, "leaf_index")?,
+            wallet: row.string("wallet")?,
+            amount: units(row, "amount")?,
+            status: parsed(row, "status")?,
+            tx_signature: row.optional_string("tx_signature")?,
+            attempts: unsigned(row, "attempts")?,
+            last_attempt: row.optional_int("last_attempt")?,
+            confirmed_at: row.optional_int("confirmed_at")?,
+            error_message: row.optional_string("error_message")?,
+        })
+    }
+}
+
+/// Outcome of one submission attempt
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimAttempt {
+    pub status: ClaimStatus,
+    /// Kept from an earlier attempt when `None`
+    pub tx_signature: Option<String>,
+    pub error_message: Option<String>,
+}
+
+/// Track `claims` as `pending`, skipping leaves already tracked
+///
+/// Returns the number of claims inserted.
+pub async fn insert_claims<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+    claims: &[NewClaim],
+) -> Result<u64, StoreError> {
+    let leaf_indices = claims
+        .iter()
+        .map(|claim| integer("leaf_index", claim.leaf_index))
+        .collect::<Result<Vec<_>, _>>()?;
+    let wallets: Vec<&str> = claims.iter().map(|claim| claim.wallet.as_str()).collect();
+    let amounts: Vec<String> = claims
+        .iter()
+        .map(|claim| claim.amount.to_string())
+        .collect();
+
+    let result = sqlx::query(INSERT_SQL)
+        .bind(distribution_id)
+        .bind(&leaf_indices)
+        .bind(&wallets)
+        .bind(&amounts)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Claims still to submit (`pending`, or `failed` with fewer than
+/// `max_attempts` attempts), by leaf index
+pub async fn pending_claims<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+    max_attempts: u32,
+) -> Result<Vec<ClaimRecord>, StoreError> {
+    let sql = format!(
+        "{SELECT_SQL} WHERE distribution_id = $1 AND status IN ('pending', 'failed') AND attempts < $2 ORDER BY leaf_index"
+    );
+    sqlx::query(&sql)
+        .bind(distribution_id)
+        .bind(i64::from(max_attempts))
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(ClaimRecord::from_row)
+        .collect()
+}
+
+/// A wallet's tracked claims across distributions
+pub async fn wallet_claims<'e>(
+    executor: impl PgExecutor<'e>,
+    wallet: &str,
+) -> Result<Vec<ClaimRecord>, StoreError> {
+    let sql = format!("{SELECT_SQL} WHERE wallet = $1 ORDER BY distribution_id, leaf_index");
+    sqlx::query(&sql)
+        .bind(wallet)
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(ClaimRecord::from_row)
+        .collect()
+}
+
+/// Record a submission attempt of one claim
+///
+/// Returns whether the claim is tracked.
+pub async fn record_attempt<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+    leaf_index: u64,
+    attempt: &ClaimAttempt,
+) -> Result<bool, StoreError> {
+    let result = sqlx::query(RECORD_ATTEMPT_SQL)
+        .bind(distribution_id)
+        .bind(integer("leaf_index", leaf_index)?)
+        .bind(attempt.status.as_str())
+        .bind(&attempt.tx_signature)
+        .bind(&attempt.error_message)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Number of a distribution's claims not yet confirmed
+pub async fn unconfirmed_claims<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+) -> Result<u64, StoreError> {
+    let row = sqlx::query(UNCONFIRMED_SQL)
+        .bind(distribution_id)
+        .fetch_one(executor)
+        .await?;
+    unsigned(&row, "unconfirmed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_round_trips() {
+        for status in ClaimStatus::ALL {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+        assert!("retrying".parse::<ClaimStatus>().is_err());
+    }
+}
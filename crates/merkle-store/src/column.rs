@@ -0,0 +1,157 @@
+//! Column decoding shared by the repositories and both backends
+//!
+//! Amounts are selected as text (`NUMERIC::TEXT` on Postgres), other
+//! integers as 64-bit and timestamps as unix seconds, so one set of row
+//! decoders serves Postgres and SQLite without sqlx's numeric and time
+//! features.
+
+use std::str::FromStr;
+
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+
+use crate::error::StoreError;
+
+/// The column types every row decoder reads
+pub(crate) trait Columns {
+    fn string(&self, column: &'static str) -> Result<String, StoreError>;
+    fn optional_string(&self, column: &'static str) -> Result<Option<String>, StoreError>;
+    fn int(&self, column: &'static str) -> Result<i64, StoreError>;
+    fn optional_int(&self, column: &'static str) -> Result<Option<i64>, StoreError>;
+    fn boolean(&self, column: &'static str) -> Result<bool, StoreError>;
+}
+
+macro_rules! impl_columns {
+    ($row:ty) => {
+        impl Columns for $row {
+            fn string(&self, column: &'static str) -> Result<String, StoreError> {
+                Ok(self.try_get(column)?)
+            }
+
+            fn optional_string(&self, column: &'static str) -> Result<Option<String>, StoreError> {
+                Ok(self.try_get(column)?)
+            }
+
+            fn int(&self, column: &'static str) -> Result<i64, StoreError> {
+                Ok(self.try_get(column)?)
+            }
+
+            fn optional_int(&self, column: &'static str) -> Result<Option<i64>, StoreError> {
+                Ok(self.try_get(column)?)
+            }
+
+            fn boolean(&self, column: &'static str) -> Result<bool, StoreError> {
+                Ok(self.try_get(column)?)
+            }
+        }
+    };
+}
+
+impl_columns!(PgRow);
+#[cfg(feature = "sqlite")]
+impl_columns!(sqlx::sqlite::SqliteRow);
+
+/// Whole base units of an amount column
+pub(crate) fn units(row: &impl Columns, column: &'static str) -> Result<u64, StoreError> {
+    let value = row.string(column)?;
+    parse_units(&value).map_err(|reason| invalid(column, value, reason))
+}
+
+/// `units` of a nullable column
+pub(crate) fn optional_units(
+    row: &impl Columns,
+    column: &'static str,
+) -> Result<Option<u64>, StoreError> {
+    row.optional_string(column)?
+        .map(|value| parse_units(&value).map_err(|reason| invalid(column, value, reason)))
+        .transpose()
+}
+
+/// Non-negative integer column
+pub(crate) fn unsigned(row: &impl Columns, column: &'static str) -> Result<u64, StoreError> {
+    non_negative(column, row.int(column)?)
+}
+
+/// `unsigned` of a nullable column
+pub(crate) fn optional_unsigned(
+    row: &impl Columns,
+    column: &'static str,
+) -> Result<Option<u64>, StoreError> {
+    row.optional_int(column)?
+        .map(|value| non_negative(column, value))
+        .transpose()
+}
+
+/// Text column holding one of an enum's values
+pub(crate) fn parsed<T: FromStr>(
+    row: &impl Columns,
+    column: &'static str,
+) -> Result<T, StoreError> {
+    let value = row.string(column)?;
+    value
+        .parse()
+        .map_err(|_| invalid(column, value, "unknown value".to_string()))
+}
+
+fn non_negative(column: &'static str, value: i64) -> Result<u64, StoreError> {
+    u64::try_from(value).map_err(|_| invalid(column, value.to_string(), "negative".to_string()))
+}
+
+/// `value` as an `INTEGER` parameter
+pub(crate) fn integer(column: &'static str, value: u64) -> Result<i32, StoreError> {
+    i32::try_from(value)
+        .map_err(|_| invalid(column, value.to_string(), "exceeds INTEGER".to_string()))
+}
+
+/// `value` as a 64-bit `INTEGER` parameter (SQLite amounts and indices)
+#[cfg(feature = "sqlite")]
+pub(crate) fn bigint(column: &'static str, value: u64) -> Result<i64, StoreError> {
+    i64::try_from(value).map_err(|_| {
+        invalid(
+            column,
+            value.to_string(),
+            "exceeds a 64-bit INTEGER".to_string(),
+        )
+    })
+}
+
+fn invalid(column: &'static str, value: String, reason: String) -> StoreError {
+    StoreError::InvalidColumn {
+        column,
+        value,
+        reason,
+    }
+}
+
+/// Parse whole base units, allowing a zero fraction (`NUMERIC` scale)
+fn parse_units(value: &str) -> Result<u64, String> {
+    let whole = match value.split_once('.') {
+        Some((whole, fraction)) if fraction.chars().all(|c| c == '0') => whole,
+        Some(_) => return Err("not a whole number of base units".to_string()),
+        None => value,
+    };
+    if whole.is_empty() || !whole.chars().all(|c| c.is_ascii_digit()) {
+        return Err("not a non-negative number".to_string());
+    }
+    whole.parse().map_err(|_| "overflows u64".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_whole_units() {
+        assert_eq!(parse_units("1500000000"), Ok(1_500_000_000));
+        assert_eq!(parse_units("42.000"), Ok(42));
+        assert_eq!(parse_units("18446744073709551615"), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn rejects_fractions_signs_and_overflow() {
+        assert!(parse_units("1.5").is_err());
+        assert!(parse_units("-1").is_err());
+        assert!(parse_units("").is_err());
+        assert!(parse_units("18446744073709551616").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+//! `merkle_distributions`: one row per built distribution
+//!
+//! Rows are inserted when a tree is built (`pending`), given their
+//! deployment, PDA and vault when initialized on-chain (`funded`), and
+//! moved through `active` → `completed` / `clawedback` afterwards. A PDA is
+//! only unique within its deployment.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sqlx::PgExecutor;
+
+use crate::column::{integer, parsed, units, unsigned, Columns};
+use crate::error::StoreError;
+
+const SELECT_SQL: &str = "
+    SELECT
+      distribution_id,
+      reward_id,
+      window_id,
+      mint,
+      total_amount::TEXT AS total_amount,
+      merkle_root,
+      num_recipients::BIGINT AS num_recipients,
+      csv_hash,
+      artifact_path,
+      deployment,
+      on_chain_address,
+      vault_ata,
+      status,
+      claimed_amount::TEXT AS claimed_amount,
+      claimed_count::BIGINT AS claimed_count,
+      EXTRACT(EPOCH FROM created_at)::BIGINT AS created_at,
+      EXTRACT(EPOCH FROM funded_at)::BIGINT AS funded_at,
+      EXTRACT(EPOCH FROM activated_at)::BIGINT AS activated_at,
+      EXTRACT(EPOCH FROM completed_at)::BIGINT AS completed_at
+    FROM merkle_distributions
+";
+
+// Rebuilding a distribution only moves its artifact, as in build-merkle-distribution
+const UPSERT_SQL: &str = "
+    INSERT INTO merkle_distributions (
+      distribution_id,
+      reward_id,
+      window_id,
+      mint,
+      total_amount,
+      merkle_root,
+      num_recipients,
+      csv_hash,
+      artifact_path,
+      status
+    ) VALUES ($1, $2, $3, $4, $5::NUMERIC, $6, $7, $8, $9, 'pending')
+    ON CONFLICT (distribution_id) DO UPDATE SET
+      artifact_path = EXCLUDED.artifact_path,
+      updated_at = NOW()
+";
+
+const RECORD_ON_CHAIN_SQL: &str = "
+    UPDATE merkle_distributions
+    SET
+      deployment = $4,
+      on_chain_address = $2,
+      vault_ata = $3,
+      status = 'funded',
+      funded_at = NOW()
+    WHERE distribution_id = $1
+";
+
+const SET_STATUS_SQL: &str = "
+    UPDATE merkle_distributions
+    SET
+      status = $2,
+      activated_at = CASE WHEN $2 = 'active' THEN NOW() ELSE activated_at END,
+      completed_at = CASE WHEN $2 IN ('completed', 'clawedback') THEN NOW() ELSE completed_at END
+    WHERE distribution_id = $1
+";
+
+/// Lifecycle state of a distribution (`merkle_distributions.status`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DistributionStatus {
+    Pending,
+    Funded,
+    Active,
+    Completed,
+    ClawedBack,
+}
+
+impl DistributionStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Pending,
+        Self::Funded,
+        Self::Active,
+        Self::Completed,
+        Self::ClawedBack,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Funded => "funded",
+            Self::Active => "active",
+            Self::Completed => "completed",
+            Self::ClawedBack => "clawedback",
+        }
+    }
+}
+
+impl FromStr for DistributionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown distribution status `{s}`"))
+    }
+}
+
+impl fmt::Display for DistributionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A distribution as built, before it exists on-chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewDistribution {
+    /// Hex-encoded distribution id
+    pub distribution_id: String,
+    pub reward_id: String,
+    pub window_id: String,
+    pub mint: String,
+    pub total_amount: u64,
+    /// Hex-encoded root
+    pub merkle_root: String,
+    pub num_recipients: u64,
+    pub csv_hash: String,
+    pub artifact_path: String,
+}
+
+/// A `merkle_distributions` row
+///
+/// Timestamps are unix seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistributionRecord {
+    pub distribution_id: String,
+    pub reward_id: String,
+    pub window_id: String,
+    pub mint: String,
+    pub total_amount: u64,
+    pub merkle_root: String,
+    pub num_recipients: u64,
+    pub csv_hash: String,
+    pub artifact_path: String,
+    /// Deployment (program and cluster) the distribution is initialized on,
+    /// `default` until then
+    pub deployment: String,
+    /// Distribution PDA, once initialized
+    pub on_chain_address: Option<String>,
+    pub vault_ata: Option<String>,
+    pub status: DistributionStatus,
+    pub claimed_amount: u64,
+    pub claimed_count: u64,
+    pub created_at: i64,
+    pub funded_at: Option<i64>,
+    pub activated_at: Option<i64>,
+    pub completed_at: Option<i64>,
+}
+
+impl DistributionRecord {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            distribution_id: row.string("distribution_id")?,
+            reward_id: row.string("reward_id")?,
+            window_id: row.string("window_id")?,
+            mint: row.string("mint")?,
+            total_amount: units(row, "total_amount")?,
+            merkle_root: row.string("merkle_root")?,
+            num_recipients: unsigned(row, "num_recipients")?,
+            csv_hash: row.string("csv_hash")?,
+            artifact_path: row.string("artifact_path")?,
+            deployment: row.string("deployment")?,
+            on_chain_address: row.optional_string("on_chain_address")?,
+            vault_ata: row.optional_string("vault_ata")?,
+            status: parsed(row, "status")?,
+            claimed_amount: units(row, "claimed_amount")?,
+            claimed_count: unsigned(row, "claimed_count")?,
+            created_at: row.int("created_at")?,
+            funded_at: row.optional_int("funded_at")?,
+            activated_at: row.optional_int("activated_at")?,
+            completed_at: row.optional_int("completed_at")?,
+        })
+    }
+}
+
+/// Insert a built distribution as `pending`, or point an existing one at a
+/// new artifact
+pub async fn upsert_distribution<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution: &NewDistribution,
+) -> Result<(), StoreError> {
+    sqlx::query(UPSERT_SQL)
+        .bind(&distribution.distribution_id)
+        .bind(&distribution.reward_id)
+        .bind(&distribution.window_id)
+        .bind(&distribution.mint)
+        .bind(distribution.total_amount.to_string())
+        .bind(&distribution.merkle_root)
+        .bind(integer("num_recipients", distribution.num_recipients)?)
+        .bind(&distribution.csv_hash)
+        .bind(&distribution.artifact_path)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// Look up a distribution by id
+pub async fn distribution<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+) -> Result<Option<DistributionRecord>, StoreError> {
+    let sql = format!("{SELECT_SQL} WHERE distribution_id = $1");
+    sqlx::query(&sql)
+        .bind(distribution_id)
+        .fetch_optional(executor)
+        .await?
+        .as_ref()
+        .map(DistributionRecord::from_row)
+        .transpose()
+}
+
+/// Look up a distribution by its on-chain PDA in `deployment`
+pub async fn distribution_by_address<'e>(
+    executor: impl PgExecutor<'e>,
+    deployment: &str,
+    address: &str,
+) -> Result<Option<DistributionRecord>, StoreError> {
+    let sql = format!("{SELECT_SQL} WHERE deployment = $1 AND on_chain_address = $2");
+    sqlx::query(&sql)
+        .bind(deployment)
+        .bind(address)
+        .fetch_optional(executor)
+        .await?
+        .as_ref()
+        .map(DistributionRecord::from_row)
+        .transpose()
+}
+
+/// Distributions in any of `statuses`, newest first
+pub async fn distributions_with_status<'e>(
+    executor: impl PgExecutor<'e>,
+    statuses: &[DistributionStatus],
+) -> Result<Vec<DistributionRecord>, StoreError> {
+    let statuses: Vec<&str> = statuses.iter().map(|status| status.as_str()).collect();
+    let sql = format!("{SELECT_SQL} WHERE status = ANY($1) ORDER BY created_at DESC");
+    sqlx::query(&sql)
+        .bind(&statuses)
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(DistributionRecord::from_row)
+        .collect()
+}
+
+/// Record the deployment a distribution was initialized on, with its PDA
+/// and vault, marking it `funded`
+///
+/// Returns whether the distribution exists.
+pub async fn record_on_chain<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+    deployment: &str,
+    on_chain_address: &str,
+    vault_ata: &str,
+) -> Result<bool, StoreError> {
+    let result = sqlx::query(RECORD_ON_CHAIN_SQL)
+        .bind(distribution_id)
+        .bind(on_chain_address)
+        .bind(vault_ata)
+        .bind(deployment)
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Move a distribution to `status`, stamping `activated_at` or
+/// `completed_at` as it enters those states
+///
+/// Returns whether the distribution exists.
+pub async fn set_status<'e>(
+    executor: impl PgExecutor<'e>,
+    distribution_id: &str,
+    status: DistributionStatus,
+) -> Result<bool, StoreError> {
+    let result = sqlx::query(SET_STATUS_SQL)
+        .bind(distribution_id)
+        .bind(status.as_str())
+        .execute(executor)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_round_trips() {
+        for status in DistributionStatus::ALL {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+        assert!("clawed_back".parse::<DistributionStatus>().is_err());
+    }
+}
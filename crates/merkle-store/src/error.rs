@@ -0,0 +1,18 @@
+use thiserror::Error;
+
+/// Errors raised by repository functions
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("database query failed: {0}")]
+    Query(#[from] sqlx::Error),
+    #[error("invalid {column} `{value}`: {reason}")]
+    InvalidColumn {
+        column: &'static str,
+        value: String,
+        reason: String,
+    },
+    #[error("DATABASE_URL is not set")]
+    MissingUrl,
+    #[error("unsupported database URL scheme `{scheme}`; expected postgres:// or sqlite: (with the `sqlite` feature)")]
+    UnsupportedUrl { scheme: String },
+}
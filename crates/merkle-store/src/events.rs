@@ -0,0 +1,197 @@
+//! `merkle_claim_events`: claim, clawback and admin events decoded from
+//! distributor transaction logs
+//!
+//! Read-only: events are written by ingestion, never by callers.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sqlx::PgExecutor;
+
+use crate::column::{optional_units, optional_unsigned, parsed, unsigned, Columns};
+use crate::error::StoreError;
+
+const SELECT_SQL: &str = "
+    SELECT
+      deployment,
+      signature,
+      event_index::BIGINT AS event_index,
+      instruction_index::BIGINT AS instruction_index,
+      slot,
+      EXTRACT(EPOCH FROM block_time)::BIGINT AS block_time,
+      block_time_estimated,
+      distribution,
+      kind,
+      recipient,
+      amount::TEXT AS amount,
+      leaf_index,
+      referrer,
+      bonus::TEXT AS bonus,
+      destination,
+      source
+    FROM merkle_claim_events
+";
+
+/// Event kind (`merkle_claim_events.kind`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ClaimEventKind {
+    Claim,
+    ClaimReferral,
+    Escrow,
+    Renounce,
+    EscrowRelease,
+    Clawback,
+    Pause,
+    Unpause,
+    OperatorChange,
+}
+
+impl ClaimEventKind {
+    pub const ALL: [Self; 9] = [
+        Self::Claim,
+        Self::ClaimReferral,
+        Self::Escrow,
+        Self::Renounce,
+        Self::EscrowRelease,
+        Self::Clawback,
+        Self::Pause,
+        Self::Unpause,
+        Self::OperatorChange,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Claim => "claim",
+            Self::ClaimReferral => "claim_referral",
+            Self::Escrow => "escrow",
+            Self::Renounce => "renounce",
+            Self::EscrowRelease => "escrow_release",
+            Self::Clawback => "clawback",
+            Self::Pause => "pause",
+            Self::Unpause => "unpause",
+            Self::OperatorChange => "operator_change",
+        }
+    }
+}
+
+impl FromStr for ClaimEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown claim event kind `{s}`"))
+    }
+}
+
+impl fmt::Display for ClaimEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A `merkle_claim_events` row
+///
+/// Which optional fields are set depends on `kind`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimEvent {
+    /// Deployment the event was indexed from
+    pub deployment: String,
+    pub signature: String,
+    /// Position of the event within its transaction
+    pub event_index: u64,
+    /// Top-level instruction that emitted the event, `None` for rows stored
+    /// before it was recorded
+    pub instruction_index: Option<u64>,
+    pub slot: u64,
+    /// Unix seconds, interpolated from neighboring slots when the cluster
+    /// reported none
+    pub block_time: Option<i64>,
+    /// `block_time` was interpolated
+    pub block_time_estimated: bool,
+    /// Distribution PDA
+    pub distribution: String,
+    pub kind: ClaimEventKind,
+    pub recipient: Option<String>,
+    pub amount: Option<u64>,
+    pub leaf_index: Option<u64>,
+    pub referrer: Option<String>,
+    pub bonus: Option<u64>,
+    /// Clawback destination token account, or the new operator of an
+    /// `OperatorChange`
+    pub destination: Option<String>,
+    /// Ingestion path that recorded the event
+    pub source: String,
+}
+
+impl ClaimEvent {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            deployment: row.string("deployment")?,
+            signature: row.string("signature")?,
+            event_index: unsigned(row, "event_index")?,
+            instruction_index: optional_unsigned(row, "instruction_index")?,
+            slot: unsigned(row, "slot")?,
+            block_time: row.optional_int("block_time")?,
+            block_time_estimated: row.boolean("block_time_estimated")?,
+            distribution: row.string("distribution")?,
+            kind: parsed(row, "kind")?,
+            recipient: row.optional_string("recipient")?,
+            amount: optional_units(row, "amount")?,
+            leaf_index: optional_unsigned(row, "leaf_index")?,
+            referrer: row.optional_string("referrer")?,
+            bonus: optional_units(row, "bonus")?,
+            destination: row.optional_string("destination")?,
+            source: row.string("source")?,
+        })
+    }
+}
+
+/// Events of one distribution PDA in `deployment`, in chain order
+pub async fn distribution_events<'e>(
+    executor: impl PgExecutor<'e>,
+    deployment: &str,
+    distribution: &str,
+) -> Result<Vec<ClaimEvent>, StoreError> {
+    let sql = format!(
+        "{SELECT_SQL} WHERE deployment = $1 AND distribution = $2 ORDER BY slot, signature, event_index"
+    );
+    sqlx::query(&sql)
+        .bind(deployment)
+        .bind(distribution)
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(ClaimEvent::from_row)
+        .collect()
+}
+
+/// Events naming `recipient` in any deployment, newest first
+pub async fn recipient_events<'e>(
+    executor: impl PgExecutor<'e>,
+    recipient: &str,
+) -> Result<Vec<ClaimEvent>, StoreError> {
+    let sql =
+        format!("{SELECT_SQL} WHERE recipient = $1 ORDER BY slot DESC, signature, event_index");
+    sqlx::query(&sql)
+        .bind(recipient)
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(ClaimEvent::from_row)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_round_trips() {
+        for kind in ClaimEventKind::ALL {
+            assert_eq!(kind.as_str().parse(), Ok(kind));
+        }
+        assert!("claimed".parse::<ClaimEventKind>().is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! Typed repository over the indexer database
+//!
+//! One module per table group, each a set of free async functions taking
+//! any `PgExecutor` (a pool, a connection or a transaction), so callers
+//! choose the transaction boundaries. The `Store` trait exposes the same
+//! operations over a pool for either backend: Postgres (`PgStore`) or,
+//! with the `sqlite` feature, SQLite (`SqliteStore`); `AnyStore` picks one
+//! from `DATABASE_URL`. The schema is built by the migrations in
+//! `db/migrations` (`db/sqlite-migrations` for SQLite); identifiers (mints, wallets, PDAs, signatures) stay base58 / hex strings
+//! as stored.
+
+pub mod allocations;
+pub mod claims;
+mod column;
+pub mod distributions;
+pub mod error;
+pub mod events;
+pub mod reconciliation;
+pub mod snapshots;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
+
+pub use error::StoreError;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::{AnyStore, PgStore, Store};
@@ -0,0 +1,132 @@
+//! `merkle_reconciliation_divergences`: on-chain distribution state that
+//! disagrees with the indexed claim events
+//!
+//! A divergence stays open until a reconciliation run finds the values
+//! matching again. Read-only: divergences are written by those runs.
+
+use std::fmt;
+use std::str::FromStr;
+
+use sqlx::PgExecutor;
+
+use crate::column::{parsed, units, unsigned, Columns};
+use crate::error::StoreError;
+
+const OPEN_SQL: &str = "
+    SELECT
+      id,
+      deployment,
+      distribution,
+      field,
+      on_chain::TEXT AS on_chain,
+      expected::TEXT AS expected,
+      slot,
+      EXTRACT(EPOCH FROM first_seen_at)::BIGINT AS first_seen_at,
+      EXTRACT(EPOCH FROM last_seen_at)::BIGINT AS last_seen_at,
+      EXTRACT(EPOCH FROM alerted_at)::BIGINT AS alerted_at
+    FROM merkle_reconciliation_divergences
+    WHERE resolved_at IS NULL
+    ORDER BY first_seen_at, id
+";
+
+/// Reconciled value (`merkle_reconciliation_divergences.field`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DivergenceField {
+    ClaimedAmount,
+    NumClaimed,
+    VaultBalance,
+}
+
+impl DivergenceField {
+    pub const ALL: [Self; 3] = [Self::ClaimedAmount, Self::NumClaimed, Self::VaultBalance];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ClaimedAmount => "claimed_amount",
+            Self::NumClaimed => "num_claimed",
+            Self::VaultBalance => "vault_balance",
+        }
+    }
+}
+
+impl FromStr for DivergenceField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.as_str() == s)
+            .ok_or_else(|| format!("unknown divergence field `{s}`"))
+    }
+}
+
+impl fmt::Display for DivergenceField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An open divergence
+///
+/// Timestamps are unix seconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub id: i64,
+    /// Deployment the distribution is initialized on
+    pub deployment: String,
+    /// Distribution PDA
+    pub distribution: String,
+    pub field: DivergenceField,
+    pub on_chain: u64,
+    /// Indexed value, or for `VaultBalance` the balance the on-chain totals
+    /// require
+    pub expected: u64,
+    /// Slot of the most recent run that saw the divergence
+    pub slot: u64,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+    /// First alert, once the divergence outlived ingestion lag
+    pub alerted_at: Option<i64>,
+}
+
+impl Divergence {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            id: row.int("id")?,
+            deployment: row.string("deployment")?,
+            distribution: row.string("distribution")?,
+            field: parsed(row, "field")?,
+            on_chain: units(row, "on_chain")?,
+            expected: units(row, "expected")?,
+            slot: unsigned(row, "slot")?,
+            first_seen_at: row.int("first_seen_at")?,
+            last_seen_at: row.int("last_seen_at")?,
+            alerted_at: row.optional_int("alerted_at")?,
+        })
+    }
+}
+
+/// Divergences no run has resolved yet, across deployments, oldest first
+pub async fn open_divergences<'e>(
+    executor: impl PgExecutor<'e>,
+) -> Result<Vec<Divergence>, StoreError> {
+    sqlx::query(OPEN_SQL)
+        .fetch_all(executor)
+        .await?
+        .iter()
+        .map(Divergence::from_row)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_round_trips() {
+        for field in DivergenceField::ALL {
+            assert_eq!(field.as_str().parse(), Ok(field));
+        }
+        assert!("balance".parse::<DivergenceField>().is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! `epoch_snapshots`: token balances pinned at one slot per epoch
+//!
+//! Written by the `epoch-snapshot` job; read here to weight allocations.
+
+use futures_util::{Stream, StreamExt};
+use sqlx::PgExecutor;
+
+use crate::column::{units, unsigned, Columns};
+use crate::error::StoreError;
+
+const SELECT_SQL: &str = "
+    SELECT
+      epoch,
+      mint,
+      epoch_first_slot,
+      slot,
+      EXTRACT(EPOCH FROM block_time)::BIGINT AS block_time,
+      token_accounts::BIGINT AS token_accounts,
+      holders::BIGINT AS holders,
+      total_amount::TEXT AS total_amount
+    FROM epoch_snapshots
+";
+
+const HOLDERS_SQL: &str = "
+    SELECT h.wallet, h.amount::TEXT AS amount, h.token_accounts::BIGINT AS token_accounts
+    FROM epoch_snapshot_holders h
+    WHERE h.epoch = $1 AND h.mint = $2
+    ORDER BY h.amount DESC, h.wallet
+";
+
+/// An `epoch_snapshots` row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochSnapshot {
+    pub epoch: u64,
+    pub mint: String,
+    /// First slot of the epoch
+    pub epoch_first_slot: u64,
+    /// Slot every balance was read at
+    pub slot: u64,
+    /// Unix seconds of `slot`, when the cluster reported it
+    pub block_time: Option<i64>,
+    pub token_accounts: u64,
+    pub holders: u64,
+    pub total_amount: u64,
+}
+
+impl EpochSnapshot {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            epoch: unsigned(row, "epoch")?,
+            mint: row.string("mint")?,
+            epoch_first_slot: unsigned(row, "epoch_first_slot")?,
+            slot: unsigned(row, "slot")?,
+            block_time: row.optional_int("block_time")?,
+            token_accounts: unsigned(row, "token_accounts")?,
+            holders: unsigned(row, "holders")?,
+            total_amount: units(row, "total_amount")?,
+        })
+    }
+}
+
+/// One wallet's balance in a snapshot, summed over its token accounts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotHolder {
+    pub wallet: String,
+    pub amount: u64,
+    pub token_accounts: u64,
+}
+
+impl SnapshotHolder {
+    pub(crate) fn from_row(row: &impl Columns) -> Result<Self, StoreError> {
+        Ok(Self {
+            wallet: row.string("wallet")?,
+            amount: units(row, "amount")?,
+            token_accounts: unsigned(row, "token_accounts")?,
+        })
+    }
+}
+
+/// The snapshot of `mint` at `epoch`
+pub async fn epoch_snapshot<'e>(
+    executor: impl PgExecutor<'e>,
+    epoch: u64,
+    mint: &str,
+) -> Result<Option<EpochSnapshot>, StoreError> {
+    let sql = format!("{SELECT_SQL} WHERE epoch = $1 AND mint = $2");
+    sqlx::query(&sql)
+        .bind(epoch_param(epoch))
+        .bind(mint)
+        .fetch_optional(executor)
+        .await?
+        .as_ref()
+        .map(EpochSnapshot::from_row)
+        .transpose()
+}
+
+/// The most recent snapshot of `mint`
+pub async fn latest_epoch_snapshot<'e>(
+    executor: impl PgExecutor<'e>,
+    mint: &str,
+) -> Result<Option<EpochSnapshot>, StoreError> {
+    let sql = format!("{SELECT_SQL} WHERE mint = $1 ORDER BY epoch DESC LIMIT 1");
+    sqlx::query(&sql)
+        .bind(mint)
+        .fetch_optional(executor)
+        .await?
+        .as_ref()
+        .map(EpochSnapshot::from_row)
+        .transpose()
+}
+
+/// Stream the holders of a snapshot, largest balance first
+pub fn snapshot_holders<'e>(
+    executor: impl PgExecutor<'e> + 'e,
+    epoch: u64,
+    mint: &'e str,
+) -> impl Stream<Item = Result<SnapshotHolder, StoreError>> + 'e {
+    sqlx::query(HOLDERS_SQL)
+        .bind(epoch_param(epoch))
+        .bind(mint)
+        .fetch(executor)
+        .map(|row| SnapshotHolder::from_row(&row?))
+}
+
+// Epochs past BIGINT match no row
+pub(crate) fn epoch_param(epoch: u64) -> i64 {
+    i64::try_from(epoch).unwrap_or(-1)
+}
@@ -0,0 +1,576 @@
+//! SQLite backend (`sqlite` feature)
+//!
+//! Runs the `Store` operations against the schema in
+//! `db/sqlite-migrations`, for deployments that don't want to run
+//! Postgres. Amounts are 64-bit
+//! integers there, so writes above `i64::MAX` fail with
+//! `StoreError::InvalidColumn` instead of losing precision. Lists
+//! (statuses, ignored wallets) are bound as JSON arrays and read with
+//! `json_each`, SQLite having no array parameters.
+
+use std::str::FromStr;
+
+use futures_util::stream::{BoxStream, StreamExt};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+
+use crate::allocations::{Payout, RewardTotals};
+use crate::claims::{ClaimAttempt, ClaimRecord, NewClaim};
+use crate::column::{bigint, unsigned};
+use crate::distributions::{DistributionRecord, DistributionStatus, NewDistribution};
+use crate::error::StoreError;
+use crate::events::ClaimEvent;
+use crate::reconciliation::Divergence;
+use crate::snapshots::{epoch_param, EpochSnapshot, SnapshotHolder};
+use crate::store::Store;
+
+/// One script from `db/sqlite-migrations`
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// `db/sqlite-migrations`, in version order, applied by `migrate`
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "schema",
+        sql: include_str!("../../../db/sqlite-migrations/0001_schema.up.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "deployments",
+        sql: include_str!("../../../db/sqlite-migrations/0002_deployments.up.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "block_time_estimated",
+        sql: include_str!("../../../db/sqlite-migrations/0003_block_time_estimated.up.sql"),
+    },
+];
+
+const MIGRATIONS_TABLE_SQL: &str = "
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+      version INTEGER PRIMARY KEY,
+      name TEXT NOT NULL,
+      applied_at INTEGER NOT NULL DEFAULT (unixepoch())
+    )
+";
+
+const DISTRIBUTION_SQL: &str = "
+    SELECT
+      distribution_id,
+      reward_id,
+      window_id,
+      mint,
+      CAST(total_amount AS TEXT) AS total_amount,
+      merkle_root,
+      num_recipients,
+      csv_hash,
+      artifact_path,
+      deployment,
+      on_chain_address,
+      vault_ata,
+      status,
+      CAST(claimed_amount AS TEXT) AS claimed_amount,
+      claimed_count,
+      created_at,
+      funded_at,
+      activated_at,
+      completed_at
+    FROM merkle_distributions
+";
+
+const UPSERT_DISTRIBUTION_SQL: &str = "
+    INSERT INTO merkle_distributions (
+      distribution_id,
+      reward_id,
+      window_id,
+      mint,
+      total_amount,
+      merkle_root,
+      num_recipients,
+      csv_hash,
+      artifact_path,
+      status
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'pending')
+    ON CONFLICT (distribution_id) DO UPDATE SET
+      artifact_path = excluded.artifact_path,
+      updated_at = unixepoch()
+";
+
+const RECORD_ON_CHAIN_SQL: &str = "
+    UPDATE merkle_distributions
+    SET
+      deployment = ?4,
+      on_chain_address = ?2,
+      vault_ata = ?3,
+      status = 'funded',
+      funded_at = unixepoch()
+    WHERE distribution_id = ?1
+";
+
+const SET_STATUS_SQL: &str = "
+    UPDATE merkle_distributions
+    SET
+      status = ?2,
+      activated_at = CASE WHEN ?2 = 'active' THEN unixepoch() ELSE activated_at END,
+      completed_at = CASE WHEN ?2 IN ('completed', 'clawedback') THEN unixepoch() ELSE completed_at END
+    WHERE distribution_id = ?1
+";
+
+const SNAPSHOT_SQL: &str = "
+    SELECT
+      epoch,
+      mint,
+      epoch_first_slot,
+      slot,
+      block_time,
+      token_accounts,
+      holders,
+      CAST(total_amount AS TEXT) AS total_amount
+    FROM epoch_snapshots
+";
+
+const HOLDERS_SQL: &str = "
+    SELECT h.wallet, CAST(h.amount AS TEXT) AS amount, h.token_accounts
+    FROM epoch_snapshot_holders h
+    WHERE h.epoch = ?1 AND h.mint = ?2
+    ORDER BY h.amount DESC, h.wallet
+";
+
+const TOTALS_SQL: &str = "
+    SELECT
+      COUNT(*) AS num_recipients,
+      CAST(COALESCE(SUM(payout_amount), 0) AS TEXT) AS total_amount,
+      COUNT(DISTINCT mint) AS num_mints,
+      MIN(mint) AS mint,
+      MIN(window_id) AS window_id
+    FROM reward_payouts_preview
+    WHERE reward_id = ?1
+      AND payout_amount > 0
+      AND wallet NOT IN (SELECT value FROM json_each(?2))
+";
+
+const PAYOUTS_SQL: &str = "
+    SELECT wallet, CAST(payout_amount AS TEXT) AS amount
+    FROM reward_payouts_preview
+    WHERE reward_id = ?1
+      AND payout_amount > 0
+      AND wallet NOT IN (SELECT value FROM json_each(?2))
+    ORDER BY payout_amount DESC, wallet
+";
+
+const INSERT_CLAIM_SQL: &str = "
+    INSERT INTO merkle_claims (distribution_id, leaf_index, wallet, amount, status)
+    VALUES (?1, ?2, ?3, ?4, 'pending')
+    ON CONFLICT (distribution_id, leaf_index) DO NOTHING
+";
+
+const CLAIM_SQL: &str = "
+    SELECT
+      distribution_id,
+      leaf_index,
+      wallet,
+      CAST(amount AS TEXT) AS amount,
+      status,
+      tx_signature,
+      attempts,
+      last_attempt,
+      confirmed_at,
+      error_message
+    FROM merkle_claims
+";
+
+const RECORD_ATTEMPT_SQL: &str = "
+    UPDATE merkle_claims
+    SET
+      status = ?3,
+      tx_signature = COALESCE(?4, tx_signature),
+      attempts = attempts + 1,
+      last_attempt = unixepoch(),
+      confirmed_at = CASE WHEN ?3 = 'confirmed' THEN unixepoch() ELSE confirmed_at END,
+      error_message = ?5
+    WHERE distribution_id = ?1 AND leaf_index = ?2
+";
+
+const UNCONFIRMED_SQL: &str = "
+    SELECT COUNT(*) AS unconfirmed
+    FROM merkle_claims
+    WHERE distribution_id = ?1
+      AND status <> 'confirmed'
+";
+
+const EVENT_SQL: &str = "
+    SELECT
+      deployment,
+      signature,
+      event_index,
+      instruction_index,
+      slot,
+      block_time,
+      block_time_estimated,
+      distribution,
+      kind,
+      recipient,
+      CAST(amount AS TEXT) AS amount,
+      leaf_index,
+      referrer,
+      CAST(bonus AS TEXT) AS bonus,
+      destination,
+      source
+    FROM merkle_claim_events
+";
+
+const OPEN_DIVERGENCES_SQL: &str = "
+    SELECT
+      id,
+      deployment,
+      distribution,
+      field,
+      CAST(on_chain AS TEXT) AS on_chain,
+      CAST(expected AS TEXT) AS expected,
+      slot,
+      first_seen_at,
+      last_seen_at,
+      alerted_at
+    FROM merkle_reconciliation_divergences
+    WHERE resolved_at IS NULL
+    ORDER BY first_seen_at, id
+";
+
+/// SQLite backend over a pool
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Open (creating if missing) the database at `url` (`sqlite://path`
+    /// or `sqlite::memory:`) with foreign keys enforced, and apply pending
+    /// migrations
+    pub async fn connect(url: &str) -> Result<Self, StoreError> {
+        let options = SqliteConnectOptions::from_str(url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let store = Self::new(SqlitePool::connect_with(options).await?);
+        store.migrate().await?;
+        Ok(store)
+    }
+
+    /// Apply the migrations not yet recorded in `schema_migrations`, in
+    /// one write transaction so concurrent callers apply each once; safe
+    /// to repeat
+    pub async fn migrate(&self) -> Result<(), StoreError> {
+        sqlx::raw_sql(MIGRATIONS_TABLE_SQL)
+            .execute(&self.pool)
+            .await?;
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+        let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM schema_migrations")
+            .fetch_all(&mut *tx)
+            .await?;
+        for migration in MIGRATIONS {
+            if applied.contains(&migration.version) {
+                continue;
+            }
+            sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+            sqlx::query("INSERT INTO schema_migrations (version, name) VALUES (?, ?)")
+                .bind(migration.version)
+                .bind(migration.name)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The pool, for transactions and the tables `Store` doesn't write
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+impl Store for SqliteStore {
+    async fn upsert_distribution(&self, distribution: &NewDistribution) -> Result<(), StoreError> {
+        sqlx::query(UPSERT_DISTRIBUTION_SQL)
+            .bind(&distribution.distribution_id)
+            .bind(&distribution.reward_id)
+            .bind(&distribution.window_id)
+            .bind(&distribution.mint)
+            .bind(bigint("total_amount", distribution.total_amount)?)
+            .bind(&distribution.merkle_root)
+            .bind(bigint("num_recipients", distribution.num_recipients)?)
+            .bind(&distribution.csv_hash)
+            .bind(&distribution.artifact_path)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn distribution(
+        &self,
+        distribution_id: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        let sql = format!("{DISTRIBUTION_SQL} WHERE distribution_id = ?1");
+        sqlx::query(&sql)
+            .bind(distribution_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(DistributionRecord::from_row)
+            .transpose()
+    }
+
+    async fn distribution_by_address(
+        &self,
+        deployment: &str,
+        address: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        let sql = format!("{DISTRIBUTION_SQL} WHERE deployment = ?1 AND on_chain_address = ?2");
+        sqlx::query(&sql)
+            .bind(deployment)
+            .bind(address)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(DistributionRecord::from_row)
+            .transpose()
+    }
+
+    async fn distributions_with_status(
+        &self,
+        statuses: &[DistributionStatus],
+    ) -> Result<Vec<DistributionRecord>, StoreError> {
+        let statuses: Vec<&str> = statuses.iter().map(|status| status.as_str()).collect();
+        let sql = format!(
+            "{DISTRIBUTION_SQL} WHERE status IN (SELECT value FROM json_each(?1)) ORDER BY created_at DESC"
+        );
+        sqlx::query(&sql)
+            .bind(json_array(&statuses))
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(DistributionRecord::from_row)
+            .collect()
+    }
+
+    async fn record_on_chain(
+        &self,
+        distribution_id: &str,
+        deployment: &str,
+        on_chain_address: &str,
+        vault_ata: &str,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(RECORD_ON_CHAIN_SQL)
+            .bind(distribution_id)
+            .bind(on_chain_address)
+            .bind(vault_ata)
+            .bind(deployment)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_status(
+        &self,
+        distribution_id: &str,
+        status: DistributionStatus,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(SET_STATUS_SQL)
+            .bind(distribution_id)
+            .bind(status.as_str())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn epoch_snapshot(
+        &self,
+        epoch: u64,
+        mint: &str,
+    ) -> Result<Option<EpochSnapshot>, StoreError> {
+        let sql = format!("{SNAPSHOT_SQL} WHERE epoch = ?1 AND mint = ?2");
+        sqlx::query(&sql)
+            .bind(epoch_param(epoch))
+            .bind(mint)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(EpochSnapshot::from_row)
+            .transpose()
+    }
+
+    async fn latest_epoch_snapshot(&self, mint: &str) -> Result<Option<EpochSnapshot>, StoreError> {
+        let sql = format!("{SNAPSHOT_SQL} WHERE mint = ?1 ORDER BY epoch DESC LIMIT 1");
+        sqlx::query(&sql)
+            .bind(mint)
+            .fetch_optional(&self.pool)
+            .await?
+            .as_ref()
+            .map(EpochSnapshot::from_row)
+            .transpose()
+    }
+
+    fn snapshot_holders<'a>(
+        &'a self,
+        epoch: u64,
+        mint: &'a str,
+    ) -> BoxStream<'a, Result<SnapshotHolder, StoreError>> {
+        sqlx::query(HOLDERS_SQL)
+            .bind(epoch_param(epoch))
+            .bind(mint)
+            .fetch(&self.pool)
+            .map(|row| SnapshotHolder::from_row(&row?))
+            .boxed()
+    }
+
+    async fn reward_totals(
+        &self,
+        reward_id: &str,
+        ignored_wallets: &[String],
+    ) -> Result<RewardTotals, StoreError> {
+        let row = sqlx::query(TOTALS_SQL)
+            .bind(reward_id)
+            .bind(json_array(ignored_wallets))
+            .fetch_one(&self.pool)
+            .await?;
+        RewardTotals::from_row(&row)
+    }
+
+    fn reward_payouts<'a>(
+        &'a self,
+        reward_id: &'a str,
+        ignored_wallets: &'a [String],
+    ) -> BoxStream<'a, Result<Payout, StoreError>> {
+        sqlx::query(PAYOUTS_SQL)
+            .bind(reward_id)
+            .bind(json_array(ignored_wallets))
+            .fetch(&self.pool)
+            .map(|row| Payout::from_row(&row?))
+            .boxed()
+    }
+
+    async fn insert_claims(
+        &self,
+        distribution_id: &str,
+        claims: &[NewClaim],
+    ) -> Result<u64, StoreError> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for claim in claims {
+            let result = sqlx::query(INSERT_CLAIM_SQL)
+                .bind(distribution_id)
+                .bind(bigint("leaf_index", claim.leaf_index)?)
+                .bind(&claim.wallet)
+                .bind(bigint("amount", claim.amount)?)
+                .execute(&mut *tx)
+                .await?;
+            inserted += result.rows_affected();
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn pending_claims(
+        &self,
+        distribution_id: &str,
+        max_attempts: u32,
+    ) -> Result<Vec<ClaimRecord>, StoreError> {
+        let sql = format!(
+            "{CLAIM_SQL} WHERE distribution_id = ?1 AND status IN ('pending', 'failed') AND attempts < ?2 ORDER BY leaf_index"
+        );
+        sqlx::query(&sql)
+            .bind(distribution_id)
+            .bind(i64::from(max_attempts))
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(ClaimRecord::from_row)
+            .collect()
+    }
+
+    async fn wallet_claims(&self, wallet: &str) -> Result<Vec<ClaimRecord>, StoreError> {
+        let sql = format!("{CLAIM_SQL} WHERE wallet = ?1 ORDER BY distribution_id, leaf_index");
+        sqlx::query(&sql)
+            .bind(wallet)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(ClaimRecord::from_row)
+            .collect()
+    }
+
+    async fn record_attempt(
+        &self,
+        distribution_id: &str,
+        leaf_index: u64,
+        attempt: &ClaimAttempt,
+    ) -> Result<bool, StoreError> {
+        let result = sqlx::query(RECORD_ATTEMPT_SQL)
+            .bind(distribution_id)
+            .bind(bigint("leaf_index", leaf_index)?)
+            .bind(attempt.status.as_str())
+            .bind(&attempt.tx_signature)
+            .bind(&attempt.error_message)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unconfirmed_claims(&self, distribution_id: &str) -> Result<u64, StoreError> {
+        let row = sqlx::query(UNCONFIRMED_SQL)
+            .bind(distribution_id)
+            .fetch_one(&self.pool)
+            .await?;
+        unsigned(&row, "unconfirmed")
+    }
+
+    async fn distribution_events(
+        &self,
+        deployment: &str,
+        distribution: &str,
+    ) -> Result<Vec<ClaimEvent>, StoreError> {
+        let sql = format!(
+            "{EVENT_SQL} WHERE deployment = ?1 AND distribution = ?2 ORDER BY slot, signature, event_index"
+        );
+        sqlx::query(&sql)
+            .bind(deployment)
+            .bind(distribution)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(ClaimEvent::from_row)
+            .collect()
+    }
+
+    async fn recipient_events(&self, recipient: &str) -> Result<Vec<ClaimEvent>, StoreError> {
+        let sql =
+            format!("{EVENT_SQL} WHERE recipient = ?1 ORDER BY slot DESC, signature, event_index");
+        sqlx::query(&sql)
+            .bind(recipient)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(ClaimEvent::from_row)
+            .collect()
+    }
+
+    async fn open_divergences(&self) -> Result<Vec<Divergence>, StoreError> {
+        sqlx::query(OPEN_DIVERGENCES_SQL)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(Divergence::from_row)
+            .collect()
+    }
+}
+
+fn json_array<T: AsRef<str>>(values: &[T]) -> String {
+    let values: Vec<&str> = values.iter().map(AsRef::as_ref).collect();
+    serde_json::to_string(&values).expect("string arrays serialize")
+}
@@ -0,0 +1,518 @@
+//! The storage trait and backend selection
+//!
+//! `Store` is the repository API over a connection pool. `PgStore` runs the
+//! Postgres functions of each module; `SqliteStore` (`sqlite` feature) runs
+//! the same operations against `db/sqlite-migrations`. `AnyStore::connect`
+//! picks the backend from the database URL, so deployments choose one with
+//! `DATABASE_URL` alone.
+
+use std::future::Future;
+
+use futures_util::stream::{BoxStream, StreamExt};
+use sqlx::postgres::PgPool;
+
+use crate::allocations::{self, Payout, RewardTotals};
+use crate::claims::{self, ClaimAttempt, ClaimRecord, NewClaim};
+use crate::distributions::{self, DistributionRecord, DistributionStatus, NewDistribution};
+use crate::error::StoreError;
+use crate::events::{self, ClaimEvent};
+use crate::reconciliation::{self, Divergence};
+use crate::snapshots::{self, EpochSnapshot, SnapshotHolder};
+#[cfg(feature = "sqlite")]
+use crate::sqlite::SqliteStore;
+
+/// Repository operations every backend implements
+///
+/// Each method has the semantics of the Postgres function of the same name
+/// in its module.
+pub trait Store: Send + Sync {
+    fn upsert_distribution(
+        &self,
+        distribution: &NewDistribution,
+    ) -> impl Future<Output = Result<(), StoreError>> + Send;
+
+    fn distribution(
+        &self,
+        distribution_id: &str,
+    ) -> impl Future<Output = Result<Option<DistributionRecord>, StoreError>> + Send;
+
+    fn distribution_by_address(
+        &self,
+        deployment: &str,
+        address: &str,
+    ) -> impl Future<Output = Result<Option<DistributionRecord>, StoreError>> + Send;
+
+    fn distributions_with_status(
+        &self,
+        statuses: &[DistributionStatus],
+    ) -> impl Future<Output = Result<Vec<DistributionRecord>, StoreError>> + Send;
+
+    fn record_on_chain(
+        &self,
+        distribution_id: &str,
+        deployment: &str,
+        on_chain_address: &str,
+        vault_ata: &str,
+    ) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    fn set_status(
+        &self,
+        distribution_id: &str,
+        status: DistributionStatus,
+    ) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    fn epoch_snapshot(
+        &self,
+        epoch: u64,
+        mint: &str,
+    ) -> impl Future<Output = Result<Option<EpochSnapshot>, StoreError>> + Send;
+
+    fn latest_epoch_snapshot(
+        &self,
+        mint: &str,
+    ) -> impl Future<Output = Result<Option<EpochSnapshot>, StoreError>> + Send;
+
+    fn snapshot_holders<'a>(
+        &'a self,
+        epoch: u64,
+        mint: &'a str,
+    ) -> BoxStream<'a, Result<SnapshotHolder, StoreError>>;
+
+    fn reward_totals(
+        &self,
+        reward_id: &str,
+        ignored_wallets: &[String],
+    ) -> impl Future<Output = Result<RewardTotals, StoreError>> + Send;
+
+    fn reward_payouts<'a>(
+        &'a self,
+        reward_id: &'a str,
+        ignored_wallets: &'a [String],
+    ) -> BoxStream<'a, Result<Payout, StoreError>>;
+
+    fn insert_claims(
+        &self,
+        distribution_id: &str,
+        claims: &[NewClaim],
+    ) -> impl Future<Output = Result<u64, StoreError>> + Send;
+
+    fn pending_claims(
+        &self,
+        distribution_id: &str,
+        max_attempts: u32,
+    ) -> impl Future<Output = Result<Vec<ClaimRecord>, StoreError>> + Send;
+
+    fn wallet_claims(
+        &self,
+        wallet: &str,
+    ) -> impl Future<Output = Result<Vec<ClaimRecord>, StoreError>> + Send;
+
+    fn record_attempt(
+        &self,
+        distribution_id: &str,
+        leaf_index: u64,
+        attempt: &ClaimAttempt,
+    ) -> impl Future<Output = Result<bool, StoreError>> + Send;
+
+    fn unconfirmed_claims(
+        &self,
+        distribution_id: &str,
+    ) -> impl Future<Output = Result<u64, StoreError>> + Send;
+
+    fn distribution_events(
+        &self,
+        deployment: &str,
+        distribution: &str,
+    ) -> impl Future<Output = Result<Vec<ClaimEvent>, StoreError>> + Send;
+
+    fn recipient_events(
+        &self,
+        recipient: &str,
+    ) -> impl Future<Output = Result<Vec<ClaimEvent>, StoreError>> + Send;
+
+    fn open_divergences(&self) -> impl Future<Output = Result<Vec<Divergence>, StoreError>> + Send;
+}
+
+/// Postgres backend: the module functions over a pool
+#[derive(Clone, Debug)]
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Connect to the database at `url` (`postgres://…`)
+    pub async fn connect(url: &str) -> Result<Self, StoreError> {
+        Ok(Self::new(PgPool::connect(url).await?))
+    }
+
+    /// The pool, for transactions across several module functions
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+impl Store for PgStore {
+    async fn upsert_distribution(&self, distribution: &NewDistribution) -> Result<(), StoreError> {
+        distributions::upsert_distribution(&self.pool, distribution).await
+    }
+
+    async fn distribution(
+        &self,
+        distribution_id: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        distributions::distribution(&self.pool, distribution_id).await
+    }
+
+    async fn distribution_by_address(
+        &self,
+        deployment: &str,
+        address: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        distributions::distribution_by_address(&self.pool, deployment, address).await
+    }
+
+    async fn distributions_with_status(
+        &self,
+        statuses: &[DistributionStatus],
+    ) -> Result<Vec<DistributionRecord>, StoreError> {
+        distributions::distributions_with_status(&self.pool, statuses).await
+    }
+
+    async fn record_on_chain(
+        &self,
+        distribution_id: &str,
+        deployment: &str,
+        on_chain_address: &str,
+        vault_ata: &str,
+    ) -> Result<bool, StoreError> {
+        distributions::record_on_chain(
+            &self.pool,
+            distribution_id,
+            deployment,
+            on_chain_address,
+            vault_ata,
+        )
+        .await
+    }
+
+    async fn set_status(
+        &self,
+        distribution_id: &str,
+        status: DistributionStatus,
+    ) -> Result<bool, StoreError> {
+        distributions::set_status(&self.pool, distribution_id, status).await
+    }
+
+    async fn epoch_snapshot(
+        &self,
+        epoch: u64,
+        mint: &str,
+    ) -> Result<Option<EpochSnapshot>, StoreError> {
+        snapshots::epoch_snapshot(&self.pool, epoch, mint).await
+    }
+
+    async fn latest_epoch_snapshot(&self, mint: &str) -> Result<Option<EpochSnapshot>, StoreError> {
+        snapshots::latest_epoch_snapshot(&self.pool, mint).await
+    }
+
+    fn snapshot_holders<'a>(
+        &'a self,
+        epoch: u64,
+        mint: &'a str,
+    ) -> BoxStream<'a, Result<SnapshotHolder, StoreError>> {
+        snapshots::snapshot_holders(&self.pool, epoch, mint).boxed()
+    }
+
+    async fn reward_totals(
+        &self,
+        reward_id: &str,
+        ignored_wallets: &[String],
+    ) -> Result<RewardTotals, StoreError> {
+        allocations::reward_totals(&self.pool, reward_id, ignored_wallets).await
+    }
+
+    fn reward_payouts<'a>(
+        &'a self,
+        reward_id: &'a str,
+        ignored_wallets: &'a [String],
+    ) -> BoxStream<'a, Result<Payout, StoreError>> {
+        allocations::reward_payouts(&self.pool, reward_id, ignored_wallets).boxed()
+    }
+
+    async fn insert_claims(
+        &self,
+        distribution_id: &str,
+        claims: &[NewClaim],
+    ) -> Result<u64, StoreError> {
+        claims::insert_claims(&self.pool, distribution_id, claims).await
+    }
+
+    async fn pending_claims(
+        &self,
+        distribution_id: &str,
+        max_attempts: u32,
+    ) -> Result<Vec<ClaimRecord>, StoreError> {
+        claims::pending_claims(&self.pool, distribution_id, max_attempts).await
+    }
+
+    async fn wallet_claims(&self, wallet: &str) -> Result<Vec<ClaimRecord>, StoreError> {
+        claims::wallet_claims(&self.pool, wallet).await
+    }
+
+    async fn record_attempt(
+        &self,
+        distribution_id: &str,
+        leaf_index: u64,
+        attempt: &ClaimAttempt,
+    ) -> Result<bool, StoreError> {
+        claims::record_attempt(&self.pool, distribution_id, leaf_index, attempt).await
+    }
+
+    async fn unconfirmed_claims(&self, distribution_id: &str) -> Result<u64, StoreError> {
+        claims::unconfirmed_claims(&self.pool, distribution_id).await
+    }
+
+    async fn distribution_events(
+        &self,
+        deployment: &str,
+        distribution: &str,
+    ) -> Result<Vec<ClaimEvent>, StoreError> {
+        events::distribution_events(&self.pool, deployment, distribution).await
+    }
+
+    async fn recipient_events(&self, recipient: &str) -> Result<Vec<ClaimEvent>, StoreError> {
+        events::recipient_events(&self.pool, recipient).await
+    }
+
+    async fn open_divergences(&self) -> Result<Vec<Divergence>, StoreError> {
+        reconciliation::open_divergences(&self.pool).await
+    }
+}
+
+/// The backend named by a database URL
+#[derive(Clone, Debug)]
+pub enum AnyStore {
+    Postgres(PgStore),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStore),
+}
+
+impl AnyStore {
+    /// Connect to `url`: `postgres://` / `postgresql://` for Postgres,
+    /// `sqlite:` for SQLite (with the `sqlite` feature)
+    pub async fn connect(url: &str) -> Result<Self, StoreError> {
+        match Backend::of_url(url)? {
+            Backend::Postgres => Ok(Self::Postgres(PgStore::connect(url).await?)),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Ok(Self::Sqlite(SqliteStore::connect(url).await?)),
+        }
+    }
+
+    /// Connect to `DATABASE_URL`
+    pub async fn from_env() -> Result<Self, StoreError> {
+        let url = std::env::var("DATABASE_URL").map_err(|_| StoreError::MissingUrl)?;
+        Self::connect(&url).await
+    }
+}
+
+/// Backends compiled in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    Postgres,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl Backend {
+    fn of_url(url: &str) -> Result<Self, StoreError> {
+        let scheme = url.split_once(':').map_or("", |(scheme, _)| scheme);
+        match scheme {
+            "postgres" | "postgresql" => Ok(Self::Postgres),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(StoreError::UnsupportedUrl {
+                scheme: scheme.to_string(),
+            }),
+        }
+    }
+}
+
+macro_rules! dispatch {
+    ($self:ident, $store:ident => $call:expr) => {
+        match $self {
+            AnyStore::Postgres($store) => $call,
+            #[cfg(feature = "sqlite")]
+            AnyStore::Sqlite($store) => $call,
+        }
+    };
+}
+
+impl Store for AnyStore {
+    async fn upsert_distribution(&self, distribution: &NewDistribution) -> Result<(), StoreError> {
+        dispatch!(self, store => store.upsert_distribution(distribution).await)
+    }
+
+    async fn distribution(
+        &self,
+        distribution_id: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        dispatch!(self, store => store.distribution(distribution_id).await)
+    }
+
+    async fn distribution_by_address(
+        &self,
+        deployment: &str,
+        address: &str,
+    ) -> Result<Option<DistributionRecord>, StoreError> {
+        dispatch!(self, store => store.distribution_by_address(deployment, address).await)
+    }
+
+    async fn distributions_with_status(
+        &self,
+        statuses: &[DistributionStatus],
+    ) -> Result<Vec<DistributionRecord>, StoreError> {
+        dispatch!(self, store => store.distributions_with_status(statuses).await)
+    }
+
+    async fn record_on_chain(
+        &self,
+        distribution_id: &str,
+        deployment: &str,
+        on_chain_address: &str,
+        vault_ata: &str,
+    ) -> Result<bool, StoreError> {
+        dispatch!(self, store => {
+            store
+                .record_on_chain(distribution_id, deployment, on_chain_address, vault_ata)
+                .await
+        })
+    }
+
+    async fn set_status(
+        &self,
+        distribution_id: &str,
+        status: DistributionStatus,
+    ) -> Result<bool, StoreError> {
+        dispatch!(self, store => store.set_status(distribution_id, status).await)
+    }
+
+    async fn epoch_snapshot(
+        &self,
+        epoch: u64,
+        mint: &str,
+    ) -> Result<Option<EpochSnapshot>, StoreError> {
+        dispatch!(self, store => store.epoch_snapshot(epoch, mint).await)
+    }
+
+    async fn latest_epoch_snapshot(&self, mint: &str) -> Result<Option<EpochSnapshot>, StoreError> {
+        dispatch!(self, store => store.latest_epoch_snapshot(mint).await)
+    }
+
+    fn snapshot_holders<'a>(
+        &'a self,
+        epoch: u64,
+        mint: &'a str,
+    ) -> BoxStream<'a, Result<SnapshotHolder, StoreError>> {
+        dispatch!(self, store => store.snapshot_holders(epoch, mint))
+    }
+
+    async fn reward_totals(
+        &self,
+        reward_id: &str,
+        ignored_wallets: &[String],
+    ) -> Result<RewardTotals, StoreError> {
+        dispatch!(self, store => store.reward_totals(reward_id, ignored_wallets).await)
+    }
+
+    fn reward_payouts<'a>(
+        &'a self,
+        reward_id: &'a str,
+        ignored_wallets: &'a [String],
+    ) -> BoxStream<'a, Result<Payout, StoreError>> {
+        dispatch!(self, store => store.reward_payouts(reward_id, ignored_wallets))
+    }
+
+    async fn insert_claims(
+        &self,
+        distribution_id: &str,
+        claims: &[NewClaim],
+    ) -> Result<u64, StoreError> {
+        dispatch!(self, store => store.insert_claims(distribution_id, claims).await)
+    }
+
+    async fn pending_claims(
+        &self,
+        distribution_id: &str,
+        max_attempts: u32,
+    ) -> Result<Vec<ClaimRecord>, StoreError> {
+        dispatch!(self, store => store.pending_claims(distribution_id, max_attempts).await)
+    }
+
+    async fn wallet_claims(&self, wallet: &str) -> Result<Vec<ClaimRecord>, StoreError> {
+        dispatch!(self, store => store.wallet_claims(wallet).await)
+    }
+
+    async fn record_attempt(
+        &self,
+        distribution_id: &str,
+        leaf_index: u64,
+        attempt: &ClaimAttempt,
+    ) -> Result<bool, StoreError> {
+        dispatch!(self, store => store.record_attempt(distribution_id, leaf_index, attempt).await)
+    }
+
+    async fn unconfirmed_claims(&self, distribution_id: &str) -> Result<u64, StoreError> {
+        dispatch!(self, store => store.unconfirmed_claims(distribution_id).await)
+    }
+
+    async fn distribution_events(
+        &self,
+        deployment: &str,
+        distribution: &str,
+    ) -> Result<Vec<ClaimEvent>, StoreError> {
+        dispatch!(self, store => store.distribution_events(deployment, distribution).await)
+    }
+
+    async fn recipient_events(&self, recipient: &str) -> Result<Vec<ClaimEvent>, StoreError> {
+        dispatch!(self, store => store.recipient_events(recipient).await)
+    }
+
+    async fn open_divergences(&self) -> Result<Vec<Divergence>, StoreError> {
+        dispatch!(self, store => store.open_divergences().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_follows_the_url_scheme() {
+        assert_eq!(
+            Backend::of_url("postgres://localhost/indexer").unwrap(),
+            Backend::Postgres
+        );
+        assert_eq!(
+            Backend::of_url("postgresql://localhost/indexer").unwrap(),
+            Backend::Postgres
+        );
+        assert!(matches!(
+            Backend::of_url("mysql://localhost/indexer"),
+            Err(StoreError::UnsupportedUrl { scheme }) if scheme == "mysql"
+        ));
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_urls_select_sqlite() {
+        assert_eq!(
+            Backend::of_url("sqlite://indexer.db").unwrap(),
+            Backend::Sqlite
+        );
+        assert_eq!(Backend::of_url("sqlite::memory:").unwrap(), Backend::Sqlite);
+    }
+}
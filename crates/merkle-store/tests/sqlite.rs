@@ -0,0 +1,380 @@
+//! The SQLite backend against a scratch database file
+
+use futures_util::TryStreamExt;
+use merkle_store::claims::{ClaimAttempt, ClaimStatus, NewClaim};
+use merkle_store::distributions::{DistributionStatus, NewDistribution};
+use merkle_store::events::ClaimEventKind;
+use merkle_store::reconciliation::DivergenceField;
+use merkle_store::{AnyStore, SqliteStore, Store, StoreError};
+use tempfile::TempDir;
+
+async fn store() -> (TempDir, SqliteStore) {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("indexer.db").display());
+    let store = SqliteStore::connect(&url).await.unwrap();
+    (dir, store)
+}
+
+async fn exec(store: &SqliteStore, sql: &str) {
+    sqlx::raw_sql(sql).execute(store.pool()).await.unwrap();
+}
+
+fn distribution(id: &str) -> NewDistribution {
+    NewDistribution {
+        distribution_id: id.to_string(),
+        reward_id: "reward-1".to_string(),
+        window_id: "2026-W41".to_string(),
+        mint: "mint".to_string(),
+        total_amount: 1_500,
+        merkle_root: "ab".repeat(32),
+        num_recipients: 3,
+        csv_hash: "cd".repeat(32),
+        artifact_path: "distributions/a.json".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn migrations_apply_once() {
+    let (_dir, store) = store().await;
+    store.migrate().await.unwrap();
+
+    let applied: Vec<(i64, String)> =
+        sqlx::query_as("SELECT version, name FROM schema_migrations ORDER BY version")
+            .fetch_all(store.pool())
+            .await
+            .unwrap();
+    let expected: Vec<(i64, String)> = merkle_store::sqlite::MIGRATIONS
+        .iter()
+        .map(|m| (m.version, m.name.to_string()))
+        .collect();
+    assert_eq!(applied, expected);
+}
+
+#[tokio::test]
+async fn migrations_upgrade_a_database_without_schema_migrations() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("indexer.db").display());
+    {
+        let options = url
+            .parse::<sqlx::sqlite::SqliteConnectOptions>()
+            .unwrap()
+            .create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options).await.unwrap();
+        sqlx::raw_sql(merkle_store::sqlite::MIGRATIONS[0].sql)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO merkle_claim_events (signature, event_index, slot, distribution, kind, source)
+             VALUES ('s1', 0, 10, 'pda', 'pause', 'geyser')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+    }
+
+    let store = SqliteStore::connect(&url).await.unwrap();
+    let events = store.distribution_events("default", "pda").await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].deployment, "default");
+}
+
+#[tokio::test]
+async fn any_store_selects_sqlite_from_the_url() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", dir.path().join("indexer.db").display());
+    let store = AnyStore::connect(&url).await.unwrap();
+    assert!(matches!(store, AnyStore::Sqlite(_)));
+    assert_eq!(store.distribution("missing").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn distribution_lifecycle() {
+    let (_dir, store) = store().await;
+    store
+        .upsert_distribution(&distribution("d1"))
+        .await
+        .unwrap();
+
+    // Rebuilding only moves the artifact
+    let mut rebuilt = distribution("d1");
+    rebuilt.artifact_path = "distributions/b.json".to_string();
+    rebuilt.total_amount = 9;
+    store.upsert_distribution(&rebuilt).await.unwrap();
+
+    let record = store.distribution("d1").await.unwrap().unwrap();
+    assert_eq!(record.status, DistributionStatus::Pending);
+    assert_eq!(record.artifact_path, "distributions/b.json");
+    assert_eq!(record.total_amount, 1_500);
+    assert_eq!(record.num_recipients, 3);
+    assert_eq!(record.funded_at, None);
+    assert_eq!(record.deployment, "default");
+
+    assert!(store
+        .record_on_chain("d1", "devnet", "pda", "vault")
+        .await
+        .unwrap());
+    assert!(!store
+        .record_on_chain("d2", "devnet", "pda", "vault")
+        .await
+        .unwrap());
+    assert_eq!(
+        store
+            .distribution_by_address("default", "pda")
+            .await
+            .unwrap(),
+        None
+    );
+    let funded = store
+        .distribution_by_address("devnet", "pda")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(funded.deployment, "devnet");
+    assert_eq!(funded.status, DistributionStatus::Funded);
+    assert_eq!(funded.vault_ata.as_deref(), Some("vault"));
+    assert!(funded.funded_at.is_some());
+
+    assert!(store
+        .set_status("d1", DistributionStatus::Active)
+        .await
+        .unwrap());
+    let active = store.distribution("d1").await.unwrap().unwrap();
+    assert!(active.activated_at.is_some());
+    assert_eq!(active.completed_at, None);
+
+    store
+        .upsert_distribution(&distribution("d2"))
+        .await
+        .unwrap();
+    let listed = store
+        .distributions_with_status(&[DistributionStatus::Active, DistributionStatus::Funded])
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].distribution_id, "d1");
+}
+
+#[tokio::test]
+async fn amounts_beyond_sqlite_integers_are_rejected() {
+    let (_dir, store) = store().await;
+    let mut large = distribution("d1");
+    large.total_amount = u64::MAX;
+    let err = store.upsert_distribution(&large).await.unwrap_err();
+    assert!(matches!(
+        err,
+        StoreError::InvalidColumn {
+            column: "total_amount",
+            ..
+        }
+    ));
+    assert_eq!(store.distribution("d1").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn claim_attempts() {
+    let (_dir, store) = store().await;
+    store
+        .upsert_distribution(&distribution("d1"))
+        .await
+        .unwrap();
+    let claims: Vec<NewClaim> = (0..3)
+        .map(|index| NewClaim {
+            leaf_index: index,
+            wallet: format!("wallet-{index}"),
+            amount: 500,
+        })
+        .collect();
+    assert_eq!(store.insert_claims("d1", &claims).await.unwrap(), 3);
+    assert_eq!(store.insert_claims("d1", &claims[..1]).await.unwrap(), 0);
+
+    let failed = ClaimAttempt {
+        status: ClaimStatus::Failed,
+        tx_signature: Some("sig-1".to_string()),
+        error_message: Some("blockhash expired".to_string()),
+    };
+    assert!(store.record_attempt("d1", 0, &failed).await.unwrap());
+    let confirmed = ClaimAttempt {
+        status: ClaimStatus::Confirmed,
+        tx_signature: None,
+        error_message: None,
+    };
+    assert!(store.record_attempt("d1", 1, &confirmed).await.unwrap());
+    assert!(!store.record_attempt("d1", 7, &confirmed).await.unwrap());
+
+    // One attempt allowed: the failed claim is out of retries
+    let pending = store.pending_claims("d1", 1).await.unwrap();
+    let indices: Vec<u64> = pending.iter().map(|claim| claim.leaf_index).collect();
+    assert_eq!(indices, [2]);
+    let pending = store.pending_claims("d1", 2).await.unwrap();
+    let indices: Vec<u64> = pending.iter().map(|claim| claim.leaf_index).collect();
+    assert_eq!(indices, [0, 2]);
+    assert_eq!(pending[0].tx_signature.as_deref(), Some("sig-1"));
+    assert_eq!(pending[0].attempts, 1);
+
+    assert_eq!(store.unconfirmed_claims("d1").await.unwrap(), 2);
+    let wallet = store.wallet_claims("wallet-1").await.unwrap();
+    assert_eq!(wallet.len(), 1);
+    assert_eq!(wallet[0].status, ClaimStatus::Confirmed);
+    assert!(wallet[0].confirmed_at.is_some());
+}
+
+#[tokio::test]
+async fn claims_need_their_distribution() {
+    let (_dir, store) = store().await;
+    let claim = NewClaim {
+        leaf_index: 0,
+        wallet: "wallet".to_string(),
+        amount: 1,
+    };
+    assert!(matches!(
+        store.insert_claims("missing", &[claim]).await,
+        Err(StoreError::Query(_))
+    ));
+}
+
+#[tokio::test]
+async fn reward_payouts_match_the_csv_export() {
+    let (_dir, store) = store().await;
+    exec(
+        &store,
+        "INSERT INTO reward_payouts_preview VALUES
+           ('r1', 'b', 'w1', 'mint', 0.25, 1000, 250),
+           ('r1', 'a', 'w1', 'mint', 0.25, 1000, 250),
+           ('r1', 'c', 'w1', 'mint', 0.5, 1000, 500),
+           ('r1', 'ignored', 'w1', 'mint', 0.0, 1000, 9000),
+           ('r1', 'dust', 'w1', 'mint', 0.0, 1000, 0),
+           ('r2', 'a', 'w1', 'mint-a', 0.5, 10, 5),
+           ('r2', 'b', 'w1', 'mint-b', 0.5, 10, 5)",
+    )
+    .await;
+    let ignored = vec!["ignored".to_string()];
+
+    let totals = store.reward_totals("r1", &ignored).await.unwrap();
+    assert_eq!(totals.num_recipients, 3);
+    assert_eq!(totals.total_amount, 1_000);
+    assert_eq!(totals.num_mints, 1);
+    assert_eq!(totals.mint.as_deref(), Some("mint"));
+    assert_eq!(totals.window_id.as_deref(), Some("w1"));
+
+    let payouts: Vec<_> = store
+        .reward_payouts("r1", &ignored)
+        .try_collect()
+        .await
+        .unwrap();
+    let order: Vec<(&str, u64)> = payouts
+        .iter()
+        .map(|payout| (payout.wallet.as_str(), payout.amount))
+        .collect();
+    assert_eq!(order, [("c", 500), ("a", 250), ("b", 250)]);
+
+    assert_eq!(store.reward_totals("r2", &[]).await.unwrap().num_mints, 2);
+    let none = store.reward_totals("r3", &[]).await.unwrap();
+    assert_eq!(
+        (none.num_recipients, none.total_amount, none.mint),
+        (0, 0, None)
+    );
+}
+
+#[tokio::test]
+async fn snapshot_holders_sum_token_accounts() {
+    let (_dir, store) = store().await;
+    exec(
+        &store,
+        "INSERT INTO epoch_snapshots (epoch, mint, epoch_first_slot, slot, block_time, token_accounts, holders, total_amount)
+         VALUES (700, 'mint', 302400000, 302400001, NULL, 3, 2, 1200),
+                (701, 'mint', 302832000, 302832000, 1760000000, 1, 1, 5);
+         INSERT INTO epoch_snapshot_balances VALUES
+           (700, 'mint', 'ata-1', 'alice', 100),
+           (700, 'mint', 'ata-2', 'alice', 900),
+           (700, 'mint', 'ata-3', 'bob', 200),
+           (701, 'mint', 'ata-1', 'alice', 5)",
+    )
+    .await;
+
+    let snapshot = store.epoch_snapshot(700, "mint").await.unwrap().unwrap();
+    assert_eq!(
+        (snapshot.slot, snapshot.holders, snapshot.total_amount),
+        (302400001, 2, 1200)
+    );
+    assert_eq!(snapshot.block_time, None);
+    let latest = store.latest_epoch_snapshot("mint").await.unwrap().unwrap();
+    assert_eq!((latest.epoch, latest.block_time), (701, Some(1760000000)));
+
+    let holders: Vec<_> = store
+        .snapshot_holders(700, "mint")
+        .try_collect()
+        .await
+        .unwrap();
+    let holders: Vec<(&str, u64, u64)> = holders
+        .iter()
+        .map(|holder| (holder.wallet.as_str(), holder.amount, holder.token_accounts))
+        .collect();
+    assert_eq!(holders, [("alice", 1000, 2), ("bob", 200, 1)]);
+}
+
+#[tokio::test]
+async fn events_and_open_divergences() {
+    let (_dir, store) = store().await;
+    exec(
+        &store,
+        "INSERT INTO merkle_claim_events (deployment, signature, event_index, instruction_index, slot, distribution, kind, recipient, amount, leaf_index, source)
+         VALUES ('default', 's2', 0, 0, 20, 'pda', 'claim', 'alice', 500, 0, 'geyser'),
+                ('default', 's1', 1, 1, 10, 'pda', 'claim_referral', 'bob', 250, 1, 'geyser'),
+                ('default', 's1', 0, NULL, 10, 'pda', 'pause', NULL, NULL, NULL, 'geyser'),
+                ('devnet', 's2', 0, 0, 20, 'pda', 'claim', 'alice', 500, 0, 'geyser');
+         INSERT INTO merkle_reconciliation_divergences (deployment, distribution, field, on_chain, expected, slot, alerted_at, resolved_at)
+         VALUES ('default', 'pda', 'claimed_amount', 750, 500, 30, 1750000000, NULL),
+                ('devnet', 'pda', 'claimed_amount', 500, 500, 30, NULL, 1760000000),
+                ('default', 'pda', 'num_claimed', 2, 1, 30, NULL, 1760000000)",
+    )
+    .await;
+
+    exec(
+        &store,
+        "UPDATE merkle_claim_events SET block_time = 1750000000, block_time_estimated = 1
+         WHERE deployment = 'default' AND signature = 's2'",
+    )
+    .await;
+
+    // The same PDA and signature on another deployment is a separate event
+    let events = store.distribution_events("default", "pda").await.unwrap();
+    let order: Vec<(&str, u64, ClaimEventKind)> = events
+        .iter()
+        .map(|event| (event.signature.as_str(), event.event_index, event.kind))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("s1", 0, ClaimEventKind::Pause),
+            ("s1", 1, ClaimEventKind::ClaimReferral),
+            ("s2", 0, ClaimEventKind::Claim),
+        ]
+    );
+    assert_eq!((events[0].amount, events[0].leaf_index), (None, None));
+    assert_eq!(events[0].instruction_index, None);
+    assert_eq!(events[1].instruction_index, Some(1));
+    assert_eq!(
+        (events[1].block_time, events[1].block_time_estimated),
+        (None, false)
+    );
+    assert_eq!(
+        (events[2].block_time, events[2].block_time_estimated),
+        (Some(1750000000), true)
+    );
+    assert_eq!(
+        (events[2].amount, events[2].leaf_index),
+        (Some(500), Some(0))
+    );
+
+    let alice = store.recipient_events("alice").await.unwrap();
+    assert_eq!(alice.len(), 2);
+
+    let open = store.open_divergences().await.unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].deployment, "default");
+    assert_eq!(open[0].field, DivergenceField::ClaimedAmount);
+    assert_eq!((open[0].on_chain, open[0].expected), (750, 500));
+    assert_eq!(open[0].alerted_at, Some(1750000000));
+}
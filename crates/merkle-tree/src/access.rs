@@ -0,0 +1,380 @@
+//! API keys, rate limits and usage accounting for the HTTP APIs
+//!
+//! `Access::layer` wraps a router in middleware that identifies the caller
+//! and applies a token-bucket rate limit per caller:
+//!
+//! - A request with `X-API-Key: <key>` (or `Authorization: Bearer <key>`)
+//!   is limited to that key's `requestsPerMinute`; an unknown key is a 401
+//! - A request without a key is anonymous and limited per client IP to
+//!   `anonymous_per_minute` (0 requires a key), which covers the claim UI
+//!
+//! Over the limit is a 429 with `Retry-After`; error bodies are
+//! `{"error":"…"}` as in `server`. Requests are counted per caller and hour
+//! (anonymous callers together) for billing; `Access::drain_usage` hands the
+//! counts to whoever persists them (`record_usage` with `postgres`).
+//!
+//! Keys are only stored as SHA-256 hex digests. The key file is a JSON array:
+//!
+//! `[{"name":"partner-a","keySha256":"9f86…","requestsPerMinute":600}]`
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::hash::to_hex;
+
+/// Header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Usage key the anonymous callers are counted under
+pub const ANONYMOUS: &str = "anonymous";
+
+#[derive(Debug, Error)]
+pub enum AccessConfigError {
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("{0}: {1}")]
+    Json(PathBuf, serde_json::Error),
+    #[error("{0}: key {1} is listed twice")]
+    DuplicateKey(PathBuf, String),
+    #[error("{0}: key {1} has a requestsPerMinute of 0")]
+    ZeroRate(PathBuf, String),
+    #[error("{0}: key {1} has a malformed keySha256")]
+    MalformedDigest(PathBuf, String),
+}
+
+/// One entry of the key file
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKey {
+    /// Billing name of the key holder
+    pub name: String,
+    /// Hex SHA-256 of the key
+    pub key_sha256: String,
+    pub requests_per_minute: u32,
+}
+
+impl ApiKey {
+    /// Read a key file
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Self>, AccessConfigError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|e| AccessConfigError::Io(path.into(), e))?;
+        let keys: Vec<Self> =
+            serde_json::from_str(&json).map_err(|e| AccessConfigError::Json(path.into(), e))?;
+
+        let mut digests = HashSet::new();
+        for key in &keys {
+            if key.requests_per_minute == 0 {
+                return Err(AccessConfigError::ZeroRate(path.into(), key.name.clone()));
+            }
+            if key.key_sha256.len() != 64 || !key.key_sha256.bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                return Err(AccessConfigError::MalformedDigest(
+                    path.into(),
+                    key.name.clone(),
+                ));
+            }
+            if !digests.insert(key.key_sha256.to_ascii_lowercase()) {
+                return Err(AccessConfigError::DuplicateKey(
+                    path.into(),
+                    key.name.clone(),
+                ));
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// Hex SHA-256 of an API key, as stored in the key file
+pub fn key_digest(key: &str) -> String {
+    to_hex(&Sha256::digest(key.as_bytes()))
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessConfig {
+    pub keys: Vec<ApiKey>,
+    /// Per-IP limit for requests without a key (0: a key is required)
+    pub anonymous_per_minute: u32,
+    /// Take the client IP from the first `X-Forwarded-For` hop (behind a
+    /// trusted proxy only: clients can set the header themselves)
+    pub trust_forwarded_for: bool,
+}
+
+/// Requests counted for one caller in one hour
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Key name, or `ANONYMOUS`
+    pub key: String,
+    /// Unix seconds of the start of the hour
+    pub period_start: i64,
+    /// Requests let through
+    pub requests: u64,
+    /// Requests rejected with a 429
+    pub rate_limited: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Caller {
+    Key(String),
+    Anonymous(IpAddr),
+}
+
+/// Token bucket holding up to a minute's worth of requests
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(per_minute: u32, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(per_minute),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, per_minute: u32, now: Instant) {
+        let capacity = f64::from(per_minute);
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * capacity / 60.0).min(capacity);
+        self.updated = now;
+    }
+
+    /// Take a token, or return the seconds until one is available
+    fn take(&mut self, per_minute: u32, now: Instant) -> Result<(), u64> {
+        self.refill(per_minute, now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        let wait = (1.0 - self.tokens) * 60.0 / f64::from(per_minute);
+        Err(wait.ceil() as u64)
+    }
+}
+
+/// Shared state of the access middleware
+pub struct Access {
+    /// Key digest -> (name, requests per minute)
+    keys: HashMap<String, (String, u32)>,
+    anonymous_per_minute: u32,
+    trust_forwarded_for: bool,
+    buckets: Mutex<HashMap<Caller, Bucket>>,
+    usage: Mutex<HashMap<(String, i64), Usage>>,
+}
+
+impl Access {
+    pub fn new(config: AccessConfig) -> Self {
+        Self {
+            keys: config
+                .keys
+                .into_iter()
+                .map(|k| {
+                    (
+                        k.key_sha256.to_ascii_lowercase(),
+                        (k.name, k.requests_per_minute),
+                    )
+                })
+                .collect(),
+            anonymous_per_minute: config.anonymous_per_minute,
+            trust_forwarded_for: config.trust_forwarded_for,
+            buckets: Mutex::default(),
+            usage: Mutex::default(),
+        }
+    }
+
+    /// Put `router` behind the access middleware
+    pub fn layer(self: &Arc<Self>, router: Router) -> Router {
+        router.layer(middleware::from_fn_with_state(self.clone(), check))
+    }
+
+    /// Take the usage counted since the last call
+    ///
+    /// Also forgets anonymous callers whose bucket has refilled, so the
+    /// bucket map doesn't grow with every IP ever seen.
+    pub fn drain_usage(&self) -> Vec<Usage> {
+        let now = Instant::now();
+        let anonymous = self.anonymous_per_minute;
+        self.buckets
+            .lock()
+            .unwrap()
+            .retain(|caller, bucket| match caller {
+                Caller::Key(_) => true,
+                Caller::Anonymous(_) => {
+                    bucket.refill(anonymous, now);
+                    bucket.tokens < f64::from(anonymous)
+                }
+            });
+
+        let mut usage: Vec<Usage> = self.usage.lock().unwrap().drain().map(|(_, u)| u).collect();
+        usage.sort_by(|a, b| (a.period_start, &a.key).cmp(&(b.period_start, &b.key)));
+        usage
+    }
+
+    /// Who is calling: a known key, an anonymous IP, or a rejection
+    fn identify(&self, headers: &HeaderMap, peer: IpAddr) -> Result<(Caller, u32), AccessError> {
+        let key = headers
+            .get(API_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| {
+                headers
+                    .get(header::AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("Bearer "))
+            });
+
+        if let Some(key) = key {
+            let (name, per_minute) = self
+                .keys
+                .get(&key_digest(key.trim()))
+                .ok_or(AccessError::InvalidKey)?;
+            return Ok((Caller::Key(name.clone()), *per_minute));
+        }
+
+        if self.anonymous_per_minute == 0 {
+            return Err(AccessError::KeyRequired);
+        }
+        let ip = self
+            .trust_forwarded_for
+            .then(|| forwarded_for(headers))
+            .flatten()
+            .unwrap_or(peer);
+        Ok((Caller::Anonymous(ip), self.anonymous_per_minute))
+    }
+
+    fn admit(&self, caller: &Caller, per_minute: u32) -> Result<(), AccessError> {
+        let now = Instant::now();
+        let result = self
+            .buckets
+            .lock()
+            .unwrap()
+            .entry(caller.clone())
+            .or_insert_with(|| Bucket::full(per_minute, now))
+            .take(per_minute, now);
+
+        let key = match caller {
+            Caller::Key(name) => name.clone(),
+            Caller::Anonymous(_) => ANONYMOUS.to_string(),
+        };
+        let period_start = current_hour();
+        let mut usage = self.usage.lock().unwrap();
+        let entry = usage
+            .entry((key.clone(), period_start))
+            .or_insert_with(|| Usage {
+                key,
+                period_start,
+                ..Usage::default()
+            });
+        match result {
+            Ok(()) => {
+                entry.requests += 1;
+                Ok(())
+            }
+            Err(retry_after) => {
+                entry.rate_limited += 1;
+                Err(AccessError::RateLimited { retry_after })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum AccessError {
+    #[error("invalid API key")]
+    InvalidKey,
+    #[error("an API key is required")]
+    KeyRequired,
+    #[error("rate limit exceeded")]
+    RateLimited { retry_after: u64 },
+}
+
+impl IntoResponse for AccessError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AccessError::InvalidKey | AccessError::KeyRequired => StatusCode::UNAUTHORIZED,
+            AccessError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        };
+        let body = serde_json::json!({ "error": self.to_string() });
+        let mut response = (status, Json(body)).into_response();
+        if let AccessError::RateLimited { retry_after } = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+        }
+        response
+    }
+}
+
+async fn check(State(access): State<Arc<Access>>, request: Request, next: Next) -> Response {
+    // Without connect info (e.g. in tests) every anonymous caller shares one bucket
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    let admitted = access
+        .identify(request.headers(), peer)
+        .and_then(|(caller, per_minute)| access.admit(&caller, per_minute));
+    match admitted {
+        Ok(()) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get("x-forwarded-for")?
+        .to_str()
+        .ok()?
+        .split(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn current_hour() -> i64 {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    secs - secs % 3600
+}
+
+/// Add drained usage to `api_usage` (one row per key and hour)
+#[cfg(feature = "postgres")]
+pub async fn record_usage(pool: &sqlx::PgPool, usage: &[Usage]) -> Result<(), sqlx::Error> {
+    if usage.is_empty() {
+        return Ok(());
+    }
+    let mut tx = pool.begin().await?;
+    for u in usage {
+        sqlx::query(
+            "INSERT INTO api_usage (key_name, period_start, requests, rate_limited)
+             VALUES ($1, to_timestamp($2), $3, $4)
+             ON CONFLICT (key_name, period_start) DO UPDATE
+             SET requests = api_usage.requests + EXCLUDED.requests,
+                 rate_limited = api_usage.rate_limited + EXCLUDED.rate_limited,
+                 updated_at = NOW()",
+        )
+        .bind(&u.key)
+        .bind(u.period_start as f64)
+        .bind(u.requests as i64)
+        .bind(u.rate_limited as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await
+}
@@ -0,0 +1,83 @@
+//! Serve the GraphQL API over the indexer database
+//!
+//! Usage: graphql-server
+//!
+//! Connects to DATABASE_URL and listens on GRAPHQL_API_ADDR (default
+//! `0.0.0.0:8081`). See `merkle_tree::graphql` for the schema.
+//!
+//! `stats` results are cached for STATS_CACHE_TTL_SECS (default 30) and
+//! busted when `merkle_distributions` changes. With the `redis` feature,
+//! REDIS_URL shares the cache between replicas.
+
+use std::env;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use merkle_tree::cache::ResponseCache;
+use merkle_tree::graphql::{cached_schema, router, watch_distributions};
+use sqlx::postgres::PgPool;
+
+const DEFAULT_ADDR: &str = "0.0.0.0:8081";
+const DEFAULT_STATS_TTL_SECS: u64 = 30;
+const STATS_CACHE_CAPACITY: u64 = 10_000;
+const DISTRIBUTION_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Ok(database_url) = env::var("DATABASE_URL") else {
+        eprintln!("❌ Missing DATABASE_URL environment variable");
+        return ExitCode::FAILURE;
+    };
+    let addr = env::var("GRAPHQL_API_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+
+    let pool = match PgPool::connect(&database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("❌ Failed to connect to the database: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let ttl = match env::var("STATS_CACHE_TTL_SECS") {
+        Ok(value) => match value.parse() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                eprintln!("❌ Invalid STATS_CACHE_TTL_SECS: {value}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Err(_) => Duration::from_secs(DEFAULT_STATS_TTL_SECS),
+    };
+    let cache = ResponseCache::new("stats", STATS_CACHE_CAPACITY, ttl);
+    #[cfg(feature = "redis")]
+    let cache = match env::var("REDIS_URL") {
+        Ok(url) => match cache.with_redis(&url).await {
+            Ok(cache) => cache,
+            Err(e) => {
+                eprintln!("❌ Failed to connect to Redis: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Err(_) => cache,
+    };
+    tokio::spawn(watch_distributions(
+        pool.clone(),
+        cache.clone(),
+        DISTRIBUTION_POLL_INTERVAL,
+    ));
+
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("❌ Failed to bind {addr}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("✅ Serving GraphQL on {addr}/graphql");
+
+    if let Err(e) = axum::serve(listener, router(cached_schema(pool, cache))).await {
+        eprintln!("❌ {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
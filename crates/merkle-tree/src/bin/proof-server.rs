@@ -0,0 +1,180 @@
+//! Serve claim proofs over HTTP from a directory of tree files
+//!
+//! Usage: proof-server <tree-dir>
+//!
+//! Maps every `*.tree` file in `tree-dir` and listens on PROOF_API_ADDR
+//! (default `0.0.0.0:8080`). See `merkle_tree::server` for the routes and
+//! `merkle_tree::access` for keys and rate limits:
+//!
+//! - PROOF_API_KEYS: key file (without it every caller is anonymous)
+//! - PROOF_API_ANONYMOUS_RPM: per-IP limit without a key (default 60, 0 requires a key)
+//! - PROOF_API_TRUST_FORWARDED_FOR: `true` behind a proxy that sets X-Forwarded-For
+//!
+//! Usage counts are flushed every minute: to `api_usage` when built with
+//! `postgres` and DATABASE_URL is set, to stderr otherwise.
+//!
+//! Proof responses are cached for PROOF_CACHE_TTL_SECS (default 300, up to
+//! PROOF_CACHE_CAPACITY entries, default 100000). With the `redis` feature,
+//! REDIS_URL shares the cache between replicas.
+
+use std::env;
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use merkle_tree::access::{Access, AccessConfig, ApiKey, Usage};
+use merkle_tree::cache::ResponseCache;
+use merkle_tree::server::{cached_router, ProofStore};
+
+const DEFAULT_ADDR: &str = "0.0.0.0:8080";
+const DEFAULT_ANONYMOUS_RPM: u32 = 60;
+const DEFAULT_CACHE_TTL_SECS: u64 = 300;
+const DEFAULT_CACHE_CAPACITY: u64 = 100_000;
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [tree_dir] = args.as_slice() else {
+        eprintln!("Usage: proof-server <tree-dir>");
+        return ExitCode::FAILURE;
+    };
+    let addr = env::var("PROOF_API_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+
+    let store = match ProofStore::open_dir(tree_dir) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if store.is_empty() {
+        eprintln!("⚠️  No .tree files in {tree_dir}");
+    }
+
+    let access = match access_config() {
+        Ok(config) => Arc::new(Access::new(config)),
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    tokio::spawn(flush_usage(access.clone()));
+
+    let cache = match cache_settings() {
+        Ok((capacity, ttl)) => ResponseCache::new("proof", capacity, ttl),
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    #[cfg(feature = "redis")]
+    let cache = match env::var("REDIS_URL") {
+        Ok(url) => match cache.with_redis(&url).await {
+            Ok(cache) => cache,
+            Err(e) => {
+                eprintln!("❌ Failed to connect to Redis: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Err(_) => cache,
+    };
+
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("❌ Failed to bind {addr}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("✅ Serving {} distribution(s) on {addr}", store.len());
+
+    let app = access.layer(cached_router(Arc::new(store), cache));
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    if let Err(e) = axum::serve(listener, service).await {
+        eprintln!("❌ {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn access_config() -> Result<AccessConfig, String> {
+    let keys = match env::var("PROOF_API_KEYS") {
+        Ok(path) => ApiKey::read_file(path).map_err(|e| e.to_string())?,
+        Err(_) => Vec::new(),
+    };
+    let anonymous_per_minute = match env::var("PROOF_API_ANONYMOUS_RPM") {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid PROOF_API_ANONYMOUS_RPM: {value}"))?,
+        Err(_) => DEFAULT_ANONYMOUS_RPM,
+    };
+    eprintln!(
+        "API keys: {}, anonymous limit: {anonymous_per_minute}/min per IP",
+        keys.len()
+    );
+
+    Ok(AccessConfig {
+        keys,
+        anonymous_per_minute,
+        trust_forwarded_for: env::var("PROOF_API_TRUST_FORWARDED_FOR").is_ok_and(|v| v == "true"),
+    })
+}
+
+fn cache_settings() -> Result<(u64, Duration), String> {
+    let capacity = match env::var("PROOF_CACHE_CAPACITY") {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid PROOF_CACHE_CAPACITY: {value}"))?,
+        Err(_) => DEFAULT_CACHE_CAPACITY,
+    };
+    let ttl = match env::var("PROOF_CACHE_TTL_SECS") {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid PROOF_CACHE_TTL_SECS: {value}"))?,
+        Err(_) => DEFAULT_CACHE_TTL_SECS,
+    };
+    Ok((capacity, Duration::from_secs(ttl)))
+}
+
+/// Persist usage every minute; counts that fail to persist are retried with the next flush
+async fn flush_usage(access: Arc<Access>) {
+    #[cfg(feature = "postgres")]
+    let pool = match env::var("DATABASE_URL") {
+        Ok(url) => match sqlx::PgPool::connect(&url).await {
+            Ok(pool) => Some(pool),
+            Err(e) => {
+                eprintln!("⚠️  Usage not persisted, failed to connect to the database: {e}");
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
+    let mut pending: Vec<Usage> = Vec::new();
+    let mut interval = tokio::time::interval(USAGE_FLUSH_INTERVAL);
+    loop {
+        interval.tick().await;
+        pending.extend(access.drain_usage());
+        if pending.is_empty() {
+            continue;
+        }
+
+        #[cfg(feature = "postgres")]
+        if let Some(pool) = &pool {
+            match merkle_tree::access::record_usage(pool, &pending).await {
+                Ok(()) => pending.clear(),
+                Err(e) => eprintln!("⚠️  Failed to record usage (retrying): {e}"),
+            }
+            continue;
+        }
+
+        for u in pending.drain(..) {
+            eprintln!(
+                "usage key={} period_start={} requests={} rate_limited={}",
+                u.key, u.period_start, u.requests, u.rate_limited
+            );
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Sign a build summary's root with the operations key
+//!
+//! Usage: sign-root <summary.json | ->
+//!
+//! Reads the keypair path from OPS_KEYPAIR and writes the detached signature
+//! JSON to stdout, e.g. `sign-root summary.json > summary.sig.json`.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use merkle_tree::signing::{read_keypair_file, RootStatement};
+use merkle_tree::BuildSummary;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [summary_path] = args.as_slice() else {
+        eprintln!("Usage: sign-root <summary.json | ->");
+        return ExitCode::FAILURE;
+    };
+    let Ok(keypair_path) = env::var("OPS_KEYPAIR") else {
+        eprintln!("❌ Missing OPS_KEYPAIR environment variable");
+        return ExitCode::FAILURE;
+    };
+
+    let reader: Box<dyn Read> = if summary_path == "-" {
+        Box::new(io::stdin())
+    } else {
+        match File::open(summary_path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("❌ {summary_path}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+    let summary = match BuildSummary::read_json(reader) {
+        Ok(summary) => summary,
+        Err(e) => {
+            eprintln!("❌ Invalid summary: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let signed = read_keypair_file(&keypair_path).and_then(|key| {
+        RootStatement::from_summary(&summary).map(|statement| statement.sign(&key))
+    });
+    let signature = match signed {
+        Ok(signature) => signature,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Err(e) = signature.write_json(io::stdout().lock()) {
+        eprintln!("❌ {e}");
+        return ExitCode::FAILURE;
+    }
+    println!();
+    eprintln!(
+        "✅ Signed root {} as {}",
+        signature.merkle_root, signature.signer
+    );
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,40 @@
+//! Verify a tree file against its on-chain Distribution
+//!
+//! Usage: verify-tree <tree-file> <program-id>
+//!
+//! Reads the RPC endpoint from SOLANA_RPC_URL.
+
+use std::env;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use merkle_tree::verify::verify_on_chain;
+use merkle_tree::Pubkey;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [tree_path, program_id] = args.as_slice() else {
+        eprintln!("Usage: verify-tree <tree-file> <program-id>");
+        return ExitCode::FAILURE;
+    };
+
+    let Ok(program_id) = Pubkey::from_str(program_id) else {
+        eprintln!("❌ Invalid program id: {program_id}");
+        return ExitCode::FAILURE;
+    };
+    let Ok(rpc_url) = env::var("SOLANA_RPC_URL") else {
+        eprintln!("❌ Missing SOLANA_RPC_URL environment variable");
+        return ExitCode::FAILURE;
+    };
+
+    match verify_on_chain(&rpc_url, &program_id, tree_path) {
+        Ok(()) => {
+            println!("✅ {tree_path} matches on-chain state");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("❌ {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! Build options and reporting
+//!
+//! `MerkleTree::new` hashes allocations exactly as given. `MerkleTree::build`
+//! first normalises them according to `BuildOptions` and reports what it did.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use solana_pubkey::Pubkey;
+
+use crate::error::BuildError;
+use crate::hash::{HashKind, LeafSchema};
+use crate::tree::{Allocation, MerkleTree};
+
+/// How to handle a recipient listed more than once
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Fail the build
+    #[default]
+    Error,
+    /// Sum the amounts into the recipient's first leaf
+    Merge,
+    /// Keep every entry as its own indexed leaf
+    KeepSeparate,
+}
+
+/// Leaf order of the built tree
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LeafOrder {
+    /// Sort by recipient, then amount, then unlock/expiry, so the same
+    /// allocation set always yields the same root
+    #[default]
+    Canonical,
+    /// Keep input order (legacy trees whose indices are already published)
+    Preserve,
+}
+
+/// How to handle an off-curve recipient (e.g. a program-derived address)
+///
+/// No private key exists for an off-curve address, so its claim can never be
+/// signed for; a relayer could still pay it, into an account nobody
+/// controls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OffCurvePolicy {
+    /// Fail the build
+    #[default]
+    Error,
+    /// Keep the allocation and record it in `BuildReport::off_curve`
+    Warn,
+    /// Drop the allocation and record it in `BuildReport::excluded`
+    Exclude,
+}
+
+/// What to do with allocations below a minimum amount (dust)
+///
+/// Paying out less than the rent for the recipient's token account costs
+/// more than it distributes. The threshold applies to a recipient's leaf
+/// after duplicates are resolved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DustPolicy {
+    /// Keep every allocation
+    #[default]
+    Keep,
+    /// Drop allocations below `min_amount`; only their total is reported
+    Drop { min_amount: u64 },
+    /// Drop allocations below `min_amount` and return them in
+    /// `BuildReport::rolled_over`, to be added to the next epoch's input
+    RollOver { min_amount: u64 },
+}
+
+/// Where a split share is paid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitTarget {
+    /// The allocation's own recipient (lets a global rule keep a share)
+    Holder,
+    /// A fixed address, e.g. a custody wallet
+    Address(Pubkey),
+}
+
+/// `weight` parts of a split allocation, paid to `target`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SplitShare {
+    pub target: SplitTarget,
+    pub weight: u32,
+}
+
+/// Rules expanding one allocation into several weighted leaves
+///
+/// A holder's entry in `holders` takes precedence over `global`; holders
+/// covered by neither keep a single leaf. Amounts are divided by largest
+/// remainder: each share gets `amount * weight / total_weight` rounded down,
+/// and the units left over go one each to the shares with the largest
+/// remainders (earlier shares first on ties), so the parts always sum to
+/// the original amount. Shares that round to zero get no leaf.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitRules {
+    /// Rule for every holder (empty = none)
+    pub global: Vec<SplitShare>,
+    /// Per-holder rules
+    pub holders: HashMap<Pubkey, Vec<SplitShare>>,
+}
+
+impl SplitRules {
+    /// No rules at all
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.holders.is_empty()
+    }
+
+    /// Rule applying to `holder`, if any
+    pub fn rule_for(&self, holder: &Pubkey) -> Option<&[SplitShare]> {
+        match self.holders.get(holder) {
+            Some(rule) => Some(rule),
+            None if !self.global.is_empty() => Some(&self.global),
+            None => None,
+        }
+    }
+
+    /// Split `allocation` by its rule (a single leaf if none applies)
+    ///
+    /// The parts keep the allocation's unlock/expiry schedule.
+    pub fn split(&self, allocation: &Allocation) -> Result<Vec<Allocation>, BuildError> {
+        let Some(rule) = self.rule_for(&allocation.recipient) else {
+            return Ok(vec![allocation.clone()]);
+        };
+        let total_weight: u128 = rule.iter().map(|share| u128::from(share.weight)).sum();
+        if total_weight == 0 {
+            return Err(BuildError::InvalidSplitRule {
+                holder: allocation.recipient,
+            });
+        }
+
+        let amount = u128::from(allocation.amount);
+        let mut parts: Vec<(u128, u128)> = rule
+            .iter()
+            .map(|share| {
+                let scaled = amount * u128::from(share.weight);
+                (scaled / total_weight, scaled % total_weight)
+            })
+            .collect();
+
+        let mut leftover = amount - parts.iter().map(|&(part, _)| part).sum::<u128>();
+        let mut by_remainder: Vec<usize> = (0..parts.len()).collect();
+        by_remainder.sort_by(|&a, &b| parts[b].1.cmp(&parts[a].1).then(a.cmp(&b)));
+        for i in by_remainder {
+            if leftover == 0 {
+                break;
+            }
+            parts[i].0 += 1;
+            leftover -= 1;
+        }
+
+        let split_total: u128 = parts.iter().map(|&(part, _)| part).sum();
+        if split_total != amount {
+            return Err(BuildError::SplitNotConserved {
+                holder: allocation.recipient,
+                amount: allocation.amount,
+                split_total,
+            });
+        }
+
+        Ok(rule
+            .iter()
+            .zip(parts)
+            .filter(|&(_, (part, _))| part > 0)
+            .map(|(share, (part, _))| Allocation {
+                recipient: match share.target {
+                    SplitTarget::Holder => allocation.recipient,
+                    SplitTarget::Address(address) => address,
+                },
+                // Each part is at most `amount`, which came from a u64
+                amount: part as u64,
+                unlock_at: allocation.unlock_at,
+                expires_at: allocation.expires_at,
+            })
+            .collect())
+    }
+}
+
+/// Options for `MerkleTree::build`
+#[derive(Clone, Debug, Default)]
+pub struct BuildOptions {
+    pub duplicates: DuplicatePolicy,
+    pub order: LeafOrder,
+    pub hash_kind: HashKind,
+    pub schema: LeafSchema,
+    pub off_curve: OffCurvePolicy,
+    pub dust: DustPolicy,
+    /// Weighted splits, applied to the input before any other option
+    pub splits: SplitRules,
+    /// Funded budget: fail with `BuildError::OverBudget` if the leaves sum
+    /// to more
+    pub max_total: Option<u64>,
+}
+
+/// Largest allocations listed in a `BudgetOvershoot`
+pub const OVERSHOOT_TOP_N: usize = 10;
+
+/// Why a build exceeded `BuildOptions::max_total`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BudgetOvershoot {
+    pub max_total: u64,
+    /// Sum of the leaves that would have been built
+    pub total: u128,
+    pub num_allocations: usize,
+    /// Largest leaves, descending (up to `OVERSHOOT_TOP_N`)
+    pub largest: Vec<Allocation>,
+}
+
+impl BudgetOvershoot {
+    /// Amount above the cap
+    pub fn overshoot(&self) -> u128 {
+        self.total - u128::from(self.max_total)
+    }
+}
+
+impl fmt::Display for BudgetOvershoot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "total {} of {} allocations exceeds the budget of {} by {} ({:.2}%)",
+            self.total,
+            self.num_allocations,
+            self.max_total,
+            self.overshoot(),
+            self.overshoot() as f64 * 100.0 / self.max_total.max(1) as f64
+        )?;
+        write!(f, "largest allocations:")?;
+        for allocation in &self.largest {
+            write!(f, "\n  {} {}", allocation.recipient, allocation.amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// Input allocation flagged by `OffCurvePolicy`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlaggedAllocation {
+    /// Position in the input
+    pub index: usize,
+    pub allocation: Allocation,
+}
+
+/// What `MerkleTree::build` did to the input
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildReport {
+    pub duplicate_policy: DuplicatePolicy,
+    pub order: LeafOrder,
+    /// Allocations read from the input
+    pub input_allocations: usize,
+    /// Recipients listed more than once
+    pub duplicate_recipients: usize,
+    /// Entries folded into an earlier leaf by `DuplicatePolicy::Merge`
+    pub merged_allocations: usize,
+    pub off_curve_policy: OffCurvePolicy,
+    /// Off-curve allocations kept by `OffCurvePolicy::Warn`
+    pub off_curve: Vec<FlaggedAllocation>,
+    /// Off-curve allocations dropped by `OffCurvePolicy::Exclude`
+    pub excluded: Vec<FlaggedAllocation>,
+    pub dust_policy: DustPolicy,
+    /// Allocations removed as dust
+    pub dust_allocations: usize,
+    /// Their total, to carry into the next epoch
+    pub dust_amount: u128,
+    /// The removed allocations under `DustPolicy::RollOver`
+    pub rolled_over: Vec<Allocation>,
+    /// Input allocations expanded by `SplitRules`
+    pub split_allocations: usize,
+    /// Leaves they were expanded into
+    pub split_leaves: usize,
+}
+
+impl MerkleTree {
+    /// Build a tree after applying `options` to the allocations
+    pub fn build(
+        distribution_id: [u8; 32],
+        allocations: Vec<Allocation>,
+        options: &BuildOptions,
+    ) -> Result<(Self, BuildReport), BuildError> {
+        let mut report = BuildReport {
+            duplicate_policy: options.duplicates,
+            order: options.order,
+            input_allocations: allocations.len(),
+            duplicate_recipients: 0,
+            merged_allocations: 0,
+            off_curve_policy: options.off_curve,
+            off_curve: Vec::new(),
+            excluded: Vec::new(),
+            dust_policy: options.dust,
+            dust_allocations: 0,
+            dust_amount: 0,
+            rolled_over: Vec::new(),
+            split_allocations: 0,
+            split_leaves: 0,
+        };
+
+        let allocations = apply_splits(allocations, &options.splits, &mut report)?;
+        let allocations = apply_off_curve_policy(allocations, options.off_curve, &mut report)?;
+        let allocations = apply_duplicate_policy(allocations, options.duplicates, &mut report)?;
+        let mut allocations = apply_dust_policy(allocations, options.dust, &mut report);
+        if let Some(max_total) = options.max_total {
+            check_budget(&allocations, max_total)?;
+        }
+        if options.order == LeafOrder::Canonical {
+            sort_canonical(&mut allocations);
+        }
+        let tree = Self::with_hashing(
+            distribution_id,
+            &allocations,
+            options.hash_kind,
+            options.schema,
+        )?;
+
+        Ok((tree, report))
+    }
+}
+
+/// Sort into canonical leaf order
+///
+/// Duplicates are resolved before sorting, so entries that still compare
+/// equal are identical and their relative order does not affect the root.
+pub fn sort_canonical(allocations: &mut [Allocation]) {
+    allocations.sort_by(|a, b| {
+        a.recipient
+            .as_ref()
+            .cmp(b.recipient.as_ref())
+            .then(a.amount.cmp(&b.amount))
+            .then(a.unlock_at.cmp(&b.unlock_at))
+            .then(a.expires_at.cmp(&b.expires_at))
+    });
+}
+
+/// Expand split holders into their parts, tagging each leaf with the index
+/// of the input allocation it came from
+///
+/// The split leaves go through every later option: custody addresses are
+/// checked for being on-curve, and a custody address shared by several
+/// holders is a duplicate recipient (use `DuplicatePolicy::Merge` to pay it
+/// one leaf).
+fn apply_splits(
+    allocations: Vec<Allocation>,
+    rules: &SplitRules,
+    report: &mut BuildReport,
+) -> Result<Vec<(usize, Allocation)>, BuildError> {
+    if rules.is_empty() {
+        return Ok(allocations.into_iter().enumerate().collect());
+    }
+
+    let mut output = Vec::with_capacity(allocations.len());
+    for (index, allocation) in allocations.into_iter().enumerate() {
+        if rules.rule_for(&allocation.recipient).is_none() {
+            output.push((index, allocation));
+            continue;
+        }
+
+        let parts = rules.split(&allocation)?;
+        report.split_allocations += 1;
+        report.split_leaves += parts.len();
+        output.extend(parts.into_iter().map(|part| (index, part)));
+    }
+    Ok(output)
+}
+
+/// Check recipients are on the ed25519 curve
+fn apply_off_curve_policy(
+    allocations: Vec<(usize, Allocation)>,
+    policy: OffCurvePolicy,
+    report: &mut BuildReport,
+) -> Result<Vec<(usize, Allocation)>, BuildError> {
+    let mut output = Vec::with_capacity(allocations.len());
+
+    for (index, allocation) in allocations {
+        if allocation.recipient.is_on_curve() {
+            output.push((index, allocation));
+            continue;
+        }
+
+        match policy {
+            OffCurvePolicy::Error => {
+                return Err(BuildError::OffCurveRecipient {
+                    recipient: allocation.recipient,
+                    index,
+                });
+            }
+            OffCurvePolicy::Warn => {
+                report.off_curve.push(FlaggedAllocation {
+                    index,
+                    allocation: allocation.clone(),
+                });
+                output.push((index, allocation));
+            }
+            OffCurvePolicy::Exclude => report
+                .excluded
+                .push(FlaggedAllocation { index, allocation }),
+        }
+    }
+
+    Ok(output)
+}
+
+fn apply_duplicate_policy(
+    allocations: Vec<(usize, Allocation)>,
+    policy: DuplicatePolicy,
+    report: &mut BuildReport,
+) -> Result<Vec<Allocation>, BuildError> {
+    let mut first_index: HashMap<Pubkey, usize> = HashMap::with_capacity(allocations.len());
+    let mut occurrences: HashMap<Pubkey, usize> = HashMap::new();
+    let mut output: Vec<Allocation> = Vec::with_capacity(allocations.len());
+
+    for (index, allocation) in allocations {
+        let Some(&first) = first_index.get(&allocation.recipient) else {
+            first_index.insert(allocation.recipient, output.len());
+            output.push(allocation);
+            continue;
+        };
+
+        *occurrences.entry(allocation.recipient).or_insert(1) += 1;
+
+        match policy {
+            DuplicatePolicy::Error => {
+                return Err(BuildError::DuplicateRecipient {
+                    recipient: allocation.recipient,
+                    index,
+                });
+            }
+            DuplicatePolicy::Merge => {
+                let existing = &mut output[first];
+                if existing.unlock_at != allocation.unlock_at
+                    || existing.expires_at != allocation.expires_at
+                {
+                    return Err(BuildError::ConflictingDuplicate {
+                        recipient: allocation.recipient,
+                        index,
+                    });
+                }
+                existing.amount = existing.amount.checked_add(allocation.amount).ok_or(
+                    BuildError::AmountOverflow {
+                        recipient: allocation.recipient,
+                    },
+                )?;
+                report.merged_allocations += 1;
+            }
+            DuplicatePolicy::KeepSeparate => output.push(allocation),
+        }
+    }
+
+    report.duplicate_recipients = occurrences.len();
+    Ok(output)
+}
+
+fn check_budget(allocations: &[Allocation], max_total: u64) -> Result<(), BuildError> {
+    let total: u128 = allocations.iter().map(|a| u128::from(a.amount)).sum();
+    if total <= u128::from(max_total) {
+        return Ok(());
+    }
+
+    let mut largest = allocations.to_vec();
+    largest.sort_by_key(|a| std::cmp::Reverse(a.amount));
+    largest.truncate(OVERSHOOT_TOP_N);
+
+    Err(BuildError::OverBudget(Box::new(BudgetOvershoot {
+        max_total,
+        total,
+        num_allocations: allocations.len(),
+        largest,
+    })))
+}
+
+fn apply_dust_policy(
+    allocations: Vec<Allocation>,
+    policy: DustPolicy,
+    report: &mut BuildReport,
+) -> Vec<Allocation> {
+    let (min_amount, roll_over) = match policy {
+        DustPolicy::Keep => return allocations,
+        DustPolicy::Drop { min_amount } => (min_amount, false),
+        DustPolicy::RollOver { min_amount } => (min_amount, true),
+    };
+
+    let (kept, dust): (Vec<_>, Vec<_>) = allocations
+        .into_iter()
+        .partition(|allocation| allocation.amount >= min_amount);
+
+    report.dust_allocations = dust.len();
+    report.dust_amount = dust.iter().map(|a| u128::from(a.amount)).sum();
+    if roll_over {
+        report.rolled_over = dust;
+    }
+    kept
+}
@@ -0,0 +1,212 @@
+//! Compressed bulk proof export
+//!
+//! Writing one full proof per recipient repeats the upper levels of the tree
+//! millions of times. A proof bundle stores each internal node once, plus
+//! the allocation records; leaf hashes are recomputed from the records and
+//! proofs are reassembled on read. `src/merkle/bundle.ts` decodes the same
+//! format in the SDK.
+//!
+//! All integers are little-endian.
+//!
+//! | Field | Size | Notes |
+//! |-------|------|-------|
+//! | magic | 8 | `b"L33PRFB\0"` |
+//! | version | 2 | `BUNDLE_VERSION` |
+//! | depth | 1 | |
+//! | leaf_count | 8 | |
+//! | distribution_id | 32 | |
+//! | root | 32 | |
+//! | records | 40 × leaf_count | `recipient (32) \|\| amount (8)` |
+//! | nodes | 32 each | Levels `1..depth` (leaves and root omitted) |
+
+use std::io::{Read, Write};
+
+use solana_pubkey::Pubkey;
+
+use crate::format::{depth_for, read_array, FormatError};
+use crate::hash::{compute_leaf, HashKind, LeafSchema, MAX_PROOF_LEN};
+use crate::tree::{ClaimProof, MerkleTree};
+
+/// Bundle magic
+pub const BUNDLE_MAGIC: &[u8; 8] = b"L33PRFB\0";
+
+/// Current bundle version
+pub const BUNDLE_VERSION: u16 = 1;
+
+const HEADER_LEN: usize = 8 + 2 + 1 + 8 + 32 + 32;
+const RECORD_LEN: usize = 40;
+const NODE_LEN: usize = 32;
+
+/// Write every proof of `tree` as a bundle
+///
+/// Only Keccak V1 trees are supported: vesting records would need the vesting
+/// fields to recompute leaves.
+pub fn write_proof_bundle(tree: &MerkleTree, writer: &mut impl Write) -> Result<(), FormatError> {
+    if tree.schema() != LeafSchema::V1 {
+        return Err(FormatError::UnsupportedSchema(tree.schema()));
+    }
+    if tree.hash_kind() != HashKind::Keccak {
+        return Err(FormatError::UnsupportedHashKind(tree.hash_kind() as u8));
+    }
+
+    writer.write_all(BUNDLE_MAGIC)?;
+    writer.write_all(&BUNDLE_VERSION.to_le_bytes())?;
+    writer.write_all(&[tree.depth() as u8])?;
+    writer.write_all(&(tree.len() as u64).to_le_bytes())?;
+    writer.write_all(tree.distribution_id())?;
+    writer.write_all(&tree.root())?;
+
+    for allocation in tree.allocations() {
+        writer.write_all(allocation.recipient.as_ref())?;
+        writer.write_all(&allocation.amount.to_le_bytes())?;
+    }
+
+    let levels = tree.levels();
+    for level in levels.iter().take(tree.depth()).skip(1) {
+        for node in level {
+            writer.write_all(node)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A decoded proof bundle
+pub struct ProofBundle {
+    data: Vec<u8>,
+    distribution_id: [u8; 32],
+    root: [u8; 32],
+    leaf_count: usize,
+    /// Byte offset and node count of each stored level (`1..depth`)
+    levels: Vec<(usize, usize)>,
+}
+
+impl ProofBundle {
+    /// Read a bundle written by `write_proof_bundle`
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, FormatError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::from_bytes(data)
+    }
+
+    /// Decode a bundle held in memory
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FormatError> {
+        let mut header = &data[..];
+        if &read_array::<8>(&mut header)? != BUNDLE_MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+        let version = u16::from_le_bytes(read_array(&mut header)?);
+        if version > BUNDLE_VERSION {
+            return Err(FormatError::UnsupportedVersion {
+                version,
+                supported: BUNDLE_VERSION,
+            });
+        }
+        let [depth] = read_array(&mut header)?;
+        let leaf_count = u64::from_le_bytes(read_array(&mut header)?);
+        let distribution_id = read_array(&mut header)?;
+        let root = read_array(&mut header)?;
+
+        if leaf_count == 0 || usize::from(depth) > MAX_PROOF_LEN || depth != depth_for(leaf_count) {
+            return Err(FormatError::Corrupt(format!(
+                "depth {depth} is invalid for {leaf_count} leaves"
+            )));
+        }
+        let depth = usize::from(depth);
+        let leaf_count = leaf_count as usize;
+
+        let mut levels = Vec::with_capacity(depth.saturating_sub(1));
+        let mut offset = HEADER_LEN + leaf_count * RECORD_LEN;
+        let mut len = leaf_count.div_ceil(2);
+        for _ in 1..depth {
+            levels.push((offset, len));
+            offset += len * NODE_LEN;
+            len = len.div_ceil(2);
+        }
+        if data.len() != offset {
+            return Err(FormatError::Corrupt(format!(
+                "expected {offset} bytes, bundle has {}",
+                data.len()
+            )));
+        }
+
+        Ok(Self {
+            data,
+            distribution_id,
+            root,
+            leaf_count,
+            levels,
+        })
+    }
+
+    /// Distribution id the leaves are bound to
+    pub fn distribution_id(&self) -> &[u8; 32] {
+        &self.distribution_id
+    }
+
+    /// Merkle root
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Always false: bundles never hold an empty tree
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count == 0
+    }
+
+    /// Claim arguments for the leaf at `index`
+    pub fn proof_for_index(&self, index: u64) -> Option<ClaimProof> {
+        let position = usize::try_from(index)
+            .ok()
+            .filter(|&p| p < self.leaf_count)?;
+        let (recipient, amount) = self.record(position);
+
+        let mut proof = Vec::with_capacity(self.levels.len() + 1);
+        if self.leaf_count > 1 {
+            // Leaf siblings are recomputed from their records
+            let sibling = position ^ 1;
+            let (sibling_recipient, sibling_amount) = if sibling < self.leaf_count {
+                self.record(sibling)
+            } else {
+                // No sibling (odd node at end of level) - the node pairs with itself
+                (recipient, amount)
+            };
+            proof.push(compute_leaf(
+                HashKind::Keccak,
+                &self.distribution_id,
+                &sibling_recipient,
+                sibling_amount,
+            ));
+        }
+
+        let mut current = position / 2;
+        for &(offset, len) in &self.levels {
+            let sibling = current ^ 1;
+            let position = if sibling < len { sibling } else { current };
+            let start = offset + position * NODE_LEN;
+            proof.push(self.data[start..start + NODE_LEN].try_into().unwrap());
+            current /= 2;
+        }
+
+        Some(ClaimProof {
+            index,
+            recipient,
+            amount,
+            proof,
+        })
+    }
+
+    fn record(&self, position: usize) -> (Pubkey, u64) {
+        let start = HEADER_LEN + position * RECORD_LEN;
+        let record = &self.data[start..start + RECORD_LEN];
+        (
+            Pubkey::new_from_array(record[..32].try_into().unwrap()),
+            u64::from_le_bytes(record[32..].try_into().unwrap()),
+        )
+    }
+}
@@ -0,0 +1,169 @@
+//! Response cache for the HTTP APIs
+//!
+//! An in-process moka cache, optionally backed by Redis (`redis` feature)
+//! so replicas share warm entries. Concurrent misses for one key are
+//! coalesced into a single load, which is what keeps a launch-day burst of
+//! identical requests off the tree files and the database.
+//!
+//! Keys are namespaced by a generation string: `set_generation` with a new
+//! value (e.g. a fingerprint of the served distributions) busts every entry
+//! locally and, because the generation is part of the Redis key, in Redis
+//! too. Replicas that derive the same generation share entries without
+//! coordinating. Only successful loads are cached.
+//!
+//! Redis is best effort: an unreachable Redis is a miss, and writes that
+//! fail are dropped.
+
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::hash::to_hex;
+
+/// Key prefix of every Redis entry
+#[cfg(feature = "redis")]
+pub const REDIS_PREFIX: &str = "merkle-cache";
+
+/// A typed cache of one kind of response
+#[derive(Clone)]
+pub struct ResponseCache<T> {
+    namespace: &'static str,
+    ttl: Duration,
+    local: moka::future::Cache<String, T>,
+    generation: Arc<RwLock<String>>,
+    #[cfg(feature = "redis")]
+    redis: Option<redis::aio::ConnectionManager>,
+}
+
+impl<T> ResponseCache<T>
+where
+    T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// In-process cache of up to `capacity` entries, each kept for `ttl`
+    pub fn new(namespace: &'static str, capacity: u64, ttl: Duration) -> Self {
+        Self {
+            namespace,
+            ttl,
+            local: moka::future::Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(ttl)
+                .build(),
+            generation: Arc::default(),
+            #[cfg(feature = "redis")]
+            redis: None,
+        }
+    }
+
+    /// Back the cache with the Redis server at `url`
+    #[cfg(feature = "redis")]
+    pub async fn with_redis(mut self, url: &str) -> redis::RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+        self.redis = Some(redis::aio::ConnectionManager::new(client).await?);
+        Ok(self)
+    }
+
+    /// How long an entry is kept
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn generation(&self) -> String {
+        self.generation.read().unwrap().clone()
+    }
+
+    /// Switch to `generation`, busting every entry if it changed
+    ///
+    /// Returns whether it changed.
+    pub fn set_generation(&self, generation: impl Into<String>) -> bool {
+        let generation = generation.into();
+        {
+            let mut current = self.generation.write().unwrap();
+            if *current == generation {
+                return false;
+            }
+            *current = generation;
+        }
+        self.local.invalidate_all();
+        true
+    }
+
+    /// Cached value for `key`, loading it with `load` on a miss
+    ///
+    /// Concurrent callers missing the same key wait for one `load`; its
+    /// error is returned to all of them and nothing is cached.
+    pub async fn get_or_try_insert<E, F>(&self, key: &str, load: F) -> Result<T, E>
+    where
+        E: Clone + Send + Sync + 'static,
+        F: Future<Output = Result<T, E>>,
+    {
+        let key = format!("{}:{}:{key}", self.namespace, self.generation());
+        self.local
+            .try_get_with_by_ref(key.as_str(), async {
+                #[cfg(feature = "redis")]
+                if let Some(value) = self.redis_get(&key).await {
+                    return Ok(value);
+                }
+                let value = load.await?;
+                #[cfg(feature = "redis")]
+                self.redis_set(&key, &value).await;
+                Ok(value)
+            })
+            .await
+            .map_err(|e: Arc<E>| E::clone(&e))
+    }
+
+    /// Entries held in process (approximate, as in moka)
+    pub fn entry_count(&self) -> u64 {
+        self.local.entry_count()
+    }
+
+    #[cfg(feature = "redis")]
+    async fn redis_get(&self, key: &str) -> Option<T> {
+        let mut redis = self.redis.clone()?;
+        let json: Option<String> = redis::AsyncCommands::get(&mut redis, redis_key(key))
+            .await
+            .ok()?;
+        serde_json::from_str(&json?).ok()
+    }
+
+    #[cfg(feature = "redis")]
+    async fn redis_set(&self, key: &str, value: &T) {
+        let Some(mut redis) = self.redis.clone() else {
+            return;
+        };
+        let Ok(json) = serde_json::to_string(value) else {
+            return;
+        };
+        let _: redis::RedisResult<()> =
+            redis::AsyncCommands::set_ex(&mut redis, redis_key(key), json, self.ttl.as_secs())
+                .await;
+    }
+}
+
+#[cfg(feature = "redis")]
+fn redis_key(key: &str) -> String {
+    format!("{REDIS_PREFIX}:{key}")
+}
+
+/// Fingerprint of a set of items, for use as a generation
+///
+/// Order-independent: the items are sorted before hashing.
+pub fn fingerprint<I>(items: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]> + Ord,
+{
+    let mut items: Vec<I::Item> = items.into_iter().collect();
+    items.sort();
+    let mut hasher = Sha256::new();
+    for item in &items {
+        let item = item.as_ref();
+        hasher.update((item.len() as u64).to_le_bytes());
+        hasher.update(item);
+    }
+    to_hex(&hasher.finalize()[..8])
+}
@@ -0,0 +1,289 @@
+//! Diffing two trees
+//!
+//! Answers "who gained or lost allocation vs last epoch?" by comparing
+//! per-wallet totals (a wallet with several leaves counts once, with the sum
+//! of its amounts).
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::format::FormatError;
+use crate::tree::MerkleTree;
+
+/// How a wallet's allocation changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One wallet whose allocation differs between the trees
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalletDelta {
+    pub wallet: Pubkey,
+    pub change: Change,
+    /// Total in the old tree (0 if absent)
+    pub before: u128,
+    /// Total in the new tree (0 if absent)
+    pub after: u128,
+}
+
+impl WalletDelta {
+    /// `after - before`
+    pub fn delta(&self) -> i128 {
+        self.after as i128 - self.before as i128
+    }
+}
+
+/// Differences between an old and a new tree, ordered by wallet
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TreeDiff {
+    pub deltas: Vec<WalletDelta>,
+    /// Wallets present in both trees with the same total
+    pub unchanged: usize,
+}
+
+impl TreeDiff {
+    /// Compare per-wallet totals of `old` and `new`
+    pub fn between(old: &MerkleTree, new: &MerkleTree) -> Self {
+        let before = wallet_totals(old);
+        let mut after = wallet_totals(new);
+        let mut diff = TreeDiff::default();
+
+        for (wallet, before) in before {
+            match after.remove(&wallet) {
+                Some(after) if after == before => diff.unchanged += 1,
+                Some(after) => diff.deltas.push(WalletDelta {
+                    wallet,
+                    change: Change::Changed,
+                    before,
+                    after,
+                }),
+                None => diff.deltas.push(WalletDelta {
+                    wallet,
+                    change: Change::Removed,
+                    before,
+                    after: 0,
+                }),
+            }
+        }
+        diff.deltas
+            .extend(after.into_iter().map(|(wallet, after)| WalletDelta {
+                wallet,
+                change: Change::Added,
+                before: 0,
+                after,
+            }));
+        diff.deltas
+            .sort_by(|a, b| a.wallet.as_ref().cmp(b.wallet.as_ref()));
+
+        diff
+    }
+
+    /// Compare two tree files written by `MerkleTree::save`
+    pub fn between_files(
+        old: impl AsRef<Path>,
+        new: impl AsRef<Path>,
+    ) -> Result<Self, FormatError> {
+        Ok(Self::between(
+            &MerkleTree::load(old)?,
+            &MerkleTree::load(new)?,
+        ))
+    }
+
+    /// Wallets only in the new tree
+    pub fn added(&self) -> impl Iterator<Item = &WalletDelta> {
+        self.with_change(Change::Added)
+    }
+
+    /// Wallets only in the old tree
+    pub fn removed(&self) -> impl Iterator<Item = &WalletDelta> {
+        self.with_change(Change::Removed)
+    }
+
+    /// Wallets in both trees with different totals
+    pub fn changed(&self) -> impl Iterator<Item = &WalletDelta> {
+        self.with_change(Change::Changed)
+    }
+
+    fn with_change(&self, change: Change) -> impl Iterator<Item = &WalletDelta> {
+        self.deltas.iter().filter(move |d| d.change == change)
+    }
+
+    /// Write `wallet,change,before,after,delta` rows
+    pub fn write_csv(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = ::csv::Writer::from_writer(writer);
+        writer.write_record(["wallet", "change", "before", "after", "delta"])?;
+        for delta in &self.deltas {
+            let row = DeltaRow::from(delta);
+            writer.write_record([
+                row.wallet,
+                row.change.to_string(),
+                row.before,
+                row.after,
+                row.delta,
+            ])?;
+        }
+        writer.flush()
+    }
+
+    /// Write the diff as JSON (amounts as base-unit strings)
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        let deltas: Vec<_> = self.deltas.iter().map(DeltaRow::from).collect();
+        serde_json::to_writer_pretty(
+            writer,
+            &DiffJson {
+                added: self.added().count(),
+                removed: self.removed().count(),
+                changed: self.changed().count(),
+                unchanged: self.unchanged,
+                deltas,
+            },
+        )
+    }
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Changed => "changed",
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct DiffJson {
+    added: usize,
+    removed: usize,
+    changed: usize,
+    unchanged: usize,
+    deltas: Vec<DeltaRow>,
+}
+
+#[derive(Serialize)]
+struct DeltaRow {
+    wallet: String,
+    change: Change,
+    before: String,
+    after: String,
+    delta: String,
+}
+
+impl From<&WalletDelta> for DeltaRow {
+    fn from(delta: &WalletDelta) -> Self {
+        Self {
+            wallet: delta.wallet.to_string(),
+            change: delta.change,
+            before: delta.before.to_string(),
+            after: delta.after.to_string(),
+            delta: delta.delta().to_string(),
+        }
+    }
+}
+
+fn wallet_totals(tree: &MerkleTree) -> BTreeMap<Pubkey, u128> {
+    let mut totals = BTreeMap::new();
+    for allocation in tree.allocations() {
+        *totals.entry(allocation.recipient).or_default() += u128::from(allocation.amount);
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Allocation;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn tree(allocations: &[(u8, u64)]) -> MerkleTree {
+        let allocations: Vec<_> = allocations
+            .iter()
+            .map(|&(seed, amount)| Allocation::new(key(seed), amount))
+            .collect();
+        MerkleTree::new([1; 32], &allocations).unwrap()
+    }
+
+    fn diff() -> TreeDiff {
+        // Wallet 1 has two leaves summing to 30 in the old tree
+        let old = tree(&[(1, 10), (2, 5), (1, 20), (3, 7)]);
+        let new = tree(&[(3, 7), (1, 25), (4, 9)]);
+        TreeDiff::between(&old, &new)
+    }
+
+    #[test]
+    fn compares_wallet_totals() {
+        let diff = diff();
+
+        assert_eq!(
+            diff.deltas,
+            [
+                WalletDelta {
+                    wallet: key(1),
+                    change: Change::Changed,
+                    before: 30,
+                    after: 25,
+                },
+                WalletDelta {
+                    wallet: key(2),
+                    change: Change::Removed,
+                    before: 5,
+                    after: 0,
+                },
+                WalletDelta {
+                    wallet: key(4),
+                    change: Change::Added,
+                    before: 0,
+                    after: 9,
+                },
+            ]
+        );
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed().next().unwrap().delta(), -5);
+        assert_eq!(diff.added().count(), 1);
+        assert_eq!(diff.removed().count(), 1);
+    }
+
+    #[test]
+    fn identical_trees_have_no_deltas() {
+        let tree = tree(&[(1, 10), (2, 5)]);
+        let diff = TreeDiff::between(&tree, &tree);
+
+        assert!(diff.deltas.is_empty());
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn csv_and_json_output() {
+        let diff = diff();
+
+        let mut csv = Vec::new();
+        diff.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("wallet,change,before,after,delta"));
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("{},changed,30,25,-5", key(1))
+        );
+        assert_eq!(lines.count(), 2);
+
+        let mut json = Vec::new();
+        diff.write_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["added"], 1);
+        assert_eq!(json["unchanged"], 1);
+        assert_eq!(json["deltas"][1]["change"], "removed");
+        assert_eq!(json["deltas"][1]["before"], "5");
+    }
+}
@@ -0,0 +1,114 @@
+//! Epoch-append trees with an audit trail
+//!
+//! Each epoch appends a new cohort after the existing leaves, so earlier
+//! recipients keep their indices and proofs only change along the new
+//! right-hand path. The audit trail records which indices each epoch added
+//! and the root it produced.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::BuildError;
+use crate::hash::to_hex;
+use crate::tree::{Allocation, MerkleTree};
+
+/// One appended epoch
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Epoch {
+    pub epoch: u64,
+    /// First index added in this epoch
+    pub first_index: u64,
+    /// Number of leaves added
+    pub count: u64,
+    /// Hex-encoded root after this epoch
+    pub root: String,
+}
+
+/// A tree grown epoch by epoch
+#[derive(Clone, Debug)]
+pub struct EpochTree {
+    tree: MerkleTree,
+    epochs: Vec<Epoch>,
+}
+
+impl EpochTree {
+    /// Start a tree with the first epoch's allocations
+    pub fn new(
+        distribution_id: [u8; 32],
+        epoch: u64,
+        allocations: &[Allocation],
+    ) -> Result<Self, BuildError> {
+        let tree = MerkleTree::new(distribution_id, allocations)?;
+        let epochs = vec![Epoch {
+            epoch,
+            first_index: 0,
+            count: allocations.len() as u64,
+            root: to_hex(&tree.root()),
+        }];
+
+        Ok(Self { tree, epochs })
+    }
+
+    /// Resume from a saved tree and its audit trail
+    pub fn from_parts(tree: MerkleTree, epochs: Vec<Epoch>) -> Result<Self, BuildError> {
+        let covered: u64 = epochs.iter().map(|e| e.count).sum();
+        let last = epochs.last().map(|e| e.root.as_str());
+        if covered != tree.len() as u64 || last != Some(to_hex(&tree.root()).as_str()) {
+            return Err(BuildError::AuditMismatch);
+        }
+
+        Ok(Self { tree, epochs })
+    }
+
+    /// Append a new epoch's allocations
+    ///
+    /// `epoch` must be greater than the last recorded epoch.
+    pub fn append(&mut self, epoch: u64, allocations: &[Allocation]) -> Result<&Epoch, BuildError> {
+        let last = self.epochs.last().map_or(0, |e| e.epoch);
+        if epoch <= last {
+            return Err(BuildError::EpochOutOfOrder { epoch, last });
+        }
+        if allocations.is_empty() {
+            return Err(BuildError::Empty);
+        }
+
+        let added = self.tree.append(allocations)?;
+        self.epochs.push(Epoch {
+            epoch,
+            first_index: added.start,
+            count: added.end - added.start,
+            root: to_hex(&self.tree.root()),
+        });
+
+        Ok(&self.epochs[self.epochs.len() - 1])
+    }
+
+    /// The current tree
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Audit trail, oldest epoch first
+    pub fn epochs(&self) -> &[Epoch] {
+        &self.epochs
+    }
+
+    /// Epoch that added the leaf at `index`
+    pub fn epoch_of(&self, index: u64) -> Option<&Epoch> {
+        self.epochs
+            .iter()
+            .find(|e| (e.first_index..e.first_index + e.count).contains(&index))
+    }
+
+    /// Write the audit trail as JSON
+    pub fn write_audit_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, &self.epochs)
+    }
+
+    /// Read an audit trail written by `write_audit_json`
+    pub fn read_audit_json(reader: impl Read) -> serde_json::Result<Vec<Epoch>> {
+        serde_json::from_reader(reader)
+    }
+}
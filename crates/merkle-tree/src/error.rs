@@ -0,0 +1,40 @@
+use std::io;
+
+use solana_pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::build::BudgetOvershoot;
+use crate::hash::MAX_PROOF_LEN;
+
+/// Errors raised while building a tree
+#[derive(Debug, Error)]
+pub enum BuildError {
+    #[error("cannot build a Merkle tree with no allocations")]
+    Empty,
+    #[error("tree depth {depth} exceeds the on-chain maximum proof length of {MAX_PROOF_LEN}")]
+    TooDeep { depth: usize },
+    #[error("tree file I/O failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("allocation {index}: duplicate recipient {recipient}")]
+    DuplicateRecipient { recipient: Pubkey, index: usize },
+    #[error("allocation {index}: cannot merge duplicate recipient {recipient} with a different unlock/expiry schedule")]
+    ConflictingDuplicate { recipient: Pubkey, index: usize },
+    #[error("allocation {index}: recipient {recipient} is off-curve (a program-derived address nobody can sign for)")]
+    OffCurveRecipient { recipient: Pubkey, index: usize },
+    #[error("split rule for {holder} has no positive weight")]
+    InvalidSplitRule { holder: Pubkey },
+    #[error("split of {amount} for {holder} sums to {split_total}")]
+    SplitNotConserved {
+        holder: Pubkey,
+        amount: u64,
+        split_total: u128,
+    },
+    #[error("budget cap exceeded: {0}")]
+    OverBudget(Box<BudgetOvershoot>),
+    #[error("merged amount for {recipient} overflows u64")]
+    AmountOverflow { recipient: Pubkey },
+    #[error("epoch {epoch} is not after the last appended epoch {last}")]
+    EpochOutOfOrder { epoch: u64, last: u64 },
+    #[error("audit trail does not match the tree's leaf count and root")]
+    AuditMismatch,
+}
@@ -0,0 +1,113 @@
+//! Parallel JSONL proof export
+//!
+//! Worker threads format proofs chunk by chunk and hand them to the writer
+//! over a bounded channel, so memory stays at a few chunks regardless of
+//! tree size. The writer restores index order before writing.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::thread;
+
+use crate::hash::to_hex;
+use crate::tree::MerkleTree;
+
+/// Options for `export_jsonl`
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    /// Worker threads (defaults to the available parallelism)
+    pub threads: usize,
+    /// Recipients per chunk handed to a worker
+    pub chunk_size: usize,
+    /// Formatted chunks that may wait for the writer
+    pub channel_capacity: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(4, NonZeroUsize::get);
+        Self {
+            threads,
+            chunk_size: 4096,
+            channel_capacity: threads * 2,
+        }
+    }
+}
+
+/// Write one JSON line per recipient, in index order
+///
+/// `{"index":0,"wallet":"…","amount":"1500000000","proof":["ab…",…]}`
+///
+/// Field names and types match the TypeScript `MerkleProof`. Returns the
+/// number of lines written.
+pub fn export_jsonl(
+    tree: &MerkleTree,
+    writer: &mut impl Write,
+    options: &ExportOptions,
+) -> io::Result<u64> {
+    let chunk_size = options.chunk_size.max(1);
+    let num_chunks = tree.len().div_ceil(chunk_size);
+    let next_chunk = AtomicUsize::new(0);
+    let (sender, receiver) = sync_channel::<(usize, String)>(options.channel_capacity.max(1));
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads.max(1) {
+            let sender = sender.clone();
+            let next_chunk = &next_chunk;
+            scope.spawn(move || loop {
+                let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                if chunk >= num_chunks {
+                    break;
+                }
+                let start = chunk * chunk_size;
+                let end = (start + chunk_size).min(tree.len());
+                let lines = format_chunk(tree, start, end);
+                // Receiver gone means the writer failed; stop quietly
+                if sender.send((chunk, lines)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Chunks are claimed in order, so at most threads + capacity wait here
+        let mut pending = BTreeMap::new();
+        let mut next_to_write = 0;
+        for (chunk, lines) in receiver {
+            pending.insert(chunk, lines);
+            while let Some(lines) = pending.remove(&next_to_write) {
+                writer.write_all(lines.as_bytes())?;
+                next_to_write += 1;
+            }
+        }
+
+        Ok(tree.len() as u64)
+    })
+}
+
+fn format_chunk(tree: &MerkleTree, start: usize, end: usize) -> String {
+    let mut lines = String::new();
+
+    for index in start..end {
+        let allocation = &tree.allocations()[index];
+        let proof = tree.get_proof(index).unwrap_or_default();
+
+        let _ = write!(
+            lines,
+            r#"{{"index":{index},"wallet":"{}","amount":"{}","proof":["#,
+            allocation.recipient, allocation.amount
+        );
+        for (i, node) in proof.iter().enumerate() {
+            if i > 0 {
+                lines.push(',');
+            }
+            let _ = write!(lines, r#""{}""#, to_hex(node));
+        }
+        lines.push_str("]}\n");
+    }
+
+    lines
+}
@@ -0,0 +1,198 @@
+//! Deterministic random tree fixtures for tests
+//!
+//! The same `FixtureOptions` always produce the same keypairs, amounts,
+//! proofs and root, so program and SDK tests can assert against fixed
+//! expectations without hand-crafting trees. The generator is a
+//! self-contained SplitMix64, so fixtures stay stable across dependency
+//! upgrades.
+
+use std::io::Write;
+
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::error::BuildError;
+use crate::hash::to_hex;
+use crate::tree::{Allocation, MerkleTree};
+
+/// How fixture amounts are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmountDistribution {
+    /// Every recipient gets the same amount
+    Constant(u64),
+    /// Uniform in `min..=max`
+    Uniform { min: u64, max: u64 },
+    /// Heavy-tailed in `min..=max`: most amounts near `min`, a few near
+    /// `max` (like real reward allocations)
+    Skewed { min: u64, max: u64 },
+}
+
+/// Options for `Fixture::generate`
+#[derive(Clone, Debug)]
+pub struct FixtureOptions {
+    pub seed: u64,
+    pub num_recipients: usize,
+    pub amounts: AmountDistribution,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            num_recipients: 16,
+            amounts: AmountDistribution::Uniform {
+                min: 1,
+                max: 1_000_000_000,
+            },
+        }
+    }
+}
+
+/// One generated recipient
+#[derive(Clone, Debug)]
+pub struct FixtureRecipient {
+    pub keypair: SigningKey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// A generated tree with everything tests need to claim from it
+#[derive(Clone, Debug)]
+pub struct Fixture {
+    pub recipients: Vec<FixtureRecipient>,
+    pub tree: MerkleTree,
+}
+
+impl Fixture {
+    /// Generate a fixture
+    ///
+    /// The distribution id is derived from the seed as well.
+    pub fn generate(options: &FixtureOptions) -> Result<Self, BuildError> {
+        let mut rng = SplitMix64(options.seed);
+
+        let mut distribution_id = [0u8; 32];
+        rng.fill(&mut distribution_id);
+
+        let recipients: Vec<_> = (0..options.num_recipients)
+            .map(|_| {
+                let mut secret = [0u8; 32];
+                rng.fill(&mut secret);
+                let keypair = SigningKey::from_bytes(&secret);
+                let recipient = Pubkey::new_from_array(keypair.verifying_key().to_bytes());
+                let amount = rng.amount(options.amounts);
+                FixtureRecipient {
+                    keypair,
+                    recipient,
+                    amount,
+                }
+            })
+            .collect();
+
+        let allocations: Vec<_> = recipients
+            .iter()
+            .map(|r| Allocation::new(r.recipient, r.amount))
+            .collect();
+        let tree = MerkleTree::new(distribution_id, &allocations)?;
+
+        Ok(Self { recipients, tree })
+    }
+
+    /// Write the fixture as JSON
+    ///
+    /// Recipient entries use the TypeScript `MerkleProof` field names plus the
+    /// 64-byte Solana keypair, so SDK tests can load them directly.
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        let recipients = self
+            .recipients
+            .iter()
+            .enumerate()
+            .map(|(index, r)| FixtureRecipientJson {
+                index: index as u64,
+                wallet: r.recipient.to_string(),
+                amount: r.amount.to_string(),
+                proof: self
+                    .tree
+                    .get_proof(index)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|node| to_hex(node))
+                    .collect(),
+                keypair: r.keypair.to_keypair_bytes().to_vec(),
+            })
+            .collect();
+
+        let total_amount: u128 = self.recipients.iter().map(|r| u128::from(r.amount)).sum();
+
+        serde_json::to_writer_pretty(
+            writer,
+            &FixtureJson {
+                distribution_id: to_hex(self.tree.distribution_id()),
+                merkle_root: to_hex(&self.tree.root()),
+                total_amount: total_amount.to_string(),
+                num_recipients: self.recipients.len() as u64,
+                recipients,
+            },
+        )
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureJson {
+    distribution_id: String,
+    merkle_root: String,
+    total_amount: String,
+    num_recipients: u64,
+    recipients: Vec<FixtureRecipientJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FixtureRecipientJson {
+    index: u64,
+    wallet: String,
+    amount: String,
+    proof: Vec<String>,
+    keypair: Vec<u8>,
+}
+
+/// SplitMix64 (Steele, Lea & Flood)
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill(&mut self, bytes: &mut [u8]) {
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    /// Uniform in `0..=span`
+    fn up_to(&mut self, span: u64) -> u64 {
+        match span.checked_add(1) {
+            Some(bound) => self.next() % bound,
+            None => self.next(),
+        }
+    }
+
+    fn amount(&mut self, distribution: AmountDistribution) -> u64 {
+        match distribution {
+            AmountDistribution::Constant(amount) => amount,
+            AmountDistribution::Uniform { min, max } => min + self.up_to(max.saturating_sub(min)),
+            AmountDistribution::Skewed { min, max } => {
+                // Geometric shift: half the draws use the full range, a
+                // quarter half of it, and so on
+                let shift = self.next().trailing_zeros().min(63);
+                min + self.up_to(max.saturating_sub(min) >> shift)
+            }
+        }
+    }
+}
@@ -0,0 +1,361 @@
+//! Versioned binary tree file format
+//!
+//! All integers are little-endian.
+//!
+//! | Field | Size | Notes |
+//! |-------|------|-------|
+//! | magic | 8 | `b"L33TREE\0"` |
+//! | version | 2 | `FORMAT_VERSION`; newer versions are rejected |
+//! | flags | 2 | Reserved; unknown bits are rejected |
+//! | header_len | 4 | Total header size; readers skip fields they don't know |
+//! | hash_kind | 1 | `HashKind`: `0` = Keccak-256, `1` = SHA-256 |
+//! | domain_len | 1 | |
+//! | domain | domain_len | Leaf domain separator (identifies the `LeafSchema`) |
+//! | distribution_id | 32 | |
+//! | leaf_count | 8 | |
+//! | depth | 1 | |
+//!
+//! The header is followed by `leaf_count` allocation records and then every
+//! level from the leaves up to the root as consecutive 32-byte nodes. Level
+//! `n + 1` holds `ceil(len(n) / 2)` nodes. Records hold the fields committed
+//! in the leaves: `recipient (32) || amount (8)` for V1, plus
+//! `unlock_at (8) || expires_at (8)` (0 = unset) for vesting trees.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use solana_pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::hash::{HashKind, LeafSchema, MAX_PROOF_LEN};
+use crate::tree::{Allocation, MerkleTree};
+
+/// File magic
+pub const MAGIC: &[u8; 8] = b"L33TREE\0";
+
+/// Current format version
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Size of the header fields known to `FORMAT_VERSION`, excluding the domain
+const FIXED_HEADER_LEN: u32 = 8 + 2 + 2 + 4 + 1 + 1 + 32 + 8 + 1;
+
+/// Errors raised while reading or writing tree files
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("tree file I/O failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a tree file (bad magic)")]
+    BadMagic,
+    #[error("file version {version} is newer than supported version {supported}")]
+    UnsupportedVersion { version: u16, supported: u16 },
+    #[error("tree file uses unknown flags {0:#06x}")]
+    UnknownFlags(u16),
+    #[error("tree file uses unsupported hash kind {0}")]
+    UnsupportedHashKind(u8),
+    #[error("unknown leaf domain separator `{}`", String::from_utf8_lossy(.0))]
+    UnknownDomain(Vec<u8>),
+    #[error("{0:?} leaves are not supported by this format")]
+    UnsupportedSchema(LeafSchema),
+    #[error("corrupt tree file: {0}")]
+    Corrupt(String),
+}
+
+impl MerkleTree {
+    /// Write the tree to `path` in the binary tree format
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FormatError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the tree in the binary tree format
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), FormatError> {
+        let domain = self.schema().domain_separator();
+        let header_len = FIXED_HEADER_LEN + domain.len() as u32;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.write_all(&header_len.to_le_bytes())?;
+        writer.write_all(&[self.hash_kind() as u8, domain.len() as u8])?;
+        writer.write_all(domain)?;
+        writer.write_all(self.distribution_id())?;
+        writer.write_all(&(self.len() as u64).to_le_bytes())?;
+        writer.write_all(&[self.depth() as u8])?;
+
+        for allocation in self.allocations() {
+            writer.write_all(allocation.recipient.as_ref())?;
+            writer.write_all(&allocation.amount.to_le_bytes())?;
+            if self.schema() == LeafSchema::Vesting {
+                writer.write_all(&allocation.unlock_at.unwrap_or(0).to_le_bytes())?;
+                writer.write_all(&allocation.expires_at.unwrap_or(0).to_le_bytes())?;
+            }
+        }
+        for level in self.levels() {
+            for node in level {
+                writer.write_all(node)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a tree written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FormatError> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Read a tree in the binary tree format
+    pub fn read_from(reader: &mut impl Read) -> Result<Self, FormatError> {
+        let header = Header::read(reader)?;
+
+        let mut allocations = Vec::with_capacity(header.leaf_count as usize);
+        for _ in 0..header.leaf_count {
+            let recipient = Pubkey::new_from_array(read_array(reader)?);
+            let amount = u64::from_le_bytes(read_array(reader)?);
+            let mut allocation = Allocation::new(recipient, amount);
+            if header.schema == LeafSchema::Vesting {
+                let unset_if_zero = |t: i64| (t != 0).then_some(t);
+                allocation.unlock_at = unset_if_zero(i64::from_le_bytes(read_array(reader)?));
+                allocation.expires_at = unset_if_zero(i64::from_le_bytes(read_array(reader)?));
+            }
+            allocations.push(allocation);
+        }
+
+        let mut levels = Vec::with_capacity(usize::from(header.depth) + 1);
+        let mut len = header.leaf_count;
+        for _ in 0..=header.depth {
+            let mut level = Vec::with_capacity(len as usize);
+            for _ in 0..len {
+                level.push(read_array(reader)?);
+            }
+            levels.push(level);
+            len = len.div_ceil(2);
+        }
+
+        if reader.read(&mut [0u8; 1])? != 0 {
+            return Err(FormatError::Corrupt(
+                "trailing bytes after last level".to_string(),
+            ));
+        }
+
+        Ok(MerkleTree::from_parts(
+            header.distribution_id,
+            header.hash_kind,
+            header.schema,
+            allocations,
+            levels,
+        ))
+    }
+}
+
+/// Parsed tree file header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub hash_kind: HashKind,
+    pub schema: LeafSchema,
+    pub distribution_id: [u8; 32],
+    pub leaf_count: u64,
+    pub depth: u8,
+    /// Total header size in bytes
+    pub header_len: u32,
+}
+
+impl Header {
+    /// Read and validate a header, leaving `reader` at the first allocation
+    pub fn read(reader: &mut impl Read) -> Result<Self, FormatError> {
+        if &read_array::<8>(reader)? != MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+
+        let version = u16::from_le_bytes(read_array(reader)?);
+        if version > FORMAT_VERSION {
+            return Err(FormatError::UnsupportedVersion {
+                version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        let flags = u16::from_le_bytes(read_array(reader)?);
+        if flags != 0 {
+            return Err(FormatError::UnknownFlags(flags));
+        }
+        let header_len = u32::from_le_bytes(read_array(reader)?);
+
+        let [hash_kind, domain_len] = read_array(reader)?;
+        let hash_kind =
+            HashKind::from_u8(hash_kind).ok_or(FormatError::UnsupportedHashKind(hash_kind))?;
+        let mut domain = vec![0u8; usize::from(domain_len)];
+        reader.read_exact(&mut domain)?;
+        let schema =
+            LeafSchema::from_domain_separator(&domain).ok_or(FormatError::UnknownDomain(domain))?;
+
+        let distribution_id = read_array(reader)?;
+        let leaf_count = u64::from_le_bytes(read_array(reader)?);
+        let [depth] = read_array(reader)?;
+
+        let known_len = FIXED_HEADER_LEN + u32::from(domain_len);
+        if header_len < known_len {
+            return Err(FormatError::Corrupt(format!(
+                "header length {header_len} is shorter than {known_len}"
+            )));
+        }
+        // Skip header fields added by later minor revisions
+        io::copy(
+            &mut reader.by_ref().take(u64::from(header_len - known_len)),
+            &mut io::sink(),
+        )?;
+
+        if leaf_count == 0 {
+            return Err(FormatError::Corrupt("tree has no leaves".to_string()));
+        }
+        if usize::from(depth) > MAX_PROOF_LEN || depth != depth_for(leaf_count) {
+            return Err(FormatError::Corrupt(format!(
+                "depth {depth} is invalid for {leaf_count} leaves"
+            )));
+        }
+
+        Ok(Self {
+            version,
+            hash_kind,
+            schema,
+            distribution_id,
+            leaf_count,
+            depth,
+            header_len,
+        })
+    }
+
+    /// Size of one allocation record
+    pub fn record_len(&self) -> usize {
+        match self.schema {
+            LeafSchema::V1 => 40,
+            LeafSchema::Vesting => 56,
+        }
+    }
+}
+
+/// Depth of a tree with `leaf_count` leaves
+pub(crate) fn depth_for(leaf_count: u64) -> u8 {
+    let mut depth = 0;
+    let mut len = leaf_count;
+    while len > 1 {
+        len = len.div_ceil(2);
+        depth += 1;
+    }
+    depth
+}
+
+pub(crate) fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], FormatError> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => FormatError::Corrupt("unexpected end of file".to_string()),
+        _ => e.into(),
+    })?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Length of the V1 domain separator in the header
+    const DOMAIN_LEN: usize = crate::hash::DOMAIN_SEPARATOR.len();
+
+    fn tree(schema: LeafSchema, hash_kind: HashKind) -> MerkleTree {
+        let allocations: Vec<_> = (0..5u8)
+            .map(|i| Allocation {
+                recipient: Pubkey::new_from_array([i; 32]),
+                amount: u64::from(i) + 1,
+                unlock_at: (i % 2 == 1).then_some(100),
+                expires_at: (i % 2 == 1).then_some(200),
+            })
+            .collect();
+        MerkleTree::with_hashing([7; 32], &allocations, hash_kind, schema).unwrap()
+    }
+
+    fn bytes(tree: &MerkleTree) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        tree.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> Result<MerkleTree, FormatError> {
+        MerkleTree::read_from(&mut &bytes[..])
+    }
+
+    #[test]
+    fn round_trips_every_schema_and_hash() {
+        for schema in [LeafSchema::V1, LeafSchema::Vesting] {
+            for hash_kind in [HashKind::Keccak, HashKind::Sha256] {
+                let tree = tree(schema, hash_kind);
+                let read = read(&bytes(&tree)).unwrap();
+
+                assert_eq!(read.schema(), schema);
+                assert_eq!(read.hash_kind(), hash_kind);
+                assert_eq!(read.root(), tree.root());
+                assert_eq!(read.levels(), tree.levels());
+                // V1 records don't carry the vesting fields
+                if schema == LeafSchema::Vesting {
+                    assert_eq!(read.allocations(), tree.allocations());
+                } else {
+                    assert_eq!(read.allocation(1).unwrap().unlock_at, None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_trailing_header_fields_are_skipped() {
+        let tree = tree(LeafSchema::V1, HashKind::Keccak);
+        let mut bytes = bytes(&tree);
+        let known_len = FIXED_HEADER_LEN as usize + DOMAIN_LEN;
+
+        // A later minor revision appending a 4-byte header field
+        bytes.splice(known_len..known_len, [0xaa; 4]);
+        bytes[12..16].copy_from_slice(&(known_len as u32 + 4).to_le_bytes());
+
+        let read = read(&bytes).unwrap();
+        assert_eq!(read.root(), tree.root());
+        assert_eq!(
+            Header::read(&mut &bytes[..]).unwrap().header_len,
+            known_len as u32 + 4
+        );
+    }
+
+    #[test]
+    fn newer_versions_and_unknown_flags_are_rejected() {
+        let mut bytes = bytes(&tree(LeafSchema::V1, HashKind::Keccak));
+
+        bytes[8..10].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            read(&bytes),
+            Err(FormatError::UnsupportedVersion { version, supported: FORMAT_VERSION })
+                if version == FORMAT_VERSION + 1
+        ));
+
+        bytes[8..10].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[10..12].copy_from_slice(&4u16.to_le_bytes());
+        assert!(matches!(read(&bytes), Err(FormatError::UnknownFlags(4))));
+    }
+
+    #[test]
+    fn damaged_files_are_rejected() {
+        let bytes = bytes(&tree(LeafSchema::V1, HashKind::Keccak));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(read(&bad_magic), Err(FormatError::BadMagic)));
+
+        assert!(matches!(
+            read(&bytes[..bytes.len() - 1]),
+            Err(FormatError::Corrupt(_))
+        ));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(read(&trailing), Err(FormatError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,921 @@
+//! GraphQL API over the indexer database (`graphql` feature)
+//!
+//! A read-only async-graphql schema for the analytics frontend, so new
+//! questions don't each need a REST endpoint:
+//!
+//! - `distributions` (`merkle_distributions`)
+//! - `epochs` (`epoch_snapshots` with the epoch's exchange rate)
+//! - `allocations` (`reward_payouts_preview`)
+//! - `claimEvents` (finalized `merkle_claim_events`)
+//! - `stats` (totals over all of the above)
+//!
+//! Every list takes a filter, a `sort` and `order`, and `first` / `after`
+//! keyset pagination, and returns `{ items, pageInfo { endCursor,
+//! hasNextPage } }`. Cursors are opaque and only valid for the sort they
+//! were issued under. Amounts are base-unit strings and timestamps unix
+//! seconds, as in the REST API.
+//!
+//! `cached_schema` caches `stats` per mint in a `ResponseCache`;
+//! `watch_distributions` busts it when a distribution is added or changes.
+
+use std::time::Duration;
+
+use async_graphql::connection::{CursorType, OpaqueCursor};
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Enum, InputObject, Object, OutputType, Schema,
+    SimpleObject,
+};
+use async_graphql_axum::GraphQL;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgPool, PgRow, Postgres};
+use sqlx::{QueryBuilder, Row};
+
+use crate::cache::ResponseCache;
+
+/// Page size when `first` is not given
+pub const DEFAULT_LIMIT: i64 = 50;
+/// Largest accepted `first`
+pub const MAX_LIMIT: i64 = 500;
+
+/// Event kinds that pay tokens out of a distribution
+const CLAIM_KINDS: [&str; 3] = ["claim", "claim_referral", "escrow"];
+
+/// Whether allocation `p` has been claimed, escrowed or renounced on-chain,
+/// or its leaf confirmed by the relayer
+macro_rules! allocation_claimed {
+    () => {
+        "(
+          EXISTS (
+            SELECT 1
+            FROM merkle_distributions cd
+            JOIN merkle_claim_events ce
+              ON ce.deployment = cd.deployment AND ce.distribution = cd.on_chain_address
+            WHERE cd.reward_id = p.reward_id
+              AND ce.recipient = p.wallet
+              AND ce.kind IN ('claim', 'claim_referral', 'escrow', 'renounce')
+          ) OR EXISTS (
+            SELECT 1
+            FROM merkle_distributions cd
+            JOIN merkle_claims cc ON cc.distribution_id = cd.distribution_id
+            WHERE cd.reward_id = p.reward_id
+              AND cc.wallet = p.wallet
+              AND cc.status = 'confirmed'
+          )
+        )"
+    };
+}
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Schema reading from `pool`
+pub fn schema(pool: PgPool) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(pool)
+        .limit_depth(8)
+        .finish()
+}
+
+/// Schema reading from `pool`, with `stats` cached in `stats_cache`
+pub fn cached_schema(pool: PgPool, stats_cache: ResponseCache<Stats>) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(pool)
+        .data(stats_cache)
+        .limit_depth(8)
+        .finish()
+}
+
+/// Bust `cache` whenever `merkle_distributions` gains or updates a row,
+/// checking every `interval`
+///
+/// The generation is the row count and newest `updated_at`, so replicas
+/// sharing a Redis cache agree on it. Runs until the task is dropped; a
+/// failed check keeps the current generation.
+pub async fn watch_distributions<T>(pool: PgPool, cache: ResponseCache<T>, interval: Duration)
+where
+    T: Clone + Serialize + serde::de::DeserializeOwned + Send + Sync + 'static,
+{
+    let mut ticks = tokio::time::interval(interval);
+    loop {
+        ticks.tick().await;
+        let generation: Result<String, sqlx::Error> = sqlx::query_scalar(
+            "SELECT COUNT(*)::TEXT || '-' || COALESCE(EXTRACT(EPOCH FROM MAX(updated_at))::BIGINT, 0)::TEXT
+             FROM merkle_distributions",
+        )
+        .fetch_one(&pool)
+        .await;
+        if let Ok(generation) = generation {
+            cache.set_generation(generation);
+        }
+    }
+}
+
+/// `POST /graphql` for queries, `GET /graphql` for GraphiQL
+pub fn router(schema: ApiSchema) -> Router {
+    Router::new().route("/graphql", get(graphiql).post_service(GraphQL::new(schema)))
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// One page of a list
+#[derive(SimpleObject)]
+#[graphql(concrete(name = "DistributionPage", params(Distribution)))]
+#[graphql(concrete(name = "EpochPage", params(Epoch)))]
+#[graphql(concrete(name = "AllocationPage", params(Allocation)))]
+#[graphql(concrete(name = "ClaimEventPage", params(ClaimEvent)))]
+pub struct Page<T: OutputType> {
+    pub items: Vec<T>,
+    pub page_info: PageInfo,
+}
+
+#[derive(SimpleObject)]
+pub struct PageInfo {
+    /// Pass as `after` to get the next page; `None` on an empty page
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    fn sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    /// Row comparison selecting rows after the cursor
+    fn after(self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+}
+
+/// Columns a list is ordered by, most significant first
+///
+/// Trailing columns make the order total, so every row has a distinct
+/// position to resume after. Each is `(expression, SQL type)`; none may be
+/// NULL.
+struct SortKey {
+    name: &'static str,
+    columns: &'static [(&'static str, &'static str)],
+}
+
+/// Decoded `after` cursor: the sort it was issued under and the last row's
+/// sort key values
+#[derive(Serialize, Deserialize)]
+struct Position {
+    sort: String,
+    keys: Vec<String>,
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DistributionSort {
+    #[default]
+    CreatedAt,
+    TotalAmount,
+}
+
+impl DistributionSort {
+    fn key(self) -> &'static SortKey {
+        match self {
+            DistributionSort::CreatedAt => &SortKey {
+                name: "distributions.created_at",
+                columns: &[("d.created_at", "TIMESTAMP"), ("d.distribution_id", "TEXT")],
+            },
+            DistributionSort::TotalAmount => &SortKey {
+                name: "distributions.total_amount",
+                columns: &[("d.total_amount", "NUMERIC"), ("d.distribution_id", "TEXT")],
+            },
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EpochSort {
+    #[default]
+    Epoch,
+}
+
+impl EpochSort {
+    fn key(self) -> &'static SortKey {
+        match self {
+            EpochSort::Epoch => &SortKey {
+                name: "epochs.epoch",
+                columns: &[("s.epoch", "BIGINT"), ("s.mint", "TEXT")],
+            },
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AllocationSort {
+    #[default]
+    Amount,
+    Reward,
+}
+
+impl AllocationSort {
+    fn key(self) -> &'static SortKey {
+        match self {
+            AllocationSort::Amount => &SortKey {
+                name: "allocations.amount",
+                columns: &[
+                    ("p.payout_amount", "NUMERIC"),
+                    ("p.reward_id", "TEXT"),
+                    ("p.wallet", "TEXT"),
+                ],
+            },
+            AllocationSort::Reward => &SortKey {
+                name: "allocations.reward",
+                columns: &[("p.reward_id", "TEXT"), ("p.wallet", "TEXT")],
+            },
+        }
+    }
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClaimEventSort {
+    #[default]
+    Slot,
+    Amount,
+}
+
+impl ClaimEventSort {
+    fn key(self) -> &'static SortKey {
+        match self {
+            ClaimEventSort::Slot => &SortKey {
+                name: "claim_events.slot",
+                columns: &[
+                    ("e.slot", "BIGINT"),
+                    ("e.signature", "TEXT"),
+                    ("e.event_index", "INTEGER"),
+                ],
+            },
+            ClaimEventSort::Amount => &SortKey {
+                name: "claim_events.amount",
+                columns: &[
+                    ("COALESCE(e.amount, 0)", "NUMERIC"),
+                    ("e.slot", "BIGINT"),
+                    ("e.signature", "TEXT"),
+                    ("e.event_index", "INTEGER"),
+                ],
+            },
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Distribution {
+    /// Hex distribution id
+    pub distribution_id: String,
+    pub reward_id: String,
+    pub window_id: String,
+    pub mint: String,
+    pub total_amount: String,
+    pub merkle_root: String,
+    pub num_recipients: i32,
+    /// `pending`, `funded`, `active`, `completed` or `clawedback`
+    pub status: String,
+    /// Deployment (program and cluster) the distribution is initialized on
+    pub deployment: String,
+    /// Distribution PDA, once initialized
+    pub on_chain_address: Option<String>,
+    pub claimed_amount: String,
+    pub claimed_count: i32,
+    pub created_at: i64,
+    pub activated_at: Option<i64>,
+}
+
+#[derive(InputObject, Default)]
+pub struct DistributionFilter {
+    pub deployment: Option<String>,
+    pub reward_id: Option<String>,
+    pub window_id: Option<String>,
+    pub mint: Option<String>,
+    pub status: Option<String>,
+    /// Created at or after (unix seconds)
+    pub from_time: Option<i64>,
+    /// Created at or before (unix seconds)
+    pub to_time: Option<i64>,
+}
+
+#[derive(SimpleObject)]
+pub struct Epoch {
+    pub epoch: i64,
+    pub mint: String,
+    /// Slot the boundary snapshot was pinned to
+    pub slot: i64,
+    pub block_time: Option<i64>,
+    pub holders: i32,
+    pub token_accounts: i32,
+    /// Supply held by snapshotted accounts, in base units
+    pub total_amount: String,
+    /// LST -> SOL rate, if the epoch's rate was recorded
+    pub sol_per_token: Option<String>,
+}
+
+#[derive(InputObject, Default)]
+pub struct EpochFilter {
+    pub mint: Option<String>,
+    pub from_epoch: Option<i64>,
+    pub to_epoch: Option<i64>,
+}
+
+#[derive(SimpleObject)]
+pub struct Allocation {
+    pub reward_id: String,
+    pub window_id: String,
+    pub wallet: String,
+    pub mint: String,
+    /// Normalized share (0-1)
+    pub share: String,
+    pub payout_amount: String,
+    /// Claimed, escrowed or renounced on-chain, or confirmed by the relayer
+    pub claimed: bool,
+}
+
+#[derive(InputObject, Default)]
+pub struct AllocationFilter {
+    pub reward_id: Option<String>,
+    pub window_id: Option<String>,
+    pub wallet: Option<String>,
+    pub mint: Option<String>,
+    /// Hex id of the distribution paying the reward
+    pub distribution_id: Option<String>,
+    /// Smallest payout, in base units
+    pub min_amount: Option<String>,
+    pub claimed: Option<bool>,
+}
+
+#[derive(SimpleObject)]
+pub struct ClaimEvent {
+    /// Deployment the event was indexed from
+    pub deployment: String,
+    pub signature: String,
+    pub event_index: i32,
+    pub slot: i64,
+    pub block_time: Option<i64>,
+    /// Distribution PDA
+    pub distribution: String,
+    /// Hex distribution id, if the PDA belongs to a tracked distribution
+    pub distribution_id: Option<String>,
+    pub kind: String,
+    pub recipient: Option<String>,
+    pub amount: Option<String>,
+    pub leaf_index: Option<i64>,
+    pub referrer: Option<String>,
+    pub bonus: Option<String>,
+    pub destination: Option<String>,
+}
+
+#[derive(InputObject, Default)]
+pub struct ClaimEventFilter {
+    pub deployment: Option<String>,
+    /// Distribution PDA
+    pub distribution: Option<String>,
+    /// Hex distribution id
+    pub distribution_id: Option<String>,
+    pub recipient: Option<String>,
+    pub kinds: Option<Vec<String>>,
+    pub from_slot: Option<i64>,
+    pub to_slot: Option<i64>,
+    /// Block time at or after (unix seconds)
+    pub from_time: Option<i64>,
+    /// Block time at or before (unix seconds)
+    pub to_time: Option<i64>,
+    /// Smallest event amount, in base units
+    pub min_amount: Option<String>,
+}
+
+#[derive(Clone, SimpleObject, Serialize, Deserialize)]
+pub struct Stats {
+    pub distributions: i64,
+    pub active_distributions: i64,
+    /// Sum of distribution totals
+    pub total_allocated: String,
+    /// Sum of claim, referral-claim and escrow event amounts
+    pub total_claimed: String,
+    pub claim_events: i64,
+    /// Distinct wallets with a claim event
+    pub claimers: i64,
+    pub latest_epoch: Option<i64>,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Distributions, newest first by default
+    async fn distributions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: DistributionFilter,
+        #[graphql(default)] sort: DistributionSort,
+        #[graphql(default)] order: SortOrder,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Page<Distribution>> {
+        let push_filters = |query: &mut QueryBuilder<'static, Postgres>| {
+            push_eq(query, "d.deployment", &filter.deployment);
+            push_eq(query, "d.reward_id", &filter.reward_id);
+            push_eq(query, "d.window_id", &filter.window_id);
+            push_eq(query, "d.mint", &filter.mint);
+            push_eq(query, "d.status", &filter.status);
+            push_cmp(
+                query,
+                "EXTRACT(EPOCH FROM d.created_at)",
+                ">=",
+                filter.from_time,
+            );
+            push_cmp(
+                query,
+                "EXTRACT(EPOCH FROM d.created_at)",
+                "<=",
+                filter.to_time,
+            );
+        };
+        fetch_page(
+            ctx.data::<PgPool>()?,
+            &DISTRIBUTIONS,
+            push_filters,
+            (sort.key(), order),
+            (first, after),
+            distribution,
+        )
+        .await
+    }
+
+    /// One distribution by hex id
+    async fn distribution(
+        &self,
+        ctx: &Context<'_>,
+        distribution_id: String,
+    ) -> async_graphql::Result<Option<Distribution>> {
+        let filter = |query: &mut QueryBuilder<'static, Postgres>| {
+            query
+                .push(" AND d.distribution_id = ")
+                .push_bind(distribution_id.to_ascii_lowercase());
+        };
+        let page = fetch_page(
+            ctx.data::<PgPool>()?,
+            &DISTRIBUTIONS,
+            filter,
+            (DistributionSort::default().key(), SortOrder::default()),
+            (Some(1), None),
+            distribution,
+        )
+        .await?;
+        Ok(page.items.into_iter().next())
+    }
+
+    /// Epoch boundary snapshots, newest first by default
+    async fn epochs(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: EpochFilter,
+        #[graphql(default)] sort: EpochSort,
+        #[graphql(default)] order: SortOrder,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Page<Epoch>> {
+        let push_filters = |query: &mut QueryBuilder<'static, Postgres>| {
+            push_eq(query, "s.mint", &filter.mint);
+            push_cmp(query, "s.epoch", ">=", filter.from_epoch);
+            push_cmp(query, "s.epoch", "<=", filter.to_epoch);
+        };
+        fetch_page(
+            ctx.data::<PgPool>()?,
+            &EPOCHS,
+            push_filters,
+            (sort.key(), order),
+            (first, after),
+            epoch,
+        )
+        .await
+    }
+
+    /// Per-wallet allocations, largest first by default
+    async fn allocations(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: AllocationFilter,
+        #[graphql(default)] sort: AllocationSort,
+        #[graphql(default)] order: SortOrder,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Page<Allocation>> {
+        check_amount("minAmount", &filter.min_amount)?;
+        let push_filters = |query: &mut QueryBuilder<'static, Postgres>| {
+            push_eq(query, "p.reward_id", &filter.reward_id);
+            push_eq(query, "p.window_id", &filter.window_id);
+            push_eq(query, "p.wallet", &filter.wallet);
+            push_eq(query, "p.mint", &filter.mint);
+            if let Some(distribution_id) = &filter.distribution_id {
+                query
+                    .push(
+                        " AND p.reward_id IN (SELECT reward_id FROM merkle_distributions WHERE distribution_id = ",
+                    )
+                    .push_bind(distribution_id.to_ascii_lowercase())
+                    .push(")");
+            }
+            push_min_amount(query, "p.payout_amount", &filter.min_amount);
+            match filter.claimed {
+                Some(true) => {
+                    query.push(concat!(" AND ", allocation_claimed!()));
+                }
+                Some(false) => {
+                    query.push(concat!(" AND NOT ", allocation_claimed!()));
+                }
+                None => {}
+            }
+        };
+        fetch_page(
+            ctx.data::<PgPool>()?,
+            &ALLOCATIONS,
+            push_filters,
+            (sort.key(), order),
+            (first, after),
+            allocation,
+        )
+        .await
+    }
+
+    /// Finalized claim events, newest first by default
+    async fn claim_events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: ClaimEventFilter,
+        #[graphql(default)] sort: ClaimEventSort,
+        #[graphql(default)] order: SortOrder,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> async_graphql::Result<Page<ClaimEvent>> {
+        check_amount("minAmount", &filter.min_amount)?;
+        let push_filters = |query: &mut QueryBuilder<'static, Postgres>| {
+            push_eq(query, "e.deployment", &filter.deployment);
+            push_eq(query, "e.distribution", &filter.distribution);
+            push_eq(query, "d.distribution_id", &filter.distribution_id);
+            push_eq(query, "e.recipient", &filter.recipient);
+            if let Some(kinds) = &filter.kinds {
+                query
+                    .push(" AND e.kind = ANY(")
+                    .push_bind(kinds.clone())
+                    .push(")");
+            }
+            push_cmp(query, "e.slot", ">=", filter.from_slot);
+            push_cmp(query, "e.slot", "<=", filter.to_slot);
+            push_cmp(
+                query,
+                "EXTRACT(EPOCH FROM e.block_time)",
+                ">=",
+                filter.from_time,
+            );
+            push_cmp(
+                query,
+                "EXTRACT(EPOCH FROM e.block_time)",
+                "<=",
+                filter.to_time,
+            );
+            push_min_amount(query, "e.amount", &filter.min_amount);
+        };
+        fetch_page(
+            ctx.data::<PgPool>()?,
+            &CLAIM_EVENTS,
+            push_filters,
+            (sort.key(), order),
+            (first, after),
+            claim_event,
+        )
+        .await
+    }
+
+    /// Totals across all distributions, optionally for one mint
+    async fn stats(&self, ctx: &Context<'_>, mint: Option<String>) -> async_graphql::Result<Stats> {
+        let pool = ctx.data::<PgPool>()?;
+        let load = async {
+            let row = sqlx::query(STATS)
+                .bind(&mint)
+                .bind(CLAIM_KINDS.map(str::to_string).to_vec())
+                .fetch_one(pool)
+                .await
+                .map_err(db_error)?;
+            stats(&row).map_err(db_error)
+        };
+        match ctx.data_opt::<ResponseCache<Stats>>() {
+            Some(cache) => {
+                let key = mint.as_deref().unwrap_or("*");
+                cache.get_or_try_insert(key, load).await
+            }
+            None => load.await,
+        }
+    }
+}
+
+const DISTRIBUTIONS: Listing = Listing {
+    columns: "
+    d.distribution_id, d.reward_id, d.window_id, d.mint, d.total_amount::TEXT AS total_amount,
+    d.merkle_root, d.num_recipients, d.status, d.deployment, d.on_chain_address,
+    d.claimed_amount::TEXT AS claimed_amount, d.claimed_count,
+    EXTRACT(EPOCH FROM d.created_at)::BIGINT AS created_at,
+    EXTRACT(EPOCH FROM d.activated_at)::BIGINT AS activated_at
+    ",
+    from: "
+    FROM merkle_distributions d
+    WHERE TRUE
+    ",
+};
+
+const EPOCHS: Listing = Listing {
+    columns: "
+    s.epoch, s.mint, s.slot, EXTRACT(EPOCH FROM s.block_time)::BIGINT AS block_time,
+    s.holders, s.token_accounts, s.total_amount::TEXT AS total_amount,
+    r.sol_per_token::TEXT AS sol_per_token
+    ",
+    from: "
+    FROM epoch_snapshots s
+    LEFT JOIN epoch_exchange_rates r ON r.epoch = s.epoch AND r.mint = s.mint
+    WHERE TRUE
+    ",
+};
+
+const ALLOCATIONS: Listing = Listing {
+    columns: concat!(
+        "p.reward_id, p.window_id, p.wallet, p.mint, p.share::TEXT AS share,
+        p.payout_amount::TEXT AS payout_amount, ",
+        allocation_claimed!(),
+        " AS claimed"
+    ),
+    from: "
+    FROM reward_payouts_preview p
+    WHERE p.payout_amount > 0
+    ",
+};
+
+const CLAIM_EVENTS: Listing = Listing {
+    columns: "
+    e.deployment, e.signature, e.event_index, e.slot, EXTRACT(EPOCH FROM e.block_time)::BIGINT AS block_time,
+    e.distribution, d.distribution_id, e.kind, e.recipient, e.amount::TEXT AS amount,
+    e.leaf_index, e.referrer, e.bonus::TEXT AS bonus, e.destination
+    ",
+    from: "
+    FROM merkle_claim_events e
+    LEFT JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+    WHERE TRUE
+    ",
+};
+
+const STATS: &str = "
+    WITH dists AS (
+      SELECT * FROM merkle_distributions WHERE $1::TEXT IS NULL OR mint = $1
+    ),
+    claims AS (
+      SELECT e.*
+      FROM merkle_claim_events e
+      JOIN dists d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+      WHERE e.kind = ANY($2)
+    )
+    SELECT
+      (SELECT COUNT(*) FROM dists) AS distributions,
+      (SELECT COUNT(*) FROM dists WHERE status = 'active') AS active_distributions,
+      (SELECT COALESCE(SUM(total_amount), 0)::TEXT FROM dists) AS total_allocated,
+      (SELECT COALESCE(SUM(amount), 0)::TEXT FROM claims) AS total_claimed,
+      (SELECT COUNT(*) FROM claims) AS claim_events,
+      (SELECT COUNT(DISTINCT recipient) FROM claims) AS claimers,
+      (SELECT MAX(epoch) FROM epoch_snapshots WHERE $1::TEXT IS NULL OR mint = $1) AS latest_epoch
+";
+
+/// Columns and `FROM … WHERE …` clause of a list query
+///
+/// Filters append `AND` clauses after `from`.
+struct Listing {
+    columns: &'static str,
+    from: &'static str,
+}
+
+/// Fetch the page of a listing after `after`, in `sort` order
+///
+/// Pages are found by row comparison on the sort key rather than OFFSET, so
+/// deep pages cost the same as the first and rows inserted meanwhile don't
+/// shift them.
+async fn fetch_page<T: OutputType>(
+    pool: &PgPool,
+    listing: &Listing,
+    push_filters: impl Fn(&mut QueryBuilder<'static, Postgres>),
+    (sort, order): (&SortKey, SortOrder),
+    (first, after): (Option<i64>, Option<String>),
+    decode: fn(&PgRow) -> Result<T, sqlx::Error>,
+) -> async_graphql::Result<Page<T>> {
+    let first = first.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let after = after
+        .as_deref()
+        .map(|cursor| decode_cursor(cursor, sort))
+        .transpose()?;
+    let columns: Vec<&str> = sort.columns.iter().map(|&(column, _)| column).collect();
+
+    let mut query = QueryBuilder::new(format!("SELECT {}", listing.columns));
+    for (i, column) in columns.iter().enumerate() {
+        query.push(format!(", ({column})::TEXT AS cursor_{i}"));
+    }
+    query.push(" ").push(listing.from);
+    push_filters(&mut query);
+
+    if let Some(keys) = after {
+        query.push(format!(" AND ({}) {} (", columns.join(", "), order.after()));
+        let mut values = query.separated(", ");
+        for (&(_, sql_type), key) in sort.columns.iter().zip(keys) {
+            values.push_bind(key);
+            values.push_unseparated(format!("::{sql_type}"));
+        }
+        query.push(")");
+    }
+
+    let order_by: Vec<String> = columns
+        .iter()
+        .map(|column| format!("{column} {}", order.sql()))
+        .collect();
+    query
+        .push(format!(" ORDER BY {} LIMIT ", order_by.join(", ")))
+        .push_bind(first + 1);
+
+    let mut rows = query.build().fetch_all(pool).await.map_err(db_error)?;
+    let has_next_page = rows.len() as i64 > first;
+    rows.truncate(first as usize);
+
+    let end_cursor = rows
+        .last()
+        .map(|row| encode_cursor(row, sort))
+        .transpose()
+        .map_err(db_error)?;
+    let items = rows
+        .iter()
+        .map(decode)
+        .collect::<Result<_, _>>()
+        .map_err(db_error)?;
+
+    Ok(Page {
+        items,
+        page_info: PageInfo {
+            end_cursor,
+            has_next_page,
+        },
+    })
+}
+
+fn encode_cursor(row: &PgRow, sort: &SortKey) -> Result<String, sqlx::Error> {
+    let keys = (0..sort.columns.len())
+        .map(|i| row.try_get(format!("cursor_{i}").as_str()))
+        .collect::<Result<_, _>>()?;
+    Ok(OpaqueCursor(Position {
+        sort: sort.name.to_string(),
+        keys,
+    })
+    .encode_cursor())
+}
+
+fn decode_cursor(cursor: &str, sort: &SortKey) -> async_graphql::Result<Vec<String>> {
+    let OpaqueCursor(position) = OpaqueCursor::<Position>::decode_cursor(cursor)
+        .map_err(|_| async_graphql::Error::new("invalid cursor"))?;
+    if position.sort != sort.name || position.keys.len() != sort.columns.len() {
+        return Err(async_graphql::Error::new(
+            "cursor was issued for a different sort",
+        ));
+    }
+    Ok(position.keys)
+}
+
+/// Reject amounts that aren't non-negative integers before they reach SQL
+fn check_amount(field: &str, value: &Option<String>) -> async_graphql::Result<()> {
+    match value {
+        Some(value) if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) => Err(
+            async_graphql::Error::new(format!("{field} must be a base-unit integer")),
+        ),
+        _ => Ok(()),
+    }
+}
+
+fn push_eq(query: &mut QueryBuilder<'static, Postgres>, column: &str, value: &Option<String>) {
+    if let Some(value) = value {
+        query
+            .push(format!(" AND {column} = "))
+            .push_bind(value.clone());
+    }
+}
+
+fn push_cmp(
+    query: &mut QueryBuilder<'static, Postgres>,
+    column: &str,
+    op: &str,
+    value: Option<i64>,
+) {
+    if let Some(value) = value {
+        query.push(format!(" AND {column} {op} ")).push_bind(value);
+    }
+}
+
+fn push_min_amount(
+    query: &mut QueryBuilder<'static, Postgres>,
+    column: &str,
+    value: &Option<String>,
+) {
+    if let Some(value) = value {
+        query
+            .push(format!(" AND {column} >= "))
+            .push_bind(value.clone())
+            .push("::NUMERIC");
+    }
+}
+
+/// Log the database error and return a generic one to the client
+fn db_error(e: sqlx::Error) -> async_graphql::Error {
+    eprintln!("❌ GraphQL query failed: {e}");
+    async_graphql::Error::new("database query failed")
+}
+
+fn distribution(row: &PgRow) -> Result<Distribution, sqlx::Error> {
+    Ok(Distribution {
+        distribution_id: row.try_get("distribution_id")?,
+        reward_id: row.try_get("reward_id")?,
+        window_id: row.try_get("window_id")?,
+        mint: row.try_get("mint")?,
+        total_amount: row.try_get("total_amount")?,
+        merkle_root: row.try_get("merkle_root")?,
+        num_recipients: row.try_get("num_recipients")?,
+        status: row.try_get("status")?,
+        deployment: row.try_get("deployment")?,
+        on_chain_address: row.try_get("on_chain_address")?,
+        claimed_amount: row.try_get("claimed_amount")?,
+        claimed_count: row.try_get("claimed_count")?,
+        created_at: row.try_get("created_at")?,
+        activated_at: row.try_get("activated_at")?,
+    })
+}
+
+fn epoch(row: &PgRow) -> Result<Epoch, sqlx::Error> {
+    Ok(Epoch {
+        epoch: row.try_get("epoch")?,
+        mint: row.try_get("mint")?,
+        slot: row.try_get("slot")?,
+        block_time: row.try_get("block_time")?,
+        holders: row.try_get("holders")?,
+        token_accounts: row.try_get("token_accounts")?,
+        total_amount: row.try_get("total_amount")?,
+        sol_per_token: row.try_get("sol_per_token")?,
+    })
+}
+
+fn allocation(row: &PgRow) -> Result<Allocation, sqlx::Error> {
+    Ok(Allocation {
+        reward_id: row.try_get("reward_id")?,
+        window_id: row.try_get("window_id")?,
+        wallet: row.try_get("wallet")?,
+        mint: row.try_get("mint")?,
+        share: row.try_get("share")?,
+        payout_amount: row.try_get("payout_amount")?,
+        claimed: row.try_get("claimed")?,
+    })
+}
+
+fn claim_event(row: &PgRow) -> Result<ClaimEvent, sqlx::Error> {
+    Ok(ClaimEvent {
+        deployment: row.try_get("deployment")?,
+        signature: row.try_get("signature")?,
+        event_index: row.try_get("event_index")?,
+        slot: row.try_get("slot")?,
+        block_time: row.try_get("block_time")?,
+        distribution: row.try_get("distribution")?,
+        distribution_id: row.try_get("distribution_id")?,
+        kind: row.try_get("kind")?,
+        recipient: row.try_get("recipient")?,
+        amount: row.try_get("amount")?,
+        leaf_index: row.try_get("leaf_index")?,
+        referrer: row.try_get("referrer")?,
+        bonus: row.try_get("bonus")?,
+        destination: row.try_get("destination")?,
+    })
+}
+
+fn stats(row: &PgRow) -> Result<Stats, sqlx::Error> {
+    Ok(Stats {
+        distributions: row.try_get("distributions")?,
+        active_distributions: row.try_get("active_distributions")?,
+        total_allocated: row.try_get("total_allocated")?,
+        total_claimed: row.try_get("total_claimed")?,
+        claim_events: row.try_get("claim_events")?,
+        claimers: row.try_get("claimers")?,
+        latest_epoch: row.try_get("latest_epoch")?,
+    })
+}
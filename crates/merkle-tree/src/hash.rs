@@ -0,0 +1,137 @@
+//! Leaf and node hashing
+//!
+//! Must match `compute_leaf` / `hash_pair` in the merkle-distributor program
+//! exactly, for both hash kinds (see `tests/onchain_parity.rs`). The hashing
+//! itself lives in the `merkle-core` crate, which the program and claim UIs
+//! also use; this module adapts it to `Pubkey` and `Allocation`. The builder
+//! produces `HashScheme::V1`, sorted-pair trees.
+
+use solana_pubkey::Pubkey;
+
+use crate::tree::Allocation;
+
+pub use merkle_core::{
+    HashKind, HashScheme, ProofMode, DOMAIN_SEPARATOR, LEAF_PREFIX, MAX_PROOF_LEN, NODE_PREFIX,
+    VESTING_DOMAIN_SEPARATOR,
+};
+
+/// Leaf preimage layout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LeafSchema {
+    /// `DOMAIN_SEPARATOR || distribution_id || recipient || amount_le`
+    /// (what the program verifies today)
+    #[default]
+    V1,
+    /// `VESTING_DOMAIN_SEPARATOR || distribution_id || recipient || amount_le ||
+    /// unlock_at_le || expires_at_le`, for a program that enforces per-leaf
+    /// vesting and expiry. Unset timestamps are encoded as 0.
+    Vesting,
+}
+
+impl LeafSchema {
+    /// Domain separator that prefixes this schema's leaves
+    pub fn domain_separator(self) -> &'static [u8] {
+        match self {
+            LeafSchema::V1 => DOMAIN_SEPARATOR,
+            LeafSchema::Vesting => VESTING_DOMAIN_SEPARATOR,
+        }
+    }
+
+    /// Schema identified by a domain separator
+    pub fn from_domain_separator(domain: &[u8]) -> Option<Self> {
+        [LeafSchema::V1, LeafSchema::Vesting]
+            .into_iter()
+            .find(|schema| schema.domain_separator() == domain)
+    }
+
+    /// Lowercase name used in JSON output (`v1`, `vesting`)
+    pub fn name(self) -> &'static str {
+        match self {
+            LeafSchema::V1 => "v1",
+            LeafSchema::Vesting => "vesting",
+        }
+    }
+
+    /// Exact bytes hashed into the leaf of `allocation`
+    pub fn preimage(self, distribution_id: &[u8; 32], allocation: &Allocation) -> Vec<u8> {
+        let mut preimage = self.domain_separator().to_vec();
+        preimage.extend_from_slice(distribution_id);
+        preimage.extend_from_slice(allocation.recipient.as_ref());
+        preimage.extend_from_slice(&allocation.amount.to_le_bytes());
+        if self == LeafSchema::Vesting {
+            preimage.extend_from_slice(&allocation.unlock_at.unwrap_or(0).to_le_bytes());
+            preimage.extend_from_slice(&allocation.expires_at.unwrap_or(0).to_le_bytes());
+        }
+        preimage
+    }
+
+    /// Leaf hash of `allocation` under this schema
+    pub fn leaf(
+        self,
+        kind: HashKind,
+        distribution_id: &[u8; 32],
+        allocation: &Allocation,
+    ) -> [u8; 32] {
+        match self {
+            LeafSchema::V1 => compute_leaf(
+                kind,
+                distribution_id,
+                &allocation.recipient,
+                allocation.amount,
+            ),
+            LeafSchema::Vesting => compute_vesting_leaf(
+                kind,
+                distribution_id,
+                &allocation.recipient,
+                allocation.amount,
+                allocation.unlock_at.unwrap_or(0),
+                allocation.expires_at.unwrap_or(0),
+            ),
+        }
+    }
+}
+
+/// Compute leaf hash using domain separation
+pub fn compute_leaf(
+    kind: HashKind,
+    distribution_id: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+) -> [u8; 32] {
+    merkle_core::compute_leaf(
+        kind,
+        HashScheme::V1,
+        distribution_id,
+        &recipient.to_bytes(),
+        amount,
+    )
+}
+
+/// Compute a vesting leaf hash (unlock / expiry committed in the leaf)
+pub fn compute_vesting_leaf(
+    kind: HashKind,
+    distribution_id: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+    unlock_at: i64,
+    expires_at: i64,
+) -> [u8; 32] {
+    merkle_core::compute_vesting_leaf(
+        kind,
+        distribution_id,
+        &recipient.to_bytes(),
+        amount,
+        unlock_at,
+        expires_at,
+    )
+}
+
+/// Hash two nodes, sorting for determinism
+pub fn hash_pair(kind: HashKind, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    merkle_core::hash_pair(kind, HashScheme::V1, a, b)
+}
+
+/// Lowercase hex encoding (roots, ids and proof nodes in JSON output)
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
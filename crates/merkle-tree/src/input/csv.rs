@@ -0,0 +1,176 @@
+//! CSV allocation input
+//!
+//! Reads `(recipient, amount)` rows from CSV exports such as the ones
+//! produced by `export-reward-csv`. Extra columns are ignored.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use solana_pubkey::Pubkey;
+
+use super::{parse_amount, InputError};
+use crate::tree::Allocation;
+
+/// CSV reader options
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    /// Header of the recipient column (case-insensitive)
+    pub recipient_column: String,
+    /// Header of the amount column (case-insensitive)
+    pub amount_column: String,
+    /// Amounts are decimal token amounts with this many decimals
+    /// (`None` = integer base units)
+    pub decimals: Option<u8>,
+    /// Field delimiter
+    pub delimiter: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            recipient_column: "wallet".to_string(),
+            amount_column: "amount".to_string(),
+            decimals: None,
+            delimiter: b',',
+        }
+    }
+}
+
+/// Read allocations from a CSV file
+pub fn read_csv(
+    path: impl AsRef<Path>,
+    options: &CsvOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    read_csv_from(File::open(path)?, options)
+}
+
+/// Read allocations from any CSV source
+pub fn read_csv_from<R: Read>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+
+    let headers = reader.headers().map_err(|e| malformed(1, e))?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| InputError::MissingColumn(name.to_string()))
+    };
+    let recipient_idx = column(&options.recipient_column)?;
+    let amount_idx = column(&options.amount_column)?;
+
+    let mut allocations = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map(|p| p.line()).unwrap_or(0);
+            malformed(line, e)
+        })?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+
+        let recipient = record.get(recipient_idx).unwrap_or("");
+        let amount = record.get(amount_idx).unwrap_or("");
+
+        let recipient = Pubkey::from_str(recipient).map_err(|_| InputError::InvalidRecipient {
+            line,
+            value: recipient.to_string(),
+        })?;
+        let amount =
+            parse_amount(amount, options.decimals).map_err(|reason| InputError::InvalidAmount {
+                line,
+                value: amount.to_string(),
+                reason,
+            })?;
+
+        allocations.push(Allocation::new(recipient, amount));
+    }
+
+    Ok(allocations)
+}
+
+fn malformed(line: u64, error: ::csv::Error) -> InputError {
+    InputError::Malformed {
+        line,
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn read(input: &str, options: &CsvOptions) -> Result<Vec<Allocation>, InputError> {
+        read_csv_from(input.as_bytes(), options)
+    }
+
+    #[test]
+    fn reads_rows_in_order_ignoring_extra_columns() {
+        let input = format!(
+            "Wallet,share,AMOUNT\n{},0.5,100\n {} ,0.25, 7 \n",
+            key(1),
+            key(2)
+        );
+
+        assert_eq!(
+            read(&input, &CsvOptions::default()).unwrap(),
+            [Allocation::new(key(1), 100), Allocation::new(key(2), 7)]
+        );
+    }
+
+    #[test]
+    fn decimal_amounts_are_scaled() {
+        let input = format!("wallet;amount\n{};1.5\n", key(1));
+        let options = CsvOptions {
+            decimals: Some(9),
+            delimiter: b';',
+            ..CsvOptions::default()
+        };
+
+        assert_eq!(
+            read(&input, &options).unwrap(),
+            [Allocation::new(key(1), 1_500_000_000)]
+        );
+    }
+
+    #[test]
+    fn missing_column_is_reported() {
+        let input = format!("recipient,amount\n{},1\n", key(1));
+
+        assert!(matches!(
+            read(&input, &CsvOptions::default()),
+            Err(InputError::MissingColumn(column)) if column == "wallet"
+        ));
+    }
+
+    #[test]
+    fn bad_rows_report_their_line() {
+        let input = format!("wallet,amount\n{},1\nnot-a-key,2\n", key(1));
+        assert!(matches!(
+            read(&input, &CsvOptions::default()),
+            Err(InputError::InvalidRecipient { line: 3, value }) if value == "not-a-key"
+        ));
+
+        let input = format!("wallet,amount\n{},1\n{},1.5\n", key(1), key(2));
+        assert!(matches!(
+            read(&input, &CsvOptions::default()),
+            Err(InputError::InvalidAmount { line: 3, value, .. }) if value == "1.5"
+        ));
+
+        let input = format!("wallet,amount\n{},1\n{},2,extra\n", key(1), key(2));
+        assert!(matches!(
+            read(&input, &CsvOptions::default()),
+            Err(InputError::Malformed { line: 3, .. })
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+//! JSON / JSONL allocation input
+//!
+//! Schema of one allocation (a JSON file holds an array of them, a JSONL
+//! file one per line):
+//!
+//! ```json
+//! {
+//!   "recipient": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+//!   "amount": "1500000000",
+//!   "unlock_at": 1735689600,
+//!   "expires_at": 1767225600
+//! }
+//! ```
+//!
+//! - `recipient` — base58 pubkey (required)
+//! - `amount` — integer or string; base units, or a decimal string when
+//!   `decimals` is set (required)
+//! - `unlock_at` — vesting unlock, unix seconds (optional)
+//! - `expires_at` — claim expiry, unix seconds (optional)
+//!
+//! All invalid records are reported together rather than stopping at the
+//! first one.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde_json::Value;
+use solana_pubkey::Pubkey;
+
+use super::{parse_amount, InputError};
+use crate::tree::Allocation;
+
+/// How strictly records are checked
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Validation {
+    /// Unknown fields and zero amounts are errors
+    #[default]
+    Strict,
+    /// Unknown fields are ignored and zero-amount records are skipped
+    Lenient,
+}
+
+/// JSON reader options
+#[derive(Clone, Debug, Default)]
+pub struct JsonOptions {
+    pub validation: Validation,
+    /// String amounts are decimal token amounts with this many decimals
+    /// (`None` = integer base units)
+    pub decimals: Option<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AmountValue {
+    Integer(u64),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+struct Record {
+    recipient: String,
+    amount: AmountValue,
+    #[serde(default)]
+    unlock_at: Option<i64>,
+    #[serde(default)]
+    expires_at: Option<i64>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+/// Read allocations from a JSON file holding an array of records
+pub fn read_json(
+    path: impl AsRef<Path>,
+    options: &JsonOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    read_json_str(&fs::read_to_string(path)?, options)
+}
+
+/// Read allocations from a JSON array
+pub fn read_json_str(input: &str, options: &JsonOptions) -> Result<Vec<Allocation>, InputError> {
+    let values: Vec<Value> = serde_json::from_str(input).map_err(|e| InputError::Malformed {
+        line: e.line() as u64,
+        message: e.to_string(),
+    })?;
+
+    collect(
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (format!("entry {i}"), Ok(value))),
+        options,
+    )
+}
+
+/// Read allocations from a JSONL file (one record per line)
+pub fn read_jsonl(
+    path: impl AsRef<Path>,
+    options: &JsonOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    read_jsonl_str(&fs::read_to_string(path)?, options)
+}
+
+/// Read allocations from JSONL text
+pub fn read_jsonl_str(input: &str, options: &JsonOptions) -> Result<Vec<Allocation>, InputError> {
+    collect(
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let value = serde_json::from_str(line).map_err(|e| e.to_string());
+                (format!("line {}", i + 1), value)
+            }),
+        options,
+    )
+}
+
+/// Validate every record, aggregating errors
+fn collect(
+    records: impl Iterator<Item = (String, Result<Value, String>)>,
+    options: &JsonOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    let mut allocations = Vec::new();
+    let mut errors = Vec::new();
+
+    for (location, value) in records {
+        let parsed = value.and_then(|value| parse_record(value, options));
+        match parsed {
+            Ok(Some(allocation)) => allocations.push(allocation),
+            Ok(None) => {}
+            Err(message) => errors.push(InputError::InvalidRecord {
+                record: location,
+                message,
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(allocations)
+    } else {
+        Err(InputError::Multiple(errors))
+    }
+}
+
+/// Parse one record; `Ok(None)` means it was skipped (lenient mode)
+fn parse_record(value: Value, options: &JsonOptions) -> Result<Option<Allocation>, String> {
+    let record: Record = serde_json::from_value(value).map_err(|e| e.to_string())?;
+
+    if options.validation == Validation::Strict && !record.unknown.is_empty() {
+        let fields: Vec<&str> = record.unknown.keys().map(String::as_str).collect();
+        return Err(format!("unknown field(s): {}", fields.join(", ")));
+    }
+
+    let recipient = Pubkey::from_str(&record.recipient).map_err(|_| {
+        format!(
+            "invalid recipient `{}`: not a base58 pubkey",
+            record.recipient
+        )
+    })?;
+
+    let amount = match record.amount {
+        AmountValue::Integer(amount) => amount,
+        AmountValue::Text(text) => parse_amount(&text, options.decimals)
+            .map_err(|reason| format!("invalid amount `{text}`: {reason}"))?,
+    };
+
+    if amount == 0 {
+        return match options.validation {
+            Validation::Strict => Err("amount must be greater than zero".to_string()),
+            Validation::Lenient => Ok(None),
+        };
+    }
+
+    if let (Some(unlock_at), Some(expires_at)) = (record.unlock_at, record.expires_at) {
+        if expires_at <= unlock_at {
+            return Err(format!(
+                "expires_at ({expires_at}) must be after unlock_at ({unlock_at})"
+            ));
+        }
+    }
+
+    Ok(Some(Allocation {
+        recipient,
+        amount,
+        unlock_at: record.unlock_at,
+        expires_at: record.expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn lenient() -> JsonOptions {
+        JsonOptions {
+            validation: Validation::Lenient,
+            ..JsonOptions::default()
+        }
+    }
+
+    /// Record locations of an aggregated error
+    fn locations(error: InputError) -> Vec<String> {
+        match error {
+            InputError::Multiple(errors) => errors
+                .into_iter()
+                .map(|e| match e {
+                    InputError::InvalidRecord { record, .. } => record,
+                    other => panic!("unexpected error {other}"),
+                })
+                .collect(),
+            other => panic!("unexpected error {other}"),
+        }
+    }
+
+    #[test]
+    fn array_records_with_vesting_fields() {
+        let input = format!(
+            r#"[
+                {{"recipient": "{}", "amount": 100}},
+                {{"recipient": "{}", "amount": "1.5", "unlock_at": 10, "expires_at": 20}}
+            ]"#,
+            key(1),
+            key(2)
+        );
+        let options = JsonOptions {
+            decimals: Some(2),
+            ..JsonOptions::default()
+        };
+
+        assert_eq!(
+            read_json_str(&input, &options).unwrap(),
+            [
+                Allocation::new(key(1), 100),
+                Allocation {
+                    recipient: key(2),
+                    amount: 150,
+                    unlock_at: Some(10),
+                    expires_at: Some(20),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn jsonl_skips_blank_lines_and_numbers_the_rest() {
+        let input = format!(
+            "{{\"recipient\": \"{}\", \"amount\": 1}}\n\n{{\"recipient\": \"bad\", \"amount\": 1}}\n",
+            key(1)
+        );
+
+        let error = read_jsonl_str(&input, &JsonOptions::default()).unwrap_err();
+        assert_eq!(locations(error), ["line 3"]);
+    }
+
+    #[test]
+    fn strict_mode_reports_every_invalid_record() {
+        let input = format!(
+            r#"[
+                {{"recipient": "{}", "amount": 0}},
+                {{"recipient": "{}", "amount": 5, "memo": "x"}},
+                {{"recipient": "{}", "amount": 5, "unlock_at": 20, "expires_at": 20}},
+                {{"recipient": "{}", "amount": 5}}
+            ]"#,
+            key(1),
+            key(2),
+            key(3),
+            key(4)
+        );
+
+        let error = read_json_str(&input, &JsonOptions::default()).unwrap_err();
+        assert_eq!(locations(error), ["entry 0", "entry 1", "entry 2"]);
+    }
+
+    #[test]
+    fn lenient_mode_skips_zero_amounts_and_unknown_fields() {
+        let input = format!(
+            r#"[
+                {{"recipient": "{}", "amount": 0}},
+                {{"recipient": "{}", "amount": 5, "memo": "x"}}
+            ]"#,
+            key(1),
+            key(2)
+        );
+
+        assert_eq!(
+            read_json_str(&input, &lenient()).unwrap(),
+            [Allocation::new(key(2), 5)]
+        );
+    }
+
+    #[test]
+    fn malformed_json_reports_its_line() {
+        assert!(matches!(
+            read_json_str("[\n{\"recipient\": }\n]", &JsonOptions::default()),
+            Err(InputError::Malformed { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,92 @@
+//! Allocation input readers
+//!
+//! Every reader produces `Allocation`s in source order (which becomes the
+//! claim index order) and reports bad rows with their line or row number.
+
+pub mod csv;
+pub mod json;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod splits;
+
+use std::io;
+
+use thiserror::Error;
+
+/// Errors raised while reading allocations
+#[derive(Debug, Error)]
+pub enum InputError {
+    #[error("failed to read input: {0}")]
+    Io(#[from] io::Error),
+    #[error("line {line}: malformed row: {message}")]
+    Malformed { line: u64, message: String },
+    #[error("missing column `{0}`")]
+    MissingColumn(String),
+    #[error("line {line}: invalid recipient `{value}`: not a base58 pubkey")]
+    InvalidRecipient { line: u64, value: String },
+    #[error("line {line}: invalid amount `{value}`: {reason}")]
+    InvalidAmount {
+        line: u64,
+        value: String,
+        reason: String,
+    },
+    #[error("{record}: {message}")]
+    InvalidRecord { record: String, message: String },
+    #[error("{} invalid records:\n{}", .0.len(), join_errors(.0))]
+    Multiple(Vec<InputError>),
+}
+
+fn join_errors(errors: &[InputError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("  {e}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parse a token amount into base units
+///
+/// With `decimals`, `value` may be a decimal string (`"1.5"` with 9 decimals
+/// is `1_500_000_000`); without, it must be an integer in base units.
+/// Never goes through floating point.
+pub fn parse_amount(value: &str, decimals: Option<u8>) -> Result<u64, String> {
+    let (whole, fraction) = match value.split_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (value, ""),
+    };
+
+    let decimals = u32::from(decimals.unwrap_or(0));
+    if fraction.len() > decimals as usize {
+        return Err(format!("more than {decimals} decimal places"));
+    }
+    if whole.is_empty() && fraction.is_empty() {
+        return Err("empty amount".to_string());
+    }
+    if !whole
+        .chars()
+        .chain(fraction.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return Err("not a non-negative number".to_string());
+    }
+
+    let scale = 10u64
+        .checked_pow(decimals)
+        .ok_or_else(|| "too many decimals".to_string())?;
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| "overflows u64")?
+    };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        let padding = 10u64.pow(decimals - fraction.len() as u32);
+        fraction.parse::<u64>().map_err(|_| "overflows u64")? * padding
+    };
+
+    whole
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction))
+        .ok_or_else(|| "overflows u64".to_string())
+}
@@ -0,0 +1,206 @@
+//! Postgres allocation input (`postgres` feature)
+//!
+//! Streams a reward's payouts straight from the indexer database into a
+//! `StreamingBuilder`, replacing the `export-reward-csv` → CSV → builder
+//! round trip. Rows arrive incrementally through a sqlx cursor and leaves go
+//! to disk as they are read, so memory stays bounded however many
+//! recipients the reward has.
+//!
+//! Payouts are read through `merkle_store::allocations`, which selects them
+//! exactly as `export-reward-csv` does (`reward_payouts_preview`, positive
+//! amounts, ignored wallets excluded, largest first), so the tree matches
+//! one built from that CSV.
+
+use std::path::Path;
+use std::pin::pin;
+use std::str::FromStr;
+
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use merkle_store::allocations::{self, Payout};
+use merkle_store::StoreError;
+use solana_pubkey::Pubkey;
+use sqlx::postgres::PgPool;
+use sqlx::PgExecutor;
+use thiserror::Error;
+
+use super::InputError;
+use crate::error::BuildError;
+use crate::streaming::{DiskTree, StreamingBuilder};
+use crate::summary::suggest_distribution_id;
+use crate::tree::Allocation;
+
+/// Errors raised while building from the database
+#[derive(Debug, Error)]
+pub enum PostgresError {
+    #[error("database query failed: {0}")]
+    Query(#[from] sqlx::Error),
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Input(#[from] InputError),
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error("no payouts found for reward {0}")]
+    NoPayouts(String),
+    #[error("reward {reward_id} pays out {num_mints} mints; expected exactly one")]
+    MultipleMints { reward_id: String, num_mints: u64 },
+    #[error("streamed payouts do not match their aggregate: expected {expected} recipients totalling {expected_total}, read {read} totalling {read_total}")]
+    Inconsistent {
+        expected: u64,
+        expected_total: u64,
+        read: u64,
+        read_total: u128,
+    },
+}
+
+/// Which payouts to read
+#[derive(Clone, Debug, Default)]
+pub struct PayoutQuery {
+    pub reward_id: String,
+    /// Wallets to exclude (the `IGNORE_WALLETS` safety net)
+    pub ignored_wallets: Vec<String>,
+}
+
+impl PayoutQuery {
+    /// All payouts of `reward_id`
+    pub fn new(reward_id: impl Into<String>) -> Self {
+        Self {
+            reward_id: reward_id.into(),
+            ignored_wallets: Vec::new(),
+        }
+    }
+
+    /// Also exclude the comma-separated wallets in `IGNORE_WALLETS`, like
+    /// the TypeScript jobs
+    pub fn ignoring_env(mut self) -> Self {
+        if let Ok(wallets) = std::env::var("IGNORE_WALLETS") {
+            self.ignored_wallets.extend(
+                wallets
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|w| !w.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        self
+    }
+}
+
+/// Aggregate of a reward's payouts, read before streaming them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RewardPayouts {
+    pub reward_id: String,
+    pub window_id: String,
+    pub mint: Pubkey,
+    pub total_amount: u64,
+    pub num_recipients: u64,
+}
+
+/// Result of `build_from_postgres`
+pub struct PostgresBuild {
+    pub tree: DiskTree,
+    /// `suggest_distribution_id` of the reward, which the leaves are bound to
+    pub distribution_id: [u8; 32],
+    pub payouts: RewardPayouts,
+}
+
+/// Read the count, total, mint and window of a reward's payouts
+pub async fn reward_payouts<'e>(
+    executor: impl PgExecutor<'e>,
+    query: &PayoutQuery,
+) -> Result<RewardPayouts, PostgresError> {
+    let totals =
+        allocations::reward_totals(executor, &query.reward_id, &query.ignored_wallets).await?;
+
+    let (Some(mint), Some(window_id)) = (totals.mint, totals.window_id) else {
+        return Err(PostgresError::NoPayouts(query.reward_id.clone()));
+    };
+    if totals.num_mints != 1 {
+        return Err(PostgresError::MultipleMints {
+            reward_id: query.reward_id.clone(),
+            num_mints: totals.num_mints,
+        });
+    }
+
+    Ok(RewardPayouts {
+        reward_id: query.reward_id.clone(),
+        window_id,
+        mint: Pubkey::from_str(&mint).map_err(|_| InputError::InvalidRecord {
+            record: format!("reward {}", query.reward_id),
+            message: format!("invalid mint `{mint}`: not a base58 pubkey"),
+        })?,
+        total_amount: totals.total_amount,
+        num_recipients: totals.num_recipients,
+    })
+}
+
+/// Stream a reward's payouts as allocations, in `export-reward-csv` order
+pub fn stream_payouts<'e>(
+    executor: impl PgExecutor<'e> + 'e,
+    query: &'e PayoutQuery,
+) -> impl Stream<Item = Result<Allocation, PostgresError>> + 'e {
+    allocations::reward_payouts(executor, &query.reward_id, &query.ignored_wallets)
+        .enumerate()
+        .map(|(row_index, payout)| allocation(row_index as u64 + 1, payout?))
+}
+
+fn allocation(row_number: u64, payout: Payout) -> Result<Allocation, PostgresError> {
+    let recipient = Pubkey::from_str(&payout.wallet).map_err(|_| InputError::InvalidRecord {
+        record: format!("row {row_number} ({})", payout.wallet),
+        message: "invalid recipient: not a base58 pubkey".to_string(),
+    })?;
+    Ok(Allocation::new(recipient, payout.amount))
+}
+
+/// Build a reward's tree on disk in `dir` straight from the database
+///
+/// The distribution id is derived from the reward's aggregate (as
+/// `build-merkle-distribution` does) before any leaf is hashed. Both
+/// queries run in one read-only repeatable-read transaction, and the
+/// streamed rows are checked against the aggregate.
+pub async fn build_from_postgres(
+    pool: &PgPool,
+    query: &PayoutQuery,
+    dir: impl AsRef<Path>,
+) -> Result<PostgresBuild, PostgresError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let payouts = reward_payouts(&mut *tx, query).await?;
+    let distribution_id = suggest_distribution_id(
+        &payouts.reward_id,
+        &payouts.window_id,
+        &payouts.mint,
+        payouts.total_amount,
+    );
+
+    let mut builder = StreamingBuilder::create(dir, distribution_id)?;
+    let mut read = 0u64;
+    let mut read_total = 0u128;
+    {
+        let mut rows = pin!(stream_payouts(&mut *tx, query));
+        while let Some(allocation) = rows.try_next().await? {
+            builder.push(&allocation)?;
+            read += 1;
+            read_total += u128::from(allocation.amount);
+        }
+    }
+    tx.commit().await?;
+
+    if read != payouts.num_recipients || read_total != u128::from(payouts.total_amount) {
+        return Err(PostgresError::Inconsistent {
+            expected: payouts.num_recipients,
+            expected_total: payouts.total_amount,
+            read,
+            read_total,
+        });
+    }
+
+    Ok(PostgresBuild {
+        tree: builder.finish()?,
+        distribution_id,
+        payouts,
+    })
+}
@@ -0,0 +1,96 @@
+//! CSV split-rule input
+//!
+//! One row per share:
+//!
+//! ```csv
+//! holder,recipient,weight
+//! *,holder,80
+//! *,Cust0dyWa11et...,20
+//! HotWa11et...,holder,1
+//! ```
+//!
+//! `holder` is the wallet whose allocation is split, or `*` for the global
+//! rule. `recipient` is the address paid, or `holder` for the holder itself.
+//! A holder listed with a single `holder` share is exempt from the global
+//! rule.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use solana_pubkey::Pubkey;
+
+use super::InputError;
+use crate::build::{SplitRules, SplitShare, SplitTarget};
+
+/// Read split rules from a CSV file
+pub fn read_split_rules(path: impl AsRef<Path>) -> Result<SplitRules, InputError> {
+    read_split_rules_from(File::open(path)?)
+}
+
+/// Read split rules from any CSV source
+pub fn read_split_rules_from<R: Read>(reader: R) -> Result<SplitRules, InputError> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+
+    let headers = reader.headers().map_err(|e| malformed(1, e))?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| InputError::MissingColumn(name.to_string()))
+    };
+    let holder_idx = column("holder")?;
+    let recipient_idx = column("recipient")?;
+    let weight_idx = column("weight")?;
+
+    let mut rules = SplitRules::default();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map(|p| p.line()).unwrap_or(0);
+            malformed(line, e)
+        })?;
+        let line = record.position().map(|p| p.line()).unwrap_or(0);
+
+        let holder = record.get(holder_idx).unwrap_or("");
+        let recipient = record.get(recipient_idx).unwrap_or("");
+        let weight = record.get(weight_idx).unwrap_or("");
+
+        let pubkey = |value: &str| {
+            Pubkey::from_str(value).map_err(|_| InputError::InvalidRecipient {
+                line,
+                value: value.to_string(),
+            })
+        };
+        let target = match recipient {
+            "holder" => SplitTarget::Holder,
+            address => SplitTarget::Address(pubkey(address)?),
+        };
+        let weight = weight.parse().map_err(|_| InputError::Malformed {
+            line,
+            message: format!("invalid weight `{weight}`"),
+        })?;
+
+        let share = SplitShare { target, weight };
+        match holder {
+            "*" => rules.global.push(share),
+            holder => rules
+                .holders
+                .entry(pubkey(holder)?)
+                .or_default()
+                .push(share),
+        }
+    }
+
+    Ok(rules)
+}
+
+fn malformed(line: u64, error: ::csv::Error) -> InputError {
+    InputError::Malformed {
+        line,
+        message: error.to_string(),
+    }
+}
@@ -0,0 +1,64 @@
+//! Merkle Tree Builder
+//!
+//! Canonical off-chain builder for merkle-distributor trees.
+//!
+//! ## Overview
+//!
+//! Leaf and node hashing match the on-chain program byte for byte:
+//!
+//! - `leaf = keccak(DOMAIN_SEPARATOR || distribution_id || recipient || amount_le)`
+//! - `node = keccak(min(a, b) || max(a, b))` (sorted pair)
+//! - An odd node at the end of a level is paired with itself
+//!
+//! ```ignore
+//! let tree = MerkleTree::new(distribution_id, &allocations)?;
+//! let proof = tree.get_proof(0).unwrap();
+//! assert!(MerkleTree::verify(&tree.root(), &tree.leaf(0).unwrap(), &proof));
+//! ```
+
+#[cfg(feature = "server")]
+pub mod access;
+pub mod build;
+pub mod bundle;
+#[cfg(feature = "server")]
+pub mod cache;
+pub mod diff;
+pub mod epoch;
+pub mod error;
+pub mod export;
+#[cfg(feature = "test-util")]
+pub mod fixtures;
+pub mod format;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod hash;
+pub mod input;
+pub mod mmap;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod preimage;
+pub mod report;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod signing;
+#[cfg(feature = "test-util")]
+pub mod strategies;
+pub mod streaming;
+pub mod summary;
+pub mod tree;
+pub mod verify;
+
+pub use build::{
+    BudgetOvershoot, BuildOptions, BuildReport, DuplicatePolicy, DustPolicy, FlaggedAllocation,
+    LeafOrder, OffCurvePolicy, SplitRules, SplitShare, SplitTarget,
+};
+pub use error::BuildError;
+pub use format::FormatError;
+pub use hash::{
+    compute_leaf, compute_vesting_leaf, hash_pair, HashKind, HashScheme, LeafSchema, ProofMode,
+    DOMAIN_SEPARATOR, LEAF_PREFIX, MAX_PROOF_LEN, NODE_PREFIX, VESTING_DOMAIN_SEPARATOR,
+};
+pub use input::InputError;
+pub use solana_pubkey::Pubkey;
+pub use summary::{suggest_distribution_id, BuildSummary};
+pub use tree::{Allocation, ClaimProof, MerkleTree};
@@ -0,0 +1,234 @@
+//! Memory-mapped reader over tree files
+//!
+//! Maps a file written by `MerkleTree::save` without reading it, so a proof
+//! server starts instantly regardless of tree size. A proof query touches
+//! one allocation record and one node per level.
+
+use std::fs::File;
+use std::path::Path;
+
+use memmap2::Mmap;
+use solana_pubkey::Pubkey;
+
+use crate::format::{FormatError, Header};
+use crate::hash::LeafSchema;
+use crate::tree::ClaimProof;
+
+const NODE_LEN: usize = 32;
+
+/// Read-only view of a tree file
+pub struct MmapTree {
+    mmap: Mmap,
+    header: Header,
+    allocations_offset: usize,
+    /// Byte offset and node count of each level, leaves first
+    levels: Vec<(usize, usize)>,
+}
+
+impl MmapTree {
+    /// Map a tree file and validate its header and length
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FormatError> {
+        let file = File::open(path)?;
+        // SAFETY: tree files are written once and never modified in place;
+        // truncating one while it is served is an operator error
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let header = Header::read(&mut &mmap[..])?;
+        let leaf_count = header.leaf_count as usize;
+        let allocations_offset = header.header_len as usize;
+
+        let mut levels = Vec::with_capacity(usize::from(header.depth) + 1);
+        let mut offset = allocations_offset + leaf_count * header.record_len();
+        let mut len = leaf_count;
+        for _ in 0..=header.depth {
+            levels.push((offset, len));
+            offset += len * NODE_LEN;
+            len = len.div_ceil(2);
+        }
+
+        if mmap.len() != offset {
+            return Err(FormatError::Corrupt(format!(
+                "expected {offset} bytes, file has {}",
+                mmap.len()
+            )));
+        }
+
+        Ok(Self {
+            mmap,
+            header,
+            allocations_offset,
+            levels,
+        })
+    }
+
+    /// Parsed file header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Distribution id the leaves are bound to
+    pub fn distribution_id(&self) -> &[u8; 32] {
+        &self.header.distribution_id
+    }
+
+    /// Merkle root
+    pub fn root(&self) -> [u8; 32] {
+        self.node(self.depth(), 0)
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.levels[0].1
+    }
+
+    /// Always false: tree files never hold an empty tree
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Proof depth
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Leaf hash at `index`
+    pub fn leaf(&self, index: usize) -> Option<[u8; 32]> {
+        (index < self.len()).then(|| self.node(0, index))
+    }
+
+    /// Recipient of the leaf at `index`
+    pub fn recipient(&self, index: usize) -> Option<Pubkey> {
+        let record = self.record(index)?;
+        Some(Pubkey::new_from_array(record[..32].try_into().unwrap()))
+    }
+
+    /// `(unlock_at, expires_at)` of the leaf at `index`, `None` for V1 trees
+    pub fn schedule(&self, index: usize) -> Option<(i64, i64)> {
+        if self.header.schema != LeafSchema::Vesting {
+            return None;
+        }
+        let record = self.record(index)?;
+        Some((
+            i64::from_le_bytes(record[40..48].try_into().unwrap()),
+            i64::from_le_bytes(record[48..56].try_into().unwrap()),
+        ))
+    }
+
+    /// Claim arguments for the leaf at `index`
+    pub fn proof_for_index(&self, index: u64) -> Option<ClaimProof> {
+        let position = usize::try_from(index).ok()?;
+        let record = self.record(position)?;
+        let recipient = Pubkey::new_from_array(record[..32].try_into().unwrap());
+        let amount = u64::from_le_bytes(record[32..40].try_into().unwrap());
+
+        let mut proof = Vec::with_capacity(self.depth());
+        let mut current = position;
+
+        for level in 0..self.depth() {
+            let sibling = current ^ 1;
+            // No sibling (odd node at end of level) - the node pairs with itself
+            let position = if sibling < self.levels[level].1 {
+                sibling
+            } else {
+                current
+            };
+            proof.push(self.node(level, position));
+            current /= 2;
+        }
+
+        Some(ClaimProof {
+            index,
+            recipient,
+            amount,
+            proof,
+        })
+    }
+
+    fn record(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.len() {
+            return None;
+        }
+        let record_len = self.header.record_len();
+        let start = self.allocations_offset + index * record_len;
+        Some(&self.mmap[start..start + record_len])
+    }
+
+    fn node(&self, level: usize, position: usize) -> [u8; 32] {
+        let start = self.levels[level].0 + position * NODE_LEN;
+        self.mmap[start..start + NODE_LEN].try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use super::*;
+    use crate::hash::HashKind;
+    use crate::tree::{Allocation, MerkleTree};
+
+    fn tree(schema: LeafSchema, size: u8) -> MerkleTree {
+        let allocations: Vec<_> = (0..size)
+            .map(|i| Allocation {
+                recipient: Pubkey::new_from_array([i; 32]),
+                amount: u64::from(i) * 3 + 1,
+                unlock_at: Some(10),
+                expires_at: Some(20),
+            })
+            .collect();
+        MerkleTree::with_hashing([2; 32], &allocations, HashKind::Keccak, schema).unwrap()
+    }
+
+    #[test]
+    fn serves_the_saved_tree() {
+        for size in [1u8, 6, 9] {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("tree.bin");
+            let tree = tree(LeafSchema::V1, size);
+            tree.save(&path).unwrap();
+
+            let mapped = MmapTree::open(&path).unwrap();
+            assert_eq!(mapped.len(), tree.len());
+            assert_eq!(mapped.root(), tree.root());
+            assert_eq!(mapped.distribution_id(), tree.distribution_id());
+            for index in 0..tree.len() {
+                assert_eq!(mapped.leaf(index), tree.leaf(index));
+                assert_eq!(
+                    mapped.proof_for_index(index as u64),
+                    tree.proof_for_index(index as u64)
+                );
+            }
+            assert_eq!(mapped.proof_for_index(u64::from(size)), None);
+            assert_eq!(mapped.schedule(0), None);
+        }
+    }
+
+    #[test]
+    fn vesting_records_carry_the_schedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tree.bin");
+        let tree = tree(LeafSchema::Vesting, 3);
+        tree.save(&path).unwrap();
+
+        let mapped = MmapTree::open(&path).unwrap();
+        assert_eq!(mapped.schedule(2), Some((10, 20)));
+        assert_eq!(mapped.recipient(2), Some(Pubkey::new_from_array([2; 32])));
+        assert_eq!(mapped.proof_for_index(2), tree.proof_for_index(2));
+    }
+
+    #[test]
+    fn truncated_file_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tree.bin");
+        tree(LeafSchema::V1, 4).save(&path).unwrap();
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 32).unwrap();
+
+        assert!(matches!(
+            MmapTree::open(&path),
+            Err(FormatError::Corrupt(_))
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+//! Parquet allocation and proof files (`parquet` feature)
+//!
+//! Lets warehouse snapshots flow into tree construction without a CSV
+//! conversion, and hands proofs back in the same format. Files are read and
+//! written in record batches, so `AllocationReader` can feed a
+//! `StreamingBuilder` with bounded memory.
+//!
+//! Allocation files:
+//!
+//! | Column | Type |
+//! |--------|------|
+//! | `wallet` | `Utf8` (base58) or `FixedSizeBinary(32)` |
+//! | `amount` | `UInt64`, non-negative `Int64`, or `Utf8` (see `decimals`) |
+//! | `unlock_at`, `expires_at` | optional, nullable `Int64` / `Timestamp(Second)` |
+//!
+//! Proof files: `index: UInt64`, `wallet: Utf8`, `amount: UInt64`,
+//! `proof: List<FixedSizeBinary(32)>` (leaf level first).
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ::parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::errors::ParquetError;
+use arrow_array::builder::{
+    FixedSizeBinaryBuilder, Int64Builder, ListBuilder, StringBuilder, UInt64Builder,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Int64Type, TimestampSecondType, UInt64Type};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use solana_pubkey::Pubkey;
+
+use crate::input::{parse_amount, InputError};
+use crate::tree::{Allocation, ClaimProof, MerkleTree};
+
+/// Rows per record batch when writing
+const BATCH_ROWS: usize = 64 * 1024;
+
+/// Parquet reader options
+#[derive(Clone, Debug)]
+pub struct ParquetOptions {
+    /// Name of the recipient column
+    pub recipient_column: String,
+    /// Name of the amount column
+    pub amount_column: String,
+    /// `Utf8` amounts are decimal token amounts with this many decimals
+    /// (`None` = integer base units); integer columns are always base units
+    pub decimals: Option<u8>,
+    /// Rows decoded per batch
+    pub batch_size: usize,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self {
+            recipient_column: "wallet".to_string(),
+            amount_column: "amount".to_string(),
+            decimals: None,
+            batch_size: 8192,
+        }
+    }
+}
+
+/// Read all allocations from a Parquet file
+pub fn read_parquet(
+    path: impl AsRef<Path>,
+    options: &ParquetOptions,
+) -> Result<Vec<Allocation>, InputError> {
+    AllocationReader::open(path, options)?.collect()
+}
+
+/// Allocations decoded batch by batch, in file order
+///
+/// Row numbers in errors count from 1.
+pub struct AllocationReader {
+    batches: ParquetRecordBatchReader,
+    options: ParquetOptions,
+    batch: Option<RecordBatch>,
+    position: usize,
+    row: u64,
+}
+
+impl AllocationReader {
+    /// Open `path` for reading
+    pub fn open(path: impl AsRef<Path>, options: &ParquetOptions) -> Result<Self, InputError> {
+        let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)
+            .map_err(parquet_error)?
+            .with_batch_size(options.batch_size.max(1))
+            .build()
+            .map_err(parquet_error)?;
+
+        Ok(Self {
+            batches,
+            options: options.clone(),
+            batch: None,
+            position: 0,
+            row: 0,
+        })
+    }
+
+    fn allocation(&self, batch: &RecordBatch, i: usize) -> Result<Allocation, InputError> {
+        let line = self.row;
+        Ok(Allocation {
+            recipient: recipient_at(column(batch, &self.options.recipient_column)?, i, line)?,
+            amount: amount_at(
+                column(batch, &self.options.amount_column)?,
+                i,
+                line,
+                self.options.decimals,
+            )?,
+            unlock_at: timestamp_at(batch.column_by_name("unlock_at"), i, line)?,
+            expires_at: timestamp_at(batch.column_by_name("expires_at"), i, line)?,
+        })
+    }
+}
+
+impl Iterator for AllocationReader {
+    type Item = Result<Allocation, InputError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(batch) = &self.batch {
+                if self.position < batch.num_rows() {
+                    let i = self.position;
+                    self.position += 1;
+                    self.row += 1;
+                    return Some(self.allocation(batch, i));
+                }
+            }
+
+            match self.batches.next()? {
+                Ok(batch) => {
+                    self.batch = Some(batch);
+                    self.position = 0;
+                }
+                Err(e) => return Some(Err(parquet_error(e.into()))),
+            }
+        }
+    }
+}
+
+fn parquet_error(e: ParquetError) -> InputError {
+    InputError::InvalidRecord {
+        record: "parquet file".to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef, InputError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| InputError::MissingColumn(name.to_string()))
+}
+
+fn unsupported(column: &ArrayRef) -> InputError {
+    InputError::InvalidRecord {
+        record: "parquet file".to_string(),
+        message: format!("unsupported column type {}", column.data_type()),
+    }
+}
+
+fn null(line: u64, what: &str) -> InputError {
+    InputError::Malformed {
+        line,
+        message: format!("null {what}"),
+    }
+}
+
+fn recipient_at(column: &ArrayRef, i: usize, line: u64) -> Result<Pubkey, InputError> {
+    if column.is_null(i) {
+        return Err(null(line, "recipient"));
+    }
+    let text = match column.data_type() {
+        DataType::Utf8 => column.as_string::<i32>().value(i),
+        DataType::LargeUtf8 => column.as_string::<i64>().value(i),
+        DataType::FixedSizeBinary(32) => {
+            let bytes = column.as_fixed_size_binary().value(i);
+            return Ok(Pubkey::new_from_array(bytes.try_into().unwrap()));
+        }
+        _ => return Err(unsupported(column)),
+    };
+    Pubkey::from_str(text).map_err(|_| InputError::InvalidRecipient {
+        line,
+        value: text.to_string(),
+    })
+}
+
+fn amount_at(
+    column: &ArrayRef,
+    i: usize,
+    line: u64,
+    decimals: Option<u8>,
+) -> Result<u64, InputError> {
+    if column.is_null(i) {
+        return Err(null(line, "amount"));
+    }
+    let invalid = |value: String, reason: &str| InputError::InvalidAmount {
+        line,
+        value,
+        reason: reason.to_string(),
+    };
+    match column.data_type() {
+        DataType::UInt64 => Ok(column.as_primitive::<UInt64Type>().value(i)),
+        DataType::Int64 => {
+            let value = column.as_primitive::<Int64Type>().value(i);
+            u64::try_from(value).map_err(|_| invalid(value.to_string(), "negative"))
+        }
+        DataType::Utf8 => {
+            let value = column.as_string::<i32>().value(i);
+            parse_amount(value, decimals).map_err(|reason| invalid(value.to_string(), &reason))
+        }
+        _ => Err(unsupported(column)),
+    }
+}
+
+fn timestamp_at(column: Option<&ArrayRef>, i: usize, line: u64) -> Result<Option<i64>, InputError> {
+    let Some(column) = column else {
+        return Ok(None);
+    };
+    if column.is_null(i) {
+        return Ok(None);
+    }
+    match column.data_type() {
+        DataType::Int64 => Ok(Some(column.as_primitive::<Int64Type>().value(i))),
+        DataType::Timestamp(TimeUnit::Second, _) => {
+            Ok(Some(column.as_primitive::<TimestampSecondType>().value(i)))
+        }
+        _ => Err(InputError::Malformed {
+            line,
+            message: format!("unsupported timestamp type {}", column.data_type()),
+        }),
+    }
+}
+
+fn allocation_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("wallet", DataType::Utf8, false),
+        Field::new("amount", DataType::UInt64, false),
+        Field::new("unlock_at", DataType::Int64, true),
+        Field::new("expires_at", DataType::Int64, true),
+    ]))
+}
+
+fn proof_node_field() -> Arc<Field> {
+    Arc::new(Field::new("item", DataType::FixedSizeBinary(32), false))
+}
+
+fn proof_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("index", DataType::UInt64, false),
+        Field::new("wallet", DataType::Utf8, false),
+        Field::new("amount", DataType::UInt64, false),
+        Field::new("proof", DataType::List(proof_node_field()), false),
+    ]))
+}
+
+/// Write allocations in the allocation file layout
+pub fn write_allocations(
+    allocations: &[Allocation],
+    writer: impl Write + Send,
+) -> Result<(), ParquetError> {
+    let schema = allocation_schema();
+    let mut writer = ArrowWriter::try_new(writer, schema.clone(), None)?;
+
+    for chunk in allocations.chunks(BATCH_ROWS) {
+        let mut wallets = StringBuilder::new();
+        let mut amounts = UInt64Builder::with_capacity(chunk.len());
+        let mut unlock_at = Int64Builder::with_capacity(chunk.len());
+        let mut expires_at = Int64Builder::with_capacity(chunk.len());
+        for allocation in chunk {
+            wallets.append_value(allocation.recipient.to_string());
+            amounts.append_value(allocation.amount);
+            unlock_at.append_option(allocation.unlock_at);
+            expires_at.append_option(allocation.expires_at);
+        }
+
+        writer.write(&RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(wallets.finish()),
+                Arc::new(amounts.finish()),
+                Arc::new(unlock_at.finish()),
+                Arc::new(expires_at.finish()),
+            ],
+        )?)?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+/// Write every claim proof of `tree`, in index order
+pub fn write_proofs(tree: &MerkleTree, writer: impl Write + Send) -> Result<(), ParquetError> {
+    let schema = proof_schema();
+    let mut writer = ArrowWriter::try_new(writer, schema.clone(), None)?;
+
+    let mut start = 0;
+    while start < tree.len() {
+        let end = (start + BATCH_ROWS).min(tree.len());
+        let mut indices = UInt64Builder::with_capacity(end - start);
+        let mut wallets = StringBuilder::new();
+        let mut amounts = UInt64Builder::with_capacity(end - start);
+        let mut proofs =
+            ListBuilder::new(FixedSizeBinaryBuilder::new(32)).with_field(proof_node_field());
+
+        for index in start as u64..end as u64 {
+            let claim = tree
+                .proof_for_index(index)
+                .expect("index is within the tree");
+            indices.append_value(claim.index);
+            wallets.append_value(claim.recipient.to_string());
+            amounts.append_value(claim.amount);
+            for node in &claim.proof {
+                proofs.values().append_value(node)?;
+            }
+            proofs.append(true);
+        }
+
+        writer.write(&RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(indices.finish()),
+                Arc::new(wallets.finish()),
+                Arc::new(amounts.finish()),
+                Arc::new(proofs.finish()),
+            ],
+        )?)?;
+        start = end;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+/// Read a proof file written by `write_proofs`
+pub fn read_proofs(path: impl AsRef<Path>) -> Result<Vec<ClaimProof>, InputError> {
+    let batches = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)
+        .map_err(parquet_error)?
+        .build()
+        .map_err(parquet_error)?;
+
+    let mut claims = Vec::new();
+    let mut line = 0;
+    for batch in batches {
+        let batch = batch.map_err(|e| parquet_error(e.into()))?;
+        let indices = column(&batch, "index")?;
+        let wallets = column(&batch, "wallet")?;
+        let amounts = column(&batch, "amount")?;
+        let proofs = column(&batch, "proof")?;
+        if !matches!(indices.data_type(), DataType::UInt64) {
+            return Err(unsupported(indices));
+        }
+        if !matches!(proofs.data_type(), DataType::List(field) if field.data_type() == &DataType::FixedSizeBinary(32))
+        {
+            return Err(unsupported(proofs));
+        }
+
+        for i in 0..batch.num_rows() {
+            line += 1;
+            let nodes = proofs.as_list::<i32>().value(i);
+            claims.push(ClaimProof {
+                index: indices.as_primitive::<UInt64Type>().value(i),
+                recipient: recipient_at(wallets, i, line)?,
+                amount: amount_at(amounts, i, line, None)?,
+                proof: nodes
+                    .as_fixed_size_binary()
+                    .iter()
+                    .map(|node| {
+                        node.map(|node| node.try_into().unwrap())
+                            .ok_or_else(|| null(line, "proof node"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            });
+        }
+    }
+    Ok(claims)
+}
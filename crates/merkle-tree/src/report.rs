@@ -0,0 +1,404 @@
+//! Human-auditable allocation report
+//!
+//! A digest for multisig signers to check before approving `initialize`:
+//! totals, recipient count, the largest allocations, how amounts are
+//! distributed, and the root they are signing off on. Rendered as JSON for
+//! tooling and as plain text for pasting into the proposal.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::hash::{to_hex, HashKind, LeafSchema};
+use crate::tree::MerkleTree;
+
+/// What to include in a report
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReportOptions {
+    /// Number of largest wallets to list
+    pub top_n: usize,
+    /// Mint decimals, used to show UI amounts in the text report
+    pub decimals: Option<u8>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            top_n: 10,
+            decimals: None,
+        }
+    }
+}
+
+/// One of the largest wallets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopAllocation {
+    pub wallet: Pubkey,
+    /// Sum of the wallet's leaves
+    pub amount: u128,
+}
+
+/// Wallets whose total falls in `[min, max)`
+///
+/// Buckets are powers of ten (`[1, 10)`, `[10, 100)`, ...); the first bucket
+/// starts at 0 and holds zero-amount wallets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistogramBucket {
+    pub min: u128,
+    /// Exclusive upper bound, `None` for the open-ended last bucket
+    pub max: Option<u128>,
+    pub wallets: u64,
+    pub amount: u128,
+}
+
+/// Digest of a tree for signers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocationReport {
+    pub distribution_id: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub hash_kind: HashKind,
+    pub schema: LeafSchema,
+    /// Sum of all leaf amounts in base units
+    pub total_amount: u128,
+    /// Number of leaves (`num_recipients` on-chain)
+    pub num_leaves: u64,
+    /// Number of distinct wallets
+    pub num_wallets: u64,
+    /// Smallest, median and largest per-wallet total
+    pub min_amount: u128,
+    pub median_amount: u128,
+    pub max_amount: u128,
+    /// Largest wallets, descending
+    pub top: Vec<TopAllocation>,
+    /// Non-empty buckets, ascending
+    pub histogram: Vec<HistogramBucket>,
+    decimals: Option<u8>,
+}
+
+impl AllocationReport {
+    /// Build a report over per-wallet totals of `tree`
+    pub fn new(tree: &MerkleTree, options: &ReportOptions) -> Self {
+        let mut totals: HashMap<Pubkey, u128> = HashMap::new();
+        for allocation in tree.allocations() {
+            *totals.entry(allocation.recipient).or_default() += u128::from(allocation.amount);
+        }
+
+        // Largest first, ties broken by wallet bytes so reports are reproducible
+        let mut wallets: Vec<(Pubkey, u128)> = totals.into_iter().collect();
+        wallets.sort_by(|(a_wallet, a), (b_wallet, b)| {
+            b.cmp(a)
+                .then_with(|| a_wallet.as_ref().cmp(b_wallet.as_ref()))
+        });
+
+        let mut histogram: Vec<HistogramBucket> = Vec::new();
+        for &(_, amount) in wallets.iter().rev() {
+            let (min, max) = bucket_bounds(amount);
+            match histogram.last_mut() {
+                Some(bucket) if bucket.min == min => {
+                    bucket.wallets += 1;
+                    bucket.amount += amount;
+                }
+                _ => histogram.push(HistogramBucket {
+                    min,
+                    max,
+                    wallets: 1,
+                    amount,
+                }),
+            }
+        }
+
+        // Trees are never empty, so neither is `wallets`
+        Self {
+            distribution_id: *tree.distribution_id(),
+            merkle_root: tree.root(),
+            hash_kind: tree.hash_kind(),
+            schema: tree.schema(),
+            total_amount: wallets.iter().map(|(_, amount)| amount).sum(),
+            num_leaves: tree.len() as u64,
+            num_wallets: wallets.len() as u64,
+            min_amount: wallets[wallets.len() - 1].1,
+            median_amount: wallets[wallets.len() / 2].1,
+            max_amount: wallets[0].1,
+            top: wallets
+                .iter()
+                .take(options.top_n)
+                .map(|&(wallet, amount)| TopAllocation { wallet, amount })
+                .collect(),
+            histogram,
+            decimals: options.decimals,
+        }
+    }
+
+    /// Share of the total, in percent
+    pub fn share(&self, amount: u128) -> f64 {
+        if self.total_amount == 0 {
+            return 0.0;
+        }
+        amount as f64 * 100.0 / self.total_amount as f64
+    }
+
+    /// Write the report as pretty-printed JSON (amounts as base-unit strings)
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, &ReportJson::from(self))
+    }
+
+    /// Write the plain-text summary (same as `Display`)
+    pub fn write_text(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "{self}")
+    }
+
+    fn amount(&self, amount: u128) -> String {
+        match self.decimals {
+            Some(decimals) => format!("{} ({amount})", ui_amount(amount, decimals)),
+            None => amount.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AllocationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Distribution allocation report")?;
+        writeln!(f)?;
+        writeln!(f, "Distribution id: {}", to_hex(&self.distribution_id))?;
+        writeln!(f, "Merkle root:     {}", to_hex(&self.merkle_root))?;
+        writeln!(
+            f,
+            "Hashing:         {} / {} leaves",
+            self.hash_kind.name(),
+            self.schema.name()
+        )?;
+        writeln!(f, "Total amount:    {}", self.amount(self.total_amount))?;
+        writeln!(f, "Leaves:          {}", self.num_leaves)?;
+        writeln!(f, "Wallets:         {}", self.num_wallets)?;
+        writeln!(
+            f,
+            "Per wallet:      min {}, median {}, max {}",
+            self.amount(self.min_amount),
+            self.amount(self.median_amount),
+            self.amount(self.max_amount)
+        )?;
+
+        writeln!(f)?;
+        writeln!(f, "Top {} wallets:", self.top.len())?;
+        for (rank, top) in self.top.iter().enumerate() {
+            writeln!(
+                f,
+                "  {:>3}. {:<44}  {}  {:>6.2}%",
+                rank + 1,
+                top.wallet.to_string(),
+                self.amount(top.amount),
+                self.share(top.amount)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Amount distribution (base units):")?;
+        for bucket in &self.histogram {
+            let range = match bucket.max {
+                Some(max) => format!("[{}, {max})", bucket.min),
+                None => format!("[{}, ...)", bucket.min),
+            };
+            writeln!(
+                f,
+                "  {range:<44}  {:>8} wallets  {:>6.2}%",
+                bucket.wallets,
+                self.share(bucket.amount)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Power-of-ten bucket containing `amount`
+fn bucket_bounds(amount: u128) -> (u128, Option<u128>) {
+    if amount == 0 {
+        return (0, Some(1));
+    }
+    let min = 10u128.pow(amount.ilog10());
+    (min, min.checked_mul(10))
+}
+
+/// Base units rendered with `decimals` fractional digits
+fn ui_amount(amount: u128, decimals: u8) -> String {
+    let scale = 10u128.pow(u32::from(decimals));
+    let whole = amount / scale;
+    let fraction = amount % scale;
+    if decimals == 0 || fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{fraction:0width$}", width = usize::from(decimals));
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportJson {
+    distribution_id: String,
+    merkle_root: String,
+    hash_kind: &'static str,
+    leaf_schema: &'static str,
+    total_amount: String,
+    num_leaves: u64,
+    num_wallets: u64,
+    min_amount: String,
+    median_amount: String,
+    max_amount: String,
+    top: Vec<TopJson>,
+    histogram: Vec<BucketJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopJson {
+    wallet: String,
+    amount: String,
+    share_percent: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BucketJson {
+    min: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<String>,
+    wallets: u64,
+    amount: String,
+}
+
+impl From<&AllocationReport> for ReportJson {
+    fn from(report: &AllocationReport) -> Self {
+        Self {
+            distribution_id: to_hex(&report.distribution_id),
+            merkle_root: to_hex(&report.merkle_root),
+            hash_kind: report.hash_kind.name(),
+            leaf_schema: report.schema.name(),
+            total_amount: report.total_amount.to_string(),
+            num_leaves: report.num_leaves,
+            num_wallets: report.num_wallets,
+            min_amount: report.min_amount.to_string(),
+            median_amount: report.median_amount.to_string(),
+            max_amount: report.max_amount.to_string(),
+            top: report
+                .top
+                .iter()
+                .map(|top| TopJson {
+                    wallet: top.wallet.to_string(),
+                    amount: top.amount.to_string(),
+                    share_percent: report.share(top.amount),
+                })
+                .collect(),
+            histogram: report
+                .histogram
+                .iter()
+                .map(|bucket| BucketJson {
+                    min: bucket.min.to_string(),
+                    max: bucket.max.map(|max| max.to_string()),
+                    wallets: bucket.wallets,
+                    amount: bucket.amount.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Allocation;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn report(options: &ReportOptions) -> AllocationReport {
+        // Wallet 1 has two leaves (8 in total); wallets 2 and 3 tie
+        let allocations: Vec<_> = [(1, 5), (2, 150), (3, 150), (1, 3), (4, 12), (5, 0)]
+            .into_iter()
+            .map(|(seed, amount)| Allocation::new(key(seed), amount))
+            .collect();
+        let tree = MerkleTree::new([3; 32], &allocations).unwrap();
+        AllocationReport::new(&tree, options)
+    }
+
+    #[test]
+    fn summarizes_wallet_totals() {
+        let report = report(&ReportOptions {
+            top_n: 2,
+            decimals: None,
+        });
+
+        assert_eq!(report.total_amount, 320);
+        assert_eq!(report.num_leaves, 6);
+        assert_eq!(report.num_wallets, 5);
+        assert_eq!(
+            (report.min_amount, report.median_amount, report.max_amount),
+            (0, 12, 150)
+        );
+        assert_eq!(
+            report.top,
+            [
+                TopAllocation {
+                    wallet: key(2),
+                    amount: 150,
+                },
+                TopAllocation {
+                    wallet: key(3),
+                    amount: 150,
+                },
+            ]
+        );
+        assert_eq!(report.share(80), 25.0);
+    }
+
+    #[test]
+    fn histogram_buckets_are_powers_of_ten() {
+        let buckets: Vec<_> = report(&ReportOptions::default())
+            .histogram
+            .into_iter()
+            .map(|b| (b.min, b.max, b.wallets, b.amount))
+            .collect();
+
+        assert_eq!(
+            buckets,
+            [
+                (0, Some(1), 1, 0),
+                (1, Some(10), 1, 8),
+                (10, Some(100), 1, 12),
+                (100, Some(1000), 2, 300),
+            ]
+        );
+        assert_eq!(bucket_bounds(u128::MAX).1, None);
+    }
+
+    #[test]
+    fn ui_amounts_trim_trailing_zeros() {
+        assert_eq!(ui_amount(1_500_000_000, 9), "1.5");
+        assert_eq!(ui_amount(2_000, 3), "2");
+        assert_eq!(ui_amount(1_234, 2), "12.34");
+        assert_eq!(ui_amount(5, 0), "5");
+    }
+
+    #[test]
+    fn text_and_json_output() {
+        let report = report(&ReportOptions {
+            top_n: 1,
+            decimals: Some(1),
+        });
+
+        let text = report.to_string();
+        assert!(text.contains(&format!("Merkle root:     {}", to_hex(&report.merkle_root))));
+        assert!(text.contains("Total amount:    32 (320)"));
+        assert!(text.contains("Top 1 wallets:"));
+
+        let mut json = Vec::new();
+        report.write_json(&mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json["totalAmount"], "320");
+        assert_eq!(json["numWallets"], 5);
+        assert_eq!(json["top"][0]["wallet"], key(2).to_string());
+        assert_eq!(json["histogram"][3]["max"], "1000");
+    }
+}
@@ -0,0 +1,248 @@
+//! HTTP proof API over tree files
+//!
+//! Serves claim arguments to the claim frontend straight from mmap'd tree
+//! files (`mmap::MmapTree`), so no proof JSON has to be exported or hosted:
+//!
+//! `GET /distributions/{id}/proofs/{wallet}`
+//!
+//! `{"distributionId":"…","index":0,"wallet":"…","amount":"1500000000","proof":["ab…",…]}`
+//!
+//! `id` is the hex distribution id. Vesting trees add `unlockAt` / `expiresAt`.
+//! An unknown distribution or a wallet with no leaf is a 404, a malformed
+//! wallet a 400; error bodies are `{"error":"…"}`.
+//!
+//! `cached_router` puts a `ResponseCache` in front of the lookups, namespaced
+//! by `ProofStore::fingerprint` so entries never outlive the trees served.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use axum::extract::{Path as UrlPath, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::cache::{fingerprint, ResponseCache};
+use crate::format::FormatError;
+use crate::hash::to_hex;
+use crate::mmap::MmapTree;
+
+/// File extension of the tree files `ProofStore::open_dir` serves
+pub const TREE_EXTENSION: &str = "tree";
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("{0}: {1}")]
+    Format(PathBuf, FormatError),
+    #[error("{0} and {1} hold the same distribution id {2}")]
+    Duplicate(PathBuf, PathBuf, String),
+}
+
+/// A mapped tree and its recipients' leaf indices
+struct ServedTree {
+    path: PathBuf,
+    tree: MmapTree,
+    indices: HashMap<Pubkey, u64>,
+}
+
+/// Trees served by the API, keyed by hex distribution id
+#[derive(Default)]
+pub struct ProofStore {
+    trees: HashMap<String, ServedTree>,
+}
+
+impl ProofStore {
+    /// Map every `*.tree` file in `dir`
+    pub fn open_dir(dir: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir).map_err(|e| LoadError::Io(dir.to_path_buf(), e))?;
+
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| LoadError::Io(dir.to_path_buf(), e))?
+                .path();
+            if path.extension().is_some_and(|ext| ext == TREE_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        // Deterministic duplicate reporting
+        paths.sort();
+
+        let mut store = Self::default();
+        for path in paths {
+            store.open(path)?;
+        }
+        Ok(store)
+    }
+
+    /// Map one tree file and index its recipients
+    ///
+    /// Indexing reads every allocation record once. A recipient listed more
+    /// than once resolves to their first leaf, as in `proof_for_recipient`.
+    pub fn open(&mut self, path: impl Into<PathBuf>) -> Result<(), LoadError> {
+        let path = path.into();
+        let tree = MmapTree::open(&path).map_err(|e| LoadError::Format(path.clone(), e))?;
+        let id = to_hex(tree.distribution_id());
+        if let Some(existing) = self.trees.get(&id) {
+            return Err(LoadError::Duplicate(existing.path.clone(), path, id));
+        }
+
+        let mut indices = HashMap::with_capacity(tree.len());
+        for index in 0..tree.len() {
+            let recipient = tree.recipient(index).expect("index below len");
+            indices.entry(recipient).or_insert(index as u64);
+        }
+
+        self.trees.insert(
+            id,
+            ServedTree {
+                path,
+                tree,
+                indices,
+            },
+        );
+        Ok(())
+    }
+
+    /// Number of trees served
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// True if no tree is served
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Fingerprint of the served (distribution id, root) pairs
+    ///
+    /// Changes whenever a distribution is added or a tree rebuilt, so it
+    /// serves as the proof cache generation.
+    pub fn fingerprint(&self) -> String {
+        fingerprint(
+            self.trees
+                .iter()
+                .map(|(id, served)| format!("{id}:{}", to_hex(&served.tree.root()))),
+        )
+    }
+
+    /// Proof for `wallet` in the distribution with hex id `distribution_id`
+    pub fn proof(&self, distribution_id: &str, wallet: &str) -> Result<ProofResponse, ProofError> {
+        let id = distribution_id.to_ascii_lowercase();
+        let served = self.trees.get(&id).ok_or(ProofError::UnknownDistribution)?;
+        let wallet = Pubkey::from_str(wallet).map_err(|_| ProofError::InvalidWallet)?;
+        let &index = served
+            .indices
+            .get(&wallet)
+            .ok_or(ProofError::NotRecipient)?;
+
+        let claim = served
+            .tree
+            .proof_for_index(index)
+            .expect("indexed leaf is in the tree");
+        let schedule = served.tree.schedule(index as usize);
+
+        Ok(ProofResponse {
+            distribution_id: id,
+            index: claim.index,
+            wallet: claim.recipient.to_string(),
+            amount: claim.amount.to_string(),
+            proof: claim.proof.iter().map(|node| to_hex(node)).collect(),
+            unlock_at: schedule.map(|(unlock_at, _)| unlock_at),
+            expires_at: schedule.map(|(_, expires_at)| expires_at),
+        })
+    }
+}
+
+/// Body of a successful proof lookup
+///
+/// Field names and types match the JSONL export and the TypeScript
+/// `MerkleProof`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofResponse {
+    pub distribution_id: String,
+    pub index: u64,
+    pub wallet: String,
+    /// Base units (string, as in the export)
+    pub amount: String,
+    /// Hex sibling hashes from the leaf level upwards
+    pub proof: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlock_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum ProofError {
+    #[error("unknown distribution")]
+    UnknownDistribution,
+    #[error("wallet is not a recipient of this distribution")]
+    NotRecipient,
+    #[error("invalid wallet address")]
+    InvalidWallet,
+}
+
+impl IntoResponse for ProofError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ProofError::UnknownDistribution | ProofError::NotRecipient => StatusCode::NOT_FOUND,
+            ProofError::InvalidWallet => StatusCode::BAD_REQUEST,
+        };
+        let body = serde_json::json!({ "error": self.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+#[derive(Clone)]
+struct Api {
+    store: Arc<ProofStore>,
+    cache: Option<ResponseCache<ProofResponse>>,
+}
+
+/// Routes of the proof API
+pub fn router(store: Arc<ProofStore>) -> Router {
+    routes(Api { store, cache: None })
+}
+
+/// Routes of the proof API, with lookups cached in `cache`
+///
+/// Switches `cache` to the store's fingerprint.
+pub fn cached_router(store: Arc<ProofStore>, cache: ResponseCache<ProofResponse>) -> Router {
+    cache.set_generation(store.fingerprint());
+    routes(Api {
+        store,
+        cache: Some(cache),
+    })
+}
+
+fn routes(api: Api) -> Router {
+    Router::new()
+        .route("/distributions/{id}/proofs/{wallet}", get(get_proof))
+        .with_state(api)
+}
+
+async fn get_proof(
+    State(api): State<Api>,
+    UrlPath((id, wallet)): UrlPath<(String, String)>,
+) -> Result<Json<ProofResponse>, ProofError> {
+    let Some(cache) = &api.cache else {
+        return api.store.proof(&id, &wallet).map(Json);
+    };
+    let key = format!("{}:{wallet}", id.to_ascii_lowercase());
+    cache
+        .get_or_try_insert(&key, async { api.store.proof(&id, &wallet) })
+        .await
+        .map(Json)
+}
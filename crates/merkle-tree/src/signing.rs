@@ -0,0 +1,248 @@
+//! Detached root signatures by the operations key
+//!
+//! The pipeline signs what `initialize` will commit to, so multisig signers
+//! can check a root came from the official build before approving it:
+//!
+//! `message = "L33_ROOT_SIG_V1" || distribution_id || merkle_root || total_amount_le || num_recipients_le`
+//!
+//! The signature file sits next to the build summary and matches
+//! `src/merkle/signing.ts`, which verifies it before building the
+//! initialize transaction.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::hash::to_hex;
+use crate::summary::BuildSummary;
+use crate::tree::MerkleTree;
+
+/// Domain prefix of the signed message (matches `src/merkle/signing.ts`)
+pub const ROOT_SIGNATURE_DOMAIN: &[u8] = b"L33_ROOT_SIG_V1";
+
+#[derive(Debug, Error)]
+pub enum SigningError {
+    #[error("failed to read keypair: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid keypair file: {0}")]
+    InvalidKeypair(String),
+    #[error("malformed {0} in signature file")]
+    Malformed(&'static str),
+    #[error("signed by {actual}, expected {expected}")]
+    WrongSigner { expected: Pubkey, actual: Pubkey },
+    #[error("signature does not verify")]
+    InvalidSignature,
+    #[error("{field} differs: signed {signed}, summary has {summary}")]
+    Mismatch {
+        field: &'static str,
+        signed: String,
+        summary: String,
+    },
+}
+
+/// The values `initialize` commits to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RootStatement {
+    pub distribution_id: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub num_recipients: u64,
+}
+
+impl RootStatement {
+    /// Statement for `tree`, or `None` if its total overflows `u64`
+    pub fn from_tree(tree: &MerkleTree) -> Option<Self> {
+        let total: u128 = tree
+            .allocations()
+            .iter()
+            .map(|a| u128::from(a.amount))
+            .sum();
+
+        Some(Self {
+            distribution_id: *tree.distribution_id(),
+            merkle_root: tree.root(),
+            total_amount: u64::try_from(total).ok()?,
+            num_recipients: tree.len() as u64,
+        })
+    }
+
+    /// Statement for a build summary (as piped to the init job)
+    pub fn from_summary(summary: &BuildSummary) -> Result<Self, SigningError> {
+        Ok(Self {
+            distribution_id: parse_hex32(&summary.distribution_id)
+                .ok_or(SigningError::Malformed("distributionId"))?,
+            merkle_root: parse_hex32(&summary.merkle_root)
+                .ok_or(SigningError::Malformed("merkleRoot"))?,
+            total_amount: summary
+                .total_amount
+                .parse()
+                .map_err(|_| SigningError::Malformed("totalAmount"))?,
+            num_recipients: summary.num_recipients,
+        })
+    }
+
+    /// Bytes that get signed
+    pub fn message(&self) -> Vec<u8> {
+        [
+            ROOT_SIGNATURE_DOMAIN,
+            &self.distribution_id,
+            &self.merkle_root,
+            &self.total_amount.to_le_bytes(),
+            &self.num_recipients.to_le_bytes(),
+        ]
+        .concat()
+    }
+
+    /// Sign with the operations key
+    pub fn sign(&self, key: &SigningKey) -> RootSignature {
+        let signature = key.sign(&self.message());
+        RootSignature {
+            distribution_id: to_hex(&self.distribution_id),
+            merkle_root: to_hex(&self.merkle_root),
+            total_amount: self.total_amount.to_string(),
+            num_recipients: self.num_recipients,
+            signer: Pubkey::new_from_array(key.verifying_key().to_bytes()).to_string(),
+            signature: to_hex(&signature.to_bytes()),
+        }
+    }
+}
+
+/// Detached signature file
+///
+/// Repeats the signed values so a reviewer can read what was signed without
+/// the summary; verification still checks them against the summary.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RootSignature {
+    pub distribution_id: String,
+    pub merkle_root: String,
+    /// Base units (string, as in the summary)
+    pub total_amount: String,
+    pub num_recipients: u64,
+    /// Base58 public key of the operations key
+    pub signer: String,
+    /// Hex-encoded 64-byte ed25519 signature
+    pub signature: String,
+}
+
+impl RootSignature {
+    /// Check the signature was made by `expected_signer` and return what it signs
+    pub fn verify(&self, expected_signer: &Pubkey) -> Result<RootStatement, SigningError> {
+        let signer =
+            Pubkey::from_str(&self.signer).map_err(|_| SigningError::Malformed("signer"))?;
+        if signer != *expected_signer {
+            return Err(SigningError::WrongSigner {
+                expected: *expected_signer,
+                actual: signer,
+            });
+        }
+
+        let statement = RootStatement {
+            distribution_id: parse_hex32(&self.distribution_id)
+                .ok_or(SigningError::Malformed("distributionId"))?,
+            merkle_root: parse_hex32(&self.merkle_root)
+                .ok_or(SigningError::Malformed("merkleRoot"))?,
+            total_amount: self
+                .total_amount
+                .parse()
+                .map_err(|_| SigningError::Malformed("totalAmount"))?,
+            num_recipients: self.num_recipients,
+        };
+        let signature: [u8; 64] = parse_hex(&self.signature)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(SigningError::Malformed("signature"))?;
+
+        let key = VerifyingKey::from_bytes(&signer.to_bytes())
+            .map_err(|_| SigningError::Malformed("signer"))?;
+        key.verify_strict(&statement.message(), &Signature::from_bytes(&signature))
+            .map_err(|_| SigningError::InvalidSignature)?;
+
+        Ok(statement)
+    }
+
+    /// Verify, then check the signed values are exactly `summary`'s
+    ///
+    /// Run before building the initialize transaction from `summary`.
+    pub fn verify_summary(
+        &self,
+        summary: &BuildSummary,
+        expected_signer: &Pubkey,
+    ) -> Result<(), SigningError> {
+        let signed = self.verify(expected_signer)?;
+        let expected = RootStatement::from_summary(summary)?;
+
+        let fields = [
+            (
+                "distributionId",
+                to_hex(&signed.distribution_id),
+                to_hex(&expected.distribution_id),
+            ),
+            (
+                "merkleRoot",
+                to_hex(&signed.merkle_root),
+                to_hex(&expected.merkle_root),
+            ),
+            (
+                "totalAmount",
+                signed.total_amount.to_string(),
+                expected.total_amount.to_string(),
+            ),
+            (
+                "numRecipients",
+                signed.num_recipients.to_string(),
+                expected.num_recipients.to_string(),
+            ),
+        ];
+        for (field, signed, summary) in fields {
+            if signed != summary {
+                return Err(SigningError::Mismatch {
+                    field,
+                    signed,
+                    summary,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the signature file as pretty-printed JSON
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    /// Read a signature file
+    pub fn read_json(reader: impl Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+}
+
+/// Load a Solana CLI keypair file (JSON array of 64 bytes)
+pub fn read_keypair_file(path: impl AsRef<Path>) -> Result<SigningKey, SigningError> {
+    let bytes: Vec<u8> = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| SigningError::InvalidKeypair(e.to_string()))?;
+    let bytes: [u8; 64] = bytes
+        .try_into()
+        .map_err(|_| SigningError::InvalidKeypair("expected 64 bytes".to_string()))?;
+    SigningKey::from_keypair_bytes(&bytes)
+        .map_err(|_| SigningError::InvalidKeypair("public key does not match secret".to_string()))
+}
+
+fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    let pairs = value.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+fn parse_hex32(value: &str) -> Option<[u8; 32]> {
+    parse_hex(value)?.try_into().ok()
+}
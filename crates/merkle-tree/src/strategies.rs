@@ -0,0 +1,219 @@
+//! Proptest strategies for allocations and trees
+//!
+//! For integrators property-testing their own claim pipelines against
+//! arbitrary trees: sizes, duplicate policies, leaf orders, hash kinds and
+//! schemas all vary, and amounts favour the edges (0 and `u64::MAX`).
+//! Recipients are on-curve wallets unless drawn from `off_curve_pubkey`.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn every_leaf_claims(tree in strategies::tree(1..200)) {
+//!         for index in 0..tree.len() as u64 {
+//!             prop_assert!(my_pipeline.claim(&tree, index).is_ok());
+//!         }
+//!     }
+//! }
+//! ```
+
+use ed25519_dalek::SigningKey;
+use proptest::collection::{self, SizeRange};
+use proptest::prelude::*;
+use solana_pubkey::Pubkey;
+
+use crate::build::{
+    BuildOptions, DuplicatePolicy, DustPolicy, LeafOrder, OffCurvePolicy, SplitRules,
+};
+use crate::hash::{HashKind, LeafSchema};
+use crate::tree::{Allocation, MerkleTree};
+
+/// Any wallet (the public key of a random keypair, so on-curve)
+pub fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(|secret| {
+        Pubkey::new_from_array(SigningKey::from_bytes(&secret).verifying_key().to_bytes())
+    })
+}
+
+/// Any off-curve address, like a PDA
+pub fn off_curve_pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>()
+        .prop_map(Pubkey::new_from_array)
+        .prop_filter("on-curve", |pubkey| !pubkey.is_on_curve())
+}
+
+/// Amount in `0..=max`, with `0` and `max` drawn often
+pub fn amount_up_to(max: u64) -> impl Strategy<Value = u64> {
+    prop_oneof![
+        1 => Just(0),
+        1 => Just(max),
+        3 => 0..=max,
+    ]
+}
+
+/// Any amount, with `0` and `u64::MAX` drawn often
+pub fn amount() -> impl Strategy<Value = u64> {
+    amount_up_to(u64::MAX)
+}
+
+/// Unset, or any unix timestamp
+pub fn timestamp() -> impl Strategy<Value = Option<i64>> {
+    proptest::option::of(any::<i64>())
+}
+
+/// Allocation to `recipient` with an amount in `0..=max_amount`
+fn allocation_to(recipient: Pubkey, max_amount: u64) -> impl Strategy<Value = Allocation> {
+    (amount_up_to(max_amount), timestamp(), timestamp()).prop_map(
+        move |(amount, unlock_at, expires_at)| Allocation {
+            recipient,
+            amount,
+            unlock_at,
+            expires_at,
+        },
+    )
+}
+
+/// Any allocation
+pub fn allocation() -> impl Strategy<Value = Allocation> {
+    pubkey().prop_flat_map(|recipient| allocation_to(recipient, u64::MAX))
+}
+
+/// Allocations to distinct recipients, in arbitrary order
+pub fn allocations(size: impl Into<SizeRange>) -> impl Strategy<Value = Vec<Allocation>> {
+    collection::btree_set(pubkey(), size)
+        .prop_flat_map(|recipients| {
+            recipients
+                .into_iter()
+                .map(|recipient| allocation_to(recipient, u64::MAX))
+                .collect::<Vec<_>>()
+        })
+        .prop_shuffle()
+}
+
+/// Allocations in which recipients repeat
+///
+/// Repeats of a recipient share its unlock/expiry schedule, and amounts are
+/// bounded so that merging all of them cannot overflow `u64`, so every
+/// `DuplicatePolicy` except `Error` builds.
+pub fn allocations_with_duplicates(
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<Allocation>> {
+    let size = size.into();
+    let max_amount = u64::MAX / size.end_incl().max(1) as u64;
+    repeated_allocations(size, max_amount)
+}
+
+fn repeated_allocations(
+    size: SizeRange,
+    max_amount: u64,
+) -> impl Strategy<Value = Vec<Allocation>> {
+    // About two entries per recipient
+    let recipients = 1..=size.end_incl().div_ceil(2).max(1);
+    allocations(recipients).prop_flat_map(move |pool| {
+        collection::vec((0..pool.len(), amount_up_to(max_amount)), size.clone()).prop_map(
+            move |entries| {
+                entries
+                    .into_iter()
+                    .map(|(i, amount)| Allocation {
+                        amount,
+                        ..pool[i].clone()
+                    })
+                    .collect()
+            },
+        )
+    })
+}
+
+/// Any duplicate policy
+pub fn duplicate_policy() -> impl Strategy<Value = DuplicatePolicy> {
+    prop_oneof![
+        Just(DuplicatePolicy::Error),
+        Just(DuplicatePolicy::Merge),
+        Just(DuplicatePolicy::KeepSeparate),
+    ]
+}
+
+/// Any leaf order
+pub fn leaf_order() -> impl Strategy<Value = LeafOrder> {
+    prop_oneof![Just(LeafOrder::Canonical), Just(LeafOrder::Preserve)]
+}
+
+/// Any hash kind
+pub fn hash_kind() -> impl Strategy<Value = HashKind> {
+    prop_oneof![Just(HashKind::Keccak), Just(HashKind::Sha256)]
+}
+
+/// Any leaf schema
+pub fn leaf_schema() -> impl Strategy<Value = LeafSchema> {
+    prop_oneof![Just(LeafSchema::V1), Just(LeafSchema::Vesting)]
+}
+
+/// Any off-curve policy
+pub fn off_curve_policy() -> impl Strategy<Value = OffCurvePolicy> {
+    prop_oneof![
+        Just(OffCurvePolicy::Error),
+        Just(OffCurvePolicy::Warn),
+        Just(OffCurvePolicy::Exclude),
+    ]
+}
+
+/// Any dust policy, with thresholds up to a few token-account rents
+pub fn dust_policy() -> impl Strategy<Value = DustPolicy> {
+    prop_oneof![
+        Just(DustPolicy::Keep),
+        amount_up_to(10_000_000).prop_map(|min_amount| DustPolicy::Drop { min_amount }),
+        amount_up_to(10_000_000).prop_map(|min_amount| DustPolicy::RollOver { min_amount }),
+    ]
+}
+
+/// Any build options
+pub fn build_options() -> impl Strategy<Value = BuildOptions> {
+    (
+        duplicate_policy(),
+        leaf_order(),
+        hash_kind(),
+        leaf_schema(),
+        off_curve_policy(),
+        dust_policy(),
+    )
+        .prop_map(
+            |(duplicates, order, hash_kind, schema, off_curve, dust)| BuildOptions {
+                duplicates,
+                order,
+                hash_kind,
+                schema,
+                off_curve,
+                dust,
+                splits: SplitRules::default(),
+                max_total: None,
+            },
+        )
+}
+
+/// Tree built by `MerkleTree::build` from `size` allocations under any
+/// build options
+///
+/// Inputs contain duplicates unless the policy is `DuplicatePolicy::Error`.
+/// `size` must stay within `MAX_PROOF_LEN` (2^20 leaves); inputs left empty
+/// (e.g. entirely dust) are rejected.
+pub fn tree(size: impl Into<SizeRange>) -> impl Strategy<Value = MerkleTree> {
+    let size = size.into();
+    build_options()
+        .prop_flat_map(move |options| {
+            let inputs = match options.duplicates {
+                DuplicatePolicy::Error => allocations(size.clone()).boxed(),
+                DuplicatePolicy::Merge => allocations_with_duplicates(size.clone()).boxed(),
+                DuplicatePolicy::KeepSeparate => {
+                    repeated_allocations(size.clone(), u64::MAX).boxed()
+                }
+            };
+            (any::<[u8; 32]>(), inputs, Just(options))
+        })
+        .prop_filter_map(
+            "no allocations left",
+            |(distribution_id, allocations, options)| {
+                MerkleTree::build(distribution_id, allocations, &options)
+                    .ok()
+                    .map(|(tree, _)| tree)
+            },
+        )
+}
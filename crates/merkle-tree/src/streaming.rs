@@ -0,0 +1,286 @@
+//! Streaming tree builder with bounded memory
+//!
+//! Leaves are hashed as allocations are pushed and written straight to
+//! disk; each level is then built by streaming the previous level's file,
+//! so memory use stays at a few buffers regardless of tree size. The
+//! resulting `DiskTree` answers proof queries with one small read per level.
+//! Trees use Keccak and `LeafSchema::V1`.
+//!
+//! Directory layout:
+//!
+//! - `allocations.bin` — `recipient (32) || amount (8, LE)` per leaf
+//! - `level_<n>.bin` — 32-byte node hashes of level `n` (`level_0` = leaves)
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+
+use solana_pubkey::Pubkey;
+
+use crate::error::BuildError;
+use crate::hash::{compute_leaf, hash_pair, HashKind, MAX_PROOF_LEN};
+use crate::tree::{Allocation, ClaimProof};
+
+const ALLOCATIONS_FILE: &str = "allocations.bin";
+const ALLOCATION_LEN: u64 = 40;
+const NODE_LEN: u64 = 32;
+
+fn level_path(dir: &Path, level: usize) -> PathBuf {
+    dir.join(format!("level_{level}.bin"))
+}
+
+/// Builds a tree on disk from a stream of allocations
+pub struct StreamingBuilder {
+    dir: PathBuf,
+    distribution_id: [u8; 32],
+    leaves: BufWriter<File>,
+    allocations: BufWriter<File>,
+    count: u64,
+}
+
+impl StreamingBuilder {
+    /// Start a build in `dir` (created if missing, existing files replaced)
+    pub fn create(dir: impl AsRef<Path>, distribution_id: [u8; 32]) -> Result<Self, BuildError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            leaves: BufWriter::new(File::create(level_path(&dir, 0))?),
+            allocations: BufWriter::new(File::create(dir.join(ALLOCATIONS_FILE))?),
+            dir,
+            distribution_id,
+            count: 0,
+        })
+    }
+
+    /// Append the allocation for the next index
+    pub fn push(&mut self, allocation: &Allocation) -> Result<(), BuildError> {
+        let leaf = compute_leaf(
+            HashKind::Keccak,
+            &self.distribution_id,
+            &allocation.recipient,
+            allocation.amount,
+        );
+        self.leaves.write_all(&leaf)?;
+        self.allocations.write_all(allocation.recipient.as_ref())?;
+        self.allocations
+            .write_all(&allocation.amount.to_le_bytes())?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Hash the remaining levels and open the finished tree
+    pub fn finish(mut self) -> Result<DiskTree, BuildError> {
+        if self.count == 0 {
+            return Err(BuildError::Empty);
+        }
+        self.leaves.flush()?;
+        self.allocations.flush()?;
+
+        let mut level = 0;
+        let mut len = self.count;
+
+        while len > 1 {
+            if level == MAX_PROOF_LEN {
+                return Err(BuildError::TooDeep { depth: level + 1 });
+            }
+            len = hash_level(
+                &level_path(&self.dir, level),
+                &level_path(&self.dir, level + 1),
+            )?;
+            level += 1;
+        }
+
+        DiskTree::open(&self.dir)
+    }
+}
+
+/// Stream one level into the next, returning the next level's length
+fn hash_level(input: &Path, output: &Path) -> Result<u64, BuildError> {
+    let mut reader = BufReader::new(File::open(input)?);
+    let mut writer = BufWriter::new(File::create(output)?);
+    let mut len = 0;
+
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    while read_node(&mut reader, &mut left)? {
+        let parent = if read_node(&mut reader, &mut right)? {
+            hash_pair(HashKind::Keccak, &left, &right)
+        } else {
+            // Odd node - paired with itself
+            hash_pair(HashKind::Keccak, &left, &left)
+        };
+        writer.write_all(&parent)?;
+        len += 1;
+    }
+
+    writer.flush()?;
+    Ok(len)
+}
+
+/// Read the next node, returning false at end of file
+fn read_node(reader: &mut impl Read, node: &mut [u8; 32]) -> Result<bool, BuildError> {
+    match reader.read_exact(node) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A tree built by `StreamingBuilder`, read from disk on demand
+pub struct DiskTree {
+    allocations: File,
+    /// Level files from leaves up to the root, with their node counts
+    levels: Vec<(File, u64)>,
+}
+
+impl DiskTree {
+    /// Open a tree directory written by `StreamingBuilder`
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, BuildError> {
+        let dir = dir.as_ref();
+        let mut levels = Vec::new();
+
+        loop {
+            let file = File::open(level_path(dir, levels.len()))?;
+            let len = file.metadata()?.len() / NODE_LEN;
+            levels.push((file, len));
+            if len <= 1 {
+                break;
+            }
+        }
+
+        Ok(Self {
+            allocations: File::open(dir.join(ALLOCATIONS_FILE))?,
+            levels,
+        })
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> u64 {
+        self.levels[0].1
+    }
+
+    /// Always false: empty trees cannot be built
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Proof depth
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Merkle root
+    pub fn root(&self) -> Result<[u8; 32], BuildError> {
+        self.node(self.depth(), 0)
+    }
+
+    /// Claim arguments for the leaf at `index`
+    pub fn proof_for_index(&self, index: u64) -> Result<Option<ClaimProof>, BuildError> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+
+        let mut record = [0u8; ALLOCATION_LEN as usize];
+        self.allocations
+            .read_exact_at(&mut record, index * ALLOCATION_LEN)?;
+        let recipient = Pubkey::new_from_array(record[..32].try_into().unwrap());
+        let amount = u64::from_le_bytes(record[32..].try_into().unwrap());
+
+        let mut proof = Vec::with_capacity(self.depth());
+        let mut current = index;
+
+        for level in 0..self.depth() {
+            let sibling = current ^ 1;
+            // No sibling (odd node at end of level) - the node pairs with itself
+            let position = if sibling < self.levels[level].1 {
+                sibling
+            } else {
+                current
+            };
+            proof.push(self.node(level, position)?);
+            current /= 2;
+        }
+
+        Ok(Some(ClaimProof {
+            index,
+            recipient,
+            amount,
+            proof,
+        }))
+    }
+
+    fn node(&self, level: usize, position: u64) -> Result<[u8; 32], BuildError> {
+        let mut node = [0u8; 32];
+        self.levels[level]
+            .0
+            .read_exact_at(&mut node, position * NODE_LEN)?;
+        Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::MerkleTree;
+
+    const DISTRIBUTION_ID: [u8; 32] = [4; 32];
+
+    fn allocations(size: u8) -> Vec<Allocation> {
+        (0..size)
+            .map(|i| Allocation::new(Pubkey::new_from_array([i; 32]), u64::from(i) * 10 + 1))
+            .collect()
+    }
+
+    fn build(dir: &Path, allocations: &[Allocation]) -> DiskTree {
+        let mut builder = StreamingBuilder::create(dir, DISTRIBUTION_ID).unwrap();
+        for allocation in allocations {
+            builder.push(allocation).unwrap();
+        }
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn matches_the_in_memory_tree() {
+        for size in [1u8, 2, 5, 8, 33] {
+            let dir = tempfile::tempdir().unwrap();
+            let allocations = allocations(size);
+            let disk = build(dir.path(), &allocations);
+            let memory = MerkleTree::new(DISTRIBUTION_ID, &allocations).unwrap();
+
+            assert_eq!(disk.len(), u64::from(size));
+            assert_eq!(disk.depth(), memory.depth());
+            assert_eq!(disk.root().unwrap(), memory.root(), "root of {size}");
+            for index in 0..u64::from(size) {
+                assert_eq!(
+                    disk.proof_for_index(index).unwrap(),
+                    memory.proof_for_index(index),
+                    "proof {index} of {size}"
+                );
+            }
+            assert_eq!(disk.proof_for_index(u64::from(size)).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn reopened_tree_serves_the_same_proofs() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build(dir.path(), &allocations(6));
+        let reopened = DiskTree::open(dir.path()).unwrap();
+
+        assert_eq!(reopened.root().unwrap(), built.root().unwrap());
+        assert_eq!(
+            reopened.proof_for_index(5).unwrap(),
+            built.proof_for_index(5).unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_build_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let builder = StreamingBuilder::create(dir.path(), DISTRIBUTION_ID).unwrap();
+
+        assert!(matches!(builder.finish(), Err(BuildError::Empty)));
+    }
+}
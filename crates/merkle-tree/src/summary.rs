@@ -0,0 +1,108 @@
+//! Build summary for `initialize`
+//!
+//! The summary carries exactly the values `initialize` takes from the tree,
+//! using the same field names as the TypeScript distribution artifact, so
+//! `src/jobs/init-merkle-distribution.ts` can read it directly (including
+//! from stdin) instead of operators copying totals by hand.
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use solana_pubkey::Pubkey;
+
+use crate::hash::to_hex;
+use crate::tree::MerkleTree;
+
+/// Domain prefix of `suggest_distribution_id` (matches the TypeScript builder)
+pub const DISTRIBUTION_ID_DOMAIN: &[u8] = b"L33_DIST_V1";
+
+/// Machine-readable summary of a built tree
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildSummary {
+    /// Hex-encoded distribution id the leaves are bound to
+    pub distribution_id: String,
+    /// Hex-encoded Merkle root
+    pub merkle_root: String,
+    /// Sum of all leaf amounts in base units (string, as in the artifact)
+    pub total_amount: String,
+    pub num_recipients: u64,
+    /// Absent in legacy artifacts, which are all V1
+    #[serde(default = "default_hash_scheme")]
+    pub hash_scheme: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reward_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+}
+
+impl BuildSummary {
+    /// Summarise `tree`
+    ///
+    /// `total_amount` is summed in `u128` and must fit the on-chain `u64`,
+    /// otherwise `None` is returned.
+    pub fn new(tree: &MerkleTree) -> Option<Self> {
+        let total: u128 = tree
+            .allocations()
+            .iter()
+            .map(|a| u128::from(a.amount))
+            .sum();
+        let total_amount = u64::try_from(total).ok()?;
+
+        Some(Self {
+            distribution_id: to_hex(tree.distribution_id()),
+            merkle_root: to_hex(&tree.root()),
+            total_amount: total_amount.to_string(),
+            num_recipients: tree.len() as u64,
+            hash_scheme: "v1".to_string(),
+            mint: None,
+            reward_id: None,
+            window_id: None,
+        })
+    }
+
+    /// Record the reward the tree pays out (shown and stored by the init job)
+    pub fn with_reward(mut self, mint: &Pubkey, reward_id: &str, window_id: &str) -> Self {
+        self.mint = Some(mint.to_string());
+        self.reward_id = Some(reward_id.to_string());
+        self.window_id = Some(window_id.to_string());
+        self
+    }
+
+    /// Write the summary as pretty-printed JSON
+    pub fn write_json(&self, writer: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+
+    /// Read a summary (or a full distribution artifact; proofs are ignored)
+    pub fn read_json(reader: impl Read) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+}
+
+fn default_hash_scheme() -> String {
+    "v1".to_string()
+}
+
+/// Deterministic distribution id for a reward window
+///
+/// `sha256("L33_DIST_V1" || reward_id || window_id || mint_base58 || total_amount_le)`,
+/// identical to `generateDistributionId` in `src/merkle/builder.ts`. Compute
+/// it before building, since the leaves are bound to the id.
+pub fn suggest_distribution_id(
+    reward_id: &str,
+    window_id: &str,
+    mint: &Pubkey,
+    total_amount: u64,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(DISTRIBUTION_ID_DOMAIN);
+    hasher.update(reward_id.as_bytes());
+    hasher.update(window_id.as_bytes());
+    hasher.update(mint.to_string().as_bytes());
+    hasher.update(total_amount.to_le_bytes());
+    hasher.finalize().into()
+}
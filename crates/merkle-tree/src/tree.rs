@@ -0,0 +1,442 @@
+//! Tree construction and proof generation
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use solana_pubkey::Pubkey;
+
+use crate::error::BuildError;
+use crate::hash::{hash_pair, HashKind, HashScheme, LeafSchema, ProofMode, MAX_PROOF_LEN};
+
+/// One recipient's allocation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Allocation {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Unix timestamp before which the allocation cannot be claimed
+    /// (vesting; not part of V1 leaves)
+    pub unlock_at: Option<i64>,
+    /// Unix timestamp after which the allocation can no longer be claimed
+    /// (not part of V1 leaves)
+    pub expires_at: Option<i64>,
+}
+
+impl Allocation {
+    pub fn new(recipient: Pubkey, amount: u64) -> Self {
+        Self {
+            recipient,
+            amount,
+            ..Self::default()
+        }
+    }
+}
+
+/// Everything `claim` needs for one recipient
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimProof {
+    pub index: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Sibling hashes from the leaf level upwards
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Merkle tree over a distribution's allocations
+///
+/// Leaf `i` is the allocation at position `i` of the input, which is also
+/// the claim `index` used on-chain.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    distribution_id: [u8; 32],
+    hash_kind: HashKind,
+    schema: LeafSchema,
+    allocations: Vec<Allocation>,
+    /// First index of each recipient
+    index_by_recipient: HashMap<Pubkey, u64>,
+    /// Levels from leaves (`levels[0]`) up to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Build a Keccak tree of V1 leaves from allocations in claim-index order
+    pub fn new(distribution_id: [u8; 32], allocations: &[Allocation]) -> Result<Self, BuildError> {
+        Self::with_hashing(
+            distribution_id,
+            allocations,
+            HashKind::Keccak,
+            LeafSchema::V1,
+        )
+    }
+
+    /// Build a Keccak tree with the given leaf schema
+    pub fn with_schema(
+        distribution_id: [u8; 32],
+        allocations: &[Allocation],
+        schema: LeafSchema,
+    ) -> Result<Self, BuildError> {
+        Self::with_hashing(distribution_id, allocations, HashKind::Keccak, schema)
+    }
+
+    /// Build a tree with the given hash function and leaf schema
+    pub fn with_hashing(
+        distribution_id: [u8; 32],
+        allocations: &[Allocation],
+        hash_kind: HashKind,
+        schema: LeafSchema,
+    ) -> Result<Self, BuildError> {
+        let leaves = allocations
+            .iter()
+            .map(|a| schema.leaf(hash_kind, &distribution_id, a))
+            .collect();
+        let levels = build_levels(hash_kind, leaves)?;
+
+        Ok(Self::from_parts(
+            distribution_id,
+            hash_kind,
+            schema,
+            allocations.to_vec(),
+            levels,
+        ))
+    }
+
+    /// Distribution id the leaves are bound to
+    pub fn distribution_id(&self) -> &[u8; 32] {
+        &self.distribution_id
+    }
+
+    /// Hash function the tree was built with
+    pub fn hash_kind(&self) -> HashKind {
+        self.hash_kind
+    }
+
+    /// Leaf schema the tree was built with
+    pub fn schema(&self) -> LeafSchema {
+        self.schema
+    }
+
+    /// Merkle root to pass to `initialize`
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Always false: empty trees cannot be built
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Proof depth (number of siblings in every proof)
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Leaf hash at `index`
+    pub fn leaf(&self, index: usize) -> Option<[u8; 32]> {
+        self.levels[0].get(index).copied()
+    }
+
+    /// Allocation at `index`
+    pub fn allocation(&self, index: usize) -> Option<&Allocation> {
+        self.allocations.get(index)
+    }
+
+    /// All allocations in claim-index order
+    pub fn allocations(&self) -> &[Allocation] {
+        &self.allocations
+    }
+
+    /// Levels from leaves up to the root
+    pub(crate) fn levels(&self) -> &[Vec<[u8; 32]>] {
+        &self.levels
+    }
+
+    /// Reassemble a tree from already-hashed levels
+    pub(crate) fn from_parts(
+        distribution_id: [u8; 32],
+        hash_kind: HashKind,
+        schema: LeafSchema,
+        allocations: Vec<Allocation>,
+        levels: Vec<Vec<[u8; 32]>>,
+    ) -> Self {
+        Self {
+            distribution_id,
+            hash_kind,
+            schema,
+            index_by_recipient: index_by_recipient(&allocations),
+            allocations,
+            levels,
+        }
+    }
+
+    /// Claim arguments for the leaf at `index`
+    pub fn proof_for_index(&self, index: u64) -> Option<ClaimProof> {
+        let position = usize::try_from(index).ok()?;
+        let allocation = self.allocations.get(position)?;
+
+        Some(ClaimProof {
+            index,
+            recipient: allocation.recipient,
+            amount: allocation.amount,
+            proof: self.get_proof(position)?,
+        })
+    }
+
+    /// Claim arguments for `recipient`
+    ///
+    /// If a recipient appears more than once, this returns their first leaf;
+    /// use `proof_for_index` for the others.
+    pub fn proof_for_recipient(&self, recipient: &Pubkey) -> Option<ClaimProof> {
+        self.proof_for_index(*self.index_by_recipient.get(recipient)?)
+    }
+
+    /// Proof for the leaf at `index`, from the leaf level upwards
+    pub fn get_proof(&self, index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let mut proof = Vec::with_capacity(self.depth());
+        let mut current = index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = current ^ 1;
+            // No sibling (odd node at end of level) - the node pairs with itself
+            proof.push(*level.get(sibling).unwrap_or(&level[current]));
+            current /= 2;
+        }
+
+        Some(proof)
+    }
+
+    /// Append allocations as new leaves after the existing ones
+    ///
+    /// Existing indices and subtrees are kept; only the nodes on the path
+    /// from the first new leaf to the root are rehashed. Returns the indices
+    /// of the new leaves. On error the tree is unchanged.
+    pub fn append(&mut self, allocations: &[Allocation]) -> Result<Range<u64>, BuildError> {
+        let start = self.len();
+        let end = start + allocations.len();
+
+        let mut depth = 0;
+        let mut len = end;
+        while len > 1 {
+            len = len.div_ceil(2);
+            depth += 1;
+        }
+        if depth > MAX_PROOF_LEN {
+            return Err(BuildError::TooDeep { depth });
+        }
+
+        for (offset, allocation) in allocations.iter().enumerate() {
+            self.index_by_recipient
+                .entry(allocation.recipient)
+                .or_insert((start + offset) as u64);
+            self.levels[0].push(self.schema.leaf(
+                self.hash_kind,
+                &self.distribution_id,
+                allocation,
+            ));
+        }
+        self.allocations.extend_from_slice(allocations);
+
+        // First node whose children changed, per level
+        let mut dirty = start;
+        let mut level = 0;
+        while self.levels[level].len() > 1 {
+            dirty /= 2;
+            let next_len = self.levels[level].len().div_ceil(2);
+            if level + 1 == self.levels.len() {
+                self.levels.push(Vec::with_capacity(next_len));
+            }
+
+            let (lower, upper) = self.levels.split_at_mut(level + 1);
+            let (children, parents) = (&lower[level], &mut upper[0]);
+            parents.truncate(dirty);
+            parents.extend(children[dirty * 2..].chunks(2).map(|pair| match pair {
+                [left, right] => hash_pair(self.hash_kind, left, right),
+                // Odd node - paired with itself
+                [single] => hash_pair(self.hash_kind, single, single),
+                _ => unreachable!(),
+            }));
+            level += 1;
+        }
+
+        Ok(start as u64..end as u64)
+    }
+
+    /// Verify a Keccak proof the same way the on-chain program does
+    pub fn verify(root: &[u8; 32], leaf: &[u8; 32], proof: &[[u8; 32]]) -> bool {
+        Self::verify_with(HashKind::Keccak, root, leaf, proof)
+    }
+
+    /// Verify a proof built with `kind`
+    pub fn verify_with(
+        kind: HashKind,
+        root: &[u8; 32],
+        leaf: &[u8; 32],
+        proof: &[[u8; 32]],
+    ) -> bool {
+        // Builder trees are V1 / sorted, where the index plays no part
+        merkle_core::verify_proof(
+            kind,
+            HashScheme::V1,
+            ProofMode::Sorted,
+            proof,
+            root,
+            *leaf,
+            0,
+        )
+    }
+}
+
+/// First index of each recipient
+fn index_by_recipient(allocations: &[Allocation]) -> HashMap<Pubkey, u64> {
+    let mut index_by_recipient = HashMap::with_capacity(allocations.len());
+    for (index, allocation) in allocations.iter().enumerate() {
+        index_by_recipient
+            .entry(allocation.recipient)
+            .or_insert(index as u64);
+    }
+    index_by_recipient
+}
+
+/// Hash levels from the leaves up to the root
+fn build_levels(kind: HashKind, leaves: Vec<[u8; 32]>) -> Result<Vec<Vec<[u8; 32]>>, BuildError> {
+    if leaves.is_empty() {
+        return Err(BuildError::Empty);
+    }
+
+    let mut levels = vec![leaves];
+
+    while levels[levels.len() - 1].len() > 1 {
+        let level = &levels[levels.len() - 1];
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(kind, left, right),
+                // Odd node - paired with itself
+                [single] => hash_pair(kind, single, single),
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+
+    let depth = levels.len() - 1;
+    if depth > MAX_PROOF_LEN {
+        return Err(BuildError::TooDeep { depth });
+    }
+
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::compute_leaf;
+
+    const DISTRIBUTION_ID: [u8; 32] = [9; 32];
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn tree(size: u8) -> MerkleTree {
+        let allocations: Vec<_> = (0..size)
+            .map(|i| Allocation::new(key(i), u64::from(i) + 1))
+            .collect();
+        MerkleTree::new(DISTRIBUTION_ID, &allocations).unwrap()
+    }
+
+    #[test]
+    fn proof_for_index_carries_claim_arguments() {
+        let tree = tree(5);
+        let claim = tree.proof_for_index(3).unwrap();
+
+        assert_eq!(claim.recipient, key(3));
+        assert_eq!(claim.amount, 4);
+        assert_eq!(claim.proof.len(), tree.depth());
+        assert!(MerkleTree::verify(
+            &tree.root(),
+            &compute_leaf(HashKind::Keccak, &DISTRIBUTION_ID, &key(3), 4),
+            &claim.proof,
+        ));
+        assert_eq!(tree.proof_for_index(5), None);
+    }
+
+    #[test]
+    fn proof_for_recipient_returns_first_leaf() {
+        let allocations = [
+            Allocation::new(key(1), 10),
+            Allocation::new(key(2), 20),
+            Allocation::new(key(1), 30),
+        ];
+        let tree = MerkleTree::new(DISTRIBUTION_ID, &allocations).unwrap();
+
+        assert_eq!(tree.proof_for_recipient(&key(1)).unwrap().index, 0);
+        assert_eq!(tree.proof_for_index(2).unwrap().amount, 30);
+        assert_eq!(tree.proof_for_recipient(&key(3)), None);
+    }
+
+    #[test]
+    fn odd_last_node_pairs_with_itself() {
+        let tree = tree(3);
+        let proof = tree.get_proof(2).unwrap();
+
+        assert_eq!(proof[0], tree.leaf(2).unwrap());
+        assert!(MerkleTree::verify(
+            &tree.root(),
+            &tree.leaf(2).unwrap(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn multiproof_verifies_against_the_root() {
+        // Leaves 0 and 1 share a parent; leaf 2's branch comes from the proof
+        let tree = tree(4);
+        let leaves = [tree.leaf(0).unwrap(), tree.leaf(1).unwrap()];
+        let proof = [tree.levels()[1][1]];
+
+        assert!(merkle_core::verify_multiproof(
+            HashKind::Keccak,
+            HashScheme::V1,
+            &proof,
+            &[true, false],
+            &tree.root(),
+            &leaves,
+        ));
+        assert!(!merkle_core::verify_multiproof(
+            HashKind::Keccak,
+            HashScheme::V1,
+            &proof,
+            &[false, true],
+            &tree.root(),
+            &leaves,
+        ));
+    }
+
+    #[test]
+    fn append_matches_a_full_rebuild() {
+        let full = tree(11);
+        let mut appended = tree(4);
+
+        let indices = appended.append(&full.allocations()[4..]).unwrap();
+
+        assert_eq!(indices, 4..11);
+        assert_eq!(appended.root(), full.root());
+        assert_eq!(appended.get_proof(7), full.get_proof(7));
+    }
+
+    #[test]
+    fn empty_input_is_rejected() {
+        assert!(matches!(
+            MerkleTree::new(DISTRIBUTION_ID, &[]),
+            Err(BuildError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+//! Verify a tree against its on-chain `Distribution`
+//!
+//! Belt-and-suspenders check before claims are enabled: the tree's root is
+//! recomputed from its allocations (not trusted from the file) and compared,
+//! together with the total, recipient count and hashing parameters, with
+//! what `initialize` stored on-chain.
+
+use std::fmt;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::format::FormatError;
+use crate::hash::to_hex;
+use crate::tree::MerkleTree;
+
+/// Length of a `Distribution` account (discriminator + zero-copy struct)
+pub const DISTRIBUTION_ACCOUNT_LEN: usize = 8 + 400;
+
+/// Errors raised while verifying a tree
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    #[error(transparent)]
+    Format(#[from] FormatError),
+    #[error("tree file is inconsistent: {0}")]
+    InconsistentTree(String),
+    #[error("invalid Distribution account: {0}")]
+    InvalidAccount(String),
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error("tree does not match on-chain state:\n{}", join_mismatches(.0))]
+    Mismatch(Vec<Mismatch>),
+}
+
+/// One field that differs between the tree and the chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub tree: String,
+    pub on_chain: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: tree {}, on-chain {}",
+            self.field, self.tree, self.on_chain
+        )
+    }
+}
+
+fn join_mismatches(mismatches: &[Mismatch]) -> String {
+    mismatches
+        .iter()
+        .map(|m| format!("  {m}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Fields of an on-chain `Distribution` the tree must agree with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistributionState {
+    pub distribution_id: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub num_recipients: u64,
+    pub hash_scheme: u8,
+    pub hash_kind: u8,
+    pub proof_mode: u8,
+}
+
+impl DistributionState {
+    /// Parse raw `Distribution` account data
+    pub fn parse(data: &[u8]) -> Result<Self, VerifyError> {
+        if data.len() != DISTRIBUTION_ACCOUNT_LEN {
+            return Err(VerifyError::InvalidAccount(format!(
+                "expected {DISTRIBUTION_ACCOUNT_LEN} bytes, got {}",
+                data.len()
+            )));
+        }
+        if data[..8] != account_discriminator("Distribution") {
+            return Err(VerifyError::InvalidAccount(
+                "discriminator is not Distribution".to_string(),
+            ));
+        }
+
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        // Offsets include the 8-byte discriminator; see `Distribution` in the program
+        Ok(Self {
+            distribution_id: data[136..168].try_into().unwrap(),
+            merkle_root: data[168..200].try_into().unwrap(),
+            total_amount: u64_at(200),
+            num_recipients: u64_at(216),
+            hash_scheme: data[395],
+            hash_kind: data[396],
+            proof_mode: data[397],
+        })
+    }
+}
+
+/// Anchor account discriminator: `sha256("account:<Name>")[..8]`
+fn account_discriminator(name: &str) -> [u8; 8] {
+    let hash: [u8; 32] = Sha256::digest(format!("account:{name}")).into();
+    hash[..8].try_into().unwrap()
+}
+
+/// Check `tree` against on-chain `state`, reporting every mismatch
+pub fn verify_tree(tree: &MerkleTree, state: &DistributionState) -> Result<(), VerifyError> {
+    let rebuilt = MerkleTree::with_hashing(
+        *tree.distribution_id(),
+        tree.allocations(),
+        tree.hash_kind(),
+        tree.schema(),
+    )
+    .map_err(|e| VerifyError::InconsistentTree(e.to_string()))?;
+    if rebuilt.root() != tree.root() {
+        return Err(VerifyError::InconsistentTree(
+            "stored root does not match the root recomputed from allocations".to_string(),
+        ));
+    }
+
+    let total_amount: u128 = tree
+        .allocations()
+        .iter()
+        .map(|a| u128::from(a.amount))
+        .sum();
+    let mut mismatches = Vec::new();
+    let mut check = |field, tree: String, on_chain: String| {
+        if tree != on_chain {
+            mismatches.push(Mismatch {
+                field,
+                tree,
+                on_chain,
+            });
+        }
+    };
+
+    check(
+        "distribution_id",
+        to_hex(tree.distribution_id()),
+        to_hex(&state.distribution_id),
+    );
+    check(
+        "merkle_root",
+        to_hex(&rebuilt.root()),
+        to_hex(&state.merkle_root),
+    );
+    check(
+        "total_amount",
+        total_amount.to_string(),
+        state.total_amount.to_string(),
+    );
+    check(
+        "num_recipients",
+        tree.len().to_string(),
+        state.num_recipients.to_string(),
+    );
+    // The builder produces unprefixed (HashScheme::V1), sorted-pair trees
+    check(
+        "hash_scheme",
+        "0".to_string(),
+        state.hash_scheme.to_string(),
+    );
+    check(
+        "hash_kind",
+        (tree.hash_kind() as u8).to_string(),
+        state.hash_kind.to_string(),
+    );
+    check("proof_mode", "0".to_string(), state.proof_mode.to_string());
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(VerifyError::Mismatch(mismatches))
+    }
+}
+
+/// Load a tree file, fetch its `Distribution` over RPC and verify them
+#[cfg(feature = "rpc")]
+pub fn verify_on_chain(
+    rpc_url: &str,
+    program_id: &solana_pubkey::Pubkey,
+    tree_path: impl AsRef<Path>,
+) -> Result<(), VerifyError> {
+    use solana_client::rpc_client::RpcClient;
+    use solana_pubkey::Pubkey;
+
+    let tree = MerkleTree::load(tree_path)?;
+    let (address, _) =
+        Pubkey::find_program_address(&[b"distribution", tree.distribution_id()], program_id);
+
+    let data = RpcClient::new(rpc_url.to_string())
+        .get_account_data(&address)
+        .map_err(|e| VerifyError::Rpc(format!("{address}: {e}")))?;
+
+    verify_tree(&tree, &DistributionState::parse(&data)?)
+}
+
+/// Verify a tree file against already-fetched `Distribution` account data
+pub fn verify_tree_file(
+    tree_path: impl AsRef<Path>,
+    account_data: &[u8],
+) -> Result<(), VerifyError> {
+    let tree = MerkleTree::load(tree_path)?;
+    verify_tree(&tree, &DistributionState::parse(account_data)?)
+}
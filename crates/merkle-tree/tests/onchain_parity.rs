@@ -0,0 +1,292 @@
+//! Cross-tests: builder hashing must match the on-chain program byte for byte,
+//! and reference V2 / positional trees must verify with the program
+
+use anchor_lang::prelude::Pubkey as ChainPubkey;
+use merkle_distributor::{HashKind as ChainHashKind, HashScheme, ProofMode};
+use merkle_tree::{Allocation, HashKind, MerkleTree, Pubkey, DOMAIN_SEPARATOR};
+
+const KINDS: [(HashKind, ChainHashKind); 2] = [
+    (HashKind::Keccak, ChainHashKind::Keccak),
+    (HashKind::Sha256, ChainHashKind::Sha256),
+];
+
+fn recipient(seed: u8) -> Pubkey {
+    Pubkey::new_from_array([seed; 32])
+}
+
+fn chain_pubkey(pubkey: &Pubkey) -> ChainPubkey {
+    ChainPubkey::new_from_array(pubkey.to_bytes())
+}
+
+#[test]
+fn compute_leaf_matches_program() {
+    let amounts = [0, 1, 1_500_000_000, u64::MAX];
+
+    for (kind, chain_kind) in KINDS {
+        for seed in [0u8, 7, 255] {
+            for amount in amounts {
+                let distribution_id = [seed.wrapping_mul(3); 32];
+                let recipient = recipient(seed);
+
+                assert_eq!(
+                    merkle_tree::compute_leaf(kind, &distribution_id, &recipient, amount),
+                    merkle_distributor::compute_leaf(
+                        chain_kind,
+                        HashScheme::V1,
+                        &distribution_id,
+                        &chain_pubkey(&recipient),
+                        amount,
+                    ),
+                    "{kind:?} leaf for seed {seed}, amount {amount}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn hash_pair_matches_program() {
+    let nodes = [[0u8; 32], [1u8; 32], [0xab; 32], [0xff; 32]];
+
+    for (kind, chain_kind) in KINDS {
+        for a in &nodes {
+            for b in &nodes {
+                assert_eq!(
+                    merkle_tree::hash_pair(kind, a, b),
+                    merkle_distributor::hash_pair(chain_kind, HashScheme::V1, a, b),
+                    "{kind:?} pair {:02x}/{:02x}",
+                    a[0],
+                    b[0]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn proofs_verify_on_chain() {
+    let distribution_id = [42u8; 32];
+
+    for (kind, chain_kind) in KINDS {
+        // Odd and even sizes exercise the self-paired last node
+        for size in [1u8, 2, 3, 7, 8, 33] {
+            let allocations: Vec<_> = (0..size)
+                .map(|i| Allocation::new(recipient(i), u64::from(i) * 1_000 + 1))
+                .collect();
+            let tree = MerkleTree::with_hashing(
+                distribution_id,
+                &allocations,
+                kind,
+                merkle_tree::LeafSchema::V1,
+            )
+            .unwrap();
+
+            for index in 0..u64::from(size) {
+                let claim = tree.proof_for_index(index).unwrap();
+                let leaf = merkle_distributor::compute_leaf(
+                    chain_kind,
+                    HashScheme::V1,
+                    &distribution_id,
+                    &chain_pubkey(&claim.recipient),
+                    claim.amount,
+                );
+
+                assert!(
+                    merkle_distributor::verify_proof(
+                        chain_kind,
+                        HashScheme::V1,
+                        ProofMode::Sorted,
+                        &claim.proof,
+                        &tree.root(),
+                        leaf,
+                        index,
+                    ),
+                    "{kind:?} proof for index {index} of {size}"
+                );
+            }
+        }
+    }
+}
+
+/// Reference `HashScheme::V2` leaf, hashed with the prefix spelled out
+fn v2_leaf(
+    kind: HashKind,
+    distribution_id: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+) -> [u8; 32] {
+    kind.hashv(&[
+        &[0x00],
+        DOMAIN_SEPARATOR,
+        distribution_id,
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+}
+
+/// Reference tree levels, leaves first; an odd last node pairs with itself
+fn levels(
+    leaves: Vec<[u8; 32]>,
+    node: impl Fn(&[u8; 32], &[u8; 32]) -> [u8; 32],
+) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn proof(levels: &[Vec<[u8; 32]>], index: usize) -> Vec<[u8; 32]> {
+    let mut current = index;
+    levels[..levels.len() - 1]
+        .iter()
+        .map(|level| {
+            let sibling = *level.get(current ^ 1).unwrap_or(&level[current]);
+            current /= 2;
+            sibling
+        })
+        .collect()
+}
+
+#[test]
+fn v2_hashing_matches_program() {
+    let distribution_id = [5u8; 32];
+    let nodes = [[0u8; 32], [0xab; 32], [0xff; 32]];
+
+    for (kind, chain_kind) in KINDS {
+        for seed in [0u8, 9] {
+            let recipient = recipient(seed);
+            let leaf = merkle_distributor::compute_leaf(
+                chain_kind,
+                HashScheme::V2,
+                &distribution_id,
+                &chain_pubkey(&recipient),
+                1_000,
+            );
+
+            assert_eq!(leaf, v2_leaf(kind, &distribution_id, &recipient, 1_000));
+            assert_ne!(
+                leaf,
+                merkle_tree::compute_leaf(kind, &distribution_id, &recipient, 1_000),
+                "{kind:?} V2 leaf must differ from V1"
+            );
+        }
+
+        for a in &nodes {
+            for b in &nodes {
+                let (low, high) = if a <= b { (a, b) } else { (b, a) };
+                assert_eq!(
+                    merkle_distributor::hash_pair(chain_kind, HashScheme::V2, a, b),
+                    kind.hashv(&[&[0x01], low, high]),
+                    "{kind:?} V2 pair {:02x}/{:02x}",
+                    a[0],
+                    b[0]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn v2_sorted_proofs_verify_on_chain() {
+    let distribution_id = [6u8; 32];
+
+    for (kind, chain_kind) in KINDS {
+        for size in [1u8, 2, 5, 8] {
+            let leaves: Vec<_> = (0..size)
+                .map(|i| v2_leaf(kind, &distribution_id, &recipient(i), u64::from(i) + 1))
+                .collect();
+            let levels = levels(leaves.clone(), |a, b| {
+                let (low, high) = if a <= b { (a, b) } else { (b, a) };
+                kind.hashv(&[&[0x01], low, high])
+            });
+            let root = levels[levels.len() - 1][0];
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof(&levels, index);
+                let verify = |scheme| {
+                    merkle_distributor::verify_proof(
+                        chain_kind,
+                        scheme,
+                        ProofMode::Sorted,
+                        &proof,
+                        &root,
+                        *leaf,
+                        index as u64,
+                    )
+                };
+
+                assert!(verify(HashScheme::V2), "{kind:?} proof {index} of {size}");
+                if size > 1 {
+                    assert!(
+                        !verify(HashScheme::V1),
+                        "{kind:?} V1 accepted proof {index} of {size}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn positional_proofs_verify_on_chain() {
+    let distribution_id = [7u8; 32];
+
+    for (kind, chain_kind) in KINDS {
+        for scheme in [HashScheme::V1, HashScheme::V2] {
+            for size in [1u8, 3, 4, 7] {
+                let leaves: Vec<_> = (0..size)
+                    .map(|i| {
+                        merkle_distributor::compute_leaf(
+                            chain_kind,
+                            scheme,
+                            &distribution_id,
+                            &chain_pubkey(&recipient(i)),
+                            u64::from(i) + 1,
+                        )
+                    })
+                    .collect();
+                // Children in position order, never sorted
+                let levels = levels(leaves.clone(), |left, right| match scheme {
+                    HashScheme::V1 => kind.hashv(&[left, right]),
+                    HashScheme::V2 => kind.hashv(&[&[0x01], left, right]),
+                });
+                let root = levels[levels.len() - 1][0];
+                let depth = levels.len() - 1;
+
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = proof(&levels, index);
+                    let verify = |index| {
+                        merkle_distributor::verify_proof(
+                            chain_kind,
+                            scheme,
+                            ProofMode::Positional,
+                            &proof,
+                            &root,
+                            *leaf,
+                            index,
+                        )
+                    };
+
+                    assert!(
+                        verify(index as u64),
+                        "{kind:?} {scheme:?} proof {index} of {size}"
+                    );
+                    // The same leaf can't be claimed under another index
+                    assert!(!verify((index as u64) | (1 << depth)));
+                    // A self-paired last leaf hashes the same from either side
+                    if (index ^ 1) < leaves.len() {
+                        assert!(
+                            !verify(index as u64 ^ 1),
+                            "{kind:?} {scheme:?} swapped index {index}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
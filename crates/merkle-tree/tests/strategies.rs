@@ -0,0 +1,181 @@
+//! Builder properties over the exported proptest strategies
+
+use std::collections::HashMap;
+
+use merkle_tree::strategies;
+use merkle_tree::{
+    Allocation, BuildError, BuildOptions, DuplicatePolicy, DustPolicy, LeafOrder, MerkleTree,
+    OffCurvePolicy, Pubkey, SplitRules, SplitShare, SplitTarget,
+};
+use proptest::prelude::*;
+
+fn wallet_totals<'a>(
+    allocations: impl IntoIterator<Item = &'a Allocation>,
+) -> HashMap<Pubkey, u128> {
+    let mut totals = HashMap::new();
+    for allocation in allocations {
+        *totals.entry(allocation.recipient).or_default() += u128::from(allocation.amount);
+    }
+    totals
+}
+
+proptest! {
+    #[test]
+    fn every_proof_verifies(tree in strategies::tree(1..300)) {
+        for index in 0..tree.len() {
+            let proof = tree.get_proof(index).unwrap();
+            prop_assert_eq!(proof.len(), tree.depth());
+            prop_assert!(MerkleTree::verify_with(
+                tree.hash_kind(),
+                &tree.root(),
+                &tree.leaf(index).unwrap(),
+                &proof,
+            ));
+        }
+    }
+
+    #[test]
+    fn canonical_root_ignores_input_order(
+        distribution_id in any::<[u8; 32]>(),
+        allocations in strategies::allocations_with_duplicates(1..200),
+        options in strategies::build_options(),
+    ) {
+        let options = BuildOptions {
+            duplicates: DuplicatePolicy::KeepSeparate,
+            order: LeafOrder::Canonical,
+            // Dust filtering could leave nothing to build
+            dust: DustPolicy::Keep,
+            ..options
+        };
+        let reversed = allocations.iter().rev().cloned().collect();
+
+        let (tree, _) = MerkleTree::build(distribution_id, allocations, &options).unwrap();
+        let (reversed, _) = MerkleTree::build(distribution_id, reversed, &options).unwrap();
+        prop_assert_eq!(tree.root(), reversed.root());
+    }
+
+    #[test]
+    fn merge_preserves_wallet_totals(
+        distribution_id in any::<[u8; 32]>(),
+        allocations in strategies::allocations_with_duplicates(1..200),
+    ) {
+        let options = BuildOptions {
+            duplicates: DuplicatePolicy::Merge,
+            ..BuildOptions::default()
+        };
+        let (tree, report) =
+            MerkleTree::build(distribution_id, allocations.clone(), &options).unwrap();
+
+        let totals = wallet_totals(&allocations);
+        prop_assert_eq!(tree.len(), totals.len());
+        prop_assert_eq!(wallet_totals(tree.allocations()), totals);
+        prop_assert_eq!(report.merged_allocations, allocations.len() - tree.len());
+    }
+
+    #[test]
+    fn rolled_over_dust_accounts_for_the_filtered_total(
+        distribution_id in any::<[u8; 32]>(),
+        allocations in strategies::allocations(1..200),
+        min_amount in strategies::amount(),
+    ) {
+        let options = BuildOptions {
+            dust: DustPolicy::RollOver { min_amount },
+            ..BuildOptions::default()
+        };
+        let input_total: u128 = allocations.iter().map(|a| u128::from(a.amount)).sum();
+
+        match MerkleTree::build(distribution_id, allocations.clone(), &options) {
+            Ok((tree, report)) => {
+                prop_assert!(tree.allocations().iter().all(|a| a.amount >= min_amount));
+                let kept: u128 = tree.allocations().iter().map(|a| u128::from(a.amount)).sum();
+                prop_assert_eq!(kept + report.dust_amount, input_total);
+                prop_assert_eq!(report.rolled_over.len(), report.dust_allocations);
+                prop_assert_eq!(tree.len() + report.dust_allocations, allocations.len());
+            }
+            Err(BuildError::Empty) => {
+                prop_assert!(allocations.iter().all(|a| a.amount < min_amount));
+            }
+            Err(e) => panic!("unexpected build error: {e}"),
+        }
+    }
+
+    #[test]
+    fn splits_conserve_totals_and_round_by_one_unit(
+        distribution_id in any::<[u8; 32]>(),
+        allocations in strategies::allocations(1..100),
+        holder_weight in 0..100u32,
+        custody in proptest::collection::vec((strategies::pubkey(), 1..100u32), 1..4),
+    ) {
+        let mut global = vec![SplitShare { target: SplitTarget::Holder, weight: holder_weight }];
+        global.extend(custody.iter().map(|&(address, weight)| SplitShare {
+            target: SplitTarget::Address(address),
+            weight,
+        }));
+        let total_weight: u128 = global.iter().map(|share| u128::from(share.weight)).sum();
+        let rules = SplitRules { global, ..SplitRules::default() };
+
+        for allocation in &allocations {
+            let parts = rules.split(allocation).unwrap();
+            let amount = u128::from(allocation.amount);
+            prop_assert_eq!(parts.iter().map(|p| u128::from(p.amount)).sum::<u128>(), amount);
+            for part in &parts {
+                let weight: u128 = rules
+                    .global
+                    .iter()
+                    .filter(|share| match share.target {
+                        SplitTarget::Holder => part.recipient == allocation.recipient,
+                        SplitTarget::Address(address) => part.recipient == address,
+                    })
+                    .map(|share| u128::from(share.weight))
+                    .sum();
+                let exact = amount * weight;
+                let rounded = u128::from(part.amount) * total_weight;
+                prop_assert!(rounded.abs_diff(exact) < total_weight);
+            }
+        }
+
+        // Zero amounts split into no leaves, which could leave nothing to build
+        prop_assume!(allocations.iter().any(|allocation| allocation.amount > 0));
+        let options = BuildOptions {
+            duplicates: DuplicatePolicy::KeepSeparate,
+            splits: rules,
+            ..BuildOptions::default()
+        };
+        let (tree, report) = MerkleTree::build(distribution_id, allocations.clone(), &options).unwrap();
+        let input_total: u128 = allocations.iter().map(|a| u128::from(a.amount)).sum();
+        let tree_total: u128 = tree.allocations().iter().map(|a| u128::from(a.amount)).sum();
+        prop_assert_eq!(tree_total, input_total);
+        prop_assert_eq!(report.split_allocations, allocations.len());
+        prop_assert_eq!(report.split_leaves, tree.len());
+    }
+
+    #[test]
+    fn off_curve_recipients_are_flagged(
+        distribution_id in any::<[u8; 32]>(),
+        wallets in strategies::allocations(1..100),
+        program_addresses in proptest::collection::vec(strategies::off_curve_pubkey(), 1..10),
+    ) {
+        let mut allocations: Vec<_> = program_addresses
+            .iter()
+            .map(|&address| Allocation::new(address, 1))
+            .collect();
+        allocations.extend(wallets.iter().cloned());
+
+        let rejected = MerkleTree::build(distribution_id, allocations.clone(), &BuildOptions::default());
+        let first_rejected = matches!(rejected, Err(BuildError::OffCurveRecipient { index: 0, .. }));
+        prop_assert!(first_rejected);
+
+        let options = BuildOptions {
+            order: LeafOrder::Preserve,
+            duplicates: DuplicatePolicy::KeepSeparate,
+            off_curve: OffCurvePolicy::Exclude,
+            ..BuildOptions::default()
+        };
+        let (tree, report) = MerkleTree::build(distribution_id, allocations, &options).unwrap();
+        prop_assert_eq!(tree.allocations(), &wallets[..]);
+        prop_assert_eq!(
+            report.excluded.iter().map(|flagged| flagged.index).collect::<Vec<_>>(),
+            (0..program_addresses.len()).collect::<Vec<_>>()
+        );
+    }
+}
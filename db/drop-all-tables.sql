@@ -1,7 +1,63 @@
--- Drop all existing tables (CASCADE will drop dependent objects)
--- WARNING: This will delete ALL data in these tables!
+-- Drop every table, view and function the migrations create (CASCADE will
+-- drop dependent objects), then schema_migrations itself, so
+-- `npm run migrate` rebuilds the schema from scratch.
+-- WARNING: This will delete ALL data, Merkle distributions and claim events included!
+-- Extend this script whenever a migration creates a new object.
 
+-- 0008_holder_diffs
+DROP TABLE IF EXISTS holder_diff_wallets CASCADE;
+DROP TABLE IF EXISTS holder_diffs CASCADE;
+
+-- 0007_ingestion_lag
+DROP TABLE IF EXISTS ingestion_lag CASCADE;
+
+-- 0006_raw_data_retention: columns and indexes only
+
+-- 0005_ingestion_dead_letters
+DROP TABLE IF EXISTS ingestion_dead_letters CASCADE;
+
+-- 0004_slot_block_times
+DROP TABLE IF EXISTS slot_block_times CASCADE;
+
+-- 0003_deployments: columns and keys only
+
+-- 0002_merkle_schema
+DROP VIEW IF EXISTS merkle_pending_claims CASCADE;
+DROP VIEW IF EXISTS merkle_distribution_summary CASCADE;
+DROP TABLE IF EXISTS finance_exports CASCADE;
+DROP TABLE IF EXISTS ingestion_checkpoints CASCADE;
+DROP TABLE IF EXISTS api_usage CASCADE;
+DROP TABLE IF EXISTS alert_log CASCADE;
+DROP TABLE IF EXISTS merkle_reconciliation_divergences CASCADE;
+DROP TABLE IF EXISTS webhook_deliveries CASCADE;
+DROP TABLE IF EXISTS merkle_claim_events_staged CASCADE;
+DROP TABLE IF EXISTS merkle_claim_events CASCADE;
+DROP TABLE IF EXISTS merkle_relayer_batches CASCADE;
+DROP TABLE IF EXISTS merkle_claims CASCADE;
+DROP TABLE IF EXISTS merkle_distributions CASCADE;
+DROP FUNCTION IF EXISTS update_merkle_distribution_timestamp() CASCADE;
+
+-- 0001_schema
+DROP TABLE IF EXISTS epoch_reward_budgets CASCADE;
+DROP TABLE IF EXISTS epoch_validator_rewards CASCADE;
+DROP VIEW IF EXISTS epoch_snapshot_holders_sol CASCADE;
+DROP TABLE IF EXISTS epoch_exchange_rates CASCADE;
+DROP VIEW IF EXISTS epoch_twab_holders CASCADE;
+DROP TABLE IF EXISTS epoch_balance_sample_holders CASCADE;
+DROP TABLE IF EXISTS epoch_balance_samples CASCADE;
+DROP VIEW IF EXISTS epoch_native_stake_holders CASCADE;
+DROP TABLE IF EXISTS epoch_snapshot_native_stake CASCADE;
+DROP TABLE IF EXISTS epoch_native_stake_snapshots CASCADE;
+DROP VIEW IF EXISTS epoch_snapshot_holders CASCADE;
+DROP TABLE IF EXISTS epoch_snapshot_positions CASCADE;
+DROP TABLE IF EXISTS epoch_position_vaults CASCADE;
+DROP TABLE IF EXISTS epoch_position_snapshots CASCADE;
+DROP TABLE IF EXISTS epoch_snapshot_balances CASCADE;
+DROP TABLE IF EXISTS epoch_snapshots CASCADE;
 DROP TABLE IF EXISTS reward_dust_ledger CASCADE;
+DROP TABLE IF EXISTS reward_exclusions CASCADE;
+DROP VIEW IF EXISTS wallet_accruals CASCADE;
+DROP TABLE IF EXISTS wallet_accrual_ledger CASCADE;
 DROP TABLE IF EXISTS reward_payouts_preview CASCADE;
 DROP TABLE IF EXISTS reward_configs CASCADE;
 DROP TABLE IF EXISTS reward_shares CASCADE;
@@ -9,6 +65,9 @@ DROP TABLE IF EXISTS weights CASCADE;
 DROP TABLE IF EXISTS snapshots CASCADE;
 DROP TABLE IF EXISTS wallets CASCADE;
 
+-- Forget applied migrations so `npm run migrate` applies all of them again
+DROP TABLE IF EXISTS schema_migrations CASCADE;
+
 -- Verify all tables are dropped
 SELECT 'Tables remaining:' as status;
 SELECT table_name
@@ -0,0 +1,601 @@
+-- LST Rewards Indexer - Database Schema
+-- This script creates all required tables and indexes for the rewards system
+--
+-- This system is TOKEN-AGNOSTIC and can track any SPL token with configurable
+-- eligibility requirements.
+--
+-- Baseline migration, applied by `npm run migrate` and on runner startup
+-- (src/utils/migrations.ts). Schema changes go in new migrations; an applied
+-- migration is never edited.
+
+-- ============================================================================
+-- WALLETS
+-- Tracks all discovered token holders and their classification
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS wallets (
+    wallet TEXT PRIMARY KEY,
+    first_seen TIMESTAMP NOT NULL DEFAULT NOW(),
+    is_system_owned BOOLEAN
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallets_system_owned
+    ON wallets(is_system_owned)
+    WHERE is_system_owned = true;
+
+COMMENT ON TABLE wallets IS 'Discovered wallet addresses and their ownership classification';
+COMMENT ON COLUMN wallets.wallet IS 'Base58-encoded Solana wallet address';
+COMMENT ON COLUMN wallets.first_seen IS 'When this wallet was first discovered by the indexer';
+COMMENT ON COLUMN wallets.is_system_owned IS 'True if wallet is owned by System Program (eligible for rewards), false if program-owned, null if not yet classified';
+
+-- ============================================================================
+-- SNAPSHOTS
+-- Append-only ledger of balance snapshots
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS snapshots (
+    id SERIAL PRIMARY KEY,
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    primary_token_amount NUMERIC NOT NULL,
+    eligibility_token_amount NUMERIC,
+    eligibility_token_mint TEXT,
+    eligible BOOLEAN NOT NULL,
+    window_id TEXT NOT NULL,
+    ts TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_wallet
+    ON snapshots(wallet);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_window
+    ON snapshots(window_id);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_ts
+    ON snapshots(ts DESC);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_wallet_window
+    ON snapshots(wallet, window_id);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_eligibility_mint
+    ON snapshots(eligibility_token_mint)
+    WHERE eligibility_token_mint IS NOT NULL;
+
+COMMENT ON TABLE snapshots IS 'Append-only history of wallet balances at each snapshot time';
+COMMENT ON COLUMN snapshots.wallet IS 'Wallet address';
+COMMENT ON COLUMN snapshots.primary_token_amount IS 'Primary token (LST) balance in raw units';
+COMMENT ON COLUMN snapshots.eligibility_token_amount IS 'Optional eligibility token balance in raw units (NULL if no eligibility tracking)';
+COMMENT ON COLUMN snapshots.eligibility_token_mint IS 'Which eligibility token was tracked (NULL if none)';
+COMMENT ON COLUMN snapshots.eligible IS 'True if wallet met eligibility requirements at snapshot time';
+COMMENT ON COLUMN snapshots.window_id IS 'ISO week identifier (format: YYYY-WNN)';
+COMMENT ON COLUMN snapshots.ts IS 'Timestamp when snapshot was taken';
+
+-- ============================================================================
+-- WEIGHTS
+-- Time-weighted stake per wallet per window
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS weights (
+    window_id TEXT NOT NULL,
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    weight NUMERIC NOT NULL,
+    last_ts TIMESTAMP NOT NULL,
+    PRIMARY KEY (window_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_weights_window
+    ON weights(window_id);
+
+COMMENT ON TABLE weights IS 'Time-weighted stake computed as SUM(primary_token_amount × seconds_held) per wallet per window';
+COMMENT ON COLUMN weights.window_id IS 'ISO week identifier (format: YYYY-WNN)';
+COMMENT ON COLUMN weights.wallet IS 'Wallet address';
+COMMENT ON COLUMN weights.weight IS 'Time-weighted stake amount (amount × seconds held)';
+COMMENT ON COLUMN weights.last_ts IS 'Timestamp of last snapshot included in weight calculation';
+
+-- ============================================================================
+-- REWARD SHARES
+-- Normalized shares (0-1) derived from weights
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_shares (
+    window_id TEXT NOT NULL,
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    share NUMERIC NOT NULL,
+    weight NUMERIC NOT NULL,
+    total_weight NUMERIC NOT NULL,
+    PRIMARY KEY (window_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reward_shares_window
+    ON reward_shares(window_id);
+
+COMMENT ON TABLE reward_shares IS 'Normalized shares computed as wallet_weight / total_weight per window';
+COMMENT ON COLUMN reward_shares.window_id IS 'ISO week identifier (format: YYYY-WNN)';
+COMMENT ON COLUMN reward_shares.wallet IS 'Wallet address';
+COMMENT ON COLUMN reward_shares.share IS 'Normalized share (0-1) representing proportion of total weight';
+COMMENT ON COLUMN reward_shares.weight IS 'This wallet''s time-weighted stake';
+COMMENT ON COLUMN reward_shares.total_weight IS 'Sum of all weights for this window';
+
+-- ============================================================================
+-- REWARD CONFIGS
+-- Declarative reward definitions (created via CLI or manually)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_configs (
+    reward_id TEXT PRIMARY KEY,
+    window_start TEXT NOT NULL,
+    window_end TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    eligibility_mode TEXT NOT NULL CHECK (eligibility_mode IN ('eligible_only', 'all_weighted')),
+    eligibility_token_mint TEXT,
+    eligibility_token_min_amount NUMERIC,
+    min_snapshot_balance NUMERIC,
+    below_min_balance TEXT NOT NULL DEFAULT 'redistribute' CHECK (below_min_balance IN ('redistribute', 'remainder')),
+    label TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_reward_configs_window_start
+    ON reward_configs(window_start);
+
+CREATE INDEX IF NOT EXISTS idx_reward_configs_window_end
+    ON reward_configs(window_end);
+
+CREATE INDEX IF NOT EXISTS idx_reward_configs_mint
+    ON reward_configs(mint);
+
+CREATE INDEX IF NOT EXISTS idx_reward_configs_created
+    ON reward_configs(created_at);
+
+COMMENT ON TABLE reward_configs IS 'Reward distribution configurations defining how rewards are allocated (supports single-week or multi-week ranges)';
+COMMENT ON COLUMN reward_configs.reward_id IS 'Unique identifier for this reward (e.g., ORE_W51_2025 or ORE_W51_W52_2025)';
+COMMENT ON COLUMN reward_configs.window_start IS 'ISO week identifier for range start (format: YYYY-WNN). For single-week rewards, set equal to window_end';
+COMMENT ON COLUMN reward_configs.window_end IS 'ISO week identifier for range end (format: YYYY-WNN). For single-week rewards, set equal to window_start';
+COMMENT ON COLUMN reward_configs.mint IS 'SPL token mint address for reward token';
+COMMENT ON COLUMN reward_configs.total_amount IS 'Total reward pool in raw token units (e.g., 7000000000 for 7 tokens with 9 decimals)';
+COMMENT ON COLUMN reward_configs.eligibility_mode IS 'eligible_only: only wallets meeting eligibility criteria, all_weighted: all wallets by stake weight';
+COMMENT ON COLUMN reward_configs.eligibility_token_mint IS 'Optional: SPL token mint required for eligibility (NULL = no requirement)';
+COMMENT ON COLUMN reward_configs.eligibility_token_min_amount IS 'Optional: Minimum balance required of eligibility token (raw units)';
+COMMENT ON COLUMN reward_configs.min_snapshot_balance IS 'Optional: epoch rewards skip holders with a smaller snapshot balance (raw LST units)';
+COMMENT ON COLUMN reward_configs.below_min_balance IS 'redistribute: their share goes to eligible holders, remainder: it carries out with the dust';
+COMMENT ON COLUMN reward_configs.label IS 'Human-readable description';
+COMMENT ON COLUMN reward_configs.created_at IS 'When this reward was configured';
+
+-- ============================================================================
+-- REWARD PAYOUTS PREVIEW
+-- Computed payout amounts (can be recomputed, not authoritative)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_payouts_preview (
+    reward_id TEXT NOT NULL REFERENCES reward_configs(reward_id),
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    window_id TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    share NUMERIC NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    payout_amount NUMERIC NOT NULL,
+    PRIMARY KEY (reward_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payouts_preview_reward
+    ON reward_payouts_preview(reward_id);
+
+CREATE INDEX IF NOT EXISTS idx_payouts_preview_wallet
+    ON reward_payouts_preview(wallet);
+
+COMMENT ON TABLE reward_payouts_preview IS 'Computed payout amounts per wallet (non-authoritative, can be regenerated)';
+COMMENT ON COLUMN reward_payouts_preview.reward_id IS 'Reference to reward configuration';
+COMMENT ON COLUMN reward_payouts_preview.wallet IS 'Recipient wallet address';
+COMMENT ON COLUMN reward_payouts_preview.window_id IS 'ISO week identifier';
+COMMENT ON COLUMN reward_payouts_preview.mint IS 'SPL token mint address';
+COMMENT ON COLUMN reward_payouts_preview.share IS 'Wallet''s normalized share (0-1)';
+COMMENT ON COLUMN reward_payouts_preview.total_amount IS 'Total reward pool (including carry-in dust)';
+COMMENT ON COLUMN reward_payouts_preview.payout_amount IS 'FLOOR(share × total_amount) - actual tokens to send';
+
+-- ============================================================================
+-- WALLET ACCRUAL LEDGER
+-- Per-wallet rounding remainders carried between rewards (append-only)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS wallet_accrual_ledger (
+    reward_id TEXT NOT NULL REFERENCES reward_configs(reward_id),
+    mint TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    carry_in NUMERIC NOT NULL,
+    paid NUMERIC NOT NULL,
+    carry_out NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (reward_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_wallet_accrual_ledger_wallet
+    ON wallet_accrual_ledger(mint, wallet, created_at);
+
+-- Latest accrual per wallet and mint
+CREATE OR REPLACE VIEW wallet_accruals AS
+SELECT DISTINCT ON (mint, wallet)
+    mint,
+    wallet,
+    carry_out AS accrued,
+    reward_id,
+    created_at
+FROM wallet_accrual_ledger
+ORDER BY mint, wallet, created_at DESC;
+
+COMMENT ON TABLE wallet_accrual_ledger IS 'Per-wallet accruals of rewards allocated with --rounding accrue (append-only, never modify)';
+COMMENT ON COLUMN wallet_accrual_ledger.carry_in IS 'Accrual added to the wallet''s exact share (raw units, 12 fractional digits)';
+COMMENT ON COLUMN wallet_accrual_ledger.paid IS 'Whole units paid to the wallet in this reward';
+COMMENT ON COLUMN wallet_accrual_ledger.carry_out IS 'Remainder kept for the wallet''s next allocation (the whole share below --min-payout)';
+COMMENT ON VIEW wallet_accruals IS 'Current accrual of each wallet per reward mint; backed by reward_dust_ledger.carry_out';
+
+-- ============================================================================
+-- REWARD EXCLUSIONS
+-- Snapshot holders left out of an epoch reward, and why
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_exclusions (
+    reward_id TEXT NOT NULL REFERENCES reward_configs(reward_id),
+    epoch BIGINT NOT NULL,
+    wallet TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    weight NUMERIC NOT NULL,
+    PRIMARY KEY (reward_id, wallet)
+);
+
+COMMENT ON TABLE reward_exclusions IS 'Holders excluded from an epoch reward (regenerated with the payouts)';
+COMMENT ON COLUMN reward_exclusions.reason IS 'Exclusion list entry reason, stake pool operator role, or program-owned / unclassified';
+COMMENT ON COLUMN reward_exclusions.weight IS 'Weight the holder would have had (lamports or raw tokens, as allocated)';
+
+-- ============================================================================
+-- REWARD DUST LEDGER
+-- Authoritative record of dust accounting (NEVER modify, append-only)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_dust_ledger (
+    reward_id TEXT PRIMARY KEY REFERENCES reward_configs(reward_id),
+    mint TEXT NOT NULL,
+    configured_total NUMERIC NOT NULL,
+    carry_in NUMERIC NOT NULL,
+    distributed NUMERIC NOT NULL,
+    carry_out NUMERIC NOT NULL,
+    remainder_pool NUMERIC NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_dust_ledger_mint
+    ON reward_dust_ledger(mint);
+
+CREATE INDEX IF NOT EXISTS idx_dust_ledger_created
+    ON reward_dust_ledger(created_at);
+
+COMMENT ON TABLE reward_dust_ledger IS 'AUTHORITATIVE dust accounting ledger (append-only, never modify)';
+COMMENT ON COLUMN reward_dust_ledger.reward_id IS 'Reference to reward configuration';
+COMMENT ON COLUMN reward_dust_ledger.mint IS 'SPL token mint address';
+COMMENT ON COLUMN reward_dust_ledger.configured_total IS 'Reward amount from reward_configs';
+COMMENT ON COLUMN reward_dust_ledger.carry_in IS 'Dust carried forward from previous reward of same mint';
+COMMENT ON COLUMN reward_dust_ledger.distributed IS 'SUM of all payout_amounts (actual tokens sent)';
+COMMENT ON COLUMN reward_dust_ledger.carry_out IS 'Dust carried to next reward: (configured_total + carry_in) - distributed';
+COMMENT ON COLUMN reward_dust_ledger.remainder_pool IS 'Part of carry_out set aside for holders below min_snapshot_balance (remainder mode)';
+COMMENT ON COLUMN reward_dust_ledger.created_at IS 'When payout computation was performed';
+
+-- ============================================================================
+-- EPOCH SNAPSHOTS
+-- Holder balances of the LST mint at each epoch boundary, pinned to one slot
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS epoch_snapshots (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    epoch_first_slot BIGINT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP,
+    token_accounts INTEGER NOT NULL,
+    holders INTEGER NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    supply NUMERIC,
+    supply_slot BIGINT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint),
+    CHECK (slot >= epoch_first_slot)
+);
+
+-- Supply check columns (added after the initial release)
+ALTER TABLE epoch_snapshots ADD COLUMN IF NOT EXISTS supply NUMERIC;
+ALTER TABLE epoch_snapshots ADD COLUMN IF NOT EXISTS supply_slot BIGINT;
+
+CREATE TABLE IF NOT EXISTS epoch_snapshot_balances (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    token_account TEXT NOT NULL,
+    owner TEXT NOT NULL REFERENCES wallets(wallet),
+    amount NUMERIC NOT NULL,
+    PRIMARY KEY (epoch, mint, token_account),
+    FOREIGN KEY (epoch, mint) REFERENCES epoch_snapshots(epoch, mint)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_snapshot_balances_owner
+    ON epoch_snapshot_balances(owner, epoch);
+
+-- LST deposited in lending markets or AMM pools, attributed to the position owner
+CREATE TABLE IF NOT EXISTS epoch_position_snapshots (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    protocol TEXT NOT NULL CHECK (protocol IN ('kamino', 'marginfi', 'orca', 'raydium')),
+    slot BIGINT NOT NULL,
+    positions INTEGER NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint, protocol),
+    FOREIGN KEY (epoch, mint) REFERENCES epoch_snapshots(epoch, mint)
+);
+
+CREATE TABLE IF NOT EXISTS epoch_position_vaults (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    protocol TEXT NOT NULL,
+    token_account TEXT NOT NULL,
+    PRIMARY KEY (epoch, mint, token_account),
+    FOREIGN KEY (epoch, mint, protocol) REFERENCES epoch_position_snapshots(epoch, mint, protocol)
+);
+
+CREATE TABLE IF NOT EXISTS epoch_snapshot_positions (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    protocol TEXT NOT NULL,
+    position_account TEXT NOT NULL,
+    owner TEXT NOT NULL REFERENCES wallets(wallet),
+    amount NUMERIC NOT NULL,
+    PRIMARY KEY (epoch, mint, protocol, position_account),
+    FOREIGN KEY (epoch, mint, protocol) REFERENCES epoch_position_snapshots(epoch, mint, protocol)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_snapshot_positions_owner
+    ON epoch_snapshot_positions(owner, epoch);
+
+-- Wallet balances plus attributed protocol positions; the protocols' vault
+-- token accounts are left out so their tokens aren't counted twice
+CREATE OR REPLACE VIEW epoch_snapshot_holders AS
+SELECT
+    epoch,
+    mint,
+    wallet,
+    SUM(amount) AS amount,
+    SUM(token_accounts) AS token_accounts
+FROM (
+    SELECT b.epoch, b.mint, b.owner AS wallet, b.amount, 1 AS token_accounts
+    FROM epoch_snapshot_balances b
+    WHERE NOT EXISTS (
+        SELECT 1
+        FROM epoch_position_vaults v
+        WHERE v.epoch = b.epoch AND v.mint = b.mint AND v.token_account = b.token_account
+    )
+    UNION ALL
+    SELECT p.epoch, p.mint, p.owner, p.amount, 0
+    FROM epoch_snapshot_positions p
+) h
+GROUP BY epoch, mint, wallet;
+
+-- Native stake delegated to the LST's configured validators, active at the
+-- epoch boundary and credited to the stake account's withdraw authority
+CREATE TABLE IF NOT EXISTS epoch_native_stake_snapshots (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    validators TEXT[] NOT NULL,
+    stake_accounts INTEGER NOT NULL,
+    total_lamports NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint),
+    FOREIGN KEY (epoch, mint) REFERENCES epoch_snapshots(epoch, mint)
+);
+
+CREATE TABLE IF NOT EXISTS epoch_snapshot_native_stake (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    stake_account TEXT NOT NULL,
+    vote_account TEXT NOT NULL,
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    lamports NUMERIC NOT NULL,
+    PRIMARY KEY (epoch, mint, stake_account),
+    FOREIGN KEY (epoch, mint) REFERENCES epoch_native_stake_snapshots(epoch, mint)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_snapshot_native_stake_wallet
+    ON epoch_snapshot_native_stake(wallet, epoch);
+
+CREATE OR REPLACE VIEW epoch_native_stake_holders AS
+SELECT
+    epoch,
+    mint,
+    wallet,
+    SUM(lamports) AS lamports,
+    COUNT(*) AS stake_accounts
+FROM epoch_snapshot_native_stake
+GROUP BY epoch, mint, wallet;
+
+-- Intra-epoch balance samples; together with the boundary snapshots they give
+-- each wallet's time-weighted average balance (TWAB) over the epoch
+CREATE TABLE IF NOT EXISTS epoch_balance_samples (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    holders INTEGER NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint, slot)
+);
+
+CREATE TABLE IF NOT EXISTS epoch_balance_sample_holders (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    wallet TEXT NOT NULL REFERENCES wallets(wallet),
+    amount NUMERIC NOT NULL,
+    PRIMARY KEY (epoch, mint, slot, wallet),
+    FOREIGN KEY (epoch, mint, slot) REFERENCES epoch_balance_samples(epoch, mint, slot)
+);
+
+-- Each point (the epoch's boundary snapshot and every sample) holds from its
+-- slot until the next point; the last one until the next epoch's snapshot.
+-- Until that snapshot exists the average only covers up to the last point.
+CREATE OR REPLACE VIEW epoch_twab_holders AS
+WITH points AS (
+    SELECT epoch, mint, slot FROM epoch_snapshots
+    UNION
+    SELECT epoch, mint, slot FROM epoch_balance_samples
+),
+spans AS (
+    SELECT
+        p.epoch,
+        p.mint,
+        p.slot,
+        COALESCE(LEAD(p.slot) OVER w, n.slot) - p.slot AS slots
+    FROM points p
+    LEFT JOIN epoch_snapshots n ON n.epoch = p.epoch + 1 AND n.mint = p.mint
+    WINDOW w AS (PARTITION BY p.epoch, p.mint ORDER BY p.slot)
+),
+covered AS (
+    SELECT epoch, mint, slot, slots, SUM(slots) OVER (PARTITION BY epoch, mint) AS total_slots
+    FROM spans
+    WHERE slots > 0
+),
+balances AS (
+    SELECT b.epoch, b.mint, s.slot, b.owner AS wallet, b.amount
+    FROM epoch_snapshot_balances b
+    JOIN epoch_snapshots s ON s.epoch = b.epoch AND s.mint = b.mint
+    UNION ALL
+    SELECT h.epoch, h.mint, h.slot, h.wallet, h.amount
+    FROM epoch_balance_sample_holders h
+    WHERE NOT EXISTS (
+        SELECT 1 FROM epoch_snapshots s
+        WHERE s.epoch = h.epoch AND s.mint = h.mint AND s.slot = h.slot
+    )
+)
+SELECT
+    c.epoch,
+    c.mint,
+    b.wallet,
+    FLOOR(SUM(b.amount * c.slots) / MAX(c.total_slots)) AS amount,
+    MAX(c.total_slots) AS covered_slots
+FROM covered c
+JOIN balances b ON b.epoch = c.epoch AND b.mint = c.mint AND b.slot = c.slot
+GROUP BY c.epoch, c.mint, b.wallet;
+
+COMMENT ON TABLE epoch_snapshots IS 'One row per epoch and mint: the slot all balances were read at';
+COMMENT ON COLUMN epoch_snapshots.epoch_first_slot IS 'First slot of the epoch (the boundary)';
+COMMENT ON COLUMN epoch_snapshots.slot IS 'Pinned slot: context slot of the getProgramAccounts call, or the newest shard (>= epoch_first_slot)';
+COMMENT ON COLUMN epoch_snapshots.block_time IS 'Block time of the pinned slot (NULL if unavailable)';
+COMMENT ON COLUMN epoch_snapshots.total_amount IS 'Sum of all non-zero balances in raw units';
+COMMENT ON COLUMN epoch_snapshots.supply IS 'Mint supply the total was checked against (NULL for snapshots before the check)';
+COMMENT ON COLUMN epoch_snapshots.supply_slot IS 'Slot the supply was read at (>= slot)';
+COMMENT ON TABLE epoch_snapshot_balances IS 'Non-zero token account balances at the pinned slot';
+COMMENT ON COLUMN epoch_snapshot_balances.owner IS 'Token account owner (the holder wallet)';
+COMMENT ON TABLE epoch_position_snapshots IS 'One row per epoch, mint and protocol whose positions were attributed';
+COMMENT ON COLUMN epoch_position_snapshots.slot IS 'Slot the protocol accounts were read at (>= the snapshot slot)';
+COMMENT ON TABLE epoch_position_vaults IS 'Protocol token accounts holding pooled LST, excluded from epoch_snapshot_holders';
+COMMENT ON TABLE epoch_snapshot_positions IS 'Per-position LST (underlying raw units) credited to the position owner';
+COMMENT ON VIEW epoch_snapshot_holders IS 'Per-wallet balances at each epoch snapshot (token accounts plus protocol positions)';
+COMMENT ON TABLE epoch_native_stake_snapshots IS 'One row per epoch and mint whose native stake was recorded';
+COMMENT ON COLUMN epoch_native_stake_snapshots.slot IS 'Slot the stake accounts were read at (>= the snapshot slot)';
+COMMENT ON COLUMN epoch_native_stake_snapshots.validators IS 'Vote accounts whose delegations were included';
+COMMENT ON TABLE epoch_snapshot_native_stake IS 'Stake accounts delegated to the configured validators and active at the epoch boundary';
+COMMENT ON COLUMN epoch_snapshot_native_stake.wallet IS 'Withdraw authority of the stake account';
+COMMENT ON COLUMN epoch_snapshot_native_stake.lamports IS 'Delegated stake (excludes the rent-exempt reserve)';
+COMMENT ON VIEW epoch_native_stake_holders IS 'Per-wallet active native stake at each epoch snapshot, in lamports';
+COMMENT ON TABLE epoch_balance_samples IS 'Intra-epoch balance samples of an LST, taken on the LST''s TWAB schedule';
+COMMENT ON COLUMN epoch_balance_samples.slot IS 'Context slot of the getProgramAccounts call';
+COMMENT ON TABLE epoch_balance_sample_holders IS 'Per-wallet balances (summed over token accounts) at each sample';
+COMMENT ON VIEW epoch_twab_holders IS 'Per-wallet time-weighted average balance over each epoch (boundary snapshot plus samples, weighted by slots held)';
+COMMENT ON COLUMN epoch_twab_holders.covered_slots IS 'Slots averaged over (the whole epoch once the next boundary snapshot exists)';
+
+-- ============================================================================
+-- EPOCH EXCHANGE RATES
+-- LST -> SOL rate per epoch, read from stake-pool state
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS epoch_exchange_rates (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    pool_kind TEXT NOT NULL CHECK (pool_kind IN ('spl', 'jito', 'marinade')),
+    pool_address TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    rate_numerator NUMERIC NOT NULL,
+    rate_denominator NUMERIC NOT NULL CHECK (rate_denominator > 0),
+    sol_per_token NUMERIC NOT NULL,
+    pool_last_update_epoch BIGINT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint)
+);
+
+CREATE OR REPLACE VIEW epoch_snapshot_holders_sol AS
+SELECT
+    h.epoch,
+    h.mint,
+    h.wallet,
+    h.amount,
+    FLOOR(h.amount * r.rate_numerator / r.rate_denominator) AS lamports
+FROM epoch_snapshot_holders h
+JOIN epoch_exchange_rates r ON r.epoch = h.epoch AND r.mint = h.mint;
+
+COMMENT ON TABLE epoch_exchange_rates IS 'Token -> SOL exchange rate of the LST at each epoch';
+COMMENT ON COLUMN epoch_exchange_rates.pool_kind IS 'Stake-pool adapter: spl, jito (SPL program) or marinade';
+COMMENT ON COLUMN epoch_exchange_rates.slot IS 'Slot the stake-pool account was read at (>= first slot of the epoch)';
+COMMENT ON COLUMN epoch_exchange_rates.rate_numerator IS 'Exact rate numerator: SPL total_lamports, Marinade msol_price';
+COMMENT ON COLUMN epoch_exchange_rates.rate_denominator IS 'Exact rate denominator: SPL pool_token_supply, Marinade 2^32';
+COMMENT ON COLUMN epoch_exchange_rates.sol_per_token IS 'rate_numerator / rate_denominator to 18 decimals (display only)';
+COMMENT ON COLUMN epoch_exchange_rates.pool_last_update_epoch IS 'Epoch the pool state was last updated (SPL pools; NULL for Marinade)';
+COMMENT ON VIEW epoch_snapshot_holders_sol IS 'Epoch snapshot balances converted to lamports at that epoch''s exchange rate';
+
+-- Inflation rewards earned by the LST's stake pool, per validator
+CREATE TABLE IF NOT EXISTS epoch_validator_rewards (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    vote_account TEXT NOT NULL,
+    stake_accounts INTEGER NOT NULL,
+    delegated_lamports NUMERIC NOT NULL,
+    reward_lamports NUMERIC NOT NULL,
+    commission SMALLINT,
+    commission_lamports NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint, vote_account)
+);
+
+COMMENT ON TABLE epoch_validator_rewards IS 'Inflation rewards paid to the stake pool''s stake accounts for an epoch, per validator';
+COMMENT ON COLUMN epoch_validator_rewards.epoch IS 'Epoch the rewards were earned in (paid at the start of the next)';
+COMMENT ON COLUMN epoch_validator_rewards.delegated_lamports IS 'Stake delegated from the pool to the validator when recorded';
+COMMENT ON COLUMN epoch_validator_rewards.reward_lamports IS 'Rewards credited to the pool''s stake accounts (after commission)';
+COMMENT ON COLUMN epoch_validator_rewards.commission IS 'Vote account commission percentage for the epoch (NULL if no reward was paid)';
+COMMENT ON COLUMN epoch_validator_rewards.commission_lamports IS 'Commission kept by the validator on the pool''s stake: reward * commission / (100 - commission)';
+
+-- Total inflation rewards of the pool per epoch: the default budget of the
+-- epoch's SOL reward (written with its epoch_validator_rewards rows)
+CREATE TABLE IF NOT EXISTS epoch_reward_budgets (
+    epoch BIGINT NOT NULL,
+    mint TEXT NOT NULL,
+    stake_accounts INTEGER NOT NULL,
+    validators INTEGER NOT NULL,
+    reward_lamports NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (epoch, mint)
+);
+
+COMMENT ON TABLE epoch_reward_budgets IS 'Inflation rewards paid to the LST''s stake pool for an epoch, used as the default budget of its SOL epoch reward';
+COMMENT ON COLUMN epoch_reward_budgets.mint IS 'LST mint (not the reward mint)';
+COMMENT ON COLUMN epoch_reward_budgets.reward_lamports IS 'Sum of getInflationReward over the pool''s stake accounts for the epoch';
+
+-- ============================================================================
+-- VERIFICATION QUERIES
+-- Run these to verify schema is working correctly
+-- ============================================================================
+
+-- Uncomment to verify tables were created:
+-- SELECT table_name
+-- FROM information_schema.tables
+-- WHERE table_schema = 'public'
+-- ORDER BY table_name;
+
+-- Uncomment to verify indexes were created:
+-- SELECT indexname, tablename
+-- FROM pg_indexes
+-- WHERE schemaname = 'public'
+-- ORDER BY tablename, indexname;
@@ -0,0 +1,435 @@
+-- Merkle Distribution Schema Extensions
+-- Adds tables for tracking Merkle-based reward distributions
+--
+-- Baseline migration, applied after 0001_schema (merkle_distributions
+-- references reward_configs)
+
+-- ============================================================================
+-- MERKLE DISTRIBUTIONS
+-- Tracks Merkle distribution configurations and state
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_distributions (
+    distribution_id TEXT PRIMARY KEY,
+    reward_id TEXT NOT NULL REFERENCES reward_configs(reward_id),
+    window_id TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    total_amount NUMERIC NOT NULL,
+    merkle_root TEXT NOT NULL,
+    num_recipients INTEGER NOT NULL,
+    csv_hash TEXT NOT NULL,
+    artifact_path TEXT NOT NULL,
+    
+    -- On-chain state (populated after multisig initialization)
+    on_chain_address TEXT,
+    vault_ata TEXT,
+    
+    -- Tracking
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'funded', 'active', 'completed', 'clawedback')
+    ),
+    claimed_amount NUMERIC NOT NULL DEFAULT 0,
+    claimed_count INTEGER NOT NULL DEFAULT 0,
+    
+    -- Timestamps
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    funded_at TIMESTAMP,
+    activated_at TIMESTAMP,
+    completed_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_reward
+    ON merkle_distributions(reward_id);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_status
+    ON merkle_distributions(status);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_mint
+    ON merkle_distributions(mint);
+
+COMMENT ON TABLE merkle_distributions IS 'Merkle distribution configurations for scalable reward distribution';
+COMMENT ON COLUMN merkle_distributions.distribution_id IS 'Unique hash-derived identifier for this distribution';
+COMMENT ON COLUMN merkle_distributions.merkle_root IS 'Hex-encoded Merkle root committing to all payouts';
+COMMENT ON COLUMN merkle_distributions.csv_hash IS 'SHA-256 hash of source CSV for verification';
+COMMENT ON COLUMN merkle_distributions.artifact_path IS 'Path to JSON artifact containing proofs';
+COMMENT ON COLUMN merkle_distributions.on_chain_address IS 'Distribution PDA address after initialization';
+COMMENT ON COLUMN merkle_distributions.vault_ata IS 'Token vault ATA for this distribution';
+COMMENT ON COLUMN merkle_distributions.status IS 'Current state: pending → funded → active → completed/clawedback';
+
+-- ============================================================================
+-- MERKLE CLAIMS
+-- Tracks claim status for relayer processing
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_claims (
+    distribution_id TEXT NOT NULL REFERENCES merkle_distributions(distribution_id),
+    leaf_index INTEGER NOT NULL,
+    wallet TEXT NOT NULL,
+    amount NUMERIC NOT NULL,
+    
+    -- Claim state
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'submitted', 'confirmed', 'failed')
+    ),
+    
+    -- Transaction tracking
+    tx_signature TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_attempt TIMESTAMP,
+    confirmed_at TIMESTAMP,
+    error_message TEXT,
+    
+    PRIMARY KEY (distribution_id, leaf_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_distribution
+    ON merkle_claims(distribution_id);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_wallet
+    ON merkle_claims(wallet);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_status
+    ON merkle_claims(status);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_pending
+    ON merkle_claims(distribution_id, status)
+    WHERE status IN ('pending', 'failed');
+
+COMMENT ON TABLE merkle_claims IS 'Claim tracking for relayer batch processing';
+COMMENT ON COLUMN merkle_claims.leaf_index IS 'Index of this claim in the Merkle tree';
+COMMENT ON COLUMN merkle_claims.status IS 'Claim state: pending → submitted → confirmed/failed';
+COMMENT ON COLUMN merkle_claims.attempts IS 'Number of submission attempts (for retry logic)';
+
+-- ============================================================================
+-- MERKLE RELAYER BATCHES
+-- Tracks relayer batch submissions for monitoring
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_relayer_batches (
+    id SERIAL PRIMARY KEY,
+    distribution_id TEXT NOT NULL REFERENCES merkle_distributions(distribution_id),
+    batch_index INTEGER NOT NULL,
+    claim_indices INTEGER[] NOT NULL,
+    
+    -- Transaction info
+    tx_signature TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'submitted', 'confirmed', 'failed')
+    ),
+    
+    -- Metrics
+    claims_count INTEGER NOT NULL,
+    compute_units INTEGER,
+    fee_lamports BIGINT,
+    
+    -- Timing
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    submitted_at TIMESTAMP,
+    confirmed_at TIMESTAMP,
+    error_message TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_relayer_batches_distribution
+    ON merkle_relayer_batches(distribution_id);
+
+CREATE INDEX IF NOT EXISTS idx_relayer_batches_status
+    ON merkle_relayer_batches(status);
+
+COMMENT ON TABLE merkle_relayer_batches IS 'Batch submission tracking for relayer operations';
+
+-- ============================================================================
+-- MERKLE CLAIM EVENTS
+-- Finalized on-chain events decoded from distributor transaction logs
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_claim_events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    instruction_index INTEGER,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMP,
+
+    -- Distribution PDA (matches merkle_distributions.on_chain_address)
+    distribution TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (
+        kind IN ('claim', 'claim_referral', 'escrow', 'renounce', 'escrow_release', 'clawback', 'pause', 'unpause', 'operator_change')
+    ),
+    recipient TEXT,
+    amount NUMERIC,
+    leaf_index BIGINT,
+    referrer TEXT,
+    bonus NUMERIC,
+    destination TEXT,
+
+    source TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_distribution
+    ON merkle_claim_events(distribution, slot);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_recipient
+    ON merkle_claim_events(recipient);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_slot
+    ON merkle_claim_events(slot);
+
+COMMENT ON TABLE merkle_claim_events IS 'Finalized claim, clawback and pause events from the distributor program';
+COMMENT ON COLUMN merkle_claim_events.event_index IS 'Position of the event within its transaction';
+COMMENT ON COLUMN merkle_claim_events.instruction_index IS 'Top-level instruction that emitted the event; (signature, instruction_index, event_index) is the upsert key (NULL for rows stored before it was recorded)';
+COMMENT ON COLUMN merkle_claim_events.source IS 'First ingestion path that recorded the event (geyser, websocket, backfill)';
+
+-- Events seen at processed/confirmed, held until their transaction finalizes
+-- (promoted to merkle_claim_events) or its slot is passed by finality (dropped)
+CREATE TABLE IF NOT EXISTS merkle_claim_events_staged (
+    LIKE merkle_claim_events INCLUDING DEFAULTS INCLUDING CONSTRAINTS,
+    commitment TEXT NOT NULL CHECK (
+        commitment IN ('processed', 'confirmed')
+    ),
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_staged_slot
+    ON merkle_claim_events_staged(slot);
+
+COMMENT ON TABLE merkle_claim_events_staged IS 'Not-yet-finalized claim events awaiting promotion or rollback';
+COMMENT ON COLUMN merkle_claim_events_staged.commitment IS 'Highest commitment the transaction has been seen at';
+
+-- Databases created before events were keyed by instruction
+ALTER TABLE merkle_claim_events ADD COLUMN IF NOT EXISTS instruction_index INTEGER;
+ALTER TABLE merkle_claim_events_staged ADD COLUMN IF NOT EXISTS instruction_index INTEGER;
+
+-- Upsert keys: one row per event whichever sources deliver it. A claim_batch
+-- instruction emits one event per claim, so the event index is part of the key
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_instruction
+    ON merkle_claim_events(signature, instruction_index, event_index);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_staged_instruction
+    ON merkle_claim_events_staged(signature, instruction_index, event_index);
+
+-- Databases created before operator_change was indexed
+ALTER TABLE merkle_claim_events DROP CONSTRAINT IF EXISTS merkle_claim_events_kind_check;
+ALTER TABLE merkle_claim_events ADD CONSTRAINT merkle_claim_events_kind_check CHECK (
+    kind IN ('claim', 'claim_referral', 'escrow', 'renounce', 'escrow_release', 'clawback', 'pause', 'unpause', 'operator_change')
+);
+ALTER TABLE merkle_claim_events_staged DROP CONSTRAINT IF EXISTS merkle_claim_events_kind_check;
+ALTER TABLE merkle_claim_events_staged ADD CONSTRAINT merkle_claim_events_kind_check CHECK (
+    kind IN ('claim', 'claim_referral', 'escrow', 'renounce', 'escrow_release', 'clawback', 'pause', 'unpause', 'operator_change')
+);
+
+-- ============================================================================
+-- WEBHOOK DELIVERIES
+-- One row per (webhook, finalized event), retried until delivered or out of attempts
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+
+    -- Source event (merkle_claim_events)
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'delivered', 'failed')
+    ),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_status_code INTEGER,
+    last_error TEXT,
+
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP,
+
+    UNIQUE (webhook, signature, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at)
+    WHERE status = 'pending';
+
+COMMENT ON TABLE webhook_deliveries IS 'Webhook notifications for finalized claim and admin events, with delivery status';
+COMMENT ON COLUMN webhook_deliveries.webhook IS 'Webhook name from WEBHOOKS_CONFIG';
+COMMENT ON COLUMN webhook_deliveries.payload IS 'Event body, identical across retries (the signature header is not)';
+COMMENT ON COLUMN webhook_deliveries.next_attempt_at IS 'Earliest next attempt; also leases in-flight deliveries to one dispatcher';
+
+-- ============================================================================
+-- RECONCILIATION
+-- On-chain Distribution state that disagrees with the indexed claim events
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_reconciliation_divergences (
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Distribution PDA
+    distribution TEXT NOT NULL,
+    field TEXT NOT NULL CHECK (
+        field IN ('claimed_amount', 'num_claimed', 'vault_balance')
+    ),
+    on_chain NUMERIC NOT NULL,
+    expected NUMERIC NOT NULL,
+    slot BIGINT NOT NULL,
+
+    first_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    alerted_at TIMESTAMP,
+    resolved_at TIMESTAMP
+);
+
+-- At most one open divergence per distribution and field
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reconciliation_open
+    ON merkle_reconciliation_divergences(distribution, field)
+    WHERE resolved_at IS NULL;
+
+COMMENT ON TABLE merkle_reconciliation_divergences IS 'Distribution values that disagree with the indexed claim events, open until they match again';
+COMMENT ON COLUMN merkle_reconciliation_divergences.expected IS 'Indexed value, or for vault_balance the balance the on-chain totals require';
+COMMENT ON COLUMN merkle_reconciliation_divergences.slot IS 'Slot of the most recent run that saw the divergence';
+COMMENT ON COLUMN merkle_reconciliation_divergences.alerted_at IS 'When the divergence was first alerted on, once it outlived ingestion lag';
+
+-- ============================================================================
+-- ALERTS
+-- Alerts sent to Slack/Discord, keyed so each is sent once (or once per cooldown)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS alert_log (
+    rule TEXT NOT NULL,
+    key TEXT NOT NULL,
+    severity TEXT NOT NULL CHECK (
+        severity IN ('critical', 'warning', 'info')
+    ),
+    message TEXT NOT NULL,
+    sent_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (rule, key)
+);
+
+COMMENT ON TABLE alert_log IS 'Last alert sent per rule and subject (src/alerts)';
+COMMENT ON COLUMN alert_log.key IS 'Subject of the alert: event, distribution, divergence id or ingestion source';
+
+-- ============================================================================
+-- API USAGE
+-- Proof API requests per API key and hour, for billing (merkle_tree::access)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS api_usage (
+    key_name TEXT NOT NULL,
+    period_start TIMESTAMP NOT NULL,
+    requests BIGINT NOT NULL DEFAULT 0,
+    rate_limited BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (key_name, period_start)
+);
+
+COMMENT ON TABLE api_usage IS 'Proof API requests per API key and hour, flushed by proof-server every minute';
+COMMENT ON COLUMN api_usage.key_name IS 'Key name from the key file, or anonymous for requests without a key';
+COMMENT ON COLUMN api_usage.rate_limited IS 'Requests rejected with 429 (not billed)';
+
+-- ============================================================================
+-- INGESTION CHECKPOINTS
+-- Newest transaction handled per claim event source, resumed from on restart
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS ingestion_checkpoints (
+    source TEXT NOT NULL,
+    program_id TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    signature TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (source, program_id)
+);
+
+COMMENT ON TABLE ingestion_checkpoints IS 'Claim event ingestion cursor per source, saved by the claim event runner';
+COMMENT ON COLUMN ingestion_checkpoints.source IS 'Ingestion path (geyser, websocket)';
+COMMENT ON COLUMN ingestion_checkpoints.signature IS 'Newest transaction handled; everything before it has been ingested';
+
+-- ============================================================================
+-- FINANCE EXPORTS
+-- Exports started through the API (POST /api/exports), tracked until written
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS finance_exports (
+    id BIGSERIAL PRIMARY KEY,
+    dataset TEXT NOT NULL CHECK (dataset IN ('allocations', 'claims', 'reconciliation')),
+    format TEXT NOT NULL CHECK (format IN ('csv', 'parquet')),
+    range_from TIMESTAMP NOT NULL,
+    range_to TIMESTAMP NOT NULL,
+    location TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('running', 'completed', 'failed')),
+    rows BIGINT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP
+);
+
+COMMENT ON TABLE finance_exports IS 'Finance exports started through the API (CLI exports are not recorded)';
+COMMENT ON COLUMN finance_exports.range_to IS 'Exclusive end of the exported range';
+COMMENT ON COLUMN finance_exports.location IS 'File path or s3://bucket/key written to';
+COMMENT ON COLUMN finance_exports.status IS 'running until written; an export interrupted by a restart stays running';
+
+-- ============================================================================
+-- HELPER VIEWS
+-- ============================================================================
+
+CREATE OR REPLACE VIEW merkle_distribution_summary AS
+SELECT
+    d.distribution_id,
+    d.reward_id,
+    d.window_id,
+    d.mint,
+    d.total_amount,
+    d.num_recipients,
+    d.status,
+    d.claimed_count,
+    d.claimed_amount,
+    CASE
+        WHEN d.num_recipients > 0
+        THEN ROUND(100.0 * d.claimed_count / d.num_recipients, 1)
+        ELSE 0
+    END AS claim_progress_pct,
+    d.created_at,
+    d.activated_at
+FROM merkle_distributions d
+ORDER BY d.created_at DESC;
+
+COMMENT ON VIEW merkle_distribution_summary IS 'Summary view of Merkle distributions with progress';
+
+CREATE OR REPLACE VIEW merkle_pending_claims AS
+SELECT
+    c.distribution_id,
+    c.leaf_index,
+    c.wallet,
+    c.amount,
+    c.attempts,
+    c.last_attempt,
+    c.error_message
+FROM merkle_claims c
+WHERE c.status IN ('pending', 'failed')
+ORDER BY c.distribution_id, c.leaf_index;
+
+COMMENT ON VIEW merkle_pending_claims IS 'Claims awaiting processing by relayer';
+
+-- ============================================================================
+-- UPDATE TRIGGER
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION update_merkle_distribution_timestamp()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_merkle_distribution_updated ON merkle_distributions;
+
+CREATE TRIGGER trigger_merkle_distribution_updated
+    BEFORE UPDATE ON merkle_distributions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_merkle_distribution_timestamp();
+
@@ -0,0 +1,37 @@
+-- Reverts 0003_deployments
+--
+-- Fails if rows of deployments other than 'default' collide on the old
+-- keys; delete them first.
+
+ALTER TABLE ingestion_checkpoints DROP CONSTRAINT IF EXISTS ingestion_checkpoints_pkey;
+ALTER TABLE ingestion_checkpoints ADD PRIMARY KEY (source, program_id);
+
+DROP INDEX IF EXISTS idx_reconciliation_open;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reconciliation_open
+    ON merkle_reconciliation_divergences(distribution, field)
+    WHERE resolved_at IS NULL;
+
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS webhook_deliveries_event_key;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT webhook_deliveries_webhook_signature_event_index_key
+    UNIQUE (webhook, signature, event_index);
+
+DROP INDEX IF EXISTS idx_claim_events_staged_instruction;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_staged_instruction
+    ON merkle_claim_events_staged(signature, instruction_index, event_index);
+DROP INDEX IF EXISTS idx_claim_events_instruction;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_instruction
+    ON merkle_claim_events(signature, instruction_index, event_index);
+
+ALTER TABLE merkle_claim_events_staged DROP CONSTRAINT IF EXISTS merkle_claim_events_staged_pkey;
+ALTER TABLE merkle_claim_events_staged ADD PRIMARY KEY (signature, event_index);
+ALTER TABLE merkle_claim_events DROP CONSTRAINT IF EXISTS merkle_claim_events_pkey;
+ALTER TABLE merkle_claim_events ADD PRIMARY KEY (signature, event_index);
+
+DROP INDEX IF EXISTS idx_merkle_dist_address;
+
+ALTER TABLE ingestion_checkpoints DROP COLUMN IF EXISTS deployment;
+ALTER TABLE merkle_reconciliation_divergences DROP COLUMN IF EXISTS deployment;
+ALTER TABLE webhook_deliveries DROP COLUMN IF EXISTS deployment;
+ALTER TABLE merkle_claim_events_staged DROP COLUMN IF EXISTS deployment;
+ALTER TABLE merkle_claim_events DROP COLUMN IF EXISTS deployment;
+ALTER TABLE merkle_distributions DROP COLUMN IF EXISTS deployment;
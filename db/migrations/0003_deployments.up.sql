@@ -0,0 +1,49 @@
+-- Deployments
+-- Namespaces indexed state by deployment: one distributor program on one
+-- cluster, as configured in DEPLOYMENTS_CONFIG (src/config/deployments.ts).
+-- The same program ID, and so the same distribution PDAs, can exist on
+-- several clusters, so every key that holds a PDA or a signature now
+-- includes the deployment. Rows indexed before deployments existed belong
+-- to 'default', the deployment configured from the environment.
+
+ALTER TABLE merkle_distributions ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE merkle_claim_events ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE merkle_claim_events_staged ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE webhook_deliveries ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE merkle_reconciliation_divergences ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE ingestion_checkpoints ADD COLUMN IF NOT EXISTS deployment TEXT NOT NULL DEFAULT 'default';
+
+COMMENT ON COLUMN merkle_distributions.deployment IS 'Deployment (program and cluster) the distribution is initialized on';
+COMMENT ON COLUMN merkle_claim_events.deployment IS 'Deployment the event was indexed from';
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_address
+    ON merkle_distributions(deployment, on_chain_address);
+
+-- Claim events: keyed by (deployment, signature, ...)
+ALTER TABLE merkle_claim_events DROP CONSTRAINT IF EXISTS merkle_claim_events_pkey;
+ALTER TABLE merkle_claim_events ADD PRIMARY KEY (deployment, signature, event_index);
+ALTER TABLE merkle_claim_events_staged DROP CONSTRAINT IF EXISTS merkle_claim_events_staged_pkey;
+ALTER TABLE merkle_claim_events_staged ADD PRIMARY KEY (deployment, signature, event_index);
+
+DROP INDEX IF EXISTS idx_claim_events_instruction;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_instruction
+    ON merkle_claim_events(deployment, signature, instruction_index, event_index);
+DROP INDEX IF EXISTS idx_claim_events_staged_instruction;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_staged_instruction
+    ON merkle_claim_events_staged(deployment, signature, instruction_index, event_index);
+
+-- Webhook deliveries: one per (webhook, event)
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS webhook_deliveries_webhook_signature_event_index_key;
+ALTER TABLE webhook_deliveries DROP CONSTRAINT IF EXISTS webhook_deliveries_event_key;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT webhook_deliveries_event_key
+    UNIQUE (webhook, deployment, signature, event_index);
+
+-- Reconciliation: at most one open divergence per distribution and field
+DROP INDEX IF EXISTS idx_reconciliation_open;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reconciliation_open
+    ON merkle_reconciliation_divergences(deployment, distribution, field)
+    WHERE resolved_at IS NULL;
+
+-- Ingestion checkpoints: one cursor per deployment and source
+ALTER TABLE ingestion_checkpoints DROP CONSTRAINT IF EXISTS ingestion_checkpoints_pkey;
+ALTER TABLE ingestion_checkpoints ADD PRIMARY KEY (deployment, source, program_id);
@@ -0,0 +1,11 @@
+-- Reverts 0004_slot_block_times
+
+ALTER TABLE merkle_claim_events_staged DROP COLUMN IF EXISTS block_time_estimated;
+ALTER TABLE merkle_claim_events DROP COLUMN IF EXISTS block_time_estimated;
+
+ALTER TABLE merkle_claim_events_staged
+    ALTER COLUMN block_time TYPE TIMESTAMP USING block_time AT TIME ZONE 'UTC';
+ALTER TABLE merkle_claim_events
+    ALTER COLUMN block_time TYPE TIMESTAMP USING block_time AT TIME ZONE 'UTC';
+
+DROP TABLE IF EXISTS slot_block_times;
@@ -0,0 +1,45 @@
+-- Slot block times
+-- Block times of slots events were indexed in, as reported by the cluster
+-- or interpolated from neighboring slots when the cluster has none
+-- (src/indexers/slot-times.ts). Event block times become UTC timestamptz:
+-- they were written with to_timestamp() into TIMESTAMP columns, i.e. in the
+-- session time zone, and are converted on the assumption that was UTC.
+
+CREATE TABLE IF NOT EXISTS slot_block_times (
+    deployment TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    block_time TIMESTAMPTZ NOT NULL,
+    estimated BOOLEAN NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (deployment, slot)
+);
+
+COMMENT ON TABLE slot_block_times IS 'Block time cache for slots holding indexed events';
+COMMENT ON COLUMN slot_block_times.estimated IS 'Interpolated from neighboring slots; replaced once the cluster reports the block time';
+
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'merkle_claim_events' AND column_name = 'block_time'
+          AND data_type = 'timestamp without time zone'
+    ) THEN
+        ALTER TABLE merkle_claim_events
+            ALTER COLUMN block_time TYPE TIMESTAMPTZ USING block_time AT TIME ZONE 'UTC';
+    END IF;
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'merkle_claim_events_staged' AND column_name = 'block_time'
+          AND data_type = 'timestamp without time zone'
+    ) THEN
+        ALTER TABLE merkle_claim_events_staged
+            ALTER COLUMN block_time TYPE TIMESTAMPTZ USING block_time AT TIME ZONE 'UTC';
+    END IF;
+END $$;
+
+ALTER TABLE merkle_claim_events ADD COLUMN IF NOT EXISTS block_time_estimated BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE merkle_claim_events_staged ADD COLUMN IF NOT EXISTS block_time_estimated BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN merkle_claim_events.block_time IS 'Block time of the slot (UTC); NULL only if no neighboring slot had one either';
+COMMENT ON COLUMN merkle_claim_events.block_time_estimated IS 'block_time was interpolated from neighboring slots';
@@ -0,0 +1,3 @@
+-- Reverts 0005_ingestion_dead_letters
+
+DROP TABLE IF EXISTS ingestion_dead_letters;
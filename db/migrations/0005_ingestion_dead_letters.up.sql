@@ -0,0 +1,34 @@
+-- Ingestion dead letters
+-- Distributor transactions the claim event ingesters could not decode or
+-- store, kept with the normalized transaction so they can be re-processed
+-- after a decoder fix (npm run replay-dead-letters,
+-- src/indexers/dead-letters.ts).
+
+CREATE TABLE IF NOT EXISTS ingestion_dead_letters (
+    id BIGSERIAL PRIMARY KEY,
+    deployment TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot BIGINT NOT NULL,
+    source TEXT NOT NULL,
+    commitment TEXT NOT NULL,
+    stage TEXT NOT NULL CHECK (stage IN ('decode', 'persist')),
+    error TEXT NOT NULL,
+    -- ProgramTransaction as JSON, inner instruction data base64
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'replayed', 'discarded')),
+    attempts INTEGER NOT NULL DEFAULT 1,
+    first_failed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_failed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    replayed_at TIMESTAMP,
+
+    UNIQUE (deployment, signature)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_pending
+    ON ingestion_dead_letters(deployment, slot)
+    WHERE status = 'pending';
+
+COMMENT ON TABLE ingestion_dead_letters IS 'Distributor transactions that failed to decode or store, for replay';
+COMMENT ON COLUMN ingestion_dead_letters.stage IS 'Step that failed: decode (merkle-events / attribution) or persist (database)';
+COMMENT ON COLUMN ingestion_dead_letters.status IS 'pending until a replay stores its events (replayed) or an operator gives up on it (discarded)';
+COMMENT ON COLUMN ingestion_dead_letters.attempts IS 'Failed attempts, including replays';
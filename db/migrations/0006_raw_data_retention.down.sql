@@ -0,0 +1,16 @@
+-- Reverts 0006_raw_data_retention
+--
+-- Pruned payloads are gone; their rows are deleted so the columns can be
+-- NOT NULL again.
+
+DROP INDEX IF EXISTS idx_slot_block_times_created;
+DROP INDEX IF EXISTS idx_webhook_deliveries_prunable;
+
+DELETE FROM ingestion_dead_letters WHERE payload IS NULL;
+DELETE FROM webhook_deliveries WHERE payload IS NULL;
+
+ALTER TABLE ingestion_dead_letters ALTER COLUMN payload SET NOT NULL;
+ALTER TABLE webhook_deliveries ALTER COLUMN payload SET NOT NULL;
+
+COMMENT ON COLUMN webhook_deliveries.payload IS 'Event body, identical across retries (the signature header is not)';
+COMMENT ON COLUMN ingestion_dead_letters.payload IS NULL;
@@ -0,0 +1,18 @@
+-- Retention of raw ingestion data
+-- Raw payloads may be pruned once they have served their purpose, while
+-- the rows themselves stay (src/utils/retention.ts): a delivered webhook's
+-- row still keeps the event from being re-sent, a resolved dead letter's
+-- row still records what failed.
+
+ALTER TABLE webhook_deliveries ALTER COLUMN payload DROP NOT NULL;
+ALTER TABLE ingestion_dead_letters ALTER COLUMN payload DROP NOT NULL;
+
+COMMENT ON COLUMN webhook_deliveries.payload IS 'Event body, identical across retries (the signature header is not); NULL once pruned after delivery';
+COMMENT ON COLUMN ingestion_dead_letters.payload IS 'Normalized transaction; NULL once pruned after the dead letter was replayed or discarded';
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_prunable
+    ON webhook_deliveries(delivered_at)
+    WHERE status = 'delivered' AND payload IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_slot_block_times_created
+    ON slot_block_times(created_at);
@@ -0,0 +1,3 @@
+-- Reverts 0007_ingestion_lag
+
+DROP TABLE IF EXISTS ingestion_lag;
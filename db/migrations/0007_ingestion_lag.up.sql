@@ -0,0 +1,16 @@
+-- Ingestion lag
+-- Latest lag measurement of each claim event ingester, so processes that
+-- don't ingest (the API) can tell whether the indexed state is current
+-- (readiness, src/utils/health.ts).
+
+CREATE TABLE IF NOT EXISTS ingestion_lag (
+    deployment TEXT NOT NULL,
+    source TEXT NOT NULL,
+    lag_slots BIGINT NOT NULL,
+    measured_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (deployment, source)
+);
+
+COMMENT ON TABLE ingestion_lag IS 'Latest ingestion lag per deployment and source, written by the claim event runner every 15 seconds';
+COMMENT ON COLUMN ingestion_lag.lag_slots IS 'Slots between the newest distributor transaction on chain and the newest one ingested';
@@ -0,0 +1,4 @@
+-- Reverts 0008_holder_diffs
+
+DROP TABLE IF EXISTS holder_diff_wallets;
+DROP TABLE IF EXISTS holder_diffs;
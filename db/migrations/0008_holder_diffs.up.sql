@@ -0,0 +1,42 @@
+-- Holder diffs
+-- Who entered and left the holder set of an LST between two epoch
+-- snapshots, and how the balances of those who stayed changed
+-- (src/indexers/holder-diffs.ts). Built from epoch_snapshot_holders,
+-- IGNORE_WALLETS left out.
+
+CREATE TABLE IF NOT EXISTS holder_diffs (
+    mint TEXT NOT NULL,
+    from_epoch BIGINT NOT NULL,
+    to_epoch BIGINT NOT NULL,
+    holders_from INTEGER NOT NULL,
+    holders_to INTEGER NOT NULL,
+    amount_from NUMERIC NOT NULL,
+    amount_to NUMERIC NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (mint, from_epoch, to_epoch),
+    FOREIGN KEY (from_epoch, mint) REFERENCES epoch_snapshots(epoch, mint),
+    FOREIGN KEY (to_epoch, mint) REFERENCES epoch_snapshots(epoch, mint),
+    CHECK (from_epoch < to_epoch)
+);
+
+CREATE TABLE IF NOT EXISTS holder_diff_wallets (
+    mint TEXT NOT NULL,
+    from_epoch BIGINT NOT NULL,
+    to_epoch BIGINT NOT NULL,
+    wallet TEXT NOT NULL,
+    cohort TEXT NOT NULL CHECK (cohort IN (
+        'entered', 'exited', 'up_100', 'up_25', 'up', 'unchanged', 'down', 'down_25', 'down_75'
+    )),
+    amount_from NUMERIC NOT NULL,
+    amount_to NUMERIC NOT NULL,
+
+    PRIMARY KEY (mint, from_epoch, to_epoch, wallet),
+    FOREIGN KEY (mint, from_epoch, to_epoch) REFERENCES holder_diffs(mint, from_epoch, to_epoch) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_holder_diff_wallets_cohort
+    ON holder_diff_wallets(mint, from_epoch, to_epoch, cohort, wallet);
+
+COMMENT ON TABLE holder_diffs IS 'Holder set comparison of two epoch snapshots of a mint (consecutive ones unless built for a longer span)';
+COMMENT ON COLUMN holder_diff_wallets.cohort IS 'entered / exited the holder set, else the balance change band: up_100 (>= +100%), up_25 (>= +25%), up, unchanged, down, down_25 (<= -25%), down_75 (<= -75%)';
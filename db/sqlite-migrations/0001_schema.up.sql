@@ -0,0 +1,228 @@
+-- SQLite Schema (baseline)
+-- The tables merkle-store reads and writes, for deployments without Postgres
+--
+-- Same tables, columns, keys and CHECK constraints as
+-- db/migrations/0001_schema.up.sql and 0002_merkle_schema.up.sql, with two
+-- differences:
+--   - Amounts are INTEGER base units (64-bit signed, so at most i64::MAX)
+--     and timestamps are INTEGER unix seconds.
+--   - References to tables outside this file (wallets, reward_configs) are
+--     dropped; references between these tables are kept.
+--
+-- Idempotent, so applying it to a database created from the former
+-- db/sqlite-schema.sql changes nothing. SqliteStore::migrate applies it and
+-- records it in schema_migrations; later changes go in new migrations here.
+
+-- ============================================================================
+-- REWARD PAYOUTS PREVIEW
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS reward_payouts_preview (
+    reward_id TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    window_id TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    share NUMERIC NOT NULL,
+    total_amount INTEGER NOT NULL,
+    payout_amount INTEGER NOT NULL,
+    PRIMARY KEY (reward_id, wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payouts_preview_reward
+    ON reward_payouts_preview(reward_id);
+
+CREATE INDEX IF NOT EXISTS idx_payouts_preview_wallet
+    ON reward_payouts_preview(wallet);
+
+-- ============================================================================
+-- EPOCH SNAPSHOTS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS epoch_snapshots (
+    epoch INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    epoch_first_slot INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    token_accounts INTEGER NOT NULL,
+    holders INTEGER NOT NULL,
+    total_amount INTEGER NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    PRIMARY KEY (epoch, mint),
+    CHECK (slot >= epoch_first_slot)
+);
+
+CREATE TABLE IF NOT EXISTS epoch_snapshot_balances (
+    epoch INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    token_account TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (epoch, mint, token_account),
+    FOREIGN KEY (epoch, mint) REFERENCES epoch_snapshots(epoch, mint)
+);
+
+CREATE INDEX IF NOT EXISTS idx_epoch_snapshot_balances_owner
+    ON epoch_snapshot_balances(owner, epoch);
+
+CREATE VIEW IF NOT EXISTS epoch_snapshot_holders AS
+SELECT
+    epoch,
+    mint,
+    owner AS wallet,
+    SUM(amount) AS amount,
+    COUNT(*) AS token_accounts
+FROM epoch_snapshot_balances
+GROUP BY epoch, mint, owner;
+
+-- ============================================================================
+-- MERKLE DISTRIBUTIONS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_distributions (
+    distribution_id TEXT PRIMARY KEY,
+    reward_id TEXT NOT NULL,
+    window_id TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    total_amount INTEGER NOT NULL,
+    merkle_root TEXT NOT NULL,
+    num_recipients INTEGER NOT NULL,
+    csv_hash TEXT NOT NULL,
+    artifact_path TEXT NOT NULL,
+
+    -- On-chain state (populated after multisig initialization)
+    on_chain_address TEXT,
+    vault_ata TEXT,
+
+    -- Tracking
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'funded', 'active', 'completed', 'clawedback')
+    ),
+    claimed_amount INTEGER NOT NULL DEFAULT 0,
+    claimed_count INTEGER NOT NULL DEFAULT 0,
+
+    -- Timestamps
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    updated_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    funded_at INTEGER,
+    activated_at INTEGER,
+    completed_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_reward
+    ON merkle_distributions(reward_id);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_status
+    ON merkle_distributions(status);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_mint
+    ON merkle_distributions(mint);
+
+-- updated_at on every update, unless the update set it
+CREATE TRIGGER IF NOT EXISTS trigger_merkle_distribution_updated
+    AFTER UPDATE ON merkle_distributions
+    FOR EACH ROW
+    WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+    UPDATE merkle_distributions
+    SET updated_at = unixepoch()
+    WHERE distribution_id = NEW.distribution_id;
+END;
+
+-- ============================================================================
+-- MERKLE CLAIMS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_claims (
+    distribution_id TEXT NOT NULL REFERENCES merkle_distributions(distribution_id),
+    leaf_index INTEGER NOT NULL,
+    wallet TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+
+    -- Claim state
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (
+        status IN ('pending', 'submitted', 'confirmed', 'failed')
+    ),
+
+    -- Transaction tracking
+    tx_signature TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_attempt INTEGER,
+    confirmed_at INTEGER,
+    error_message TEXT,
+
+    PRIMARY KEY (distribution_id, leaf_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_wallet
+    ON merkle_claims(wallet);
+
+CREATE INDEX IF NOT EXISTS idx_merkle_claims_pending
+    ON merkle_claims(distribution_id, status)
+    WHERE status IN ('pending', 'failed');
+
+-- ============================================================================
+-- MERKLE CLAIM EVENTS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_claim_events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    instruction_index INTEGER,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+
+    -- Distribution PDA (matches merkle_distributions.on_chain_address)
+    distribution TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (
+        kind IN ('claim', 'claim_referral', 'escrow', 'renounce', 'escrow_release', 'clawback', 'pause', 'unpause', 'operator_change')
+    ),
+    recipient TEXT,
+    amount INTEGER,
+    leaf_index INTEGER,
+    referrer TEXT,
+    bonus INTEGER,
+    destination TEXT,
+
+    source TEXT NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_distribution
+    ON merkle_claim_events(distribution, slot);
+
+CREATE INDEX IF NOT EXISTS idx_claim_events_recipient
+    ON merkle_claim_events(recipient);
+
+-- Upsert key, as in db/migrations/0002_merkle_schema.up.sql
+CREATE UNIQUE INDEX IF NOT EXISTS idx_claim_events_instruction
+    ON merkle_claim_events(signature, instruction_index, event_index);
+
+-- ============================================================================
+-- RECONCILIATION
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS merkle_reconciliation_divergences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+
+    -- Distribution PDA
+    distribution TEXT NOT NULL,
+    field TEXT NOT NULL CHECK (
+        field IN ('claimed_amount', 'num_claimed', 'vault_balance')
+    ),
+    on_chain INTEGER NOT NULL,
+    expected INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+
+    first_seen_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    last_seen_at INTEGER NOT NULL DEFAULT (unixepoch()),
+    alerted_at INTEGER,
+    resolved_at INTEGER
+);
+
+-- At most one open divergence per distribution and field
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reconciliation_open
+    ON merkle_reconciliation_divergences(distribution, field)
+    WHERE resolved_at IS NULL;
@@ -0,0 +1,66 @@
+-- Deployments
+-- The deployment column and keys of db/migrations/0003_deployments.up.sql:
+-- a deployment is one distributor program on one cluster, and every key
+-- holding a PDA or a signature includes it. Existing rows belong to
+-- 'default'.
+
+ALTER TABLE merkle_distributions ADD COLUMN deployment TEXT NOT NULL DEFAULT 'default';
+ALTER TABLE merkle_reconciliation_divergences ADD COLUMN deployment TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_merkle_dist_address
+    ON merkle_distributions(deployment, on_chain_address);
+
+DROP INDEX IF EXISTS idx_reconciliation_open;
+CREATE UNIQUE INDEX idx_reconciliation_open
+    ON merkle_reconciliation_divergences(deployment, distribution, field)
+    WHERE resolved_at IS NULL;
+
+-- Claim events: SQLite can't change a primary key in place, so the table is
+-- rebuilt keyed by (deployment, signature, event_index)
+CREATE TABLE merkle_claim_events_new (
+    deployment TEXT NOT NULL DEFAULT 'default',
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    instruction_index INTEGER,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+
+    -- Distribution PDA (matches merkle_distributions.on_chain_address)
+    distribution TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (
+        kind IN ('claim', 'claim_referral', 'escrow', 'renounce', 'escrow_release', 'clawback', 'pause', 'unpause', 'operator_change')
+    ),
+    recipient TEXT,
+    amount INTEGER,
+    leaf_index INTEGER,
+    referrer TEXT,
+    bonus INTEGER,
+    destination TEXT,
+
+    source TEXT NOT NULL,
+    created_at INTEGER NOT NULL DEFAULT (unixepoch()),
+
+    PRIMARY KEY (deployment, signature, event_index)
+);
+
+INSERT INTO merkle_claim_events_new (
+    signature, event_index, instruction_index, slot, block_time, distribution, kind,
+    recipient, amount, leaf_index, referrer, bonus, destination, source, created_at
+)
+SELECT
+    signature, event_index, instruction_index, slot, block_time, distribution, kind,
+    recipient, amount, leaf_index, referrer, bonus, destination, source, created_at
+FROM merkle_claim_events;
+
+DROP TABLE merkle_claim_events;
+ALTER TABLE merkle_claim_events_new RENAME TO merkle_claim_events;
+
+CREATE INDEX idx_claim_events_distribution
+    ON merkle_claim_events(distribution, slot);
+
+CREATE INDEX idx_claim_events_recipient
+    ON merkle_claim_events(recipient);
+
+-- Upsert key, as in db/migrations/0003_deployments.up.sql
+CREATE UNIQUE INDEX idx_claim_events_instruction
+    ON merkle_claim_events(deployment, signature, instruction_index, event_index);
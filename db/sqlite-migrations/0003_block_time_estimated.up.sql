@@ -0,0 +1,7 @@
+-- Estimated block times
+-- The block_time_estimated flag of db/migrations/0004_slot_block_times.up.sql:
+-- an event's block time may be interpolated from neighboring slots when the
+-- cluster reports none for its slot.
+
+ALTER TABLE merkle_claim_events ADD COLUMN block_time_estimated INTEGER NOT NULL DEFAULT 0
+    CHECK (block_time_estimated IN (0, 1));
@@ -1,53 +1,166 @@
 //! Merkle Distributor Program
-//! 
+//!
 //! A Solana program for distributing SPL tokens using Merkle proofs.
-//! 
+//!
 //! ## Overview
-//! 
+//!
 //! This program enables efficient, trustless distribution of SPL tokens to
 //! many recipients through a single Merkle root commitment. The workflow:
-//! 
+//!
 //! 1. Off-chain: Build Merkle tree from (recipient, amount) pairs
 //! 2. Multisig: Fund vault and initialize distribution with Merkle root
 //! 3. Relayer/Users: Submit claims with Merkle proofs
-//! 4. Optional: Clawback remaining funds after expiry
-//! 
+//! 4. Optional: Clawback remaining funds after expiry, or sweep rounding dust
+//!
 //! ## Security Properties
-//! 
+//!
 //! - Only the committed Merkle root can authorize claims
 //! - Each leaf can only be claimed once (tracked via claim PDAs)
 //! - Domain separation prevents cross-distribution replay
 //! - Authority controls initialization and clawback
 
+// The IDL instructions generated by `#[program]` still call
+// `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::memo::{self, BuildMemo, Memo};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_hook, StateWithExtensions};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 declare_id!("8LMVzwtrcVCLJPFfUFviqWv49WoyN1PKNLd9EDj4X4H4");
 
-/// Domain separator for leaf hashing (must match off-chain builder)
-pub const DOMAIN_SEPARATOR: &[u8] = b"L33_MERKLE_V1";
+// Leaf domain separator, V2 prefixes and maximum proof depth (2^20 = ~1M
+// recipients), shared with the off-chain builder
+pub use merkle_core::{DOMAIN_SEPARATOR, LEAF_PREFIX, MAX_PROOF_LEN, NODE_PREFIX};
+
+/// Maximum metadata name length in bytes
+pub const MAX_NAME_LEN: usize = 32;
+
+/// Maximum metadata symbol length in bytes
+pub const MAX_SYMBOL_LEN: usize = 10;
+
+/// Maximum metadata URI length in bytes
+pub const MAX_URI_LEN: usize = 200;
+
+/// Maximum claim memo (external reference) length in bytes
+pub const MAX_MEMO_LEN: usize = 32;
+
+/// Maximum number of mints in the global allow-list
+pub const MAX_ALLOWED_MINTS: usize = 16;
+
+/// Maximum number of relayers in the global allow-list
+pub const MAX_ALLOWED_RELAYERS: usize = 16;
+
+/// Basis point denominator
+pub const BPS_DENOMINATOR: u64 = 10_000;
 
-/// Maximum proof depth (supports up to 2^20 = ~1M recipients)
-pub const MAX_PROOF_LEN: usize = 20;
+/// Maximum number of claims in one `claim_batch`
+pub const MAX_BATCH_CLAIMS: usize = 8;
 
 #[program]
 pub mod merkle_distributor {
     use super::*;
 
+    /// Create the global protocol config
+    ///
+    /// Callable once, by the program's upgrade authority.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        validate_config(&params)?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin = params.admin;
+        config.default_claim_fee_lamports = params.default_claim_fee_lamports;
+        config.allowed_mints = params.allowed_mints;
+        config.allowed_relayers = params.allowed_relayers;
+        config.bump = ctx.bumps.config;
+
+        msg!("Config initialized: admin={}", config.admin);
+
+        Ok(())
+    }
+
+    /// Replace the global protocol config (admin only)
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        validate_config(&params)?;
+
+        let config = &mut ctx.accounts.config;
+        config.admin = params.admin;
+        config.default_claim_fee_lamports = params.default_claim_fee_lamports;
+        config.allowed_mints = params.allowed_mints;
+        config.allowed_relayers = params.allowed_relayers;
+
+        msg!("Config updated: admin={}", config.admin);
+
+        Ok(())
+    }
+
     /// Initialize a new distribution
-    /// 
+    ///
     /// Creates the distribution account and vault for token storage.
     /// Must be called by the distribution authority (typically a multisig).
+    /// The mint must be on the global config's allow-list (if non-empty);
+    /// the claim fee defaults to the global config's default. Before
+    /// `initialize_config` has run, any mint is allowed and the fee is zero.
+    ///
+    /// With `id_epoch` set (strict mode), `distribution_id` must equal
+    /// `keccak(mint || merkle_root || epoch)`, binding the id to its contents.
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<Initialize>,
         distribution_id: [u8; 32],
         merkle_root: [u8; 32],
         total_amount: u64,
         num_recipients: u64,
+        clawback_destination: Pubkey,
+        require_recipient_signature: bool,
+        hash_scheme: HashScheme,
+        hash_kind: HashKind,
+        proof_mode: ProofMode,
+        id_epoch: Option<u64>,
     ) -> Result<()> {
-        let distribution = &mut ctx.accounts.distribution;
-        
+        if let Some(epoch) = id_epoch {
+            require!(
+                distribution_id
+                    == derive_distribution_id(&ctx.accounts.mint.key(), &merkle_root, epoch),
+                DistributorError::DistributionIdMismatch
+            );
+        }
+
+        let config = load_config(&ctx.accounts.config)?;
+        require!(
+            config.as_ref().is_none_or(|config| {
+                config.allowed_mints.is_empty()
+                    || config.allowed_mints.contains(&ctx.accounts.mint.key())
+            }),
+            DistributorError::MintNotAllowed
+        );
+        let default_claim_fee_lamports = config
+            .as_ref()
+            .map_or(0, |config| config.default_claim_fee_lamports);
+
+        // Append to the global registry so distributions can be enumerated
+        let registry = &mut ctx.accounts.registry;
+        let registry_index = registry.count;
+        registry.count = registry
+            .count
+            .checked_add(1)
+            .ok_or(DistributorError::Overflow)?;
+        registry.bump = ctx.bumps.registry;
+
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.index = registry_index;
+        entry.distribution = ctx.accounts.distribution.key();
+        entry.authority = ctx.accounts.authority.key();
+        entry.mint = ctx.accounts.mint.key();
+        entry.created_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.registry_entry;
+
+        let mut distribution = ctx.accounts.distribution.load_init()?;
+
         distribution.authority = ctx.accounts.authority.key();
         distribution.operator = ctx.accounts.authority.key(); // Default: authority is operator
         distribution.mint = ctx.accounts.mint.key();
@@ -58,82 +171,419 @@ pub mod merkle_distributor {
         distribution.claimed_amount = 0;
         distribution.num_recipients = num_recipients;
         distribution.num_claimed = 0;
-        distribution.paused = false;
+        distribution.clawback_destination = clawback_destination;
+        distribution.require_recipient_signature = u8::from(require_recipient_signature);
+        distribution.hash_scheme = hash_scheme as u8;
+        distribution.hash_kind = hash_kind as u8;
+        distribution.proof_mode = proof_mode as u8;
+        distribution.dust_collector = Pubkey::default();
+        distribution.dust_threshold = 0;
+        distribution.escrowed_amount = 0;
+        distribution.clawed_back_amount = 0;
+        distribution.renounced_amount = 0;
+        distribution.bonus_vault = Pubkey::default();
+        distribution.referral_bonus_bps = 0;
+        distribution.referral_cap = 0;
+        distribution.referral_paid_amount = 0;
+        distribution.claim_fee_lamports = default_claim_fee_lamports;
+        distribution.registry_index = registry_index;
+        distribution.set_paused(false);
         distribution.bump = ctx.bumps.distribution;
         distribution.vault_bump = ctx.bumps.vault;
 
         msg!(
-            "Distribution initialized: recipients={}, total={}",
+            "Distribution initialized: recipients={}, total={}, registry_index={}",
             num_recipients,
-            total_amount
+            total_amount,
+            registry_index
         );
 
         Ok(())
     }
 
     /// Set the operator (relayer) that can submit claims
-    /// 
+    ///
     /// The operator can submit claims on behalf of recipients but cannot
     /// modify the distribution or claim funds for themselves.
+    ///
+    /// The operator must be on the global config's relayer allow-list
+    /// (if the config exists and the list is non-empty).
     pub fn set_operator(ctx: Context<SetOperator>, new_operator: Pubkey) -> Result<()> {
-        ctx.accounts.distribution.operator = new_operator;
+        let config = load_config(&ctx.accounts.config)?;
+        require!(
+            config.as_ref().is_none_or(|config| {
+                config.allowed_relayers.is_empty()
+                    || config.allowed_relayers.contains(&new_operator)
+            }),
+            DistributorError::RelayerNotAllowed
+        );
+
+        ctx.accounts.distribution.load_mut()?.operator = new_operator;
         msg!("Operator set to: {}", new_operator);
         Ok(())
     }
 
     /// Claim tokens for a single recipient
-    /// 
+    ///
     /// Verifies the Merkle proof and transfers tokens to the recipient.
     /// Creates a claim PDA to prevent double-claiming.
-    pub fn claim(
-        ctx: Context<ProcessClaim>,
+    ///
+    /// The only signer is the payer, so other programs can claim on behalf
+    /// of their users via CPI (`cpi` feature). The claim result is returned
+    /// as return data.
+    ///
+    /// A non-empty `memo` (e.g. payout batch number) is written to the SPL
+    /// Memo program and stored on the claim record.
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessClaim<'info>>,
         index: u64,
         amount: u64,
         proof: Vec<[u8; 32]>,
-    ) -> Result<()> {
-        let distribution = &ctx.accounts.distribution;
-        
-        // Check not paused
-        require!(!distribution.paused, DistributorError::Paused);
+        memo: Vec<u8>,
+    ) -> Result<ClaimResult> {
+        let distribution = ctx.accounts.distribution.load()?;
 
-        // Verify the Merkle proof
-        let leaf = compute_leaf(
-            &distribution.distribution_id,
-            &ctx.accounts.recipient.key(),
+        require!(memo.len() <= MAX_MEMO_LEN, DistributorError::MemoTooLong);
+
+        verify_claim(
+            &distribution,
+            &ctx.accounts.recipient,
+            index,
+            amount,
+            &proof,
+        )?;
+
+        charge_claim_fee(
+            &distribution,
+            &ctx.accounts.payer,
+            &ctx.accounts.operator,
+            &ctx.accounts.system_program,
+            1,
+        )?;
+
+        // Transfer tokens (transfer-hook accounts are passed as remaining accounts)
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        if !memo.is_empty() {
+            let memo_program = ctx
+                .accounts
+                .memo_program
+                .as_ref()
+                .ok_or(DistributorError::MissingMemoProgram)?;
+            memo::build_memo(
+                CpiContext::new(memo_program.to_account_info(), BuildMemo {}),
+                &memo,
+            )?;
+        }
+
+        // Update distribution stats
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.claimed_amount = distribution
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.num_claimed = distribution
+            .num_claimed
+            .checked_add(1)
+            .ok_or(DistributorError::Overflow)?;
+
+        // Initialize claim record
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.distribution = ctx.accounts.distribution.key();
+        claim_record.index = index;
+        claim_record.recipient = ctx.accounts.recipient.key();
+        claim_record.amount = amount;
+        claim_record.claimed_at = Clock::get()?.unix_timestamp;
+        claim_record.memo = memo;
+        claim_record.escrowed = false;
+        claim_record.renounced = false;
+        claim_record.escrowed_amount = 0;
+        claim_record.bump = ctx.bumps.claim_record;
+
+        msg!(
+            "Claimed: recipient={}, amount={}, index={}",
+            ctx.accounts.recipient.key(),
+            amount,
+            index
+        );
+
+        Ok(ClaimResult {
+            recipient: ctx.accounts.recipient.key(),
+            index,
             amount,
+        })
+    }
+
+    /// Claim tokens for several recipients with one Merkle multiproof
+    ///
+    /// Claims from the same tree share internal nodes, so a multiproof
+    /// (`proof` + `proof_flags`) is much smaller than one proof per leaf.
+    /// `claims` must be in ascending index order. For each claim the
+    /// remaining accounts carry `[claim_record, recipient,
+    /// recipient_token_account]`.
+    ///
+    /// Only supported for `ProofMode::Sorted` distributions and mints
+    /// without a transfer hook: the remaining accounts have no room for
+    /// per-recipient hook accounts, so hook mints are rejected up front.
+    pub fn claim_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimBatch<'info>>,
+        claims: Vec<BatchClaim>,
+        proof: Vec<[u8; 32]>,
+        proof_flags: Vec<bool>,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+
+        require!(!distribution.paused(), DistributorError::Paused);
+        require!(
+            distribution.proof_mode() == ProofMode::Sorted,
+            DistributorError::UnsupportedProofMode
+        );
+        require!(
+            !has_transfer_hook(&ctx.accounts.mint)?,
+            DistributorError::TransferHookUnsupported
+        );
+        require!(
+            !claims.is_empty()
+                && claims.len() <= MAX_BATCH_CLAIMS
+                && ctx.remaining_accounts.len() == claims.len() * 3
+                && claims.windows(2).all(|pair| pair[0].index < pair[1].index),
+            DistributorError::InvalidBatch
+        );
+        // Indices are ascending, so checking the last bounds them all
+        require!(
+            claims
+                .last()
+                .is_some_and(|claim| claim.index < distribution.num_recipients),
+            DistributorError::IndexOutOfRange
         );
-        
+
+        // Verify all leaves against the root at once
+        let mut leaves = Vec::with_capacity(claims.len());
+        for (claim, accounts) in claims.iter().zip(ctx.remaining_accounts.chunks(3)) {
+            let recipient = &accounts[1];
+            require!(
+                !distribution.require_recipient_signature() || recipient.is_signer,
+                DistributorError::RecipientSignatureRequired
+            );
+            leaves.push(compute_leaf(
+                distribution.hash_kind(),
+                distribution.hash_scheme(),
+                &distribution.distribution_id,
+                recipient.key,
+                claim.amount,
+            ));
+        }
+
         require!(
-            verify_proof(&proof, &distribution.merkle_root, leaf),
+            verify_multiproof(
+                distribution.hash_kind(),
+                distribution.hash_scheme(),
+                &proof,
+                &proof_flags,
+                &distribution.merkle_root,
+                &leaves,
+            ),
             DistributorError::InvalidProof
         );
 
-        // Transfer tokens
-        let seeds = &[
-            b"distribution",
-            distribution.distribution_id.as_ref(),
-            &[distribution.bump],
-        ];
-        let signer = &[&seeds[..]];
-
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.recipient_token_account.to_account_info(),
-                authority: ctx.accounts.distribution.to_account_info(),
-            },
-            signer,
+        charge_claim_fee(
+            &distribution,
+            &ctx.accounts.payer,
+            &ctx.accounts.operator,
+            &ctx.accounts.system_program,
+            claims.len() as u64,
+        )?;
+
+        let claimed_at = Clock::get()?.unix_timestamp;
+        let mut batch_amount: u64 = 0;
+
+        for (claim, accounts) in claims.iter().zip(ctx.remaining_accounts.chunks(3)) {
+            let [claim_info, recipient, recipient_token_info] = accounts else {
+                return err!(DistributorError::InvalidBatch);
+            };
+
+            // Same constraints as `token::mint` / `token::authority` on `claim`
+            let recipient_token_account =
+                InterfaceAccount::<TokenAccount>::try_from(recipient_token_info)?;
+            require_keys_eq!(
+                recipient_token_account.mint,
+                distribution.mint,
+                DistributorError::InvalidMint
+            );
+            require_keys_eq!(
+                recipient_token_account.owner,
+                recipient.key(),
+                DistributorError::InvalidRecipientTokenAccount
+            );
+
+            let bump = create_claim_record(
+                &ctx.accounts.distribution.key(),
+                claim.index,
+                claim_info,
+                &ctx.accounts.payer,
+                &ctx.accounts.system_program,
+            )?;
+
+            let record = ClaimRecord {
+                distribution: ctx.accounts.distribution.key(),
+                index: claim.index,
+                recipient: recipient.key(),
+                amount: claim.amount,
+                claimed_at,
+                memo: Vec::new(),
+                escrowed: false,
+                renounced: false,
+                escrowed_amount: 0,
+                bump,
+            };
+            let mut data = claim_info.try_borrow_mut_data()?;
+            record.try_serialize(&mut &mut data[..])?;
+            drop(data);
+
+            transfer_signed_by_distribution(
+                &ctx.accounts.distribution,
+                &ctx.accounts.vault,
+                &ctx.accounts.mint,
+                recipient_token_info.clone(),
+                &ctx.accounts.token_program,
+                &[],
+                claim.amount,
+            )?;
+
+            batch_amount = batch_amount
+                .checked_add(claim.amount)
+                .ok_or(DistributorError::Overflow)?;
+
+            msg!(
+                "Claimed: recipient={}, amount={}, index={}",
+                recipient.key(),
+                claim.amount,
+                claim.index
+            );
+        }
+
+        // Update distribution stats
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.claimed_amount = distribution
+            .claimed_amount
+            .checked_add(batch_amount)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.num_claimed = distribution
+            .num_claimed
+            .checked_add(claims.len() as u64)
+            .ok_or(DistributorError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Claim tokens and pay a referral bonus
+    ///
+    /// Pays the standard amount to the recipient exactly like `claim`, then
+    /// pays `referral_bonus_bps` of the amount to the referrer from the bonus
+    /// vault. Each referrer's cumulative bonus is capped at `referral_cap`;
+    /// a claim past the cap still succeeds with a reduced (or zero) bonus.
+    pub fn claim_with_referral<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithReferral<'info>>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        referrer: Pubkey,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+
+        require!(
+            distribution.referral_bonus_bps > 0,
+            DistributorError::ReferralsDisabled
+        );
+        require!(
+            referrer != ctx.accounts.recipient.key(),
+            DistributorError::SelfReferral
         );
 
-        token::transfer(transfer_ctx, amount)?;
+        verify_claim(
+            &distribution,
+            &ctx.accounts.recipient,
+            index,
+            amount,
+            &proof,
+        )?;
+
+        charge_claim_fee(
+            &distribution,
+            &ctx.accounts.payer,
+            &ctx.accounts.operator,
+            &ctx.accounts.system_program,
+            1,
+        )?;
+
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        // Bonus: bps of the claim, limited by what is left under the cap
+        let bonus = (amount as u128 * distribution.referral_bonus_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let remaining_cap = distribution
+            .referral_cap
+            .saturating_sub(ctx.accounts.referrer_record.total_bonus);
+        let bonus = bonus.min(remaining_cap);
+
+        if bonus > 0 {
+            transfer_signed_by_distribution(
+                &ctx.accounts.distribution,
+                &ctx.accounts.bonus_vault,
+                &ctx.accounts.mint,
+                ctx.accounts.referrer_token_account.to_account_info(),
+                &ctx.accounts.token_program,
+                ctx.remaining_accounts,
+                bonus,
+            )?;
+        }
 
         // Update distribution stats
-        let distribution = &mut ctx.accounts.distribution;
-        distribution.claimed_amount = distribution.claimed_amount.checked_add(amount)
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.claimed_amount = distribution
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.num_claimed = distribution
+            .num_claimed
+            .checked_add(1)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.referral_paid_amount = distribution
+            .referral_paid_amount
+            .checked_add(bonus)
             .ok_or(DistributorError::Overflow)?;
-        distribution.num_claimed = distribution.num_claimed.checked_add(1)
+
+        let referrer_record = &mut ctx.accounts.referrer_record;
+        referrer_record.distribution = ctx.accounts.distribution.key();
+        referrer_record.referrer = referrer;
+        referrer_record.total_bonus = referrer_record
+            .total_bonus
+            .checked_add(bonus)
+            .ok_or(DistributorError::Overflow)?;
+        referrer_record.num_referrals = referrer_record
+            .num_referrals
+            .checked_add(1)
             .ok_or(DistributorError::Overflow)?;
+        referrer_record.bump = ctx.bumps.referrer_record;
 
         // Initialize claim record
         let claim_record = &mut ctx.accounts.claim_record;
@@ -142,10 +592,149 @@ pub mod merkle_distributor {
         claim_record.recipient = ctx.accounts.recipient.key();
         claim_record.amount = amount;
         claim_record.claimed_at = Clock::get()?.unix_timestamp;
+        claim_record.memo = Vec::new();
+        claim_record.escrowed = false;
+        claim_record.renounced = false;
+        claim_record.escrowed_amount = 0;
         claim_record.bump = ctx.bumps.claim_record;
 
         msg!(
-            "Claimed: recipient={}, amount={}, index={}",
+            "Claimed with referral: recipient={}, amount={}, index={}, referrer={}, bonus={}",
+            ctx.accounts.recipient.key(),
+            amount,
+            index,
+            referrer,
+            bonus
+        );
+
+        Ok(())
+    }
+
+    /// Claim tokens into a per-recipient escrow
+    ///
+    /// Fallback for recipients whose token account is frozen or missing.
+    /// Verifies the proof and charges the claim fee exactly like `claim` and
+    /// consumes the leaf, but parks the tokens in an escrow PDA owned by the
+    /// distribution until `release_escrow` is called for this index.
+    pub fn claim_to_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimToEscrow<'info>>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+
+        verify_claim(
+            &distribution,
+            &ctx.accounts.recipient,
+            index,
+            amount,
+            &proof,
+        )?;
+
+        charge_claim_fee(
+            &distribution,
+            &ctx.accounts.payer,
+            &ctx.accounts.operator,
+            &ctx.accounts.system_program,
+            1,
+        )?;
+
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.escrow.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        // Update distribution stats
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.claimed_amount = distribution
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.num_claimed = distribution
+            .num_claimed
+            .checked_add(1)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.escrowed_amount = distribution
+            .escrowed_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+
+        // Initialize claim record
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.distribution = ctx.accounts.distribution.key();
+        claim_record.index = index;
+        claim_record.recipient = ctx.accounts.recipient.key();
+        claim_record.amount = amount;
+        claim_record.claimed_at = Clock::get()?.unix_timestamp;
+        claim_record.memo = Vec::new();
+        claim_record.escrowed = true;
+        claim_record.renounced = false;
+        claim_record.escrowed_amount = amount;
+        claim_record.bump = ctx.bumps.claim_record;
+
+        msg!(
+            "Escrowed: recipient={}, amount={}, index={}",
+            ctx.accounts.recipient.key(),
+            amount,
+            index
+        );
+
+        Ok(())
+    }
+
+    /// Renounce an allocation
+    ///
+    /// Signed by the recipient. Verifies the proof and consumes the leaf
+    /// without transferring tokens, so the amount stays in the vault and is
+    /// immediately eligible for clawback.
+    pub fn renounce_claim(
+        ctx: Context<RenounceClaim>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+
+        verify_claim(
+            &distribution,
+            &ctx.accounts.recipient,
+            index,
+            amount,
+            &proof,
+        )?;
+
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.renounced_amount = distribution
+            .renounced_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+        distribution.num_claimed = distribution
+            .num_claimed
+            .checked_add(1)
+            .ok_or(DistributorError::Overflow)?;
+
+        let claim_record = &mut ctx.accounts.claim_record;
+        claim_record.distribution = ctx.accounts.distribution.key();
+        claim_record.index = index;
+        claim_record.recipient = ctx.accounts.recipient.key();
+        claim_record.amount = amount;
+        claim_record.claimed_at = Clock::get()?.unix_timestamp;
+        claim_record.memo = Vec::new();
+        claim_record.escrowed = false;
+        claim_record.renounced = true;
+        claim_record.escrowed_amount = 0;
+        claim_record.bump = ctx.bumps.claim_record;
+
+        msg!(
+            "Renounced: recipient={}, amount={}, index={}",
             ctx.accounts.recipient.key(),
             amount,
             index
@@ -154,114 +743,841 @@ pub mod merkle_distributor {
         Ok(())
     }
 
+    /// Release the tokens escrowed by the claim at `index` to the recipient
+    ///
+    /// Permissionless: tokens can only move to a token account owned by the
+    /// recipient the escrow was created for. Releases the amount recorded
+    /// on the claim record, not the escrow's balance, so tokens sent to the
+    /// escrow directly are never counted against `escrowed_amount`.
+    pub fn release_escrow<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReleaseEscrow<'info>>,
+        _index: u64,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+        let amount = ctx.accounts.claim_record.escrowed_amount;
+
+        require!(!distribution.paused(), DistributorError::Paused);
+        require!(amount > 0, DistributorError::EscrowEmpty);
+
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.escrow,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        ctx.accounts.claim_record.escrowed_amount = 0;
+
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.escrowed_amount = distribution
+            .escrowed_amount
+            .checked_sub(amount)
+            .ok_or(DistributorError::Overflow)?;
+
+        msg!(
+            "Escrow released: recipient={}, amount={}",
+            ctx.accounts.recipient.key(),
+            amount
+        );
+
+        Ok(())
+    }
+
+    /// Create the metadata account for a distribution
+    ///
+    /// Optional: typically bundled in the same transaction as `initialize`
+    /// so explorers and claim UIs can display the distribution.
+    pub fn initialize_metadata(
+        ctx: Context<InitializeMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+        epoch: u64,
+    ) -> Result<()> {
+        validate_metadata(&name, &symbol, &uri)?;
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.distribution = ctx.accounts.distribution.key();
+        metadata.name = name;
+        metadata.symbol = symbol;
+        metadata.uri = uri;
+        metadata.epoch = epoch;
+        metadata.tree_uri = String::new();
+        metadata.tree_file_hash = [0u8; 32];
+        metadata.bump = ctx.bumps.metadata;
+
+        msg!(
+            "Metadata initialized: name={}, epoch={}",
+            metadata.name,
+            epoch
+        );
+
+        Ok(())
+    }
+
+    /// Update the metadata of a distribution
+    pub fn update_metadata(
+        ctx: Context<UpdateMetadata>,
+        name: String,
+        symbol: String,
+        uri: String,
+        epoch: u64,
+    ) -> Result<()> {
+        validate_metadata(&name, &symbol, &uri)?;
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.name = name;
+        metadata.symbol = symbol;
+        metadata.uri = uri;
+        metadata.epoch = epoch;
+
+        msg!("Metadata updated: name={}, epoch={}", metadata.name, epoch);
+
+        Ok(())
+    }
+
+    /// Record where the full tree is published and its content hash
+    ///
+    /// Lets third parties fetch the recipient file (e.g. `ipfs://<cid>`) and
+    /// check it against `tree_file_hash` and the on-chain root. Only allowed
+    /// before the first claim, so the reference cannot be swapped once
+    /// claims depend on it.
+    pub fn set_tree_source(
+        ctx: Context<UpdateMetadata>,
+        tree_uri: String,
+        tree_file_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.distribution.load()?.num_claimed == 0,
+            DistributorError::ClaimsStarted
+        );
+        require!(
+            tree_uri.len() <= MAX_URI_LEN,
+            DistributorError::MetadataTooLong
+        );
+
+        let metadata = &mut ctx.accounts.metadata;
+        metadata.tree_uri = tree_uri;
+        metadata.tree_file_hash = tree_file_hash;
+
+        msg!("Tree source set: uri={}", metadata.tree_uri);
+
+        Ok(())
+    }
+
+    /// Set the lamport fee charged on claims not submitted by the operator
+    pub fn set_claim_fee(ctx: Context<AdminAction>, claim_fee_lamports: u64) -> Result<()> {
+        ctx.accounts.distribution.load_mut()?.claim_fee_lamports = claim_fee_lamports;
+        msg!("Claim fee set to: {} lamports", claim_fee_lamports);
+        Ok(())
+    }
+
+    /// Enable referral bonuses
+    ///
+    /// Creates the bonus vault (funded separately by the authority) and sets
+    /// the bonus rate and per-referrer cap. Can be called again to change
+    /// the rate or cap; a rate of zero disables referrals.
+    pub fn set_referral_pool(
+        ctx: Context<SetReferralPool>,
+        referral_bonus_bps: u16,
+        referral_cap: u64,
+    ) -> Result<()> {
+        require!(
+            referral_bonus_bps as u64 <= BPS_DENOMINATOR,
+            DistributorError::InvalidBps
+        );
+
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.bonus_vault = ctx.accounts.bonus_vault.key();
+        distribution.referral_bonus_bps = referral_bonus_bps;
+        distribution.referral_cap = referral_cap;
+
+        msg!(
+            "Referral pool set: bonus_bps={}, cap={}",
+            referral_bonus_bps,
+            referral_cap
+        );
+
+        Ok(())
+    }
+
     /// Pause the distribution (emergency only)
     pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
-        ctx.accounts.distribution.paused = true;
+        ctx.accounts.distribution.load_mut()?.set_paused(true);
         msg!("Distribution paused");
         Ok(())
     }
 
     /// Unpause the distribution
     pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
-        ctx.accounts.distribution.paused = false;
+        ctx.accounts.distribution.load_mut()?.set_paused(false);
         msg!("Distribution unpaused");
         Ok(())
     }
 
-    /// Clawback remaining funds to authority
-    /// 
-    /// Returns any unclaimed tokens to the distribution authority.
-    /// Typically used after claim period expires.
-    pub fn clawback(ctx: Context<Clawback>) -> Result<()> {
-        let distribution = &ctx.accounts.distribution;
+    /// Configure dust sweeping
+    ///
+    /// Sets the token account that receives residual vault balances and the
+    /// maximum balance the operator is allowed to sweep. A threshold of zero
+    /// disables sweeping.
+    pub fn set_dust_config(
+        ctx: Context<AdminAction>,
+        dust_collector: Pubkey,
+        dust_threshold: u64,
+    ) -> Result<()> {
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.dust_collector = dust_collector;
+        distribution.dust_threshold = dust_threshold;
+        msg!(
+            "Dust config set: collector={}, threshold={}",
+            dust_collector,
+            dust_threshold
+        );
+        Ok(())
+    }
+
+    /// Sweep residual vault dust to the dust collector
+    ///
+    /// Callable by the operator once every recipient has claimed. Moves the
+    /// remaining vault balance (rounding leftovers) to the configured dust
+    /// collector, provided it does not exceed the dust threshold.
+    pub fn sweep_dust<'info>(ctx: Context<'_, '_, 'info, 'info, SweepDust<'info>>) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
         let remaining = ctx.accounts.vault.amount;
 
-        let seeds = &[
-            b"distribution",
-            distribution.distribution_id.as_ref(),
-            &[distribution.bump],
-        ];
-        let signer = &[&seeds[..]];
-
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.authority_token_account.to_account_info(),
-                authority: ctx.accounts.distribution.to_account_info(),
-            },
-            signer,
+        require!(
+            distribution.num_claimed >= distribution.num_recipients,
+            DistributorError::ClaimsOutstanding
+        );
+        require!(
+            remaining > 0 && remaining <= distribution.dust_threshold,
+            DistributorError::DustAboveThreshold
         );
 
-        token::transfer(transfer_ctx, remaining)?;
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.dust_collector.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            remaining,
+        )?;
 
-        msg!("Clawback: {} tokens returned to authority", remaining);
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.clawed_back_amount = distribution
+            .clawed_back_amount
+            .checked_add(remaining)
+            .ok_or(DistributorError::Overflow)?;
+
+        msg!("Dust swept: {} tokens to collector", remaining);
 
         Ok(())
     }
-}
 
-// ============================================================================
-// Accounts
-// ============================================================================
+    /// Clawback remaining funds to the clawback destination
+    ///
+    /// Returns unclaimed tokens to the token account fixed at
+    /// initialization. Typically used after claim period expires.
+    ///
+    /// `amount` allows clawing back in tranches; `u64::MAX` drains the vault.
+    pub fn clawback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Clawback<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+        let vault_balance = ctx.accounts.vault.amount;
+        let amount = if amount == u64::MAX {
+            vault_balance
+        } else {
+            amount
+        };
+
+        require!(
+            amount <= vault_balance,
+            DistributorError::InsufficientVaultBalance
+        );
+
+        transfer_signed_by_distribution(
+            &ctx.accounts.distribution,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.clawback_destination.to_account_info(),
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        let destination = ctx.accounts.clawback_destination.key();
+        drop(distribution);
+        let mut distribution = ctx.accounts.distribution.load_mut()?;
+        distribution.clawed_back_amount = distribution
+            .clawed_back_amount
+            .checked_add(amount)
+            .ok_or(DistributorError::Overflow)?;
+
+        msg!(
+            "Clawback: {} tokens returned to {}, {} remaining",
+            amount,
+            destination,
+            vault_balance - amount
+        );
+
+        Ok(())
+    }
+
+    /// Check the distribution's accounting invariants
+    ///
+    /// Permissionless and read-only. Emits a `TotalsVerified` event with the
+    /// observed figures and fails with `InvariantViolated` if any of these
+    /// does not hold:
+    ///
+    /// - `claimed_amount + clawed_back_amount + vault.amount >= total_amount`
+    /// - `claimed_amount <= total_amount`
+    /// - `num_claimed <= num_recipients`
+    /// - `escrowed_amount <= claimed_amount`
+    pub fn verify_totals(ctx: Context<VerifyTotals>) -> Result<()> {
+        let distribution = ctx.accounts.distribution.load()?;
+        let vault_amount = ctx.accounts.vault.amount;
+
+        let accounted = (distribution.claimed_amount as u128)
+            + (distribution.clawed_back_amount as u128)
+            + (vault_amount as u128);
+
+        let ok = accounted >= distribution.total_amount as u128
+            && distribution.claimed_amount <= distribution.total_amount
+            && distribution.num_claimed <= distribution.num_recipients
+            && distribution.escrowed_amount <= distribution.claimed_amount;
+
+        emit!(TotalsVerified {
+            distribution: ctx.accounts.distribution.key(),
+            total_amount: distribution.total_amount,
+            claimed_amount: distribution.claimed_amount,
+            clawed_back_amount: distribution.clawed_back_amount,
+            escrowed_amount: distribution.escrowed_amount,
+            vault_amount,
+            num_recipients: distribution.num_recipients,
+            num_claimed: distribution.num_claimed,
+            ok,
+        });
+
+        require!(ok, DistributorError::InvariantViolated);
+
+        Ok(())
+    }
+
+    /// Check whether a claim would succeed, without claiming
+    ///
+    /// Permissionless and read-only: runs the same checks as `claim` (pause,
+    /// recipient signature, Merkle proof) plus the claim status, writes
+    /// nothing and never fails on an invalid claim.
+    /// The outcome is returned as return data, so frontends can run it with
+    /// `simulateTransaction` before paying for a claim record.
+    pub fn check_claim(
+        ctx: Context<CheckClaim>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<ClaimCheck> {
+        let distribution = ctx.accounts.distribution.load()?;
+
+        let checks = ClaimChecks::run(
+            &distribution,
+            &ctx.accounts.recipient,
+            index,
+            amount,
+            &proof,
+        );
+        let claimed = !ctx.accounts.claim_record.data_is_empty();
+
+        Ok(ClaimCheck {
+            proof_valid: checks.proof_valid,
+            index_in_range: checks.index_in_range,
+            claimed,
+            paused: checks.paused,
+            recipient_signed: checks.recipient_signed,
+            claimable: checks.passed() && !claimed,
+        })
+    }
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = 8 + GlobalConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::MerkleDistributor>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key())
+            @ DistributorError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ DistributorError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(distribution_id: [u8; 32])]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<Distribution>(),
+        seeds = [b"distribution", distribution_id.as_ref()],
+        bump
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    /// The token mint for this distribution (SPL Token or Token-2022)
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = distribution,
+        token::token_program = token_program,
+        seeds = [b"vault", distribution_id.as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Global registry, created by the first initialize
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + Registry::INIT_SPACE,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Account<'info, Registry>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RegistryEntry::INIT_SPACE,
+        seeds = [b"registry_entry", registry.count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub registry_entry: Account<'info, RegistryEntry>,
+
+    /// Global config PDA; may not be initialized yet
+    /// CHECK: Address checked by seeds, contents read by `load_config`
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperator<'info> {
+    #[account(
+        mut,
+        has_one = authority @ DistributorError::Unauthorized
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    pub authority: Signer<'info>,
+
+    /// Global config PDA; may not be initialized yet
+    /// CHECK: Address checked by seeds, contents read by `load_config`
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64, amount: u64)]
+pub struct ProcessClaim<'info> {
+    #[account(
+        mut,
+        has_one = vault @ DistributorError::InvalidVault
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimRecord::INIT_SPACE,
+        seeds = [
+            b"claim",
+            distribution.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim_record: Account<'info, ClaimRecord>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The recipient wallet
+    /// CHECK: Verified via Merkle proof; must also sign when the distribution
+    /// requires recipient signatures
+    pub recipient: UncheckedAccount<'info>,
+
+    /// The recipient's token account
+    #[account(
+        mut,
+        token::mint = distribution.load()?.mint,
+        token::authority = recipient
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Anyone can submit claims (relayer pattern)
+    /// Security is provided by the Merkle proof - tokens always go to the
+    /// verified recipient regardless of who submits the transaction.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The distribution operator, receives the claim fee
+    /// CHECK: Address checked against the distribution
+    #[account(
+        mut,
+        address = distribution.load()?.operator @ DistributorError::InvalidOperator
+    )]
+    pub operator: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// Required only when the claim carries a memo
+    pub memo_program: Option<Program<'info, Memo>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBatch<'info> {
+    #[account(
+        mut,
+        has_one = vault @ DistributorError::InvalidVault
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Pays rent for the claim records
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The distribution operator, receives the claim fees
+    /// CHECK: Address checked against the distribution
+    #[account(
+        mut,
+        address = distribution.load()?.operator @ DistributorError::InvalidOperator
+    )]
+    pub operator: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64, amount: u64, proof: Vec<[u8; 32]>, referrer: Pubkey)]
+pub struct ClaimWithReferral<'info> {
+    #[account(
+        mut,
+        has_one = vault @ DistributorError::InvalidVault,
+        has_one = bonus_vault @ DistributorError::InvalidVault
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimRecord::INIT_SPACE,
+        seeds = [
+            b"claim",
+            distribution.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim_record: Account<'info, ClaimRecord>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub bonus_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The recipient wallet
+    /// CHECK: Verified via Merkle proof
+    pub recipient: UncheckedAccount<'info>,
+
+    /// The recipient's token account
+    #[account(
+        mut,
+        token::mint = distribution.load()?.mint,
+        token::authority = recipient
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Running bonus total for this referrer
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ReferrerRecord::INIT_SPACE,
+        seeds = [
+            b"referrer",
+            distribution.key().as_ref(),
+            referrer.as_ref()
+        ],
+        bump
+    )]
+    pub referrer_record: Account<'info, ReferrerRecord>,
+
+    /// The referrer's token account
+    #[account(
+        mut,
+        token::mint = distribution.load()?.mint,
+        token::authority = referrer
+    )]
+    pub referrer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The distribution operator, receives the claim fee
+    /// CHECK: Address checked against the distribution
+    #[account(
+        mut,
+        address = distribution.load()?.operator @ DistributorError::InvalidOperator
+    )]
+    pub operator: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ DistributorError::Unauthorized
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = mint,
+        token::authority = distribution,
+        token::token_program = token_program,
+        seeds = [b"bonus_vault", distribution.load()?.distribution_id.as_ref()],
+        bump
+    )]
+    pub bonus_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct ClaimToEscrow<'info> {
+    #[account(
+        mut,
+        has_one = vault @ DistributorError::InvalidVault
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ClaimRecord::INIT_SPACE,
+        seeds = [
+            b"claim",
+            distribution.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim_record: Account<'info, ClaimRecord>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The recipient wallet
+    /// CHECK: Verified via Merkle proof
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Escrow token account for this recipient, owned by the distribution
+    #[account(
+        init_if_needed,
+        payer = payer,
+        token::mint = mint,
+        token::authority = distribution,
+        token::token_program = token_program,
+        seeds = [
+            b"escrow",
+            distribution.key().as_ref(),
+            recipient.key().as_ref()
+        ],
+        bump
+    )]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The distribution operator, receives the claim fee
+    /// CHECK: Address checked against the distribution
+    #[account(
+        mut,
+        address = distribution.load()?.operator @ DistributorError::InvalidOperator
+    )]
+    pub operator: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct RenounceClaim<'info> {
+    #[account(mut)]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(
+        init,
+        payer = recipient,
+        space = 8 + ClaimRecord::INIT_SPACE,
+        seeds = [
+            b"claim",
+            distribution.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub claim_record: Account<'info, ClaimRecord>,
+
+    /// The recipient refusing the allocation
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
 
 #[derive(Accounts)]
-#[instruction(distribution_id: [u8; 32])]
-pub struct Initialize<'info> {
+#[instruction(index: u64)]
+pub struct ReleaseEscrow<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub distribution: AccountLoader<'info, Distribution>,
 
+    /// Claim record of the escrowed claim
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Distribution::INIT_SPACE,
-        seeds = [b"distribution", distribution_id.as_ref()],
-        bump
+        mut,
+        seeds = [
+            b"claim",
+            distribution.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump = claim_record.bump,
+        has_one = recipient @ DistributorError::InvalidClaimRecord
     )]
-    pub distribution: Account<'info, Distribution>,
-
-    /// The token mint for this distribution
-    pub mint: Account<'info, token::Mint>,
+    pub claim_record: Account<'info, ClaimRecord>,
 
     #[account(
-        init,
-        payer = authority,
-        token::mint = mint,
-        token::authority = distribution,
-        seeds = [b"vault", distribution_id.as_ref()],
+        mut,
+        seeds = [
+            b"escrow",
+            distribution.key().as_ref(),
+            recipient.key().as_ref()
+        ],
         bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
-#[derive(Accounts)]
-pub struct SetOperator<'info> {
+    /// The recipient wallet
+    /// CHECK: Only used to derive the escrow and check token account ownership
+    pub recipient: UncheckedAccount<'info>,
+
+    /// The recipient's token account (must be usable, i.e. not frozen)
     #[account(
         mut,
-        has_one = authority @ DistributorError::Unauthorized
+        token::mint = distribution.load()?.mint,
+        token::authority = recipient
     )]
-    pub distribution: Account<'info, Distribution>,
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-#[instruction(index: u64, amount: u64)]
-pub struct ProcessClaim<'info> {
-    #[account(
-        mut,
-        has_one = vault @ DistributorError::InvalidVault
-    )]
-    pub distribution: Account<'info, Distribution>,
+pub struct VerifyTotals<'info> {
+    #[account(has_one = vault @ DistributorError::InvalidVault)]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u64)]
+pub struct CheckClaim<'info> {
+    pub distribution: AccountLoader<'info, Distribution>,
 
+    /// Claim record PDA; exists once the leaf has been claimed
+    /// CHECK: Only inspected for existence, address checked by seeds
     #[account(
-        init,
-        payer = payer,
-        space = 8 + ClaimRecord::INIT_SPACE,
         seeds = [
             b"claim",
             distribution.key().as_ref(),
@@ -269,44 +1585,87 @@ pub struct ProcessClaim<'info> {
         ],
         bump
     )]
-    pub claim_record: Account<'info, ClaimRecord>,
-
-    #[account(mut)]
-    pub vault: Account<'info, TokenAccount>,
+    pub claim_record: UncheckedAccount<'info>,
 
     /// The recipient wallet
-    /// CHECK: Verified via Merkle proof
+    /// CHECK: Only used to compute the leaf and check for a signature
     pub recipient: UncheckedAccount<'info>,
+}
 
-    /// The recipient's token account
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
     #[account(
         mut,
-        token::mint = distribution.mint,
-        token::authority = recipient
+        has_one = authority @ DistributorError::Unauthorized
     )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    pub distribution: AccountLoader<'info, Distribution>,
 
-    /// Anyone can submit claims (relayer pattern)
-    /// Security is provided by the Merkle proof - tokens always go to the
-    /// verified recipient regardless of who submits the transaction.
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMetadata<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ DistributorError::Unauthorized)]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + DistributionMetadata::INIT_SPACE,
+        seeds = [b"metadata", distribution.key().as_ref()],
+        bump
+    )]
+    pub metadata: Account<'info, DistributionMetadata>,
 
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminAction<'info> {
+pub struct UpdateMetadata<'info> {
+    #[account(has_one = authority @ DistributorError::Unauthorized)]
+    pub distribution: AccountLoader<'info, Distribution>,
+
     #[account(
         mut,
-        has_one = authority @ DistributorError::Unauthorized
+        seeds = [b"metadata", distribution.key().as_ref()],
+        bump = metadata.bump
     )]
-    pub distribution: Account<'info, Distribution>,
+    pub metadata: Account<'info, DistributionMetadata>,
 
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(
+        mut,
+        has_one = operator @ DistributorError::Unauthorized,
+        has_one = vault @ DistributorError::InvalidVault
+    )]
+    pub distribution: AccountLoader<'info, Distribution>,
+
+    #[account(mut)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The designated dust collector token account
+    #[account(
+        mut,
+        address = distribution.load()?.dust_collector @ DistributorError::InvalidDustCollector,
+        token::mint = distribution.load()?.mint
+    )]
+    pub dust_collector: InterfaceAccount<'info, TokenAccount>,
+
+    pub operator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
 #[derive(Accounts)]
 pub struct Clawback<'info> {
     #[account(
@@ -314,29 +1673,38 @@ pub struct Clawback<'info> {
         has_one = authority @ DistributorError::Unauthorized,
         has_one = vault @ DistributorError::InvalidVault
     )]
-    pub distribution: Account<'info, Distribution>,
+    pub distribution: AccountLoader<'info, Distribution>,
 
     #[account(mut)]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = distribution.load()?.mint @ DistributorError::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
+    /// The token account fixed as clawback destination at initialization
     #[account(
         mut,
-        token::mint = distribution.mint,
-        token::authority = authority
+        address = distribution.load()?.clawback_destination @ DistributorError::InvalidClawbackDestination,
+        token::mint = distribution.load()?.mint
     )]
-    pub authority_token_account: Account<'info, TokenAccount>,
+    pub clawback_destination: InterfaceAccount<'info, TokenAccount>,
 
     pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 // ============================================================================
 // State
 // ============================================================================
 
-#[account]
-#[derive(InitSpace)]
+/// Distribution state
+///
+/// Zero-copy so claims read it in place instead of Borsh-deserializing it
+/// on every instruction. Fields are ordered (largest alignment first) so
+/// the `repr(C)` layout has no implicit padding; flags and enums are stored
+/// as `u8` and read through the accessors below.
+#[account(zero_copy)]
 pub struct Distribution {
     /// The authority that can manage this distribution
     pub authority: Pubkey,
@@ -358,12 +1726,190 @@ pub struct Distribution {
     pub num_recipients: u64,
     /// Number of claims processed
     pub num_claimed: u64,
-    /// Emergency pause flag
-    pub paused: bool,
+    /// Token account that receives clawed-back funds (immutable)
+    pub clawback_destination: Pubkey,
+    /// Token account that receives swept dust
+    pub dust_collector: Pubkey,
+    /// Token account funding referral bonuses (default until enabled)
+    pub bonus_vault: Pubkey,
+    /// Maximum vault balance the operator may sweep as dust (0 = disabled)
+    pub dust_threshold: u64,
+    /// Tokens currently parked in recipient escrows
+    pub escrowed_amount: u64,
+    /// Tokens returned via clawback or dust sweeps
+    pub clawed_back_amount: u64,
+    /// Tokens refused by recipients (left in the vault for clawback)
+    pub renounced_amount: u64,
+    /// Lamports paid to the operator by non-operator claim payers (0 = free)
+    pub claim_fee_lamports: u64,
+    /// Position of this distribution in the global registry
+    pub registry_index: u64,
+    /// Maximum cumulative bonus per referrer
+    pub referral_cap: u64,
+    /// Total referral bonuses paid
+    pub referral_paid_amount: u64,
+    /// Referral bonus in basis points of the claim (0 = disabled)
+    pub referral_bonus_bps: u16,
+    /// Claims require the recipient to co-sign (bool)
+    pub require_recipient_signature: u8,
+    /// Leaf/node hashing scheme the Merkle root was built with (`HashScheme`)
+    pub hash_scheme: u8,
+    /// Hash function the Merkle root was built with (`HashKind`)
+    pub hash_kind: u8,
+    /// How proof siblings are ordered when hashing (`ProofMode`)
+    pub proof_mode: u8,
+    /// Emergency pause flag (bool)
+    pub paused: u8,
     /// PDA bump
     pub bump: u8,
     /// Vault PDA bump
     pub vault_bump: u8,
+    /// Pads the account to a multiple of 8 bytes
+    pub _padding: [u8; 7],
+}
+
+impl Distribution {
+    pub fn require_recipient_signature(&self) -> bool {
+        self.require_recipient_signature != 0
+    }
+
+    pub fn hash_scheme(&self) -> HashScheme {
+        match self.hash_scheme {
+            1 => HashScheme::V2,
+            _ => HashScheme::V1,
+        }
+    }
+
+    pub fn hash_kind(&self) -> HashKind {
+        match self.hash_kind {
+            1 => HashKind::Sha256,
+            _ => HashKind::Keccak,
+        }
+    }
+
+    pub fn proof_mode(&self) -> ProofMode {
+        match self.proof_mode {
+            1 => ProofMode::Positional,
+            _ => ProofMode::Sorted,
+        }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = u8::from(paused);
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferrerRecord {
+    /// The distribution this record belongs to
+    pub distribution: Pubkey,
+    /// Referrer wallet
+    pub referrer: Pubkey,
+    /// Cumulative bonus paid to the referrer
+    pub total_bonus: u64,
+    /// Number of referred claims
+    pub num_referrals: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalConfig {
+    /// Protocol admin allowed to update this config
+    pub admin: Pubkey,
+    /// Claim fee applied to new distributions
+    pub default_claim_fee_lamports: u64,
+    /// Mints distributions may be created for (empty = any)
+    #[max_len(MAX_ALLOWED_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,
+    /// Wallets that may be set as operator (empty = any)
+    #[max_len(MAX_ALLOWED_RELAYERS)]
+    pub allowed_relayers: Vec<Pubkey>,
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// Arguments of `initialize_config` / `update_config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigParams {
+    pub admin: Pubkey,
+    pub default_claim_fee_lamports: u64,
+    pub allowed_mints: Vec<Pubkey>,
+    pub allowed_relayers: Vec<Pubkey>,
+}
+
+/// One claim of a `claim_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchClaim {
+    pub index: u64,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Registry {
+    /// Number of distributions ever initialized
+    pub count: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct RegistryEntry {
+    /// Position in the registry
+    pub index: u64,
+    /// The registered distribution
+    pub distribution: Pubkey,
+    /// Authority that initialized the distribution
+    pub authority: Pubkey,
+    /// The distribution mint
+    pub mint: Pubkey,
+    /// Initialization timestamp
+    pub created_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// Merkle node hashing scheme
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum HashScheme {
+    /// Unprefixed leaves and sorted-pair internal nodes (legacy)
+    V1,
+    /// Leaves prefixed with `0x00`, sorted-pair internal nodes prefixed with
+    /// `0x01`, so an internal node can never be presented as a leaf
+    V2,
+}
+
+/// Hash function used for leaves and internal nodes
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum HashKind {
+    /// Keccak-256 (default)
+    #[default]
+    Keccak,
+    /// SHA-256
+    Sha256,
+}
+
+/// Sibling ordering used when verifying proofs
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace,
+)]
+pub enum ProofMode {
+    /// Siblings are sorted before hashing (default)
+    #[default]
+    Sorted,
+    /// Bit `i` of the claim index gives the node's position at level `i`
+    /// (0 = left, 1 = right)
+    Positional,
 }
 
 #[account]
@@ -379,10 +1925,97 @@ pub struct ClaimRecord {
     pub amount: u64,
     /// Timestamp of claim
     pub claimed_at: i64,
+    /// External reference attached to the claim (empty if none)
+    #[max_len(MAX_MEMO_LEN)]
+    pub memo: Vec<u8>,
+    /// Tokens were parked in the recipient's escrow instead of paid out
+    pub escrowed: bool,
+    /// The recipient refused the allocation; nothing was transferred
+    pub renounced: bool,
+    /// Tokens of this claim still held in the recipient's escrow (zero
+    /// once released)
+    pub escrowed_amount: u64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DistributionMetadata {
+    /// The distribution this metadata describes
+    pub distribution: Pubkey,
+    /// Human-readable name
+    #[max_len(MAX_NAME_LEN)]
+    pub name: String,
+    /// Short symbol shown in explorers
+    #[max_len(MAX_SYMBOL_LEN)]
+    pub symbol: String,
+    /// URI of the published tree
+    #[max_len(MAX_URI_LEN)]
+    pub uri: String,
+    /// Reward epoch this distribution covers
+    pub epoch: u64,
+    /// Where the full recipient file is published, e.g. `ipfs://<cid>`
+    /// (empty if unset)
+    #[max_len(MAX_URI_LEN)]
+    pub tree_uri: String,
+    /// SHA-256 of the published recipient file (zero if unset)
+    pub tree_file_hash: [u8; 32],
     /// PDA bump
     pub bump: u8,
 }
 
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Emitted by `verify_totals`
+#[event]
+pub struct TotalsVerified {
+    pub distribution: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub clawed_back_amount: u64,
+    pub escrowed_amount: u64,
+    pub vault_amount: u64,
+    pub num_recipients: u64,
+    pub num_claimed: u64,
+    /// Whether all invariants held
+    pub ok: bool,
+}
+
+// ============================================================================
+// Return Data
+// ============================================================================
+
+/// Return data of `claim`, readable by CPI callers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimResult {
+    /// Recipient wallet that received the tokens
+    pub recipient: Pubkey,
+    /// Index in the Merkle tree
+    pub index: u64,
+    /// Amount transferred
+    pub amount: u64,
+}
+
+/// Return data of `check_claim`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimCheck {
+    /// The proof verifies against the distribution's root
+    pub proof_valid: bool,
+    /// `index < num_recipients`
+    pub index_in_range: bool,
+    /// The leaf has already been claimed (or escrowed / renounced)
+    pub claimed: bool,
+    /// The distribution is paused
+    pub paused: bool,
+    /// The recipient signed, or the distribution does not require it
+    pub recipient_signed: bool,
+    /// A `claim` with these arguments would pass verification
+    pub claimable: bool,
+}
+
 // ============================================================================
 // Errors
 // ============================================================================
@@ -401,6 +2034,307 @@ pub enum DistributorError {
     Overflow,
     #[msg("Proof too long")]
     ProofTooLong,
+    #[msg("Not all recipients have claimed")]
+    ClaimsOutstanding,
+    #[msg("Vault balance exceeds dust threshold")]
+    DustAboveThreshold,
+    #[msg("Invalid dust collector")]
+    InvalidDustCollector,
+    #[msg("Invalid clawback destination")]
+    InvalidClawbackDestination,
+    #[msg("Recipient signature required")]
+    RecipientSignatureRequired,
+    #[msg("Metadata field too long")]
+    MetadataTooLong,
+    #[msg("Memo too long")]
+    MemoTooLong,
+    #[msg("Memo program required for claims with a memo")]
+    MissingMemoProgram,
+    #[msg("Invalid mint")]
+    InvalidMint,
+    #[msg("Escrow is empty")]
+    EscrowEmpty,
+    #[msg("Invalid or already used claim record")]
+    InvalidClaimRecord,
+    #[msg("Insufficient vault balance")]
+    InsufficientVaultBalance,
+    #[msg("Accounting invariant violated")]
+    InvariantViolated,
+    #[msg("Invalid operator")]
+    InvalidOperator,
+    #[msg("Mint not allowed by global config")]
+    MintNotAllowed,
+    #[msg("Relayer not allowed by global config")]
+    RelayerNotAllowed,
+    #[msg("Config allow-list too long")]
+    ConfigListTooLong,
+    #[msg("Referrals are not enabled")]
+    ReferralsDisabled,
+    #[msg("Recipient cannot refer themselves")]
+    SelfReferral,
+    #[msg("Basis points exceed 10000")]
+    InvalidBps,
+    #[msg("Not supported for this distribution's proof mode")]
+    UnsupportedProofMode,
+    #[msg("Invalid batch")]
+    InvalidBatch,
+    #[msg("Invalid recipient token account")]
+    InvalidRecipientTokenAccount,
+    #[msg("Not supported for mints with a transfer hook")]
+    TransferHookUnsupported,
+    #[msg("Claim index out of range")]
+    IndexOutOfRange,
+    #[msg("Claims have already started")]
+    ClaimsStarted,
+    #[msg("Distribution id does not match keccak(mint || merkle_root || epoch)")]
+    DistributionIdMismatch,
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Deterministic distribution id used by strict-mode `initialize`
+///
+/// `keccak(mint || merkle_root || epoch)`, with `epoch` as u64 LE.
+pub fn derive_distribution_id(mint: &Pubkey, merkle_root: &[u8; 32], epoch: u64) -> [u8; 32] {
+    keccak::hashv(&[mint.as_ref(), merkle_root.as_ref(), &epoch.to_le_bytes()]).to_bytes()
+}
+
+/// Read the global config, or `None` before `initialize_config` has run
+///
+/// The config address is fixed by seeds, so a caller cannot skip the
+/// allow-lists by passing another account. Distributions created before
+/// the config exists behave as before it was introduced: no allow-lists
+/// and no default claim fee.
+fn load_config(config: &AccountInfo) -> Result<Option<GlobalConfig>> {
+    if config.owner != &crate::ID || config.data_is_empty() {
+        return Ok(None);
+    }
+    let data = config.try_borrow_data()?;
+    Ok(Some(GlobalConfig::try_deserialize(&mut &data[..])?))
+}
+
+/// Check config allow-lists fit in the allocated account space
+fn validate_config(params: &ConfigParams) -> Result<()> {
+    require!(
+        params.allowed_mints.len() <= MAX_ALLOWED_MINTS
+            && params.allowed_relayers.len() <= MAX_ALLOWED_RELAYERS,
+        DistributorError::ConfigListTooLong
+    );
+    Ok(())
+}
+
+/// Check metadata strings fit in the allocated account space
+fn validate_metadata(name: &str, symbol: &str, uri: &str) -> Result<()> {
+    require!(
+        name.len() <= MAX_NAME_LEN && symbol.len() <= MAX_SYMBOL_LEN && uri.len() <= MAX_URI_LEN,
+        DistributorError::MetadataTooLong
+    );
+    Ok(())
+}
+
+/// Charge the claim fee when the payer is not the operator
+///
+/// Self-claims cover infrastructure costs; the operator never pays itself.
+fn charge_claim_fee<'info>(
+    distribution: &Distribution,
+    payer: &Signer<'info>,
+    operator: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    num_claims: u64,
+) -> Result<()> {
+    if distribution.claim_fee_lamports == 0 || payer.key() == distribution.operator {
+        return Ok(());
+    }
+
+    let fee = distribution
+        .claim_fee_lamports
+        .checked_mul(num_claims)
+        .ok_or(DistributorError::Overflow)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: operator.to_account_info(),
+            },
+        ),
+        fee,
+    )
+}
+
+/// Create a claim record PDA passed as a remaining account
+///
+/// Fails unless the account is the expected, still-uninitialized PDA, so
+/// each index can only be claimed once. Returns the PDA bump.
+fn create_claim_record<'info>(
+    distribution: &Pubkey,
+    index: u64,
+    claim_info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<u8> {
+    let index_bytes = index.to_le_bytes();
+    let (address, bump) = Pubkey::find_program_address(
+        &[b"claim", distribution.as_ref(), index_bytes.as_ref()],
+        &crate::ID,
+    );
+
+    require_keys_eq!(
+        claim_info.key(),
+        address,
+        DistributorError::InvalidClaimRecord
+    );
+    require!(
+        claim_info.data_is_empty() && claim_info.owner == &system_program::ID,
+        DistributorError::InvalidClaimRecord
+    );
+
+    let space = 8 + ClaimRecord::INIT_SPACE;
+    let seeds: &[&[u8]] = &[
+        b"claim",
+        distribution.as_ref(),
+        index_bytes.as_ref(),
+        &[bump],
+    ];
+
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: payer.to_account_info(),
+                to: claim_info.clone(),
+            },
+            &[seeds],
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &crate::ID,
+    )?;
+
+    Ok(bump)
+}
+
+/// Outcome of each check `claim` applies before paying out
+struct ClaimChecks {
+    paused: bool,
+    index_in_range: bool,
+    recipient_signed: bool,
+    proof_valid: bool,
+}
+
+impl ClaimChecks {
+    fn run(
+        distribution: &Distribution,
+        recipient: &AccountInfo,
+        index: u64,
+        amount: u64,
+        proof: &[[u8; 32]],
+    ) -> Self {
+        let leaf = compute_leaf(
+            distribution.hash_kind(),
+            distribution.hash_scheme(),
+            &distribution.distribution_id,
+            recipient.key,
+            amount,
+        );
+
+        ClaimChecks {
+            paused: distribution.paused(),
+            // Claim PDAs only exist for real leaves
+            index_in_range: index < distribution.num_recipients,
+            // Permissioned distributions only pay out with the recipient's consent
+            recipient_signed: !distribution.require_recipient_signature() || recipient.is_signer,
+            proof_valid: verify_proof(
+                distribution.hash_kind(),
+                distribution.hash_scheme(),
+                distribution.proof_mode(),
+                proof,
+                &distribution.merkle_root,
+                leaf,
+                index,
+            ),
+        }
+    }
+
+    fn passed(&self) -> bool {
+        !self.paused && self.index_in_range && self.recipient_signed && self.proof_valid
+    }
+}
+
+/// Verify a claim for `recipient` against the distribution's Merkle root
+///
+/// `check_claim` reports the same checks without failing.
+fn verify_claim(
+    distribution: &Distribution,
+    recipient: &AccountInfo,
+    index: u64,
+    amount: u64,
+    proof: &[[u8; 32]],
+) -> Result<()> {
+    let checks = ClaimChecks::run(distribution, recipient, index, amount, proof);
+
+    require!(!checks.paused, DistributorError::Paused);
+    require!(checks.index_in_range, DistributorError::IndexOutOfRange);
+    require!(
+        checks.recipient_signed,
+        DistributorError::RecipientSignatureRequired
+    );
+    require!(checks.proof_valid, DistributorError::InvalidProof);
+
+    Ok(())
+}
+
+// ============================================================================
+// Token Transfers
+// ============================================================================
+
+/// Transfer tokens out of a distribution-owned account (vault or escrow)
+///
+/// Uses `transfer_checked` so Token-2022 mints are supported, and resolves
+/// transfer-hook accounts from `additional_accounts` when the mint has a
+/// transfer-hook extension.
+fn transfer_signed_by_distribution<'info>(
+    distribution: &AccountLoader<'info, Distribution>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    additional_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let (distribution_id, bump) = {
+        let distribution = distribution.load()?;
+        (distribution.distribution_id, distribution.bump)
+    };
+    let seeds: &[&[u8]] = &[b"distribution", distribution_id.as_ref(), &[bump]];
+
+    spl_token_2022::onchain::invoke_transfer_checked(
+        &token_program.key(),
+        from.to_account_info(),
+        mint.to_account_info(),
+        to,
+        distribution.to_account_info(),
+        additional_accounts,
+        amount,
+        mint.decimals,
+        &[seeds],
+    )?;
+
+    Ok(())
+}
+
+/// Whether a Token-2022 mint has a transfer-hook program set
+fn has_transfer_hook(mint: &InterfaceAccount<Mint>) -> Result<bool> {
+    let mint_info = mint.to_account_info();
+    if mint_info.owner != &spl_token_2022::ID {
+        return Ok(false);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(transfer_hook::get_program_id(&mint_state).is_some())
 }
 
 // ============================================================================
@@ -410,45 +2344,88 @@ pub enum DistributorError {
 /// Compute leaf hash using domain separation
 /// Must match the off-chain builder exactly
 pub fn compute_leaf(
+    kind: HashKind,
+    scheme: HashScheme,
     distribution_id: &[u8; 32],
     recipient: &Pubkey,
     amount: u64,
 ) -> [u8; 32] {
-    let mut data = Vec::with_capacity(DOMAIN_SEPARATOR.len() + 32 + 32 + 8);
-    data.extend_from_slice(DOMAIN_SEPARATOR);
-    data.extend_from_slice(distribution_id);
-    data.extend_from_slice(recipient.as_ref());
-    data.extend_from_slice(&amount.to_le_bytes());
-    
-    keccak::hash(&data).to_bytes()
+    merkle_core::compute_leaf(
+        kind.into(),
+        scheme.into(),
+        distribution_id,
+        &recipient.to_bytes(),
+        amount,
+    )
 }
 
 /// Verify a Merkle proof
+///
+/// `index` is only used in `ProofMode::Positional`, where it must address a
+/// leaf within the proof's depth so one leaf cannot be claimed under
+/// several indices.
 pub fn verify_proof(
+    kind: HashKind,
+    scheme: HashScheme,
+    mode: ProofMode,
     proof: &[[u8; 32]],
     root: &[u8; 32],
     leaf: [u8; 32],
+    index: u64,
 ) -> bool {
-    if proof.len() > MAX_PROOF_LEN {
-        return false;
-    }
+    merkle_core::verify_proof(
+        kind.into(),
+        scheme.into(),
+        mode.into(),
+        proof,
+        root,
+        leaf,
+        index,
+    )
+}
+
+/// Verify a Merkle multiproof for several leaves of a sorted-pair tree
+///
+/// See `merkle_core::verify_multiproof` for the `proof_flags` layout.
+pub fn verify_multiproof(
+    kind: HashKind,
+    scheme: HashScheme,
+    proof: &[[u8; 32]],
+    proof_flags: &[bool],
+    root: &[u8; 32],
+    leaves: &[[u8; 32]],
+) -> bool {
+    merkle_core::verify_multiproof(kind.into(), scheme.into(), proof, proof_flags, root, leaves)
+}
+
+/// Hash two nodes, sorting for determinism
+pub fn hash_pair(kind: HashKind, scheme: HashScheme, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    merkle_core::hash_pair(kind.into(), scheme.into(), a, b)
+}
 
-    let mut current = leaf;
-    
-    for sibling in proof {
-        current = hash_pair(&current, sibling);
+impl From<HashKind> for merkle_core::HashKind {
+    fn from(kind: HashKind) -> Self {
+        match kind {
+            HashKind::Keccak => merkle_core::HashKind::Keccak,
+            HashKind::Sha256 => merkle_core::HashKind::Sha256,
+        }
     }
+}
 
-    current == *root
+impl From<HashScheme> for merkle_core::HashScheme {
+    fn from(scheme: HashScheme) -> Self {
+        match scheme {
+            HashScheme::V1 => merkle_core::HashScheme::V1,
+            HashScheme::V2 => merkle_core::HashScheme::V2,
+        }
+    }
 }
 
-/// Hash two nodes, sorting for determinism
-fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
-    let (first, second) = if a <= b { (a, b) } else { (b, a) };
-    
-    let mut data = [0u8; 64];
-    data[..32].copy_from_slice(first);
-    data[32..].copy_from_slice(second);
-    
-    keccak::hash(&data).to_bytes()
+impl From<ProofMode> for merkle_core::ProofMode {
+    fn from(mode: ProofMode) -> Self {
+        match mode {
+            ProofMode::Sorted => merkle_core::ProofMode::Sorted,
+            ProofMode::Positional => merkle_core::ProofMode::Positional,
+        }
+    }
 }
@@ -0,0 +1,82 @@
+// src/alerts/notify.ts
+// Posts alerts to Slack and/or Discord incoming webhooks, at most once per key
+
+import { pool } from '../db';
+
+export type AlertSeverity = 'critical' | 'warning' | 'info';
+
+export type Alert = {
+  // Rule that fired, e.g. distribution_paused
+  rule: string;
+  // What the alert is about; one alert per (rule, key) per cooldown
+  key: string;
+  severity: AlertSeverity;
+  title: string;
+  detail?: string;
+};
+
+const SEVERITY_EMOJI: Record<AlertSeverity, string> = {
+  critical: '🚨',
+  warning: '⚠️',
+  info: 'ℹ️',
+};
+
+const REQUEST_TIMEOUT_MS = 10_000;
+
+function format(alert: Alert): string {
+  const env = process.env.ALERT_ENVIRONMENT ? ` [${process.env.ALERT_ENVIRONMENT}]` : '';
+  const detail = alert.detail ? `\n${alert.detail}` : '';
+  return `${SEVERITY_EMOJI[alert.severity]}${env} ${alert.title}${detail}`;
+}
+
+async function post(url: string, body: object): Promise<void> {
+  const res = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify(body),
+    signal: AbortSignal.timeout(REQUEST_TIMEOUT_MS),
+  });
+  if (!res.ok) {
+    throw new Error(`HTTP ${res.status} ${res.statusText}`);
+  }
+}
+
+/**
+ * Send `alert` unless it was already sent for its (rule, key)
+ *
+ * With `cooldownMinutes` the alert repeats once that long has passed since
+ * it was last sent (for conditions that persist); without it the alert is
+ * sent once (for events). The send is recorded in alert_log first, so
+ * concurrent processes never both send it. Returns whether it was sent.
+ */
+export async function sendAlert(alert: Alert, cooldownMinutes?: number): Promise<boolean> {
+  const { rowCount } = await pool.query(
+    `
+    INSERT INTO alert_log (rule, key, severity, message)
+    VALUES ($1, $2, $3, $4)
+    ON CONFLICT (rule, key) DO UPDATE
+    SET sent_at = NOW(), severity = EXCLUDED.severity, message = EXCLUDED.message
+    WHERE $5::int IS NOT NULL AND alert_log.sent_at <= NOW() - make_interval(mins => $5::int)
+    `,
+    [alert.rule, alert.key, alert.severity, format(alert), cooldownMinutes ?? null]
+  );
+  if ((rowCount ?? 0) === 0) return false;
+
+  const text = format(alert);
+  const log = alert.severity === 'info' ? console.log : console.error;
+  log(`[alert] ${text}`);
+
+  const targets: [string, string | undefined, object][] = [
+    ['Slack', process.env.SLACK_WEBHOOK_URL, { text }],
+    ['Discord', process.env.DISCORD_WEBHOOK_URL, { content: text }],
+  ];
+  for (const [name, url, body] of targets) {
+    if (!url) continue;
+    try {
+      await post(url, body);
+    } catch (error: any) {
+      console.error(`[alert] ${name} notification failed: ${error.message}`);
+    }
+  }
+  return true;
+}
@@ -0,0 +1,201 @@
+// src/alerts/rules.ts
+// Anomaly rules evaluated against the indexed state
+
+import { pool } from '../db';
+import { sendAlert } from './notify';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Events older than this (block time) are not alerted on, so backfills stay quiet
+const DEFAULT_EVENT_MAX_AGE_MINUTES = 60;
+
+const DEFAULT_CLAIM_FAILURE_WINDOW_MINUTES = 15;
+const DEFAULT_CLAIM_FAILURE_THRESHOLD = 10;
+
+// About a minute of slots
+const DEFAULT_INGESTION_LAG_SLOTS = 150;
+
+// Conditions that persist are re-sent this often while they last
+const DEFAULT_COOLDOWN_MINUTES = 60;
+
+function envNumber(name: string, fallback: number): number {
+  const value = process.env[name];
+  if (!value) return fallback;
+  const n = Number(value);
+  if (!Number.isFinite(n)) throw new Error(`Invalid ${name}: ${value}`);
+  return n;
+}
+
+const cooldownMinutes = () => envNumber('ALERT_COOLDOWN_MINUTES', DEFAULT_COOLDOWN_MINUTES);
+
+// ---------------------------
+// Rules
+// ---------------------------
+
+/**
+ * Distribution paused, clawback executed: once per finalized event
+ */
+async function checkAdminEvents(): Promise<number> {
+  const { rows } = await pool.query<{
+    deployment: string;
+    signature: string;
+    event_index: number;
+    kind: 'pause' | 'clawback';
+    distribution: string;
+    reward_id: string | null;
+    amount: string | null;
+    destination: string | null;
+  }>(
+    `
+    SELECT e.deployment, e.signature, e.event_index, e.kind, e.distribution, d.reward_id, e.amount::text, e.destination
+    FROM merkle_claim_events e
+    LEFT JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+    WHERE e.kind IN ('pause', 'clawback')
+      AND e.block_time >= NOW() - make_interval(mins => $1)
+    ORDER BY e.slot, e.event_index
+    `,
+    [envNumber('ALERT_EVENT_MAX_AGE_MINUTES', DEFAULT_EVENT_MAX_AGE_MINUTES)]
+  );
+
+  let sent = 0;
+  for (const row of rows) {
+    const name = row.reward_id ?? row.distribution;
+    const paused = row.kind === 'pause';
+    const ok = await sendAlert({
+      rule: paused ? 'distribution_paused' : 'clawback_executed',
+      key: `${row.deployment}:${row.signature}:${row.event_index}`,
+      severity: paused ? 'critical' : 'warning',
+      title: paused
+        ? `Distribution paused: ${name}`
+        : `Clawback executed on ${name}: ${row.amount} raw units to ${row.destination}`,
+      detail: `Distribution ${row.distribution} (${row.deployment}), tx ${row.signature}`,
+    });
+    if (ok) sent++;
+  }
+  return sent;
+}
+
+/**
+ * Claim failure spike: relayer claims that failed within the window
+ */
+async function checkClaimFailures(): Promise<number> {
+  const windowMinutes = envNumber('CLAIM_FAILURE_WINDOW_MINUTES', DEFAULT_CLAIM_FAILURE_WINDOW_MINUTES);
+  const threshold = envNumber('CLAIM_FAILURE_ALERT_THRESHOLD', DEFAULT_CLAIM_FAILURE_THRESHOLD);
+
+  const { rows } = await pool.query<{ distribution_id: string; failed: number; error: string | null }>(
+    `
+    SELECT distribution_id, COUNT(*)::int AS failed, MAX(error_message) AS error
+    FROM merkle_claims
+    WHERE status = 'failed'
+      AND last_attempt >= NOW() - make_interval(mins => $1)
+    GROUP BY distribution_id
+    ORDER BY failed DESC
+    `,
+    [windowMinutes]
+  );
+  const total = rows.reduce((sum, r) => sum + r.failed, 0);
+  if (total < threshold) return 0;
+
+  const ok = await sendAlert(
+    {
+      rule: 'claim_failure_spike',
+      key: 'relayer',
+      severity: 'critical',
+      title: `${total} claim(s) failed in the last ${windowMinutes} minutes`,
+      detail: rows.map((r) => `${r.distribution_id}: ${r.failed} (${r.error ?? 'no error recorded'})`).join('\n'),
+    },
+    cooldownMinutes()
+  );
+  return ok ? 1 : 0;
+}
+
+/**
+ * Open reconciliation divergences (merkle_reconciliation_divergences)
+ *
+ * A vault below its unclaimed liability is alerted on as soon as it is
+ * recorded: both sides are on-chain, so ingestion lag cannot cause it.
+ * Indexed-vs-on-chain mismatches wait for the reconciler's grace period.
+ */
+async function checkReconciliation(): Promise<number> {
+  const { rows } = await pool.query<{
+    id: string;
+    deployment: string;
+    distribution: string;
+    field: string;
+    on_chain: string;
+    expected: string;
+    reward_id: string | null;
+  }>(
+    `
+    SELECT r.id, r.deployment, r.distribution, r.field, r.on_chain::text, r.expected::text, d.reward_id
+    FROM merkle_reconciliation_divergences r
+    LEFT JOIN merkle_distributions d ON d.deployment = r.deployment AND d.on_chain_address = r.distribution
+    WHERE r.resolved_at IS NULL
+      AND (r.field = 'vault_balance' OR r.alerted_at IS NOT NULL)
+    ORDER BY r.id
+    `
+  );
+
+  let sent = 0;
+  for (const row of rows) {
+    const name = row.reward_id ?? row.distribution;
+    const vault = row.field === 'vault_balance';
+    const ok = await sendAlert({
+      rule: vault ? 'vault_below_liability' : 'reconciliation_divergence',
+      key: row.id,
+      severity: vault ? 'critical' : 'warning',
+      title: vault
+        ? `Vault of ${name} holds ${row.on_chain}, below its unclaimed liability of ${row.expected}`
+        : `${name}: on-chain ${row.field} ${row.on_chain}, indexed ${row.expected}`,
+      detail: `Distribution ${row.distribution} (${row.deployment})`,
+    });
+    if (ok) sent++;
+  }
+  return sent;
+}
+
+// ---------------------------
+// Entry points
+// ---------------------------
+
+/**
+ * Evaluate every database-backed rule; returns the number of alerts sent
+ *
+ * A failing rule does not stop the others.
+ */
+export async function runAlertRules(): Promise<number> {
+  let sent = 0;
+  for (const [name, rule] of [
+    ['admin events', checkAdminEvents],
+    ['claim failures', checkClaimFailures],
+    ['reconciliation', checkReconciliation],
+  ] as const) {
+    try {
+      sent += await rule();
+    } catch (error: any) {
+      console.error(`[alert] ${name} rule failed: ${error.message}`);
+    }
+  }
+  return sent;
+}
+
+/**
+ * Ingestion lag rule, fed by the claim event runner's lag measurement
+ */
+export async function checkIngestionLag(deployment: string, source: string, lagSlots: number): Promise<void> {
+  const threshold = envNumber('INGESTION_LAG_ALERT_SLOTS', DEFAULT_INGESTION_LAG_SLOTS);
+  if (lagSlots <= threshold) return;
+
+  await sendAlert(
+    {
+      rule: 'ingestion_lag',
+      key: `${deployment}:${source}`,
+      severity: 'warning',
+      title: `Claim event ingestion of ${deployment} (${source}) is ${lagSlots} slots behind`,
+      detail: `Threshold: ${threshold} slots`,
+    },
+    cooldownMinutes()
+  );
+}
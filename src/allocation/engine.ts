@@ -0,0 +1,271 @@
+// src/allocation/engine.ts
+// Pro-rata reward allocation over a holder snapshot (pure, no I/O)
+
+/**
+ * One holder's stake in the snapshot (token units or lamports)
+ */
+export type HolderWeight = {
+  wallet: string;
+  weight: bigint;
+  // Snapshot balance in raw token units, checked against minBalance (defaults to weight)
+  balance?: bigint;
+};
+
+/**
+ * One output allocation (raw units of the reward mint)
+ */
+export type Allocation = {
+  wallet: string;
+  amount: bigint;
+  weight: bigint;
+};
+
+/**
+ * floor: every holder gets FLOOR(budget × weight / total); the rounding dust carries out
+ * largest-remainder: the leftover units go one each to the largest remainders, nothing carries out
+ * accrue: each holder's fractional remainder (and any payout below minPayout) is kept in
+ * the holder's accrual and added to its next allocation; carried-in dust backs the accruals
+ */
+export type RoundingMode = 'floor' | 'largest-remainder' | 'accrue';
+
+// Accruals are kept in 1e-12 raw units
+export const ACCRUAL_SCALE = 10n ** 12n;
+
+/**
+ * What happens to the share of holders below the minimum balance
+ *
+ * redistribute: split among the eligible holders
+ * remainder: set aside pro rata and carried out with the dust
+ */
+export type BelowMinBalanceMode = 'redistribute' | 'remainder';
+
+export interface AllocationConfig {
+  // Reward budget in raw units
+  budget: bigint;
+  // Dust carried in from the previous allocation of the same mint
+  carryIn?: bigint;
+  rounding?: RoundingMode;
+  // Wallets that never receive a share
+  exclude?: Iterable<string>;
+  // Holders with less weight are not eligible
+  minWeight?: bigint;
+  // Holders with a smaller snapshot balance are not eligible
+  minBalance?: bigint;
+  belowMinBalance?: BelowMinBalanceMode;
+  // Holders whose share would be smaller are dropped and the budget re-split among the rest
+  // (accrue: their share is accrued instead)
+  minPayout?: bigint;
+  // accrue: each wallet's accrual carried in, in ACCRUAL_SCALE units
+  accruals?: Map<string, bigint>;
+}
+
+export interface AllocationResult {
+  // Sorted by amount descending, then wallet (export-reward-csv order)
+  allocations: Allocation[];
+  effectiveBudget: bigint;
+  distributed: bigint;
+  // Includes the remainder pool
+  carryOut: bigint;
+  // Share of holders below minBalance, set aside in 'remainder' mode
+  remainderPool: bigint;
+  totalWeight: bigint;
+  excludedHolders: number;
+  // Excluded holders with their merged weight, in input order
+  excluded: HolderWeight[];
+  belowMinWeight: number;
+  belowMinBalance: number;
+  belowMinPayout: number;
+  // accrue: new accrual (ACCRUAL_SCALE units) of every eligible holder
+  accruals: Map<string, bigint>;
+}
+
+/**
+ * Split `budget + carryIn` across holders in proportion to their weight
+ *
+ * Conservation is checked before returning: distributed + carryOut always
+ * equals the effective budget, and no allocation is zero.
+ */
+export function allocateProRata(holders: HolderWeight[], config: AllocationConfig): AllocationResult {
+  const effectiveBudget = config.budget + (config.carryIn ?? 0n);
+  const rounding = config.rounding ?? 'floor';
+  const exclude = new Set(config.exclude ?? []);
+  const minWeight = config.minWeight ?? 0n;
+  const minBalance = config.minBalance ?? 0n;
+  const belowMinBalanceMode = config.belowMinBalance ?? 'redistribute';
+  const minPayout = config.minPayout ?? 0n;
+
+  if (effectiveBudget < 0n) {
+    throw new Error(`Negative budget: ${effectiveBudget}`);
+  }
+
+  // Merge duplicate wallets (a holder listed once per token account)
+  const weights = new Map<string, { weight: bigint; balance: bigint }>();
+  for (const holder of holders) {
+    if (holder.weight < 0n) {
+      throw new Error(`Negative weight for ${holder.wallet}`);
+    }
+    const merged = weights.get(holder.wallet) ?? { weight: 0n, balance: 0n };
+    merged.weight += holder.weight;
+    merged.balance += holder.balance ?? holder.weight;
+    weights.set(holder.wallet, merged);
+  }
+
+  let excludedHolders = 0;
+  let belowMinWeight = 0;
+  let belowMinBalance = 0;
+  let belowMinBalanceWeight = 0n;
+  const excluded: HolderWeight[] = [];
+  let eligible: HolderWeight[] = [];
+  for (const [wallet, { weight, balance }] of weights) {
+    if (exclude.has(wallet)) {
+      excludedHolders++;
+      excluded.push({ wallet, weight, balance });
+    } else if (balance < minBalance) {
+      belowMinBalance++;
+      belowMinBalanceWeight += weight;
+    } else if (weight === 0n || weight < minWeight) {
+      belowMinWeight++;
+    } else {
+      eligible.push({ wallet, weight });
+    }
+  }
+
+  // Accrued remainders are paid from the carried-in dust, so only the
+  // budget itself is split
+  const splitBase = rounding === 'accrue' ? config.budget : effectiveBudget;
+
+  // In remainder mode the holders below minBalance keep their pro-rata share
+  // out of the split; it carries out with the dust
+  let remainderPool = 0n;
+  if (belowMinBalanceMode === 'remainder' && belowMinBalanceWeight > 0n) {
+    const eligibleWeight = eligible.reduce((sum, h) => sum + h.weight, 0n);
+    remainderPool = (splitBase * belowMinBalanceWeight) / (eligibleWeight + belowMinBalanceWeight);
+  }
+  const splitBudget = splitBase - remainderPool;
+
+  let belowMinPayout = 0;
+  let allocations: Allocation[];
+  const accruals = new Map<string, bigint>();
+  if (rounding === 'accrue') {
+    ({ allocations, belowMinPayout } = accrue(eligible, splitBudget, config.accruals, minPayout, accruals));
+  } else {
+    // Dropping holders below minPayout raises everyone else's share, so repeat
+    // until every remaining payout clears it
+    allocations = split(eligible, splitBudget, rounding);
+    while (allocations.some((a) => a.amount < minPayout || a.amount === 0n)) {
+      const kept = new Set(
+        allocations.filter((a) => a.amount >= minPayout && a.amount > 0n).map((a) => a.wallet)
+      );
+      belowMinPayout += eligible.length - kept.size;
+      eligible = eligible.filter((h) => kept.has(h.wallet));
+      allocations = split(eligible, splitBudget, rounding);
+    }
+  }
+
+  allocations.sort((a, b) =>
+    a.amount === b.amount ? (a.wallet < b.wallet ? -1 : 1) : a.amount > b.amount ? -1 : 1
+  );
+
+  const totalWeight = eligible.reduce((sum, h) => sum + h.weight, 0n);
+  const distributed = allocations.reduce((sum, a) => sum + a.amount, 0n);
+  const carryOut = effectiveBudget - distributed;
+  if (
+    carryOut < remainderPool ||
+    (rounding === 'largest-remainder' && allocations.length > 0 && carryOut !== remainderPool)
+  ) {
+    throw new Error(
+      `Allocation not conserved: budget ${effectiveBudget}, distributed ${distributed}, carry ${carryOut}`
+    );
+  }
+
+  return {
+    allocations,
+    effectiveBudget,
+    distributed,
+    carryOut,
+    remainderPool,
+    totalWeight,
+    excludedHolders,
+    excluded,
+    belowMinWeight,
+    belowMinBalance,
+    belowMinPayout,
+    accruals,
+  };
+}
+
+/**
+ * Floor split that keeps each holder's remainder in `accrualsOut`
+ *
+ * A holder's exact share plus its accrual carried in is paid in whole units;
+ * what's left (or all of it, below minPayout) is its new accrual.
+ */
+function accrue(
+  holders: HolderWeight[],
+  budget: bigint,
+  accrualsIn: Map<string, bigint> | undefined,
+  minPayout: bigint,
+  accrualsOut: Map<string, bigint>
+): { allocations: Allocation[]; belowMinPayout: number } {
+  const totalWeight = holders.reduce((sum, h) => sum + h.weight, 0n);
+  if (totalWeight === 0n) return { allocations: [], belowMinPayout: 0 };
+
+  const allocations: Allocation[] = [];
+  let belowMinPayout = 0;
+  for (const h of holders) {
+    const exact = (budget * h.weight * ACCRUAL_SCALE) / totalWeight + (accrualsIn?.get(h.wallet) ?? 0n);
+    const amount = exact / ACCRUAL_SCALE;
+    if (amount === 0n || amount < minPayout) {
+      belowMinPayout++;
+      accrualsOut.set(h.wallet, exact);
+      continue;
+    }
+    allocations.push({ wallet: h.wallet, weight: h.weight, amount });
+    accrualsOut.set(h.wallet, exact % ACCRUAL_SCALE);
+  }
+  return { allocations, belowMinPayout };
+}
+
+/**
+ * Accrual (ACCRUAL_SCALE units) as a decimal string of raw units, for NUMERIC columns
+ */
+export function formatAccrual(accrual: bigint): string {
+  const fraction = (accrual % ACCRUAL_SCALE).toString().padStart(12, '0');
+  return `${accrual / ACCRUAL_SCALE}.${fraction}`;
+}
+
+/**
+ * Inverse of formatAccrual (digits beyond the scale are dropped)
+ */
+export function parseAccrual(value: string): bigint {
+  const [whole, fraction = ''] = value.split('.');
+  return BigInt(whole) * ACCRUAL_SCALE + BigInt(fraction.slice(0, 12).padEnd(12, '0'));
+}
+
+function split(holders: HolderWeight[], budget: bigint, rounding: RoundingMode): Allocation[] {
+  const totalWeight = holders.reduce((sum, h) => sum + h.weight, 0n);
+  if (totalWeight === 0n) return [];
+
+  const parts = holders.map((h) => ({
+    wallet: h.wallet,
+    weight: h.weight,
+    amount: (budget * h.weight) / totalWeight,
+    remainder: (budget * h.weight) % totalWeight,
+  }));
+
+  if (rounding === 'largest-remainder') {
+    let leftover = budget - parts.reduce((sum, p) => sum + p.amount, 0n);
+    const order = [...parts].sort((a, b) =>
+      a.remainder === b.remainder
+        ? (a.wallet < b.wallet ? -1 : 1)
+        : a.remainder > b.remainder ? -1 : 1
+    );
+    for (const part of order) {
+      if (leftover === 0n) break;
+      part.amount += 1n;
+      leftover -= 1n;
+    }
+  }
+
+  return parts.map(({ wallet, weight, amount }) => ({ wallet, weight, amount }));
+}
@@ -0,0 +1,128 @@
+// src/allocation/exclusions.ts
+// Treasury / protocol wallets that never receive rewards, each with a reason
+
+import fs from 'fs';
+import { PublicKey } from '@solana/web3.js';
+import { getIgnoredWalletsArray } from '../api/queries/ignored-wallets';
+import { LstConfig } from '../config/lsts';
+import { getStakePoolAdapter } from '../indexers/exchange-rate';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const rpcConfig = getRpcConfigFromEnv();
+const rpc = new FailoverConnection(rpcConfig);
+
+// ---------------------------
+// Types
+// ---------------------------
+
+/**
+ * Excluded wallet -> reason; the first source listing a wallet gives the reason
+ */
+export type ExclusionList = Map<string, string>;
+
+// ---------------------------
+// Sources
+// ---------------------------
+
+/**
+ * Wallets in an exclusion file: one per line, optionally followed by the
+ * reason; blank lines and `#` comments are skipped
+ *
+ *   9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM  Treasury multisig
+ */
+export function readExclusionFile(path: string, defaultReason: string): [string, string][] {
+  return fs
+    .readFileSync(path, 'utf8')
+    .split(/\r?\n/)
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0 && !line.startsWith('#'))
+    .map((line) => {
+      const [wallet, ...reason] = line.split(/\s+/);
+      try {
+        new PublicKey(wallet);
+      } catch {
+        throw new Error(`${path}: invalid wallet ${wallet}`);
+      }
+      return [wallet, reason.join(' ') || defaultReason];
+    });
+}
+
+/**
+ * Operator wallets of the LST's stake pool (manager, fee account owner, ...),
+ * read from the current pool state
+ */
+export async function fetchStakePoolExclusions(lst: LstConfig): Promise<[string, string][]> {
+  if (!lst.stakePool) return [];
+
+  const adapter = getStakePoolAdapter(lst.stakePool.kind, lst.stakePool.address);
+  const account = await rpc.execute(
+    (connection) => connection.getAccountInfo(adapter.address, 'finalized'),
+    'getStakePool'
+  );
+  if (!account) {
+    throw new Error(`Stake pool account not found: ${adapter.address.toBase58()}`);
+  }
+
+  const operators = adapter.operatorAccounts(account.data);
+  const tokenAccounts = operators.filter((a) => a.tokenAccount);
+  const infos =
+    tokenAccounts.length > 0
+      ? await rpc.execute(
+          (connection) =>
+            connection.getMultipleAccountsInfo(
+              tokenAccounts.map((a) => new PublicKey(a.address)),
+              'finalized'
+            ),
+          'getOperatorTokenAccounts'
+        )
+      : [];
+
+  const exclusions: [string, string][] = [];
+  for (const operator of operators) {
+    if (!operator.tokenAccount) {
+      exclusions.push([operator.address, `${lst.symbol} stake pool ${operator.role}`]);
+      continue;
+    }
+    const info = infos[tokenAccounts.indexOf(operator)];
+    if (info) {
+      // Token account layout: mint (0..32), owner (32..64)
+      const owner = new PublicKey(info.data.subarray(32, 64)).toBase58();
+      exclusions.push([owner, `owner of ${lst.symbol} stake pool ${operator.role}`]);
+    }
+  }
+  return exclusions;
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Every wallet excluded from `lst`'s allocations, with the reason
+ *
+ * Sources, in order: EXCLUSIONS_FILE, the LST's `exclude` list,
+ * IGNORE_WALLETS, the run's --exclude file, then the stake pool's operator
+ * wallets looked up on-chain.
+ */
+export async function loadExclusions(lst: LstConfig, file?: string): Promise<ExclusionList> {
+  const exclusions: ExclusionList = new Map();
+  const add = (entries: [string, string][]) => {
+    for (const [wallet, reason] of entries) {
+      if (!exclusions.has(wallet)) exclusions.set(wallet, reason);
+    }
+  };
+
+  if (process.env.EXCLUSIONS_FILE) {
+    add(readExclusionFile(process.env.EXCLUSIONS_FILE, 'exclusions file'));
+  }
+  add(lst.exclude.map((wallet) => [wallet, `${lst.symbol} exclude list`]));
+  add(getIgnoredWalletsArray().map((wallet) => [wallet, 'IGNORE_WALLETS']));
+  if (file) {
+    add(readExclusionFile(file, file));
+  }
+  add(await fetchStakePoolExclusions(lst));
+  return exclusions;
+}
@@ -2,11 +2,14 @@ import express from 'express';
 import cors from 'cors';
 import 'dotenv/config';
 
-import { healthRouter } from './routes/health';
+import { healthRouter, probesRouter } from './routes/health';
 import { statsRouter } from './routes/stats';
 import { walletRouter } from './routes/wallet';
 import { leaderboardRouter } from './routes/leaderboard';
 import { windowPayoutsRouter } from './routes/window-payouts';
+import { distributionsRouter } from './routes/distributions';
+import { exportsRouter } from './routes/exports';
+import { holderDiffsRouter } from './routes/holder-diffs';
 import { errorHandler } from './middleware/error-handler';
 import { rateLimiter } from './middleware/rate-limit';
 
@@ -26,6 +29,9 @@ app.use(
   })
 );
 
+// Kubernetes probes, ahead of rate limiting and request logging
+app.use(probesRouter);
+
 // Parse JSON bodies (not strictly needed for GET-only API, but good practice)
 app.use(express.json());
 
@@ -53,6 +59,9 @@ app.use('/api/stats', statsRouter);
 app.use('/api/wallet', walletRouter);
 app.use('/api/leaderboard', leaderboardRouter);
 app.use('/api/rewards', windowPayoutsRouter);
+app.use('/api/distributions', distributionsRouter);
+app.use('/api/exports', exportsRouter);
+app.use('/api/holder-diffs', holderDiffsRouter);
 
 // Root endpoint
 app.get('/api', (_req, res) => {
@@ -64,10 +73,18 @@ app.get('/api', (_req, res) => {
       stats: '/api/stats',
       wallet: '/api/wallet/:address',
       walletHistory: '/api/wallet/:address/history',
+      walletClaims: '/api/wallet/:address/claims',
+      walletUnclaimed: '/api/wallet/:address/unclaimed',
       leaderboard: '/api/leaderboard',
       rewardsWindows: '/api/rewards/windows',
       rewardsWindow: '/api/rewards/window/:windowId',
       rewardsTotal: '/api/rewards/total/:address',
+      distributionClaims: '/api/distributions/:distributionId/claims',
+      distributionClaimHistogram: '/api/distributions/:distributionId/claims/histogram',
+      distributionUnclaimed: '/api/distributions/:distributionId/unclaimed',
+      holderDiffs: '/api/holder-diffs',
+      holderDiff: '/api/holder-diffs/:mint/:toEpoch',
+      holderDiffWallets: '/api/holder-diffs/:mint/:toEpoch/wallets',
     },
   });
 });
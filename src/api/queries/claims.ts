@@ -0,0 +1,305 @@
+import { pool } from '../../db';
+import { isWalletIgnored } from './ignored-wallets';
+import { getTokenByMint } from '../../config/tokens';
+import {
+  ListFilters,
+  ListParams,
+  PageInfo,
+  SortKey,
+  afterClause,
+  cursorColumns,
+  orderClause,
+  pageOf,
+} from './pagination';
+
+/** Event kinds that settle a leaf (claimed, escrowed or renounced) */
+export const SETTLING_KINDS = ['claim', 'claim_referral', 'escrow', 'renounce'];
+
+/** Event kinds shown in a wallet's claim history */
+export const HISTORY_KINDS = [...SETTLING_KINDS, 'escrow_release'];
+
+export const CLAIM_HISTORY_SORTS = {
+  time: {
+    name: 'claims.time',
+    columns: [
+      ['e.slot', 'bigint'],
+      ['e.signature', 'text'],
+      ['e.event_index', 'integer'],
+    ],
+  },
+  amount: {
+    name: 'claims.amount',
+    columns: [
+      ['COALESCE(e.amount, 0)', 'numeric'],
+      ['e.slot', 'bigint'],
+      ['e.signature', 'text'],
+      ['e.event_index', 'integer'],
+    ],
+  },
+} satisfies Record<string, SortKey>;
+
+export type ClaimHistorySort = keyof typeof CLAIM_HISTORY_SORTS;
+
+/**
+ * SQL condition: the wallet's leaf in the reward's distribution has been
+ * claimed, escrowed or renounced on-chain, or confirmed by the relayer
+ */
+export function claimedSql(rewardColumn: string, walletColumn: string): string {
+  return `(
+    EXISTS (
+      SELECT 1
+      FROM merkle_distributions cd
+      JOIN merkle_claim_events ce ON ce.deployment = cd.deployment AND ce.distribution = cd.on_chain_address
+      WHERE cd.reward_id = ${rewardColumn}
+        AND ce.recipient = ${walletColumn}
+        AND ce.kind IN ('claim', 'claim_referral', 'escrow', 'renounce')
+    ) OR EXISTS (
+      SELECT 1
+      FROM merkle_distributions cd
+      JOIN merkle_claims cc ON cc.distribution_id = cd.distribution_id
+      WHERE cd.reward_id = ${rewardColumn}
+        AND cc.wallet = ${walletColumn}
+        AND cc.status = 'confirmed'
+    )
+  )`;
+}
+
+export interface ClaimHistoryEntry {
+  deployment: string;
+  distributionId: string | null;
+  rewardId: string | null;
+  windowId: string | null;
+  mint: string | null;
+  symbol: string | null;
+  kind: string;
+  amount: string;
+  displayAmount: string | null;
+  bonus: string | null;
+  referrer: string | null;
+  leafIndex: number | null;
+  signature: string;
+  slot: number;
+  claimedAt: string | null;
+}
+
+export interface ClaimHistory {
+  wallet: string;
+  claims: ClaimHistoryEntry[];
+  pageInfo: PageInfo;
+}
+
+export interface UnclaimedAllocation {
+  deployment: string;
+  distributionId: string;
+  rewardId: string;
+  windowId: string;
+  mint: string;
+  symbol: string | null;
+  status: string;
+  claimable: boolean;
+  allocated: string;
+  settled: string;
+  unclaimed: string;
+  displayAmount: string | null;
+  createdAt: string;
+}
+
+export interface UnclaimedAllocations {
+  wallet: string;
+  unclaimed: UnclaimedAllocation[];
+}
+
+/**
+ * Format raw units with the mint's decimals, or null for unknown mints
+ */
+export function displayAmount(amount: string, mint: string | null): string | null {
+  const token = mint ? getTokenByMint(mint) : undefined;
+  if (!token) return null;
+  return (Number(amount) / 10 ** token.decimals).toFixed(token.decimals);
+}
+
+/**
+ * Get a page of on-chain claim history for a wallet (finalized events only)
+ *
+ * Supports the distributionId, deployment, from/to (block time) and
+ * minAmount filters, plus `kinds` to narrow the event kinds.
+ */
+export async function getClaimHistory(
+  walletAddress: string,
+  params: ListParams<ClaimHistorySort>,
+  filters: ListFilters,
+  kinds: string[] = HISTORY_KINDS
+): Promise<ClaimHistory> {
+  const sort = CLAIM_HISTORY_SORTS[params.sort];
+  const values: unknown[] = [walletAddress, kinds];
+  const conditions: string[] = [];
+
+  if (filters.distributionId !== null) {
+    values.push(filters.distributionId);
+    conditions.push(`AND d.distribution_id = $${values.length}`);
+  }
+  if (filters.deployment !== null) {
+    values.push(filters.deployment);
+    conditions.push(`AND e.deployment = $${values.length}`);
+  }
+  if (filters.from !== null) {
+    values.push(filters.from);
+    conditions.push(`AND EXTRACT(EPOCH FROM e.block_time) >= $${values.length}`);
+  }
+  if (filters.to !== null) {
+    values.push(filters.to);
+    conditions.push(`AND EXTRACT(EPOCH FROM e.block_time) <= $${values.length}`);
+  }
+  if (filters.minAmount !== null) {
+    values.push(filters.minAmount);
+    conditions.push(`AND e.amount >= $${values.length}::numeric`);
+  }
+  conditions.push(afterClause(sort, params, values));
+
+  // Events are keyed by distribution PDA; the join finds the reward behind it
+  const historyResult = await pool.query<{
+    deployment: string;
+    distribution_id: string | null;
+    reward_id: string | null;
+    window_id: string | null;
+    mint: string | null;
+    kind: string;
+    amount: string;
+    bonus: string | null;
+    referrer: string | null;
+    leaf_index: string | null;
+    signature: string;
+    slot: string;
+    block_time: Date | null;
+  }>(
+    `SELECT e.deployment, d.distribution_id, d.reward_id, d.window_id, d.mint,
+            e.kind, e.amount::text, e.bonus::text, e.referrer, e.leaf_index::text,
+            e.signature, e.slot::text, e.block_time
+            ${cursorColumns(sort)}
+     FROM merkle_claim_events e
+     LEFT JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+     WHERE e.recipient = $1 AND e.kind = ANY($2)
+       ${conditions.join('\n       ')}
+     ${orderClause(sort, params, values)}`,
+    values
+  );
+
+  const page = pageOf(historyResult.rows, sort, params);
+
+  const claims: ClaimHistoryEntry[] = page.rows.map((row) => ({
+    deployment: row.deployment,
+    distributionId: row.distribution_id,
+    rewardId: row.reward_id,
+    windowId: row.window_id,
+    mint: row.mint,
+    symbol: row.mint ? getTokenByMint(row.mint)?.symbol ?? null : null,
+    kind: row.kind,
+    amount: row.amount,
+    displayAmount: displayAmount(row.amount, row.mint),
+    bonus: row.bonus,
+    referrer: row.referrer,
+    leafIndex: row.leaf_index === null ? null : Number(row.leaf_index),
+    signature: row.signature,
+    slot: Number(row.slot),
+    claimedAt: row.block_time ? row.block_time.toISOString() : null,
+  }));
+
+  return {
+    wallet: walletAddress,
+    claims,
+    pageInfo: page.pageInfo,
+  };
+}
+
+/**
+ * Get a wallet's allocations that are not yet settled on-chain, across all
+ * distributions that have not completed or been clawed back (at most one
+ * row per distribution, so not paged; supports distributionId, deployment
+ * and minAmount)
+ *
+ * Allocations come from reward_payouts_preview (what the trees are built
+ * from); settled amounts from finalized claim, escrow and renounce events
+ * plus leaves the relayer confirmed.
+ */
+export async function getUnclaimedAllocations(
+  walletAddress: string,
+  filters: ListFilters
+): Promise<UnclaimedAllocations> {
+  // Ignored wallets are left out of every tree
+  if (isWalletIgnored(walletAddress)) {
+    return { wallet: walletAddress, unclaimed: [] };
+  }
+
+  const result = await pool.query<{
+    deployment: string;
+    distribution_id: string;
+    reward_id: string;
+    window_id: string;
+    mint: string;
+    status: string;
+    allocated: string;
+    settled: string;
+    created_at: Date;
+  }>(
+    `WITH allocations AS (
+       SELECT d.deployment, d.distribution_id, d.reward_id, d.window_id, d.mint, d.status,
+              d.on_chain_address, d.created_at, rpp.payout_amount AS allocated
+       FROM merkle_distributions d
+       JOIN reward_payouts_preview rpp ON rpp.reward_id = d.reward_id
+       WHERE rpp.wallet = $1 AND rpp.payout_amount > 0
+         AND d.status NOT IN ('completed', 'clawedback')
+         AND ($3::text IS NULL OR d.distribution_id = $3)
+         AND ($5::text IS NULL OR d.deployment = $5)
+     ),
+     settled AS (
+       SELECT a.distribution_id,
+              GREATEST(
+                COALESCE((
+                  SELECT SUM(e.amount)
+                  FROM merkle_claim_events e
+                  WHERE e.deployment = a.deployment
+                    AND e.distribution = a.on_chain_address
+                    AND e.recipient = $1
+                    AND e.kind = ANY($2)
+                ), 0),
+                COALESCE((
+                  SELECT SUM(c.amount)
+                  FROM merkle_claims c
+                  WHERE c.distribution_id = a.distribution_id
+                    AND c.wallet = $1
+                    AND c.status = 'confirmed'
+                ), 0)
+              ) AS settled
+       FROM allocations a
+     )
+     SELECT a.deployment, a.distribution_id, a.reward_id, a.window_id, a.mint, a.status,
+            a.allocated::text, s.settled::text, a.created_at
+     FROM allocations a
+     JOIN settled s ON s.distribution_id = a.distribution_id
+     WHERE a.allocated > s.settled
+       AND a.allocated - s.settled >= COALESCE($4::numeric, 0)
+     ORDER BY a.created_at DESC`,
+    [walletAddress, SETTLING_KINDS, filters.distributionId, filters.minAmount, filters.deployment]
+  );
+
+  const unclaimed: UnclaimedAllocation[] = result.rows.map((row) => {
+    const remaining = (BigInt(row.allocated) - BigInt(row.settled)).toString();
+    return {
+      deployment: row.deployment,
+      distributionId: row.distribution_id,
+      rewardId: row.reward_id,
+      windowId: row.window_id,
+      mint: row.mint,
+      symbol: getTokenByMint(row.mint)?.symbol ?? null,
+      status: row.status,
+      claimable: row.status === 'active',
+      allocated: row.allocated,
+      settled: row.settled,
+      unclaimed: remaining,
+      displayAmount: displayAmount(remaining, row.mint),
+      createdAt: row.created_at.toISOString(),
+    };
+  });
+
+  return { wallet: walletAddress, unclaimed };
+}
@@ -0,0 +1,273 @@
+import { pool } from '../../db';
+import { getTokenByMint } from '../../config/tokens';
+import { getIgnoredWalletsArray } from './ignored-wallets';
+import { displayAmount, SETTLING_KINDS } from './claims';
+
+export const HISTOGRAM_BUCKETS = ['hour', 'day', 'week'] as const;
+
+export type HistogramBucket = (typeof HISTOGRAM_BUCKETS)[number];
+
+interface DistributionRow {
+  deployment: string;
+  distribution_id: string;
+  reward_id: string;
+  mint: string;
+  status: string;
+  on_chain_address: string | null;
+}
+
+export interface DistributionClaimSummary {
+  deployment: string;
+  distributionId: string;
+  rewardId: string;
+  mint: string;
+  symbol: string | null;
+  status: string;
+  recipients: number;
+  claimedRecipients: number;
+  allocated: string;
+  claimed: string;
+  unclaimed: string;
+  displayUnclaimed: string | null;
+  percentClaimedByCount: number;
+  percentClaimedByValue: number;
+}
+
+export interface ClaimHistogramBucket {
+  start: string;
+  claims: number;
+  amount: string;
+  cumulativeClaims: number;
+  cumulativeAmount: string;
+  percentClaimedByValue: number;
+}
+
+export interface ClaimHistogram {
+  deployment: string;
+  distributionId: string;
+  bucket: HistogramBucket;
+  allocated: string;
+  buckets: ClaimHistogramBucket[];
+}
+
+export interface TopUnclaimedAllocation {
+  wallet: string;
+  allocated: string;
+  settled: string;
+  unclaimed: string;
+  displayAmount: string | null;
+}
+
+export interface TopUnclaimed {
+  deployment: string;
+  distributionId: string;
+  mint: string;
+  symbol: string | null;
+  allocations: TopUnclaimedAllocation[];
+}
+
+/**
+ * Per-recipient allocation and settled amount of one distribution
+ *
+ * $1 reward ID, $2 distribution PDA, $3 distribution ID, $4 settling kinds,
+ * $5 ignored wallets, $6 deployment. Allocations come from reward_payouts_preview (ignored
+ * wallets are left out of every tree); settled is the larger of finalized
+ * settling events and relayer-confirmed claims, capped at the allocation.
+ */
+const RECIPIENTS_CTE = `
+  WITH allocations AS (
+    SELECT wallet, payout_amount AS allocated
+    FROM reward_payouts_preview
+    WHERE reward_id = $1 AND payout_amount > 0 AND NOT (wallet = ANY($5))
+  ),
+  events AS (
+    SELECT recipient AS wallet, SUM(amount) AS amount
+    FROM merkle_claim_events
+    WHERE deployment = $6 AND distribution = $2 AND kind = ANY($4)
+    GROUP BY recipient
+  ),
+  relayed AS (
+    SELECT wallet, SUM(amount) AS amount
+    FROM merkle_claims
+    WHERE distribution_id = $3 AND status = 'confirmed'
+    GROUP BY wallet
+  ),
+  recipients AS (
+    SELECT a.wallet, a.allocated,
+           LEAST(a.allocated, GREATEST(COALESCE(e.amount, 0), COALESCE(r.amount, 0))) AS settled
+    FROM allocations a
+    LEFT JOIN events e ON e.wallet = a.wallet
+    LEFT JOIN relayed r ON r.wallet = a.wallet
+  )`;
+
+async function getDistribution(distributionId: string): Promise<DistributionRow | null> {
+  const result = await pool.query<DistributionRow>(
+    `SELECT deployment, distribution_id, reward_id, mint, status, on_chain_address
+     FROM merkle_distributions
+     WHERE distribution_id = $1`,
+    [distributionId]
+  );
+  return result.rows[0] ?? null;
+}
+
+function recipientParams(distribution: DistributionRow): unknown[] {
+  return [
+    distribution.reward_id,
+    distribution.on_chain_address,
+    distribution.distribution_id,
+    SETTLING_KINDS,
+    getIgnoredWalletsArray(),
+    distribution.deployment,
+  ];
+}
+
+/**
+ * Percentage of `part` in `total` to two decimals (0 for an empty total)
+ */
+function percent(part: bigint, total: bigint): number {
+  if (total === 0n) return 0;
+  return Number((part * 10000n) / total) / 100;
+}
+
+/**
+ * Share of a distribution claimed, by recipient count and by value
+ *
+ * A recipient counts as claimed once anything of its leaf is settled.
+ */
+export async function getDistributionClaimSummary(distributionId: string): Promise<DistributionClaimSummary | null> {
+  const distribution = await getDistribution(distributionId);
+  if (!distribution) return null;
+
+  const result = await pool.query<{
+    recipients: number;
+    claimed_recipients: number;
+    allocated: string;
+    claimed: string;
+  }>(
+    `${RECIPIENTS_CTE}
+     SELECT COUNT(*)::int AS recipients,
+            COUNT(*) FILTER (WHERE settled > 0)::int AS claimed_recipients,
+            COALESCE(SUM(allocated), 0)::text AS allocated,
+            COALESCE(SUM(settled), 0)::text AS claimed
+     FROM recipients`,
+    recipientParams(distribution)
+  );
+
+  const row = result.rows[0];
+  const allocated = BigInt(row.allocated);
+  const claimed = BigInt(row.claimed);
+  const unclaimed = (allocated - claimed).toString();
+
+  return {
+    deployment: distribution.deployment,
+    distributionId: distribution.distribution_id,
+    rewardId: distribution.reward_id,
+    mint: distribution.mint,
+    symbol: getTokenByMint(distribution.mint)?.symbol ?? null,
+    status: distribution.status,
+    recipients: row.recipients,
+    claimedRecipients: row.claimed_recipients,
+    allocated: row.allocated,
+    claimed: row.claimed,
+    unclaimed,
+    displayUnclaimed: displayAmount(unclaimed, distribution.mint),
+    percentClaimedByCount: percent(BigInt(row.claimed_recipients), BigInt(row.recipients)),
+    percentClaimedByValue: percent(claimed, allocated),
+  };
+}
+
+/**
+ * Finalized settling events of a distribution per time bucket, with running
+ * totals (the claim-rate curve)
+ *
+ * Buckets run from the first event's to the last event's, including empty
+ * ones. Events without a block time are left out.
+ */
+export async function getClaimHistogram(
+  distributionId: string,
+  bucket: HistogramBucket
+): Promise<ClaimHistogram | null> {
+  const distribution = await getDistribution(distributionId);
+  if (!distribution) return null;
+
+  const allocatedResult = await pool.query<{ allocated: string }>(
+    `${RECIPIENTS_CTE}
+     SELECT COALESCE(SUM(allocated), 0)::text AS allocated FROM recipients`,
+    recipientParams(distribution)
+  );
+  const allocated = BigInt(allocatedResult.rows[0].allocated);
+
+  const result = await pool.query<{ start: Date; claims: number; amount: string }>(
+    `WITH events AS (
+       SELECT date_trunc($3, block_time AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS start, amount
+       FROM merkle_claim_events
+       WHERE deployment = $4 AND distribution = $1 AND kind = ANY($2) AND block_time IS NOT NULL
+     ),
+     series AS (
+       SELECT generate_series(MIN(start), MAX(start), ('1 ' || $3)::interval) AS start
+       FROM events
+     )
+     SELECT s.start, COUNT(e.start)::int AS claims, COALESCE(SUM(e.amount), 0)::text AS amount
+     FROM series s
+     LEFT JOIN events e ON e.start = s.start
+     GROUP BY s.start
+     ORDER BY s.start`,
+    [distribution.on_chain_address, SETTLING_KINDS, bucket, distribution.deployment]
+  );
+
+  let cumulativeClaims = 0;
+  let cumulativeAmount = 0n;
+  const buckets: ClaimHistogramBucket[] = result.rows.map((row) => {
+    cumulativeClaims += row.claims;
+    cumulativeAmount += BigInt(row.amount);
+    return {
+      start: row.start.toISOString(),
+      claims: row.claims,
+      amount: row.amount,
+      cumulativeClaims,
+      cumulativeAmount: cumulativeAmount.toString(),
+      percentClaimedByValue: percent(cumulativeAmount, allocated),
+    };
+  });
+
+  return {
+    deployment: distribution.deployment,
+    distributionId: distribution.distribution_id,
+    bucket,
+    allocated: allocated.toString(),
+    buckets,
+  };
+}
+
+/**
+ * The `limit` largest allocations of a distribution not yet settled,
+ * largest remaining amount first
+ */
+export async function getTopUnclaimed(distributionId: string, limit: number): Promise<TopUnclaimed | null> {
+  const distribution = await getDistribution(distributionId);
+  if (!distribution) return null;
+
+  const result = await pool.query<{ wallet: string; allocated: string; settled: string; unclaimed: string }>(
+    `${RECIPIENTS_CTE}
+     SELECT wallet, allocated::text, settled::text, (allocated - settled)::text AS unclaimed
+     FROM recipients
+     WHERE allocated > settled
+     ORDER BY allocated - settled DESC, wallet
+     LIMIT $7`,
+    [...recipientParams(distribution), limit]
+  );
+
+  return {
+    deployment: distribution.deployment,
+    distributionId: distribution.distribution_id,
+    mint: distribution.mint,
+    symbol: getTokenByMint(distribution.mint)?.symbol ?? null,
+    allocations: result.rows.map((row) => ({
+      wallet: row.wallet,
+      allocated: row.allocated,
+      settled: row.settled,
+      unclaimed: row.unclaimed,
+      displayAmount: displayAmount(row.unclaimed, distribution.mint),
+    })),
+  };
+}
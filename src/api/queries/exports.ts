@@ -0,0 +1,56 @@
+import { pool } from '../../db';
+
+export interface FinanceExportStatus {
+  id: string;
+  dataset: string;
+  format: string;
+  from: string;
+  to: string;
+  location: string;
+  status: 'running' | 'completed' | 'failed';
+  rows: number | null;
+  error: string | null;
+  createdAt: string;
+  completedAt: string | null;
+}
+
+/**
+ * Get a finance export started through the API
+ */
+export async function getFinanceExport(id: string): Promise<FinanceExportStatus | null> {
+  const result = await pool.query<{
+    id: string;
+    dataset: string;
+    format: string;
+    range_from: Date;
+    range_to: Date;
+    location: string;
+    status: 'running' | 'completed' | 'failed';
+    rows: string | null;
+    error: string | null;
+    created_at: Date;
+    completed_at: Date | null;
+  }>(
+    `SELECT id::text, dataset, format, range_from, range_to, location, status, rows::text, error,
+            created_at, completed_at
+     FROM finance_exports
+     WHERE id = $1`,
+    [id]
+  );
+
+  const row = result.rows[0];
+  if (!row) return null;
+  return {
+    id: row.id,
+    dataset: row.dataset,
+    format: row.format,
+    from: row.range_from.toISOString(),
+    to: row.range_to.toISOString(),
+    location: row.location,
+    status: row.status,
+    rows: row.rows === null ? null : Number(row.rows),
+    error: row.error,
+    createdAt: row.created_at.toISOString(),
+    completedAt: row.completed_at ? row.completed_at.toISOString() : null,
+  };
+}
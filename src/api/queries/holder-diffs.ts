@@ -0,0 +1,208 @@
+import { pool } from '../../db';
+import { getTokenByMint } from '../../config/tokens';
+import { HOLDER_COHORTS, HolderCohort, HolderDiffKey } from '../../indexers/holder-diffs';
+import { ListParams, PageInfo, SortKey, afterClause, cursorColumns, orderClause, pageOf } from './pagination';
+
+export const HOLDER_DIFF_WALLET_SORTS = {
+  change: {
+    name: 'holderDiff.change',
+    columns: [
+      ['amount_to - amount_from', 'numeric'],
+      ['wallet', 'text'],
+    ],
+  },
+  amount: {
+    name: 'holderDiff.amount',
+    columns: [
+      ['amount_to', 'numeric'],
+      ['wallet', 'text'],
+    ],
+  },
+  wallet: {
+    name: 'holderDiff.wallet',
+    columns: [['wallet', 'text']],
+  },
+} satisfies Record<string, SortKey>;
+
+export type HolderDiffWalletSort = keyof typeof HOLDER_DIFF_WALLET_SORTS;
+
+export interface HolderDiffSummary {
+  mint: string;
+  symbol: string | null;
+  fromEpoch: number;
+  toEpoch: number;
+  holdersFrom: number;
+  holdersTo: number;
+  amountFrom: string;
+  amountTo: string;
+  builtAt: string;
+}
+
+export interface HolderDiffCohort {
+  cohort: HolderCohort;
+  wallets: number;
+  amountFrom: string;
+  amountTo: string;
+  change: string;
+}
+
+export interface HolderDiff extends HolderDiffSummary {
+  cohorts: HolderDiffCohort[];
+}
+
+export interface HolderDiffWallet {
+  wallet: string;
+  cohort: HolderCohort;
+  amountFrom: string;
+  amountTo: string;
+  change: string;
+}
+
+export interface HolderDiffWallets {
+  mint: string;
+  fromEpoch: number;
+  toEpoch: number;
+  wallets: HolderDiffWallet[];
+  pageInfo: PageInfo;
+}
+
+type SummaryRow = {
+  mint: string;
+  from_epoch: string;
+  to_epoch: string;
+  holders_from: number;
+  holders_to: number;
+  amount_from: string;
+  amount_to: string;
+  created_at: Date;
+};
+
+const SUMMARY_COLUMNS = `mint, from_epoch::text, to_epoch::text, holders_from, holders_to,
+       amount_from::text, amount_to::text, created_at`;
+
+function toSummary(row: SummaryRow): HolderDiffSummary {
+  return {
+    mint: row.mint,
+    symbol: getTokenByMint(row.mint)?.symbol ?? null,
+    fromEpoch: Number(row.from_epoch),
+    toEpoch: Number(row.to_epoch),
+    holdersFrom: row.holders_from,
+    holdersTo: row.holders_to,
+    amountFrom: row.amount_from,
+    amountTo: row.amount_to,
+    builtAt: row.created_at.toISOString(),
+  };
+}
+
+/**
+ * The most recent holder diffs, optionally of one mint
+ */
+export async function listHolderDiffs(mint: string | null, limit: number): Promise<HolderDiffSummary[]> {
+  const result = await pool.query<SummaryRow>(
+    `SELECT ${SUMMARY_COLUMNS}
+     FROM holder_diffs
+     WHERE ($1::text IS NULL OR mint = $1)
+     ORDER BY to_epoch DESC, from_epoch DESC, mint
+     LIMIT $2`,
+    [mint, limit]
+  );
+  return result.rows.map(toSummary);
+}
+
+export async function holderDiffExists({ mint, fromEpoch, toEpoch }: HolderDiffKey): Promise<boolean> {
+  const result = await pool.query(
+    `SELECT 1 FROM holder_diffs WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3`,
+    [mint, fromEpoch, toEpoch]
+  );
+  return result.rows.length > 0;
+}
+
+/**
+ * A holder diff with wallet counts and balances per cohort, or null if it
+ * hasn't been built
+ */
+export async function getHolderDiff({ mint, fromEpoch, toEpoch }: HolderDiffKey): Promise<HolderDiff | null> {
+  const summary = await pool.query<SummaryRow>(
+    `SELECT ${SUMMARY_COLUMNS}
+     FROM holder_diffs
+     WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3`,
+    [mint, fromEpoch, toEpoch]
+  );
+  if (summary.rows.length === 0) return null;
+
+  const cohortsResult = await pool.query<{
+    cohort: HolderCohort;
+    wallets: number;
+    amount_from: string;
+    amount_to: string;
+  }>(
+    `SELECT cohort, COUNT(*)::int AS wallets, SUM(amount_from)::text AS amount_from, SUM(amount_to)::text AS amount_to
+     FROM holder_diff_wallets
+     WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3
+     GROUP BY cohort`,
+    [mint, fromEpoch, toEpoch]
+  );
+  const byCohort = new Map(cohortsResult.rows.map((row) => [row.cohort, row]));
+
+  // Every cohort is listed, in band order, empty ones included
+  const cohorts = HOLDER_COHORTS.map((cohort) => {
+    const row = byCohort.get(cohort);
+    const amountFrom = row?.amount_from ?? '0';
+    const amountTo = row?.amount_to ?? '0';
+    return {
+      cohort,
+      wallets: row?.wallets ?? 0,
+      amountFrom,
+      amountTo,
+      change: (BigInt(amountTo) - BigInt(amountFrom)).toString(),
+    };
+  });
+
+  return { ...toSummary(summary.rows[0]), cohorts };
+}
+
+/**
+ * A page of a holder diff's wallets, optionally of one cohort
+ */
+export async function getHolderDiffWallets(
+  { mint, fromEpoch, toEpoch }: HolderDiffKey,
+  cohort: HolderCohort | null,
+  params: ListParams<HolderDiffWalletSort>
+): Promise<HolderDiffWallets> {
+  const sort = HOLDER_DIFF_WALLET_SORTS[params.sort];
+  const values: unknown[] = [mint, fromEpoch, toEpoch, cohort];
+  const after = afterClause(sort, params, values);
+
+  const result = await pool.query<{
+    wallet: string;
+    cohort: HolderCohort;
+    amount_from: string;
+    amount_to: string;
+    change: string;
+  }>(
+    `SELECT wallet, cohort, amount_from::text, amount_to::text, (amount_to - amount_from)::text AS change
+            ${cursorColumns(sort)}
+     FROM holder_diff_wallets
+     WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3
+       AND ($4::text IS NULL OR cohort = $4)
+       ${after}
+     ${orderClause(sort, params, values)}`,
+    values
+  );
+
+  const page = pageOf(result.rows, sort, params);
+
+  return {
+    mint,
+    fromEpoch,
+    toEpoch,
+    wallets: page.rows.map((row) => ({
+      wallet: row.wallet,
+      cohort: row.cohort,
+      amountFrom: row.amount_from,
+      amountTo: row.amount_to,
+      change: row.change,
+    })),
+    pageInfo: page.pageInfo,
+  };
+}
@@ -1,3 +1,31 @@
 export { getGlobalStats, GlobalStats } from './stats';
 export { getWalletData, getWalletHistory, WalletData, WalletHistory, WalletHistoryEntry } from './wallet';
 export { getLeaderboard, LeaderboardEntry, LeaderboardResponse } from './leaderboard';
+export {
+  getClaimHistory,
+  getUnclaimedAllocations,
+  ClaimHistory,
+  ClaimHistoryEntry,
+  UnclaimedAllocation,
+  UnclaimedAllocations,
+} from './claims';
+export {
+  getClaimHistogram,
+  getDistributionClaimSummary,
+  getTopUnclaimed,
+  ClaimHistogram,
+  ClaimHistogramBucket,
+  DistributionClaimSummary,
+  TopUnclaimed,
+  TopUnclaimedAllocation,
+} from './distributions';
+export {
+  getHolderDiff,
+  getHolderDiffWallets,
+  listHolderDiffs,
+  HolderDiff,
+  HolderDiffCohort,
+  HolderDiffSummary,
+  HolderDiffWallet,
+  HolderDiffWallets,
+} from './holder-diffs';
@@ -0,0 +1,205 @@
+/**
+ * Cursor pagination, sorting and filters shared by the list endpoints.
+ *
+ * Lists are paged by keyset: the cursor holds the last row's sort key values
+ * and the next page starts strictly after them, so deep pages cost the same
+ * as the first and rows inserted meanwhile don't shift them. Cursors are
+ * opaque (base64url JSON) and only valid for the sort they were issued under.
+ *
+ * Query parameters:
+ *   ?limit=25&sort=time&order=desc&cursor=...
+ *   ?distributionId=<hex>&from=<unix|ISO>&to=<unix|ISO>&minAmount=<base units>&claimed=true|false
+ *   ?deployment=<name>
+ */
+
+import { createError } from '../middleware/error-handler';
+
+export type SortOrder = 'asc' | 'desc';
+
+/**
+ * Columns a list is ordered by, most significant first. Trailing columns make
+ * the order total. Each entry is [SQL expression, SQL type]; none may be NULL.
+ */
+export interface SortKey {
+  name: string;
+  columns: [string, string][];
+}
+
+export interface ListParams<S extends string> {
+  limit: number;
+  sort: S;
+  order: SortOrder;
+  /** Sort key values of the row to resume after */
+  after: string[] | null;
+}
+
+export interface ListFilters {
+  distributionId: string | null;
+  /** Deployment name (DEPLOYMENTS_CONFIG) */
+  deployment: string | null;
+  /** Unix seconds, inclusive */
+  from: number | null;
+  /** Unix seconds, inclusive */
+  to: number | null;
+  /** Base units, inclusive */
+  minAmount: string | null;
+  claimed: boolean | null;
+}
+
+export interface PageInfo {
+  nextCursor: string | null;
+  hasMore: boolean;
+}
+
+type Query = Record<string, unknown>;
+
+function param(query: Query, name: string): string | undefined {
+  const value = query[name];
+  return typeof value === 'string' && value.length > 0 ? value : undefined;
+}
+
+export function encodeCursor(sort: string, keys: string[]): string {
+  return Buffer.from(JSON.stringify({ s: sort, k: keys })).toString('base64url');
+}
+
+function decodeCursor(cursor: string, sort: SortKey): string[] {
+  let decoded: { s?: unknown; k?: unknown };
+  try {
+    decoded = JSON.parse(Buffer.from(cursor, 'base64url').toString('utf8'));
+  } catch {
+    throw createError('Invalid cursor', 400, 'INVALID_CURSOR');
+  }
+  const keys = decoded?.k;
+  if (
+    decoded?.s !== sort.name ||
+    !Array.isArray(keys) ||
+    keys.length !== sort.columns.length ||
+    !keys.every((k) => typeof k === 'string')
+  ) {
+    throw createError('Cursor does not match the requested sort', 400, 'INVALID_CURSOR');
+  }
+  return keys;
+}
+
+/**
+ * Parse limit, sort, order and cursor. The first sort is the default.
+ */
+export function parseListParams<S extends string>(
+  query: Query,
+  sorts: Record<S, SortKey>,
+  defaultLimit: number,
+  maxLimit: number
+): ListParams<S> {
+  const names = Object.keys(sorts) as S[];
+  const sort = (param(query, 'sort') ?? names[0]) as S;
+  if (!names.includes(sort)) {
+    throw createError(`Invalid sort. Expected one of: ${names.join(', ')}`, 400, 'INVALID_SORT');
+  }
+
+  const order = param(query, 'order') ?? 'desc';
+  if (order !== 'asc' && order !== 'desc') {
+    throw createError('Invalid order. Expected asc or desc', 400, 'INVALID_ORDER');
+  }
+
+  const limit = Math.min(maxLimit, Math.max(1, parseInt(param(query, 'limit') ?? '', 10) || defaultLimit));
+  const cursor = param(query, 'cursor');
+
+  return {
+    limit,
+    sort,
+    order,
+    after: cursor ? decodeCursor(cursor, sorts[sort]) : null,
+  };
+}
+
+/**
+ * Unix seconds or an ISO date
+ */
+function parseTime(query: Query, name: string): number | null {
+  const value = param(query, name);
+  if (value === undefined) return null;
+  const seconds = /^\d+$/.test(value) ? Number(value) : Date.parse(value) / 1000;
+  if (!Number.isFinite(seconds)) {
+    throw createError(`Invalid ${name}. Expected unix seconds or an ISO date`, 400, 'INVALID_FILTER');
+  }
+  return Math.floor(seconds);
+}
+
+/**
+ * Parse the shared filters (each endpoint applies the ones it supports)
+ */
+export function parseListFilters(query: Query): ListFilters {
+  const distributionId = param(query, 'distributionId') ?? null;
+  if (distributionId !== null && !/^[0-9a-fA-F]{64}$/.test(distributionId)) {
+    throw createError('Invalid distributionId. Expected 64 hex characters', 400, 'INVALID_FILTER');
+  }
+
+  const deployment = param(query, 'deployment') ?? null;
+  if (deployment !== null && !/^[a-z0-9][a-z0-9_-]*$/.test(deployment)) {
+    throw createError('Invalid deployment. Expected a deployment name', 400, 'INVALID_FILTER');
+  }
+
+  const minAmount = param(query, 'minAmount') ?? null;
+  if (minAmount !== null && !/^\d+$/.test(minAmount)) {
+    throw createError('Invalid minAmount. Expected a base-unit integer', 400, 'INVALID_FILTER');
+  }
+
+  const claimed = param(query, 'claimed');
+  if (claimed !== undefined && claimed !== 'true' && claimed !== 'false') {
+    throw createError('Invalid claimed. Expected true or false', 400, 'INVALID_FILTER');
+  }
+
+  return {
+    distributionId: distributionId?.toLowerCase() ?? null,
+    deployment,
+    from: parseTime(query, 'from'),
+    to: parseTime(query, 'to'),
+    minAmount,
+    claimed: claimed === undefined ? null : claimed === 'true',
+  };
+}
+
+/**
+ * Select list for the sort key values, read back by pageOf()
+ */
+export function cursorColumns(sort: SortKey): string {
+  return sort.columns.map(([column], i) => `, (${column})::text AS cursor_${i}`).join('');
+}
+
+/**
+ * `AND (keys) < (cursor)` (or `>` ascending), binding the cursor into params
+ */
+export function afterClause<S extends string>(sort: SortKey, params: ListParams<S>, values: unknown[]): string {
+  if (!params.after) return '';
+  const columns = sort.columns.map(([column]) => column).join(', ');
+  const placeholders = sort.columns.map(([, type], i) => {
+    values.push(params.after![i]);
+    return `$${values.length}::${type}`;
+  });
+  return `AND (${columns}) ${params.order === 'desc' ? '<' : '>'} (${placeholders.join(', ')})`;
+}
+
+/**
+ * `ORDER BY ... LIMIT` fetching one extra row to detect a next page
+ */
+export function orderClause<S extends string>(sort: SortKey, params: ListParams<S>, values: unknown[]): string {
+  const direction = params.order.toUpperCase();
+  values.push(params.limit + 1);
+  return `ORDER BY ${sort.columns.map(([column]) => `${column} ${direction}`).join(', ')} LIMIT $${values.length}`;
+}
+
+/**
+ * Trim the extra row and build the next cursor from the last kept row
+ */
+export function pageOf<Row extends Record<string, unknown>, S extends string>(
+  rows: Row[],
+  sort: SortKey,
+  params: ListParams<S>
+): { rows: Row[]; pageInfo: PageInfo } {
+  const hasMore = rows.length > params.limit;
+  const page = rows.slice(0, params.limit);
+  const last = page[page.length - 1];
+  const nextCursor =
+    hasMore && last ? encodeCursor(sort.name, sort.columns.map((_, i) => String(last[`cursor_${i}`]))) : null;
+  return { rows: page, pageInfo: { nextCursor, hasMore } };
+}
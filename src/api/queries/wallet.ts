@@ -1,6 +1,17 @@
 import { pool } from '../../db';
 import { isWalletIgnored, getIgnoredWalletsArray } from './ignored-wallets';
 import { getTokenBySymbol } from '../../config/tokens';
+import { claimedSql } from './claims';
+import {
+  ListFilters,
+  ListParams,
+  PageInfo,
+  SortKey,
+  afterClause,
+  cursorColumns,
+  orderClause,
+  pageOf,
+} from './pagination';
 
 export interface WalletData {
   wallet: string;
@@ -33,19 +44,34 @@ export interface WalletHistoryEntry {
   symbol: string;
   displayAmount: string;
   distributedAt: string;
+  claimed: boolean;
 }
 
 export interface WalletHistory {
   wallet: string;
   history: WalletHistoryEntry[];
-  pagination: {
-    page: number;
-    limit: number;
-    totalItems: number;
-    totalPages: number;
-  };
+  pageInfo: PageInfo;
 }
 
+export const WALLET_HISTORY_SORTS = {
+  time: {
+    name: 'history.time',
+    columns: [
+      ['rc.created_at', 'timestamp'],
+      ['rpp.reward_id', 'text'],
+    ],
+  },
+  amount: {
+    name: 'history.amount',
+    columns: [
+      ['rpp.payout_amount', 'numeric'],
+      ['rpp.reward_id', 'text'],
+    ],
+  },
+} satisfies Record<string, SortKey>;
+
+export type WalletHistorySort = keyof typeof WALLET_HISTORY_SORTS;
+
 /**
  * Get the most recent window that has weight data
  */
@@ -219,51 +245,78 @@ export async function getWalletData(walletAddress: string): Promise<WalletData |
 }
 
 /**
- * Get paginated reward history for a wallet
+ * Get a page of reward history for a wallet
+ *
+ * Supports the distributionId, deployment (rewards distributed on it),
+ * from/to (reward creation time), minAmount and claimed filters.
  */
 export async function getWalletHistory(
   walletAddress: string,
-  page: number = 1,
-  limit: number = 10
+  params: ListParams<WalletHistorySort>,
+  filters: ListFilters
 ): Promise<WalletHistory> {
-  const offset = (page - 1) * limit;
-
-  // Get total count first (only production rewards)
-  const countResult = await pool.query<{ count: string }>(
-    `SELECT COUNT(*) as count
-     FROM reward_payouts_preview
-     WHERE wallet = $1 AND payout_amount > 0
-       AND reward_id ~ '^[A-Z]+_[0-9]{4}_W[0-9]{2}$'`,
-    [walletAddress]
-  );
+  const sort = WALLET_HISTORY_SORTS[params.sort];
+  const claimed = claimedSql('rpp.reward_id', 'rpp.wallet');
+  const values: unknown[] = [walletAddress];
+  const conditions: string[] = [];
 
-  const totalItems = parseInt(countResult.rows[0]?.count || '0', 10);
-  const totalPages = Math.ceil(totalItems / limit);
+  if (filters.distributionId !== null) {
+    values.push(filters.distributionId);
+    conditions.push(
+      `AND rpp.reward_id IN (SELECT reward_id FROM merkle_distributions WHERE distribution_id = $${values.length})`
+    );
+  }
+  if (filters.deployment !== null) {
+    values.push(filters.deployment);
+    conditions.push(
+      `AND rpp.reward_id IN (SELECT reward_id FROM merkle_distributions WHERE deployment = $${values.length})`
+    );
+  }
+  if (filters.from !== null) {
+    values.push(filters.from);
+    conditions.push(`AND EXTRACT(EPOCH FROM rc.created_at) >= $${values.length}`);
+  }
+  if (filters.to !== null) {
+    values.push(filters.to);
+    conditions.push(`AND EXTRACT(EPOCH FROM rc.created_at) <= $${values.length}`);
+  }
+  if (filters.minAmount !== null) {
+    values.push(filters.minAmount);
+    conditions.push(`AND rpp.payout_amount >= $${values.length}::numeric`);
+  }
+  if (filters.claimed !== null) {
+    conditions.push(filters.claimed ? `AND ${claimed}` : `AND NOT ${claimed}`);
+  }
+  conditions.push(afterClause(sort, params, values));
 
-  // Get paginated history (only production rewards)
+  // Only production rewards
   const historyResult = await pool.query<{
     reward_id: string;
     window_id: string;
     payout_amount: string;
     mint: string;
     created_at: Date;
+    claimed: boolean;
   }>(
     `SELECT rpp.reward_id, rpp.window_id, rpp.payout_amount::text, rpp.mint,
-            rc.created_at
+            rc.created_at, ${claimed} AS claimed
+            ${cursorColumns(sort)}
      FROM reward_payouts_preview rpp
      JOIN reward_configs rc ON rpp.reward_id = rc.reward_id
      WHERE rpp.wallet = $1 AND rpp.payout_amount > 0
        AND rpp.reward_id ~ '^[A-Z]+_[0-9]{4}_W[0-9]{2}$'
-     ORDER BY rc.created_at DESC
-     LIMIT $2 OFFSET $3`,
-    [walletAddress, limit, offset]
+       ${conditions.join('\n       ')}
+     ${orderClause(sort, params, values)}`,
+    values
   );
 
+  const page = pageOf(historyResult.rows, sort, params);
+
   const symbol = process.env.WEEKLY_REWARD_SYMBOL || 'ORE';
   const tokenInfo = getTokenBySymbol(symbol);
   const decimals = tokenInfo?.decimals || 11; // Default to 11 for ORE
 
-  const history: WalletHistoryEntry[] = historyResult.rows.map((row) => {
+  const history: WalletHistoryEntry[] = page.rows.map((row) => {
     const displayAmount = (Number(row.payout_amount) / 10 ** decimals).toFixed(decimals);
 
     return {
@@ -273,17 +326,13 @@ export async function getWalletHistory(
       symbol,
       displayAmount,
       distributedAt: row.created_at.toISOString(),
+      claimed: row.claimed,
     };
   });
 
   return {
     wallet: walletAddress,
     history,
-    pagination: {
-      page,
-      limit,
-      totalItems,
-      totalPages,
-    },
+    pageInfo: page.pageInfo,
   };
 }
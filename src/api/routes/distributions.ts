@@ -0,0 +1,90 @@
+import { Router, Request, Response } from 'express';
+import { asyncHandler, createError } from '../middleware/error-handler';
+import {
+  getClaimHistogram,
+  getDistributionClaimSummary,
+  getTopUnclaimed,
+  HISTOGRAM_BUCKETS,
+  HistogramBucket,
+} from '../queries/distributions';
+
+export const distributionsRouter = Router();
+
+/**
+ * Validate the distribution ID path parameter (32 bytes as hex)
+ */
+function distributionIdParam(req: Request): string {
+  const distributionId = req.params.distributionId as string;
+  if (!/^[0-9a-fA-F]{64}$/.test(distributionId)) {
+    throw createError('Invalid distribution ID. Expected 64 hex characters', 400, 'INVALID_DISTRIBUTION_ID');
+  }
+  return distributionId;
+}
+
+/**
+ * GET /api/distributions/:distributionId/claims
+ * Returns the share of a distribution claimed, by recipient count and by value
+ */
+distributionsRouter.get(
+  '/:distributionId/claims',
+  asyncHandler(async (req: Request, res: Response) => {
+    const distributionId = distributionIdParam(req);
+
+    const data = await getDistributionClaimSummary(distributionId);
+    if (!data) {
+      throw createError(`Distribution not found: ${distributionId}`, 404, 'DISTRIBUTION_NOT_FOUND');
+    }
+
+    // Cache for 1 minute
+    res.set('Cache-Control', 'public, max-age=60');
+    res.json(data);
+  })
+);
+
+/**
+ * GET /api/distributions/:distributionId/claims/histogram
+ * Returns claims per time bucket with running totals
+ * Query: bucket (hour|day|week, default day)
+ */
+distributionsRouter.get(
+  '/:distributionId/claims/histogram',
+  asyncHandler(async (req: Request, res: Response) => {
+    const distributionId = distributionIdParam(req);
+
+    const bucket = (req.query.bucket as string | undefined) ?? 'day';
+    if (!HISTOGRAM_BUCKETS.includes(bucket as HistogramBucket)) {
+      throw createError(`Invalid bucket. Expected one of: ${HISTOGRAM_BUCKETS.join(', ')}`, 400, 'INVALID_BUCKET');
+    }
+
+    const data = await getClaimHistogram(distributionId, bucket as HistogramBucket);
+    if (!data) {
+      throw createError(`Distribution not found: ${distributionId}`, 404, 'DISTRIBUTION_NOT_FOUND');
+    }
+
+    // Cache for 1 minute
+    res.set('Cache-Control', 'public, max-age=60');
+    res.json(data);
+  })
+);
+
+/**
+ * GET /api/distributions/:distributionId/unclaimed
+ * Returns the largest allocations not yet claimed
+ * Query: limit (1-100, default 10)
+ */
+distributionsRouter.get(
+  '/:distributionId/unclaimed',
+  asyncHandler(async (req: Request, res: Response) => {
+    const distributionId = distributionIdParam(req);
+    const limit = Math.min(100, Math.max(1, parseInt(req.query.limit as string, 10) || 10));
+
+    const data = await getTopUnclaimed(distributionId, limit);
+    if (!data) {
+      throw createError(`Distribution not found: ${distributionId}`, 404, 'DISTRIBUTION_NOT_FOUND');
+    }
+
+    // Cache for 1 minute
+    res.set('Cache-Control', 'public, max-age=60');
+    res.json(data);
+  })
+);
@@ -0,0 +1,104 @@
+import crypto from 'crypto';
+import { Router, Request, Response, NextFunction } from 'express';
+import { asyncHandler, createError } from '../middleware/error-handler';
+import { getFinanceExport } from '../queries/exports';
+import {
+  FINANCE_DATASETS,
+  FinanceDataset,
+  parseExportDate,
+  previousMonth,
+  startFinanceExport,
+} from '../../exports/finance';
+import { EXPORT_FORMATS, ExportFormat } from '../../exports/writers';
+
+export const exportsRouter = Router();
+
+/**
+ * Exports are only served with EXPORT_API_TOKEN set, to callers sending it
+ * as a bearer token
+ */
+function requireExportToken(req: Request, _res: Response, next: NextFunction): void {
+  const token = process.env.EXPORT_API_TOKEN;
+  if (!token) {
+    next(createError('Not found', 404, 'NOT_FOUND'));
+    return;
+  }
+
+  const expected = Buffer.from(`Bearer ${token}`);
+  const given = Buffer.from(req.get('Authorization') ?? '');
+  if (given.length !== expected.length || !crypto.timingSafeEqual(given, expected)) {
+    next(createError('Invalid or missing export token', 401, 'UNAUTHORIZED'));
+    return;
+  }
+  next();
+}
+
+exportsRouter.use(requireExportToken);
+
+/**
+ * POST /api/exports
+ * Starts finance exports to EXPORT_DESTINATION and returns their IDs
+ * Body: dataset (allocations|claims|reconciliation|all, default all),
+ *       format (csv|parquet, default csv), from, to (default: the previous month)
+ */
+exportsRouter.post(
+  '/',
+  asyncHandler(async (req: Request, res: Response) => {
+    const body = req.body ?? {};
+
+    const defaults = previousMonth();
+    let range;
+    try {
+      range = {
+        from: body.from ? parseExportDate(String(body.from), 'from') : defaults.from,
+        to: body.to ? parseExportDate(String(body.to), 'to') : defaults.to,
+      };
+    } catch (e: any) {
+      throw createError(e.message, 400, 'INVALID_RANGE');
+    }
+    if (range.from >= range.to) {
+      throw createError('from must be before to', 400, 'INVALID_RANGE');
+    }
+
+    const format = (body.format ?? 'csv') as ExportFormat;
+    if (!EXPORT_FORMATS.includes(format)) {
+      throw createError(`Invalid format. Expected one of: ${EXPORT_FORMATS.join(', ')}`, 400, 'INVALID_FORMAT');
+    }
+
+    const dataset = body.dataset ?? 'all';
+    const datasets = dataset === 'all' ? FINANCE_DATASETS : [dataset as FinanceDataset];
+    if (!datasets.every((d) => FINANCE_DATASETS.includes(d))) {
+      throw createError(`Invalid dataset. Expected one of: ${FINANCE_DATASETS.join(', ')}, all`, 400, 'INVALID_DATASET');
+    }
+
+    const exports = [];
+    for (const d of datasets) {
+      const { id, location } = await startFinanceExport({ dataset: d, format, ...range });
+      exports.push({ id, dataset: d, location, status: 'running' });
+    }
+
+    res.status(202).json({ exports });
+  })
+);
+
+/**
+ * GET /api/exports/:id
+ * Returns the status of an export started with POST /api/exports
+ */
+exportsRouter.get(
+  '/:id',
+  asyncHandler(async (req: Request, res: Response) => {
+    const id = req.params.id as string;
+    if (!/^\d{1,18}$/.test(id)) {
+      throw createError('Invalid export ID', 400, 'INVALID_EXPORT_ID');
+    }
+
+    const data = await getFinanceExport(id);
+    if (!data) {
+      throw createError(`Export not found: ${id}`, 404, 'EXPORT_NOT_FOUND');
+    }
+
+    res.set('Cache-Control', 'no-store');
+    res.json(data);
+  })
+);
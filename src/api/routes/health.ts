@@ -1,9 +1,39 @@
 import { Router, Request, Response } from 'express';
 import { pool } from '../../db';
+import { readiness } from '../../utils/health';
 import { asyncHandler } from '../middleware/error-handler';
 
 export const healthRouter = Router();
 
+// Kubernetes probes, mounted at the root
+export const probesRouter = Router();
+
+/**
+ * GET /healthz
+ * Liveness: the process is up and serving requests
+ */
+probesRouter.get('/healthz', (_req: Request, res: Response) => {
+  res.json({ status: 'ok' });
+});
+
+/**
+ * GET /readyz
+ * Readiness: the database answers and claim event ingestion of every
+ * deployment is at most READINESS_MAX_LAG_SLOTS behind (503 otherwise), so
+ * traffic isn't routed to an instance serving stale state
+ */
+probesRouter.get(
+  '/readyz',
+  asyncHandler(async (_req: Request, res: Response) => {
+    const result = await readiness();
+    res.status(result.ready ? 200 : 503).json({
+      status: result.ready ? 'ready' : 'not_ready',
+      timestamp: new Date().toISOString(),
+      checks: result.checks,
+    });
+  })
+);
+
 /**
  * GET /api/health
  * Health check endpoint for monitoring
@@ -0,0 +1,152 @@
+import { Router, Request, Response } from 'express';
+import { asyncHandler, createError } from '../middleware/error-handler';
+import {
+  getHolderDiff,
+  getHolderDiffWallets,
+  holderDiffExists,
+  HOLDER_DIFF_WALLET_SORTS,
+  listHolderDiffs,
+} from '../queries/holder-diffs';
+import { parseListParams } from '../queries/pagination';
+import { HOLDER_COHORTS, HolderCohort, HolderDiffKey, previousSnapshotEpoch } from '../../indexers/holder-diffs';
+import { HOLDER_DIFF_CONTENT_TYPES, holderDiffFileName, writeHolderDiff } from '../../exports/holder-diffs';
+import { EXPORT_FORMATS, ExportFormat } from '../../exports/writers';
+
+export const holderDiffsRouter = Router();
+
+/**
+ * Validate a mint address (base58, 32-44 characters)
+ */
+function parseMint(value: unknown): string {
+  if (typeof value !== 'string' || !/^[1-9A-HJ-NP-Za-km-z]{32,44}$/.test(value)) {
+    throw createError('Invalid mint address', 400, 'INVALID_MINT');
+  }
+  return value;
+}
+
+function parseEpoch(value: unknown, name: string): number {
+  if (typeof value !== 'string' || !/^\d{1,10}$/.test(value)) {
+    throw createError(`Invalid ${name}. Expected an epoch number`, 400, 'INVALID_EPOCH');
+  }
+  return Number(value);
+}
+
+/**
+ * The diff addressed by :mint/:toEpoch, from ?from or else the snapshot
+ * before it
+ */
+async function diffKey(req: Request): Promise<HolderDiffKey> {
+  const mint = parseMint(req.params.mint);
+  const toEpoch = parseEpoch(req.params.toEpoch, 'epoch');
+
+  const fromEpoch =
+    req.query.from !== undefined ? parseEpoch(req.query.from, 'from') : await previousSnapshotEpoch(mint, toEpoch);
+  if (fromEpoch === null) {
+    throw createError(`No snapshot of ${mint} before epoch ${toEpoch}`, 404, 'HOLDER_DIFF_NOT_FOUND');
+  }
+  if (fromEpoch >= toEpoch) {
+    throw createError('from must be before the epoch', 400, 'INVALID_EPOCH');
+  }
+  return { mint, fromEpoch, toEpoch };
+}
+
+/**
+ * GET /api/holder-diffs
+ * Returns the most recent holder diffs
+ * Query: mint, limit (1-100, default 20)
+ */
+holderDiffsRouter.get(
+  '/',
+  asyncHandler(async (req: Request, res: Response) => {
+    const mint = req.query.mint !== undefined ? parseMint(req.query.mint) : null;
+    const limit = Math.min(100, Math.max(1, parseInt(req.query.limit as string, 10) || 20));
+
+    const diffs = await listHolderDiffs(mint, limit);
+
+    // Cache for 5 minutes (diffs are built once per epoch)
+    res.set('Cache-Control', 'public, max-age=300');
+    res.json({ diffs });
+  })
+);
+
+/**
+ * GET /api/holder-diffs/:mint/:toEpoch
+ * Returns who entered and exited the holder set, and the balance change
+ * cohorts, between a snapshot and the one before it
+ * Query: from (epoch to compare against, default the previous snapshot)
+ */
+holderDiffsRouter.get(
+  '/:mint/:toEpoch',
+  asyncHandler(async (req: Request, res: Response) => {
+    const key = await diffKey(req);
+
+    const data = await getHolderDiff(key);
+    if (!data) {
+      throw createError(
+        `Holder diff not built: ${key.mint} epochs ${key.fromEpoch} to ${key.toEpoch}`,
+        404,
+        'HOLDER_DIFF_NOT_FOUND'
+      );
+    }
+
+    // Cache for 5 minutes
+    res.set('Cache-Control', 'public, max-age=300');
+    res.json(data);
+  })
+);
+
+/**
+ * GET /api/holder-diffs/:mint/:toEpoch/wallets
+ * Returns cursor-paginated wallets of a holder diff, or all of them as a
+ * file with format=csv|parquet
+ * Query: from, cohort, limit, cursor, sort (change|amount|wallet), order, format
+ */
+holderDiffsRouter.get(
+  '/:mint/:toEpoch/wallets',
+  asyncHandler(async (req: Request, res: Response) => {
+    const key = await diffKey(req);
+
+    const cohort = (req.query.cohort as string | undefined) ?? null;
+    if (cohort !== null && !HOLDER_COHORTS.includes(cohort as HolderCohort)) {
+      throw createError(`Invalid cohort. Expected one of: ${HOLDER_COHORTS.join(', ')}`, 400, 'INVALID_COHORT');
+    }
+
+    const format = req.query.format as string | undefined;
+    if (format !== undefined && !EXPORT_FORMATS.includes(format as ExportFormat)) {
+      throw createError(`Invalid format. Expected one of: ${EXPORT_FORMATS.join(', ')}`, 400, 'INVALID_FORMAT');
+    }
+
+    if (!(await holderDiffExists(key))) {
+      throw createError(
+        `Holder diff not built: ${key.mint} epochs ${key.fromEpoch} to ${key.toEpoch}`,
+        404,
+        'HOLDER_DIFF_NOT_FOUND'
+      );
+    }
+
+    if (format !== undefined) {
+      const exportFormat = format as ExportFormat;
+      res.set('Content-Type', HOLDER_DIFF_CONTENT_TYPES[exportFormat]);
+      res.set('Content-Disposition', `attachment; filename="${holderDiffFileName(key, exportFormat)}"`);
+      res.set('Cache-Control', 'public, max-age=300');
+      try {
+        await writeHolderDiff(
+          { ...key, format: exportFormat, cohorts: cohort ? [cohort as HolderCohort] : undefined },
+          res
+        );
+      } catch (e) {
+        // Once streaming has started the status can't change: cut the response short
+        if (!res.headersSent) throw e;
+        console.error(`❌ Holder diff export failed mid-stream: ${(e as Error).message}`);
+        res.destroy();
+      }
+      return;
+    }
+
+    const params = parseListParams(req.query, HOLDER_DIFF_WALLET_SORTS, 25, 100);
+    const data = await getHolderDiffWallets(key, cohort as HolderCohort | null, params);
+
+    res.set('Cache-Control', 'public, max-age=300');
+    res.json(data);
+  })
+);
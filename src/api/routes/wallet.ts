@@ -1,6 +1,13 @@
 import { Router, Request, Response } from 'express';
 import { asyncHandler, createError } from '../middleware/error-handler';
-import { getWalletData, getWalletHistory } from '../queries/wallet';
+import { getWalletData, getWalletHistory, WALLET_HISTORY_SORTS } from '../queries/wallet';
+import {
+  getClaimHistory,
+  getUnclaimedAllocations,
+  CLAIM_HISTORY_SORTS,
+  HISTORY_KINDS,
+} from '../queries/claims';
+import { parseListFilters, parseListParams } from '../queries/pagination';
 
 export const walletRouter = Router();
 
@@ -42,23 +49,77 @@ walletRouter.get(
 
 /**
  * GET /api/wallet/:address/history
- * Returns paginated reward history for a wallet
+ * Returns cursor-paginated reward history for a wallet
+ * Query: limit, cursor, sort (time|amount), order, distributionId, deployment, from, to, minAmount, claimed
  */
 walletRouter.get(
   '/:address/history',
   asyncHandler(async (req: Request, res: Response) => {
     const address = req.params.address as string;
-    const page = Math.max(1, parseInt(req.query.page as string, 10) || 1);
-    const limit = Math.min(50, Math.max(1, parseInt(req.query.limit as string, 10) || 10));
 
     if (!isValidWalletAddress(address)) {
       throw createError('Invalid wallet address', 400, 'INVALID_ADDRESS');
     }
 
-    const history = await getWalletHistory(address, page, limit);
+    const params = parseListParams(req.query, WALLET_HISTORY_SORTS, 10, 50);
+    const history = await getWalletHistory(address, params, parseListFilters(req.query));
 
     // Cache for 60 seconds (history changes less frequently)
     res.set('Cache-Control', 'public, max-age=60');
     res.json(history);
   })
 );
+
+/**
+ * GET /api/wallet/:address/claims
+ * Returns cursor-paginated on-chain claim history (amounts, timestamps, signatures)
+ * Query: limit, cursor, sort (time|amount), order, distributionId, deployment, from, to, minAmount, kind
+ */
+walletRouter.get(
+  '/:address/claims',
+  asyncHandler(async (req: Request, res: Response) => {
+    const address = req.params.address as string;
+
+    if (!isValidWalletAddress(address)) {
+      throw createError('Invalid wallet address', 400, 'INVALID_ADDRESS');
+    }
+
+    // Comma-separated event kinds, e.g. kind=claim,claim_referral
+    const kinds =
+      typeof req.query.kind === 'string' && req.query.kind.length > 0
+        ? req.query.kind.split(',').map((k) => k.trim())
+        : HISTORY_KINDS;
+    if (!kinds.every((k) => HISTORY_KINDS.includes(k))) {
+      throw createError(`Invalid kind. Expected any of: ${HISTORY_KINDS.join(', ')}`, 400, 'INVALID_FILTER');
+    }
+
+    const params = parseListParams(req.query, CLAIM_HISTORY_SORTS, 10, 50);
+    const claims = await getClaimHistory(address, params, parseListFilters(req.query), kinds);
+
+    // Cache for 30 seconds (new claims should show up quickly)
+    res.set('Cache-Control', 'public, max-age=30');
+    res.json(claims);
+  })
+);
+
+/**
+ * GET /api/wallet/:address/unclaimed
+ * Returns allocations not yet claimed across all open distributions
+ * Query: distributionId, deployment, minAmount
+ */
+walletRouter.get(
+  '/:address/unclaimed',
+  asyncHandler(async (req: Request, res: Response) => {
+    const address = req.params.address as string;
+
+    if (!isValidWalletAddress(address)) {
+      throw createError('Invalid wallet address', 400, 'INVALID_ADDRESS');
+    }
+
+    const unclaimed = await getUnclaimedAllocations(address, parseListFilters(req.query));
+
+    // Cache for 30 seconds (new claims should show up quickly)
+    res.set('Cache-Control', 'public, max-age=30');
+    res.json(unclaimed);
+  })
+);
@@ -0,0 +1,144 @@
+// Deployment Registry
+// Distributor program deployments indexed by this instance: one program ID on one cluster each
+
+import fs from 'fs';
+import { PublicKey } from '@solana/web3.js';
+import { MERKLE_DISTRIBUTOR_PROGRAM_ID } from '../merkle/types';
+import { getRpcConfigFromEnv, RpcConfig } from '../utils/rpc';
+import { getGeyserConfigFromEnv, GeyserConfig } from '../indexers/geyser';
+import { getClaimEventsCommitmentFromEnv } from '../indexers/claim-events';
+
+// Deployment configured from the environment when DEPLOYMENTS_CONFIG is unset;
+// rows indexed before deployments existed belong to it too
+export const DEFAULT_DEPLOYMENT = 'default';
+
+const NAME_PATTERN = /^[a-z0-9][a-z0-9_-]*$/;
+
+export type DeploymentConfig = {
+  // Namespaces every stored row and API response; keep it stable
+  name: string;
+  // Cluster label, reported as is (mainnet-beta, devnet, ...)
+  cluster: string;
+  programId: string;
+  rpc: RpcConfig;
+  // Websocket endpoint for logsSubscribe (default: derived from the RPC URL)
+  wsUrl?: string;
+  // Geyser stream; without it events are ingested via logsSubscribe
+  geyser: GeyserConfig | null;
+};
+
+type DeploymentConfigFile = {
+  name: string;
+  cluster: string;
+  programId: string;
+  rpcUrl?: string;
+  rpcUrlEnv?: string;
+  backupRpcUrl?: string;
+  backupRpcUrlEnv?: string;
+  wsUrl?: string;
+  geyserEndpoint?: string;
+  geyserTokenEnv?: string;
+};
+
+// Literal value, or the environment variable named by `<key>Env` (URLs often embed API keys)
+function valueOrEnv(entry: DeploymentConfigFile, key: 'rpcUrl' | 'backupRpcUrl'): string | undefined {
+  const envName = entry[`${key}Env`];
+  return envName ? process.env[envName] || undefined : entry[key];
+}
+
+function fromFileEntry(entry: DeploymentConfigFile): DeploymentConfig {
+  if (!entry.name) throw new Error('Deployment config entry without a name');
+  const name = entry.name;
+  if (!NAME_PATTERN.test(name)) {
+    throw new Error(`${name}: names are lowercase letters, digits, - and _`);
+  }
+
+  if (!entry.cluster) throw new Error(`${name}: missing "cluster"`);
+  if (!entry.programId) throw new Error(`${name}: missing "programId"`);
+  new PublicKey(entry.programId);
+
+  const primaryUrl = valueOrEnv(entry, 'rpcUrl');
+  if (!primaryUrl) {
+    throw new Error(`${name}: set "rpcUrl" or "rpcUrlEnv"${entry.rpcUrlEnv ? ` (${entry.rpcUrlEnv} is empty)` : ''}`);
+  }
+
+  return {
+    name,
+    cluster: entry.cluster,
+    programId: entry.programId,
+    rpc: {
+      primaryUrl,
+      backupUrl: valueOrEnv(entry, 'backupRpcUrl'),
+      commitment: 'confirmed',
+      maxRetries: 3,
+      retryDelayMs: 1000,
+    },
+    wsUrl: entry.wsUrl,
+    geyser: entry.geyserEndpoint
+      ? {
+          endpoint: entry.geyserEndpoint,
+          xToken: entry.geyserTokenEnv ? process.env[entry.geyserTokenEnv] || undefined : undefined,
+          commitment: getClaimEventsCommitmentFromEnv(),
+        }
+      : null,
+  };
+}
+
+/**
+ * The single deployment described by SOLANA_RPC_URL, MERKLE_PROGRAM_ID,
+ * GEYSER_ENDPOINT etc.
+ */
+function fromEnv(): DeploymentConfig {
+  return {
+    name: DEFAULT_DEPLOYMENT,
+    cluster: process.env.SOLANA_CLUSTER || 'mainnet-beta',
+    programId: process.env.MERKLE_PROGRAM_ID || MERKLE_DISTRIBUTOR_PROGRAM_ID.toBase58(),
+    rpc: getRpcConfigFromEnv(),
+    wsUrl: process.env.SOLANA_WS_URL || undefined,
+    geyser: getGeyserConfigFromEnv(),
+  };
+}
+
+let cached: DeploymentConfig[] | undefined;
+
+/**
+ * Deployments from the JSON file at DEPLOYMENTS_CONFIG, or the single
+ * `default` deployment configured from the environment if unset
+ */
+export function loadDeploymentConfigs(): DeploymentConfig[] {
+  if (cached) return cached;
+
+  const path = process.env.DEPLOYMENTS_CONFIG;
+  if (!path) return (cached = [fromEnv()]);
+
+  const entries = JSON.parse(fs.readFileSync(path, 'utf8'));
+  if (!Array.isArray(entries) || entries.length === 0) {
+    throw new Error(`${path}: expected a non-empty array of deployments`);
+  }
+
+  const deployments = entries.map(fromFileEntry);
+  const names = deployments.map((d) => d.name);
+  const duplicate = names.find((n, i) => names.indexOf(n) !== i);
+  if (duplicate) throw new Error(`${path}: duplicate name ${duplicate}`);
+
+  return (cached = deployments);
+}
+
+/**
+ * The deployment called `name`, or the only one configured if omitted
+ */
+export function getDeploymentConfig(name?: string): DeploymentConfig {
+  const deployments = loadDeploymentConfigs();
+  if (name === undefined) {
+    if (deployments.length > 1) {
+      throw new Error(`Several deployments configured (${deployments.map((d) => d.name).join(', ')}); pick one`);
+    }
+    return deployments[0];
+  }
+
+  const deployment = deployments.find((d) => d.name === name);
+  if (!deployment) {
+    throw new Error(`Unknown deployment ${name} (configured: ${deployments.map((d) => d.name).join(', ')})`);
+  }
+  return deployment;
+}
@@ -0,0 +1,145 @@
+// LST Registry
+// The liquid staking tokens this deployment tracks, each with its own pipeline
+
+import fs from 'fs';
+import { PublicKey } from '@solana/web3.js';
+import { getTokenBySymbol } from './tokens';
+import type { StakePoolKind } from '../indexers/exchange-rate';
+import type { LendingProtocol, LiquidityProtocol } from '../indexers/protocol-positions';
+
+export type LstConfig = {
+  symbol: string;
+  mint: string;
+  // Stake pool the LST -> SOL rate is read from (null: rates not recorded)
+  stakePool: { kind: StakePoolKind; address?: string } | null;
+  // Cron expression for the epoch snapshot check (a no-op until a new epoch)
+  schedule: string;
+  // Cron expression for intra-epoch TWAB balance samples (null: not sampled)
+  twabSchedule: string | null;
+  // Lending markets whose deposits are attributed back to depositors
+  lending: LendingProtocol[];
+  // AMMs whose LP positions are attributed back to position owners
+  liquidity: LiquidityProtocol[];
+  // Vote accounts whose native stakers are credited alongside holders
+  nativeStake: string[];
+  // Wallets excluded from this LST's allocations (on top of IGNORE_WALLETS)
+  exclude: string[];
+  // Distribution series: epoch rewards default to `${series}_EPOCH_<epoch>`
+  series: string;
+};
+
+const DEFAULT_SCHEDULE = '*/10 * * * *';
+
+type LstConfigFile = {
+  symbol: string;
+  mint?: string;
+  stakePool?: { kind: StakePoolKind; address?: string };
+  schedule?: string;
+  twabSchedule?: string;
+  lending?: LendingProtocol[];
+  liquidity?: LiquidityProtocol[];
+  nativeStake?: string[];
+  exclude?: string[];
+  series?: string;
+};
+
+const LENDING_PROTOCOLS: LendingProtocol[] = ['kamino', 'marginfi'];
+const LIQUIDITY_PROTOCOLS: LiquidityProtocol[] = ['orca', 'raydium'];
+
+function fromFileEntry(entry: LstConfigFile): LstConfig {
+  if (!entry.symbol) throw new Error('LST config entry without a symbol');
+  const symbol = entry.symbol.toUpperCase();
+
+  const mint = entry.mint ?? getTokenBySymbol(symbol)?.mint;
+  if (!mint) throw new Error(`${symbol}: unknown token, set "mint"`);
+  new PublicKey(mint);
+
+  const lending = entry.lending ?? [];
+  const unknown = lending.find((p) => !LENDING_PROTOCOLS.includes(p));
+  if (unknown) throw new Error(`${symbol}: unknown lending protocol ${unknown} (expected kamino or marginfi)`);
+
+  const liquidity = entry.liquidity ?? [];
+  const unknownAmm = liquidity.find((p) => !LIQUIDITY_PROTOCOLS.includes(p));
+  if (unknownAmm) throw new Error(`${symbol}: unknown liquidity protocol ${unknownAmm} (expected orca or raydium)`);
+
+  const nativeStake = entry.nativeStake ?? [];
+  nativeStake.forEach((voteAccount) => new PublicKey(voteAccount));
+
+  return {
+    symbol,
+    mint,
+    stakePool: entry.stakePool ?? null,
+    schedule: entry.schedule ?? DEFAULT_SCHEDULE,
+    twabSchedule: entry.twabSchedule ?? null,
+    lending,
+    liquidity,
+    nativeStake,
+    exclude: entry.exclude ?? [],
+    series: entry.series ?? symbol,
+  };
+}
+
+/**
+ * LSTs from the JSON file at LST_CONFIG
+ *
+ * Without LST_CONFIG a single LST is built from EPOCH_SNAPSHOT_MINT (or
+ * PRIMARY_TOKEN_MINT), STAKE_POOL_KIND / STAKE_POOL_ADDRESS and
+ * NATIVE_STAKE_VALIDATORS, as before.
+ */
+export function loadLstConfigs(): LstConfig[] {
+  const path = process.env.LST_CONFIG;
+  if (!path) {
+    const mint = process.env.EPOCH_SNAPSHOT_MINT || process.env.PRIMARY_TOKEN_MINT;
+    if (!mint) throw new Error('Missing LST_CONFIG (or EPOCH_SNAPSHOT_MINT / PRIMARY_TOKEN_MINT)');
+    const kind = process.env.STAKE_POOL_KIND as StakePoolKind | undefined;
+    const symbol = process.env.PRIMARY_TOKEN_SYMBOL || 'LST';
+    const nativeStake = (process.env.NATIVE_STAKE_VALIDATORS || '')
+      .split(',')
+      .map((v) => v.trim())
+      .filter(Boolean);
+    return [
+      {
+        symbol,
+        mint,
+        stakePool: kind ? { kind, address: process.env.STAKE_POOL_ADDRESS || undefined } : null,
+        schedule: DEFAULT_SCHEDULE,
+        twabSchedule: process.env.TWAB_SCHEDULE || null,
+        lending: [],
+        liquidity: [],
+        nativeStake,
+        exclude: [],
+        series: symbol,
+      },
+    ];
+  }
+
+  const entries = JSON.parse(fs.readFileSync(path, 'utf8'));
+  if (!Array.isArray(entries) || entries.length === 0) {
+    throw new Error(`${path}: expected a non-empty array of LSTs`);
+  }
+
+  const lsts = entries.map(fromFileEntry);
+  for (const key of ['symbol', 'mint', 'series'] as const) {
+    const values = lsts.map((lst) => lst[key]);
+    const duplicate = values.find((v, i) => values.indexOf(v) !== i);
+    if (duplicate) throw new Error(`${path}: duplicate ${key} ${duplicate}`);
+  }
+  return lsts;
+}
+
+/**
+ * The configured LST with `symbol`, or the only one if `symbol` is omitted
+ */
+export function getLstConfig(symbol?: string): LstConfig {
+  const lsts = loadLstConfigs();
+  if (!symbol) {
+    if (lsts.length > 1) {
+      throw new Error(`Several LSTs configured (${lsts.map((l) => l.symbol).join(', ')}); pick one with --lst`);
+    }
+    return lsts[0];
+  }
+
+  const lst = lsts.find((l) => l.symbol === symbol.toUpperCase());
+  if (!lst) throw new Error(`LST not configured: ${symbol}`);
+  return lst;
+}
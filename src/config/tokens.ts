@@ -15,6 +15,24 @@ export const KNOWN_TOKENS: Record<string, TokenInfo> = {
     symbol: 'INDIESOL',
     name: 'IndieSOL',
   },
+  MSOL: {
+    mint: 'mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So',
+    decimals: 9,
+    symbol: 'MSOL',
+    name: 'Marinade staked SOL',
+  },
+  JITOSOL: {
+    mint: 'J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn',
+    decimals: 9,
+    symbol: 'JITOSOL',
+    name: 'Jito Staked SOL',
+  },
+  BSOL: {
+    mint: 'bSo13r4TkiE4KumL71LsHTPpL2euBYLFx6h9HP3piy1',
+    decimals: 9,
+    symbol: 'BSOL',
+    name: 'BlazeStake Staked SOL',
+  },
   ORE: {
     mint: 'oreoU2P8bN6jkk3jbaiVxYnG1dCXcYxwhwyK9jSybcp',
     decimals: 11,
@@ -0,0 +1,90 @@
+// Webhook Registry
+// Downstream endpoints notified of finalized claim and admin events
+
+import fs from 'fs';
+
+// Event types a webhook can subscribe to, and the claim event kinds behind them
+export const WEBHOOK_EVENT_KINDS = {
+  Claimed: ['claim', 'claim_referral', 'escrow'],
+  Clawback: ['clawback'],
+  Paused: ['pause'],
+  Unpaused: ['unpause'],
+  OperatorChanged: ['operator_change'],
+} as const;
+
+export type WebhookEventType = keyof typeof WEBHOOK_EVENT_KINDS;
+
+const EVENT_TYPES = Object.keys(WEBHOOK_EVENT_KINDS) as WebhookEventType[];
+
+export type WebhookConfig = {
+  // Identifies the webhook in webhook_deliveries; keep it stable
+  name: string;
+  url: string;
+  // HMAC-SHA256 key for the X-Webhook-Signature header
+  secret: string;
+  events: WebhookEventType[];
+  // Only deliver events of these distribution PDAs (empty: all)
+  distributions: string[];
+  // Only deliver events of these deployments (empty: all)
+  deployments: string[];
+};
+
+type WebhookConfigFile = {
+  name: string;
+  url: string;
+  secret?: string;
+  secretEnv?: string;
+  events?: WebhookEventType[];
+  distributions?: string[];
+  deployments?: string[];
+};
+
+function fromFileEntry(entry: WebhookConfigFile): WebhookConfig {
+  if (!entry.name) throw new Error('Webhook config entry without a name');
+  const name = entry.name;
+
+  if (!entry.url) throw new Error(`${name}: missing "url"`);
+  new URL(entry.url);
+
+  const secret = entry.secretEnv ? process.env[entry.secretEnv] : entry.secret;
+  if (!secret) {
+    throw new Error(`${name}: set "secret" or "secretEnv"${entry.secretEnv ? ` (${entry.secretEnv} is empty)` : ''}`);
+  }
+
+  const events = entry.events ?? EVENT_TYPES;
+  const unknown = events.find((e) => !EVENT_TYPES.includes(e));
+  if (unknown) throw new Error(`${name}: unknown event ${unknown} (expected ${EVENT_TYPES.join(', ')})`);
+
+  return {
+    name,
+    url: entry.url,
+    secret,
+    events,
+    distributions: entry.distributions ?? [],
+    deployments: entry.deployments ?? [],
+  };
+}
+
+let cached: WebhookConfig[] | undefined;
+
+/**
+ * Webhooks from the JSON file at WEBHOOKS_CONFIG (none if unset)
+ */
+export function loadWebhookConfigs(): WebhookConfig[] {
+  if (cached) return cached;
+
+  const path = process.env.WEBHOOKS_CONFIG;
+  if (!path) return (cached = []);
+
+  const entries = JSON.parse(fs.readFileSync(path, 'utf8'));
+  if (!Array.isArray(entries)) {
+    throw new Error(`${path}: expected an array of webhooks`);
+  }
+
+  const webhooks = entries.map(fromFileEntry);
+  const names = webhooks.map((w) => w.name);
+  const duplicate = names.find((n, i) => names.indexOf(n) !== i);
+  if (duplicate) throw new Error(`${path}: duplicate name ${duplicate}`);
+
+  return (cached = webhooks);
+}
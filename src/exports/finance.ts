@@ -0,0 +1,297 @@
+// src/exports/finance.ts
+// Month-end finance exports: allocations, claims and reconciliation status over a date range
+
+import Cursor from 'pg-cursor';
+import { pool } from '../db';
+import { getIgnoredWalletsArray } from '../api/queries/ignored-wallets';
+import { Column, destinationPath, ExportFormat, openDestination, openRowWriter, Row } from './writers';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Rows read from the database per round trip
+const CURSOR_BATCH_SIZE = 5000;
+
+const DEFAULT_DESTINATION = 'exports';
+
+const CONTENT_TYPES: Record<ExportFormat, string> = {
+  csv: 'text/csv',
+  parquet: 'application/vnd.apache.parquet',
+};
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type FinanceDataset = 'allocations' | 'claims' | 'reconciliation';
+
+export const FINANCE_DATASETS: FinanceDataset[] = ['allocations', 'claims', 'reconciliation'];
+
+export type ExportRange = {
+  // Inclusive
+  from: Date;
+  // Exclusive
+  to: Date;
+};
+
+export type FinanceExportOptions = ExportRange & {
+  dataset: FinanceDataset;
+  format: ExportFormat;
+  // File path or s3://bucket/key; defaults to a dated name under EXPORT_DESTINATION
+  location?: string;
+};
+
+export type FinanceExportResult = {
+  dataset: FinanceDataset;
+  location: string;
+  rows: number;
+};
+
+type DatasetQuery = {
+  columns: Column[];
+  sql: string;
+  params: (range: ExportRange) => unknown[];
+};
+
+// ---------------------------
+// Datasets
+// ---------------------------
+
+const DATASETS: Record<FinanceDataset, DatasetQuery> = {
+  // Every leaf of the distributions created in the range (ignored wallets are left out of every tree)
+  allocations: {
+    columns: [
+      ['distribution_id', 'text'],
+      ['reward_id', 'text'],
+      ['window_id', 'text'],
+      ['mint', 'text'],
+      ['deployment', 'text'],
+      ['distribution', 'text'],
+      ['status', 'text'],
+      ['created_at', 'timestamp'],
+      ['wallet', 'text'],
+      ['amount', 'amount'],
+    ],
+    sql: `
+      SELECT d.distribution_id, d.reward_id, d.window_id, d.mint, d.deployment, d.on_chain_address AS distribution,
+             d.status, d.created_at, p.wallet, p.payout_amount::text AS amount
+      FROM merkle_distributions d
+      JOIN reward_payouts_preview p ON p.reward_id = d.reward_id
+      WHERE d.created_at >= $1 AND d.created_at < $2
+        AND p.payout_amount > 0
+        AND NOT (p.wallet = ANY($3))
+      ORDER BY d.created_at, d.distribution_id, p.wallet
+    `,
+    params: ({ from, to }) => [from, to, getIgnoredWalletsArray()],
+  },
+
+  // Finalized distributor events with a block time in the range
+  claims: {
+    columns: [
+      ['block_time', 'timestamp'],
+      ['slot', 'integer'],
+      ['signature', 'text'],
+      ['event_index', 'integer'],
+      ['kind', 'text'],
+      ['deployment', 'text'],
+      ['distribution', 'text'],
+      ['distribution_id', 'text'],
+      ['reward_id', 'text'],
+      ['mint', 'text'],
+      ['recipient', 'text'],
+      ['amount', 'amount'],
+      ['bonus', 'amount'],
+      ['referrer', 'text'],
+      ['destination', 'text'],
+    ],
+    sql: `
+      SELECT e.block_time, e.slot::text, e.signature, e.event_index, e.kind, e.deployment, e.distribution,
+             d.distribution_id, d.reward_id, d.mint, e.recipient, e.amount::text, e.bonus::text,
+             e.referrer, e.destination
+      FROM merkle_claim_events e
+      LEFT JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+      WHERE e.block_time >= $1 AND e.block_time < $2
+      ORDER BY e.slot, e.signature, e.event_index
+    `,
+    params: ({ from, to }) => [from, to],
+  },
+
+  // Each distribution live during the range, with its indexed totals as of
+  // the end of the range and the reconciler's divergences open during it
+  reconciliation: {
+    columns: [
+      ['distribution_id', 'text'],
+      ['reward_id', 'text'],
+      ['mint', 'text'],
+      ['deployment', 'text'],
+      ['distribution', 'text'],
+      ['status', 'text'],
+      ['total_amount', 'amount'],
+      ['claimed_amount', 'amount'],
+      ['claimed_count', 'integer'],
+      ['clawed_back_amount', 'amount'],
+      ['unclaimed_amount', 'amount'],
+      ['divergences', 'text'],
+      ['reconciled', 'boolean'],
+    ],
+    sql: `
+      SELECT d.distribution_id, d.reward_id, d.mint, d.deployment, d.on_chain_address AS distribution, d.status,
+             d.total_amount::text,
+             COALESCE(c.claimed, 0)::text AS claimed_amount,
+             c.claims AS claimed_count,
+             COALESCE(c.clawed_back, 0)::text AS clawed_back_amount,
+             (d.total_amount - COALESCE(c.claimed, 0) - COALESCE(c.clawed_back, 0))::text AS unclaimed_amount,
+             v.fields AS divergences,
+             v.fields IS NULL AS reconciled
+      FROM merkle_distributions d
+      LEFT JOIN LATERAL (
+        SELECT SUM(e.amount) FILTER (WHERE e.kind IN ('claim', 'claim_referral', 'escrow')) AS claimed,
+               COUNT(*) FILTER (WHERE e.kind IN ('claim', 'claim_referral', 'escrow', 'renounce')) AS claims,
+               SUM(e.amount) FILTER (WHERE e.kind = 'clawback') AS clawed_back
+        FROM merkle_claim_events e
+        WHERE e.deployment = d.deployment AND e.distribution = d.on_chain_address AND e.block_time < $2
+      ) c ON true
+      LEFT JOIN LATERAL (
+        SELECT string_agg(DISTINCT r.field, ';') AS fields
+        FROM merkle_reconciliation_divergences r
+        WHERE r.deployment = d.deployment
+          AND r.distribution = d.on_chain_address
+          AND r.first_seen_at < $2
+          AND (r.resolved_at IS NULL OR r.resolved_at >= $1)
+      ) v ON true
+      WHERE d.created_at < $2
+        AND (d.completed_at IS NULL OR d.completed_at >= $1)
+      ORDER BY d.created_at, d.distribution_id
+    `,
+    params: ({ from, to }) => [from, to],
+  },
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * YYYY-MM-DD (UTC midnight) or a full ISO timestamp
+ */
+export function parseExportDate(value: string, name: string): Date {
+  const date = new Date(/^\d{4}-\d{2}-\d{2}$/.test(value) ? `${value}T00:00:00Z` : value);
+  if (Number.isNaN(date.getTime())) throw new Error(`Invalid ${name}: ${value}`);
+  return date;
+}
+
+/**
+ * The previous calendar month (UTC), the default month-end range
+ */
+export function previousMonth(now = new Date()): ExportRange {
+  const to = new Date(Date.UTC(now.getUTCFullYear(), now.getUTCMonth(), 1));
+  const from = new Date(Date.UTC(now.getUTCFullYear(), now.getUTCMonth() - 1, 1));
+  return { from, to };
+}
+
+export function validateRange({ from, to }: ExportRange): void {
+  if (from >= to) throw new Error(`Empty range: ${from.toISOString()} is not before ${to.toISOString()}`);
+}
+
+/**
+ * Default file name: <dataset>_<from>_<to>.<format>, dates as YYYY-MM-DD
+ * (or full timestamps when the range isn't whole days)
+ */
+export function exportFileName(dataset: FinanceDataset, format: ExportFormat, { from, to }: ExportRange): string {
+  const stamp = (d: Date) => {
+    const iso = d.toISOString();
+    return iso.endsWith('T00:00:00.000Z') ? iso.slice(0, 10) : iso.replace(/[:.]/g, '-');
+  };
+  return `${dataset}_${stamp(from)}_${stamp(to)}.${format}`;
+}
+
+function defaultLocation(options: FinanceExportOptions): string {
+  return destinationPath(
+    process.env.EXPORT_DESTINATION || DEFAULT_DESTINATION,
+    exportFileName(options.dataset, options.format, options)
+  );
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Stream one dataset over a date range to a file or S3-compatible store
+ *
+ * Rows are read with a cursor and written as they arrive, so exports of any
+ * size run in constant memory. A failed export removes the partial file (or
+ * aborts the upload).
+ */
+export async function runFinanceExport(options: FinanceExportOptions): Promise<FinanceExportResult> {
+  validateRange(options);
+  const query = DATASETS[options.dataset];
+  const location = options.location ?? defaultLocation(options);
+
+  const destination = openDestination(location, CONTENT_TYPES[options.format]);
+  const client = await pool.connect();
+  let rows = 0;
+  try {
+    const writer = await openRowWriter(options.format, query.columns, destination.stream);
+    const cursor = client.query(new Cursor<Row>(query.sql, query.params(options)));
+    try {
+      for (;;) {
+        const batch = await cursor.read(CURSOR_BATCH_SIZE);
+        if (batch.length === 0) break;
+        for (const row of batch) await writer.write(row);
+        rows += batch.length;
+      }
+    } finally {
+      await cursor.close();
+    }
+    await writer.close();
+    await destination.complete();
+  } catch (e) {
+    await destination.abort().catch(() => undefined);
+    throw e;
+  } finally {
+    client.release();
+  }
+
+  return { dataset: options.dataset, location, rows };
+}
+
+/**
+ * Record an export in finance_exports and run it in the background
+ *
+ * For callers that can't wait on the stream (the API); the row tracks it
+ * from running to completed or failed.
+ */
+export async function startFinanceExport(options: FinanceExportOptions): Promise<{ id: string; location: string }> {
+  validateRange(options);
+  const location = options.location ?? defaultLocation(options);
+
+  const { rows } = await pool.query<{ id: string }>(
+    `
+    INSERT INTO finance_exports (dataset, format, range_from, range_to, location, status)
+    VALUES ($1, $2, $3, $4, $5, 'running')
+    RETURNING id::text
+    `,
+    [options.dataset, options.format, options.from, options.to, location]
+  );
+  const id = rows[0].id;
+
+  runFinanceExport({ ...options, location })
+    .then((result) =>
+      pool.query(
+        `UPDATE finance_exports SET status = 'completed', rows = $2, completed_at = NOW() WHERE id = $1`,
+        [id, result.rows]
+      )
+    )
+    .catch((e: any) => {
+      console.error(`❌ Finance export ${id} (${options.dataset}) failed: ${e.message}`);
+      return pool.query(
+        `UPDATE finance_exports SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1`,
+        [id, e.message]
+      );
+    })
+    .catch((e: any) => console.error(`❌ Failed to record finance export ${id}: ${e.message}`));
+
+  return { id, location };
+}
@@ -0,0 +1,125 @@
+// src/exports/holder-diffs.ts
+// Holder diff wallets (see src/indexers/holder-diffs.ts) to CSV or Parquet
+
+import { Writable } from 'stream';
+import Cursor from 'pg-cursor';
+import { pool } from '../db';
+import { HolderCohort, HolderDiffKey } from '../indexers/holder-diffs';
+import { Column, destinationPath, ExportFormat, openDestination, openRowWriter, Row } from './writers';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Rows read from the database per round trip
+const CURSOR_BATCH_SIZE = 5000;
+
+const DEFAULT_DESTINATION = 'exports';
+
+export const HOLDER_DIFF_CONTENT_TYPES: Record<ExportFormat, string> = {
+  csv: 'text/csv',
+  parquet: 'application/vnd.apache.parquet',
+};
+
+const COLUMNS: Column[] = [
+  ['mint', 'text'],
+  ['from_epoch', 'integer'],
+  ['to_epoch', 'integer'],
+  ['wallet', 'text'],
+  ['cohort', 'text'],
+  ['amount_from', 'amount'],
+  ['amount_to', 'amount'],
+  ['change', 'amount'],
+];
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type HolderDiffExportOptions = HolderDiffKey & {
+  format: ExportFormat;
+  // Only these cohorts (default: all)
+  cohorts?: HolderCohort[];
+  // File path or s3://bucket/key; defaults to a name under EXPORT_DESTINATION
+  location?: string;
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * Default file name: holder_diff_<mint>_<from>_<to>.<format>
+ */
+export function holderDiffFileName({ mint, fromEpoch, toEpoch }: HolderDiffKey, format: ExportFormat): string {
+  return `holder_diff_${mint}_${fromEpoch}_${toEpoch}.${format}`;
+}
+
+/**
+ * Stream a diff's wallets to `stream`, largest balance change first within
+ * each cohort; returns the number of rows written
+ */
+export async function writeHolderDiff(
+  { mint, fromEpoch, toEpoch, format, cohorts }: Omit<HolderDiffExportOptions, 'location'>,
+  stream: Writable
+): Promise<number> {
+  const client = await pool.connect();
+  let rows = 0;
+  try {
+    const writer = await openRowWriter(format, COLUMNS, stream);
+    const cursor = client.query(
+      new Cursor<Row>(
+        `
+        SELECT mint, from_epoch::text, to_epoch::text, wallet, cohort,
+               amount_from::text, amount_to::text, (amount_to - amount_from)::text AS change
+        FROM holder_diff_wallets
+        WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3
+          AND ($4::text[] IS NULL OR cohort = ANY($4))
+        ORDER BY cohort, ABS(amount_to - amount_from) DESC, wallet
+        `,
+        [mint, fromEpoch, toEpoch, cohorts ?? null]
+      )
+    );
+    try {
+      for (;;) {
+        const batch = await cursor.read(CURSOR_BATCH_SIZE);
+        if (batch.length === 0) break;
+        for (const row of batch) await writer.write(row);
+        rows += batch.length;
+      }
+    } finally {
+      await cursor.close();
+    }
+    await writer.close();
+  } finally {
+    client.release();
+  }
+  return rows;
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Export a diff's wallets to a file or S3-compatible store
+ *
+ * A failed export removes the partial file (or aborts the upload).
+ */
+export async function runHolderDiffExport(
+  options: HolderDiffExportOptions
+): Promise<{ location: string; rows: number }> {
+  const location =
+    options.location ??
+    destinationPath(process.env.EXPORT_DESTINATION || DEFAULT_DESTINATION, holderDiffFileName(options, options.format));
+
+  const destination = openDestination(location, HOLDER_DIFF_CONTENT_TYPES[options.format]);
+  try {
+    const rows = await writeHolderDiff(options, destination.stream);
+    await destination.complete();
+    return { location, rows };
+  } catch (e) {
+    await destination.abort().catch(() => undefined);
+    throw e;
+  }
+}
@@ -0,0 +1,174 @@
+// src/exports/writers.ts
+// Streaming CSV / Parquet writers and export destinations (local path or S3-compatible store)
+
+import fs from 'fs';
+import path from 'path';
+import { once } from 'events';
+import { PassThrough, Writable } from 'stream';
+import { finished } from 'stream/promises';
+import { S3Client } from '@aws-sdk/client-s3';
+import { Upload } from '@aws-sdk/lib-storage';
+import { ParquetSchema, ParquetWriter } from '@dsnp/parquetjs';
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type ExportFormat = 'csv' | 'parquet';
+
+export const EXPORT_FORMATS: ExportFormat[] = ['csv', 'parquet'];
+
+// Amounts are raw-unit strings in both formats: u64 doesn't fit Parquet's INT64
+export type ColumnType = 'text' | 'amount' | 'integer' | 'timestamp' | 'boolean';
+
+export type Column = [name: string, type: ColumnType];
+
+export type Row = Record<string, unknown>;
+
+export type RowWriter = {
+  write(row: Row): Promise<void>;
+  // Flushes and ends the underlying stream
+  close(): Promise<void>;
+};
+
+export type Destination = {
+  // Where the file ends up: a local path or s3://bucket/key
+  location: string;
+  stream: Writable;
+  // Resolves once the file is fully written (or uploaded)
+  complete(): Promise<void>;
+  abort(): Promise<void>;
+};
+
+// ---------------------------
+// Writers
+// ---------------------------
+
+function csvField(value: unknown): string {
+  if (value === null || value === undefined) return '';
+  const text = value instanceof Date ? value.toISOString() : String(value);
+  return /[",\r\n]/.test(text) ? `"${text.replace(/"/g, '""')}"` : text;
+}
+
+function csvWriter(columns: Column[], stream: Writable): RowWriter {
+  const writeLine = async (fields: unknown[]) => {
+    if (!stream.write(fields.map(csvField).join(',') + '\n')) {
+      await once(stream, 'drain');
+    }
+  };
+
+  let header = false;
+  return {
+    async write(row) {
+      if (!header) {
+        header = true;
+        await writeLine(columns.map(([name]) => name));
+      }
+      await writeLine(columns.map(([name]) => row[name]));
+    },
+    async close() {
+      if (!header) await writeLine(columns.map(([name]) => name));
+      stream.end();
+    },
+  };
+}
+
+const PARQUET_TYPES = {
+  text: 'UTF8',
+  amount: 'UTF8',
+  integer: 'INT64',
+  timestamp: 'TIMESTAMP_MILLIS',
+  boolean: 'BOOLEAN',
+} as const;
+
+async function parquetWriter(columns: Column[], stream: Writable): Promise<RowWriter> {
+  const schema = new ParquetSchema(
+    Object.fromEntries(columns.map(([name, type]) => [name, { type: PARQUET_TYPES[type], optional: true }]))
+  );
+  const writer = await ParquetWriter.openStream(schema, stream);
+
+  return {
+    async write(row) {
+      const values: Row = {};
+      for (const [name, type] of columns) {
+        const value = row[name];
+        if (value === null || value === undefined) continue;
+        values[name] = type === 'integer' ? BigInt(value as string | number) : value;
+      }
+      await writer.appendRow(values);
+    },
+    // Writes the footer and ends the stream
+    close: () => writer.close(),
+  };
+}
+
+export function openRowWriter(format: ExportFormat, columns: Column[], stream: Writable): Promise<RowWriter> {
+  return format === 'csv' ? Promise.resolve(csvWriter(columns, stream)) : parquetWriter(columns, stream);
+}
+
+// ---------------------------
+// Destinations
+// ---------------------------
+
+/**
+ * S3 client for S3_ENDPOINT (any S3-compatible store; AWS without it)
+ *
+ * Credentials come from the usual AWS_ACCESS_KEY_ID / AWS_SECRET_ACCESS_KEY
+ * environment or instance role.
+ */
+function s3Client(): S3Client {
+  return new S3Client({
+    endpoint: process.env.S3_ENDPOINT || undefined,
+    region: process.env.S3_REGION || 'us-east-1',
+    forcePathStyle: process.env.S3_FORCE_PATH_STYLE === 'true',
+  });
+}
+
+/**
+ * Open `location` for writing: `s3://bucket/key` streams a multipart upload,
+ * anything else is a local path (parent directories are created)
+ */
+export function openDestination(location: string, contentType: string): Destination {
+  const s3 = location.match(/^s3:\/\/([^/]+)\/(.+)$/);
+  if (s3) {
+    const stream = new PassThrough();
+    const upload = new Upload({
+      client: s3Client(),
+      params: { Bucket: s3[1], Key: s3[2], Body: stream, ContentType: contentType },
+    });
+    const done = upload.done();
+    // Surfaced by complete(); keeps an aborted upload from going unhandled
+    done.catch(() => undefined);
+    return {
+      location,
+      stream,
+      complete: async () => {
+        await done;
+      },
+      abort: async () => {
+        stream.destroy();
+        await upload.abort();
+      },
+    };
+  }
+
+  fs.mkdirSync(path.dirname(path.resolve(location)), { recursive: true });
+  const stream = fs.createWriteStream(location);
+  return {
+    location,
+    stream,
+    complete: () => finished(stream),
+    abort: async () => {
+      stream.destroy();
+      await fs.promises.rm(location, { force: true });
+    },
+  };
+}
+
+/**
+ * `name` under the destination prefix `base` (a directory or s3://bucket/prefix)
+ */
+export function destinationPath(base: string, name: string): string {
+  if (base.startsWith('s3://')) return `${base.replace(/\/+$/, '')}/${name}`;
+  return path.join(base, name);
+}
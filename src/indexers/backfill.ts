@@ -0,0 +1,266 @@
+// src/indexers/backfill.ts
+// Re-index distributor claim events from finalized transaction history
+
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { DeploymentConfig } from '../config/deployments';
+import { FailoverConnection } from '../utils/rpc';
+import { programTransactionFromRpc } from './claim-events';
+import { ingestTransaction } from './dead-letters';
+import { SlotTimeMapper, stampMissingBlockTimes } from './slot-times';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// getSignaturesForAddress page size (RPC maximum)
+const SIGNATURE_PAGE_SIZE = 1000;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type BackfillOptions = {
+  deployment: DeploymentConfig;
+  // Only this distribution PDA (default: the program and every initialized distribution)
+  distribution?: string;
+  skipProgram?: boolean;
+  // Stop at this signature (exclusive)
+  until?: string;
+  // Window, inclusive; time bounds are unix seconds of block time
+  fromSlot?: number;
+  toSlot?: number;
+  fromTime?: number;
+  toTime?: number;
+  // Re-decode transactions that already have stored events, adding any missing ones
+  redecode?: boolean;
+  // Transactions fetched per request
+  batchSize: number;
+  // Maximum RPC requests per second
+  rps: number;
+};
+
+export type BackfillResult = {
+  transactions: number;
+  events: number;
+  // Transactions that failed to decode or store, left in ingestion_dead_letters
+  deadLettered: number;
+  // Stored events that had no block time and were given one
+  stamped: number;
+};
+
+// ---------------------------
+// CLI
+// ---------------------------
+
+export function printBackfillUsage(command: string) {
+  console.log(`Usage: ${command} [options]`);
+  console.log('');
+  console.log('Walks finalized transaction history of the distributor program and every');
+  console.log('initialized distribution PDA, storing decoded events. Safe to re-run.');
+  console.log('Block times missing from the cluster are interpolated from neighboring');
+  console.log('slots, and stored events still without one are stamped at the end.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --deployment <name>    Deployment to backfill (required with several configured)');
+  console.log('  --distribution <PDA>   Only backfill this distribution');
+  console.log('  --skip-program         Walk distribution PDAs only (much shorter history)');
+  console.log('  --from-slot <slot>     Oldest slot to index (inclusive)');
+  console.log('  --to-slot <slot>       Newest slot to index (inclusive)');
+  console.log('  --from <time>          Oldest block time to index (ISO 8601 or unix seconds)');
+  console.log('  --to <time>            Newest block time to index');
+  console.log('  --until <signature>    Stop at this signature (exclusive)');
+  console.log('  --redecode             Also re-decode transactions that already have events');
+  console.log('  --batch <n>            Transactions fetched per request (default 50)');
+  console.log('  --rps <n>              Maximum RPC requests per second (default 5)');
+}
+
+function parseSlot(name: string, value: string | undefined): number | undefined {
+  if (value === undefined) return undefined;
+  const slot = Number(value);
+  if (!Number.isInteger(slot) || slot < 0) throw new Error(`Invalid --${name}: ${value}`);
+  return slot;
+}
+
+function parseTime(name: string, value: string | undefined): number | undefined {
+  if (value === undefined) return undefined;
+  const seconds = /^\d+$/.test(value) ? Number(value) : Date.parse(value) / 1000;
+  if (!Number.isFinite(seconds)) throw new Error(`Invalid --${name}: ${value}`);
+  return Math.floor(seconds);
+}
+
+/**
+ * Backfill options from parsed `--key value` arguments
+ */
+export function backfillOptionsFromArgs(
+  args: Record<string, string>,
+  deployment: DeploymentConfig
+): BackfillOptions {
+  const options: BackfillOptions = {
+    deployment,
+    distribution: args.distribution,
+    skipProgram: args['skip-program'] === 'true',
+    until: args.until,
+    fromSlot: parseSlot('from-slot', args['from-slot']),
+    toSlot: parseSlot('to-slot', args['to-slot']),
+    fromTime: parseTime('from', args.from),
+    toTime: parseTime('to', args.to),
+    redecode: args.redecode === 'true',
+    batchSize: Number(args.batch ?? 50),
+    rps: Number(args.rps ?? 5),
+  };
+  if (!(options.batchSize > 0) || !(options.rps > 0)) {
+    throw new Error('--batch and --rps must be positive');
+  }
+  if (options.distribution) new PublicKey(options.distribution);
+  if (options.fromSlot !== undefined && options.toSlot !== undefined && options.fromSlot > options.toSlot) {
+    throw new Error('--from-slot is after --to-slot');
+  }
+  if (options.fromTime !== undefined && options.toTime !== undefined && options.fromTime > options.toTime) {
+    throw new Error('--from is after --to');
+  }
+  return options;
+}
+
+// ---------------------------
+// Backfill
+// ---------------------------
+
+/**
+ * Fetch and decode every successful transaction in the window, storing its events
+ *
+ * Histories are walked newest first: signatures newer than `toSlot` / `toTime`
+ * are skipped and the walk stops at the first one older than `fromSlot` /
+ * `fromTime`. Events are upserted by `persistClaimEvents` on (deployment,
+ * signature, instruction_index, event_index), so re-running a window never
+ * duplicates rows. Without
+ * `redecode`, transactions that already have stored events are not fetched.
+ * Finally, events of the deployment stored without a block time are stamped.
+ */
+export async function backfillClaimEvents(options: BackfillOptions): Promise<BackfillResult> {
+  const { deployment, batchSize, rps } = options;
+  const programId = deployment.programId;
+  const rpc = new FailoverConnection(deployment.rpc);
+  const context = {
+    deployment: deployment.name,
+    programId,
+    commitment: 'finalized' as const,
+    source: 'backfill',
+    slotTimes: new SlotTimeMapper(deployment.name, rpc),
+  };
+
+  // Simple rate limiter: space requests at least 1/rps seconds apart
+  let nextRequestAt = 0;
+  const throttle = async () => {
+    const wait = nextRequestAt - Date.now();
+    if (wait > 0) await sleep(wait);
+    nextRequestAt = Math.max(Date.now(), nextRequestAt) + 1000 / rps;
+  };
+
+  let addresses: string[];
+  if (options.distribution) {
+    addresses = [options.distribution];
+  } else {
+    const { rows } = await pool.query<{ on_chain_address: string }>(
+      `
+      SELECT on_chain_address
+      FROM merkle_distributions
+      WHERE deployment = $1 AND on_chain_address IS NOT NULL
+      ORDER BY created_at
+      `,
+      [deployment.name]
+    );
+    addresses = rows.map((r) => r.on_chain_address);
+    if (!options.skipProgram) addresses.unshift(programId);
+  }
+  const result: BackfillResult = { transactions: 0, events: 0, deadLettered: 0, stamped: 0 };
+  if (addresses.length === 0) {
+    console.log('No initialized distributions to backfill');
+    return result;
+  }
+
+  const tooNew = (s: { slot: number; blockTime?: number | null }) =>
+    (options.toSlot !== undefined && s.slot > options.toSlot) ||
+    (options.toTime !== undefined && s.blockTime != null && s.blockTime > options.toTime);
+  const tooOld = (s: { slot: number; blockTime?: number | null }) =>
+    (options.fromSlot !== undefined && s.slot < options.fromSlot) ||
+    (options.fromTime !== undefined && s.blockTime != null && s.blockTime < options.fromTime);
+
+  // Shared across addresses: program and PDA histories overlap
+  const handled = new Set<string>();
+
+  for (const address of addresses) {
+    console.log(`Backfilling ${address}${address === programId ? ' (program)' : ''}...`);
+    let before: string | undefined;
+    let scanned = 0;
+
+    for (;;) {
+      await throttle();
+      const page = await rpc.execute(
+        (connection) =>
+          connection.getSignaturesForAddress(
+            new PublicKey(address),
+            { before, until: options.until, limit: SIGNATURE_PAGE_SIZE },
+            'finalized'
+          ),
+        'getSignaturesForAddress'
+      );
+      if (page.length === 0) break;
+      before = page[page.length - 1].signature;
+      scanned += page.length;
+
+      const inWindow = page.filter((s) => !tooNew(s) && !tooOld(s));
+      const reachedStart = page.some(tooOld);
+
+      const candidates = inWindow
+        .filter((s) => !s.err && !handled.has(s.signature))
+        .map((s) => s.signature);
+      candidates.forEach((s) => handled.add(s));
+
+      // Skip transactions already ingested
+      let pending = candidates;
+      if (!options.redecode) {
+        const { rows: stored } = await pool.query<{ signature: string }>(
+          `SELECT DISTINCT signature FROM merkle_claim_events WHERE deployment = $1 AND signature = ANY($2)`,
+          [deployment.name, candidates]
+        );
+        const storedSet = new Set(stored.map((r) => r.signature));
+        pending = candidates.filter((s) => !storedSet.has(s));
+      }
+
+      for (let i = 0; i < pending.length; i += batchSize) {
+        const batch = pending.slice(i, i + batchSize);
+        await throttle();
+        const txs = await rpc.execute(
+          (connection) =>
+            connection.getTransactions(batch, {
+              commitment: 'finalized',
+              maxSupportedTransactionVersion: 0,
+            }),
+          'getTransactions'
+        );
+
+        for (let j = 0; j < batch.length; j++) {
+          const tx = txs[j];
+          if (!tx || tx.meta?.err) continue;
+
+          const events = await ingestTransaction(programTransactionFromRpc(batch[j], tx), context);
+          result.transactions++;
+          if (events === null) result.deadLettered++;
+          else result.events += events.length;
+        }
+      }
+
+      const oldest = page[page.length - 1];
+      console.log(`  ${scanned} signatures scanned (back to slot ${oldest.slot}), ${result.events} events stored so far`);
+      if (reachedStart || page.length < SIGNATURE_PAGE_SIZE) break;
+    }
+  }
+
+  result.stamped = await stampMissingBlockTimes(context.slotTimes);
+  return result;
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
@@ -0,0 +1,148 @@
+// src/indexers/checkpoints.ts
+// Persisted ingestion cursors, so a restarted claim event stream resumes where it stopped
+
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { FailoverConnection } from '../utils/rpc';
+import { Commitment, ProgramTransaction, programTransactionFromRpc } from './claim-events';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// getSignaturesForAddress page size (RPC maximum)
+const SIGNATURE_PAGE_SIZE = 1000;
+
+// Transactions fetched per getTransactions call while replaying
+const REPLAY_BATCH_SIZE = 50;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type IngestionCheckpoint = {
+  slot: number;
+  signature: string;
+};
+
+// ---------------------------
+// Storage
+// ---------------------------
+
+/**
+ * Last checkpoint saved by `source` for `programId` of `deployment`, or null on first run
+ */
+export async function loadCheckpoint(
+  deployment: string,
+  source: string,
+  programId: string
+): Promise<IngestionCheckpoint | null> {
+  const { rows } = await pool.query<{ slot: string; signature: string }>(
+    `SELECT slot, signature FROM ingestion_checkpoints WHERE deployment = $1 AND source = $2 AND program_id = $3`,
+    [deployment, source, programId]
+  );
+  if (rows.length === 0) return null;
+  return { slot: Number(rows[0].slot), signature: rows[0].signature };
+}
+
+/**
+ * Save `checkpoint` for `source`; never moves an existing checkpoint back
+ */
+export async function saveCheckpoint(
+  deployment: string,
+  source: string,
+  programId: string,
+  checkpoint: IngestionCheckpoint
+): Promise<void> {
+  await pool.query(
+    `
+    INSERT INTO ingestion_checkpoints (deployment, source, program_id, slot, signature)
+    VALUES ($1, $2, $3, $4, $5)
+    ON CONFLICT (deployment, source, program_id) DO UPDATE
+    SET slot = EXCLUDED.slot, signature = EXCLUDED.signature, updated_at = NOW()
+    WHERE ingestion_checkpoints.slot <= EXCLUDED.slot
+    `,
+    [deployment, source, programId, checkpoint.slot, checkpoint.signature]
+  );
+}
+
+/**
+ * Record the latest lag measurement of `source`, read by readiness checks
+ */
+export async function saveIngestionLag(deployment: string, source: string, lagSlots: number): Promise<void> {
+  await pool.query(
+    `
+    INSERT INTO ingestion_lag (deployment, source, lag_slots, measured_at)
+    VALUES ($1, $2, $3, NOW())
+    ON CONFLICT (deployment, source) DO UPDATE
+    SET lag_slots = EXCLUDED.lag_slots, measured_at = EXCLUDED.measured_at
+    `,
+    [deployment, source, lagSlots]
+  );
+}
+
+// ---------------------------
+// Replay
+// ---------------------------
+
+/**
+ * Hand every successful program transaction after `checkpoint` to
+ * `onTransaction`, oldest first; returns how many were replayed
+ *
+ * History is walked back to the checkpoint signature, or to the first
+ * signature below the checkpoint slot in case the checkpointed transaction
+ * was on a fork that never confirmed. Transactions in the checkpoint slot are
+ * replayed too; persisting events is idempotent, so repeats are harmless.
+ * Processed ingestion replays at confirmed, the lowest commitment the
+ * history RPCs serve.
+ */
+export async function replaySince(
+  rpc: FailoverConnection,
+  programId: string,
+  checkpoint: IngestionCheckpoint,
+  commitment: Commitment,
+  onTransaction: (tx: ProgramTransaction) => Promise<void>
+): Promise<number> {
+  const program = new PublicKey(programId);
+  const fetchCommitment = commitment === 'finalized' ? 'finalized' : 'confirmed';
+
+  // Newest first
+  const signatures: string[] = [];
+  let before: string | undefined;
+  for (;;) {
+    const page = await rpc.execute(
+      (connection) =>
+        connection.getSignaturesForAddress(
+          program,
+          { before, until: checkpoint.signature, limit: SIGNATURE_PAGE_SIZE },
+          fetchCommitment
+        ),
+      'getSignaturesForAddress'
+    );
+    const newer = page.filter((s) => s.slot >= checkpoint.slot);
+    signatures.push(...newer.filter((s) => !s.err).map((s) => s.signature));
+    if (page.length < SIGNATURE_PAGE_SIZE || newer.length < page.length) break;
+    before = page[page.length - 1].signature;
+  }
+  signatures.reverse();
+
+  let replayed = 0;
+  for (let i = 0; i < signatures.length; i += REPLAY_BATCH_SIZE) {
+    const batch = signatures.slice(i, i + REPLAY_BATCH_SIZE);
+    const txs = await rpc.execute(
+      (connection) =>
+        connection.getTransactions(batch, {
+          commitment: fetchCommitment,
+          maxSupportedTransactionVersion: 0,
+        }),
+      'getTransactions'
+    );
+    for (let j = 0; j < batch.length; j++) {
+      const tx = txs[j];
+      if (!tx || tx.meta?.err) continue;
+      await onTransaction(programTransactionFromRpc(batch[j], tx));
+      replayed++;
+    }
+  }
+  return replayed;
+}
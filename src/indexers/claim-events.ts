@@ -0,0 +1,489 @@
+// src/indexers/claim-events.ts
+// Decodes merkle-distributor claim / clawback / pause events from transaction logs
+
+import { VersionedTransactionResponse } from '@solana/web3.js';
+import bs58 from 'bs58';
+import { PoolClient } from 'pg';
+import { decodeTransaction } from 'merkle-events';
+import { pool } from '../db';
+import { FailoverConnection } from '../utils/rpc';
+import { SlotTime } from './slot-times';
+import { enqueueWebhookDeliveries } from './webhooks';
+
+export type Commitment = 'processed' | 'confirmed' | 'finalized';
+
+export type ClaimEventKind =
+  | 'claim'
+  | 'claim_referral'
+  | 'escrow'
+  | 'renounce'
+  | 'escrow_release'
+  | 'clawback'
+  | 'pause'
+  | 'unpause'
+  | 'operator_change';
+
+/**
+ * A successful transaction touching the distributor program, in the shape
+ * every ingestion path (Geyser, RPC) normalizes to
+ */
+export interface ProgramTransaction {
+  signature: string;
+  slot: number;
+  blockTime: number | null;
+  /** Static keys followed by lookup-table writable, then readonly, addresses */
+  accountKeys: string[];
+  /** Top-level instructions, account indexes into `accountKeys` */
+  instructions: { programIdIndex: number; accounts: number[] }[];
+  /** Instructions invoked through CPI, flattened; `instructionIndex` is the top-level instruction */
+  innerInstructions: InnerInstruction[];
+  logMessages: string[];
+}
+
+export interface InnerInstruction {
+  instructionIndex: number;
+  /** Position among the top-level instruction's inner instructions */
+  innerIndex: number;
+  programIdIndex: number;
+  accounts: number[];
+  data: Uint8Array;
+  /** Invocation depth (2 = invoked by the top-level instruction), where the source reports it */
+  stackHeight: number | null;
+}
+
+export interface ClaimEvent {
+  signature: string;
+  slot: number;
+  /** Position of the event within the transaction */
+  eventIndex: number;
+  /** Top-level instruction that emitted the event; with the signature, the event's identity */
+  instructionIndex: number;
+  /** Distribution PDA (first account of every claim and admin instruction) */
+  distribution: string;
+  kind: ClaimEventKind;
+  recipient: string | null;
+  amount: bigint | null;
+  leafIndex: number | null;
+  referrer: string | null;
+  bonus: bigint | null;
+  /** Clawback destination token account, or the new operator of operator_change */
+  destination: string | null;
+}
+
+/**
+ * Normalize a transaction fetched with `getTransaction`
+ */
+export function programTransactionFromRpc(
+  signature: string,
+  tx: VersionedTransactionResponse
+): ProgramTransaction {
+  const message = tx.transaction.message;
+  const loaded = tx.meta?.loadedAddresses;
+  return {
+    signature,
+    slot: tx.slot,
+    blockTime: tx.blockTime ?? null,
+    accountKeys: [
+      ...message.staticAccountKeys,
+      ...(loaded?.writable ?? []),
+      ...(loaded?.readonly ?? []),
+    ].map((key) => key.toBase58()),
+    instructions: message.compiledInstructions.map((ix) => ({
+      programIdIndex: ix.programIdIndex,
+      accounts: ix.accountKeyIndexes,
+    })),
+    innerInstructions: (tx.meta?.innerInstructions ?? []).flatMap((inner) =>
+      inner.instructions.map((ix, innerIndex) => ({
+        instructionIndex: inner.index,
+        innerIndex,
+        programIdIndex: ix.programIdIndex,
+        accounts: ix.accounts,
+        data: bs58.decode(ix.data),
+        // Reported by RPC nodes since 1.16 but missing from the web3.js type
+        stackHeight: (ix as { stackHeight?: number }).stackHeight ?? null,
+      }))
+    ),
+    logMessages: tx.meta?.logMessages ?? [],
+  };
+}
+
+/**
+ * Commitment events are ingested at (`CLAIM_EVENTS_COMMITMENT`, default confirmed)
+ */
+export function getClaimEventsCommitmentFromEnv(): Commitment {
+  const commitment = process.env.CLAIM_EVENTS_COMMITMENT || 'confirmed';
+  if (commitment !== 'processed' && commitment !== 'confirmed' && commitment !== 'finalized') {
+    throw new Error(`Invalid CLAIM_EVENTS_COMMITMENT: ${commitment}`);
+  }
+  return commitment;
+}
+
+// Kinds stored in merkle_claim_events; the decoder also returns dust sweeps
+// and TotalsVerified events, which are not claim events
+const CLAIM_EVENT_KINDS: ReadonlySet<string> = new Set<ClaimEventKind>([
+  'claim',
+  'claim_referral',
+  'escrow',
+  'renounce',
+  'escrow_release',
+  'clawback',
+  'pause',
+  'unpause',
+  'operator_change',
+]);
+
+// Shape of crates/merkle-events `decodeTransaction` output
+interface DecodedProgramEvent {
+  instructionIndex: number;
+  innerIndex: number | null;
+  kind: string;
+  recipient: string | null;
+  amount: string | null;
+  leafIndex: number | null;
+  referrer: string | null;
+  bonus: string | null;
+  destination: string | null;
+}
+
+/**
+ * Decode the events the distributor emitted in `tx`
+ *
+ * Events are decoded by the merkle-events crate (see
+ * crates/merkle-events/README.md) from the logs and from self-CPI
+ * (`emit_cpi!`) inner instructions, and attributed to the instruction that
+ * invoked the distributor: the top-level instruction, or for a distributor
+ * invoked through CPI one of its inner instructions. That instruction's
+ * first account is the distribution.
+ */
+export function decodeClaimEvents(tx: ProgramTransaction, programId: string): ClaimEvent[] {
+  const inner = tx.innerInstructions
+    .filter((ix) => tx.accountKeys[ix.programIdIndex] === programId)
+    .map((ix) => ({
+      instructionIndex: ix.instructionIndex,
+      innerIndex: ix.innerIndex,
+      programId,
+      data: Buffer.from(ix.data).toString('base64'),
+      stackHeight: ix.stackHeight,
+    }));
+  const decoded: DecodedProgramEvent[] = JSON.parse(decodeTransaction(tx.logMessages, JSON.stringify(inner), programId));
+  const events: ClaimEvent[] = [];
+
+  for (const e of decoded) {
+    if (!CLAIM_EVENT_KINDS.has(e.kind)) continue;
+
+    const ix =
+      e.innerIndex === null
+        ? tx.instructions[e.instructionIndex]
+        : tx.innerInstructions.find(
+            (inner) => inner.instructionIndex === e.instructionIndex && inner.innerIndex === e.innerIndex
+          );
+    const distribution = ix && tx.accountKeys[ix.accounts[0]];
+    if (!distribution || tx.accountKeys[ix.programIdIndex] !== programId) {
+      throw new Error(`${tx.signature}: log without a matching distributor instruction`);
+    }
+
+    events.push({
+      signature: tx.signature,
+      slot: tx.slot,
+      eventIndex: events.length,
+      instructionIndex: e.instructionIndex,
+      distribution,
+      kind: e.kind as ClaimEventKind,
+      recipient: e.recipient,
+      amount: e.amount === null ? null : BigInt(e.amount),
+      leafIndex: e.leafIndex,
+      referrer: e.referrer,
+      bonus: e.bonus === null ? null : BigInt(e.bonus),
+      destination: e.destination,
+    });
+  }
+
+  return events;
+}
+
+/**
+ * Store decoded events of `meta.deployment`
+ *
+ * Finalized events go straight to `merkle_claim_events`. Processed and
+ * confirmed ones are held in `merkle_claim_events_staged` (re-deliveries only
+ * raise their commitment) until `finalizeStagedEvents` promotes or drops them,
+ * so a skipped slot never leaves rows behind.
+ *
+ * Events are upserted on (deployment, signature, instruction_index,
+ * event_index), so any number of sources and replays delivering the same
+ * transaction converge on one row per event, and each claim of a claim_batch
+ * keeps its own row: the first source is kept, and a missing or estimated
+ * block time is replaced by a better one.
+ * Rows stored before instruction_index was recorded are replaced by the new
+ * decode of their transaction.
+ */
+export async function persistClaimEvents(
+  events: ClaimEvent[],
+  meta: { deployment: string; commitment: Commitment; blockTime: SlotTime | null; source: string }
+): Promise<void> {
+  if (events.length === 0) return;
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    const signatures = [...new Set(events.map((e) => e.signature))];
+
+    if (meta.commitment === 'finalized') {
+      await client.query(
+        `DELETE FROM merkle_claim_events
+         WHERE deployment = $1 AND signature = ANY($2) AND instruction_index IS NULL`,
+        [meta.deployment, signatures]
+      );
+      for (const e of events) {
+        await client.query(
+          `
+          INSERT INTO merkle_claim_events (${EVENT_COLUMNS})
+          VALUES ($1, $2, $3, $4, $5, to_timestamp($6), $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+          ON CONFLICT (deployment, signature, instruction_index, event_index) DO UPDATE
+          SET ${blockTimeUpdate('merkle_claim_events')}
+          `,
+          eventValues(e, meta)
+        );
+      }
+      await client.query(
+        `DELETE FROM merkle_claim_events_staged WHERE deployment = $1 AND signature = ANY($2)`,
+        [meta.deployment, signatures]
+      );
+      await confirmClaimedLeaves(client, meta.deployment, signatures);
+      await enqueueWebhookDeliveries(client, meta.deployment, signatures);
+    } else {
+      await client.query(
+        `DELETE FROM merkle_claim_events_staged
+         WHERE deployment = $1 AND signature = ANY($2) AND instruction_index IS NULL`,
+        [meta.deployment, signatures]
+      );
+      for (const e of events) {
+        await client.query(
+          `
+          INSERT INTO merkle_claim_events_staged AS s (${EVENT_COLUMNS}, commitment)
+          VALUES ($1, $2, $3, $4, $5, to_timestamp($6), $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+          ON CONFLICT (deployment, signature, instruction_index, event_index) DO UPDATE
+          SET
+            commitment = CASE WHEN s.commitment = 'processed' THEN EXCLUDED.commitment ELSE s.commitment END,
+            slot = CASE WHEN s.commitment = 'processed' THEN EXCLUDED.slot ELSE s.slot END,
+            ${blockTimeUpdate('s')}
+          `,
+          [...eventValues(e, meta), meta.commitment]
+        );
+      }
+    }
+
+    await client.query('COMMIT');
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
+
+const EVENT_COLUMNS = `
+  deployment,
+  signature,
+  event_index,
+  instruction_index,
+  slot,
+  block_time,
+  block_time_estimated,
+  distribution,
+  kind,
+  recipient,
+  amount,
+  leaf_index,
+  referrer,
+  bonus,
+  destination,
+  source
+`;
+
+function eventValues(
+  e: ClaimEvent,
+  meta: { deployment: string; blockTime: SlotTime | null; source: string }
+): unknown[] {
+  return [
+    meta.deployment,
+    e.signature,
+    e.eventIndex,
+    e.instructionIndex,
+    e.slot,
+    meta.blockTime?.blockTime ?? null,
+    meta.blockTime?.estimated ?? false,
+    e.distribution,
+    e.kind,
+    e.recipient,
+    e.amount?.toString() ?? null,
+    e.leafIndex,
+    e.referrer,
+    e.bonus?.toString() ?? null,
+    e.destination,
+    meta.source,
+  ];
+}
+
+/**
+ * Upsert assignments keeping the stored block time of `table` unless it is
+ * missing, or estimated while the incoming one is not
+ */
+function blockTimeUpdate(table: string): string {
+  const replace = `${table}.block_time IS NULL
+    OR (${table}.block_time_estimated AND NOT EXCLUDED.block_time_estimated AND EXCLUDED.block_time IS NOT NULL)`;
+  return `
+    block_time = CASE WHEN ${replace} THEN EXCLUDED.block_time ELSE ${table}.block_time END,
+    block_time_estimated = CASE WHEN ${replace} THEN EXCLUDED.block_time_estimated ELSE ${table}.block_time_estimated END`;
+}
+
+/**
+ * Mark leaves claimed in finalized `signatures` of `deployment` as confirmed,
+ * so the relayer doesn't resubmit leaves users claimed themselves
+ */
+async function confirmClaimedLeaves(client: PoolClient, deployment: string, signatures: string[]): Promise<void> {
+  await client.query(
+    `
+    UPDATE merkle_claims c
+    SET
+      status = 'confirmed',
+      tx_signature = e.signature,
+      confirmed_at = COALESCE(c.confirmed_at, NOW()),
+      error_message = NULL
+    FROM merkle_claim_events e
+    JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+    WHERE e.deployment = $1
+      AND e.signature = ANY($2)
+      AND e.kind IN ('claim', 'claim_referral', 'escrow')
+      AND c.distribution_id = d.distribution_id
+      AND c.leaf_index = e.leaf_index
+      AND c.status <> 'confirmed'
+    `,
+    [deployment, signatures]
+  );
+}
+
+export interface FinalizeResult {
+  promoted: number;
+  rolledBack: number;
+  pending: number;
+}
+
+// getSignatureStatuses accepts up to 256 signatures per call
+const STATUS_BATCH_SIZE = 256;
+
+/**
+ * Promote staged events of `deployment` whose transactions finalized; drop
+ * those whose slot is at or below the finalized slot but never finalized
+ * (skipped or forked). `rpc` must serve the deployment's cluster.
+ */
+export async function finalizeStagedEvents(deployment: string, rpc: FailoverConnection): Promise<FinalizeResult> {
+  const { rows } = await pool.query<{ signature: string; slot: string }>(
+    `SELECT signature, MIN(slot) AS slot FROM merkle_claim_events_staged WHERE deployment = $1 GROUP BY signature`,
+    [deployment]
+  );
+  const result: FinalizeResult = { promoted: 0, rolledBack: 0, pending: 0 };
+  if (rows.length === 0) return result;
+
+  const finalizedSlot = await rpc.execute((connection) => connection.getSlot('finalized'), 'getSlot');
+
+  const promote: { signature: string; slot: number }[] = [];
+  const rollBack: string[] = [];
+
+  for (let i = 0; i < rows.length; i += STATUS_BATCH_SIZE) {
+    const batch = rows.slice(i, i + STATUS_BATCH_SIZE);
+    const statuses = await rpc.execute(
+      (connection) =>
+        connection.getSignatureStatuses(
+          batch.map((r) => r.signature),
+          { searchTransactionHistory: true }
+        ),
+      'getSignatureStatuses'
+    );
+
+    batch.forEach((row, j) => {
+      const status = statuses.value[j];
+      if (status && !status.err && status.confirmationStatus === 'finalized') {
+        // May have landed in a different slot than first seen
+        promote.push({ signature: row.signature, slot: status.slot });
+      } else if ((!status || status.err) && Number(row.slot) <= finalizedSlot) {
+        rollBack.push(row.signature);
+      } else {
+        result.pending++;
+      }
+    });
+  }
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    if (promote.length > 0) {
+      const signatures = promote.map((p) => p.signature);
+      // Same upsert as persistClaimEvents. Staged rows from before
+      // instruction_index was recorded are only promoted if no other source
+      // finalized the transaction first.
+      await client.query(
+        `
+        DELETE FROM merkle_claim_events e
+        WHERE e.deployment = $1
+          AND e.signature = ANY($2)
+          AND e.instruction_index IS NULL
+          AND EXISTS (
+            SELECT 1 FROM merkle_claim_events_staged s
+            WHERE s.deployment = e.deployment AND s.signature = e.signature AND s.instruction_index IS NOT NULL
+          )
+        `,
+        [deployment, signatures]
+      );
+      await client.query(
+        `
+        INSERT INTO merkle_claim_events (${EVENT_COLUMNS})
+        SELECT
+          s.deployment, s.signature, s.event_index, s.instruction_index, p.slot, s.block_time, s.block_time_estimated,
+          s.distribution, s.kind, s.recipient, s.amount, s.leaf_index, s.referrer, s.bonus, s.destination, s.source
+        FROM merkle_claim_events_staged s
+        JOIN unnest($2::text[], $3::bigint[]) AS p(signature, slot) ON p.signature = s.signature
+        WHERE s.deployment = $1
+          AND (
+            s.instruction_index IS NOT NULL
+            OR NOT EXISTS (
+              SELECT 1 FROM merkle_claim_events e WHERE e.deployment = s.deployment AND e.signature = s.signature
+            )
+          )
+        ON CONFLICT (deployment, signature, instruction_index, event_index) DO UPDATE
+        SET ${blockTimeUpdate('merkle_claim_events')}
+        `,
+        [deployment, signatures, promote.map((p) => p.slot)]
+      );
+      await client.query(
+        `DELETE FROM merkle_claim_events_staged WHERE deployment = $1 AND signature = ANY($2)`,
+        [deployment, signatures]
+      );
+      await confirmClaimedLeaves(client, deployment, signatures);
+      await enqueueWebhookDeliveries(client, deployment, signatures);
+      result.promoted = promote.length;
+    }
+
+    if (rollBack.length > 0) {
+      await client.query(
+        `DELETE FROM merkle_claim_events_staged WHERE deployment = $1 AND signature = ANY($2)`,
+        [deployment, rollBack]
+      );
+      result.rolledBack = rollBack.length;
+    }
+
+    await client.query('COMMIT');
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+
+  for (const signature of rollBack) {
+    console.warn(`[${deployment}] Rolled back events of ${signature}: slot passed finality without it`);
+  }
+  return result;
+}
@@ -0,0 +1,152 @@
+// src/indexers/compressed-tokens.ts
+// Compressed token balances of a mint, merged into holder snapshots
+
+import { PublicKey } from '@solana/web3.js';
+import type { TokenBalance } from './epoch-snapshot';
+import { FailoverConnection } from '../utils/rpc';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Light Protocol compressed token program
+const COMPRESSED_TOKEN_PROGRAM_ID = new PublicKey('cTokenmWW8bLPjZEBAUgYy3zKxQZW6VKi7bqNFEVv3m');
+
+// Token pools per mint: index 0 is seeded without an index byte
+const TOKEN_POOL_SEED = Buffer.from('pool');
+const MAX_TOKEN_POOLS = 5;
+
+// Accounts per getCompressedTokenAccountsByMint page
+const PAGE_SIZE = 1000;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type CompressedBalances = {
+  // Slot the indexer had processed when answering
+  slot: number;
+  // tokenAccount is the compressed account's hash
+  balances: TokenBalance[];
+  // SPL token accounts holding the tokens behind the compressed balances,
+  // left out of the token account scan so they aren't counted twice
+  backingAccounts: string[];
+};
+
+/**
+ * Source of compressed token state for a mint
+ *
+ * Compressed accounts live in state trees rather than token accounts, so a
+ * getProgramAccounts scan of the token program can't see them; an indexer
+ * has to. Implementations return every compressed balance of the mint as of
+ * one indexed slot.
+ */
+export interface CompressedTokenSource {
+  readonly name: string;
+  fetchBalances(mint: PublicKey): Promise<CompressedBalances>;
+}
+
+type PhotonTokenAccount = {
+  account: { hash: string };
+  tokenData: { owner: string; amount: number | string };
+};
+
+type PhotonPage = {
+  context: { slot: number };
+  value: { items: PhotonTokenAccount[]; cursor: string | null };
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * SPL token accounts the compressed token program holds a mint's tokens in
+ */
+export function tokenPoolAccounts(mint: PublicKey): PublicKey[] {
+  return Array.from({ length: MAX_TOKEN_POOLS }, (_, index) => {
+    const seeds = index === 0 ? [TOKEN_POOL_SEED, mint.toBuffer()] : [TOKEN_POOL_SEED, mint.toBuffer(), Buffer.from([index])];
+    return PublicKey.findProgramAddressSync(seeds, COMPRESSED_TOKEN_PROGRAM_ID)[0];
+  });
+}
+
+async function photonRequest<T>(url: string, method: string, params: Record<string, unknown>): Promise<T> {
+  const res = await fetch(url, {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ jsonrpc: '2.0', id: method, method, params }),
+  });
+  if (!res.ok) {
+    throw new Error(`${method}: HTTP ${res.status}`);
+  }
+  const body = (await res.json()) as { result?: T; error?: { code: number; message: string } };
+  if (body.error) {
+    throw new Error(`${method}: ${body.error.message} (${body.error.code})`);
+  }
+  return body.result as T;
+}
+
+// ---------------------------
+// Sources
+// ---------------------------
+
+/**
+ * Compressed balances from a Photon (ZK Compression) indexer
+ *
+ * Photon's methods are served by most RPC providers that index compression
+ * (e.g. Helius) on the regular RPC URL, so without a dedicated URL the
+ * snapshot RPC is used, failover included. Pages are read until the cursor
+ * runs out; the lowest slot any page was answered at is reported.
+ */
+export class PhotonCompressedTokenSource implements CompressedTokenSource {
+  readonly name = 'photon';
+
+  constructor(
+    private rpc: FailoverConnection,
+    private url: string | null = null
+  ) {}
+
+  async fetchBalances(mint: PublicKey): Promise<CompressedBalances> {
+    const balances: TokenBalance[] = [];
+    let slot = Infinity;
+    let cursor: string | null = null;
+
+    do {
+      const params: Record<string, unknown> = { mint: mint.toBase58(), limit: PAGE_SIZE, ...(cursor ? { cursor } : {}) };
+      const page: PhotonPage = this.url
+        ? await photonRequest<PhotonPage>(this.url, 'getCompressedTokenAccountsByMint', params)
+        : await this.rpc.execute(
+            (connection) => photonRequest<PhotonPage>(connection.rpcEndpoint, 'getCompressedTokenAccountsByMint', params),
+            'getCompressedTokenAccountsByMint'
+          );
+
+      slot = Math.min(slot, page.context.slot);
+      for (const item of page.value.items) {
+        const amount = BigInt(item.tokenData.amount);
+        if (amount === 0n) continue;
+        balances.push({ tokenAccount: item.account.hash, owner: item.tokenData.owner, amount });
+      }
+      cursor = page.value.items.length > 0 ? page.value.cursor : null;
+    } while (cursor);
+
+    return {
+      slot: Number.isFinite(slot) ? slot : 0,
+      balances,
+      backingAccounts: tokenPoolAccounts(mint).map((account) => account.toBase58()),
+    };
+  }
+}
+
+/**
+ * Compressed token source from SNAPSHOT_COMPRESSED_TOKENS (null when unset)
+ *
+ * - photon: Photon indexer at COMPRESSED_TOKEN_RPC_URL, else the snapshot RPC
+ */
+export function getCompressedTokenSourceFromEnv(rpc: FailoverConnection): CompressedTokenSource | null {
+  const kind = process.env.SNAPSHOT_COMPRESSED_TOKENS;
+  if (!kind) return null;
+  if (kind === 'photon') {
+    return new PhotonCompressedTokenSource(rpc, process.env.COMPRESSED_TOKEN_RPC_URL || null);
+  }
+  throw new Error(`Invalid SNAPSHOT_COMPRESSED_TOKENS: ${kind} (expected photon)`);
+}
@@ -0,0 +1,220 @@
+// src/indexers/dead-letters.ts
+// Dead-letter queue of distributor transactions that failed to decode or store, and its replay
+
+import { pool } from '../db';
+import { DeploymentConfig } from '../config/deployments';
+import { deadLetters } from '../utils/metrics';
+import { FailoverConnection } from '../utils/rpc';
+import { ClaimEvent, Commitment, decodeClaimEvents, persistClaimEvents, ProgramTransaction } from './claim-events';
+import { SlotTimeMapper } from './slot-times';
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type IngestionStage = 'decode' | 'persist';
+
+export interface IngestContext {
+  deployment: string;
+  programId: string;
+  commitment: Commitment;
+  source: string;
+  slotTimes: SlotTimeMapper;
+}
+
+export interface DeadLetter {
+  id: number;
+  signature: string;
+  slot: number;
+  source: string;
+  stage: IngestionStage;
+  error: string;
+  attempts: number;
+  lastFailedAt: Date;
+}
+
+export interface ReplayResult {
+  replayed: number;
+  failed: number;
+  events: number;
+}
+
+// ---------------------------
+// Ingestion
+// ---------------------------
+
+/**
+ * Decode and store the events of `tx`; returns them, or null if the
+ * transaction was dead-lettered instead
+ *
+ * A transaction that fails to decode or store is recorded in
+ * `ingestion_dead_letters` with its payload rather than thrown, so one bad
+ * transaction doesn't stall or drop the stream. Throws only if the dead
+ * letter can't be recorded either (the database is down), leaving the
+ * caller's own retry to it.
+ */
+export async function ingestTransaction(tx: ProgramTransaction, ctx: IngestContext): Promise<ClaimEvent[] | null> {
+  let stage: IngestionStage = 'decode';
+  try {
+    const events = decodeClaimEvents(tx, ctx.programId);
+    if (events.length > 0) {
+      stage = 'persist';
+      // Geyser updates carry no block time, and pruned slots have none
+      const blockTime = await ctx.slotTimes.resolve(tx.slot, tx.blockTime);
+      await persistClaimEvents(events, {
+        deployment: ctx.deployment,
+        commitment: ctx.commitment,
+        blockTime,
+        source: ctx.source,
+      });
+    }
+    return events;
+  } catch (error: any) {
+    try {
+      await recordDeadLetter(tx, ctx, stage, error);
+    } catch (recordError: any) {
+      console.error(`[${ctx.deployment}] Failed to dead-letter ${tx.signature}:`, recordError.message);
+      throw error;
+    }
+    return null;
+  }
+}
+
+async function recordDeadLetter(
+  tx: ProgramTransaction,
+  ctx: IngestContext,
+  stage: IngestionStage,
+  error: any
+): Promise<void> {
+  const message = error?.message ?? String(error);
+  await pool.query(
+    `
+    INSERT INTO ingestion_dead_letters (deployment, signature, slot, source, commitment, stage, error, payload)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+    ON CONFLICT (deployment, signature) DO UPDATE
+    SET
+      slot = EXCLUDED.slot,
+      source = EXCLUDED.source,
+      commitment = EXCLUDED.commitment,
+      stage = EXCLUDED.stage,
+      error = EXCLUDED.error,
+      payload = EXCLUDED.payload,
+      status = 'pending',
+      attempts = ingestion_dead_letters.attempts + 1,
+      last_failed_at = NOW()
+    `,
+    [ctx.deployment, tx.signature, tx.slot, ctx.source, ctx.commitment, stage, message, toPayload(tx)]
+  );
+  deadLetters.inc({ deployment: ctx.deployment, stage });
+  console.error(`[${ctx.deployment}] Dead-lettered ${tx.signature} (${stage} failed): ${message}`);
+}
+
+// Inner instruction data is stored base64
+function toPayload(tx: ProgramTransaction): unknown {
+  return {
+    ...tx,
+    innerInstructions: tx.innerInstructions.map((ix) => ({ ...ix, data: Buffer.from(ix.data).toString('base64') })),
+  };
+}
+
+function fromPayload(payload: any): ProgramTransaction {
+  return {
+    ...payload,
+    innerInstructions: payload.innerInstructions.map((ix: any) => ({ ...ix, data: Buffer.from(ix.data, 'base64') })),
+  };
+}
+
+// ---------------------------
+// Queue
+// ---------------------------
+
+/**
+ * Pending dead letters of `deployment`, oldest slot first
+ */
+export async function listDeadLetters(deployment: string): Promise<DeadLetter[]> {
+  const { rows } = await pool.query(
+    `
+    SELECT id, signature, slot, source, stage, error, attempts, last_failed_at
+    FROM ingestion_dead_letters
+    WHERE deployment = $1 AND status = 'pending'
+    ORDER BY slot, id
+    `,
+    [deployment]
+  );
+  return rows.map((row) => ({
+    id: Number(row.id),
+    signature: row.signature,
+    slot: Number(row.slot),
+    source: row.source,
+    stage: row.stage,
+    error: row.error,
+    attempts: row.attempts,
+    lastFailedAt: row.last_failed_at,
+  }));
+}
+
+/**
+ * Re-process pending dead letters of `deployment` (only `ids`, if given),
+ * oldest slot first
+ *
+ * Each goes through `ingestTransaction` again with its original source and
+ * commitment: events below finalized are staged and finalize as usual. A
+ * transaction that still fails stays pending with its attempts counted.
+ */
+export async function replayDeadLetters(
+  deployment: DeploymentConfig,
+  options: { ids?: number[]; limit?: number } = {}
+): Promise<ReplayResult> {
+  const { rows } = await pool.query<{ id: string; source: string; commitment: Commitment; payload: unknown }>(
+    `
+    SELECT id, source, commitment, payload
+    FROM ingestion_dead_letters
+    WHERE deployment = $1
+      AND status = 'pending'
+      AND payload IS NOT NULL
+      AND ($2::bigint[] IS NULL OR id = ANY($2))
+    ORDER BY slot, id
+    LIMIT $3
+    `,
+    [deployment.name, options.ids ?? null, options.limit ?? null]
+  );
+
+  const slotTimes = new SlotTimeMapper(deployment.name, new FailoverConnection(deployment.rpc));
+  const result: ReplayResult = { replayed: 0, failed: 0, events: 0 };
+
+  for (const row of rows) {
+    const events = await ingestTransaction(fromPayload(row.payload), {
+      deployment: deployment.name,
+      programId: deployment.programId,
+      commitment: row.commitment,
+      source: row.source,
+      slotTimes,
+    });
+    if (events === null) {
+      result.failed++;
+      continue;
+    }
+    await pool.query(
+      `UPDATE ingestion_dead_letters SET status = 'replayed', replayed_at = NOW() WHERE id = $1`,
+      [row.id]
+    );
+    result.replayed++;
+    result.events += events.length;
+  }
+  return result;
+}
+
+/**
+ * Give up on pending dead letters `ids` of `deployment`; returns how many were discarded
+ */
+export async function discardDeadLetters(deployment: string, ids: number[]): Promise<number> {
+  const result = await pool.query(
+    `
+    UPDATE ingestion_dead_letters
+    SET status = 'discarded'
+    WHERE deployment = $1 AND status = 'pending' AND id = ANY($2)
+    `,
+    [deployment, ids]
+  );
+  return result.rowCount ?? 0;
+}
@@ -0,0 +1,474 @@
+// src/indexers/epoch-snapshot.ts
+// Epoch-boundary holder snapshots of an LST mint, pinned to a single slot
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID } from '@solana/spl-token';
+import bs58 from 'bs58';
+import { pool } from '../db';
+import { sendAlert } from '../alerts/notify';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+import { getCompressedTokenSourceFromEnv } from './compressed-tokens';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const rpcConfig = getRpcConfigFromEnv();
+const rpc = new FailoverConnection(rpcConfig);
+const compressedTokens = getCompressedTokenSourceFromEnv(rpc);
+
+// Token account layout: mint (0..32), owner (32..64), amount (64..72, little-endian).
+// Only owner + amount are fetched.
+const OWNER_OFFSET = 32;
+const SLICE_LENGTH = 40;
+const TOKEN_ACCOUNT_SIZE = 165;
+
+// Rows per INSERT when persisting balances
+const INSERT_BATCH_SIZE = 5000;
+
+// Mint layout: mint_authority (COption<Pubkey>, 0..36), supply (36..44)
+const MINT_SUPPLY_OFFSET = 36;
+
+// Sharded scans (SNAPSHOT_GPA_SHARDED=true): one getProgramAccounts call per
+// first owner byte, SNAPSHOT_GPA_CONCURRENCY at a time
+const SHARD_COUNT = 256;
+const DEFAULT_SHARD_CONCURRENCY = 16;
+
+// Rounds of re-fetching shards answered at another slot than the newest shard
+const DEFAULT_SHARD_PIN_ROUNDS = 5;
+
+// Largest accepted difference between a snapshot's total and the mint supply, in basis points
+const DEFAULT_SUPPLY_TOLERANCE_BPS = 10;
+
+// Largest accepted gap between the token account scan and the compressed token indexer
+const DEFAULT_MAX_COMPRESSED_SLOT_SPREAD = 150;
+
+// ---------------------------
+// Types
+// ---------------------------
+export type TokenBalance = {
+  tokenAccount: string;
+  owner: string;
+  amount: bigint;
+};
+
+export type EpochSnapshotResult = {
+  epoch: number;
+  slot: number | null;
+  skipped: boolean;
+  tokenAccounts: number;
+  holders: number;
+  totalAmount: bigint;
+};
+
+export type SupplyCheck = {
+  total: bigint;
+  supply: bigint;
+  supplySlot: number;
+  // |total - supply| in basis points of the supply
+  diffBps: number;
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * Current epoch and its first slot, as seen at finalized commitment
+ */
+async function getEpochBoundary(): Promise<{ epoch: number; firstSlot: number }> {
+  const info = await rpc.execute(
+    (connection) => connection.getEpochInfo('finalized'),
+    'getEpochInfo'
+  );
+  return { epoch: info.epoch, firstSlot: info.absoluteSlot - info.slotIndex };
+}
+
+/**
+ * Token program that owns the mint (SPL Token or Token-2022)
+ */
+export async function getTokenProgram(mint: PublicKey): Promise<PublicKey> {
+  const account = await rpc.execute(
+    (connection) => connection.getAccountInfo(mint),
+    'getMintAccount'
+  );
+  if (!account) {
+    throw new Error(`Mint not found: ${mint.toBase58()}`);
+  }
+  if (!account.owner.equals(TOKEN_PROGRAM_ID) && !account.owner.equals(TOKEN_2022_PROGRAM_ID)) {
+    throw new Error(`${mint.toBase58()} is not a token mint (owner ${account.owner.toBase58()})`);
+  }
+  return account.owner;
+}
+
+function envNumber(name: string, fallback: number): number {
+  const value = process.env[name];
+  if (!value) return fallback;
+  const n = Number(value);
+  if (!Number.isFinite(n) || n < 0) throw new Error(`Invalid ${name}: ${value}`);
+  return n;
+}
+
+/**
+ * Fetch every token account of `mint` as of one slot at or after `minContextSlot`
+ *
+ * The scan covers the program that owns the mint, SPL Token or Token-2022.
+ * A single getProgramAccounts call by default. With SNAPSHOT_GPA_SHARDED=true
+ * the scan is split by the first byte of the owner (256 calls), for mints
+ * whose full scan times out. Empty token accounts are skipped.
+ *
+ * With SNAPSHOT_COMPRESSED_TOKENS set, compressed token balances are added
+ * (see `mergeCompressedBalances`).
+ */
+export async function fetchBalancesAtSlot(
+  mint: PublicKey,
+  tokenProgram: PublicKey,
+  minContextSlot: number
+): Promise<{ slot: number; balances: TokenBalance[] }> {
+  const scan =
+    process.env.SNAPSHOT_GPA_SHARDED === 'true'
+      ? await fetchBalancesSharded(mint, tokenProgram, minContextSlot)
+      : await fetchShard(mint, tokenProgram, minContextSlot, null);
+  if (!compressedTokens) return scan;
+  return mergeCompressedBalances(mint, scan);
+}
+
+/**
+ * Add the mint's compressed token balances to a token account scan
+ *
+ * Compressed tokens are backed by the compressed token program's token pool
+ * accounts; those are dropped from the scan so each token is counted once,
+ * under the compressed account's owner. The indexer answers at its own slot,
+ * which must be within SNAPSHOT_MAX_COMPRESSED_SLOT_SPREAD slots of the
+ * scan's; movements in between count against the supply check's tolerance.
+ */
+async function mergeCompressedBalances(
+  mint: PublicKey,
+  scan: { slot: number; balances: TokenBalance[] }
+): Promise<{ slot: number; balances: TokenBalance[] }> {
+  const label = mint.toBase58();
+  const maxSpread = envNumber('SNAPSHOT_MAX_COMPRESSED_SLOT_SPREAD', DEFAULT_MAX_COMPRESSED_SLOT_SPREAD);
+
+  const compressed = await compressedTokens!.fetchBalances(mint);
+  const spread = Math.abs(compressed.slot - scan.slot);
+  if (spread > maxSpread) {
+    throw new Error(
+      `${label}: ${compressedTokens!.name} answered at slot ${compressed.slot}, ${spread} slots from the scan at ` +
+        `${scan.slot} (max ${maxSpread}); retry the snapshot`
+    );
+  }
+
+  const backing = new Set(compressed.backingAccounts);
+  const balances = scan.balances.filter((b) => !backing.has(b.tokenAccount));
+  console.log(
+    `${label}: ${compressed.balances.length} compressed token account(s) at slot ${compressed.slot}, ` +
+      `${scan.balances.length - balances.length} token pool account(s) left out`
+  );
+
+  return { slot: scan.slot, balances: [...balances, ...compressed.balances] };
+}
+
+/**
+ * One getProgramAccounts call, optionally limited to owners starting with `ownerPrefix`
+ *
+ * One call is answered from one bank, so every balance is as of the returned
+ * context slot. minContextSlot keeps that slot at or after the epoch boundary.
+ */
+async function fetchShard(
+  mint: PublicKey,
+  tokenProgram: PublicKey,
+  minContextSlot: number,
+  ownerPrefix: number | null
+): Promise<{ slot: number; balances: TokenBalance[] }> {
+  const response = await rpc.execute(
+    (connection) => connection.getProgramAccounts(tokenProgram, {
+      commitment: 'finalized',
+      encoding: 'base64',
+      withContext: true,
+      minContextSlot,
+      dataSlice: { offset: OWNER_OFFSET, length: SLICE_LENGTH },
+      filters: [
+        // Token-2022 accounts with extensions are larger than the base layout, so
+        // Token-2022 mints are matched on the mint alone
+        ...(tokenProgram.equals(TOKEN_PROGRAM_ID) ? [{ dataSize: TOKEN_ACCOUNT_SIZE }] : []),
+        { memcmp: { offset: 0, bytes: mint.toBase58() } },
+        ...(ownerPrefix === null
+          ? []
+          : [{ memcmp: { offset: OWNER_OFFSET, bytes: bs58.encode(Buffer.from([ownerPrefix])) } }]),
+      ],
+    }),
+    ownerPrefix === null ? 'fetchEpochBalances' : 'fetchEpochBalanceShard'
+  );
+
+  const balances = response.value
+    .map(({ pubkey, account }) => ({
+      tokenAccount: pubkey.toBase58(),
+      owner: new PublicKey(account.data.subarray(0, 32)).toBase58(),
+      amount: account.data.readBigUInt64LE(32),
+    }))
+    .filter((balance) => balance.amount > 0n);
+
+  return { slot: response.context.slot, balances };
+}
+
+/**
+ * Sharded scan, merged at the one slot every shard was answered at
+ *
+ * Shards are answered from different banks. Those whose context slot differs
+ * from the newest are re-fetched with minContextSlot at that slot, for up to
+ * SNAPSHOT_SHARD_PIN_ROUNDS rounds. If the shards still disagree the
+ * snapshot fails: balances merged from several slots would not be a
+ * snapshot of any one of them. A shard that came back short is caught by the
+ * supply check every snapshot goes through (`checkSnapshotSupply`).
+ */
+async function fetchBalancesSharded(
+  mint: PublicKey,
+  tokenProgram: PublicKey,
+  minContextSlot: number
+): Promise<{ slot: number; balances: TokenBalance[] }> {
+  const concurrency = envNumber('SNAPSHOT_GPA_CONCURRENCY', DEFAULT_SHARD_CONCURRENCY) || 1;
+  const pinRounds = envNumber('SNAPSHOT_SHARD_PIN_ROUNDS', DEFAULT_SHARD_PIN_ROUNDS);
+  const label = mint.toBase58();
+
+  const shards: ({ slot: number; balances: TokenBalance[] } | null)[] = new Array(SHARD_COUNT).fill(null);
+  let pinned = minContextSlot;
+
+  // Round 0 fetches every shard; later rounds only those off the pinned slot
+  for (let round = 0; round <= pinRounds; round++) {
+    const pending = shards.flatMap((shard, prefix) => (shard === null || shard.slot !== pinned ? [prefix] : []));
+    if (pending.length === 0) break;
+
+    const target = pinned;
+    let next = 0;
+    await Promise.all(
+      Array.from({ length: Math.min(concurrency, pending.length) }, async () => {
+        while (next < pending.length) {
+          const prefix = pending[next++];
+          shards[prefix] = await fetchShard(mint, tokenProgram, target, prefix);
+        }
+      })
+    );
+    pinned = Math.max(...shards.map((s) => s!.slot));
+    console.log(`${label}: shard round ${round}: ${pending.length} shard(s) fetched, pinned slot ${pinned}`);
+  }
+
+  const unpinned = shards.filter((s) => s!.slot !== pinned);
+  if (unpinned.length > 0) {
+    const oldest = Math.min(...unpinned.map((s) => s!.slot));
+    throw new Error(
+      `${label}: ${unpinned.length} shard(s) still answered at slots ${oldest}..${pinned - 1}, ` +
+        `not pinned slot ${pinned}, after ${pinRounds} rounds; retry the snapshot`
+    );
+  }
+
+  return { slot: pinned, balances: shards.flatMap((s) => s!.balances) };
+}
+
+/**
+ * Mint supply as of a finalized slot at or after `minContextSlot`
+ */
+export async function getMintSupplyAt(
+  mint: PublicKey,
+  minContextSlot: number
+): Promise<{ slot: number; amount: bigint }> {
+  const response = await rpc.execute(
+    (connection) => connection.getAccountInfoAndContext(mint, { commitment: 'finalized', minContextSlot }),
+    'getMintSupply'
+  );
+  if (!response.value) {
+    throw new Error(`Mint not found: ${mint.toBase58()}`);
+  }
+  return { slot: response.context.slot, amount: response.value.data.readBigUInt64LE(MINT_SUPPLY_OFFSET) };
+}
+
+/**
+ * Compare the captured balances with the mint supply at the snapshot slot
+ *
+ * Every token account (and compressed balance, when enabled) is captured,
+ * excluded wallets included (they are dropped at allocation), so the total
+ * should match the whole supply. The supply is read no earlier than `slot`;
+ * deposits and withdrawals landing in between count against
+ * SNAPSHOT_SUPPLY_TOLERANCE_BPS. Beyond the tolerance an alert is sent
+ * under `alertKey` and this throws, so the caller writes nothing.
+ */
+export async function checkSnapshotSupply(
+  mint: PublicKey,
+  slot: number,
+  balances: TokenBalance[],
+  alertKey: string,
+  alertCooldownMinutes?: number
+): Promise<SupplyCheck> {
+  const total = balances.reduce((sum, b) => sum + b.amount, 0n);
+  const supply = await getMintSupplyAt(mint, slot);
+  const toleranceBps = envNumber('SNAPSHOT_SUPPLY_TOLERANCE_BPS', DEFAULT_SUPPLY_TOLERANCE_BPS);
+
+  const diff = total > supply.amount ? total - supply.amount : supply.amount - total;
+  const diffBps = supply.amount === 0n ? (total === 0n ? 0 : Infinity) : Number((diff * 10_000n) / supply.amount);
+  const check = { total, supply: supply.amount, supplySlot: supply.slot, diffBps };
+  if (diffBps <= toleranceBps) return check;
+
+  const message =
+    `${mint.toBase58()}: captured ${total} at slot ${slot} but supply is ${supply.amount} at slot ${supply.slot} ` +
+    `(${diffBps} bps off, tolerance ${toleranceBps})`;
+  await sendAlert(
+    {
+      rule: 'snapshot_supply_mismatch',
+      key: alertKey,
+      severity: 'critical',
+      title: `Snapshot of ${mint.toBase58()} does not match the mint supply; not written`,
+      detail: message,
+    },
+    alertCooldownMinutes
+  ).catch((error) => console.error(`[alert] snapshot_supply_mismatch not recorded: ${error.message}`));
+  throw new Error(`Snapshot integrity check failed: ${message}`);
+}
+
+/**
+ * Persist one epoch's snapshot atomically
+ *
+ * Returns false if a snapshot for this epoch and mint already exists (e.g.
+ * written by a concurrent runner).
+ */
+async function persistEpochSnapshot(
+  epoch: number,
+  mint: string,
+  firstSlot: number,
+  slot: number,
+  blockTime: number | null,
+  balances: TokenBalance[],
+  supply: SupplyCheck
+): Promise<boolean> {
+  const holders = new Set(balances.map((b) => b.owner));
+  const totalAmount = balances.reduce((sum, b) => sum + b.amount, 0n);
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    const inserted = await client.query(
+      `
+      INSERT INTO epoch_snapshots
+        (epoch, mint, epoch_first_slot, slot, block_time, token_accounts, holders, total_amount, supply, supply_slot)
+      VALUES ($1, $2, $3, $4, to_timestamp($5), $6, $7, $8, $9, $10)
+      ON CONFLICT (epoch, mint) DO NOTHING
+      `,
+      [
+        epoch,
+        mint,
+        firstSlot,
+        slot,
+        blockTime,
+        balances.length,
+        holders.size,
+        totalAmount.toString(),
+        supply.supply.toString(),
+        supply.supplySlot,
+      ]
+    );
+    if (inserted.rowCount === 0) {
+      await client.query('ROLLBACK');
+      return false;
+    }
+
+    await client.query(
+      `
+      INSERT INTO wallets (wallet, first_seen)
+      SELECT DISTINCT owner, NOW() FROM unnest($1::text[]) AS owner
+      ON CONFLICT (wallet) DO NOTHING
+      `,
+      [[...holders]]
+    );
+
+    for (let i = 0; i < balances.length; i += INSERT_BATCH_SIZE) {
+      const batch = balances.slice(i, i + INSERT_BATCH_SIZE);
+      await client.query(
+        `
+        INSERT INTO epoch_snapshot_balances (epoch, mint, token_account, owner, amount)
+        SELECT $1, $2, token_account, owner, amount
+        FROM unnest($3::text[], $4::text[], $5::numeric[]) AS b(token_account, owner, amount)
+        `,
+        [
+          epoch,
+          mint,
+          batch.map((b) => b.tokenAccount),
+          batch.map((b) => b.owner),
+          batch.map((b) => b.amount.toString()),
+        ]
+      );
+    }
+
+    await client.query('COMMIT');
+    return true;
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Snapshot the current epoch of `snapshotMint` once
+ *
+ * Safe to call on any schedule: it is a no-op until the chain crosses into
+ * an epoch that has no snapshot yet. Different mints can run concurrently.
+ */
+export async function runEpochSnapshot(snapshotMint: PublicKey): Promise<EpochSnapshotResult> {
+  const mint = snapshotMint.toBase58();
+  const { epoch, firstSlot } = await getEpochBoundary();
+
+  const existing = await pool.query(
+    `SELECT slot FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, mint]
+  );
+  if (existing.rows.length > 0) {
+    return {
+      epoch,
+      slot: Number(existing.rows[0].slot),
+      skipped: true,
+      tokenAccounts: 0,
+      holders: 0,
+      totalAmount: 0n,
+    };
+  }
+
+  console.log(`Epoch ${epoch} snapshot of ${mint} (first slot ${firstSlot})`);
+  console.log(`RPC: ${rpc.getCurrentUrl()}${rpc.hasBackup() ? ' (backup configured)' : ''}`);
+
+  const tokenProgram = await getTokenProgram(snapshotMint);
+  const { slot, balances } = await fetchBalancesAtSlot(snapshotMint, tokenProgram, firstSlot);
+
+  // Block time is informational; older slots may have none
+  const blockTime = await rpc
+    .execute((connection) => connection.getBlockTime(slot), 'getBlockTime')
+    .catch(() => null);
+
+  const holders = new Set(balances.map((b) => b.owner)).size;
+  const totalAmount = balances.reduce((sum, b) => sum + b.amount, 0n);
+  console.log(`${mint}: pinned slot ${slot} (${slot - firstSlot} slots after the boundary)`);
+  console.log(`${mint}: ${balances.length} token accounts, ${holders} holders, total ${totalAmount}`);
+
+  // Throws (and alerts once per epoch) on a partial snapshot; nothing is written
+  const supply = await checkSnapshotSupply(snapshotMint, slot, balances, `${mint}:${epoch}`);
+  console.log(`${mint}: supply ${supply.supply} at slot ${supply.supplySlot} (${supply.diffBps} bps off)`);
+
+  const written = await persistEpochSnapshot(epoch, mint, firstSlot, slot, blockTime, balances, supply);
+  if (!written) {
+    console.log(`${mint}: epoch ${epoch} was snapshotted concurrently; discarded this run`);
+  } else {
+    console.log(`${mint}: epoch ${epoch} snapshot written`);
+  }
+
+  return {
+    epoch,
+    slot,
+    skipped: !written,
+    tokenAccounts: balances.length,
+    holders,
+    totalAmount,
+  };
+}
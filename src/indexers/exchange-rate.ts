@@ -0,0 +1,346 @@
+// src/indexers/exchange-rate.ts
+// Per-epoch LST -> SOL exchange rates read from stake-pool state
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const rpcConfig = getRpcConfigFromEnv();
+const rpc = new FailoverConnection(rpcConfig);
+
+const SPL_STAKE_POOL_PROGRAM_ID = new PublicKey('SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy');
+const MARINADE_PROGRAM_ID = new PublicKey('MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD');
+
+const JITO_STAKE_POOL = 'Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb';
+const MARINADE_STATE = '8szGkuLTAux9XMgZ2vtY39jVSowEcpBfFfD8hXSEqdGC';
+
+// Marinade stores msol_price as a fixed-point number scaled by 2^32
+const MARINADE_PRICE_DENOMINATOR = 1n << 32n;
+
+// Digits kept in the derived sol_per_token column
+const RATE_SCALE_DIGITS = 18;
+
+// ---------------------------
+// Types
+// ---------------------------
+export type StakePoolKind = 'spl' | 'jito' | 'marinade';
+
+/**
+ * Exchange rate as an exact fraction: 1 token = numerator / denominator SOL
+ * (lamports per raw token unit, both 9 decimals)
+ */
+export type ExchangeRate = {
+  mint: string;
+  numerator: bigint;
+  denominator: bigint;
+  // Epoch the pool state was last updated in (null if the pool doesn't record it)
+  poolLastUpdateEpoch: number | null;
+};
+
+/**
+ * Account the pool's operator controls (fees, admin), never a reward recipient
+ */
+export type PoolOperatorAccount = {
+  role: string;
+  address: string;
+  // Token account whose owner is the operator wallet, rather than a wallet itself
+  tokenAccount: boolean;
+};
+
+/**
+ * Stake account the pool delegates from
+ */
+export type PoolStakeAccount = {
+  address: string;
+  // Validator stake account, or transient stake (activating / deactivating)
+  transient: boolean;
+};
+
+export interface StakePoolAdapter {
+  kind: StakePoolKind;
+  address: PublicKey;
+  programId: PublicKey;
+  decode(data: Buffer): ExchangeRate;
+  operatorAccounts(data: Buffer): PoolOperatorAccount[];
+  // Account listing the pool's validators or stake accounts
+  stakeListAddress(data: Buffer): PublicKey;
+  stakeAccounts(data: Buffer, listData: Buffer): PoolStakeAccount[];
+}
+
+// ---------------------------
+// Adapters
+// ---------------------------
+
+// SPL ValidatorList: account_type (1), max_validators (4), vec length (4), then entries
+const VALIDATOR_LIST_HEADER = 9;
+// ValidatorStakeInfo: active_stake_lamports, transient_stake_lamports, last_update_epoch,
+// transient_seed_suffix (8 each), unused (4), validator_seed_suffix (4), status (1),
+// vote_account_address (32)
+const VALIDATOR_ENTRY_SIZE = 73;
+
+// Marinade stake list: discriminator (8), then StakeRecord items of stake_list.item_size,
+// each starting with the stake account
+const MARINADE_LIST_HEADER = 8;
+
+/**
+ * SPL stake pool (also Jito, which runs the SPL program)
+ *
+ * Borsh layout: account_type (1), manager, staker, stake_deposit_authority (32 each),
+ * stake_withdraw_bump_seed (1), validator_list, reserve_stake, pool_mint,
+ * manager_fee_account, token_program_id (32 each), total_lamports (8),
+ * pool_token_supply (8), last_update_epoch (8)
+ *
+ * Stake accounts are PDAs of the pool program: [vote, pool, seed suffix if
+ * non-zero] for validator stake, ["transient", vote, pool, transient suffix]
+ * for transient stake.
+ */
+function splStakePoolAdapter(kind: StakePoolKind, address: PublicKey): StakePoolAdapter {
+  return {
+    kind,
+    address,
+    programId: SPL_STAKE_POOL_PROGRAM_ID,
+    decode(data: Buffer): ExchangeRate {
+      if (data.readUInt8(0) !== 1) {
+        throw new Error(`${address.toBase58()} is not an initialized stake pool`);
+      }
+      return {
+        mint: new PublicKey(data.subarray(162, 194)).toBase58(),
+        numerator: data.readBigUInt64LE(258),
+        denominator: data.readBigUInt64LE(266),
+        poolLastUpdateEpoch: Number(data.readBigUInt64LE(274)),
+      };
+    },
+    operatorAccounts(data: Buffer): PoolOperatorAccount[] {
+      return [
+        { role: 'manager', address: new PublicKey(data.subarray(1, 33)).toBase58(), tokenAccount: false },
+        { role: 'staker', address: new PublicKey(data.subarray(33, 65)).toBase58(), tokenAccount: false },
+        {
+          role: 'manager fee account',
+          address: new PublicKey(data.subarray(194, 226)).toBase58(),
+          tokenAccount: true,
+        },
+      ];
+    },
+    stakeListAddress(data: Buffer): PublicKey {
+      return new PublicKey(data.subarray(98, 130));
+    },
+    stakeAccounts(_data: Buffer, listData: Buffer): PoolStakeAccount[] {
+      const count = listData.readUInt32LE(5);
+      const accounts: PoolStakeAccount[] = [];
+      for (let i = 0; i < count; i++) {
+        const entry = listData.subarray(
+          VALIDATOR_LIST_HEADER + i * VALIDATOR_ENTRY_SIZE,
+          VALIDATOR_LIST_HEADER + (i + 1) * VALIDATOR_ENTRY_SIZE
+        );
+        const vote = entry.subarray(41, 73);
+        const seed = entry.readUInt32LE(36);
+        const [stake] = PublicKey.findProgramAddressSync(
+          [vote, address.toBuffer(), ...(seed === 0 ? [] : [u32(seed)])],
+          SPL_STAKE_POOL_PROGRAM_ID
+        );
+        accounts.push({ address: stake.toBase58(), transient: false });
+
+        if (entry.readBigUInt64LE(8) > 0n) {
+          const [transient] = PublicKey.findProgramAddressSync(
+            [Buffer.from('transient'), vote, address.toBuffer(), entry.subarray(24, 32)],
+            SPL_STAKE_POOL_PROGRAM_ID
+          );
+          accounts.push({ address: transient.toBase58(), transient: true });
+        }
+      }
+      return accounts;
+    },
+  };
+}
+
+function u32(value: number): Buffer {
+  const buffer = Buffer.alloc(4);
+  buffer.writeUInt32LE(value);
+  return buffer;
+}
+
+/**
+ * Marinade liquid staking State
+ *
+ * Anchor layout: discriminator (8), msol_mint, admin_authority,
+ * operational_sol_account, treasury_msol_account (32 each), two bump seeds (1
+ * each), rent_exempt_for_token_acc (8), reward_fee (4), then the stake
+ * system, whose stake_list is { account (32), item_size (4), count (4) } at
+ * byte 150 ... msol_price (8) at byte 512, after the stake system, validator
+ * system and liquidity pool blocks.
+ */
+function marinadeAdapter(address: PublicKey): StakePoolAdapter {
+  return {
+    kind: 'marinade',
+    address,
+    programId: MARINADE_PROGRAM_ID,
+    decode(data: Buffer): ExchangeRate {
+      return {
+        mint: new PublicKey(data.subarray(8, 40)).toBase58(),
+        numerator: data.readBigUInt64LE(512),
+        denominator: MARINADE_PRICE_DENOMINATOR,
+        poolLastUpdateEpoch: null,
+      };
+    },
+    operatorAccounts(data: Buffer): PoolOperatorAccount[] {
+      return [
+        { role: 'admin authority', address: new PublicKey(data.subarray(40, 72)).toBase58(), tokenAccount: false },
+        {
+          role: 'operational SOL account',
+          address: new PublicKey(data.subarray(72, 104)).toBase58(),
+          tokenAccount: false,
+        },
+        {
+          role: 'treasury mSOL account',
+          address: new PublicKey(data.subarray(104, 136)).toBase58(),
+          tokenAccount: true,
+        },
+      ];
+    },
+    stakeListAddress(data: Buffer): PublicKey {
+      return new PublicKey(data.subarray(150, 182));
+    },
+    stakeAccounts(data: Buffer, listData: Buffer): PoolStakeAccount[] {
+      // The list account is sized to its capacity; item size and count are in the State
+      const itemSize = data.readUInt32LE(182);
+      const count = data.readUInt32LE(186);
+      const accounts: PoolStakeAccount[] = [];
+      for (let i = 0; i < count; i++) {
+        const offset = MARINADE_LIST_HEADER + i * itemSize;
+        accounts.push({
+          address: new PublicKey(listData.subarray(offset, offset + 32)).toBase58(),
+          transient: false,
+        });
+      }
+      return accounts;
+    },
+  };
+}
+
+/**
+ * Adapter for a stake pool of `kind`
+ *
+ * Jito and Marinade default to their mainnet accounts; SPL pools need an address.
+ */
+export function getStakePoolAdapter(kind: StakePoolKind, address?: string): StakePoolAdapter {
+  switch (kind) {
+    case 'spl':
+      if (!address) throw new Error('Missing stake pool address for an spl pool');
+      return splStakePoolAdapter('spl', new PublicKey(address));
+    case 'jito':
+      return splStakePoolAdapter('jito', new PublicKey(address || JITO_STAKE_POOL));
+    case 'marinade':
+      return marinadeAdapter(new PublicKey(address || MARINADE_STATE));
+    default:
+      throw new Error(`Unknown stake pool kind: ${kind} (expected spl, jito or marinade)`);
+  }
+}
+
+/**
+ * Fixed-point decimal string for numerator / denominator
+ */
+export function formatRate(numerator: bigint, denominator: bigint): string {
+  const scale = 10n ** BigInt(RATE_SCALE_DIGITS);
+  const scaled = (numerator * scale) / denominator;
+  const whole = scaled / scale;
+  const fraction = (scaled % scale).toString().padStart(RATE_SCALE_DIGITS, '0');
+  return `${whole}.${fraction}`;
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+export type ExchangeRateResult = {
+  epoch: number;
+  skipped: boolean;
+  reason?: string;
+  rate?: string;
+};
+
+/**
+ * Record the current epoch's exchange rate once
+ *
+ * The pool account is read at finalized commitment, no earlier than the
+ * epoch's first slot. SPL pools are only recorded once their state has
+ * been updated for the epoch; until then the call is skipped so the
+ * scheduler retries it.
+ */
+export async function recordEpochExchangeRate(
+  adapter: StakePoolAdapter,
+  expectedMint: string
+): Promise<ExchangeRateResult> {
+  const info = await rpc.execute(
+    (connection) => connection.getEpochInfo('finalized'),
+    'getEpochInfo'
+  );
+  const epoch = info.epoch;
+  const firstSlot = info.absoluteSlot - info.slotIndex;
+
+  const existing = await pool.query(
+    `SELECT 1 FROM epoch_exchange_rates WHERE epoch = $1 AND mint = $2`,
+    [epoch, expectedMint]
+  );
+  if (existing.rows.length > 0) {
+    return { epoch, skipped: true, reason: 'already recorded' };
+  }
+
+  const response = await rpc.execute(
+    (connection) => connection.getAccountInfoAndContext(adapter.address, {
+      commitment: 'finalized',
+      minContextSlot: firstSlot,
+    }),
+    'getStakePool'
+  );
+  const account = response.value;
+  if (!account) {
+    throw new Error(`Stake pool account not found: ${adapter.address.toBase58()}`);
+  }
+  if (!account.owner.equals(adapter.programId)) {
+    throw new Error(
+      `${adapter.address.toBase58()} is owned by ${account.owner.toBase58()}, not the ${adapter.kind} program`
+    );
+  }
+
+  const rate = adapter.decode(account.data);
+  if (rate.mint !== expectedMint) {
+    throw new Error(`Stake pool mint ${rate.mint} does not match ${expectedMint}`);
+  }
+  if (rate.denominator === 0n) {
+    throw new Error('Stake pool has no tokens outstanding');
+  }
+  if (rate.poolLastUpdateEpoch !== null && rate.poolLastUpdateEpoch < epoch) {
+    return {
+      epoch,
+      skipped: true,
+      reason: `pool last updated in epoch ${rate.poolLastUpdateEpoch}`,
+    };
+  }
+
+  const solPerToken = formatRate(rate.numerator, rate.denominator);
+  await pool.query(
+    `
+    INSERT INTO epoch_exchange_rates
+      (epoch, mint, pool_kind, pool_address, slot, rate_numerator, rate_denominator, sol_per_token, pool_last_update_epoch)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+    ON CONFLICT (epoch, mint) DO NOTHING
+    `,
+    [
+      epoch,
+      expectedMint,
+      adapter.kind,
+      adapter.address.toBase58(),
+      response.context.slot,
+      rate.numerator.toString(),
+      rate.denominator.toString(),
+      solPerToken,
+      rate.poolLastUpdateEpoch,
+    ]
+  );
+
+  console.log(`Epoch ${epoch} exchange rate (${adapter.kind}): 1 token = ${solPerToken} SOL`);
+  return { epoch, skipped: false, rate: solPerToken };
+}
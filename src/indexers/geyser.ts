@@ -0,0 +1,174 @@
+// src/indexers/geyser.ts
+// Yellowstone gRPC (Geyser) stream of transactions touching the distributor
+
+import Client, { CommitmentLevel, SubscribeRequest, SubscribeUpdate } from '@triton-one/yellowstone-grpc';
+import { PublicKey } from '@solana/web3.js';
+import bs58 from 'bs58';
+import { Commitment, getClaimEventsCommitmentFromEnv, ProgramTransaction } from './claim-events';
+
+export interface GeyserConfig {
+  endpoint: string;
+  xToken?: string;
+  commitment: Commitment;
+}
+
+const COMMITMENT_LEVELS: Record<Commitment, CommitmentLevel> = {
+  processed: CommitmentLevel.PROCESSED,
+  confirmed: CommitmentLevel.CONFIRMED,
+  finalized: CommitmentLevel.FINALIZED,
+};
+
+// Keep idle streams open through load balancers
+const PING_INTERVAL_MS = 30_000;
+const MAX_RECONNECT_DELAY_MS = 30_000;
+
+/**
+ * Get Geyser configuration from environment variables, or null if unset
+ */
+export function getGeyserConfigFromEnv(): GeyserConfig | null {
+  const endpoint = process.env.GEYSER_ENDPOINT;
+  if (!endpoint) return null;
+
+  return {
+    endpoint,
+    xToken: process.env.GEYSER_X_TOKEN || undefined,
+    commitment: getClaimEventsCommitmentFromEnv(),
+  };
+}
+
+function subscribeRequest(programId: string, commitment: Commitment, pingId?: number): SubscribeRequest {
+  return {
+    accounts: {},
+    slots: {},
+    transactions: {
+      distributor: {
+        vote: false,
+        failed: false,
+        accountInclude: [programId],
+        accountExclude: [],
+        accountRequired: [],
+      },
+    },
+    transactionsStatus: {},
+    blocks: {},
+    blocksMeta: {},
+    entry: {},
+    accountsDataSlice: [],
+    commitment: COMMITMENT_LEVELS[commitment],
+    ping: pingId === undefined ? undefined : { id: pingId },
+  };
+}
+
+/**
+ * Normalize a Geyser transaction update, or null for other updates
+ */
+export function toProgramTransaction(update: SubscribeUpdate): ProgramTransaction | null {
+  const info = update.transaction?.transaction;
+  const message = info?.transaction?.message;
+  const meta = info?.meta;
+  if (!info || !message || !meta || meta.err) return null;
+
+  const key = (bytes: Uint8Array) => new PublicKey(bytes).toBase58();
+  return {
+    signature: bs58.encode(info.signature),
+    slot: Number(update.transaction!.slot),
+    // Not carried on transaction updates
+    blockTime: null,
+    accountKeys: [
+      ...message.accountKeys.map(key),
+      ...meta.loadedWritableAddresses.map(key),
+      ...meta.loadedReadonlyAddresses.map(key),
+    ],
+    instructions: message.instructions.map((ix) => ({
+      programIdIndex: ix.programIdIndex,
+      accounts: Array.from(ix.accounts),
+    })),
+    innerInstructions: meta.innerInstructions.flatMap((inner) =>
+      inner.instructions.map((ix, innerIndex) => ({
+        instructionIndex: inner.index,
+        innerIndex,
+        programIdIndex: ix.programIdIndex,
+        accounts: Array.from(ix.accounts),
+        data: ix.data,
+        stackHeight: ix.stackHeight ?? null,
+      }))
+    ),
+    logMessages: meta.logMessages,
+  };
+}
+
+/**
+ * Stream successful transactions touching `programId` until the process exits
+ *
+ * Reconnects with backoff when the stream drops. After every (re)subscribe
+ * `onSubscribed` runs before any streamed transaction is handed on, so the
+ * caller can replay what landed before the stream started; streamed
+ * transactions wait behind it. Without it the gap is only logged.
+ * `onTransaction` calls are serialized in stream order.
+ */
+export async function streamProgramTransactions(
+  config: GeyserConfig,
+  programId: string,
+  onTransaction: (tx: ProgramTransaction) => Promise<void>,
+  onSubscribed?: () => Promise<void>
+): Promise<never> {
+  let attempt = 0;
+  let lastSlot: number | null = null;
+
+  for (;;) {
+    try {
+      const client = new Client(config.endpoint, config.xToken, {
+        'grpc.max_receive_message_length': 64 * 1024 * 1024,
+      });
+      const stream = await client.subscribe();
+
+      await new Promise<void>((resolve, reject) => {
+        let queue = Promise.resolve();
+        let pingId = 0;
+        const ping = setInterval(() => {
+          stream.write(subscribeRequest(programId, config.commitment, ++pingId));
+        }, PING_INTERVAL_MS);
+
+        const done = (error?: Error) => {
+          clearInterval(ping);
+          stream.destroy();
+          if (error) reject(error);
+          else resolve();
+        };
+
+        stream.on('error', done);
+        stream.on('end', () => done());
+        stream.on('data', (update: SubscribeUpdate) => {
+          const tx = toProgramTransaction(update);
+          if (!tx) return;
+          queue = queue
+            .then(async () => {
+              await onTransaction(tx);
+              lastSlot = Math.max(lastSlot ?? 0, tx.slot);
+              attempt = 0;
+            })
+            .catch(done);
+        });
+
+        stream.write(subscribeRequest(programId, config.commitment), (error: Error | null | undefined) => {
+          if (error) return done(error);
+          console.log(`Geyser: subscribed to ${programId} (${config.commitment})`);
+          if (onSubscribed) queue = queue.then(onSubscribed).catch(done);
+        });
+      });
+
+      console.warn('Geyser: stream ended');
+    } catch (error: any) {
+      console.error('Geyser: stream failed:', error.message);
+    }
+
+    attempt++;
+    const delay = Math.min(1000 * 2 ** (attempt - 1), MAX_RECONNECT_DELAY_MS);
+    if (lastSlot !== null && !onSubscribed) {
+      console.warn(`Geyser: reconnecting in ${delay}ms (last processed slot ${lastSlot}; backfill from there if needed)`);
+    } else {
+      console.warn(`Geyser: reconnecting in ${delay}ms`);
+    }
+    await new Promise((resolve) => setTimeout(resolve, delay));
+  }
+}
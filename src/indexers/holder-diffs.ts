@@ -0,0 +1,258 @@
+// src/indexers/holder-diffs.ts
+// Holder set diffs between epoch snapshots: who entered, who exited, and how the balances of the rest changed
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getIgnoredWalletsArray } from '../api/queries/ignored-wallets';
+
+// ---------------------------
+// Types
+// ---------------------------
+
+/**
+ * Where a wallet ended up between two snapshots:
+ * - entered / exited: joined or left the holder set
+ * - up_100, up_25, up: balance grew by >= 100%, >= 25%, or less
+ * - unchanged
+ * - down, down_25, down_75: balance shrank by less than 25%, >= 25%, or >= 75%
+ */
+export type HolderCohort =
+  | 'entered'
+  | 'exited'
+  | 'up_100'
+  | 'up_25'
+  | 'up'
+  | 'unchanged'
+  | 'down'
+  | 'down_25'
+  | 'down_75';
+
+export const HOLDER_COHORTS: HolderCohort[] = [
+  'entered',
+  'exited',
+  'up_100',
+  'up_25',
+  'up',
+  'unchanged',
+  'down',
+  'down_25',
+  'down_75',
+];
+
+export type HolderDiffKey = {
+  mint: string;
+  fromEpoch: number;
+  toEpoch: number;
+};
+
+export type HolderDiffResult = HolderDiffKey & {
+  // Already built (and not rebuilt)
+  skipped: boolean;
+  holdersFrom: number;
+  holdersTo: number;
+  // Wallets per cohort
+  cohorts: Record<HolderCohort, number>;
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+// Per-wallet balances of one snapshot, ignored wallets left out
+const HOLDERS_SQL = (epochParam: string) => `
+  SELECT wallet, amount
+  FROM epoch_snapshot_holders
+  WHERE mint = $1 AND epoch = ${epochParam} AND amount > 0 AND NOT (wallet = ANY($4))
+`;
+
+// Bands compare amounts multiplied out rather than divided, so they're exact
+const COHORT_SQL = `
+  CASE
+    WHEN amount_from = 0 THEN 'entered'
+    WHEN amount_to = 0 THEN 'exited'
+    WHEN amount_to >= amount_from * 2 THEN 'up_100'
+    WHEN amount_to * 4 >= amount_from * 5 THEN 'up_25'
+    WHEN amount_to > amount_from THEN 'up'
+    WHEN amount_to = amount_from THEN 'unchanged'
+    WHEN amount_to * 4 <= amount_from THEN 'down_75'
+    WHEN amount_to * 4 <= amount_from * 3 THEN 'down_25'
+    ELSE 'down'
+  END
+`;
+
+/**
+ * Consecutive snapshot pairs of `mint` with no diff built yet, oldest first
+ */
+export async function pendingHolderDiffs(mint: string): Promise<HolderDiffKey[]> {
+  const { rows } = await pool.query<{ from_epoch: string; to_epoch: string }>(
+    `
+    SELECT p.from_epoch::text, p.to_epoch::text
+    FROM (
+      SELECT LAG(epoch) OVER (ORDER BY epoch) AS from_epoch, epoch AS to_epoch
+      FROM epoch_snapshots
+      WHERE mint = $1
+    ) p
+    WHERE p.from_epoch IS NOT NULL
+      AND NOT EXISTS (
+        SELECT 1 FROM holder_diffs d
+        WHERE d.mint = $1 AND d.from_epoch = p.from_epoch AND d.to_epoch = p.to_epoch
+      )
+    ORDER BY p.to_epoch
+    `,
+    [mint]
+  );
+  return rows.map((r) => ({ mint, fromEpoch: Number(r.from_epoch), toEpoch: Number(r.to_epoch) }));
+}
+
+/**
+ * The snapshot of `mint` a diff ending at `toEpoch` starts from: the one
+ * before it, or with `days`, the latest one taken at least that many days
+ * earlier (null if there is none)
+ */
+export async function previousSnapshotEpoch(mint: string, toEpoch: number, days?: number): Promise<number | null> {
+  const { rows } = await pool.query<{ epoch: string }>(
+    days === undefined
+      ? `SELECT epoch::text FROM epoch_snapshots WHERE mint = $1 AND epoch < $2 ORDER BY epoch DESC LIMIT 1`
+      : `
+        SELECT s.epoch::text
+        FROM epoch_snapshots s, epoch_snapshots t
+        WHERE s.mint = $1 AND t.mint = $1 AND t.epoch = $2
+          AND s.epoch < t.epoch
+          AND s.block_time <= t.block_time - make_interval(days => $3)
+        ORDER BY s.epoch DESC
+        LIMIT 1
+        `,
+    days === undefined ? [mint, toEpoch] : [mint, toEpoch, days]
+  );
+  return rows[0] ? Number(rows[0].epoch) : null;
+}
+
+export async function latestSnapshotEpoch(mint: string): Promise<number | null> {
+  const { rows } = await pool.query<{ epoch: string | null }>(
+    `SELECT MAX(epoch)::text AS epoch FROM epoch_snapshots WHERE mint = $1`,
+    [mint]
+  );
+  return rows[0]?.epoch ? Number(rows[0].epoch) : null;
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Compare the holder sets of two snapshots of `mint` and store the result
+ *
+ * Holders are wallets with a positive balance in epoch_snapshot_holders
+ * (wallet balances plus attributed protocol positions); IGNORE_WALLETS are
+ * left out of both sides. A diff already built is left as is unless
+ * `rebuild` is set.
+ */
+export async function buildHolderDiff(
+  { mint, fromEpoch, toEpoch }: HolderDiffKey,
+  { rebuild = false }: { rebuild?: boolean } = {}
+): Promise<HolderDiffResult> {
+  if (fromEpoch >= toEpoch) {
+    throw new Error(`Epoch ${fromEpoch} is not before epoch ${toEpoch}`);
+  }
+  const params = [mint, fromEpoch, toEpoch, getIgnoredWalletsArray()];
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    const snapshots = await client.query(
+      `SELECT epoch FROM epoch_snapshots WHERE mint = $1 AND epoch IN ($2, $3) FOR SHARE`,
+      [mint, fromEpoch, toEpoch]
+    );
+    if (snapshots.rowCount !== 2) {
+      throw new Error(`${mint}: epochs ${fromEpoch} and ${toEpoch} must both have a snapshot`);
+    }
+
+    if (rebuild) {
+      await client.query(`DELETE FROM holder_diffs WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3`, [
+        mint,
+        fromEpoch,
+        toEpoch,
+      ]);
+    }
+
+    const summary = await client.query<{ holders_from: number; holders_to: number }>(
+      `
+      INSERT INTO holder_diffs (mint, from_epoch, to_epoch, holders_from, holders_to, amount_from, amount_to)
+      SELECT $1, $2, $3, f.holders, t.holders, f.amount, t.amount
+      FROM (SELECT COUNT(*)::int AS holders, COALESCE(SUM(amount), 0) AS amount FROM (${HOLDERS_SQL('$2')}) h) f,
+           (SELECT COUNT(*)::int AS holders, COALESCE(SUM(amount), 0) AS amount FROM (${HOLDERS_SQL('$3')}) h) t
+      ON CONFLICT (mint, from_epoch, to_epoch) DO NOTHING
+      RETURNING holders_from, holders_to
+      `,
+      params
+    );
+
+    const built = summary.rows[0];
+    if (built) {
+      await client.query(
+        `
+        INSERT INTO holder_diff_wallets (mint, from_epoch, to_epoch, wallet, cohort, amount_from, amount_to)
+        SELECT $1, $2, $3, wallet, ${COHORT_SQL}, amount_from, amount_to
+        FROM (
+          SELECT COALESCE(t.wallet, f.wallet) AS wallet,
+                 COALESCE(f.amount, 0) AS amount_from,
+                 COALESCE(t.amount, 0) AS amount_to
+          FROM (${HOLDERS_SQL('$2')}) f
+          FULL JOIN (${HOLDERS_SQL('$3')}) t ON t.wallet = f.wallet
+        ) d
+        `,
+        params
+      );
+    }
+
+    const counts = await client.query<{ cohort: HolderCohort; wallets: number }>(
+      `
+      SELECT cohort, COUNT(*)::int AS wallets
+      FROM holder_diff_wallets
+      WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3
+      GROUP BY cohort
+      `,
+      [mint, fromEpoch, toEpoch]
+    );
+    const totals = built
+      ? built
+      : (
+          await client.query<{ holders_from: number; holders_to: number }>(
+            `SELECT holders_from, holders_to FROM holder_diffs WHERE mint = $1 AND from_epoch = $2 AND to_epoch = $3`,
+            [mint, fromEpoch, toEpoch]
+          )
+        ).rows[0];
+
+    await client.query('COMMIT');
+
+    const cohorts = Object.fromEntries(HOLDER_COHORTS.map((c) => [c, 0])) as Record<HolderCohort, number>;
+    for (const row of counts.rows) cohorts[row.cohort] = row.wallets;
+
+    return {
+      mint,
+      fromEpoch,
+      toEpoch,
+      skipped: !built,
+      holdersFrom: totals.holders_from,
+      holdersTo: totals.holders_to,
+      cohorts,
+    };
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
+
+/**
+ * Build the diff of every consecutive snapshot pair of `mint` not diffed yet
+ */
+export async function buildPendingHolderDiffs(mint: string): Promise<HolderDiffResult[]> {
+  const results: HolderDiffResult[] = [];
+  for (const key of await pendingHolderDiffs(mint)) {
+    results.push(await buildHolderDiff(key));
+  }
+  return results;
+}
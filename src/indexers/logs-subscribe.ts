@@ -0,0 +1,149 @@
+// src/indexers/logs-subscribe.ts
+// WebSocket logsSubscribe fallback for environments without Geyser access
+
+import { Connection, PublicKey } from '@solana/web3.js';
+import { FailoverConnection } from '../utils/rpc';
+import { ProgramTransaction, programTransactionFromRpc } from './claim-events';
+
+// How often to compare the subscription against getSignaturesForAddress
+const GAP_CHECK_INTERVAL_MS = 30_000;
+// Signatures remembered to drop duplicate notifications
+const SEEN_LIMIT = 10_000;
+// A notification can arrive before the transaction is served at the same commitment
+const FETCH_ATTEMPTS = 5;
+const FETCH_RETRY_DELAY_MS = 1000;
+
+/**
+ * Stream successful transactions touching `programId` via `logsSubscribe`
+ *
+ * Log notifications carry no account keys, so each transaction is fetched
+ * over RPC before it is handed on. Every `GAP_CHECK_INTERVAL_MS` the program's
+ * signature history is compared with what the subscription delivered; any
+ * transaction it missed is processed (oldest first) and the subscription is
+ * re-created. `onSubscribed` runs once after subscribing, before the first
+ * notification is handled, to replay what landed before the stream started.
+ * `onTransaction` calls are serialized. The subscription goes to
+ * `wsEndpoint`, by default derived from the primary RPC URL.
+ */
+export async function streamProgramTransactionsViaLogs(
+  rpc: FailoverConnection,
+  programId: string,
+  commitment: 'confirmed' | 'finalized',
+  onTransaction: (tx: ProgramTransaction) => Promise<void>,
+  onSubscribed?: () => Promise<void>,
+  wsEndpoint?: string
+): Promise<never> {
+  const program = new PublicKey(programId);
+  const rpcConfig = rpc.getConfig();
+  const ws = new Connection(rpcConfig.primaryUrl, { commitment, wsEndpoint });
+
+  const seen = new Set<string>();
+  const seenOrder: string[] = [];
+  // Newest signature up to which history has been checked
+  let checkedUntil: string | null = null;
+  let queue = Promise.resolve();
+
+  const markSeen = (signature: string) => {
+    seen.add(signature);
+    seenOrder.push(signature);
+    if (seenOrder.length > SEEN_LIMIT) {
+      seen.delete(seenOrder.shift()!);
+    }
+  };
+
+  // False if the transaction could not be fetched yet
+  const processSignature = async (signature: string): Promise<boolean> => {
+    if (seen.has(signature)) return true;
+
+    for (let attempt = 1; ; attempt++) {
+      const tx = await rpc.execute(
+        (connection) =>
+          connection.getTransaction(signature, { commitment, maxSupportedTransactionVersion: 0 }),
+        'getTransaction'
+      );
+      if (tx) {
+        if (!tx.meta?.err) {
+          await onTransaction(programTransactionFromRpc(signature, tx));
+        }
+        markSeen(signature);
+        return true;
+      }
+      if (attempt === FETCH_ATTEMPTS) {
+        // Left for the next gap check
+        console.warn(`logsSubscribe: ${signature} not available at ${commitment} yet`);
+        return false;
+      }
+      await sleep(FETCH_RETRY_DELAY_MS);
+    }
+  };
+
+  const enqueue = (job: () => Promise<void>) => {
+    queue = queue.then(job).catch((error) => {
+      console.error('logsSubscribe: processing failed:', error.message);
+    });
+  };
+
+  const subscribe = () =>
+    ws.onLogs(
+      program,
+      (logs) => {
+        if (logs.err) return;
+        enqueue(async () => {
+          await processSignature(logs.signature);
+        });
+      },
+      commitment
+    );
+
+  let subscriptionId = subscribe();
+  console.log(`logsSubscribe: subscribed to ${programId} (${commitment})`);
+  if (onSubscribed) enqueue(onSubscribed);
+
+  const checkGap = async () => {
+    // Newest first; page back until the last checked signature
+    const history: { signature: string; slot: number; err: unknown }[] = [];
+    let before: string | undefined;
+    for (;;) {
+      const page = await rpc.execute(
+        (connection) =>
+          connection.getSignaturesForAddress(
+            program,
+            { before, until: checkedUntil ?? undefined, limit: checkedUntil ? 1000 : 1 },
+            commitment
+          ),
+        'getSignaturesForAddress'
+      );
+      history.push(...page);
+      if (!checkedUntil || page.length < 1000) break;
+      before = page[page.length - 1].signature;
+    }
+    if (history.length === 0) return;
+
+    if (checkedUntil) {
+      const missed = history.filter((s) => !s.err && !seen.has(s.signature)).reverse();
+      if (missed.length > 0) {
+        console.warn(
+          `logsSubscribe: gap detected, ${missed.length} transaction(s) missed ` +
+            `(slots ${missed[0].slot}-${missed[missed.length - 1].slot}); resubscribing`
+        );
+        await ws.removeOnLogsListener(subscriptionId).catch(() => undefined);
+        subscriptionId = subscribe();
+
+        for (const s of missed) {
+          // Retry the whole range on the next check
+          if (!(await processSignature(s.signature))) return;
+        }
+      }
+    }
+    checkedUntil = history[0].signature;
+  };
+
+  enqueue(checkGap);
+  setInterval(() => enqueue(checkGap), GAP_CHECK_INTERVAL_MS);
+
+  return new Promise<never>(() => undefined);
+}
+
+function sleep(ms: number): Promise<void> {
+  return new Promise((resolve) => setTimeout(resolve, ms));
+}
@@ -0,0 +1,208 @@
+// src/indexers/lp-positions.ts
+// Concentrated-liquidity LP positions holding an LST (Orca Whirlpools, Raydium CLMM)
+
+import { PublicKey } from '@solana/web3.js';
+import { discriminatorFilter, getProgramAccountsAt, readU128LE, rpc } from './program-accounts';
+import type { LiquidityProtocol, PositionSnapshot, ProtocolPosition } from './protocol-positions';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const ORCA_WHIRLPOOL_PROGRAM_ID = new PublicKey('whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc');
+const RAYDIUM_CLMM_PROGRAM_ID = new PublicKey('CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK');
+
+// Both programs store sqrt prices as Q64.64
+const Q64 = 1n << 64n;
+
+// getMultipleAccounts limit
+const ACCOUNTS_BATCH_SIZE = 100;
+
+/**
+ * Byte offsets of the fields read from a CLMM's pool and position accounts
+ */
+type ClmmLayout = {
+  protocol: LiquidityProtocol;
+  programId: PublicKey;
+  poolAccount: string;
+  positionAccount: string;
+  pool: { sqrtPrice: number; mintA: number; vaultA: number; mintB: number; vaultB: number };
+  position: { pool: number; nftMint: number; liquidity: number; tickLower: number; tickUpper: number };
+};
+
+const ORCA_LAYOUT: ClmmLayout = {
+  protocol: 'orca',
+  programId: ORCA_WHIRLPOOL_PROGRAM_ID,
+  poolAccount: 'Whirlpool',
+  positionAccount: 'Position',
+  pool: { sqrtPrice: 65, mintA: 101, vaultA: 133, mintB: 181, vaultB: 213 },
+  position: { pool: 8, nftMint: 40, liquidity: 72, tickLower: 88, tickUpper: 92 },
+};
+
+const RAYDIUM_LAYOUT: ClmmLayout = {
+  protocol: 'raydium',
+  programId: RAYDIUM_CLMM_PROGRAM_ID,
+  poolAccount: 'PoolState',
+  positionAccount: 'PersonalPositionState',
+  pool: { sqrtPrice: 253, mintA: 73, vaultA: 137, mintB: 105, vaultB: 169 },
+  position: { pool: 41, nftMint: 9, liquidity: 81, tickLower: 73, tickUpper: 77 },
+};
+
+// ---------------------------
+// Pool math
+// ---------------------------
+
+/**
+ * sqrt(1.0001^tick) as Q64.64
+ *
+ * Computed in floating point rather than with the programs' bit-exact
+ * tables; the relative error (~1e-16) is far below a raw unit of any
+ * position worth attributing.
+ */
+export function sqrtPriceAtTick(tick: number): bigint {
+  return BigInt(Math.round(Math.pow(1.0001, tick / 2) * 2 ** 64));
+}
+
+/**
+ * Token A and B amounts of `liquidity` between sqrt prices `lower` and `upper`
+ * at the pool's current sqrt price (uncollected fees excluded)
+ */
+export function positionAmounts(
+  liquidity: bigint,
+  current: bigint,
+  lower: bigint,
+  upper: bigint
+): [bigint, bigint] {
+  if (current <= lower) {
+    return [(liquidity * (upper - lower) * Q64) / (lower * upper), 0n];
+  }
+  if (current >= upper) {
+    return [0n, (liquidity * (upper - lower)) / Q64];
+  }
+  return [(liquidity * (upper - current) * Q64) / (current * upper), (liquidity * (current - lower)) / Q64];
+}
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * Wallet holding each position NFT, by NFT mint
+ *
+ * NFTs that were burned or can't be found are left out.
+ */
+async function resolveNftOwners(nftMints: string[]): Promise<Map<string, string>> {
+  const holders: [string, PublicKey][] = [];
+  for (const nftMint of nftMints) {
+    const largest = await rpc.execute(
+      (connection) => connection.getTokenLargestAccounts(new PublicKey(nftMint), 'finalized'),
+      'getTokenLargestAccounts'
+    );
+    const holder = largest.value.find((account) => account.amount === '1');
+    if (holder) holders.push([nftMint, holder.address]);
+  }
+
+  const owners = new Map<string, string>();
+  for (let i = 0; i < holders.length; i += ACCOUNTS_BATCH_SIZE) {
+    const batch = holders.slice(i, i + ACCOUNTS_BATCH_SIZE);
+    const accounts = await rpc.execute(
+      (connection) => connection.getMultipleAccountsInfo(batch.map(([, address]) => address), 'finalized'),
+      'getPositionNftAccounts'
+    );
+    batch.forEach(([nftMint], j) => {
+      const account = accounts[j];
+      // Token account layout: mint (0..32), owner (32..64)
+      if (account) owners.set(nftMint, new PublicKey(account.data.subarray(32, 64)).toBase58());
+    });
+  }
+  return owners;
+}
+
+/**
+ * Every position in every pool of `mint`, with its LST amount at the pool's
+ * current price, credited to the holder of the position NFT
+ */
+async function fetchClmmPositions(
+  layout: ClmmLayout,
+  mint: PublicKey,
+  minContextSlot: number
+): Promise<PositionSnapshot> {
+  let slot = minContextSlot;
+
+  const pools: { address: PublicKey; data: Buffer; side: 'a' | 'b' }[] = [];
+  for (const side of ['a', 'b'] as const) {
+    const offset = side === 'a' ? layout.pool.mintA : layout.pool.mintB;
+    const result = await getProgramAccountsAt(
+      layout.programId,
+      [discriminatorFilter(layout.poolAccount), { memcmp: { offset, bytes: mint.toBase58() } }],
+      slot,
+      `${layout.protocol}Pools`
+    );
+    slot = Math.max(slot, result.slot);
+    pools.push(...result.accounts.map(({ pubkey, account }) => ({ address: pubkey, data: account.data, side })));
+  }
+
+  const vaults: string[] = [];
+  const found: { positionAccount: string; nftMint: string; amount: bigint }[] = [];
+
+  for (const pool of pools) {
+    const vaultOffset = pool.side === 'a' ? layout.pool.vaultA : layout.pool.vaultB;
+    vaults.push(new PublicKey(pool.data.subarray(vaultOffset, vaultOffset + 32)).toBase58());
+    const sqrtPrice = readU128LE(pool.data, layout.pool.sqrtPrice);
+
+    const result = await getProgramAccountsAt(
+      layout.programId,
+      [
+        discriminatorFilter(layout.positionAccount),
+        { memcmp: { offset: layout.position.pool, bytes: pool.address.toBase58() } },
+      ],
+      slot,
+      `${layout.protocol}Positions`
+    );
+    slot = Math.max(slot, result.slot);
+
+    for (const { pubkey, account } of result.accounts) {
+      const data = account.data;
+      const liquidity = readU128LE(data, layout.position.liquidity);
+      if (liquidity === 0n) continue;
+
+      const [amountA, amountB] = positionAmounts(
+        liquidity,
+        sqrtPrice,
+        sqrtPriceAtTick(data.readInt32LE(layout.position.tickLower)),
+        sqrtPriceAtTick(data.readInt32LE(layout.position.tickUpper))
+      );
+      const amount = pool.side === 'a' ? amountA : amountB;
+      if (amount === 0n) continue;
+
+      found.push({
+        positionAccount: pubkey.toBase58(),
+        nftMint: new PublicKey(data.subarray(layout.position.nftMint, layout.position.nftMint + 32)).toBase58(),
+        amount,
+      });
+    }
+  }
+
+  const owners = await resolveNftOwners(found.map((p) => p.nftMint));
+  const positions: ProtocolPosition[] = [];
+  for (const p of found) {
+    const owner = owners.get(p.nftMint);
+    if (owner) positions.push({ positionAccount: p.positionAccount, owner, amount: p.amount });
+  }
+  if (positions.length < found.length) {
+    console.warn(`${layout.protocol}: ${found.length - positions.length} position(s) without an NFT holder skipped`);
+  }
+
+  return { protocol: layout.protocol, slot, vaults, positions };
+}
+
+// ---------------------------
+// Adapters
+// ---------------------------
+
+export function fetchOrcaPositions(mint: PublicKey, minContextSlot: number): Promise<PositionSnapshot> {
+  return fetchClmmPositions(ORCA_LAYOUT, mint, minContextSlot);
+}
+
+export function fetchRaydiumPositions(mint: PublicKey, minContextSlot: number): Promise<PositionSnapshot> {
+  return fetchClmmPositions(RAYDIUM_LAYOUT, mint, minContextSlot);
+}
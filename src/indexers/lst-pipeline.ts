@@ -0,0 +1,51 @@
+// src/indexers/lst-pipeline.ts
+// Per-LST epoch pipeline: holder snapshot, protocol positions, native stake, exchange rate, then validator rewards
+
+import { PublicKey } from '@solana/web3.js';
+import { LstConfig } from '../config/lsts';
+import { EpochSnapshotResult, runEpochSnapshot } from './epoch-snapshot';
+import { ExchangeRateResult, getStakePoolAdapter, recordEpochExchangeRate } from './exchange-rate';
+import { NativeStakeResult, recordNativeStake } from './native-stake';
+import { PositionsResult, recordProtocolPositions } from './protocol-positions';
+import { recordValidatorRewards, ValidatorRewardsResult } from './validator-rewards';
+
+export type LstEpochResult = {
+  symbol: string;
+  snapshot: EpochSnapshotResult;
+  // Lending and LP positions, in the order the protocols are configured
+  positions: PositionsResult[];
+  // null when the LST has no native stake validators configured
+  nativeStake: NativeStakeResult | null;
+  // null when the LST has no stake pool configured
+  rate: ExchangeRateResult | null;
+  // Previous epoch's inflation rewards per validator; null without a stake pool
+  validatorRewards: ValidatorRewardsResult | null;
+};
+
+/**
+ * Run one LST's epoch pipeline
+ *
+ * Every step is a no-op once done for the epoch, so this can run on any
+ * schedule and concurrently with other LSTs. Protocol positions are read
+ * right after the holder snapshot so both reflect (nearly) the same slot;
+ * native stake follows them.
+ */
+export async function runLstEpoch(lst: LstConfig): Promise<LstEpochResult> {
+  const snapshot = await runEpochSnapshot(new PublicKey(lst.mint));
+
+  const positions: PositionsResult[] = [];
+  for (const protocol of [...lst.lending, ...lst.liquidity]) {
+    positions.push(await recordProtocolPositions(protocol, lst.mint, snapshot.epoch));
+  }
+
+  const nativeStake =
+    lst.nativeStake.length > 0 ? await recordNativeStake(lst.mint, snapshot.epoch, lst.nativeStake) : null;
+
+  if (!lst.stakePool) {
+    return { symbol: lst.symbol, snapshot, positions, nativeStake, rate: null, validatorRewards: null };
+  }
+  const adapter = getStakePoolAdapter(lst.stakePool.kind, lst.stakePool.address);
+  const rate = await recordEpochExchangeRate(adapter, lst.mint);
+  const validatorRewards = await recordValidatorRewards(adapter, lst.mint);
+  return { symbol: lst.symbol, snapshot, positions, nativeStake, rate, validatorRewards };
+}
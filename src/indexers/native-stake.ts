@@ -0,0 +1,223 @@
+// src/indexers/native-stake.ts
+// Native stake delegated to a configured validator set, credited to each stake account's withdraw authority
+
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { getProgramAccountsAt } from './program-accounts';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const STAKE_PROGRAM_ID = new PublicKey('Stake11111111111111111111111111111111111111');
+
+// StakeStateV2 (200 bytes): tag (4); Stake = 2. Meta: rent_exempt_reserve (8),
+// staker (32), withdrawer (32), lockup (48). Delegation: voter_pubkey (32),
+// stake (8), activation_epoch (8), deactivation_epoch (8)
+const STAKE_ACCOUNT_SIZE = 200;
+const STAKE_STATE_STAKE = 2;
+const WITHDRAWER_OFFSET = 44;
+const VOTER_OFFSET = 124;
+const STAKE_OFFSET = 156;
+const ACTIVATION_EPOCH_OFFSET = 164;
+const DEACTIVATION_EPOCH_OFFSET = 172;
+
+// activation_epoch of bootstrap stake, deactivation_epoch of stake never deactivated
+const EPOCH_NONE = 2n ** 64n - 1n;
+
+// Rows per INSERT when persisting stake accounts
+const INSERT_BATCH_SIZE = 5000;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type NativeStakeAccount = {
+  stakeAccount: string;
+  voteAccount: string;
+  // Withdraw authority: the wallet the stake is credited to
+  withdrawer: string;
+  lamports: bigint;
+};
+
+export type NativeStakeSnapshot = {
+  slot: number;
+  accounts: NativeStakeAccount[];
+};
+
+export type NativeStakeResult = {
+  skipped: boolean;
+  slot: number | null;
+  stakeAccounts: number;
+  lamports: bigint;
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * Whether a delegation is active at the start of `epoch`
+ *
+ * Stake delegated in epoch N is active from N + 1; stake deactivated in N is
+ * still active during N. The cluster-wide warmup/cooldown limit is ignored:
+ * it only holds stake back when a large share of the cluster moves at once.
+ */
+export function isActiveAt(activationEpoch: bigint, deactivationEpoch: bigint, epoch: number): boolean {
+  const activated = activationEpoch === EPOCH_NONE || activationEpoch < BigInt(epoch);
+  return activated && deactivationEpoch >= BigInt(epoch);
+}
+
+/**
+ * Stake accounts delegated to `validators` and active at the start of `epoch`
+ *
+ * Read no earlier than `minContextSlot`. Delegations made after the boundary
+ * are not active yet and deactivations after it still count, so a later read
+ * only misses accounts that were withdrawn and closed in between.
+ */
+export async function fetchNativeStake(
+  validators: string[],
+  epoch: number,
+  minContextSlot: number
+): Promise<NativeStakeSnapshot> {
+  let slot = minContextSlot;
+  const accounts: NativeStakeAccount[] = [];
+
+  for (const voteAccount of validators) {
+    const response = await getProgramAccountsAt(
+      STAKE_PROGRAM_ID,
+      [{ dataSize: STAKE_ACCOUNT_SIZE }, { memcmp: { offset: VOTER_OFFSET, bytes: voteAccount } }],
+      slot,
+      'nativeStakeAccounts'
+    );
+    slot = Math.max(slot, response.slot);
+
+    for (const { pubkey, account } of response.accounts) {
+      const data = account.data;
+      if (data.readUInt32LE(0) !== STAKE_STATE_STAKE) continue;
+
+      const activationEpoch = data.readBigUInt64LE(ACTIVATION_EPOCH_OFFSET);
+      const deactivationEpoch = data.readBigUInt64LE(DEACTIVATION_EPOCH_OFFSET);
+      if (!isActiveAt(activationEpoch, deactivationEpoch, epoch)) continue;
+
+      const lamports = data.readBigUInt64LE(STAKE_OFFSET);
+      if (lamports === 0n) continue;
+
+      accounts.push({
+        stakeAccount: pubkey.toBase58(),
+        voteAccount,
+        withdrawer: new PublicKey(data.subarray(WITHDRAWER_OFFSET, WITHDRAWER_OFFSET + 32)).toBase58(),
+        lamports,
+      });
+    }
+  }
+
+  return { slot, accounts };
+}
+
+/**
+ * Persist the native stake of an epoch snapshot atomically
+ *
+ * Returns false if it was already recorded.
+ */
+async function persistNativeStake(
+  epoch: number,
+  mint: string,
+  validators: string[],
+  snapshot: NativeStakeSnapshot
+): Promise<boolean> {
+  const withdrawers = [...new Set(snapshot.accounts.map((a) => a.withdrawer))];
+  const total = snapshot.accounts.reduce((sum, a) => sum + a.lamports, 0n);
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    const inserted = await client.query(
+      `
+      INSERT INTO epoch_native_stake_snapshots (epoch, mint, slot, validators, stake_accounts, total_lamports)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      ON CONFLICT (epoch, mint) DO NOTHING
+      `,
+      [epoch, mint, snapshot.slot, validators, snapshot.accounts.length, total.toString()]
+    );
+    if (inserted.rowCount === 0) {
+      await client.query('ROLLBACK');
+      return false;
+    }
+
+    await client.query(
+      `
+      INSERT INTO wallets (wallet, first_seen)
+      SELECT wallet, NOW() FROM unnest($1::text[]) AS wallet
+      ON CONFLICT (wallet) DO NOTHING
+      `,
+      [withdrawers]
+    );
+
+    for (let i = 0; i < snapshot.accounts.length; i += INSERT_BATCH_SIZE) {
+      const batch = snapshot.accounts.slice(i, i + INSERT_BATCH_SIZE);
+      await client.query(
+        `
+        INSERT INTO epoch_snapshot_native_stake (epoch, mint, stake_account, vote_account, wallet, lamports)
+        SELECT $1, $2, stake_account, vote_account, wallet, lamports
+        FROM unnest($3::text[], $4::text[], $5::text[], $6::numeric[]) AS s(stake_account, vote_account, wallet, lamports)
+        `,
+        [
+          epoch,
+          mint,
+          batch.map((a) => a.stakeAccount),
+          batch.map((a) => a.voteAccount),
+          batch.map((a) => a.withdrawer),
+          batch.map((a) => a.lamports.toString()),
+        ]
+      );
+    }
+
+    await client.query('COMMIT');
+    return true;
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Record the native stake delegated to `validators` for an existing epoch
+ * snapshot of `mint`, once
+ *
+ * Stake accounts are read no earlier than the snapshot's pinned slot; the
+ * slot actually read is stored with them.
+ */
+export async function recordNativeStake(mint: string, epoch: number, validators: string[]): Promise<NativeStakeResult> {
+  const snapshot = await pool.query<{ slot: string }>(
+    `SELECT slot FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, mint]
+  );
+  if (snapshot.rows.length === 0) {
+    throw new Error(`No epoch ${epoch} snapshot of ${mint} to add native stake to`);
+  }
+
+  const existing = await pool.query<{ slot: string }>(
+    `SELECT slot FROM epoch_native_stake_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, mint]
+  );
+  if (existing.rows.length > 0) {
+    return { skipped: true, slot: Number(existing.rows[0].slot), stakeAccounts: 0, lamports: 0n };
+  }
+
+  const result = await fetchNativeStake(validators, epoch, Number(snapshot.rows[0].slot));
+  const lamports = result.accounts.reduce((sum, a) => sum + a.lamports, 0n);
+  console.log(
+    `${mint}: native stake ${result.accounts.length} stake accounts, ${lamports} lamports ` +
+      `(${validators.length} validators, slot ${result.slot})`
+  );
+
+  const written = await persistNativeStake(epoch, mint, validators, result);
+  return { skipped: !written, slot: result.slot, stakeAccounts: result.accounts.length, lamports };
+}
@@ -0,0 +1,57 @@
+// src/indexers/program-accounts.ts
+// Shared helpers for reading other programs' accounts at a snapshot slot
+
+import crypto from 'crypto';
+import { GetProgramAccountsFilter, PublicKey } from '@solana/web3.js';
+import bs58 from 'bs58';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+
+const rpcConfig = getRpcConfigFromEnv();
+export const rpc = new FailoverConnection(rpcConfig);
+
+/**
+ * Anchor account discriminator: first 8 bytes of sha256("account:<Name>")
+ */
+export function accountDiscriminator(name: string): Buffer {
+  return crypto.createHash('sha256').update(`account:${name}`).digest().subarray(0, 8);
+}
+
+/**
+ * getProgramAccounts filter matching Anchor accounts of type `name`
+ */
+export function discriminatorFilter(name: string): GetProgramAccountsFilter {
+  return { memcmp: { offset: 0, bytes: bs58.encode(accountDiscriminator(name)) } };
+}
+
+export function readU128LE(data: Buffer, offset: number): bigint {
+  return data.readBigUInt64LE(offset) + (data.readBigUInt64LE(offset + 8) << 64n);
+}
+
+export function readI128LE(data: Buffer, offset: number): bigint {
+  return data.readBigUInt64LE(offset) + (data.readBigInt64LE(offset + 8) << 64n);
+}
+
+/**
+ * Finalized getProgramAccounts answered no earlier than `minContextSlot`,
+ * with the slot it was answered at (over `connection`, default SOLANA_RPC_URL)
+ */
+export async function getProgramAccountsAt(
+  programId: PublicKey,
+  filters: GetProgramAccountsFilter[],
+  minContextSlot: number,
+  label: string,
+  connection: FailoverConnection = rpc
+) {
+  const response = await connection.execute(
+    (connection) =>
+      connection.getProgramAccounts(programId, {
+        commitment: 'finalized',
+        encoding: 'base64',
+        withContext: true,
+        minContextSlot,
+        filters,
+      }),
+    label
+  );
+  return { slot: response.context.slot, accounts: response.value };
+}
@@ -0,0 +1,337 @@
+// src/indexers/protocol-positions.ts
+// Attributes LST held in DeFi protocols back to the position owners:
+// lending markets (Kamino, MarginFi) here, AMM LP positions in lp-positions.ts
+
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { discriminatorFilter, getProgramAccountsAt, readI128LE, readU128LE } from './program-accounts';
+import { fetchOrcaPositions, fetchRaydiumPositions } from './lp-positions';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const KAMINO_LEND_PROGRAM_ID = new PublicKey('KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD');
+const MARGINFI_PROGRAM_ID = new PublicKey('MFv2hWf31Z9kbCa1snEPYctwafyhdvnV7FNnsHdLNPa');
+
+// Kamino Reserve: liquidity block starts at 128, collateral block at 2560
+const RESERVE_LIQUIDITY_MINT = 128;
+const RESERVE_SUPPLY_VAULT = 160;
+const RESERVE_AVAILABLE_AMOUNT = 224;
+const RESERVE_BORROWED_AMOUNT_SF = 232;
+const RESERVE_PROTOCOL_FEES_SF = 344;
+const RESERVE_REFERRER_FEES_SF = 360;
+const RESERVE_PENDING_REFERRER_FEES_SF = 376;
+const RESERVE_COLLATERAL_SUPPLY = 2592;
+// Kamino scaled fractions carry 60 fractional bits
+const KAMINO_SF_SHIFT = 60n;
+
+// Kamino Obligation: owner at 64, then 8 deposit slots of 136 bytes
+// (deposit_reserve, deposited_amount, ...)
+const OBLIGATION_OWNER = 64;
+const OBLIGATION_DEPOSITS = 96;
+const OBLIGATION_DEPOSIT_SIZE = 136;
+const OBLIGATION_DEPOSIT_SLOTS = 8;
+
+// MarginFi Bank: mint at 8, asset_share_value (I80F48) at 80, liquidity_vault at 112
+const BANK_MINT = 8;
+const BANK_ASSET_SHARE_VALUE = 80;
+const BANK_LIQUIDITY_VAULT = 112;
+
+// MarginFi account: authority at 40, then 16 balances of 104 bytes
+// (active, bank_pk, bank_asset_tag, padding, asset_shares (I80F48), ...)
+const MARGINFI_AUTHORITY = 40;
+const MARGINFI_BALANCES = 72;
+const MARGINFI_BALANCE_SIZE = 104;
+const MARGINFI_BALANCE_SLOTS = 16;
+const BALANCE_BANK = 1;
+const BALANCE_ASSET_SHARES = 40;
+// I80F48 fixed point
+const I80F48_SHIFT = 48n;
+
+// Rows per INSERT when persisting positions
+const INSERT_BATCH_SIZE = 5000;
+
+// ---------------------------
+// Types
+// ---------------------------
+export type LendingProtocol = 'kamino' | 'marginfi';
+export type LiquidityProtocol = 'orca' | 'raydium';
+export type PositionProtocol = LendingProtocol | LiquidityProtocol;
+
+export type ProtocolPosition = {
+  // Obligation (Kamino), margin account (MarginFi) or LP position
+  positionAccount: string;
+  owner: string;
+  // Underlying LST in raw units
+  amount: bigint;
+};
+
+export type PositionSnapshot = {
+  protocol: PositionProtocol;
+  slot: number;
+  // Token accounts holding the protocol's LST liquidity (attributed away from their owner)
+  vaults: string[];
+  positions: ProtocolPosition[];
+};
+
+export type PositionsResult = {
+  protocol: PositionProtocol;
+  skipped: boolean;
+  slot: number | null;
+  positions: number;
+  amount: bigint;
+};
+
+// ---------------------------
+// Adapters
+// ---------------------------
+
+/**
+ * Kamino Lend: deposits are reserve collateral (cTokens) held in obligations
+ *
+ * Collateral converts to liquidity at the reserve's rate:
+ * total_liquidity / collateral_supply, where total_liquidity =
+ * available + borrowed - protocol and referrer fees.
+ */
+async function fetchKaminoPositions(mint: PublicKey, minContextSlot: number): Promise<PositionSnapshot> {
+  const reserves = await getProgramAccountsAt(
+    KAMINO_LEND_PROGRAM_ID,
+    [
+      discriminatorFilter('Reserve'),
+      { memcmp: { offset: RESERVE_LIQUIDITY_MINT, bytes: mint.toBase58() } },
+    ],
+    minContextSlot,
+    'kaminoReserves'
+  );
+
+  let slot = reserves.slot;
+  const vaults: string[] = [];
+  const positions = new Map<string, ProtocolPosition>();
+
+  for (const { pubkey: reserve, account } of reserves.accounts) {
+    const data = account.data;
+    const totalLiquiditySf =
+      (data.readBigUInt64LE(RESERVE_AVAILABLE_AMOUNT) << KAMINO_SF_SHIFT) +
+      readU128LE(data, RESERVE_BORROWED_AMOUNT_SF) -
+      readU128LE(data, RESERVE_PROTOCOL_FEES_SF) -
+      readU128LE(data, RESERVE_REFERRER_FEES_SF) -
+      readU128LE(data, RESERVE_PENDING_REFERRER_FEES_SF);
+    const collateralSupply = data.readBigUInt64LE(RESERVE_COLLATERAL_SUPPLY);
+    vaults.push(new PublicKey(data.subarray(RESERVE_SUPPLY_VAULT, RESERVE_SUPPLY_VAULT + 32)).toBase58());
+    if (collateralSupply === 0n) continue;
+
+    // An obligation lists the reserve in any one of its deposit slots
+    for (let i = 0; i < OBLIGATION_DEPOSIT_SLOTS; i++) {
+      const depositOffset = OBLIGATION_DEPOSITS + i * OBLIGATION_DEPOSIT_SIZE;
+      const obligations = await getProgramAccountsAt(
+        KAMINO_LEND_PROGRAM_ID,
+        [discriminatorFilter('Obligation'), { memcmp: { offset: depositOffset, bytes: reserve.toBase58() } }],
+        slot,
+        'kaminoObligations'
+      );
+      slot = Math.max(slot, obligations.slot);
+
+      for (const { pubkey, account: obligation } of obligations.accounts) {
+        const collateral = obligation.data.readBigUInt64LE(depositOffset + 32);
+        const amount = (collateral * totalLiquiditySf) / (collateralSupply << KAMINO_SF_SHIFT);
+        if (amount === 0n) continue;
+
+        const key = pubkey.toBase58();
+        const existing = positions.get(key);
+        if (existing) {
+          existing.amount += amount;
+        } else {
+          positions.set(key, {
+            positionAccount: key,
+            owner: new PublicKey(obligation.data.subarray(OBLIGATION_OWNER, OBLIGATION_OWNER + 32)).toBase58(),
+            amount,
+          });
+        }
+      }
+    }
+  }
+
+  return { protocol: 'kamino', slot, vaults, positions: [...positions.values()] };
+}
+
+/**
+ * MarginFi: deposits are bank asset shares held in margin account balances
+ *
+ * Shares convert at the bank's asset_share_value (both I80F48).
+ */
+async function fetchMarginfiPositions(mint: PublicKey, minContextSlot: number): Promise<PositionSnapshot> {
+  const banks = await getProgramAccountsAt(
+    MARGINFI_PROGRAM_ID,
+    [discriminatorFilter('Bank'), { memcmp: { offset: BANK_MINT, bytes: mint.toBase58() } }],
+    minContextSlot,
+    'marginfiBanks'
+  );
+
+  let slot = banks.slot;
+  const vaults: string[] = [];
+  const positions = new Map<string, ProtocolPosition>();
+
+  for (const { pubkey: bank, account } of banks.accounts) {
+    const shareValue = readI128LE(account.data, BANK_ASSET_SHARE_VALUE);
+    vaults.push(new PublicKey(account.data.subarray(BANK_LIQUIDITY_VAULT, BANK_LIQUIDITY_VAULT + 32)).toBase58());
+
+    for (let i = 0; i < MARGINFI_BALANCE_SLOTS; i++) {
+      const balanceOffset = MARGINFI_BALANCES + i * MARGINFI_BALANCE_SIZE;
+      const accounts = await getProgramAccountsAt(
+        MARGINFI_PROGRAM_ID,
+        [
+          discriminatorFilter('MarginfiAccount'),
+          { memcmp: { offset: balanceOffset + BALANCE_BANK, bytes: bank.toBase58() } },
+        ],
+        slot,
+        'marginfiAccounts'
+      );
+      slot = Math.max(slot, accounts.slot);
+
+      for (const { pubkey, account: marginfiAccount } of accounts.accounts) {
+        const data = marginfiAccount.data;
+        if (data.readUInt8(balanceOffset) !== 1) continue;
+
+        const shares = readI128LE(data, balanceOffset + BALANCE_ASSET_SHARES);
+        const amount = (shares * shareValue) >> (2n * I80F48_SHIFT);
+        if (amount <= 0n) continue;
+
+        const key = pubkey.toBase58();
+        const existing = positions.get(key);
+        if (existing) {
+          existing.amount += amount;
+        } else {
+          positions.set(key, {
+            positionAccount: key,
+            owner: new PublicKey(data.subarray(MARGINFI_AUTHORITY, MARGINFI_AUTHORITY + 32)).toBase58(),
+            amount,
+          });
+        }
+      }
+    }
+  }
+
+  return { protocol: 'marginfi', slot, vaults, positions: [...positions.values()] };
+}
+
+const ADAPTERS: Record<PositionProtocol, (mint: PublicKey, minContextSlot: number) => Promise<PositionSnapshot>> = {
+  kamino: fetchKaminoPositions,
+  marginfi: fetchMarginfiPositions,
+  orca: fetchOrcaPositions,
+  raydium: fetchRaydiumPositions,
+};
+
+/**
+ * Persist one protocol's positions for an epoch snapshot atomically
+ *
+ * Returns false if they were already recorded.
+ */
+async function persistPositionSnapshot(epoch: number, mint: string, snapshot: PositionSnapshot): Promise<boolean> {
+  const owners = [...new Set(snapshot.positions.map((p) => p.owner))];
+  const total = snapshot.positions.reduce((sum, p) => sum + p.amount, 0n);
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    const inserted = await client.query(
+      `
+      INSERT INTO epoch_position_snapshots (epoch, mint, protocol, slot, positions, total_amount)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      ON CONFLICT (epoch, mint, protocol) DO NOTHING
+      `,
+      [epoch, mint, snapshot.protocol, snapshot.slot, snapshot.positions.length, total.toString()]
+    );
+    if (inserted.rowCount === 0) {
+      await client.query('ROLLBACK');
+      return false;
+    }
+
+    await client.query(
+      `
+      INSERT INTO epoch_position_vaults (epoch, mint, protocol, token_account)
+      SELECT $1, $2, $3, token_account FROM unnest($4::text[]) AS token_account
+      ON CONFLICT DO NOTHING
+      `,
+      [epoch, mint, snapshot.protocol, snapshot.vaults]
+    );
+
+    await client.query(
+      `
+      INSERT INTO wallets (wallet, first_seen)
+      SELECT owner, NOW() FROM unnest($1::text[]) AS owner
+      ON CONFLICT (wallet) DO NOTHING
+      `,
+      [owners]
+    );
+
+    for (let i = 0; i < snapshot.positions.length; i += INSERT_BATCH_SIZE) {
+      const batch = snapshot.positions.slice(i, i + INSERT_BATCH_SIZE);
+      await client.query(
+        `
+        INSERT INTO epoch_snapshot_positions (epoch, mint, protocol, position_account, owner, amount)
+        SELECT $1, $2, $3, position_account, owner, amount
+        FROM unnest($4::text[], $5::text[], $6::numeric[]) AS p(position_account, owner, amount)
+        `,
+        [
+          epoch,
+          mint,
+          snapshot.protocol,
+          batch.map((p) => p.positionAccount),
+          batch.map((p) => p.owner),
+          batch.map((p) => p.amount.toString()),
+        ]
+      );
+    }
+
+    await client.query('COMMIT');
+    return true;
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Record `protocol` positions in `mint` for an existing epoch snapshot, once
+ *
+ * Accounts are read no earlier than the snapshot's pinned slot; the slot
+ * actually read is stored with the positions.
+ */
+export async function recordProtocolPositions(
+  protocol: PositionProtocol,
+  mint: string,
+  epoch: number
+): Promise<PositionsResult> {
+  const snapshot = await pool.query<{ slot: string }>(
+    `SELECT slot FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, mint]
+  );
+  if (snapshot.rows.length === 0) {
+    throw new Error(`No epoch ${epoch} snapshot of ${mint} to attribute ${protocol} positions to`);
+  }
+
+  const existing = await pool.query(
+    `SELECT slot FROM epoch_position_snapshots WHERE epoch = $1 AND mint = $2 AND protocol = $3`,
+    [epoch, mint, protocol]
+  );
+  if (existing.rows.length > 0) {
+    return { protocol, skipped: true, slot: Number(existing.rows[0].slot), positions: 0, amount: 0n };
+  }
+
+  const result = await ADAPTERS[protocol](new PublicKey(mint), Number(snapshot.rows[0].slot));
+  const amount = result.positions.reduce((sum, p) => sum + p.amount, 0n);
+  console.log(
+    `${mint}: ${protocol} ${result.positions.length} positions, ${amount} attributed ` +
+      `(${result.vaults.length} vaults, slot ${result.slot})`
+  );
+
+  const written = await persistPositionSnapshot(epoch, mint, result);
+  return { protocol, skipped: !written, slot: result.slot, positions: result.positions.length, amount };
+}
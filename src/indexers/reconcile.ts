@@ -0,0 +1,233 @@
+// src/indexers/reconcile.ts
+// Reconciles on-chain Distribution accounts against the indexed claim events
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { DeploymentConfig } from '../config/deployments';
+import { FailoverConnection } from '../utils/rpc';
+import { discriminatorFilter, getProgramAccountsAt } from './program-accounts';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Distribution layout (zero-copy, programs/merkle-distributor/src/lib.rs)
+const DISTRIBUTION_VAULT = 8 + 32 * 3;
+const DISTRIBUTION_TOTAL_AMOUNT = 8 + 32 * 6;
+const DISTRIBUTION_CLAIMED_AMOUNT = DISTRIBUTION_TOTAL_AMOUNT + 8;
+const DISTRIBUTION_NUM_CLAIMED = DISTRIBUTION_TOTAL_AMOUNT + 24;
+const DISTRIBUTION_CLAWED_BACK_AMOUNT = DISTRIBUTION_TOTAL_AMOUNT + 32 + 32 * 3 + 16;
+
+// SPL token account amount
+const TOKEN_ACCOUNT_AMOUNT = 64;
+
+// getMultipleAccounts limit
+const ACCOUNTS_PER_REQUEST = 100;
+
+// A divergence is only alerted on once it outlives ingestion lag
+const DEFAULT_ALERT_AFTER_MINUTES = 10;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+/**
+ * What is compared:
+ * - claimed_amount: on-chain claimed_amount vs. claim, claim_referral and escrow events
+ * - num_claimed:    on-chain num_claimed vs. the same events plus renounce
+ * - vault_balance:  vault balance below total - claimed - clawed back (on-chain fields)
+ */
+export const DIVERGENCE_FIELDS = ['claimed_amount', 'num_claimed', 'vault_balance'] as const;
+export type DivergenceField = (typeof DIVERGENCE_FIELDS)[number];
+
+export type Divergence = {
+  distribution: string;
+  field: DivergenceField;
+  onChain: bigint;
+  /** Indexed value, or for vault_balance the balance the distribution's fields require */
+  expected: bigint;
+};
+
+export type ReconcileResult = {
+  deployment: string;
+  slot: number;
+  distributions: number;
+  divergences: Divergence[];
+  /** Open divergences alerted on for the first time by this run */
+  alerted: Divergence[];
+  resolved: number;
+};
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Compare every Distribution account of the deployment's distributor program
+ * (and its vault) with the finalized claim events indexed for it
+ *
+ * Events are counted up to the slot the accounts were read at. Divergences
+ * are kept in merkle_reconciliation_divergences until a run finds the
+ * values matching again; one still open after RECONCILE_ALERT_AFTER_MINUTES
+ * is alerted on once, so ingestion that is merely behind does not page.
+ */
+export async function runReconciliation(deployment: DeploymentConfig): Promise<ReconcileResult> {
+  const programId = new PublicKey(deployment.programId);
+  const rpc = new FailoverConnection(deployment.rpc);
+  const alertAfterMinutes = Number(process.env.RECONCILE_ALERT_AFTER_MINUTES ?? DEFAULT_ALERT_AFTER_MINUTES);
+
+  const finalizedSlot = await rpc.execute(
+    (connection) => connection.getSlot('finalized'),
+    'getSlot'
+  );
+  const { slot, accounts } = await getProgramAccountsAt(
+    programId,
+    [discriminatorFilter('Distribution')],
+    finalizedSlot,
+    'distributions',
+    rpc
+  );
+
+  const distributions = accounts.map(({ pubkey, account }) => ({
+    address: pubkey.toBase58(),
+    vault: new PublicKey(account.data.subarray(DISTRIBUTION_VAULT, DISTRIBUTION_VAULT + 32)),
+    totalAmount: account.data.readBigUInt64LE(DISTRIBUTION_TOTAL_AMOUNT),
+    claimedAmount: account.data.readBigUInt64LE(DISTRIBUTION_CLAIMED_AMOUNT),
+    numClaimed: account.data.readBigUInt64LE(DISTRIBUTION_NUM_CLAIMED),
+    clawedBackAmount: account.data.readBigUInt64LE(DISTRIBUTION_CLAWED_BACK_AMOUNT),
+  }));
+
+  // Vault balances, read no earlier than the distributions
+  const vaultBalances = new Map<string, bigint>();
+  for (let i = 0; i < distributions.length; i += ACCOUNTS_PER_REQUEST) {
+    const chunk = distributions.slice(i, i + ACCOUNTS_PER_REQUEST);
+    const { value } = await rpc.execute(
+      (connection) =>
+        connection.getMultipleAccountsInfoAndContext(
+          chunk.map((d) => d.vault),
+          { commitment: 'finalized', minContextSlot: slot }
+        ),
+      'getVaults'
+    );
+    value.forEach((account, j) => {
+      vaultBalances.set(chunk[j].address, account ? account.data.readBigUInt64LE(TOKEN_ACCOUNT_AMOUNT) : 0n);
+    });
+  }
+
+  // Indexed totals up to the same slot
+  const { rows } = await pool.query<{ distribution: string; claimed_amount: string; num_claimed: string }>(
+    `
+    SELECT
+      distribution,
+      COALESCE(SUM(amount) FILTER (WHERE kind IN ('claim', 'claim_referral', 'escrow')), 0)::text AS claimed_amount,
+      COUNT(*) FILTER (WHERE kind IN ('claim', 'claim_referral', 'escrow', 'renounce'))::text AS num_claimed
+    FROM merkle_claim_events
+    WHERE deployment = $1 AND slot <= $2
+    GROUP BY distribution
+    `,
+    [deployment.name, slot]
+  );
+  const indexed = new Map(rows.map((r) => [r.distribution, r]));
+
+  const divergences: Divergence[] = [];
+  for (const d of distributions) {
+    const row = indexed.get(d.address);
+    const indexedClaimed = BigInt(row?.claimed_amount ?? 0);
+    const indexedCount = BigInt(row?.num_claimed ?? 0);
+
+    if (d.claimedAmount !== indexedClaimed) {
+      divergences.push({ distribution: d.address, field: 'claimed_amount', onChain: d.claimedAmount, expected: indexedClaimed });
+    }
+    if (d.numClaimed !== indexedCount) {
+      divergences.push({ distribution: d.address, field: 'num_claimed', onChain: d.numClaimed, expected: indexedCount });
+    }
+
+    // Renounced tokens stay in the vault until clawed back, so only a
+    // shortfall is a divergence
+    const required = d.totalAmount - d.claimedAmount - d.clawedBackAmount;
+    const balance = vaultBalances.get(d.address) ?? 0n;
+    if (balance < required) {
+      divergences.push({ distribution: d.address, field: 'vault_balance', onChain: balance, expected: required });
+    }
+  }
+
+  const { alerted, resolved } = await recordDivergences(deployment.name, divergences, slot, alertAfterMinutes);
+
+  return { deployment: deployment.name, slot, distributions: distributions.length, divergences, alerted, resolved };
+}
+
+// ---------------------------
+// Persistence
+// ---------------------------
+
+async function recordDivergences(
+  deployment: string,
+  divergences: Divergence[],
+  slot: number,
+  alertAfterMinutes: number
+): Promise<{ alerted: Divergence[]; resolved: number }> {
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    for (const d of divergences) {
+      const updated = await client.query(
+        `
+        UPDATE merkle_reconciliation_divergences
+        SET on_chain = $3, expected = $4, slot = $5, last_seen_at = NOW()
+        WHERE deployment = $6 AND distribution = $1 AND field = $2 AND resolved_at IS NULL
+        `,
+        [d.distribution, d.field, d.onChain.toString(), d.expected.toString(), slot, deployment]
+      );
+      if ((updated.rowCount ?? 0) === 0) {
+        await client.query(
+          `
+          INSERT INTO merkle_reconciliation_divergences (deployment, distribution, field, on_chain, expected, slot)
+          VALUES ($6, $1, $2, $3, $4, $5)
+          `,
+          [d.distribution, d.field, d.onChain.toString(), d.expected.toString(), slot, deployment]
+        );
+      }
+    }
+
+    // Open divergences this run did not see again are resolved
+    const seen = divergences.map((d) => `${d.distribution}:${d.field}`);
+    const resolved = await client.query(
+      `
+      UPDATE merkle_reconciliation_divergences
+      SET resolved_at = NOW()
+      WHERE deployment = $2
+        AND resolved_at IS NULL
+        AND NOT (distribution || ':' || field = ANY($1::text[]))
+      `,
+      [seen, deployment]
+    );
+
+    const due = await client.query<{ distribution: string; field: DivergenceField }>(
+      `
+      UPDATE merkle_reconciliation_divergences
+      SET alerted_at = NOW()
+      WHERE deployment = $2
+        AND resolved_at IS NULL
+        AND alerted_at IS NULL
+        AND first_seen_at <= NOW() - make_interval(mins => $1)
+      RETURNING distribution, field
+      `,
+      [alertAfterMinutes, deployment]
+    );
+
+    await client.query('COMMIT');
+
+    const dueKeys = new Set(due.rows.map((r) => `${r.distribution}:${r.field}`));
+    return {
+      alerted: divergences.filter((d) => dueKeys.has(`${d.distribution}:${d.field}`)),
+      resolved: resolved.rowCount ?? 0,
+    };
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+}
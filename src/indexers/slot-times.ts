@@ -0,0 +1,231 @@
+// src/indexers/slot-times.ts
+// Slot → block time mapping for indexed events, interpolating slots the cluster has no block time for
+
+import { pool } from '../db';
+import { blockTimeLookups } from '../utils/metrics';
+import { FailoverConnection } from '../utils/rpc';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Nominal slot duration, for extrapolating from a neighbor on one side only
+const SLOT_DURATION_MS = 400;
+
+// Slots searched on each side of a slot without a block time (~20 minutes)
+const NEIGHBOR_WINDOW_SLOTS = 3_000;
+
+// Produced blocks asked for their time on each side before giving up on it
+const NEIGHBOR_PROBES = 4;
+
+// Block times held in process per mapper
+const CACHE_CAPACITY = 10_000;
+
+// getBlockTime errors meaning the node has no time for the slot: block not
+// available, slot skipped, block missing from long-term storage
+const UNAVAILABLE_CODES: ReadonlySet<number> = new Set([-32004, -32007, -32009]);
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export interface SlotTime {
+  /** Unix seconds (UTC) */
+  blockTime: number;
+  /** Interpolated from neighboring slots rather than reported by the cluster */
+  estimated: boolean;
+}
+
+type Neighbor = { slot: number; blockTime: number };
+
+// ---------------------------
+// Mapper
+// ---------------------------
+
+/**
+ * Block times of one deployment's slots
+ *
+ * Looked up in process, then in `slot_block_times`, then asked of the
+ * cluster. A slot the cluster has no time for (pruned from the node, or not
+ * yet available at processed) is interpolated between the nearest slots on
+ * either side that have one, or extrapolated from one side at the nominal
+ * slot duration. Such times are flagged `estimated`, and replaced in the
+ * cache once a transaction reports the real one.
+ */
+export class SlotTimeMapper {
+  private readonly cache = new Map<number, SlotTime>();
+
+  /** `rpc` must serve the deployment's cluster */
+  constructor(
+    readonly deployment: string,
+    private readonly rpc: FailoverConnection
+  ) {}
+
+  /**
+   * Block time of `slot`, or null if neither it nor any slot near it has one
+   *
+   * `reported` is the block time the transaction came with, if any; it is
+   * taken as is and cached for interpolating its neighbors.
+   */
+  async resolve(slot: number, reported: number | null = null): Promise<SlotTime | null> {
+    if (reported !== null) {
+      const time = { blockTime: reported, estimated: false };
+      if (this.cache.get(slot)?.estimated !== false) await this.store(slot, time);
+      this.count('reported');
+      return time;
+    }
+
+    const cached = this.cache.get(slot) ?? (await this.load(slot));
+    if (cached) {
+      this.count('cached');
+      return cached;
+    }
+
+    const blockTime = await this.fetch(slot);
+    if (blockTime !== null) {
+      const time = { blockTime, estimated: false };
+      await this.store(slot, time);
+      this.count('rpc');
+      return time;
+    }
+
+    const estimate = await this.interpolate(slot);
+    if (estimate === null) {
+      this.count('missing');
+      return null;
+    }
+    const time = { blockTime: estimate, estimated: true };
+    await this.store(slot, time);
+    this.count('interpolated');
+    return time;
+  }
+
+  private async interpolate(slot: number): Promise<number | null> {
+    const [before, after] = await Promise.all([this.neighbor(slot, -1), this.neighbor(slot, 1)]);
+    if (before && after) {
+      const fraction = (slot - before.slot) / (after.slot - before.slot);
+      return Math.round(before.blockTime + (after.blockTime - before.blockTime) * fraction);
+    }
+    if (before) return before.blockTime + Math.round(((slot - before.slot) * SLOT_DURATION_MS) / 1000);
+    if (after) return after.blockTime - Math.round(((after.slot - slot) * SLOT_DURATION_MS) / 1000);
+    return null;
+  }
+
+  /**
+   * Nearest slot within the window on one side whose block time the
+   * cluster reported: from the cache table, else from produced blocks
+   */
+  private async neighbor(slot: number, direction: -1 | 1): Promise<Neighbor | null> {
+    const { rows } = await pool.query<{ slot: string; block_time: number }>(
+      `
+      SELECT slot, EXTRACT(EPOCH FROM block_time)::float8 AS block_time
+      FROM slot_block_times
+      WHERE deployment = $1 AND NOT estimated AND slot BETWEEN $2 AND $3 AND slot <> $4
+      ORDER BY ABS(slot - $4)
+      LIMIT 1
+      `,
+      direction < 0
+        ? [this.deployment, slot - NEIGHBOR_WINDOW_SLOTS, slot, slot]
+        : [this.deployment, slot, slot + NEIGHBOR_WINDOW_SLOTS, slot]
+    );
+    if (rows.length > 0) return { slot: Number(rows[0].slot), blockTime: rows[0].block_time };
+
+    const start = direction < 0 ? Math.max(0, slot - NEIGHBOR_WINDOW_SLOTS) : slot + 1;
+    const end = direction < 0 ? slot - 1 : slot + NEIGHBOR_WINDOW_SLOTS;
+    if (end < start) return null;
+    const blocks = await this.rpc.execute((connection) => connection.getBlocks(start, end, 'confirmed'), 'getBlocks');
+    const nearest = direction < 0 ? blocks.reverse() : blocks;
+
+    for (const candidate of nearest.slice(0, NEIGHBOR_PROBES)) {
+      const blockTime = await this.fetch(candidate);
+      if (blockTime === null) continue;
+      await this.store(candidate, { blockTime, estimated: false });
+      return { slot: candidate, blockTime };
+    }
+    return null;
+  }
+
+  private async fetch(slot: number): Promise<number | null> {
+    return this.rpc.execute(
+      (connection) =>
+        connection.getBlockTime(slot).catch((error: any) => {
+          if (UNAVAILABLE_CODES.has(error?.code)) return null;
+          throw error;
+        }),
+      'getBlockTime'
+    );
+  }
+
+  private async load(slot: number): Promise<SlotTime | null> {
+    const { rows } = await pool.query<{ block_time: number; estimated: boolean }>(
+      `
+      SELECT EXTRACT(EPOCH FROM block_time)::float8 AS block_time, estimated
+      FROM slot_block_times
+      WHERE deployment = $1 AND slot = $2
+      `,
+      [this.deployment, slot]
+    );
+    if (rows.length === 0) return null;
+    const time = { blockTime: rows[0].block_time, estimated: rows[0].estimated };
+    this.remember(slot, time);
+    return time;
+  }
+
+  /** Cache `time`; an estimate never replaces a reported time */
+  private async store(slot: number, time: SlotTime): Promise<void> {
+    this.remember(slot, time);
+    await pool.query(
+      `
+      INSERT INTO slot_block_times (deployment, slot, block_time, estimated)
+      VALUES ($1, $2, to_timestamp($3), $4)
+      ON CONFLICT (deployment, slot) DO UPDATE
+      SET block_time = EXCLUDED.block_time, estimated = EXCLUDED.estimated
+      WHERE slot_block_times.estimated AND NOT EXCLUDED.estimated
+      `,
+      [this.deployment, slot, time.blockTime, time.estimated]
+    );
+  }
+
+  private remember(slot: number, time: SlotTime): void {
+    this.cache.delete(slot);
+    this.cache.set(slot, time);
+    if (this.cache.size > CACHE_CAPACITY) {
+      this.cache.delete(this.cache.keys().next().value!);
+    }
+  }
+
+  private count(result: string): void {
+    blockTimeLookups.inc({ deployment: this.deployment, result });
+  }
+}
+
+// ---------------------------
+// Restamping
+// ---------------------------
+
+/**
+ * Stamp events of the mapper's deployment stored without a block time;
+ * returns how many were stamped
+ */
+export async function stampMissingBlockTimes(mapper: SlotTimeMapper): Promise<number> {
+  const { rows } = await pool.query<{ slot: string }>(
+    `SELECT DISTINCT slot FROM merkle_claim_events WHERE deployment = $1 AND block_time IS NULL ORDER BY slot`,
+    [mapper.deployment]
+  );
+
+  let stamped = 0;
+  for (const row of rows) {
+    const time = await mapper.resolve(Number(row.slot));
+    if (!time) continue;
+    const result = await pool.query(
+      `
+      UPDATE merkle_claim_events
+      SET block_time = to_timestamp($3), block_time_estimated = $4
+      WHERE deployment = $1 AND slot = $2 AND block_time IS NULL
+      `,
+      [mapper.deployment, row.slot, time.blockTime, time.estimated]
+    );
+    stamped += result.rowCount ?? 0;
+  }
+  return stamped;
+}
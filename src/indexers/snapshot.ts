@@ -1,12 +1,10 @@
 // src/indexers/snapshot.ts
 import 'dotenv/config';
-import {
-  PublicKey,
-  ParsedAccountData,
-} from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } from '@solana/spl-token';
+import { PublicKey } from '@solana/web3.js';
+import { getAssociatedTokenAddressSync } from '@solana/spl-token';
 import { pool } from '../db';
 import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+import { fetchBalancesAtSlot, getTokenProgram } from './epoch-snapshot';
 
 // ---------------------------
 // Config / constants
@@ -70,35 +68,15 @@ type HolderWithEligibility = TokenHolder & {
 // ---------------------------
 // Helpers
 // ---------------------------
+/**
+ * Every non-empty token account of the primary token, through the same scan
+ * as epoch snapshots: SPL Token or Token-2022 (whichever owns the mint),
+ * sharded and with compressed balances when configured
+ */
 async function fetchTokenHolders(): Promise<TokenHolder[]> {
-  const accounts = await rpc.execute(
-    (connection) => connection.getParsedProgramAccounts(
-      TOKEN_PROGRAM_ID,
-      {
-        filters: [
-          { dataSize: 165 },
-          {
-            memcmp: {
-              offset: 0,
-              bytes: PRIMARY_TOKEN_MINT.toBase58(),
-            },
-          },
-        ],
-      }
-    ),
-    'fetchTokenHolders'
-  );
-
-  return accounts
-    .map(acc => {
-      const parsed = acc.account.data as ParsedAccountData;
-      const info = parsed.parsed.info;
-      const amount = BigInt(info.tokenAmount.amount);
-      return amount > 0n
-        ? { wallet: info.owner, primaryTokenAmount: amount }
-        : null;
-    })
-    .filter(Boolean) as TokenHolder[];
+  const tokenProgram = await getTokenProgram(PRIMARY_TOKEN_MINT);
+  const { balances } = await fetchBalancesAtSlot(PRIMARY_TOKEN_MINT, tokenProgram, 0);
+  return balances.map((b) => ({ wallet: b.owner, primaryTokenAmount: b.amount }));
 }
 
 /**
@@ -120,13 +98,17 @@ async function getEligibilityBalancesBatched(
     return balances;
   }
 
+  // ATAs are derived per token program, so a Token-2022 mint needs its own
+  const tokenProgram = await getTokenProgram(eligibilityMint);
+
   // Derive all ATA addresses at once (no RPC calls needed!)
   const atas = wallets.map(wallet => ({
     wallet,
     ata: getAssociatedTokenAddressSync(
       eligibilityMint,
       new PublicKey(wallet),
-      true // allowOwnerOffCurve
+      true, // allowOwnerOffCurve
+      tokenProgram
     ),
   }));
 
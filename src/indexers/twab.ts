@@ -0,0 +1,109 @@
+// src/indexers/twab.ts
+// Intra-epoch balance samples of an LST mint, for time-weighted average balances
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+import { checkSnapshotSupply, fetchBalancesAtSlot, getTokenProgram } from './epoch-snapshot';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const rpcConfig = getRpcConfigFromEnv();
+const rpc = new FailoverConnection(rpcConfig);
+
+// Rows per INSERT when persisting sampled balances
+const INSERT_BATCH_SIZE = 5000;
+
+// A sample failing the supply check is re-alerted this often while samples keep failing
+const SUPPLY_ALERT_COOLDOWN_MINUTES = 60;
+
+// ---------------------------
+// Types
+// ---------------------------
+export type TwabSampleResult = {
+  epoch: number;
+  slot: number;
+  holders: number;
+  totalAmount: bigint;
+};
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Sample every holder's balance of `mint` at the current finalized slot
+ *
+ * Each sample stands for the balances from its slot until the next sample
+ * (or the next epoch's boundary snapshot); the epoch_twab_holders view
+ * averages them over the epoch. Balances are summed per owner wallet.
+ */
+export async function runTwabSample(mint: PublicKey): Promise<TwabSampleResult> {
+  const info = await rpc.execute(
+    (connection) => connection.getEpochInfo('finalized'),
+    'getEpochInfo'
+  );
+  const epoch = info.epoch;
+
+  const tokenProgram = await getTokenProgram(mint);
+  const { slot, balances } = await fetchBalancesAtSlot(mint, tokenProgram, info.absoluteSlot);
+  await checkSnapshotSupply(mint, slot, balances, `${mint.toBase58()}:twab`, SUPPLY_ALERT_COOLDOWN_MINUTES);
+
+  const byOwner = new Map<string, bigint>();
+  for (const balance of balances) {
+    byOwner.set(balance.owner, (byOwner.get(balance.owner) ?? 0n) + balance.amount);
+  }
+  const totalAmount = balances.reduce((sum, b) => sum + b.amount, 0n);
+  const holders = [...byOwner];
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    await client.query(
+      `
+      INSERT INTO epoch_balance_samples (epoch, mint, slot, holders, total_amount)
+      VALUES ($1, $2, $3, $4, $5)
+      `,
+      [epoch, mint.toBase58(), slot, holders.length, totalAmount.toString()]
+    );
+
+    await client.query(
+      `
+      INSERT INTO wallets (wallet, first_seen)
+      SELECT owner, NOW() FROM unnest($1::text[]) AS owner
+      ON CONFLICT (wallet) DO NOTHING
+      `,
+      [holders.map(([owner]) => owner)]
+    );
+
+    for (let i = 0; i < holders.length; i += INSERT_BATCH_SIZE) {
+      const batch = holders.slice(i, i + INSERT_BATCH_SIZE);
+      await client.query(
+        `
+        INSERT INTO epoch_balance_sample_holders (epoch, mint, slot, wallet, amount)
+        SELECT $1, $2, $3, wallet, amount
+        FROM unnest($4::text[], $5::numeric[]) AS h(wallet, amount)
+        `,
+        [
+          epoch,
+          mint.toBase58(),
+          slot,
+          batch.map(([owner]) => owner),
+          batch.map(([, amount]) => amount.toString()),
+        ]
+      );
+    }
+
+    await client.query('COMMIT');
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+
+  return { epoch, slot, holders: holders.length, totalAmount };
+}
@@ -0,0 +1,255 @@
+// src/indexers/validator-rewards.ts
+// Per-validator attribution of the inflation rewards earned by an LST's stake pool,
+// and their total as the epoch's default reward budget
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+import { PoolStakeAccount, StakePoolAdapter } from './exchange-rate';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+const rpcConfig = getRpcConfigFromEnv();
+const rpc = new FailoverConnection(rpcConfig);
+
+const STAKE_PROGRAM_ID = new PublicKey('Stake11111111111111111111111111111111111111');
+
+// StakeStateV2: tag (4); Stake = 2. Meta (120) is followed by the delegation:
+// voter_pubkey (32), stake (8), activation_epoch (8), deactivation_epoch (8)
+const STAKE_STATE_STAKE = 2;
+const VOTER_OFFSET = 124;
+const DELEGATED_STAKE_OFFSET = 156;
+
+// Accounts per getMultipleAccounts / getInflationReward request
+const ACCOUNT_BATCH_SIZE = 100;
+
+// Partitioned epoch rewards are paid over the first blocks of the next epoch;
+// attribution waits until the epoch is this far along
+const REWARDS_SETTLED_SLOT_INDEX = 10_000;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type DelegatedStakeAccount = PoolStakeAccount & {
+  voteAccount: string;
+  delegatedLamports: bigint;
+};
+
+export type ValidatorReward = {
+  voteAccount: string;
+  stakeAccounts: number;
+  delegatedLamports: bigint;
+  // Paid to the pool's stake accounts, after commission
+  rewardLamports: bigint;
+  // Commission percentage of the vote account for the epoch (null: no reward paid)
+  commission: number | null;
+  // Commission the validator kept on the pool's stake: reward * c / (100 - c)
+  commissionLamports: bigint;
+};
+
+export type ValidatorRewardsResult = {
+  epoch: number;
+  skipped: boolean;
+  reason?: string;
+  validators?: number;
+  rewardLamports?: bigint;
+};
+
+// ---------------------------
+// Helpers
+// ---------------------------
+
+/**
+ * Stake accounts the pool currently delegates from, with the validator each
+ * is delegated to
+ *
+ * Listed accounts that are closed, not delegated (e.g. merged or still
+ * initializing) are left out.
+ */
+export async function fetchPoolStakeAccounts(adapter: StakePoolAdapter): Promise<DelegatedStakeAccount[]> {
+  const poolAccount = await rpc.execute(
+    (connection) => connection.getAccountInfo(adapter.address, 'finalized'),
+    'getStakePool'
+  );
+  if (!poolAccount) {
+    throw new Error(`Stake pool account not found: ${adapter.address.toBase58()}`);
+  }
+  const listAddress = adapter.stakeListAddress(poolAccount.data);
+  const list = await rpc.execute(
+    (connection) => connection.getAccountInfo(listAddress, 'finalized'),
+    'getStakeList'
+  );
+  if (!list) {
+    throw new Error(`Stake list not found: ${listAddress.toBase58()}`);
+  }
+  const listed = adapter.stakeAccounts(poolAccount.data, list.data);
+
+  const delegated: DelegatedStakeAccount[] = [];
+  for (let i = 0; i < listed.length; i += ACCOUNT_BATCH_SIZE) {
+    const batch = listed.slice(i, i + ACCOUNT_BATCH_SIZE);
+    const infos = await rpc.execute(
+      (connection) =>
+        connection.getMultipleAccountsInfo(
+          batch.map((a) => new PublicKey(a.address)),
+          'finalized'
+        ),
+      'getPoolStakeAccounts'
+    );
+    batch.forEach((account, j) => {
+      const info = infos[j];
+      if (!info || !info.owner.equals(STAKE_PROGRAM_ID) || info.data.readUInt32LE(0) !== STAKE_STATE_STAKE) {
+        return;
+      }
+      delegated.push({
+        ...account,
+        voteAccount: new PublicKey(info.data.subarray(VOTER_OFFSET, VOTER_OFFSET + 32)).toBase58(),
+        delegatedLamports: info.data.readBigUInt64LE(DELEGATED_STAKE_OFFSET),
+      });
+    });
+  }
+  return delegated;
+}
+
+/**
+ * Inflation rewards of `accounts` for `epoch`, grouped by validator
+ */
+export async function attributeInflationRewards(
+  accounts: DelegatedStakeAccount[],
+  epoch: number
+): Promise<ValidatorReward[]> {
+  const byValidator = new Map<string, ValidatorReward>();
+
+  for (let i = 0; i < accounts.length; i += ACCOUNT_BATCH_SIZE) {
+    const batch = accounts.slice(i, i + ACCOUNT_BATCH_SIZE);
+    const rewards = await rpc.execute(
+      (connection) =>
+        connection.getInflationReward(
+          batch.map((a) => new PublicKey(a.address)),
+          epoch,
+          'finalized'
+        ),
+      'getInflationReward'
+    );
+
+    batch.forEach((account, j) => {
+      const reward = rewards[j];
+      const entry = byValidator.get(account.voteAccount) ?? {
+        voteAccount: account.voteAccount,
+        stakeAccounts: 0,
+        delegatedLamports: 0n,
+        rewardLamports: 0n,
+        commission: null,
+        commissionLamports: 0n,
+      };
+      entry.stakeAccounts++;
+      entry.delegatedLamports += account.delegatedLamports;
+      if (reward) {
+        entry.rewardLamports += BigInt(reward.amount);
+        entry.commission = reward.commission ?? entry.commission;
+      }
+      byValidator.set(account.voteAccount, entry);
+    });
+  }
+
+  for (const entry of byValidator.values()) {
+    const c = entry.commission;
+    if (c !== null && c < 100) {
+      entry.commissionLamports = (entry.rewardLamports * BigInt(c)) / BigInt(100 - c);
+    }
+  }
+  return [...byValidator.values()].sort((a, b) => (b.rewardLamports > a.rewardLamports ? 1 : -1));
+}
+
+// ---------------------------
+// Main callable unit
+// ---------------------------
+
+/**
+ * Record an epoch's inflation rewards per validator, and their total as the
+ * epoch's reward budget, once
+ *
+ * `epoch` defaults to the previous one. Rewards for epoch N are paid at the
+ * start of epoch N + 1, so N is only recorded once N + 1 is
+ * REWARDS_SETTLED_SLOT_INDEX slots in; until then it is skipped so the
+ * scheduler retries it. The pool's stake accounts are listed as of now:
+ * accounts removed since the epoch ended are missed, so older epochs are
+ * less complete.
+ */
+export async function recordValidatorRewards(
+  adapter: StakePoolAdapter,
+  mint: string,
+  epoch?: number
+): Promise<ValidatorRewardsResult> {
+  const info = await rpc.execute(
+    (connection) => connection.getEpochInfo('finalized'),
+    'getEpochInfo'
+  );
+  epoch ??= info.epoch - 1;
+  if (epoch >= info.epoch) {
+    throw new Error(`Epoch ${epoch} has not ended (current epoch ${info.epoch})`);
+  }
+
+  // The budget is written last, in the same transaction as the validators
+  const existing = await pool.query(
+    `SELECT 1 FROM epoch_reward_budgets WHERE epoch = $1 AND mint = $2`,
+    [epoch, mint]
+  );
+  if (existing.rows.length > 0) {
+    return { epoch, skipped: true, reason: 'already recorded' };
+  }
+  if (epoch === info.epoch - 1 && info.slotIndex < REWARDS_SETTLED_SLOT_INDEX) {
+    return { epoch, skipped: true, reason: `rewards settle after slot index ${REWARDS_SETTLED_SLOT_INDEX}` };
+  }
+
+  const accounts = await fetchPoolStakeAccounts(adapter);
+  const validators = await attributeInflationRewards(accounts, epoch);
+  const rewardLamports = validators.reduce((sum, v) => sum + v.rewardLamports, 0n);
+
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+    await client.query(
+      `
+      INSERT INTO epoch_validator_rewards
+        (epoch, mint, vote_account, stake_accounts, delegated_lamports, reward_lamports, commission, commission_lamports)
+      SELECT $1, $2, v.vote_account, v.stake_accounts, v.delegated, v.reward, v.commission, v.commission_lamports
+      FROM unnest($3::text[], $4::int[], $5::numeric[], $6::numeric[], $7::smallint[], $8::numeric[])
+        AS v(vote_account, stake_accounts, delegated, reward, commission, commission_lamports)
+      ON CONFLICT (epoch, mint, vote_account) DO NOTHING
+      `,
+      [
+        epoch,
+        mint,
+        validators.map((v) => v.voteAccount),
+        validators.map((v) => v.stakeAccounts),
+        validators.map((v) => v.delegatedLamports.toString()),
+        validators.map((v) => v.rewardLamports.toString()),
+        validators.map((v) => v.commission),
+        validators.map((v) => v.commissionLamports.toString()),
+      ]
+    );
+    await client.query(
+      `
+      INSERT INTO epoch_reward_budgets (epoch, mint, stake_accounts, validators, reward_lamports)
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (epoch, mint) DO NOTHING
+      `,
+      [epoch, mint, accounts.length, validators.length, rewardLamports.toString()]
+    );
+    await client.query('COMMIT');
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+
+  console.log(
+    `Epoch ${epoch} inflation rewards of ${mint}: ${rewardLamports} lamports from ${validators.length} validator(s), ` +
+      `${accounts.length} stake account(s)`
+  );
+  return { epoch, skipped: false, validators: validators.length, rewardLamports };
+}
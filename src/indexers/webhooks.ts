@@ -0,0 +1,235 @@
+// src/indexers/webhooks.ts
+// Signed webhook notifications for finalized claim and admin events
+
+import crypto from 'crypto';
+import { PoolClient } from 'pg';
+import { pool } from '../db';
+import { loadWebhookConfigs, WEBHOOK_EVENT_KINDS, WebhookConfig, WebhookEventType } from '../config/webhooks';
+import { webhookDeliveries } from '../utils/metrics';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Deliveries leased per dispatch
+const DISPATCH_BATCH_SIZE = 50;
+
+// A leased delivery is retried by another dispatcher after this long
+const LEASE_SECONDS = 60;
+
+const REQUEST_TIMEOUT_MS = 10_000;
+
+// Backoff after the n-th failed attempt: 30s, 1m, 2m, ... capped at 1h
+const RETRY_BASE_SECONDS = 30;
+const RETRY_MAX_SECONDS = 3600;
+const DEFAULT_MAX_ATTEMPTS = 8;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type DispatchResult = {
+  delivered: number;
+  retrying: number;
+  failed: number;
+};
+
+type DueDelivery = {
+  id: string;
+  webhook: string;
+  event_type: WebhookEventType;
+  signature: string;
+  event_index: number;
+  payload: unknown;
+  attempts: number;
+};
+
+// ---------------------------
+// Enqueue
+// ---------------------------
+
+/**
+ * Queue a delivery per subscribed webhook for the finalized events of
+ * `signatures` in `deployment`
+ *
+ * Runs in the transaction that finalizes the events, so every finalized
+ * event is queued exactly once per webhook (re-ingestion and backfills hit
+ * the unique key). The payload is fixed here and identical across retries.
+ */
+export async function enqueueWebhookDeliveries(
+  client: PoolClient,
+  deployment: string,
+  signatures: string[]
+): Promise<void> {
+  for (const webhook of loadWebhookConfigs()) {
+    if (webhook.deployments.length > 0 && !webhook.deployments.includes(deployment)) continue;
+
+    const kinds: string[] = [];
+    const types: string[] = [];
+    for (const type of webhook.events) {
+      for (const kind of WEBHOOK_EVENT_KINDS[type]) {
+        kinds.push(kind);
+        types.push(type);
+      }
+    }
+
+    await client.query(
+      `
+      INSERT INTO webhook_deliveries (webhook, event_type, deployment, signature, event_index, payload)
+      SELECT
+        $1, t.event_type, e.deployment, e.signature, e.event_index,
+        jsonb_build_object(
+          'type', t.event_type,
+          'deployment', e.deployment,
+          'kind', e.kind,
+          'signature', e.signature,
+          'eventIndex', e.event_index,
+          'slot', e.slot,
+          'blockTime', EXTRACT(EPOCH FROM e.block_time)::bigint,
+          'blockTimeEstimated', e.block_time_estimated,
+          'distribution', e.distribution,
+          'distributionId', d.distribution_id,
+          'rewardId', d.reward_id,
+          'mint', d.mint,
+          'recipient', e.recipient,
+          'amount', e.amount::text,
+          'leafIndex', e.leaf_index,
+          'referrer', e.referrer,
+          'bonus', e.bonus::text,
+          'destination', CASE WHEN e.kind = 'clawback' THEN e.destination END,
+          'operator', CASE WHEN e.kind = 'operator_change' THEN e.destination END
+        )
+      FROM merkle_claim_events e
+      JOIN unnest($2::text[], $3::text[]) AS t(kind, event_type) ON t.kind = e.kind
+      LEFT JOIN merkle_distributions d ON d.deployment = e.deployment AND d.on_chain_address = e.distribution
+      WHERE e.deployment = $6
+        AND e.signature = ANY($4)
+        AND (cardinality($5::text[]) = 0 OR e.distribution = ANY($5))
+      ON CONFLICT (webhook, deployment, signature, event_index) DO NOTHING
+      `,
+      [webhook.name, kinds, types, signatures, webhook.distributions, deployment]
+    );
+  }
+}
+
+// ---------------------------
+// Dispatch
+// ---------------------------
+
+/**
+ * HMAC-SHA256 of `${timestamp}.${body}`, sent as `X-Webhook-Signature: t=<timestamp>,v1=<hex>`
+ *
+ * Receivers recompute it over the raw body and should reject stale timestamps.
+ */
+export function signWebhookBody(secret: string, timestamp: number, body: string): string {
+  const digest = crypto.createHmac('sha256', secret).update(`${timestamp}.${body}`).digest('hex');
+  return `t=${timestamp},v1=${digest}`;
+}
+
+/**
+ * Deliver due webhook notifications
+ *
+ * Due rows are leased by pushing next_attempt_at forward, so several
+ * dispatchers never send the same delivery at once. A non-2xx response or a
+ * network error is retried with exponential backoff until
+ * WEBHOOK_MAX_ATTEMPTS, after which the delivery is marked failed.
+ */
+export async function deliverDueWebhooks(): Promise<DispatchResult> {
+  const result: DispatchResult = { delivered: 0, retrying: 0, failed: 0 };
+  const webhooks = new Map(loadWebhookConfigs().map((w) => [w.name, w]));
+  if (webhooks.size === 0) return result;
+
+  const maxAttempts = Number(process.env.WEBHOOK_MAX_ATTEMPTS ?? DEFAULT_MAX_ATTEMPTS);
+
+  const { rows } = await pool.query<DueDelivery>(
+    `
+    UPDATE webhook_deliveries
+    SET next_attempt_at = NOW() + make_interval(secs => $3)
+    WHERE id IN (
+      SELECT id FROM webhook_deliveries
+      WHERE status = 'pending'
+        AND next_attempt_at <= NOW()
+        AND webhook = ANY($1)
+      ORDER BY id
+      LIMIT $2
+      FOR UPDATE SKIP LOCKED
+    )
+    RETURNING id, webhook, event_type, signature, event_index, payload, attempts
+    `,
+    [[...webhooks.keys()], DISPATCH_BATCH_SIZE, LEASE_SECONDS]
+  );
+
+  await Promise.all(
+    rows.map(async (row) => {
+      const webhook = webhooks.get(row.webhook)!;
+      const { statusCode, error } = await send(webhook, row);
+      const attempts = row.attempts + 1;
+
+      if (error === null) {
+        await pool.query(
+          `
+          UPDATE webhook_deliveries
+          SET status = 'delivered', attempts = $2, last_status_code = $3, last_error = NULL, delivered_at = NOW()
+          WHERE id = $1
+          `,
+          [row.id, attempts, statusCode]
+        );
+        webhookDeliveries.inc({ webhook: row.webhook, result: 'delivered' });
+        result.delivered++;
+        return;
+      }
+
+      const failed = attempts >= maxAttempts;
+      const backoff = Math.min(RETRY_BASE_SECONDS * 2 ** (attempts - 1), RETRY_MAX_SECONDS);
+      await pool.query(
+        `
+        UPDATE webhook_deliveries
+        SET
+          status = $2,
+          attempts = $3,
+          last_status_code = $4,
+          last_error = $5,
+          next_attempt_at = NOW() + make_interval(secs => $6)
+        WHERE id = $1
+        `,
+        [row.id, failed ? 'failed' : 'pending', attempts, statusCode, error, backoff]
+      );
+      webhookDeliveries.inc({ webhook: row.webhook, result: failed ? 'failed' : 'retry' });
+      if (failed) {
+        console.error(`❌ Webhook ${row.webhook}: ${row.event_type} ${row.signature} failed after ${attempts} attempts: ${error}`);
+        result.failed++;
+      } else {
+        result.retrying++;
+      }
+    })
+  );
+
+  return result;
+}
+
+async function send(
+  webhook: WebhookConfig,
+  row: DueDelivery
+): Promise<{ statusCode: number | null; error: string | null }> {
+  const body = JSON.stringify(row.payload);
+  const timestamp = Math.floor(Date.now() / 1000);
+
+  try {
+    const res = await fetch(webhook.url, {
+      method: 'POST',
+      headers: {
+        'Content-Type': 'application/json',
+        // Same for every retry and webhook: receivers deduplicate on it
+        'X-Webhook-Id': `${row.signature}:${row.event_index}`,
+        'X-Webhook-Event': row.event_type,
+        'X-Webhook-Signature': signWebhookBody(webhook.secret, timestamp, body),
+      },
+      body,
+      signal: AbortSignal.timeout(REQUEST_TIMEOUT_MS),
+    });
+    if (res.ok) return { statusCode: res.status, error: null };
+    return { statusCode: res.status, error: `HTTP ${res.status} ${res.statusText}` };
+  } catch (e: any) {
+    return { statusCode: null, error: e.message };
+  }
+}
@@ -0,0 +1,436 @@
+// src/jobs/allocate-epoch-reward.ts
+// Computes a reward's payouts pro rata over an epoch snapshot
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getLstConfig } from '../config/lsts';
+import { getTokenBySymbol } from '../config/tokens';
+import {
+  allocateProRata,
+  BelowMinBalanceMode,
+  formatAccrual,
+  HolderWeight,
+  parseAccrual,
+  RoundingMode,
+} from '../allocation/engine';
+import { loadExclusions } from '../allocation/exclusions';
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      }
+    }
+  }
+  return args;
+}
+
+function printUsage() {
+  console.log('Usage: npx ts-node src/jobs/allocate-epoch-reward.ts --epoch <EPOCH> [--lst <SYMBOL>] [--reward <REWARD_ID>] [options]');
+  console.log('');
+  console.log('Splits the reward budget (plus carried-in dust) across the epoch snapshot holders of one LST.');
+  console.log('The reward defaults to <series>_EPOCH_<epoch> of the LST\'s distribution series.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --lst <SYMBOL>                        LST to allocate over (required when several are configured)');
+  console.log('  --weight sol|tokens                   Weight by underlying SOL (default) or token balance');
+  console.log('  --balance snapshot|twab               Boundary snapshot balance (default) or time-weighted average');
+  console.log('                                        over the epoch (needs TWAB samples and the next epoch\'s snapshot)');
+  console.log('  --budget config|<raw>                 Amount to split: the reward\'s configured total or a raw amount');
+  console.log('                                        (default: for SOL rewards, the epoch\'s inflation rewards recorded');
+  console.log('                                        by record-epoch-budget.ts if any, else the configured total)');
+  console.log('  --rounding floor|largest-remainder|accrue');
+  console.log('                                        floor (default) carries rounding dust to the next reward;');
+  console.log('                                        accrue keeps each wallet\'s remainder for its next allocation');
+  console.log('  --min-weight <raw>                    Ignore holders below this weight (lamports or raw tokens)');
+  console.log('  --min-balance <raw>                   Ignore holders with a smaller LST balance (default: the reward\'s)');
+  console.log('  --below-min redistribute|remainder    Split their share among the rest, or carry it out (default: the reward\'s)');
+  console.log('  --min-payout <raw>                    Drop payouts below this amount and re-split the budget');
+  console.log('  --exclude <file>                      Extra wallets to exclude, one per line with an optional reason');
+  console.log('                                        (EXCLUSIONS_FILE, IGNORE_WALLETS, the LST\'s exclude list and');
+  console.log('                                        its stake pool\'s operator wallets always apply)');
+}
+
+async function allocateEpochReward() {
+  const args = parseArgs();
+  const epoch = Number(args.epoch);
+  const weightBy = args.weight ?? 'sol';
+  const balanceBy = args.balance ?? 'snapshot';
+  const rounding = (args.rounding ?? 'floor') as RoundingMode;
+
+  if (!Number.isInteger(epoch)) {
+    printUsage();
+    process.exit(1);
+  }
+  if (weightBy !== 'sol' && weightBy !== 'tokens') {
+    throw new Error(`Invalid --weight: ${weightBy}`);
+  }
+  if (balanceBy !== 'snapshot' && balanceBy !== 'twab') {
+    throw new Error(`Invalid --balance: ${balanceBy}`);
+  }
+  if (rounding !== 'floor' && rounding !== 'largest-remainder' && rounding !== 'accrue') {
+    throw new Error(`Invalid --rounding: ${rounding}`);
+  }
+
+  const lst = getLstConfig(args.lst);
+  const snapshotMint = lst.mint;
+  const rewardId = args.reward ?? `${lst.series}_EPOCH_${epoch}`;
+
+  // -----------------------------
+  // Reward + idempotency
+  // -----------------------------
+  const reward = await pool.query<{
+    mint: string;
+    total_amount: string;
+    min_snapshot_balance: string | null;
+    below_min_balance: BelowMinBalanceMode;
+  }>(
+    `SELECT mint, total_amount, min_snapshot_balance, below_min_balance FROM reward_configs WHERE reward_id = $1`,
+    [rewardId]
+  );
+  if (reward.rows.length === 0) {
+    throw new Error(`Reward not found: ${rewardId} (create it with create-reward.ts first)`);
+  }
+  const { mint } = reward.rows[0];
+
+  // Minimum-balance eligibility comes with the reward; flags override it for this run
+  const minBalanceArg = args['min-balance'] ?? reward.rows[0].min_snapshot_balance;
+  const minBalance = minBalanceArg ? BigInt(minBalanceArg) : undefined;
+  const belowMinBalance = (args['below-min'] ?? reward.rows[0].below_min_balance) as BelowMinBalanceMode;
+  if (belowMinBalance !== 'redistribute' && belowMinBalance !== 'remainder') {
+    throw new Error(`Invalid --below-min: ${belowMinBalance}`);
+  }
+
+  const existingLedger = await pool.query(
+    `SELECT 1 FROM reward_dust_ledger WHERE reward_id = $1`,
+    [rewardId]
+  );
+  if ((existingLedger.rowCount ?? 0) > 0) {
+    console.log(`Skipping ${rewardId} (already processed)`);
+    return;
+  }
+
+  // Carry-in dust (same mint, most recent prior reward)
+  const priorDust = await pool.query<{ carry_out: string }>(
+    `
+    SELECT carry_out
+    FROM reward_dust_ledger
+    WHERE mint = $1
+    ORDER BY created_at DESC
+    LIMIT 1
+    `,
+    [mint]
+  );
+  const carryIn = priorDust.rows.length > 0 ? BigInt(priorDust.rows[0].carry_out) : 0n;
+
+  // SOL rewards default to the pool's inflation rewards for the epoch
+  let configuredTotal = BigInt(reward.rows[0].total_amount);
+  let budgetSource = 'configured';
+  if (args.budget && args.budget !== 'config') {
+    if (!/^\d+$/.test(args.budget)) throw new Error(`Invalid --budget: ${args.budget}`);
+    configuredTotal = BigInt(args.budget);
+    budgetSource = '--budget';
+  } else if (!args.budget && mint === getTokenBySymbol('SOL')!.mint) {
+    const recorded = await pool.query<{ reward_lamports: string }>(
+      `SELECT reward_lamports::text FROM epoch_reward_budgets WHERE epoch = $1 AND mint = $2`,
+      [epoch, snapshotMint]
+    );
+    if (recorded.rows.length > 0) {
+      configuredTotal = BigInt(recorded.rows[0].reward_lamports);
+      budgetSource = 'recorded inflation rewards';
+    }
+  }
+
+  // -----------------------------
+  // Holder weights from the epoch snapshot
+  // -----------------------------
+  const snapshot = await pool.query(
+    `SELECT slot FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, snapshotMint]
+  );
+  if (snapshot.rows.length === 0) {
+    throw new Error(`No epoch snapshot for epoch ${epoch} (mint ${snapshotMint})`);
+  }
+
+  if (balanceBy === 'twab') {
+    // The average is only final once the next epoch's boundary closes it
+    const next = await pool.query(
+      `SELECT 1 FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+      [epoch + 1, snapshotMint]
+    );
+    if (next.rows.length === 0) {
+      throw new Error(`Epoch ${epoch} TWAB is not final until the epoch ${epoch + 1} snapshot is taken`);
+    }
+    const samples = await pool.query(
+      `SELECT COUNT(*)::int AS count FROM epoch_balance_samples WHERE epoch = $1 AND mint = $2`,
+      [epoch, snapshotMint]
+    );
+    if (samples.rows[0].count === 0) {
+      console.log(`⚠️  No TWAB samples for epoch ${epoch}; the average equals the boundary snapshot`);
+    }
+  }
+
+  // TWAB balances come from token accounts only; lending and LP positions
+  // are credited in snapshot mode
+  const source = balanceBy === 'twab' ? 'epoch_twab_holders' : 'epoch_snapshot_holders';
+  const rateJoin =
+    weightBy === 'sol' ? 'JOIN epoch_exchange_rates r ON r.epoch = h.epoch AND r.mint = h.mint' : '';
+  const weightColumn = weightBy === 'sol' ? 'FLOOR(h.amount * r.rate_numerator / r.rate_denominator)' : 'h.amount';
+
+  // Native stake is in lamports: it weighs as is, and its balance is the LST
+  // amount it is worth at the epoch's rate
+  const nativeStake = await pool.query(
+    `SELECT 1 FROM epoch_native_stake_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, snapshotMint]
+  );
+  const withNativeStake = nativeStake.rows.length > 0 && balanceBy === 'snapshot' && weightBy === 'sol';
+  if (nativeStake.rows.length > 0 && !withNativeStake) {
+    console.log('⚠️  Native stake is only credited with --balance snapshot --weight sol; not counted');
+  }
+  const nativeStakeRows = withNativeStake
+    ? `
+      UNION ALL
+      SELECT n.wallet, n.lamports, FLOOR(n.lamports * r.rate_denominator / r.rate_numerator)
+      FROM epoch_native_stake_holders n
+      JOIN epoch_exchange_rates r ON r.epoch = n.epoch AND r.mint = n.mint
+      WHERE n.epoch = $1 AND n.mint = $2`
+    : '';
+
+  const { rows } = await pool.query<{
+    wallet: string;
+    weight: string;
+    balance: string;
+    is_system_owned: boolean | null;
+  }>(
+    `
+    SELECT h.wallet, SUM(h.weight)::text AS weight, SUM(h.balance)::text AS balance, w.is_system_owned
+    FROM (
+      SELECT h.wallet, ${weightColumn} AS weight, h.amount AS balance
+      FROM ${source} h
+      ${rateJoin}
+      WHERE h.epoch = $1 AND h.mint = $2
+      ${nativeStakeRows}
+    ) h
+    JOIN wallets w ON w.wallet = h.wallet
+    GROUP BY h.wallet, w.is_system_owned
+    `,
+    [epoch, snapshotMint]
+  );
+  if (rows.length === 0) {
+    throw new Error(
+      weightBy === 'sol'
+        ? `No exchange rate recorded for epoch ${epoch}; record it or pass --weight tokens`
+        : `Epoch ${epoch} ${balanceBy} has no holders`
+    );
+  }
+
+  // Only system-owned wallets receive rewards, as in compute-reward-payouts
+  const unclassified = rows.filter((r) => r.is_system_owned === null).length;
+  if (unclassified > 0) {
+    console.log(`⚠️  ${unclassified} holder(s) not yet classified are excluded (run classify-wallets first)`);
+  }
+  const holders: HolderWeight[] = rows
+    .filter((r) => r.is_system_owned === true)
+    .map((r) => ({ wallet: r.wallet, weight: BigInt(r.weight), balance: BigInt(r.balance) }));
+
+  // Every excluded holder is recorded with its weight and reason
+  const exclusions = await loadExclusions(lst, args.exclude);
+  const excludedRows: { wallet: string; reason: string; weight: string }[] = rows
+    .filter((r) => r.is_system_owned !== true)
+    .map((r) => ({
+      wallet: r.wallet,
+      reason: r.is_system_owned === null ? 'unclassified' : 'program-owned account',
+      weight: r.weight,
+    }));
+
+  // Per-wallet remainders accrued by earlier rewards of the same mint
+  const accruals = new Map<string, bigint>();
+  if (rounding === 'accrue') {
+    const accrued = await pool.query<{ wallet: string; accrued: string }>(
+      `SELECT wallet, accrued::text AS accrued FROM wallet_accruals WHERE mint = $1 AND accrued > 0`,
+      [mint]
+    );
+    for (const row of accrued.rows) accruals.set(row.wallet, parseAccrual(row.accrued));
+  }
+
+  // -----------------------------
+  // Allocate
+  // -----------------------------
+  const result = allocateProRata(holders, {
+    budget: configuredTotal,
+    carryIn,
+    rounding,
+    exclude: exclusions.keys(),
+    minWeight: args['min-weight'] ? BigInt(args['min-weight']) : undefined,
+    minBalance,
+    belowMinBalance,
+    minPayout: args['min-payout'] ? BigInt(args['min-payout']) : undefined,
+    accruals,
+  });
+
+  for (const holder of result.excluded) {
+    excludedRows.push({
+      wallet: holder.wallet,
+      reason: exclusions.get(holder.wallet)!,
+      weight: holder.weight.toString(),
+    });
+  }
+
+  if (result.allocations.length === 0) {
+    throw new Error(`No eligible holders for ${rewardId} in epoch ${epoch}`);
+  }
+
+  console.log(`Reward ${rewardId}, ${lst.symbol} epoch ${epoch} (snapshot slot ${snapshot.rows[0].slot}), weighted by ${weightBy} (${balanceBy} balances)`);
+  console.log(`  Budget:          ${configuredTotal} (${budgetSource}) + carry-in ${carryIn} = ${result.effectiveBudget}`);
+  console.log(`  Recipients:      ${result.allocations.length}`);
+  console.log(`  Excluded:        ${excludedRows.length}`);
+  const byReason = new Map<string, { holders: number; weight: bigint }>();
+  for (const row of excludedRows) {
+    const entry = byReason.get(row.reason) ?? { holders: 0, weight: 0n };
+    entry.holders++;
+    entry.weight += BigInt(row.weight);
+    byReason.set(row.reason, entry);
+  }
+  for (const [reason, { holders: count, weight }] of byReason) {
+    console.log(`    ${reason}: ${count} holder(s), weight ${weight}`);
+  }
+  console.log(`  Below min weight: ${result.belowMinWeight}`);
+  if (minBalance !== undefined) {
+    console.log(`  Below min balance: ${result.belowMinBalance} (< ${minBalance}, ${belowMinBalance})`);
+  }
+  console.log(`  Below min payout: ${result.belowMinPayout}`);
+  console.log(`  Distributed:     ${result.distributed}`);
+  console.log(`  Carry-out dust:  ${result.carryOut}`);
+  if (result.remainderPool > 0n) {
+    console.log(`    of which remainder pool: ${result.remainderPool}`);
+  }
+
+  // Ledger entries for every wallet whose accrual was used or changed
+  const paid = new Map(result.allocations.map((a) => [a.wallet, a.amount]));
+  const accrualRows = [...result.accruals]
+    .map(([wallet, carryOut]) => ({ wallet, carryIn: accruals.get(wallet) ?? 0n, carryOut }))
+    .filter((row) => row.carryIn > 0n || row.carryOut > 0n);
+  if (rounding === 'accrue') {
+    const totalAccrued = [...result.accruals.values()].reduce((sum, a) => sum + a, 0n);
+    console.log(`  Accrued:         ${formatAccrual(totalAccrued)} across ${accrualRows.length} wallet(s)`);
+  }
+
+  // -----------------------------
+  // Persist payouts + dust ledger atomically
+  // -----------------------------
+  const windowId = `epoch-${epoch}`;
+  const client = await pool.connect();
+  try {
+    await client.query('BEGIN');
+
+    await client.query(`DELETE FROM reward_payouts_preview WHERE reward_id = $1`, [rewardId]);
+    await client.query(`DELETE FROM reward_exclusions WHERE reward_id = $1`, [rewardId]);
+
+    await client.query(
+      `
+      INSERT INTO reward_payouts_preview (
+        reward_id,
+        window_id,
+        wallet,
+        mint,
+        share,
+        total_amount,
+        payout_amount
+      )
+      SELECT $1, $2, a.wallet, $3, a.weight / $4::numeric, $5::numeric, a.amount
+      FROM unnest($6::text[], $7::numeric[], $8::numeric[]) AS a(wallet, weight, amount)
+      `,
+      [
+        rewardId,
+        windowId,
+        mint,
+        result.totalWeight.toString(),
+        result.effectiveBudget.toString(),
+        result.allocations.map((a) => a.wallet),
+        result.allocations.map((a) => a.weight.toString()),
+        result.allocations.map((a) => a.amount.toString()),
+      ]
+    );
+
+    await client.query(
+      `
+      INSERT INTO wallet_accrual_ledger (reward_id, mint, wallet, carry_in, paid, carry_out)
+      SELECT $1, $2, a.wallet, a.carry_in, a.paid, a.carry_out
+      FROM unnest($3::text[], $4::numeric[], $5::numeric[], $6::numeric[]) AS a(wallet, carry_in, paid, carry_out)
+      `,
+      [
+        rewardId,
+        mint,
+        accrualRows.map((row) => row.wallet),
+        accrualRows.map((row) => formatAccrual(row.carryIn)),
+        accrualRows.map((row) => (paid.get(row.wallet) ?? 0n).toString()),
+        accrualRows.map((row) => formatAccrual(row.carryOut)),
+      ]
+    );
+
+    await client.query(
+      `
+      INSERT INTO reward_exclusions (reward_id, epoch, wallet, reason, weight)
+      SELECT $1, $2, e.wallet, e.reason, e.weight
+      FROM unnest($3::text[], $4::text[], $5::numeric[]) AS e(wallet, reason, weight)
+      `,
+      [
+        rewardId,
+        epoch,
+        excludedRows.map((e) => e.wallet),
+        excludedRows.map((e) => e.reason),
+        excludedRows.map((e) => e.weight),
+      ]
+    );
+
+    await client.query(
+      `
+      INSERT INTO reward_dust_ledger (
+        reward_id,
+        mint,
+        configured_total,
+        carry_in,
+        distributed,
+        carry_out,
+        remainder_pool
+      ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+      `,
+      [
+        rewardId,
+        mint,
+        configuredTotal.toString(),
+        carryIn.toString(),
+        result.distributed.toString(),
+        result.carryOut.toString(),
+        result.remainderPool.toString(),
+      ]
+    );
+
+    await client.query('COMMIT');
+  } catch (e) {
+    await client.query('ROLLBACK');
+    throw e;
+  } finally {
+    client.release();
+  }
+
+  console.log('');
+  console.log('✅ Payouts written to reward_payouts_preview (exclusions to reward_exclusions)');
+  console.log('Next: export the allocation set for the tree builder:');
+  console.log(`  npx ts-node src/jobs/export-reward-csv.ts ${rewardId}`);
+}
+
+allocateEpochReward()
+  .catch((e) => {
+    console.error(e);
+    process.exit(1);
+  })
+  .finally(() => {
+    pool.end();
+  });
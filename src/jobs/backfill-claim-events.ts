@@ -0,0 +1,52 @@
+// src/jobs/backfill-claim-events.ts
+// Backfills merkle_claim_events from the distributor's transaction history
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getDeploymentConfig } from '../config/deployments';
+import { backfillClaimEvents, backfillOptionsFromArgs, printBackfillUsage } from '../indexers/backfill';
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      } else {
+        args[key] = 'true';
+      }
+    }
+  }
+  return args;
+}
+
+async function main() {
+  const args = parseArgs();
+  if (args.help) {
+    printBackfillUsage('npx ts-node src/jobs/backfill-claim-events.ts');
+    return;
+  }
+
+  const deployment = getDeploymentConfig(args.deployment);
+  const result = await backfillClaimEvents(backfillOptionsFromArgs(args, deployment));
+
+  console.log('');
+  console.log(`✅ Backfill complete: ${result.transactions} transactions fetched, ${result.events} events stored`);
+  console.log(`   ${result.stamped} stored event(s) stamped with a missing block time`);
+  if (result.deadLettered > 0) {
+    console.log(`⚠️  ${result.deadLettered} transaction(s) dead-lettered, see npm run replay-dead-letters`);
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error(e);
+    process.exit(1);
+  })
+  .finally(() => {
+    pool.end();
+  });
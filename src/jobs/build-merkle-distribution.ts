@@ -4,6 +4,7 @@
 import 'dotenv/config';
 import fs from 'fs';
 import path from 'path';
+import { Keypair } from '@solana/web3.js';
 import { pool } from '../db';
 import {
   buildDistributionArtifact,
@@ -11,10 +12,13 @@ import {
   validateArtifact,
 } from '../merkle/builder';
 import { MerkleTree, constructLeaf } from '../merkle/tree';
+import { HashScheme } from '../merkle/types';
+import { signRoot, signaturePathFor } from '../merkle/signing';
 import { getTokenByMint, fromRawAmount } from '../config/tokens';
 
 async function main() {
   const csvPath = process.argv[2];
+  const hashScheme: HashScheme = process.env.MERKLE_HASH_SCHEME === 'v2' ? 'v2' : 'v1';
 
   if (!csvPath) {
     console.log('Usage: npx ts-node src/jobs/build-merkle-distribution.ts <csv-path>');
@@ -37,7 +41,7 @@ async function main() {
   console.log('-'.repeat(60));
 
   // Build the artifact
-  const artifact = buildDistributionArtifact(csvPath);
+  const artifact = buildDistributionArtifact(csvPath, hashScheme);
 
   // Validate
   const validation = validateArtifact(artifact);
@@ -65,6 +69,7 @@ async function main() {
   console.log('');
   console.log('Merkle Data:');
   console.log(`  Root:            ${artifact.merkleRoot}`);
+  console.log(`  Hash Scheme:     ${hashScheme}`);
   console.log(`  CSV Hash:        ${artifact.csvHash}`);
   console.log('');
 
@@ -74,12 +79,14 @@ async function main() {
   const sampleLeaf = constructLeaf(
     artifact.distributionId,
     sampleProof.wallet,
-    BigInt(sampleProof.amount)
+    BigInt(sampleProof.amount),
+    hashScheme
   );
   const verified = MerkleTree.verifyHex(
     artifact.merkleRoot,
     sampleLeaf.toString('hex'),
-    sampleProof.proof
+    sampleProof.proof,
+    hashScheme
   );
   console.log(`  Sample proof (${sampleProof.wallet.slice(0, 8)}...): ${verified ? '✓ Valid' : '✗ Invalid'}`);
 
@@ -95,6 +102,15 @@ async function main() {
   console.log('');
   console.log(`✅ Artifact saved: ${artifactPath}`);
 
+  // Detached root signature by the operations key, checked by the init job
+  if (process.env.OPS_KEYPAIR) {
+    const raw = JSON.parse(fs.readFileSync(process.env.OPS_KEYPAIR, 'utf8'));
+    const signature = signRoot(artifact, Keypair.fromSecretKey(Uint8Array.from(raw)));
+    const signaturePath = signaturePathFor(artifactPath);
+    fs.writeFileSync(signaturePath, JSON.stringify(signature, null, 2));
+    console.log(`✅ Root signed by ${signature.signer}: ${signaturePath}`);
+  }
+
   // Store in database
   try {
     await pool.query(
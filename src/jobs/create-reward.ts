@@ -237,6 +237,14 @@ async function main() {
     }
   }
 
+  // Minimum snapshot balance for epoch rewards (optional, raw LST units)
+  const minSnapshotBalance = args['min-balance'] ? BigInt(args['min-balance']) : null;
+  const belowMinBalance = args['below-min'] || 'redistribute';
+  if (!['redistribute', 'remainder'].includes(belowMinBalance)) {
+    console.error('Invalid --below-min. Use "redistribute" or "remainder"');
+    process.exit(1);
+  }
+
   // Get label (optional)
   let label = args.label;
   if (!label) {
@@ -274,6 +282,10 @@ async function main() {
     console.log(`Requires:        ${eligibilityTokenMinAmount} raw units (${eligTokenInfo?.symbol || 'unknown'})`);
   }
 
+  if (minSnapshotBalance !== null) {
+    console.log(`Min balance:     ${minSnapshotBalance} raw units (below: ${belowMinBalance})`);
+  }
+
   console.log(`Label:           ${label}`);
   console.log('-'.repeat(80));
 
@@ -297,8 +309,10 @@ async function main() {
         eligibility_mode,
         eligibility_token_mint,
         eligibility_token_min_amount,
+        min_snapshot_balance,
+        below_min_balance,
         label
-      ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+      ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
       `,
       [
         rewardId,
@@ -309,6 +323,8 @@ async function main() {
         eligibilityMode,
         eligibilityTokenMint,
         eligibilityTokenMinAmount?.toString() || null,
+        minSnapshotBalance?.toString() ?? null,
+        belowMinBalance,
         label,
       ]
     );
@@ -0,0 +1,88 @@
+// src/jobs/export-finance.ts
+// Exports allocations, claims and reconciliation status over a date range to CSV or Parquet
+
+import 'dotenv/config';
+import { pool } from '../db';
+import {
+  exportFileName,
+  FINANCE_DATASETS,
+  FinanceDataset,
+  parseExportDate,
+  previousMonth,
+  runFinanceExport,
+} from '../exports/finance';
+import { destinationPath, EXPORT_FORMATS, ExportFormat } from '../exports/writers';
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      }
+    }
+  }
+  return args;
+}
+
+function printUsage() {
+  console.log('Usage: npx ts-node src/jobs/export-finance.ts [--from <DATE>] [--to <DATE>] [options]');
+  console.log('');
+  console.log('Streams finance datasets over [from, to) to files or an S3-compatible store.');
+  console.log('Dates are YYYY-MM-DD (UTC) or ISO timestamps; the default range is the previous month.');
+  console.log('');
+  console.log('Options:');
+  console.log(`  --dataset ${FINANCE_DATASETS.join('|')}|all`);
+  console.log('                          Dataset to export (default: all)');
+  console.log(`  --format ${EXPORT_FORMATS.join('|')}      File format (default: csv)`);
+  console.log('  --out <DIR|s3://bucket/prefix>');
+  console.log('                          Where to write <dataset>_<from>_<to>.<format>');
+  console.log('                          (default: EXPORT_DESTINATION, else ./exports)');
+}
+
+async function main() {
+  if (process.argv.includes('--help')) {
+    printUsage();
+    return;
+  }
+  const args = parseArgs();
+
+  const defaults = previousMonth();
+  const range = {
+    from: args.from ? parseExportDate(args.from, '--from') : defaults.from,
+    to: args.to ? parseExportDate(args.to, '--to') : defaults.to,
+  };
+
+  const format = (args.format ?? 'csv') as ExportFormat;
+  if (!EXPORT_FORMATS.includes(format)) {
+    printUsage();
+    throw new Error(`Invalid --format: ${format}`);
+  }
+
+  const datasetArg = args.dataset ?? 'all';
+  const datasets = datasetArg === 'all' ? FINANCE_DATASETS : [datasetArg as FinanceDataset];
+  if (!datasets.every((d) => FINANCE_DATASETS.includes(d))) {
+    printUsage();
+    throw new Error(`Invalid --dataset: ${datasetArg}`);
+  }
+
+  console.log(`Exporting ${range.from.toISOString()} to ${range.to.toISOString()} (${format})`);
+  for (const dataset of datasets) {
+    const location = args.out ? destinationPath(args.out, exportFileName(dataset, format, range)) : undefined;
+    const result = await runFinanceExport({ dataset, format, ...range, location });
+    console.log(`✅ ${dataset}: ${result.rows} row(s) → ${result.location}`);
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Error:', e.message);
+    process.exit(1);
+  })
+  .finally(() => {
+    pool.end();
+  });
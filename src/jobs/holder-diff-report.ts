@@ -0,0 +1,145 @@
+// src/jobs/holder-diff-report.ts
+// Builds holder diffs between epoch snapshots and exports their wallets
+//
+//   npm run holder-diff-report -- [--lst <SYMBOL>] [--epoch <N>] [--from <N> | --days <n>] [--out <DIR|s3://...>]
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getLstConfig, loadLstConfigs } from '../config/lsts';
+import {
+  buildHolderDiff,
+  buildPendingHolderDiffs,
+  HOLDER_COHORTS,
+  HolderCohort,
+  HolderDiffResult,
+  latestSnapshotEpoch,
+  previousSnapshotEpoch,
+} from '../indexers/holder-diffs';
+import { holderDiffFileName, runHolderDiffExport } from '../exports/holder-diffs';
+import { destinationPath, EXPORT_FORMATS, ExportFormat } from '../exports/writers';
+
+function printUsage() {
+  console.log('Usage: npm run holder-diff-report -- [options]');
+  console.log('');
+  console.log('Compares the holder sets of epoch snapshots: wallets that entered and exited, and');
+  console.log('balance change cohorts of the rest. Without --epoch, --from or --days, builds every');
+  console.log('consecutive snapshot pair not diffed yet.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --lst <SYMBOL>        Only this LST (default: every configured LST)');
+  console.log('  --epoch <N>           Diff ending at this snapshot (default: the latest)');
+  console.log('  --from <N>            Snapshot to compare against (default: the one before --epoch)');
+  console.log('  --days <n>            Compare against the latest snapshot at least n days older');
+  console.log('                        (--days 7 for a weekly report)');
+  console.log('  --rebuild             Rebuild diffs that already exist');
+  console.log('  --out <DIR|s3://bucket/prefix>');
+  console.log('                        Also export the wallets of each diff as');
+  console.log('                        holder_diff_<mint>_<from>_<to>.<format>');
+  console.log(`  --format ${EXPORT_FORMATS.join('|')}      Export format (default: csv)`);
+  console.log(`  --cohort <cohort,...> Only export these cohorts (${HOLDER_COHORTS.join(', ')})`);
+}
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      } else {
+        args[key] = 'true';
+      }
+    }
+  }
+  return args;
+}
+
+function parseCount(value: string | undefined, name: string): number | undefined {
+  if (value === undefined) return undefined;
+  const n = Number(value);
+  if (!Number.isInteger(n) || n < 0) throw new Error(`Invalid --${name}: ${value}`);
+  return n;
+}
+
+function parseCohorts(value: string | undefined): HolderCohort[] | undefined {
+  if (value === undefined) return undefined;
+  const cohorts = value.split(',').map((c) => c.trim()) as HolderCohort[];
+  const unknown = cohorts.find((c) => !HOLDER_COHORTS.includes(c));
+  if (unknown) throw new Error(`Invalid --cohort: ${unknown}`);
+  return cohorts;
+}
+
+function report(symbol: string, diff: HolderDiffResult) {
+  const { cohorts } = diff;
+  const status = diff.skipped ? 'already built' : 'built';
+  console.log(
+    `✅ ${symbol} epochs ${diff.fromEpoch} → ${diff.toEpoch} (${status}): ` +
+      `${diff.holdersFrom} → ${diff.holdersTo} holders, ${cohorts.entered} entered, ${cohorts.exited} exited`
+  );
+  const bands = HOLDER_COHORTS.filter((c) => c !== 'entered' && c !== 'exited');
+  console.log(`   ${bands.map((c) => `${c} ${cohorts[c]}`).join(', ')}`);
+}
+
+async function main() {
+  const args = parseArgs();
+  if (args.help) {
+    printUsage();
+    return;
+  }
+
+  const epoch = parseCount(args.epoch, 'epoch');
+  const from = parseCount(args.from, 'from');
+  const days = parseCount(args.days, 'days');
+  if (from !== undefined && days !== undefined) throw new Error('Use either --from or --days');
+  const explicit = epoch !== undefined || from !== undefined || days !== undefined;
+
+  const format = (args.format ?? 'csv') as ExportFormat;
+  if (!EXPORT_FORMATS.includes(format)) {
+    printUsage();
+    throw new Error(`Invalid --format: ${format}`);
+  }
+  const cohorts = parseCohorts(args.cohort);
+
+  const lsts = args.lst ? [getLstConfig(args.lst)] : loadLstConfigs();
+
+  for (const lst of lsts) {
+    let diffs: HolderDiffResult[];
+    if (explicit) {
+      const toEpoch = epoch ?? (await latestSnapshotEpoch(lst.mint));
+      if (toEpoch === null) {
+        console.log(`⏭️  ${lst.symbol}: no snapshots yet`);
+        continue;
+      }
+      const fromEpoch = from ?? (await previousSnapshotEpoch(lst.mint, toEpoch, days));
+      if (fromEpoch === null) {
+        const span = days === undefined ? '' : ` at least ${days} day(s)`;
+        console.log(`⏭️  ${lst.symbol}: no snapshot${span} before epoch ${toEpoch}`);
+        continue;
+      }
+      diffs = [await buildHolderDiff({ mint: lst.mint, fromEpoch, toEpoch }, { rebuild: args.rebuild === 'true' })];
+    } else {
+      diffs = await buildPendingHolderDiffs(lst.mint);
+      if (diffs.length === 0) console.log(`✅ ${lst.symbol}: holder diffs up to date`);
+    }
+
+    for (const diff of diffs) {
+      report(lst.symbol, diff);
+      if (args.out) {
+        const location = destinationPath(args.out, holderDiffFileName(diff, format));
+        const { mint, fromEpoch, toEpoch } = diff;
+        const exported = await runHolderDiffExport({ mint, fromEpoch, toEpoch, format, cohorts, location });
+        console.log(`   ${exported.rows} wallet(s) → ${exported.location}`);
+      }
+    }
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Holder diff report failed:', e.message);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
@@ -6,88 +6,33 @@ import fs from 'fs';
 import assert from 'assert';
 
 import * as multisig from '@sqds/multisig';
-import {
-  PublicKey,
-  Keypair,
-  TransactionMessage,
-  TransactionInstruction,
-  SystemProgram,
-  SYSVAR_RENT_PUBKEY,
-} from '@solana/web3.js';
-import {
-  getAssociatedTokenAddressSync,
-  createTransferCheckedInstruction,
-  getMint,
-  TOKEN_PROGRAM_ID,
-} from '@solana/spl-token';
+import { PublicKey, Keypair, TransactionMessage } from '@solana/web3.js';
+import { getMint } from '@solana/spl-token';
 
 import { pool } from '../db';
-import { loadArtifact, validateArtifact } from '../merkle/builder';
-import { getDistributionPda, getVaultPda } from '../merkle/relayer';
+import {
+  loadArtifact,
+  validateArtifact,
+  readSummaryFromStdin,
+  validateSummary,
+} from '../merkle/builder';
+import { loadRootSignature, signaturePathFor, verifyRootSignature } from '../merkle/signing';
+import { fetchRegistryCount } from '../merkle/relayer';
+import { buildInitializeAndFund } from '../merkle/initialize';
 import { getTokenByMint, fromRawAmount } from '../config/tokens';
-import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+import { getDeploymentConfig } from '../config/deployments';
+import { FailoverConnection } from '../utils/rpc';
 
 function loadKeypair(filePath: string): Keypair {
   const raw = JSON.parse(fs.readFileSync(filePath, 'utf8'));
   return Keypair.fromSecretKey(Uint8Array.from(raw));
 }
 
-/**
- * Build the initialize instruction for the Merkle distributor program
- */
-function buildInitializeInstruction(
-  programId: PublicKey,
-  authority: PublicKey,
-  distribution: PublicKey,
-  mint: PublicKey,
-  vault: PublicKey,
-  distributionId: Buffer,
-  merkleRoot: Buffer,
-  totalAmount: bigint,
-  numRecipients: number
-): TransactionInstruction {
-  // Anchor discriminator for "initialize"
-  const discriminator = Buffer.from([175, 175, 109, 31, 13, 152, 155, 237]);
-
-  // Encode instruction data
-  // [discriminator (8)] [distribution_id (32)] [merkle_root (32)] [total_amount (8)] [num_recipients (8)]
-  const data = Buffer.alloc(8 + 32 + 32 + 8 + 8);
-  let offset = 0;
-
-  discriminator.copy(data, offset);
-  offset += 8;
-
-  distributionId.copy(data, offset);
-  offset += 32;
-
-  merkleRoot.copy(data, offset);
-  offset += 32;
-
-  data.writeBigUInt64LE(totalAmount, offset);
-  offset += 8;
-
-  data.writeBigUInt64LE(BigInt(numRecipients), offset);
-
-  return new TransactionInstruction({
-    programId,
-    keys: [
-      { pubkey: authority, isSigner: true, isWritable: true },
-      { pubkey: distribution, isSigner: false, isWritable: true },
-      { pubkey: mint, isSigner: false, isWritable: false },
-      { pubkey: vault, isSigner: false, isWritable: true },
-      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
-      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
-      { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
-    ],
-    data,
-  });
-}
-
 async function main() {
   const artifactPath = process.argv[2];
 
   if (!artifactPath) {
-    console.log('Usage: npx ts-node src/jobs/init-merkle-distribution.ts <artifact-path>');
+    console.log('Usage: npx ts-node src/jobs/init-merkle-distribution.ts <artifact-path> [signature-path]');
     console.log('');
     console.log('Creates a single Squads multisig proposal that:');
     console.log('  1. Initializes the on-chain distribution with Merkle root');
@@ -95,30 +40,37 @@ async function main() {
     console.log('');
     console.log('Example:');
     console.log('  npx ts-node src/jobs/init-merkle-distribution.ts distributions/ORE_W51_TEST_merkle.json');
+    console.log('');
+    console.log('Pass - to read a build summary (no proofs) from stdin, e.g. piped from the Rust builder.');
+    console.log('');
+    console.log('With OPS_SIGNER_PUBKEY set, the root must carry a detached signature by that key');
+    console.log('(default: <artifact>.sig.json next to the artifact; required as 2nd argument with -).');
+    console.log('');
+    console.log('With several deployments in DEPLOYMENTS_CONFIG, DEPLOYMENT names the one to initialize on.');
     process.exit(1);
   }
 
   // Load environment
-  const rpcConfig = getRpcConfigFromEnv();
-  const rpc = new FailoverConnection(rpcConfig);
+  const deployment = getDeploymentConfig(process.env.DEPLOYMENT || undefined);
+  const rpc = new FailoverConnection(deployment.rpc);
 
   const multisigAddr = process.env.SQUADS_MULTISIG!;
   const vaultAddr = process.env.SQUAD_VAULT_ADDRESS!;
   const keypairPath = process.env.SQUADS_MEMBER_KEYPAIR!;
-  const programIdStr = process.env.MERKLE_PROGRAM_ID;
 
   assert(multisigAddr, 'Missing SQUADS_MULTISIG');
   assert(vaultAddr, 'Missing SQUAD_VAULT_ADDRESS');
   assert(keypairPath, 'Missing SQUADS_MEMBER_KEYPAIR');
 
-  // Load artifact
-  if (!fs.existsSync(artifactPath)) {
+  // Load artifact, or a build summary from stdin
+  const fromStdin = artifactPath === '-';
+  if (!fromStdin && !fs.existsSync(artifactPath)) {
     console.error(`❌ Artifact not found: ${artifactPath}`);
     process.exit(1);
   }
 
-  const artifact = loadArtifact(artifactPath);
-  const validation = validateArtifact(artifact);
+  const artifact = fromStdin ? readSummaryFromStdin() : loadArtifact(artifactPath);
+  const validation = artifact.proofs ? validateArtifact(artifact) : validateSummary(artifact);
 
   if (!validation.valid) {
     console.error('❌ Invalid artifact:');
@@ -126,16 +78,33 @@ async function main() {
     process.exit(1);
   }
 
+  // Root must be signed by the operations key before anything is proposed
+  const opsSigner = process.env.OPS_SIGNER_PUBKEY;
+  const signaturePath = process.argv[3] ?? (fromStdin ? undefined : signaturePathFor(artifactPath));
+  let signatureStatus = 'not checked (OPS_SIGNER_PUBKEY not set)';
+  if (opsSigner) {
+    if (!signaturePath || !fs.existsSync(signaturePath)) {
+      console.error(`❌ Root signature not found: ${signaturePath ?? '(pass a signature path with -)'}`);
+      process.exit(1);
+    }
+    const check = verifyRootSignature(artifact, loadRootSignature(signaturePath), new PublicKey(opsSigner));
+    if (!check.valid) {
+      console.error('❌ Invalid root signature:');
+      check.errors.forEach((e) => console.error(`  - ${e}`));
+      process.exit(1);
+    }
+    signatureStatus = `✓ signed by ${opsSigner}`;
+  }
+
   console.log('🔐 Merkle Distribution Initialization\n');
+  console.log(`Deployment: ${deployment.name} (${deployment.cluster}, program ${deployment.programId})`);
   console.log(`RPC: ${rpc.getCurrentUrl()}${rpc.hasBackup() ? ' (backup configured)' : ''}\n`);
 
   // Setup
   const multisigPda = new PublicKey(multisigAddr);
   const vaultAuthority = new PublicKey(vaultAddr);
   const member = loadKeypair(keypairPath);
-  const programId = programIdStr
-    ? new PublicKey(programIdStr)
-    : new PublicKey('11111111111111111111111111111111'); // Placeholder
+  const programId = new PublicKey(deployment.programId);
 
   const mint = new PublicKey(artifact.mint);
   const totalAmount = BigInt(artifact.totalAmount);
@@ -156,22 +125,40 @@ async function main() {
   console.log(`  Amount:       ${fromRawAmount(totalAmount, decimals)} ${symbol}`);
   console.log(`  Recipients:   ${artifact.numRecipients}`);
   console.log(`  Merkle Root:  ${artifact.merkleRoot}`);
+  console.log(`  Signature:    ${signatureStatus}`);
   console.log('');
 
-  // Derive PDAs
-  const distributionIdBuffer = Buffer.from(artifact.distributionId, 'hex');
-  const merkleRootBuffer = Buffer.from(artifact.merkleRoot, 'hex');
+  // Registry entry is derived from the registry count at execution time:
+  // execute pending initialize proposals in creation order
+  const registryIndex = await rpc.execute(
+    (connection) => fetchRegistryCount(connection, programId),
+    'getRegistryCount'
+  );
 
-  const [distributionPda] = getDistributionPda(programId, distributionIdBuffer);
-  const [vaultPda] = getVaultPda(programId, distributionIdBuffer);
+  // Initialize first, then fund. The clawback destination is fixed at
+  // initialization (defaults to the Squad vault ATA)
+  const {
+    distribution: distributionPda,
+    vault: vaultPda,
+    sourceAta,
+    clawbackDestination,
+    instructions,
+  } = buildInitializeAndFund(artifact, {
+    programId,
+    authority: vaultAuthority,
+    registryIndex,
+    decimals,
+    clawbackDestination: process.env.CLAWBACK_DESTINATION
+      ? new PublicKey(process.env.CLAWBACK_DESTINATION)
+      : undefined,
+    requireRecipientSignature: process.env.REQUIRE_RECIPIENT_SIGNATURE === 'true',
+  });
 
   console.log('On-Chain Addresses:');
   console.log(`  Distribution: ${distributionPda.toBase58()}`);
   console.log(`  Vault:        ${vaultPda.toBase58()}`);
   console.log('');
-
-  // Get source vault ATA
-  const sourceAta = getAssociatedTokenAddressSync(mint, vaultAuthority, true);
+  console.log(`Clawback Destination: ${clawbackDestination.toBase58()}`);
 
   // Check source balance
   const sourceInfo = await rpc.execute(
@@ -206,28 +193,6 @@ async function main() {
 
   const nextTransactionIndex = BigInt(Number(multisigInfo.transactionIndex)) + 1n;
 
-  // Build both instructions
-  const initIx = buildInitializeInstruction(
-    programId,
-    vaultAuthority,
-    distributionPda,
-    mint,
-    vaultPda,
-    distributionIdBuffer,
-    merkleRootBuffer,
-    totalAmount,
-    artifact.numRecipients
-  );
-
-  const fundIx = createTransferCheckedInstruction(
-    sourceAta,
-    mint,
-    vaultPda,
-    vaultAuthority,
-    totalAmount,
-    decimals
-  );
-
   // === SINGLE PROPOSAL: Initialize + Fund Distribution ===
   console.log('\n📝 Creating Proposal: Initialize + Fund Distribution');
   console.log('   Instructions:');
@@ -242,7 +207,7 @@ async function main() {
   const combinedMessage = new TransactionMessage({
     payerKey: vaultAuthority,
     recentBlockhash: blockhash,
-    instructions: [initIx, fundIx],  // Initialize first, then fund
+    instructions,
   });
 
   // Use current connection for multisig operations
@@ -280,13 +245,14 @@ async function main() {
       `
       UPDATE merkle_distributions
       SET
+        deployment = $4,
         on_chain_address = $2,
         vault_ata = $3,
         status = 'funded',
         funded_at = NOW()
       WHERE distribution_id = $1
       `,
-      [artifact.distributionId, distributionPda.toBase58(), vaultPda.toBase58()]
+      [artifact.distributionId, distributionPda.toBase58(), vaultPda.toBase58(), deployment.name]
     );
     console.log('\n✓ Database updated');
   } catch (error: any) {
@@ -301,7 +267,7 @@ async function main() {
   console.log('  1. Review and approve the proposal in Squads UI');
   console.log('  2. Execute the proposal (initializes distribution + funds vault)');
   console.log('  3. Run relayer to process claims:');
-  console.log(`     npx ts-node src/jobs/run-merkle-relayer.ts ${artifactPath}`);
+  console.log(`     npx ts-node src/jobs/run-merkle-relayer.ts ${fromStdin ? '<artifact-path>' : artifactPath}`);
 }
 
 main()
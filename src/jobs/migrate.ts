@@ -0,0 +1,63 @@
+// src/jobs/migrate.ts
+// Applies, checks or reverts the schema migrations in db/migrations
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { migrateDown, migrateUp, migrationStatus } from '../utils/migrations';
+
+function printUsage() {
+  console.log('Usage: npm run migrate [-- --check | --down]');
+  console.log('');
+  console.log('Applies pending migrations from db/migrations in version order, each in its own transaction.');
+  console.log('The claim event runner and the scheduler do the same on startup (MIGRATE_ON_STARTUP=false to skip).');
+  console.log('');
+  console.log('Options:');
+  console.log('  --check    Report pending, modified and missing migrations without applying anything;');
+  console.log('             exits 1 unless the database is up to date');
+  console.log('  --down     Revert the most recently applied migration with its .down.sql');
+}
+
+async function check(): Promise<void> {
+  const status = await migrationStatus();
+  const latest = status.applied[status.applied.length - 1];
+  console.log(`Applied: ${status.applied.length}${latest ? ` (latest ${latest.version}_${latest.name})` : ''}`);
+
+  for (const m of status.pending) console.log(`⏳ Pending:  ${m.version}_${m.name}`);
+  for (const m of status.modified) console.log(`❌ Modified: ${m.version}_${m.name} (changed after it was applied)`);
+  for (const m of status.missing) console.log(`❌ Missing:  ${m.version}_${m.name} (applied, not in db/migrations)`);
+
+  if (status.pending.length + status.modified.length + status.missing.length > 0) {
+    process.exitCode = 1;
+    return;
+  }
+  console.log('✅ Database schema is up to date');
+}
+
+async function main() {
+  if (process.argv.includes('--help')) {
+    printUsage();
+    return;
+  }
+
+  if (process.argv.includes('--check')) {
+    await check();
+    return;
+  }
+
+  if (process.argv.includes('--down')) {
+    const reverted = await migrateDown();
+    console.log(reverted ? `✅ Reverted migration ${reverted.version}_${reverted.name}` : '⚠️  No migration applied');
+    return;
+  }
+
+  const applied = await migrateUp();
+  for (const m of applied) console.log(`✅ Applied migration ${m.version}_${m.name}`);
+  if (applied.length === 0) console.log('✅ Database schema is up to date');
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Migration failed:', e.message);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
@@ -0,0 +1,54 @@
+// src/jobs/prune-raw-data.ts
+// One-off run of the retention policies for raw ingestion data
+//
+//   npm run prune-raw-data [-- --dry-run]
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getRetentionConfigFromEnv, pruneRawData, runRetention } from '../utils/retention';
+
+function printUsage() {
+  console.log('Usage: npm run prune-raw-data [-- --dry-run]');
+  console.log('');
+  console.log('Prunes raw payloads and caches older than their retention (RETENTION_*_DAYS),');
+  console.log('then vacuums the tables pruned (RETENTION_VACUUM). The scheduler does the same');
+  console.log('on RETENTION_SCHEDULE.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --dry-run    Count the rows that would be pruned without changing anything');
+}
+
+async function main() {
+  if (process.argv.includes('--help')) {
+    printUsage();
+    return;
+  }
+
+  const config = getRetentionConfigFromEnv();
+  for (const p of config.policies) {
+    console.log(`${p.name.padEnd(22)} ${p.table.padEnd(24)} ${p.days ? `${p.days} days` : 'kept forever'}`);
+  }
+  console.log('');
+
+  if (process.argv.includes('--dry-run')) {
+    for (const p of await pruneRawData(config.policies, true)) {
+      console.log(`⏳ ${p.policy}: ${p.rows} row(s) would be pruned`);
+    }
+    return;
+  }
+
+  const { pruned, vacuumed } = await runRetention(config);
+  for (const p of pruned) {
+    console.log(`✅ ${p.policy}: ${p.rows} row(s) pruned`);
+  }
+  for (const v of vacuumed) {
+    console.log(`✅ Vacuumed ${v.table}: ${v.bytesBefore} → ${v.bytesAfter} bytes`);
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Pruning failed:', e.message);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
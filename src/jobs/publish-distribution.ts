@@ -0,0 +1,290 @@
+// src/jobs/publish-distribution.ts
+// Publication pipeline: finalized epoch snapshot -> allocation -> Merkle tree -> initialize draft
+
+import 'dotenv/config';
+import fs from 'fs';
+import path from 'path';
+import { spawnSync } from 'child_process';
+
+import { PublicKey, TransactionMessage, VersionedTransaction } from '@solana/web3.js';
+import { getMint } from '@solana/spl-token';
+
+import { pool } from '../db';
+import { getLstConfig } from '../config/lsts';
+import { loadArtifact, validateArtifact } from '../merkle/builder';
+import { signaturePathFor } from '../merkle/signing';
+import { fetchRegistryCount } from '../merkle/relayer';
+import { buildInitializeAndFund, serializeInstruction } from '../merkle/initialize';
+import { DistributionArtifact } from '../merkle/types';
+import { FailoverConnection, getRpcConfigFromEnv } from '../utils/rpc';
+
+// Allocation flags passed through to allocate-epoch-reward.ts
+const ALLOCATION_FLAGS = [
+  'budget',
+  'weight',
+  'balance',
+  'rounding',
+  'min-weight',
+  'min-balance',
+  'below-min',
+  'min-payout',
+  'exclude',
+];
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      } else {
+        args[key] = 'true';
+      }
+    }
+  }
+  return args;
+}
+
+function printUsage() {
+  console.log('Usage: npx ts-node src/jobs/publish-distribution.ts --epoch <EPOCH> [--lst <SYMBOL>] [--reward <REWARD_ID>] [options]');
+  console.log('');
+  console.log('Takes a finalized epoch snapshot to a ready-to-sign initialize transaction:');
+  console.log('  1. Checks the epoch snapshot exists and its slot is finalized');
+  console.log('  2. Allocates the reward over it (allocate-epoch-reward.ts)');
+  console.log('  3. Exports the payout CSV and builds the Merkle tree (signed with OPS_KEYPAIR if set)');
+  console.log('  4. Uploads the proof set (PROOF_UPLOAD_URL or PROOF_UPLOAD_DIR)');
+  console.log('  5. Writes distributions/<reward>_init_draft.json: the initialize + fund instructions');
+  console.log('     as a Squads proposal payload and as an unsigned transaction');
+  console.log('');
+  console.log('The reward must exist (create-reward.ts). It defaults to <series>_EPOCH_<epoch>.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --lst <SYMBOL>          LST to allocate over (required when several are configured)');
+  console.log('  --propose               Also create the Squads proposal (init-merkle-distribution.ts)');
+  console.log(`  --${ALLOCATION_FLAGS.join(', --')}`);
+  console.log('                          Passed through to allocate-epoch-reward.ts');
+}
+
+/**
+ * Run another job with this process's environment, exiting if it fails
+ */
+function runJob(script: string, args: string[]) {
+  console.log(`\n▶ ${script} ${args.join(' ')}`);
+  const result = spawnSync('npx', ['ts-node', path.join('src/jobs', script), ...args], {
+    stdio: 'inherit',
+    env: process.env,
+  });
+  if (result.status !== 0) {
+    console.error(`❌ ${script} failed${result.status === null ? '' : ` (exit ${result.status})`}`);
+    process.exit(1);
+  }
+}
+
+/**
+ * Publish the artifact (root, proofs) where the claim frontend reads it
+ */
+async function uploadProofSet(artifact: DistributionArtifact, artifactPath: string): Promise<string | null> {
+  const name = `${artifact.distributionId}.json`;
+
+  if (process.env.PROOF_UPLOAD_URL) {
+    const url = `${process.env.PROOF_UPLOAD_URL.replace(/\/$/, '')}/${name}`;
+    const headers: Record<string, string> = { 'Content-Type': 'application/json' };
+    if (process.env.PROOF_UPLOAD_TOKEN) {
+      headers.Authorization = `Bearer ${process.env.PROOF_UPLOAD_TOKEN}`;
+    }
+    const res = await fetch(url, { method: 'PUT', headers, body: fs.readFileSync(artifactPath) });
+    if (!res.ok) {
+      throw new Error(`Proof upload to ${url} failed: ${res.status} ${res.statusText}`);
+    }
+    return url;
+  }
+
+  if (process.env.PROOF_UPLOAD_DIR) {
+    fs.mkdirSync(process.env.PROOF_UPLOAD_DIR, { recursive: true });
+    const dest = path.join(process.env.PROOF_UPLOAD_DIR, name);
+    fs.copyFileSync(artifactPath, dest);
+    return dest;
+  }
+
+  return null;
+}
+
+async function publishDistribution() {
+  const args = parseArgs();
+  const epoch = Number(args.epoch);
+
+  if (!Number.isInteger(epoch)) {
+    printUsage();
+    process.exit(1);
+  }
+
+  const lst = getLstConfig(args.lst);
+  const rewardId = args.reward ?? `${lst.series}_EPOCH_${epoch}`;
+
+  const vaultAddr = process.env.SQUAD_VAULT_ADDRESS;
+  const programIdStr = process.env.MERKLE_PROGRAM_ID;
+  if (!vaultAddr || !programIdStr) {
+    throw new Error('SQUAD_VAULT_ADDRESS and MERKLE_PROGRAM_ID are required to draft the initialize transaction');
+  }
+  const authority = new PublicKey(vaultAddr);
+  const programId = new PublicKey(programIdStr);
+
+  const rpc = new FailoverConnection(getRpcConfigFromEnv());
+
+  console.log('📦 Distribution Publication\n');
+  console.log(`Epoch:  ${epoch}`);
+  console.log(`LST:    ${lst.symbol} (${lst.mint})`);
+  console.log(`Reward: ${rewardId}`);
+  console.log('-'.repeat(60));
+
+  // -----------------------------
+  // 1. Finalized snapshot
+  // -----------------------------
+  const snapshot = await pool.query<{ slot: string; holders: number }>(
+    `SELECT slot, holders FROM epoch_snapshots WHERE epoch = $1 AND mint = $2`,
+    [epoch, lst.mint]
+  );
+  if (snapshot.rows.length === 0) {
+    throw new Error(`No epoch snapshot for epoch ${epoch} (mint ${lst.mint})`);
+  }
+  const snapshotSlot = Number(snapshot.rows[0].slot);
+  const finalizedSlot = await rpc.execute(
+    (connection) => connection.getSlot('finalized'),
+    'getSlot'
+  );
+  if (snapshotSlot > finalizedSlot) {
+    throw new Error(`Snapshot slot ${snapshotSlot} is not finalized yet (finalized: ${finalizedSlot})`);
+  }
+  console.log(`✅ Snapshot at slot ${snapshotSlot} is finalized (${snapshot.rows[0].holders} holders)`);
+
+  // -----------------------------
+  // 2-3. Allocate, export, build
+  // -----------------------------
+  const allocationArgs = ['--epoch', String(epoch), '--lst', lst.symbol, '--reward', rewardId];
+  for (const flag of ALLOCATION_FLAGS) {
+    if (args[flag] !== undefined) {
+      allocationArgs.push(`--${flag}`, args[flag]);
+    }
+  }
+  runJob('allocate-epoch-reward.ts', allocationArgs);
+  runJob('export-reward-csv.ts', [rewardId]);
+
+  const csvPath = path.join('exports', `${rewardId}.csv`);
+  if (!fs.existsSync(csvPath)) {
+    throw new Error(`No payouts exported for ${rewardId}`);
+  }
+  runJob('build-merkle-distribution.ts', [csvPath]);
+
+  const artifactPath = path.join('distributions', `${rewardId}_merkle.json`);
+  const artifact = loadArtifact(artifactPath);
+  const validation = validateArtifact(artifact);
+  if (!validation.valid) {
+    console.error('❌ Invalid artifact:');
+    validation.errors.forEach((e) => console.error(`  - ${e}`));
+    process.exit(1);
+  }
+  const signaturePath = signaturePathFor(artifactPath);
+  const signed = fs.existsSync(signaturePath);
+
+  // -----------------------------
+  // 4. Proof set
+  // -----------------------------
+  const proofLocation = await uploadProofSet(artifact, artifactPath);
+  if (proofLocation) {
+    console.log(`\n✅ Proof set published: ${proofLocation}`);
+  } else {
+    console.log('\n⚠️  PROOF_UPLOAD_URL / PROOF_UPLOAD_DIR not set, proof set not uploaded');
+  }
+
+  // -----------------------------
+  // 5. Initialize draft
+  // -----------------------------
+  const { decimals } = await rpc.execute(
+    (connection) => getMint(connection, new PublicKey(artifact.mint)),
+    'getMint'
+  );
+  // Registry index as of now: drafts must be executed in creation order
+  const registryIndex = await rpc.execute(
+    (connection) => fetchRegistryCount(connection, programId),
+    'getRegistryCount'
+  );
+  const init = buildInitializeAndFund(artifact, {
+    programId,
+    authority,
+    registryIndex,
+    decimals,
+    clawbackDestination: process.env.CLAWBACK_DESTINATION
+      ? new PublicKey(process.env.CLAWBACK_DESTINATION)
+      : undefined,
+    requireRecipientSignature: process.env.REQUIRE_RECIPIENT_SIGNATURE === 'true',
+  });
+
+  const { blockhash } = await rpc.execute(
+    (connection) => connection.getLatestBlockhash('finalized'),
+    'getLatestBlockhash'
+  );
+  const transaction = new VersionedTransaction(
+    new TransactionMessage({
+      payerKey: authority,
+      recentBlockhash: blockhash,
+      instructions: init.instructions,
+    }).compileToV0Message()
+  );
+
+  const memo = `Initialize + Fund Merkle Distribution ${artifact.rewardId}`;
+  const draft = {
+    distributionId: artifact.distributionId,
+    rewardId: artifact.rewardId,
+    epoch,
+    snapshotSlot,
+    mint: artifact.mint,
+    merkleRoot: artifact.merkleRoot,
+    totalAmount: artifact.totalAmount,
+    numRecipients: artifact.numRecipients,
+    distribution: init.distribution.toBase58(),
+    vault: init.vault.toBase58(),
+    authority: authority.toBase58(),
+    clawbackDestination: init.clawbackDestination.toBase58(),
+    registryIndex: registryIndex.toString(),
+    artifactPath,
+    signaturePath: signed ? signaturePath : null,
+    proofSet: proofLocation,
+    memo,
+    // Squads vault transaction payload
+    instructions: init.instructions.map(serializeInstruction),
+    // Unsigned, for signers that take a raw transaction; the blockhash expires
+    unsignedTransaction: Buffer.from(transaction.serialize()).toString('base64'),
+    recentBlockhash: blockhash,
+    createdAt: new Date().toISOString(),
+  };
+
+  const draftPath = path.join('distributions', `${rewardId}_init_draft.json`);
+  fs.writeFileSync(draftPath, JSON.stringify(draft, null, 2));
+
+  console.log('');
+  console.log('-'.repeat(60));
+  console.log(`✅ Initialize draft saved: ${draftPath}`);
+  console.log(`   Distribution:   ${draft.distribution}`);
+  console.log(`   Merkle Root:    ${artifact.merkleRoot}`);
+  console.log(`   Registry Index: ${registryIndex}`);
+  console.log(`   Root Signature: ${signed ? signaturePath : 'none (OPS_KEYPAIR not set)'}`);
+
+  if (args.propose === 'true') {
+    runJob('init-merkle-distribution.ts', [artifactPath]);
+  } else {
+    console.log('');
+    console.log('Next: review the draft, then create the proposal with');
+    console.log(`  npx ts-node src/jobs/init-merkle-distribution.ts ${artifactPath}`);
+  }
+}
+
+publishDistribution()
+  .catch((e) => {
+    console.error('❌ Publication failed:', e);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
@@ -0,0 +1,44 @@
+// src/jobs/reconcile-distributions.ts
+// One-off reconciliation of on-chain distributions against the indexed claim events
+//
+//   npx ts-node src/jobs/reconcile-distributions.ts [--deployment <name>]   (default: every deployment)
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getDeploymentConfig, loadDeploymentConfigs } from '../config/deployments';
+import { runReconciliation } from '../indexers/reconcile';
+
+async function main() {
+  const i = process.argv.indexOf('--deployment');
+  const deployments = i >= 0 ? [getDeploymentConfig(process.argv[i + 1])] : loadDeploymentConfigs();
+
+  for (const deployment of deployments) {
+    console.log(`🔎 Reconciling distributions of ${deployment.name} (${deployment.cluster})\n`);
+
+    const result = await runReconciliation(deployment);
+
+    console.log(`Slot:          ${result.slot}`);
+    console.log(`Distributions: ${result.distributions}`);
+    console.log(`Resolved:      ${result.resolved}`);
+    console.log('');
+
+    if (result.divergences.length === 0) {
+      console.log('✅ On-chain state matches the indexed claim events\n');
+      continue;
+    }
+
+    console.log(`❌ ${result.divergences.length} divergence(s):`);
+    for (const d of result.divergences) {
+      console.log(`  ${d.distribution}  ${d.field.padEnd(14)} on-chain ${d.onChain}, expected ${d.expected}`);
+    }
+    console.log('');
+    process.exitCode = 1;
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Reconciliation failed:', e);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
@@ -0,0 +1,81 @@
+// src/jobs/record-epoch-budget.ts
+// Records an epoch's inflation rewards on an LST's stake pool as the default budget of its epoch reward
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { getLstConfig } from '../config/lsts';
+import { getStakePoolAdapter } from '../indexers/exchange-rate';
+import { recordValidatorRewards } from '../indexers/validator-rewards';
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      }
+    }
+  }
+  return args;
+}
+
+function printUsage() {
+  console.log('Usage: npx ts-node src/jobs/record-epoch-budget.ts [--lst <SYMBOL>] [--epoch <EPOCH>]');
+  console.log('');
+  console.log('Sums the inflation rewards paid to the LST\'s stake pool for an epoch (getInflationReward over');
+  console.log('its stake accounts, in batches) and records them per validator and as the epoch\'s reward budget.');
+  console.log('allocate-epoch-reward.ts splits that budget by default when the reward is paid in SOL.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --lst <SYMBOL>     LST whose stake pool to read (required when several are configured)');
+  console.log('  --epoch <EPOCH>    Epoch the rewards were earned in (default: the previous epoch)');
+}
+
+async function main() {
+  const args = parseArgs();
+  if (process.argv.includes('--help')) {
+    printUsage();
+    return;
+  }
+
+  const epoch = args.epoch !== undefined ? Number(args.epoch) : undefined;
+  if (epoch !== undefined && !Number.isInteger(epoch)) {
+    printUsage();
+    process.exit(1);
+  }
+
+  const lst = getLstConfig(args.lst);
+  if (!lst.stakePool) {
+    throw new Error(`${lst.symbol} has no stake pool configured`);
+  }
+  const adapter = getStakePoolAdapter(lst.stakePool.kind, lst.stakePool.address);
+
+  const result = await recordValidatorRewards(adapter, lst.mint, epoch);
+  if (result.skipped) {
+    const budget = await pool.query<{ reward_lamports: string }>(
+      `SELECT reward_lamports::text FROM epoch_reward_budgets WHERE epoch = $1 AND mint = $2`,
+      [result.epoch, lst.mint]
+    );
+    const recorded = budget.rows.length > 0 ? `, budget ${budget.rows[0].reward_lamports} lamports` : '';
+    console.log(`⏳ ${lst.symbol} epoch ${result.epoch} skipped: ${result.reason}${recorded}`);
+    return;
+  }
+
+  console.log(
+    `✅ ${lst.symbol} epoch ${result.epoch} budget: ${result.rewardLamports} lamports ` +
+      `from ${result.validators} validator(s)`
+  );
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Error:', e.message);
+    process.exit(1);
+  })
+  .finally(() => {
+    pool.end();
+  });
@@ -0,0 +1,100 @@
+// src/jobs/replay-dead-letters.ts
+// Lists, replays or discards dead-lettered claim event transactions
+//
+//   npm run replay-dead-letters -- [--deployment <name>] [--list] [--id <id,...>] [--limit <n>] [--discard]
+
+import 'dotenv/config';
+import { pool } from '../db';
+import { DeploymentConfig, getDeploymentConfig, loadDeploymentConfigs } from '../config/deployments';
+import { discardDeadLetters, listDeadLetters, replayDeadLetters } from '../indexers/dead-letters';
+
+function printUsage() {
+  console.log('Usage: npm run replay-dead-letters -- [options]');
+  console.log('');
+  console.log('Re-decodes and stores pending transactions from ingestion_dead_letters, e.g. after');
+  console.log('a decoder fix. Replayed ones are marked replayed; ones that fail again stay pending.');
+  console.log('');
+  console.log('Options:');
+  console.log('  --deployment <name>   Only this deployment (default: every deployment)');
+  console.log('  --list                List pending dead letters instead of replaying them');
+  console.log('  --id <id,...>         Only these dead letters');
+  console.log('  --limit <n>           Replay at most n per deployment, oldest slot first');
+  console.log('  --discard             Mark the --id dead letters discarded instead of replaying them');
+}
+
+// Parse command line arguments
+function parseArgs(): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 2; i < process.argv.length; i++) {
+    if (process.argv[i].startsWith('--')) {
+      const key = process.argv[i].slice(2);
+      const value = process.argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      } else {
+        args[key] = 'true';
+      }
+    }
+  }
+  return args;
+}
+
+function parseIds(value: string | undefined): number[] | undefined {
+  if (value === undefined) return undefined;
+  const ids = value.split(',').map((id) => Number(id.trim()));
+  if (ids.some((id) => !Number.isInteger(id) || id <= 0)) throw new Error(`Invalid --id: ${value}`);
+  return ids;
+}
+
+async function list(deployment: DeploymentConfig) {
+  const letters = await listDeadLetters(deployment.name);
+  console.log(`${deployment.name}: ${letters.length} pending`);
+  for (const l of letters) {
+    console.log(
+      `  #${l.id}  slot ${l.slot}  ${l.signature}  ${l.source}  ${l.stage} failed ` +
+        `(${l.attempts} attempt(s), last ${l.lastFailedAt.toISOString()}): ${l.error}`
+    );
+  }
+}
+
+async function main() {
+  const args = parseArgs();
+  if (args.help) {
+    printUsage();
+    return;
+  }
+
+  const ids = parseIds(args.id);
+  const limit = args.limit === undefined ? undefined : Number(args.limit);
+  if (limit !== undefined && !(Number.isInteger(limit) && limit > 0)) {
+    throw new Error(`Invalid --limit: ${args.limit}`);
+  }
+  if (args.discard && !ids) throw new Error('--discard needs --id');
+
+  const deployments = args.deployment ? [getDeploymentConfig(args.deployment)] : loadDeploymentConfigs();
+
+  for (const deployment of deployments) {
+    if (args.list) {
+      await list(deployment);
+      continue;
+    }
+
+    if (args.discard) {
+      const discarded = await discardDeadLetters(deployment.name, ids!);
+      console.log(`${deployment.name}: ${discarded} dead letter(s) discarded`);
+      continue;
+    }
+
+    const result = await replayDeadLetters(deployment, { ids, limit });
+    console.log(`${deployment.name}: ${result.replayed} replayed (${result.events} events stored), ${result.failed} still failing`);
+    if (result.failed > 0) process.exitCode = 1;
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error('❌ Dead letter replay failed:', e.message);
+    process.exit(1);
+  })
+  .finally(() => pool.end());
@@ -111,6 +111,7 @@ async function main() {
     retryDelayMs: parseInt(process.env.RELAYER_RETRY_DELAY || '2000', 10),
     computeUnitLimit: parseInt(process.env.RELAYER_COMPUTE_UNITS || '400000', 10),
     computeUnitPrice: parseInt(process.env.RELAYER_COMPUTE_PRICE || '1000', 10),
+    memo: process.env.RELAYER_MEMO,
   };
 
   const relayer = new MerkleRelayer(config);
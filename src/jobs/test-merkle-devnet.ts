@@ -36,7 +36,17 @@ import {
 
 import { pool } from '../db';
 import { loadArtifact, buildDistributionArtifact, saveArtifact, validateArtifact } from '../merkle/builder';
-import { getDistributionPda, getVaultPda, getClaimPda, buildClaimInstruction } from '../merkle/relayer';
+import {
+  getDistributionPda,
+  getVaultPda,
+  getClaimPda,
+  getRegistryPda,
+  getRegistryEntryPda,
+  getConfigPda,
+  fetchRegistryCount,
+  buildClaimInstruction,
+} from '../merkle/relayer';
+import { HASH_KIND_KECCAK, HashScheme, PROOF_MODE_SORTED, hashSchemeIndex } from '../merkle/types';
 import { getTokenByMint, fromRawAmount } from '../config/tokens';
 
 // ============================================================================
@@ -237,15 +247,19 @@ function buildInitializeInstruction(
   distribution: PublicKey,
   mint: PublicKey,
   vault: PublicKey,
+  registryIndex: bigint,
   distributionId: Buffer,
   merkleRoot: Buffer,
   totalAmount: bigint,
-  numRecipients: number
+  numRecipients: number,
+  clawbackDestination: PublicKey,
+  requireRecipientSignature: boolean,
+  hashScheme: HashScheme
 ): TransactionInstruction {
   // Anchor discriminator for "initialize"
   const discriminator = Buffer.from([175, 175, 109, 31, 13, 152, 155, 237]);
 
-  const data = Buffer.alloc(8 + 32 + 32 + 8 + 8);
+  const data = Buffer.alloc(8 + 32 + 32 + 8 + 8 + 32 + 1 + 1 + 1 + 1 + 1);
   let offset = 0;
 
   discriminator.copy(data, offset);
@@ -261,6 +275,27 @@ function buildInitializeInstruction(
   offset += 8;
 
   data.writeBigUInt64LE(BigInt(numRecipients), offset);
+  offset += 8;
+
+  clawbackDestination.toBuffer().copy(data, offset);
+  offset += 32;
+
+  data.writeUInt8(requireRecipientSignature ? 1 : 0, offset);
+  offset += 1;
+
+  data.writeUInt8(hashSchemeIndex(hashScheme), offset);
+  offset += 1;
+
+  // hash_kind: the TypeScript builder produces Keccak-256 trees
+  data.writeUInt8(HASH_KIND_KECCAK, offset);
+  offset += 1;
+
+  // proof_mode: the TypeScript builder produces sorted-pair proofs
+  data.writeUInt8(PROOF_MODE_SORTED, offset);
+  offset += 1;
+
+  // id_epoch: None (the builder derives its own distribution ids)
+  data.writeUInt8(0, offset);
 
   return new TransactionInstruction({
     programId,
@@ -269,6 +304,9 @@ function buildInitializeInstruction(
       { pubkey: distribution, isSigner: false, isWritable: true },
       { pubkey: mint, isSigner: false, isWritable: false },
       { pubkey: vault, isSigner: false, isWritable: true },
+      { pubkey: getRegistryPda(programId)[0], isSigner: false, isWritable: true },
+      { pubkey: getRegistryEntryPda(programId, registryIndex)[0], isSigner: false, isWritable: true },
+      { pubkey: getConfigPda(programId)[0], isSigner: false, isWritable: false },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
@@ -309,16 +347,22 @@ async function initializeDistribution(
     return { distributionPda, vaultPda };
   }
 
+  const registryIndex = await fetchRegistryCount(config.connection, config.programId);
+
   const initIx = buildInitializeInstruction(
     config.programId,
     config.payer.publicKey,
     distributionPda,
     mint,
     vaultPda,
+    registryIndex,
     distributionIdBuffer,
     merkleRootBuffer,
     totalAmount,
-    artifact.numRecipients
+    artifact.numRecipients,
+    getAssociatedTokenAddressSync(mint, config.payer.publicKey),
+    false,
+    artifact.hashScheme ?? 'v1'
   );
 
   const { blockhash } = await config.connection.getLatestBlockhash();
@@ -495,9 +539,11 @@ async function processClaims(
         distributionPda,
         claimPda,
         vaultPda,
+        mint,
         recipient,
         recipientAta,
         config.payer.publicKey,
+        config.payer.publicKey, // payer initialized the distribution, so it is the operator
         proof.index,
         BigInt(proof.amount),
         proof.proof
@@ -5,7 +5,7 @@ import { createHash } from 'crypto';
 import fs from 'fs';
 import path from 'path';
 import { buildMerkleData } from './tree';
-import { DistributionArtifact, PayoutEntry } from './types';
+import { DistributionArtifact, HashScheme, PayoutEntry } from './types';
 
 /**
  * Parse CSV file into payout entries
@@ -80,7 +80,10 @@ export function generateDistributionId(
 /**
  * Build complete distribution artifact from CSV
  */
-export function buildDistributionArtifact(csvPath: string): DistributionArtifact {
+export function buildDistributionArtifact(
+  csvPath: string,
+  hashScheme: HashScheme = 'v1'
+): DistributionArtifact {
   // Parse CSV
   const entries = parseCsv(csvPath);
 
@@ -106,7 +109,8 @@ export function buildDistributionArtifact(csvPath: string): DistributionArtifact
   // Build Merkle tree and proofs
   const { root, proofs } = buildMerkleData(
     distributionId,
-    entries.map((e) => ({ wallet: e.wallet, amount: e.amount }))
+    entries.map((e) => ({ wallet: e.wallet, amount: e.amount })),
+    hashScheme
   );
 
   // Hash source CSV for verification
@@ -120,6 +124,7 @@ export function buildDistributionArtifact(csvPath: string): DistributionArtifact
     totalAmount: totalAmount.toString(),
     merkleRoot: root,
     numRecipients: entries.length,
+    hashScheme,
     csvHash,
     proofs,
     createdAt: new Date().toISOString(),
@@ -149,6 +154,44 @@ export function loadArtifact(artifactPath: string): DistributionArtifact {
   return JSON.parse(content) as DistributionArtifact;
 }
 
+/**
+ * Read a build summary (artifact header without proofs) from stdin, as
+ * emitted by the Rust builder's `BuildSummary`
+ */
+export function readSummaryFromStdin(): DistributionArtifact {
+  return JSON.parse(fs.readFileSync(0, 'utf8')) as DistributionArtifact;
+}
+
+/**
+ * Validate a build summary: the fields initialize needs, without proofs
+ */
+export function validateSummary(summary: DistributionArtifact): {
+  valid: boolean;
+  errors: string[];
+} {
+  const errors: string[] = [];
+
+  if (!/^[0-9a-f]{64}$/.test(summary.distributionId ?? '')) {
+    errors.push('Missing or malformed distributionId (expected 64 hex chars)');
+  }
+  if (!/^[0-9a-f]{64}$/.test(summary.merkleRoot ?? '')) {
+    errors.push('Missing or malformed merkleRoot (expected 64 hex chars)');
+  }
+  if (!summary.mint) errors.push('Missing mint');
+  if (!summary.rewardId) errors.push('Missing rewardId');
+  if (!/^[0-9]+$/.test(summary.totalAmount ?? '') || BigInt(summary.totalAmount) === 0n) {
+    errors.push(`Invalid totalAmount: ${summary.totalAmount}`);
+  }
+  if (!Number.isInteger(summary.numRecipients) || summary.numRecipients <= 0) {
+    errors.push(`Invalid numRecipients: ${summary.numRecipients}`);
+  }
+
+  return {
+    valid: errors.length === 0,
+    errors,
+  };
+}
+
 /**
  * Validate artifact integrity
  */
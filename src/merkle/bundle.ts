@@ -0,0 +1,122 @@
+// src/merkle/bundle.ts
+// Decoder for compressed proof bundles written by the Rust builder
+
+import { PublicKey } from '@solana/web3.js';
+import { constructLeaf, hashPair } from './tree';
+import { MerkleProof } from './types';
+
+/**
+ * Bundle layout (little-endian), see crates/merkle-tree/src/bundle.rs:
+ * [magic (8)] [version (2)] [depth (1)] [leaf_count (8)] [distribution_id (32)] [root (32)]
+ * [records: (recipient (32), amount (8)) * leaf_count]
+ * [nodes (32 each) for levels 1..depth; leaves and root are omitted]
+ */
+const BUNDLE_MAGIC = Buffer.from('L33PRFB\0', 'latin1');
+const BUNDLE_VERSION = 1;
+const HEADER_LEN = 8 + 2 + 1 + 8 + 32 + 32;
+const RECORD_LEN = 40;
+const NODE_LEN = 32;
+
+/**
+ * Decoded proof bundle; proofs are reassembled on demand
+ */
+export class ProofBundle {
+  readonly distributionId: string; // hex-encoded
+  readonly merkleRoot: string; // hex-encoded
+  readonly numRecipients: number;
+
+  private readonly data: Buffer;
+  private readonly levels: { offset: number; len: number }[];
+
+  constructor(data: Buffer) {
+    if (data.length < HEADER_LEN || !data.subarray(0, 8).equals(BUNDLE_MAGIC)) {
+      throw new Error('Not a proof bundle (bad magic)');
+    }
+    const version = data.readUInt16LE(8);
+    if (version > BUNDLE_VERSION) {
+      throw new Error(`Bundle version ${version} is newer than supported version ${BUNDLE_VERSION}`);
+    }
+
+    const depth = data.readUInt8(10);
+    const leafCount = Number(data.readBigUInt64LE(11));
+
+    const levels: { offset: number; len: number }[] = [];
+    let offset = HEADER_LEN + leafCount * RECORD_LEN;
+    let len = Math.ceil(leafCount / 2);
+    for (let level = 1; level < depth; level++) {
+      levels.push({ offset, len });
+      offset += len * NODE_LEN;
+      len = Math.ceil(len / 2);
+    }
+    if (data.length !== offset) {
+      throw new Error(`Corrupt proof bundle: expected ${offset} bytes, got ${data.length}`);
+    }
+
+    this.data = data;
+    this.levels = levels;
+    this.distributionId = data.subarray(19, 51).toString('hex');
+    this.merkleRoot = data.subarray(51, 83).toString('hex');
+    this.numRecipients = leafCount;
+  }
+
+  /**
+   * Proof for the leaf at `index` (null if out of range)
+   */
+  getProof(index: number): MerkleProof | null {
+    if (!Number.isInteger(index) || index < 0 || index >= this.numRecipients) {
+      return null;
+    }
+
+    const { wallet, amount } = this.record(index);
+    const proof: Buffer[] = [];
+
+    if (this.numRecipients > 1) {
+      // Leaf siblings are recomputed from their records
+      // (no sibling: odd node at end of level pairs with itself)
+      const sibling = (index ^ 1) < this.numRecipients ? this.record(index ^ 1) : { wallet, amount };
+      proof.push(constructLeaf(this.distributionId, sibling.wallet, sibling.amount));
+    }
+
+    let current = Math.floor(index / 2);
+    for (const { offset, len } of this.levels) {
+      const sibling = current ^ 1;
+      const position = sibling < len ? sibling : current;
+      const start = offset + position * NODE_LEN;
+      proof.push(this.data.subarray(start, start + NODE_LEN));
+      current = Math.floor(current / 2);
+    }
+
+    return {
+      index,
+      wallet,
+      amount: amount.toString(),
+      proof: proof.map((p) => p.toString('hex')),
+    };
+  }
+
+  /**
+   * Recompute the root from a proof (sanity check after decoding)
+   */
+  verifyProof(proof: MerkleProof): boolean {
+    let computed = constructLeaf(this.distributionId, proof.wallet, BigInt(proof.amount));
+    for (const sibling of proof.proof) {
+      computed = hashPair(computed, Buffer.from(sibling, 'hex'));
+    }
+    return computed.toString('hex') === this.merkleRoot;
+  }
+
+  private record(position: number): { wallet: string; amount: bigint } {
+    const start = HEADER_LEN + position * RECORD_LEN;
+    return {
+      wallet: new PublicKey(this.data.subarray(start, start + 32)).toBase58(),
+      amount: this.data.readBigUInt64LE(start + 32),
+    };
+  }
+}
+
+/**
+ * Decode a proof bundle
+ */
+export function decodeProofBundle(data: Buffer): ProofBundle {
+  return new ProofBundle(data);
+}
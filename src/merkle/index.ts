@@ -5,4 +5,7 @@ export * from './types';
 export * from './tree';
 export * from './builder';
 export * from './relayer';
+export * from './bundle';
+export * from './signing';
 
+export * from './initialize';
@@ -0,0 +1,185 @@
+// src/merkle/initialize.ts
+// Initialize + fund instructions for a built distribution, shared by the
+// init job (Squads proposal) and the publication pipeline (unsigned draft)
+
+import {
+  PublicKey,
+  TransactionInstruction,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+} from '@solana/web3.js';
+import {
+  getAssociatedTokenAddressSync,
+  createTransferCheckedInstruction,
+  TOKEN_PROGRAM_ID,
+} from '@solana/spl-token';
+
+import { DistributionArtifact, HASH_KIND_KECCAK, HashScheme, PROOF_MODE_SORTED, hashSchemeIndex } from './types';
+import {
+  getDistributionPda,
+  getVaultPda,
+  getRegistryPda,
+  getRegistryEntryPda,
+  getConfigPda,
+} from './relayer';
+
+/**
+ * Build the initialize instruction for the Merkle distributor program
+ */
+export function buildInitializeInstruction(
+  programId: PublicKey,
+  authority: PublicKey,
+  distribution: PublicKey,
+  mint: PublicKey,
+  vault: PublicKey,
+  registryIndex: bigint,
+  distributionId: Buffer,
+  merkleRoot: Buffer,
+  totalAmount: bigint,
+  numRecipients: number,
+  clawbackDestination: PublicKey,
+  requireRecipientSignature: boolean,
+  hashScheme: HashScheme
+): TransactionInstruction {
+  // Anchor discriminator for "initialize"
+  const discriminator = Buffer.from([175, 175, 109, 31, 13, 152, 155, 237]);
+
+  // Encode instruction data
+  // [discriminator (8)] [distribution_id (32)] [merkle_root (32)] [total_amount (8)] [num_recipients (8)]
+  // [clawback_destination (32)] [require_recipient_signature (1)] [hash_scheme (1)]
+  // [hash_kind (1)] [proof_mode (1)] [id_epoch (1, None)]
+  const data = Buffer.alloc(8 + 32 + 32 + 8 + 8 + 32 + 1 + 1 + 1 + 1 + 1);
+  let offset = 0;
+
+  discriminator.copy(data, offset);
+  offset += 8;
+
+  distributionId.copy(data, offset);
+  offset += 32;
+
+  merkleRoot.copy(data, offset);
+  offset += 32;
+
+  data.writeBigUInt64LE(totalAmount, offset);
+  offset += 8;
+
+  data.writeBigUInt64LE(BigInt(numRecipients), offset);
+  offset += 8;
+
+  clawbackDestination.toBuffer().copy(data, offset);
+  offset += 32;
+
+  data.writeUInt8(requireRecipientSignature ? 1 : 0, offset);
+  offset += 1;
+
+  data.writeUInt8(hashSchemeIndex(hashScheme), offset);
+  offset += 1;
+
+  // hash_kind: the TypeScript builder produces Keccak-256 trees
+  data.writeUInt8(HASH_KIND_KECCAK, offset);
+  offset += 1;
+
+  // proof_mode: the TypeScript builder produces sorted-pair proofs
+  data.writeUInt8(PROOF_MODE_SORTED, offset);
+  offset += 1;
+
+  // id_epoch: None (the builder derives its own distribution ids)
+  data.writeUInt8(0, offset);
+
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: authority, isSigner: true, isWritable: true },
+      { pubkey: distribution, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: vault, isSigner: false, isWritable: true },
+      { pubkey: getRegistryPda(programId)[0], isSigner: false, isWritable: true },
+      { pubkey: getRegistryEntryPda(programId, registryIndex)[0], isSigner: false, isWritable: true },
+      { pubkey: getConfigPda(programId)[0], isSigner: false, isWritable: false },
+      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+    ],
+    data,
+  });
+}
+
+export interface InitializeAndFundParams {
+  programId: PublicKey;
+  /** Squad vault: signs initialize and holds the tokens that fund the vault */
+  authority: PublicKey;
+  /** Registry count when the instruction executes */
+  registryIndex: bigint;
+  decimals: number;
+  /** Defaults to the authority's ATA */
+  clawbackDestination?: PublicKey;
+  requireRecipientSignature: boolean;
+}
+
+export interface InitializeAndFund {
+  distribution: PublicKey;
+  vault: PublicKey;
+  sourceAta: PublicKey;
+  clawbackDestination: PublicKey;
+  /** initialize, then fund the vault with the distribution total */
+  instructions: TransactionInstruction[];
+}
+
+/**
+ * Initialize the distribution with the artifact's root and totals, then fund
+ * its vault from the authority's ATA
+ */
+export function buildInitializeAndFund(
+  artifact: DistributionArtifact,
+  params: InitializeAndFundParams
+): InitializeAndFund {
+  const mint = new PublicKey(artifact.mint);
+  const totalAmount = BigInt(artifact.totalAmount);
+  const distributionId = Buffer.from(artifact.distributionId, 'hex');
+
+  const [distribution] = getDistributionPda(params.programId, distributionId);
+  const [vault] = getVaultPda(params.programId, distributionId);
+  const sourceAta = getAssociatedTokenAddressSync(mint, params.authority, true);
+  const clawbackDestination = params.clawbackDestination ?? sourceAta;
+
+  const initIx = buildInitializeInstruction(
+    params.programId,
+    params.authority,
+    distribution,
+    mint,
+    vault,
+    params.registryIndex,
+    distributionId,
+    Buffer.from(artifact.merkleRoot, 'hex'),
+    totalAmount,
+    artifact.numRecipients,
+    clawbackDestination,
+    params.requireRecipientSignature,
+    artifact.hashScheme ?? 'v1'
+  );
+  const fundIx = createTransferCheckedInstruction(
+    sourceAta,
+    mint,
+    vault,
+    params.authority,
+    totalAmount,
+    params.decimals
+  );
+
+  return { distribution, vault, sourceAta, clawbackDestination, instructions: [initIx, fundIx] };
+}
+
+/**
+ * JSON form of an instruction (data base64), e.g. for a Squads proposal payload
+ */
+export function serializeInstruction(ix: TransactionInstruction): {
+  programId: string;
+  keys: { pubkey: string; isSigner: boolean; isWritable: boolean }[];
+  data: string;
+} {
+  return {
+    programId: ix.programId.toBase58(),
+    keys: ix.keys.map((k) => ({ pubkey: k.pubkey.toBase58(), isSigner: k.isSigner, isWritable: k.isWritable })),
+    data: ix.data.toString('base64'),
+  };
+}
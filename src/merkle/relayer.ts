@@ -13,10 +13,18 @@ import {
 import {
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountInstruction,
+  addExtraAccountMetasForExecute,
+  getMint,
+  getTransferHook,
   TOKEN_PROGRAM_ID,
 } from '@solana/spl-token';
 import { pool } from '../db';
-import { DistributionArtifact, MerkleProof, MERKLE_DISTRIBUTOR_PROGRAM_ID } from './types';
+import {
+  DistributionArtifact,
+  MerkleMultiProof,
+  MerkleProof,
+  MERKLE_DISTRIBUTOR_PROGRAM_ID,
+} from './types';
 
 /**
  * Relayer configuration
@@ -30,23 +38,37 @@ export interface RelayerConfig {
   retryDelayMs: number;
   computeUnitLimit: number;
   computeUnitPrice: number; // micro-lamports
+  memo?: string; // external reference attached to every claim (max 32 bytes)
 }
 
+/**
+ * SPL Memo program (v2)
+ */
+export const MEMO_PROGRAM_ID = new PublicKey('MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr');
+
 /**
  * Claim instruction data layout
  * [discriminator (8)] [index (8)] [amount (8)] [proof_len (4)] [proof (32 * len)]
+ * [memo_len (4)] [memo (memo_len)]
+ *
+ * Transfer-hook accounts (Token-2022) are appended as remaining accounts
+ * by the caller, see addExtraAccountMetasForExecute.
  */
 export function buildClaimInstruction(
   programId: PublicKey,
   distribution: PublicKey,
   claim: PublicKey,
   vault: PublicKey,
+  mint: PublicKey,
   recipient: PublicKey,
   recipientAta: PublicKey,
   payer: PublicKey,
+  operator: PublicKey,
   index: number,
   amount: bigint,
-  proof: string[]
+  proof: string[],
+  memo: Buffer = Buffer.alloc(0),
+  tokenProgram: PublicKey = TOKEN_PROGRAM_ID
 ): TransactionInstruction {
   // Anchor discriminator for "claim"
   const discriminator = Buffer.from([62, 198, 214, 193, 213, 159, 108, 210]);
@@ -55,7 +77,7 @@ export function buildClaimInstruction(
   const proofBuffers = proof.map((p) => Buffer.from(p, 'hex'));
   const proofLen = proofBuffers.length;
 
-  const data = Buffer.alloc(8 + 8 + 8 + 4 + proofLen * 32);
+  const data = Buffer.alloc(8 + 8 + 8 + 4 + proofLen * 32 + 4 + memo.length);
   let offset = 0;
 
   discriminator.copy(data, offset);
@@ -75,22 +97,124 @@ export function buildClaimInstruction(
     offset += 32;
   }
 
+  data.writeUInt32LE(memo.length, offset);
+  offset += 4;
+
+  memo.copy(data, offset);
+
   return new TransactionInstruction({
     programId,
     keys: [
       { pubkey: distribution, isSigner: false, isWritable: true },
       { pubkey: claim, isSigner: false, isWritable: true },
       { pubkey: vault, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
       { pubkey: recipient, isSigner: false, isWritable: false },
       { pubkey: recipientAta, isSigner: false, isWritable: true },
       { pubkey: payer, isSigner: true, isWritable: true },
-      { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: operator, isSigner: false, isWritable: true },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
       { pubkey: new PublicKey('11111111111111111111111111111111'), isSigner: false, isWritable: false },
+      // Optional account: program ID stands in for "none" when there is no memo
+      { pubkey: memo.length > 0 ? MEMO_PROGRAM_ID : programId, isSigner: false, isWritable: false },
     ],
     data,
   });
 }
 
+/**
+ * Batch claim instruction data layout
+ * [discriminator (8)] [claims_len (4)] [(index (8), amount (8)) * len]
+ * [proof_len (4)] [proof (32 * len)] [flags_len (4)] [flags (1 * len)]
+ *
+ * `claims` must follow `multiProof.indices` (tree order). Remaining accounts
+ * are [claim, recipient, recipientAta] per claim. The program rejects mints
+ * with a transfer hook; use buildClaimInstruction for those.
+ */
+export function buildClaimBatchInstruction(
+  programId: PublicKey,
+  distribution: PublicKey,
+  vault: PublicKey,
+  mint: PublicKey,
+  payer: PublicKey,
+  operator: PublicKey,
+  claims: Array<{ index: number; amount: bigint; recipient: PublicKey; recipientAta: PublicKey }>,
+  multiProof: MerkleMultiProof,
+  tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+): TransactionInstruction {
+  // Anchor discriminator for "claim_batch"
+  const discriminator = Buffer.from([114, 42, 123, 54, 28, 139, 93, 41]);
+
+  const { proof, proofFlags } = multiProof;
+  const data = Buffer.alloc(
+    8 + 4 + claims.length * 16 + 4 + proof.length * 32 + 4 + proofFlags.length
+  );
+  let offset = 0;
+
+  discriminator.copy(data, offset);
+  offset += 8;
+
+  data.writeUInt32LE(claims.length, offset);
+  offset += 4;
+
+  for (const claim of claims) {
+    data.writeBigUInt64LE(BigInt(claim.index), offset);
+    offset += 8;
+    data.writeBigUInt64LE(claim.amount, offset);
+    offset += 8;
+  }
+
+  data.writeUInt32LE(proof.length, offset);
+  offset += 4;
+
+  for (const proofNode of proof) {
+    proofNode.copy(data, offset);
+    offset += 32;
+  }
+
+  data.writeUInt32LE(proofFlags.length, offset);
+  offset += 4;
+
+  for (const flag of proofFlags) {
+    data.writeUInt8(flag ? 1 : 0, offset);
+    offset += 1;
+  }
+
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: distribution, isSigner: false, isWritable: true },
+      { pubkey: vault, isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: payer, isSigner: true, isWritable: true },
+      { pubkey: operator, isSigner: false, isWritable: true },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      { pubkey: new PublicKey('11111111111111111111111111111111'), isSigner: false, isWritable: false },
+      ...claims.flatMap((claim) => [
+        { pubkey: getClaimPda(programId, distribution, claim.index)[0], isSigner: false, isWritable: true },
+        { pubkey: claim.recipient, isSigner: false, isWritable: false },
+        { pubkey: claim.recipientAta, isSigner: false, isWritable: true },
+      ]),
+    ],
+    data,
+  });
+}
+
+/**
+ * Read the operator from a Distribution account
+ * Layout: [discriminator (8)] [authority (32)] [operator (32)] ...
+ */
+export async function fetchDistributionOperator(
+  connection: Connection,
+  distribution: PublicKey
+): Promise<PublicKey> {
+  const account = await connection.getAccountInfo(distribution);
+  if (!account) {
+    throw new Error(`Distribution not found: ${distribution.toBase58()}`);
+  }
+  return new PublicKey(account.data.subarray(8 + 32, 8 + 32 + 32));
+}
+
 /**
  * Derive distribution PDA
  */
@@ -134,6 +258,49 @@ export function getVaultPda(
   );
 }
 
+/**
+ * Derive global config PDA
+ */
+export function getConfigPda(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([Buffer.from('config')], programId);
+}
+
+/**
+ * Derive global registry PDA
+ */
+export function getRegistryPda(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync([Buffer.from('registry')], programId);
+}
+
+/**
+ * Derive registry entry PDA for a registry index
+ */
+export function getRegistryEntryPda(
+  programId: PublicKey,
+  index: bigint
+): [PublicKey, number] {
+  const indexBuffer = Buffer.alloc(8);
+  indexBuffer.writeBigUInt64LE(index);
+
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('registry_entry'), indexBuffer],
+    programId
+  );
+}
+
+/**
+ * Read the number of registered distributions (0 if the registry does not exist yet)
+ * Layout: [discriminator (8)] [count (8)] [bump (1)]
+ */
+export async function fetchRegistryCount(
+  connection: Connection,
+  programId: PublicKey
+): Promise<bigint> {
+  const [registry] = getRegistryPda(programId);
+  const account = await connection.getAccountInfo(registry);
+  return account ? account.data.readBigUInt64LE(8) : 0n;
+}
+
 /**
  * Batch claims processor
  */
@@ -161,6 +328,18 @@ export class MerkleRelayer {
       distributionIdBuffer
     );
 
+    // Mint owner decides between SPL Token and Token-2022
+    const mintAccount = await this.config.connection.getAccountInfo(mint);
+    if (!mintAccount) {
+      throw new Error(`Mint not found: ${mint.toBase58()}`);
+    }
+    const tokenProgram = mintAccount.owner;
+    const mintInfo = await getMint(this.config.connection, mint, 'confirmed', tokenProgram);
+    const transferHookProgram = getTransferHook(mintInfo)?.programId ?? null;
+
+    // Claims not submitted by the operator pay it the claim fee
+    const operator = await fetchDistributionOperator(this.config.connection, distributionPda);
+
     // Get pending claims from database, matched with artifact proofs
     const pendingClaims = await this.getPendingClaims(artifact.distributionId, artifact);
 
@@ -189,7 +368,10 @@ export class MerkleRelayer {
           batch,
           distributionPda,
           vaultPda,
-          mint
+          mint,
+          tokenProgram,
+          transferHookProgram,
+          operator
         );
 
         processed += result.processed;
@@ -217,7 +399,10 @@ export class MerkleRelayer {
     claims: MerkleProof[],
     distributionPda: PublicKey,
     vaultPda: PublicKey,
-    mint: PublicKey
+    mint: PublicKey,
+    tokenProgram: PublicKey,
+    transferHookProgram: PublicKey | null,
+    operator: PublicKey
   ): Promise<{ processed: number; failed: number; skipped: number }> {
     const instructions: TransactionInstruction[] = [];
     const claimsToProcess: MerkleProof[] = [];
@@ -255,7 +440,7 @@ export class MerkleRelayer {
       }
 
       // Get or create recipient ATA
-      const recipientAta = getAssociatedTokenAddressSync(mint, recipient, true);
+      const recipientAta = getAssociatedTokenAddressSync(mint, recipient, true, tokenProgram);
       const ataInfo = await this.config.connection.getAccountInfo(recipientAta);
 
       if (!ataInfo) {
@@ -264,26 +449,46 @@ export class MerkleRelayer {
             this.config.payer.publicKey,
             recipientAta,
             recipient,
-            mint
+            mint,
+            tokenProgram
           )
         );
       }
 
       // Add claim instruction
-      instructions.push(
-        buildClaimInstruction(
-          this.config.programId,
-          distributionPda,
-          claimPda,
+      const claimIx = buildClaimInstruction(
+        this.config.programId,
+        distributionPda,
+        claimPda,
+        vaultPda,
+        mint,
+        recipient,
+        recipientAta,
+        this.config.payer.publicKey,
+        operator,
+        claim.index,
+        BigInt(claim.amount),
+        claim.proof,
+        Buffer.from(this.config.memo || ''),
+        tokenProgram
+      );
+
+      // Resolve transfer-hook extra accounts (vault -> recipient, signed by distribution PDA)
+      if (transferHookProgram) {
+        await addExtraAccountMetasForExecute(
+          this.config.connection,
+          claimIx,
+          transferHookProgram,
           vaultPda,
-          recipient,
+          mint,
           recipientAta,
-          this.config.payer.publicKey,
-          claim.index,
+          distributionPda,
           BigInt(claim.amount),
-          claim.proof
-        )
-      );
+          'confirmed'
+        );
+      }
+
+      instructions.push(claimIx);
 
       claimsToProcess.push(claim);
     }
@@ -0,0 +1,125 @@
+// src/merkle/signing.ts
+// Detached ed25519 root signatures by the operations key
+
+import { createPrivateKey, createPublicKey, sign, verify } from 'crypto';
+import fs from 'fs';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import { DistributionArtifact } from './types';
+
+/**
+ * Signed message, see crates/merkle-tree/src/signing.rs:
+ * ["L33_ROOT_SIG_V1"] [distribution_id (32)] [merkle_root (32)] [total_amount (8, LE)] [num_recipients (8, LE)]
+ */
+const ROOT_SIGNATURE_DOMAIN = Buffer.from('L33_ROOT_SIG_V1');
+
+/**
+ * Detached signature file, written next to the artifact / build summary
+ */
+export interface RootSignature {
+  distributionId: string; // hex-encoded
+  merkleRoot: string; // hex-encoded
+  totalAmount: string; // stringified bigint
+  numRecipients: number;
+  signer: string; // base58 ops public key
+  signature: string; // hex-encoded 64-byte ed25519 signature
+}
+
+type SignedFields = Pick<
+  DistributionArtifact,
+  'distributionId' | 'merkleRoot' | 'totalAmount' | 'numRecipients'
+>;
+
+/**
+ * Bytes signed for a distribution
+ */
+export function rootSignatureMessage(fields: SignedFields): Buffer {
+  const amounts = Buffer.alloc(16);
+  amounts.writeBigUInt64LE(BigInt(fields.totalAmount), 0);
+  amounts.writeBigUInt64LE(BigInt(fields.numRecipients), 8);
+
+  return Buffer.concat([
+    ROOT_SIGNATURE_DOMAIN,
+    Buffer.from(fields.distributionId, 'hex'),
+    Buffer.from(fields.merkleRoot, 'hex'),
+    amounts,
+  ]);
+}
+
+/**
+ * Default signature path for an artifact: `<artifact>.sig.json`
+ */
+export function signaturePathFor(artifactPath: string): string {
+  return artifactPath.replace(/\.json$/, '') + '.sig.json';
+}
+
+/**
+ * Sign an artifact's root with the operations keypair
+ */
+export function signRoot(artifact: SignedFields, opsKeypair: Keypair): RootSignature {
+  const key = createPrivateKey({
+    key: {
+      kty: 'OKP',
+      crv: 'Ed25519',
+      d: Buffer.from(opsKeypair.secretKey.subarray(0, 32)).toString('base64url'),
+      x: opsKeypair.publicKey.toBuffer().toString('base64url'),
+    },
+    format: 'jwk',
+  });
+
+  return {
+    distributionId: artifact.distributionId,
+    merkleRoot: artifact.merkleRoot,
+    totalAmount: artifact.totalAmount,
+    numRecipients: artifact.numRecipients,
+    signer: opsKeypair.publicKey.toBase58(),
+    signature: sign(null, rootSignatureMessage(artifact), key).toString('hex'),
+  };
+}
+
+/**
+ * Verify a root signature against the artifact it is meant to cover
+ *
+ * The signature must be by `expectedSigner` and its signed values must be
+ * exactly the artifact's. Run before building the initialize transaction.
+ */
+export function verifyRootSignature(
+  artifact: SignedFields,
+  signature: RootSignature,
+  expectedSigner: PublicKey
+): { valid: boolean; errors: string[] } {
+  const errors: string[] = [];
+
+  if (signature.signer !== expectedSigner.toBase58()) {
+    errors.push(`Signed by ${signature.signer}, expected ${expectedSigner.toBase58()}`);
+  }
+
+  const fields: (keyof SignedFields)[] = ['distributionId', 'merkleRoot', 'totalAmount', 'numRecipients'];
+  for (const field of fields) {
+    if (String(signature[field]) !== String(artifact[field])) {
+      errors.push(`${field} differs: signed ${signature[field]}, artifact has ${artifact[field]}`);
+    }
+  }
+
+  if (errors.length === 0) {
+    const key = createPublicKey({
+      key: { kty: 'OKP', crv: 'Ed25519', x: expectedSigner.toBuffer().toString('base64url') },
+      format: 'jwk',
+    });
+    const valid =
+      /^[0-9a-f]{128}$/.test(signature.signature) &&
+      verify(null, rootSignatureMessage(artifact), key, Buffer.from(signature.signature, 'hex'));
+    if (!valid) errors.push('Signature does not verify');
+  }
+
+  return {
+    valid: errors.length === 0,
+    errors,
+  };
+}
+
+/**
+ * Load a signature file
+ */
+export function loadRootSignature(signaturePath: string): RootSignature {
+  return JSON.parse(fs.readFileSync(signaturePath, 'utf8')) as RootSignature;
+}
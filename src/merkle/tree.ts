@@ -3,7 +3,15 @@
 
 import { keccak256 } from 'js-sha3';
 import { PublicKey } from '@solana/web3.js';
-import { DOMAIN_SEPARATOR, MerkleLeaf, MerkleProof } from './types';
+import {
+  DOMAIN_SEPARATOR,
+  HashScheme,
+  LEAF_PREFIX,
+  MerkleLeaf,
+  MerkleMultiProof,
+  MerkleProof,
+  NODE_PREFIX,
+} from './types';
 
 /**
  * Hash function for Merkle tree nodes
@@ -16,13 +24,18 @@ export function hash(data: Buffer): Buffer {
 /**
  * Hash two child nodes to produce parent
  * Sorts nodes before hashing for deterministic tree construction
+ * (v2 prefixes internal nodes with 0x01)
  */
-export function hashPair(left: Buffer, right: Buffer): Buffer {
+export function hashPair(left: Buffer, right: Buffer, scheme: HashScheme = 'v1'): Buffer {
   // Sort to ensure deterministic ordering
   const [first, second] = Buffer.compare(left, right) <= 0
     ? [left, right]
     : [right, left];
 
+  if (scheme === 'v2') {
+    return hash(Buffer.concat([Buffer.from([NODE_PREFIX]), first, second]));
+  }
+
   return hash(Buffer.concat([first, second]));
 }
 
@@ -30,6 +43,7 @@ export function hashPair(left: Buffer, right: Buffer): Buffer {
  * Construct a leaf for the Merkle tree
  * 
  * leaf = hash(domain_separator || distribution_id || recipient || amount)
+ * (v2: hash(0x00 || domain_separator || ...))
  * 
  * This provides:
  * - Domain separation (prevents cross-program attacks)
@@ -41,7 +55,8 @@ export function hashPair(left: Buffer, right: Buffer): Buffer {
 export function constructLeaf(
   distributionId: string,
   wallet: string,
-  amount: bigint
+  amount: bigint,
+  scheme: HashScheme = 'v1'
 ): Buffer {
   const walletPubkey = new PublicKey(wallet);
 
@@ -53,6 +68,7 @@ export function constructLeaf(
   const distributionIdBuffer = Buffer.from(distributionId, 'hex');
 
   const data = Buffer.concat([
+    Buffer.from(scheme === 'v2' ? [LEAF_PREFIX] : []),  // Leaf prefix (v2 only)
     Buffer.from(DOMAIN_SEPARATOR),  // Domain separator as UTF-8 bytes
     distributionIdBuffer,            // Distribution ID as raw bytes (32 bytes)
     walletPubkey.toBuffer(),         // Recipient pubkey (32 bytes)
@@ -68,13 +84,15 @@ export function constructLeaf(
 export class MerkleTree {
   private leaves: Buffer[];
   private layers: Buffer[][];
+  private scheme: HashScheme;
 
-  constructor(leaves: Buffer[]) {
+  constructor(leaves: Buffer[], scheme: HashScheme = 'v1') {
     if (leaves.length === 0) {
       throw new Error('Cannot create Merkle tree with no leaves');
     }
 
     this.leaves = leaves;
+    this.scheme = scheme;
     this.layers = this.buildLayers();
   }
 
@@ -91,10 +109,10 @@ export class MerkleTree {
       for (let i = 0; i < currentLayer.length; i += 2) {
         if (i + 1 < currentLayer.length) {
           // Hash pair of nodes
-          nextLayer.push(hashPair(currentLayer[i], currentLayer[i + 1]));
+          nextLayer.push(hashPair(currentLayer[i], currentLayer[i + 1], this.scheme));
         } else {
           // Odd node - promote to next level (duplicate for pairing)
-          nextLayer.push(hashPair(currentLayer[i], currentLayer[i]));
+          nextLayer.push(hashPair(currentLayer[i], currentLayer[i], this.scheme));
         }
       }
 
@@ -147,6 +165,60 @@ export class MerkleTree {
     return proof;
   }
 
+  /**
+   * Generate a multiproof for several leaves
+   *
+   * Matches the on-chain verify_multiproof(): leaves are returned in tree
+   * order, and each flag says whether the second input of a hash step comes
+   * from the leaf/hash queue (true) or from `proof` (false).
+   */
+  getMultiProof(indices: number[]): MerkleMultiProof {
+    const sorted = [...new Set(indices)].sort((a, b) => a - b);
+    if (sorted.length === 0) {
+      throw new Error('Multiproof requires at least one leaf');
+    }
+    for (const index of sorted) {
+      if (index < 0 || index >= this.leaves.length) {
+        throw new Error(`Invalid leaf index: ${index}`);
+      }
+    }
+
+    const proof: Buffer[] = [];
+    const proofFlags: boolean[] = [];
+    let known = sorted;
+
+    for (let i = 0; i < this.layers.length - 1; i++) {
+      const layer = this.layers[i];
+      const parents: number[] = [];
+
+      for (let j = 0; j < known.length; j++) {
+        const index = known[j];
+        const siblingIndex = index % 2 === 1 ? index - 1 : index + 1;
+
+        if (index % 2 === 0 && known[j + 1] === siblingIndex) {
+          // Both children are known - hash them from the queue
+          proofFlags.push(true);
+          j++;
+        } else {
+          // Sibling comes from the proof (odd node pairs with itself)
+          proof.push(siblingIndex < layer.length ? layer[siblingIndex] : layer[index]);
+          proofFlags.push(false);
+        }
+
+        parents.push(Math.floor(index / 2));
+      }
+
+      known = parents;
+    }
+
+    return {
+      indices: sorted,
+      leaves: sorted.map((index) => this.leaves[index]),
+      proof,
+      proofFlags,
+    };
+  }
+
   /**
    * Get proof as hex strings
    */
@@ -160,29 +232,69 @@ export class MerkleTree {
   static verify(
     root: Buffer,
     leaf: Buffer,
-    proof: Buffer[]
+    proof: Buffer[],
+    scheme: HashScheme = 'v1'
   ): boolean {
     let current = leaf;
 
     for (const sibling of proof) {
-      current = hashPair(current, sibling);
+      current = hashPair(current, sibling, scheme);
     }
 
     return Buffer.compare(current, root) === 0;
   }
 
+  /**
+   * Verify a multiproof (mirrors on-chain verify_multiproof)
+   */
+  static verifyMultiProof(
+    root: Buffer,
+    multiProof: MerkleMultiProof,
+    scheme: HashScheme = 'v1'
+  ): boolean {
+    const { leaves, proof, proofFlags } = multiProof;
+    if (leaves.length === 0 || leaves.length + proof.length !== proofFlags.length + 1) {
+      return false;
+    }
+    if (proofFlags.length === 0) {
+      return Buffer.compare(leaves[0], root) === 0;
+    }
+
+    const queue = [...leaves];
+    let queuePos = 0;
+    let proofPos = 0;
+
+    for (const flag of proofFlags) {
+      const a = queue[queuePos++];
+      const b = flag ? queue[queuePos++] : proof[proofPos++];
+      if (a === undefined || b === undefined) {
+        return false;
+      }
+      queue.push(hashPair(a, b, scheme));
+    }
+
+    // Every input must be consumed exactly once
+    if (queuePos !== queue.length - 1 || proofPos !== proof.length) {
+      return false;
+    }
+
+    return Buffer.compare(queue[queue.length - 1], root) === 0;
+  }
+
   /**
    * Verify a proof with hex inputs
    */
   static verifyHex(
     rootHex: string,
     leafHex: string,
-    proofHex: string[]
+    proofHex: string[],
+    scheme: HashScheme = 'v1'
   ): boolean {
     return MerkleTree.verify(
       Buffer.from(rootHex, 'hex'),
       Buffer.from(leafHex, 'hex'),
-      proofHex.map((p) => Buffer.from(p, 'hex'))
+      proofHex.map((p) => Buffer.from(p, 'hex')),
+      scheme
     );
   }
 }
@@ -192,7 +304,8 @@ export class MerkleTree {
  */
 export function buildMerkleData(
   distributionId: string,
-  entries: Array<{ wallet: string; amount: bigint }>
+  entries: Array<{ wallet: string; amount: bigint }>,
+  scheme: HashScheme = 'v1'
 ): {
   tree: MerkleTree;
   leaves: MerkleLeaf[];
@@ -204,11 +317,11 @@ export function buildMerkleData(
     index,
     wallet: entry.wallet,
     amount: entry.amount,
-    leaf: constructLeaf(distributionId, entry.wallet, entry.amount),
+    leaf: constructLeaf(distributionId, entry.wallet, entry.amount, scheme),
   }));
 
   // Build tree
-  const tree = new MerkleTree(leaves.map((l) => l.leaf));
+  const tree = new MerkleTree(leaves.map((l) => l.leaf), scheme);
   const root = tree.getRootHex();
 
   // Generate proofs for all leaves
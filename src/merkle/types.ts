@@ -34,6 +34,16 @@ export interface MerkleProof {
   proof: string[]; // hex-encoded proof nodes
 }
 
+/**
+ * Multiproof for several leaves (input to the batch claim instruction)
+ */
+export interface MerkleMultiProof {
+  indices: number[]; // leaf indices in tree order
+  leaves: Buffer[];
+  proof: Buffer[]; // shared sibling nodes
+  proofFlags: boolean[]; // per hash step: second input from queue (true) or proof (false)
+}
+
 /**
  * Complete distribution artifact
  * This is the output of the Merkle builder and input to multisig + relayer
@@ -51,6 +61,7 @@ export interface DistributionArtifact {
   // Merkle data
   merkleRoot: string; // hex-encoded
   numRecipients: number;
+  hashScheme?: HashScheme; // absent in legacy artifacts (v1)
 
   // Verification
   csvHash: string; // SHA-256 of source CSV
@@ -137,3 +148,33 @@ export const MERKLE_DISTRIBUTOR_PROGRAM_ID = new PublicKey(
  */
 export const DOMAIN_SEPARATOR = 'L33_MERKLE_V1';
 
+/**
+ * Merkle node hashing scheme (matches on-chain HashScheme)
+ * - v1: unprefixed leaves and internal nodes (legacy)
+ * - v2: leaves prefixed with 0x00, internal nodes with 0x01
+ */
+export type HashScheme = 'v1' | 'v2';
+
+/**
+ * Hash prefixes for the v2 scheme (second-preimage resistance)
+ */
+export const LEAF_PREFIX = 0x00;
+export const NODE_PREFIX = 0x01;
+
+/**
+ * Borsh enum index of the on-chain HashKind::Keccak
+ */
+export const HASH_KIND_KECCAK = 0;
+
+/**
+ * Borsh enum index of the on-chain ProofMode::Sorted
+ */
+export const PROOF_MODE_SORTED = 0;
+
+/**
+ * Borsh enum index of a hash scheme, as stored on-chain
+ */
+export function hashSchemeIndex(scheme: HashScheme = 'v1'): number {
+  return scheme === 'v2' ? 1 : 0;
+}
+
@@ -0,0 +1,269 @@
+// src/runners/claim-event-runner.ts
+// Long-running ingestion of distributor claim events
+//
+//   npm run claim-events                              stream every deployment (default)
+//   npm run claim-events -- --deployment <name>       stream one deployment
+//   npm run claim-events -- backfill [opts]           re-index a window and exit (--help for options)
+//
+// Deployments come from DEPLOYMENTS_CONFIG (src/config/deployments.ts), or
+// the single `default` deployment configured by SOLANA_RPC_URL,
+// MERKLE_PROGRAM_ID and GEYSER_ENDPOINT.
+
+import 'dotenv/config';
+import { PublicKey } from '@solana/web3.js';
+import { pool } from '../db';
+import { FailoverConnection } from '../utils/rpc';
+import {
+  claimEventsProcessed,
+  ingestionLagSlots,
+  lastIngestedSlot,
+  registerClaimProgressMetrics,
+  startMetricsServer,
+} from '../utils/metrics';
+import { finalizeStagedEvents, getClaimEventsCommitmentFromEnv, ProgramTransaction } from '../indexers/claim-events';
+import { streamProgramTransactions } from '../indexers/geyser';
+import { deliverDueWebhooks } from '../indexers/webhooks';
+import { loadWebhookConfigs } from '../config/webhooks';
+import { DeploymentConfig, getDeploymentConfig, loadDeploymentConfigs } from '../config/deployments';
+import { checkIngestionLag } from '../alerts/rules';
+import { streamProgramTransactionsViaLogs } from '../indexers/logs-subscribe';
+import {
+  IngestionCheckpoint,
+  loadCheckpoint,
+  replaySince,
+  saveCheckpoint,
+  saveIngestionLag,
+} from '../indexers/checkpoints';
+import { backfillClaimEvents, backfillOptionsFromArgs, printBackfillUsage } from '../indexers/backfill';
+import { migrateOnStartup } from '../utils/migrations';
+import { readiness } from '../utils/health';
+import { SlotTimeMapper } from '../indexers/slot-times';
+import { ingestTransaction } from '../indexers/dead-letters';
+
+// How often staged (not yet finalized) events are promoted or rolled back
+const FINALIZE_INTERVAL_MS = 15_000;
+
+// How often ingestion lag is measured against the chain
+const LAG_INTERVAL_MS = 15_000;
+
+// How often due webhook deliveries are sent
+const WEBHOOK_INTERVAL_MS = 5_000;
+
+// How often the ingestion checkpoint is saved; a restart replays at most this much twice
+const CHECKPOINT_INTERVAL_MS = 5_000;
+
+const MAX_REPLAY_RETRY_DELAY_MS = 30_000;
+
+async function main() {
+  const args = parseArgs(process.argv.slice(2));
+  // Fail on a bad DEPLOYMENTS_CONFIG or WEBHOOKS_CONFIG before anything is ingested
+  const deployments = args.deployment ? [getDeploymentConfig(args.deployment)] : loadDeploymentConfigs();
+  const webhooks = loadWebhookConfigs();
+
+  await migrateOnStartup();
+
+  startMetricsServer(readiness);
+  registerClaimProgressMetrics(pool);
+
+  if (webhooks.length > 0) {
+    console.log(`Webhooks: ${webhooks.map((w) => w.name).join(', ')}`);
+    let dispatching = false;
+    setInterval(async () => {
+      if (dispatching) return;
+      dispatching = true;
+      try {
+        await deliverDueWebhooks();
+      } catch (error: any) {
+        console.error('Webhook dispatch failed:', error.message);
+      } finally {
+        dispatching = false;
+      }
+    }, WEBHOOK_INTERVAL_MS);
+  }
+
+  // Deployments are ingested side by side; one failing stops the process
+  await Promise.all(deployments.map(ingest));
+}
+
+/**
+ * Stream one deployment's claim events, with its own checkpoint,
+ * finalization and lag measurement against its cluster
+ */
+async function ingest(deployment: DeploymentConfig): Promise<never> {
+  const { name, programId } = deployment;
+  const commitment = getClaimEventsCommitmentFromEnv();
+  const rpc = new FailoverConnection(deployment.rpc);
+  const log = (message: string) => console.log(`[${name}] ${message}`);
+  const logError = (message: string, error: any) => console.error(`[${name}] ${message}`, error.message);
+
+  // Geyser when configured, logsSubscribe over the RPC websocket otherwise
+  const geyser = deployment.geyser;
+  const source = geyser ? 'geyser' : 'websocket';
+  if (!geyser && commitment === 'processed') {
+    throw new Error(`${name}: logsSubscribe ingestion needs CLAIM_EVENTS_COMMITMENT=confirmed or finalized`);
+  }
+
+  // Newest transaction ingested, whether or not it carried events. Resumes
+  // from the checkpoint saved by the previous run.
+  let checkpoint: IngestionCheckpoint | null = await loadCheckpoint(name, source, programId);
+  let savedCheckpoint = checkpoint;
+  let newestSlot = checkpoint?.slot ?? 0;
+
+  const context = { deployment: name, programId, commitment, source, slotTimes: new SlotTimeMapper(name, rpc) };
+
+  // A dead-lettered transaction counts as handled: it is replayed from
+  // ingestion_dead_letters, not from the stream
+  const handle = async (tx: ProgramTransaction) => {
+    const events = (await ingestTransaction(tx, context)) ?? [];
+
+    if (tx.slot >= newestSlot) {
+      newestSlot = tx.slot;
+      checkpoint = { slot: tx.slot, signature: tx.signature };
+    }
+    lastIngestedSlot.set({ deployment: name, source }, newestSlot);
+    if (events.length === 0) return;
+
+    for (const e of events) {
+      claimEventsProcessed.inc({ deployment: name, kind: e.kind, commitment });
+      const detail = e.leafIndex !== null ? ` index=${e.leafIndex}` : '';
+      log(`[${tx.slot}] ${e.kind} ${e.distribution}${detail} ${tx.signature}`);
+    }
+  };
+
+  // Everything up to the checkpoint has been handled: transactions are
+  // handed on in slot order (logsSubscribe gap repairs aside), and replays
+  // run before streamed ones
+  setInterval(async () => {
+    if (!checkpoint || checkpoint === savedCheckpoint) return;
+    const saving = checkpoint;
+    try {
+      await saveCheckpoint(name, source, programId, saving);
+      savedCheckpoint = saving;
+    } catch (error: any) {
+      logError('Checkpoint save failed:', error);
+    }
+  }, CHECKPOINT_INTERVAL_MS);
+
+  // Replay from the checkpoint to the subscription start, retrying until it
+  // succeeds; streamed transactions queue behind it
+  const resume = async () => {
+    const from = checkpoint;
+    if (!from) {
+      log('No ingestion checkpoint, starting at the tip');
+      return;
+    }
+    for (let attempt = 1; ; attempt++) {
+      try {
+        const replayed = await replaySince(rpc, programId, from, commitment, handle);
+        log(`Resumed from slot ${from.slot}: ${replayed} transaction(s) replayed`);
+        return;
+      } catch (error: any) {
+        const delay = Math.min(1000 * 2 ** (attempt - 1), MAX_REPLAY_RETRY_DELAY_MS);
+        logError(`Replay from slot ${from.slot} failed (retrying in ${delay}ms):`, error);
+        await new Promise((resolve) => setTimeout(resolve, delay));
+      }
+    }
+  };
+
+  let finalizing = false;
+  setInterval(async () => {
+    if (finalizing) return;
+    finalizing = true;
+    try {
+      const result = await finalizeStagedEvents(name, rpc);
+      if (result.promoted > 0 || result.rolledBack > 0) {
+        log(`Finalized ${result.promoted} transaction(s), rolled back ${result.rolledBack}, ${result.pending} pending`);
+      }
+    } catch (error: any) {
+      logError('Finalization failed:', error);
+    } finally {
+      finalizing = false;
+    }
+  }, FINALIZE_INTERVAL_MS);
+
+  // Lag: newest program transaction on chain vs. newest ingested. An idle
+  // program (or one without history) reads 0; without a checkpoint the
+  // stream starts at the tip, so the first check does too. Saved for
+  // readiness checks of other processes.
+  setInterval(async () => {
+    try {
+      const [newest] = await rpc.execute(
+        (connection) =>
+          connection.getSignaturesForAddress(
+            new PublicKey(programId),
+            { limit: 1 },
+            commitment === 'finalized' ? 'finalized' : 'confirmed'
+          ),
+        'getNewestProgramSignature'
+      );
+      if (newest && newestSlot === 0) newestSlot = newest.slot;
+      const lag = newest ? Math.max(0, newest.slot - newestSlot) : 0;
+      ingestionLagSlots.set({ deployment: name, source }, lag);
+      await saveIngestionLag(name, source, lag);
+      await checkIngestionLag(name, source, lag);
+    } catch (error: any) {
+      logError('Lag check failed:', error);
+    }
+  }, LAG_INTERVAL_MS);
+
+  if (geyser) {
+    log(`Ingesting claim events for ${programId} (${deployment.cluster}) from Geyser ${geyser.endpoint}`);
+    return streamProgramTransactions(geyser, programId, handle, resume);
+  }
+
+  log(`Ingesting claim events for ${programId} (${deployment.cluster}) via logsSubscribe (no Geyser endpoint)`);
+  return streamProgramTransactionsViaLogs(rpc, programId, commitment, handle, resume, deployment.wsUrl);
+}
+
+// Parse `--key value` arguments following the subcommand
+function parseArgs(argv: string[]): Record<string, string> {
+  const args: Record<string, string> = {};
+  for (let i = 0; i < argv.length; i++) {
+    if (argv[i].startsWith('--')) {
+      const key = argv[i].slice(2);
+      const value = argv[i + 1];
+      if (value && !value.startsWith('--')) {
+        args[key] = value;
+        i++;
+      } else {
+        args[key] = 'true';
+      }
+    }
+  }
+  return args;
+}
+
+/**
+ * `backfill` subcommand: re-fetch and re-decode one window, then exit
+ */
+async function backfill() {
+  const args = parseArgs(process.argv.slice(3));
+  if (args.help) {
+    printBackfillUsage('npm run claim-events -- backfill');
+    return;
+  }
+
+  try {
+    const deployment = getDeploymentConfig(args.deployment);
+    const result = await backfillClaimEvents(backfillOptionsFromArgs(args, deployment));
+    console.log(`✅ Backfill complete: ${result.transactions} transactions fetched, ${result.events} events stored`);
+    console.log(`   ${result.stamped} stored event(s) stamped with a missing block time`);
+    if (result.deadLettered > 0) {
+      console.log(`⚠️  ${result.deadLettered} transaction(s) dead-lettered, see npm run replay-dead-letters`);
+    }
+  } finally {
+    await pool.end();
+  }
+}
+
+// Options of the default (streaming) command start with --
+const command = process.argv[2]?.startsWith('--') ? undefined : process.argv[2];
+if (command !== undefined && command !== 'backfill') {
+  console.error(`❌ Unknown subcommand: ${command} (expected backfill)`);
+  process.exit(1);
+}
+
+(command === 'backfill' ? backfill() : main()).catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
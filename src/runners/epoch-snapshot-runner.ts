@@ -0,0 +1,58 @@
+// src/runners/epoch-snapshot-runner.ts
+import { loadLstConfigs } from '../config/lsts';
+import { runLstEpoch } from '../indexers/lst-pipeline';
+import { buildPendingHolderDiffs } from '../indexers/holder-diffs';
+import { pool } from '../db';
+import { migrateOnStartup } from '../utils/migrations';
+
+async function main() {
+  const lsts = loadLstConfigs();
+  await migrateOnStartup();
+  const results = await Promise.allSettled(lsts.map((lst) => runLstEpoch(lst)));
+
+  let failed = 0;
+  for (const [i, result] of results.entries()) {
+    const symbol = lsts[i].symbol;
+    if (result.status === 'rejected') {
+      failed++;
+      console.error(`[${symbol}] failed:`, result.reason);
+      continue;
+    }
+
+    const { snapshot, rate, validatorRewards } = result.value;
+    if (snapshot.skipped) {
+      console.log(`[${symbol}] Epoch ${snapshot.epoch} already snapshotted at slot ${snapshot.slot}`);
+    }
+    if (!rate) {
+      console.log(`[${symbol}] No stake pool configured: exchange rate not recorded`);
+    } else if (rate.skipped) {
+      console.log(`[${symbol}] Epoch ${rate.epoch} exchange rate skipped: ${rate.reason}`);
+    }
+    if (validatorRewards?.skipped) {
+      console.log(`[${symbol}] Epoch ${validatorRewards.epoch} validator rewards skipped: ${validatorRewards.reason}`);
+    }
+
+    try {
+      for (const diff of await buildPendingHolderDiffs(lsts[i].mint)) {
+        const { entered, exited } = diff.cohorts;
+        console.log(`[${symbol}] Holder diff ${diff.fromEpoch} -> ${diff.toEpoch}: ${entered} entered, ${exited} exited`);
+      }
+    } catch (e) {
+      failed++;
+      console.error(`[${symbol}] holder diff failed:`, e);
+    }
+  }
+
+  if (failed > 0) {
+    throw new Error(`${failed} of ${lsts.length} LST pipeline(s) failed`);
+  }
+}
+
+main()
+  .catch((e) => {
+    console.error(e);
+    process.exit(1);
+  })
+  .finally(() => {
+    pool.end();
+  });
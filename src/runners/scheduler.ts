@@ -2,24 +2,94 @@
 // Runs snapshots every 6 hours, then processes weights automatically
 
 import cron from 'node-cron';
+import { PublicKey } from '@solana/web3.js';
 import { runSnapshot } from '../indexers/snapshot';
+import { loadLstConfigs } from '../config/lsts';
+import { loadDeploymentConfigs } from '../config/deployments';
+import { runLstEpoch } from '../indexers/lst-pipeline';
+import { runTwabSample } from '../indexers/twab';
+import { buildPendingHolderDiffs } from '../indexers/holder-diffs';
+import { DIVERGENCE_FIELDS, runReconciliation } from '../indexers/reconcile';
+import { runAlertRules } from '../alerts/rules';
+import {
+  reconciliationDivergences,
+  reconciliationLastRun,
+  snapshotDuration,
+  startMetricsServer,
+} from '../utils/metrics';
 import { classifyWallets } from '../jobs/classify-wallets';
 import { materializeWeights } from '../jobs/materialize-weights';
+import { migrateOnStartup } from '../utils/migrations';
+import { getRetentionConfigFromEnv, runRetention } from '../utils/retention';
 
 console.log('='.repeat(60));
 console.log('LST Rewards Scheduler');
 console.log('Schedule: Snapshots every 6 hours (0, 6, 12, 18 UTC)');
 console.log('Post-snapshot: Classify wallets + Materialize weights');
+console.log('Epoch snapshots + exchange rates: per LST schedule, taken once per epoch');
+console.log('Post-epoch-snapshot: Holder diff against the previous snapshot');
+
+// On-chain distributions vs. indexed claim events
+const reconcileSchedule = process.env.RECONCILE_SCHEDULE || '*/15 * * * *';
+if (!cron.validate(reconcileSchedule)) {
+  throw new Error(`Invalid RECONCILE_SCHEDULE "${reconcileSchedule}"`);
+}
+console.log(`Reconciliation: ${reconcileSchedule}`);
+
+// Anomaly alerts (Slack / Discord)
+const alertSchedule = process.env.ALERT_SCHEDULE || '* * * * *';
+if (!cron.validate(alertSchedule)) {
+  throw new Error(`Invalid ALERT_SCHEDULE "${alertSchedule}"`);
+}
+console.log(`Alert rules: ${alertSchedule}`);
+
+// Pruning of raw ingestion payloads and caches
+const retentionSchedule = process.env.RETENTION_SCHEDULE || '30 3 * * *';
+if (!cron.validate(retentionSchedule)) {
+  throw new Error(`Invalid RETENTION_SCHEDULE "${retentionSchedule}"`);
+}
+const retention = getRetentionConfigFromEnv();
+const retentionPolicies = retention.policies.map((p) => `${p.name} ${p.days ? `${p.days}d` : 'kept'}`).join(', ');
+console.log(`Retention: ${retentionSchedule} (${retentionPolicies}; vacuum ${retention.vacuum})`);
+
+const lsts = loadLstConfigs();
+for (const lst of lsts) {
+  if (!cron.validate(lst.schedule)) {
+    throw new Error(`${lst.symbol}: invalid schedule "${lst.schedule}"`);
+  }
+  if (lst.twabSchedule && !cron.validate(lst.twabSchedule)) {
+    throw new Error(`${lst.symbol}: invalid TWAB schedule "${lst.twabSchedule}"`);
+  }
+  console.log(`  ${lst.symbol.padEnd(10)} ${lst.mint}  ${lst.schedule}  ${lst.stakePool?.kind ?? 'no stake pool'}`);
+  if (lst.twabSchedule) {
+    console.log(`  ${''.padEnd(10)} TWAB samples: ${lst.twabSchedule}`);
+  }
+}
 console.log('='.repeat(60));
 
+startMetricsServer();
+
+// Every scheduled task waits for pending schema migrations; a failed
+// migration stops the scheduler
+const schemaReady = migrateOnStartup().catch((e) => {
+  console.error('❌ Schema migration failed:', e.message);
+  process.exit(1);
+});
+
 async function runScheduledTasks() {
+  await schemaReady;
   const startTime = new Date();
   console.log(`\n[${startTime.toISOString()}] Starting scheduled tasks...`);
 
   // Step 1: Take snapshot (critical - must succeed)
   console.log('\n📸 Step 1: Taking snapshot...');
+  const endSnapshotTimer = snapshotDuration.startTimer({
+    lst: process.env.PRIMARY_TOKEN_SYMBOL || 'LST',
+    kind: 'window',
+  });
   try {
     await runSnapshot();
+    endSnapshotTimer();
     console.log('✅ Snapshot complete');
   } catch (e) {
     console.error('❌ Snapshot failed:', e);
@@ -60,6 +130,142 @@ cron.schedule('0 */6 * * *', async () => {
   await runScheduledTasks();
 });
 
+// Epoch-boundary pipelines, one per LST: a no-op until a new epoch starts.
+// Each LST runs on its own schedule; a run is skipped while the previous one
+// for the same LST is still going.
+for (const lst of lsts) {
+  let running = false;
+  cron.schedule(lst.schedule, async () => {
+    if (running) return;
+    running = true;
+    await schemaReady;
+    const endTimer = snapshotDuration.startTimer({ lst: lst.symbol, kind: 'epoch' });
+    try {
+      const { snapshot, rate } = await runLstEpoch(lst);
+      // Only runs that took a snapshot are timed; the others are a quick no-op
+      if (!snapshot.skipped) endTimer();
+      if (!snapshot.skipped) {
+        console.log(`✅ [${lst.symbol}] Epoch ${snapshot.epoch} snapshot at slot ${snapshot.slot} (${snapshot.holders} holders)`);
+        // Holder diff against the previous snapshot, once its positions are recorded too
+        try {
+          for (const diff of await buildPendingHolderDiffs(lst.mint)) {
+            const { entered, exited } = diff.cohorts;
+            console.log(
+              `✅ [${lst.symbol}] Holder diff ${diff.fromEpoch} → ${diff.toEpoch}: ${entered} entered, ${exited} exited`
+            );
+          }
+        } catch (e) {
+          console.error(`⚠️  [${lst.symbol}] Holder diff failed (non-critical, retried after the next snapshot):`, e);
+        }
+      }
+      if (rate?.skipped && rate.reason !== 'already recorded') {
+        console.log(`⏳ [${lst.symbol}] Epoch ${rate.epoch} exchange rate not recorded yet: ${rate.reason}`);
+      }
+    } catch (e) {
+      console.error(`❌ [${lst.symbol}] Epoch pipeline failed (retrying on next schedule):`, e);
+    } finally {
+      running = false;
+    }
+  });
+}
+
+// Intra-epoch balance samples for TWAB weighting, for LSTs that configure them
+for (const lst of lsts) {
+  if (!lst.twabSchedule) continue;
+  let running = false;
+  cron.schedule(lst.twabSchedule, async () => {
+    if (running) return;
+    running = true;
+    await schemaReady;
+    const endTimer = snapshotDuration.startTimer({ lst: lst.symbol, kind: 'twab' });
+    try {
+      const sample = await runTwabSample(new PublicKey(lst.mint));
+      endTimer();
+      console.log(`✅ [${lst.symbol}] TWAB sample at slot ${sample.slot} (epoch ${sample.epoch}, ${sample.holders} holders)`);
+    } catch (e) {
+      console.error(`❌ [${lst.symbol}] TWAB sample failed (retrying on next schedule):`, e);
+    } finally {
+      running = false;
+    }
+  });
+}
+
+// Reconciliation: divergences are recorded every run, alerted on once they
+// outlive RECONCILE_ALERT_AFTER_MINUTES. Each deployment is reconciled
+// against its own cluster; one failing doesn't hold up the others.
+let reconciling = false;
+cron.schedule(reconcileSchedule, async () => {
+  if (reconciling) return;
+  reconciling = true;
+  await schemaReady;
+  try {
+    for (const deployment of loadDeploymentConfigs()) {
+      const tag = `[reconcile ${deployment.name}]`;
+      try {
+        const result = await runReconciliation(deployment);
+        for (const field of DIVERGENCE_FIELDS) {
+          reconciliationDivergences.set({ deployment: deployment.name, field }, 0);
+        }
+        for (const d of result.divergences) {
+          reconciliationDivergences.inc({ deployment: deployment.name, field: d.field });
+        }
+        for (const d of result.alerted) {
+          console.error(
+            `❌ ${tag} ${d.distribution} ${d.field} diverged: on-chain ${d.onChain}, expected ${d.expected} (slot ${result.slot})`
+          );
+        }
+        if (result.resolved > 0) {
+          console.log(`✅ ${tag} ${result.resolved} divergence(s) resolved`);
+        }
+      } catch (e) {
+        console.error(`❌ ${tag} Reconciliation failed (retrying on next schedule):`, e);
+      }
+    }
+    reconciliationLastRun.setToCurrentTime();
+  } catch (e) {
+    console.error('❌ [reconcile] Reconciliation failed (retrying on next schedule):', e);
+  } finally {
+    reconciling = false;
+  }
+});
+
+// Retention: raw payloads and caches past their policy are pruned, then the
+// tables they were pruned from vacuumed. Derived rows are never touched.
+let pruning = false;
+cron.schedule(retentionSchedule, async () => {
+  if (pruning) return;
+  pruning = true;
+  await schemaReady;
+  try {
+    const { pruned, vacuumed } = await runRetention(retention);
+    for (const p of pruned) {
+      if (p.rows > 0) console.log(`✅ [retention] ${p.policy}: ${p.rows} row(s) pruned from ${p.table}`);
+    }
+    for (const v of vacuumed) {
+      const reclaimed = Math.max(0, v.bytesBefore - v.bytesAfter);
+      console.log(`✅ [retention] Vacuumed ${v.table}: ${reclaimed} bytes reclaimed (${v.bytesAfter} bytes now)`);
+    }
+  } catch (e) {
+    console.error('❌ [retention] Retention failed (retrying on next schedule):', e);
+  } finally {
+    pruning = false;
+  }
+});
+
+// Alert rules over the indexed state (paused, clawback, claim failures,
+// reconciliation); ingestion lag is checked by the claim event runner
+let alerting = false;
+cron.schedule(alertSchedule, async () => {
+  if (alerting) return;
+  alerting = true;
+  await schemaReady;
+  try {
+    await runAlertRules();
+  } finally {
+    alerting = false;
+  }
+});
+
 // Uncomment for testing (runs every minute):
 // cron.schedule('* * * * *', async () => {
 //   await runScheduledTasks();
@@ -0,0 +1,108 @@
+// src/utils/health.ts
+// Liveness and readiness checks shared by the API and the metrics endpoint
+
+import { pool } from '../db';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// About two minutes of slots
+const DEFAULT_MAX_LAG_SLOTS = 300;
+
+// Lag is measured every 15 seconds; older measurements mean no ingester is running
+const DEFAULT_MAX_LAG_AGE_SECONDS = 120;
+
+// A database that doesn't answer within this is as good as down
+const DB_TIMEOUT_MS = 2_000;
+
+function envNumber(name: string, fallback: number): number {
+  const value = process.env[name];
+  if (!value) return fallback;
+  const n = Number(value);
+  if (!Number.isFinite(n) || n < 0) throw new Error(`Invalid ${name}: ${value}`);
+  return n;
+}
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export interface HealthCheck {
+  name: string;
+  ok: boolean;
+  detail: string;
+}
+
+export interface Readiness {
+  ready: boolean;
+  checks: HealthCheck[];
+}
+
+// ---------------------------
+// Checks
+// ---------------------------
+
+async function checkDatabase(): Promise<HealthCheck> {
+  let timer: NodeJS.Timeout | undefined;
+  const timeout = new Promise<never>((_, reject) => {
+    timer = setTimeout(() => reject(new Error(`no answer within ${DB_TIMEOUT_MS}ms`)), DB_TIMEOUT_MS);
+  });
+  try {
+    await Promise.race([pool.query('SELECT 1'), timeout]);
+    return { name: 'database', ok: true, detail: 'connected' };
+  } catch (error: any) {
+    return { name: 'database', ok: false, detail: error.message };
+  } finally {
+    clearTimeout(timer);
+  }
+}
+
+/**
+ * One check per deployment: its freshest lag measurement (any source) must
+ * be recent and at most READINESS_MAX_LAG_SLOTS
+ *
+ * Deployments are those with a measurement in `ingestion_lag`; delete the
+ * rows of a deployment that is no longer indexed.
+ */
+async function checkIngestionLag(): Promise<HealthCheck[]> {
+  const maxLag = envNumber('READINESS_MAX_LAG_SLOTS', DEFAULT_MAX_LAG_SLOTS);
+  const maxAge = envNumber('READINESS_MAX_LAG_AGE_SECONDS', DEFAULT_MAX_LAG_AGE_SECONDS);
+
+  const { rows } = await pool.query<{ deployment: string; source: string; lag_slots: string; age: number }>(
+    `
+    SELECT DISTINCT ON (deployment)
+      deployment, source, lag_slots, EXTRACT(EPOCH FROM NOW() - measured_at)::float8 AS age
+    FROM ingestion_lag
+    ORDER BY deployment, measured_at DESC
+    `
+  );
+
+  return rows.map((row) => {
+    const name = `ingestion_lag:${row.deployment}`;
+    const lag = Number(row.lag_slots);
+    if (row.age > maxAge) {
+      return { name, ok: false, detail: `last measured ${Math.round(row.age)}s ago (${row.source})` };
+    }
+    return { name, ok: lag <= maxLag, detail: `${lag} slots behind (${row.source}, max ${maxLag})` };
+  });
+}
+
+/**
+ * Ready to serve indexed state: the database answers and every deployment's
+ * ingestion is within the lag threshold
+ */
+export async function readiness(): Promise<Readiness> {
+  const database = await checkDatabase();
+  if (!database.ok) return { ready: false, checks: [database] };
+
+  let lag: HealthCheck[];
+  try {
+    lag = await checkIngestionLag();
+  } catch (error: any) {
+    lag = [{ name: 'ingestion_lag', ok: false, detail: error.message }];
+  }
+
+  const checks = [database, ...lag];
+  return { ready: checks.every((c) => c.ok), checks };
+}
@@ -0,0 +1,228 @@
+// src/utils/metrics.ts
+// Prometheus metrics for the indexer processes
+
+import http from 'http';
+import { Pool } from 'pg';
+import client from 'prom-client';
+import type { Readiness } from './health';
+
+// ---------------------------
+// Registry
+// ---------------------------
+export const registry = new client.Registry();
+client.collectDefaultMetrics({ register: registry, prefix: 'lst_indexer_' });
+
+// ---------------------------
+// Metrics
+// ---------------------------
+
+export const rpcRequests = new client.Counter({
+  name: 'lst_indexer_rpc_requests_total',
+  help: 'RPC attempts by operation and result (error includes retried attempts)',
+  labelNames: ['operation', 'result'] as const,
+  registers: [registry],
+});
+
+export const claimEventsProcessed = new client.Counter({
+  name: 'lst_indexer_claim_events_total',
+  help: 'Claim events decoded and stored, by deployment, kind and commitment',
+  labelNames: ['deployment', 'kind', 'commitment'] as const,
+  registers: [registry],
+});
+
+export const ingestionLagSlots = new client.Gauge({
+  name: 'lst_indexer_ingestion_lag_slots',
+  help: 'Slots between the newest distributor transaction on chain and the newest one ingested',
+  labelNames: ['deployment', 'source'] as const,
+  registers: [registry],
+});
+
+export const lastIngestedSlot = new client.Gauge({
+  name: 'lst_indexer_last_ingested_slot',
+  help: 'Slot of the newest distributor transaction ingested',
+  labelNames: ['deployment', 'source'] as const,
+  registers: [registry],
+});
+
+export const blockTimeLookups = new client.Counter({
+  name: 'lst_indexer_block_time_lookups_total',
+  help: 'Block times resolved for indexed slots, by deployment and result (reported, cached, rpc, interpolated, missing)',
+  labelNames: ['deployment', 'result'] as const,
+  registers: [registry],
+});
+
+export const deadLetters = new client.Counter({
+  name: 'lst_indexer_dead_letters_total',
+  help: 'Distributor transactions dead-lettered, by deployment and the stage that failed (decode, persist)',
+  labelNames: ['deployment', 'stage'] as const,
+  registers: [registry],
+});
+
+export const snapshotDuration = new client.Histogram({
+  name: 'lst_indexer_snapshot_duration_seconds',
+  help: 'Duration of snapshot runs by LST and kind (epoch pipeline, twab sample, window)',
+  labelNames: ['lst', 'kind'] as const,
+  buckets: [1, 5, 15, 30, 60, 120, 300, 600, 1200],
+  registers: [registry],
+});
+
+export const webhookDeliveries = new client.Counter({
+  name: 'lst_webhook_deliveries_total',
+  help: 'Webhook delivery attempts by webhook and result (retry: failed, will be retried)',
+  labelNames: ['webhook', 'result'] as const,
+  registers: [registry],
+});
+
+export const reconciliationDivergences = new client.Gauge({
+  name: 'lst_reconciliation_divergences',
+  help: 'Distributions whose on-chain state diverged from the indexed claim events in the last reconciliation, by deployment and field',
+  labelNames: ['deployment', 'field'] as const,
+  registers: [registry],
+});
+
+export const reconciliationLastRun = new client.Gauge({
+  name: 'lst_reconciliation_last_run_timestamp_seconds',
+  help: 'Unix time of the last completed reconciliation',
+  registers: [registry],
+});
+
+export const retentionRowsPruned = new client.Counter({
+  name: 'lst_retention_rows_pruned_total',
+  help: 'Rows pruned (payload cleared or row deleted) by the retention job, by table',
+  labelNames: ['table'] as const,
+  registers: [registry],
+});
+
+export const retentionReclaimedBytes = new client.Counter({
+  name: 'lst_retention_reclaimed_bytes_total',
+  help: 'Drop in table size (with indexes and TOAST) across the vacuums after pruning, by table',
+  labelNames: ['table'] as const,
+  registers: [registry],
+});
+
+export const retentionTableBytes = new client.Gauge({
+  name: 'lst_retention_table_bytes',
+  help: 'Size of each table with a retention policy (with indexes and TOAST) after the last retention run',
+  labelNames: ['table'] as const,
+  registers: [registry],
+});
+
+export const retentionLastRun = new client.Gauge({
+  name: 'lst_retention_last_run_timestamp_seconds',
+  help: 'Unix time of the last completed retention run',
+  registers: [registry],
+});
+
+/**
+ * Claim progress of every funded or active distribution, read from the
+ * database on each scrape
+ *
+ * Only registered by processes that call this, so a fleet exports each
+ * distribution's gauges once.
+ */
+export function registerClaimProgressMetrics(pool: Pool): void {
+  const labelNames = ['deployment', 'distribution', 'reward'] as const;
+  const claimed = new client.Gauge({
+    name: 'lst_distribution_claimed_amount',
+    help: 'Raw units claimed (confirmed claims)',
+    labelNames,
+    registers: [registry],
+  });
+  const total = new client.Gauge({
+    name: 'lst_distribution_total_amount',
+    help: 'Raw units allocated by the distribution',
+    labelNames,
+    registers: [registry],
+  });
+  const claimedRecipients = new client.Gauge({
+    name: 'lst_distribution_claimed_recipients',
+    help: 'Leaves claimed (confirmed claims)',
+    labelNames,
+    registers: [registry],
+  });
+  new client.Gauge({
+    name: 'lst_distribution_recipients',
+    help: 'Leaves in the distribution',
+    labelNames,
+    registers: [registry],
+    async collect() {
+      const { rows } = await pool.query<{
+        deployment: string;
+        distribution_id: string;
+        reward_id: string;
+        total_amount: string;
+        num_recipients: number;
+        claimed_amount: string;
+        claimed_count: number;
+      }>(`
+        SELECT
+          d.deployment,
+          d.distribution_id,
+          d.reward_id,
+          d.total_amount::text,
+          d.num_recipients,
+          COALESCE(SUM(c.amount) FILTER (WHERE c.status = 'confirmed'), 0)::text AS claimed_amount,
+          COUNT(*) FILTER (WHERE c.status = 'confirmed')::int AS claimed_count
+        FROM merkle_distributions d
+        LEFT JOIN merkle_claims c ON c.distribution_id = d.distribution_id
+        WHERE d.status IN ('funded', 'active')
+        GROUP BY d.distribution_id
+      `);
+
+      this.reset();
+      claimed.reset();
+      total.reset();
+      claimedRecipients.reset();
+      for (const row of rows) {
+        const labels = { deployment: row.deployment, distribution: row.distribution_id, reward: row.reward_id };
+        this.set(labels, row.num_recipients);
+        claimedRecipients.set(labels, row.claimed_count);
+        // Gauges are doubles; exact below 2^53 raw units
+        claimed.set(labels, Number(row.claimed_amount));
+        total.set(labels, Number(row.total_amount));
+      }
+    },
+  });
+}
+
+// ---------------------------
+// Endpoint
+// ---------------------------
+
+/**
+ * Serve the registry at /metrics on METRICS_PORT (no-op if unset), with
+ * probes: /healthz answers while the process runs, /readyz with `readiness`
+ * (200 when ready, 503 otherwise; always ready without it)
+ */
+export function startMetricsServer(readiness?: () => Promise<Readiness>): void {
+  const port = process.env.METRICS_PORT;
+  if (!port) return;
+
+  http
+    .createServer(async (req, res) => {
+      if (req.url === '/healthz') {
+        res.writeHead(200, { 'Content-Type': 'application/json' }).end(JSON.stringify({ status: 'ok' }));
+        return;
+      }
+      if (req.url === '/readyz') {
+        const result = readiness ? await readiness() : { ready: true, checks: [] };
+        res
+          .writeHead(result.ready ? 200 : 503, { 'Content-Type': 'application/json' })
+          .end(JSON.stringify({ status: result.ready ? 'ready' : 'not_ready', checks: result.checks }));
+        return;
+      }
+      if (req.url !== '/metrics') {
+        res.writeHead(404).end();
+        return;
+      }
+      try {
+        const body = await registry.metrics();
+        res.writeHead(200, { 'Content-Type': registry.contentType }).end(body);
+      } catch (error: any) {
+        res.writeHead(500).end(error.message);
+      }
+    })
+    .listen(Number(port), () => {
+      console.log(`Metrics on :${port}/metrics`);
+    });
+}
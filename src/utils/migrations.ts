@@ -0,0 +1,239 @@
+// src/utils/migrations.ts
+// Versioned schema migrations from db/migrations, applied at startup and by `npm run migrate`
+
+import crypto from 'crypto';
+import fs from 'fs';
+import path from 'path';
+import { PoolClient } from 'pg';
+import { pool } from '../db';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// <repo>/db/migrations, from src/utils (ts-node) and dist/utils alike
+const MIGRATIONS_DIR = path.resolve(__dirname, '../../db/migrations');
+
+// <version>_<name>.up.sql, optionally with a matching .down.sql
+const FILE_PATTERN = /^(\d+)_([a-z0-9_]+)\.(up|down)\.sql$/;
+
+// Serializes runners starting together (pg_advisory_lock key)
+const LOCK_KEY = 7_340_019;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export interface Migration {
+  version: number;
+  name: string;
+  up: string;
+  down: string | null;
+  /** SHA-256 of the up script with line endings normalized */
+  checksum: string;
+}
+
+export interface AppliedMigration {
+  version: number;
+  name: string;
+  checksum: string;
+  appliedAt: Date;
+}
+
+export interface MigrationStatus {
+  applied: AppliedMigration[];
+  pending: Migration[];
+  /** Applied, but the up script has changed since */
+  modified: Migration[];
+  /** Applied, but no longer in db/migrations (the database is ahead of this checkout) */
+  missing: AppliedMigration[];
+}
+
+// ---------------------------
+// Loading
+// ---------------------------
+
+function checksum(sql: string): string {
+  return crypto.createHash('sha256').update(sql.replace(/\r\n/g, '\n')).digest('hex');
+}
+
+/**
+ * Read the migrations in `dir`, ordered by version
+ */
+export function loadMigrations(dir: string = MIGRATIONS_DIR): Migration[] {
+  const byVersion = new Map<number, { name: string; up?: string; down?: string }>();
+
+  for (const file of fs.readdirSync(dir).sort()) {
+    const m = file.match(FILE_PATTERN);
+    if (!m) {
+      if (file.endsWith('.sql')) throw new Error(`Migration ${file} does not match <version>_<name>.up.sql / .down.sql`);
+      continue;
+    }
+    const version = Number(m[1]);
+    const entry = byVersion.get(version) ?? { name: m[2] };
+    if (entry.name !== m[2]) {
+      throw new Error(`Migration version ${version} is used by both ${entry.name} and ${m[2]}`);
+    }
+    entry[m[3] as 'up' | 'down'] = fs.readFileSync(path.join(dir, file), 'utf8');
+    byVersion.set(version, entry);
+  }
+
+  return [...byVersion.entries()]
+    .sort(([a], [b]) => a - b)
+    .map(([version, { name, up, down }]) => {
+      if (up === undefined) throw new Error(`Migration ${version}_${name} has a down script but no up script`);
+      return { version, name, up, down: down ?? null, checksum: checksum(up) };
+    });
+}
+
+// ---------------------------
+// Status
+// ---------------------------
+
+async function ensureTable(client: PoolClient): Promise<void> {
+  await client.query(`
+    CREATE TABLE IF NOT EXISTS schema_migrations (
+      version INTEGER PRIMARY KEY,
+      name TEXT NOT NULL,
+      checksum TEXT NOT NULL,
+      applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    )
+  `);
+}
+
+async function readStatus(client: PoolClient, migrations: Migration[]): Promise<MigrationStatus> {
+  const { rows: table } = await client.query<{ exists: boolean }>(
+    `SELECT to_regclass('schema_migrations') IS NOT NULL AS exists`
+  );
+  const { rows } = table[0].exists
+    ? await client.query<AppliedMigration>(
+        `SELECT version, name, checksum, applied_at AS "appliedAt" FROM schema_migrations ORDER BY version`
+      )
+    : { rows: [] as AppliedMigration[] };
+  const applied = new Map(rows.map((r) => [r.version, r]));
+  const known = new Set(migrations.map((m) => m.version));
+
+  return {
+    applied: rows,
+    pending: migrations.filter((m) => !applied.has(m.version)),
+    modified: migrations.filter((m) => applied.has(m.version) && applied.get(m.version)!.checksum !== m.checksum),
+    missing: rows.filter((r) => !known.has(r.version)),
+  };
+}
+
+function assertConsistent(status: MigrationStatus): void {
+  const problems = [
+    ...status.modified.map((m) => `${m.version}_${m.name} was modified after it was applied`),
+    ...status.missing.map((m) => `${m.version}_${m.name} is applied but missing from db/migrations`),
+  ];
+  if (problems.length > 0) {
+    throw new Error(`Schema migrations out of sync:\n  ${problems.join('\n  ')}`);
+  }
+}
+
+/**
+ * Compare db/migrations with what the database has applied, without changing anything
+ */
+export async function migrationStatus(): Promise<MigrationStatus> {
+  const migrations = loadMigrations();
+  const client = await pool.connect();
+  try {
+    return await readStatus(client, migrations);
+  } finally {
+    client.release();
+  }
+}
+
+// ---------------------------
+// Apply / revert
+// ---------------------------
+
+async function withLock<T>(fn: (client: PoolClient) => Promise<T>): Promise<T> {
+  const client = await pool.connect();
+  try {
+    await client.query('SELECT pg_advisory_lock($1)', [LOCK_KEY]);
+    try {
+      await ensureTable(client);
+      return await fn(client);
+    } finally {
+      await client.query('SELECT pg_advisory_unlock($1)', [LOCK_KEY]);
+    }
+  } finally {
+    client.release();
+  }
+}
+
+/**
+ * Apply every pending migration in version order; returns those applied
+ *
+ * Each migration runs in its own transaction together with its
+ * schema_migrations row, so a failure leaves it unapplied. Refuses to run
+ * while an applied migration was modified or is missing.
+ */
+export async function migrateUp(): Promise<Migration[]> {
+  const migrations = loadMigrations();
+  return withLock(async (client) => {
+    const status = await readStatus(client, migrations);
+    assertConsistent(status);
+
+    for (const m of status.pending) {
+      await client.query('BEGIN');
+      try {
+        await client.query(m.up);
+        await client.query(`INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)`, [
+          m.version,
+          m.name,
+          m.checksum,
+        ]);
+        await client.query('COMMIT');
+      } catch (e: any) {
+        await client.query('ROLLBACK');
+        throw new Error(`Migration ${m.version}_${m.name} failed: ${e.message}`);
+      }
+    }
+    return status.pending;
+  });
+}
+
+/**
+ * Revert the most recently applied migration with its down script; returns
+ * it, or null when nothing is applied
+ */
+export async function migrateDown(): Promise<Migration | null> {
+  const migrations = loadMigrations();
+  return withLock(async (client) => {
+    const status = await readStatus(client, migrations);
+    assertConsistent(status);
+
+    const latest = status.applied[status.applied.length - 1];
+    if (!latest) return null;
+
+    const m = migrations.find((x) => x.version === latest.version)!;
+    if (m.down === null) {
+      throw new Error(`Migration ${m.version}_${m.name} has no down script (${m.version}_${m.name}.down.sql)`);
+    }
+
+    await client.query('BEGIN');
+    try {
+      await client.query(m.down);
+      await client.query('DELETE FROM schema_migrations WHERE version = $1', [m.version]);
+      await client.query('COMMIT');
+    } catch (e: any) {
+      await client.query('ROLLBACK');
+      throw new Error(`Reverting ${m.version}_${m.name} failed: ${e.message}`);
+    }
+    return m;
+  });
+}
+
+/**
+ * Apply pending migrations before a runner starts, unless MIGRATE_ON_STARTUP=false
+ */
+export async function migrateOnStartup(): Promise<void> {
+  if (process.env.MIGRATE_ON_STARTUP === 'false') return;
+
+  const applied = await migrateUp();
+  for (const m of applied) {
+    console.log(`✅ Applied migration ${m.version}_${m.name}`);
+  }
+}
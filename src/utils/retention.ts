@@ -0,0 +1,193 @@
+// src/utils/retention.ts
+// Retention of raw ingestion data: pruning old payloads and caches, then vacuuming what was pruned
+
+import { pool } from '../db';
+import { retentionLastRun, retentionReclaimedBytes, retentionRowsPruned, retentionTableBytes } from './metrics';
+
+// ---------------------------
+// Config / constants
+// ---------------------------
+
+// Rows pruned per statement, so no run holds long row locks
+const PRUNE_BATCH_SIZE = 5_000;
+
+// What can be pruned. Claim events, distributions and every other derived
+// row are never touched: only payloads whose row has served its purpose, and
+// caches that are rebuilt on demand.
+const POLICIES = [
+  {
+    name: 'webhook_payloads',
+    table: 'webhook_deliveries',
+    env: 'RETENTION_WEBHOOK_PAYLOAD_DAYS',
+    defaultDays: 30,
+    // Delivered; failed deliveries keep theirs, an operator may retry them
+    where: `status = 'delivered' AND payload IS NOT NULL AND delivered_at < NOW() - make_interval(days => $1)`,
+    prune: `UPDATE webhook_deliveries SET payload = NULL`,
+  },
+  {
+    name: 'dead_letter_payloads',
+    table: 'ingestion_dead_letters',
+    env: 'RETENTION_DEAD_LETTER_PAYLOAD_DAYS',
+    defaultDays: 30,
+    // Replayed or discarded; pending ones are kept until replayed
+    where: `status <> 'pending' AND payload IS NOT NULL AND COALESCE(replayed_at, last_failed_at) < NOW() - make_interval(days => $1)`,
+    prune: `UPDATE ingestion_dead_letters SET payload = NULL`,
+  },
+  {
+    name: 'slot_block_times',
+    table: 'slot_block_times',
+    env: 'RETENTION_SLOT_BLOCK_TIMES_DAYS',
+    defaultDays: 90,
+    // Events keep the block time they were stamped with
+    where: `created_at < NOW() - make_interval(days => $1)`,
+    prune: `DELETE FROM slot_block_times`,
+  },
+] as const;
+
+// ---------------------------
+// Types
+// ---------------------------
+
+export type VacuumMode = 'off' | 'vacuum' | 'full';
+
+export interface RetentionPolicy {
+  name: string;
+  table: string;
+  /** Rows older than this are pruned; 0 keeps them forever */
+  days: number;
+}
+
+export interface RetentionConfig {
+  policies: RetentionPolicy[];
+  vacuum: VacuumMode;
+}
+
+export interface PruneResult {
+  policy: string;
+  table: string;
+  rows: number;
+}
+
+export interface VacuumResult {
+  table: string;
+  bytesBefore: number;
+  bytesAfter: number;
+}
+
+export interface RetentionResult {
+  pruned: PruneResult[];
+  vacuumed: VacuumResult[];
+}
+
+/**
+ * Retention from RETENTION_*_DAYS (0 disables a policy) and
+ * RETENTION_VACUUM (off, vacuum or full; default vacuum)
+ */
+export function getRetentionConfigFromEnv(): RetentionConfig {
+  const policies = POLICIES.map((p) => {
+    const value = process.env[p.env];
+    const days = value ? Number(value) : p.defaultDays;
+    if (!Number.isInteger(days) || days < 0) {
+      throw new Error(`Invalid ${p.env}: ${value}`);
+    }
+    return { name: p.name, table: p.table, days };
+  });
+
+  const vacuum = process.env.RETENTION_VACUUM || 'vacuum';
+  if (vacuum !== 'off' && vacuum !== 'vacuum' && vacuum !== 'full') {
+    throw new Error(`Invalid RETENTION_VACUUM: ${vacuum}`);
+  }
+  return { policies, vacuum };
+}
+
+// ---------------------------
+// Pruning
+// ---------------------------
+
+/**
+ * Prune rows past each enabled policy's age, in batches; with `dryRun`
+ * only count them
+ */
+export async function pruneRawData(policies: RetentionPolicy[], dryRun = false): Promise<PruneResult[]> {
+  const results: PruneResult[] = [];
+  for (const policy of policies) {
+    if (policy.days === 0) continue;
+    const { where, prune } = POLICIES.find((p) => p.name === policy.name)!;
+    const result: PruneResult = { policy: policy.name, table: policy.table, rows: 0 };
+
+    if (dryRun) {
+      const { rows } = await pool.query<{ count: string }>(
+        `SELECT COUNT(*) AS count FROM ${policy.table} WHERE ${where}`,
+        [policy.days]
+      );
+      result.rows = Number(rows[0].count);
+    } else {
+      for (;;) {
+        const batch = await pool.query(
+          `${prune} WHERE ctid = ANY(ARRAY(SELECT ctid FROM ${policy.table} WHERE ${where} LIMIT ${PRUNE_BATCH_SIZE}))`,
+          [policy.days]
+        );
+        const rows = batch.rowCount ?? 0;
+        result.rows += rows;
+        if (rows < PRUNE_BATCH_SIZE) break;
+      }
+    }
+    results.push(result);
+  }
+  return results;
+}
+
+// ---------------------------
+// Vacuum
+// ---------------------------
+
+export async function tableBytes(table: string): Promise<number> {
+  const { rows } = await pool.query<{ bytes: string }>(`SELECT pg_total_relation_size($1::regclass) AS bytes`, [table]);
+  return Number(rows[0].bytes);
+}
+
+/**
+ * Vacuum and analyze `tables`; `full` rewrites them, returning freed space
+ * to the OS but locking each table while it runs
+ */
+export async function vacuumTables(tables: string[], mode: Exclude<VacuumMode, 'off'>): Promise<VacuumResult[]> {
+  const results: VacuumResult[] = [];
+  for (const table of tables) {
+    const bytesBefore = await tableBytes(table);
+    // VACUUM can't run inside a transaction block; pool.query runs it on its own
+    await pool.query(mode === 'full' ? `VACUUM (FULL, ANALYZE) ${table}` : `VACUUM (ANALYZE) ${table}`);
+    results.push({ table, bytesBefore, bytesAfter: await tableBytes(table) });
+  }
+  return results;
+}
+
+// ---------------------------
+// Run
+// ---------------------------
+
+/**
+ * Prune, then vacuum the tables something was pruned from, recording metrics
+ *
+ * Reclaimed space is the drop in each table's size (with indexes and
+ * TOAST). A plain vacuum makes pruned space reusable but only returns
+ * trailing pages to the OS, so it usually reports little; `full` reports it
+ * all.
+ */
+export async function runRetention(config: RetentionConfig): Promise<RetentionResult> {
+  const pruned = await pruneRawData(config.policies);
+  for (const p of pruned) {
+    retentionRowsPruned.inc({ table: p.table }, p.rows);
+  }
+
+  const tables = [...new Set(pruned.filter((p) => p.rows > 0).map((p) => p.table))];
+  const vacuumed = config.vacuum === 'off' ? [] : await vacuumTables(tables, config.vacuum);
+  for (const v of vacuumed) {
+    retentionReclaimedBytes.inc({ table: v.table }, Math.max(0, v.bytesBefore - v.bytesAfter));
+  }
+
+  for (const table of new Set(config.policies.map((p) => p.table))) {
+    retentionTableBytes.set({ table }, await tableBytes(table));
+  }
+  retentionLastRun.setToCurrentTime();
+  return { pruned, vacuumed };
+}
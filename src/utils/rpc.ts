@@ -2,6 +2,7 @@
 // RPC connection utility with automatic failover support
 
 import { Connection, ConnectionConfig } from '@solana/web3.js';
+import { rpcRequests } from './metrics';
 
 export interface RpcConfig {
   primaryUrl: string;
@@ -17,11 +18,19 @@ interface RpcState {
   failoverCount: number;
 }
 
-const state: RpcState = {
-  primaryHealthy: true,
-  lastFailoverTime: null,
-  failoverCount: 0,
-};
+// Failover state per primary/backup pair, so deployments on different
+// clusters fail over independently
+const states = new Map<string, RpcState>();
+
+function stateFor(config: RpcConfig): RpcState {
+  const key = `${config.primaryUrl}|${config.backupUrl ?? ''}`;
+  let state = states.get(key);
+  if (!state) {
+    state = { primaryHealthy: true, lastFailoverTime: null, failoverCount: 0 };
+    states.set(key, state);
+  }
+  return state;
+}
 
 // Time to wait before trying primary again after failover (5 minutes)
 const PRIMARY_RECOVERY_INTERVAL_MS = 5 * 60 * 1000;
@@ -57,6 +66,8 @@ function createConnection(url: string, commitment: RpcConfig['commitment'] = 'co
  * Get the currently active RPC URL based on health state
  */
 export function getActiveRpcUrl(config: RpcConfig): string {
+  const state = stateFor(config);
+
   // If primary is healthy, use it
   if (state.primaryHealthy) {
     return config.primaryUrl;
@@ -84,6 +95,7 @@ export function getActiveRpcUrl(config: RpcConfig): string {
  * Mark primary RPC as failed and switch to backup
  */
 export function markPrimaryFailed(config: RpcConfig): void {
+  const state = stateFor(config);
   if (state.primaryHealthy && config.backupUrl) {
     state.primaryHealthy = false;
     state.lastFailoverTime = Date.now();
@@ -95,7 +107,8 @@ export function markPrimaryFailed(config: RpcConfig): void {
 /**
  * Mark primary RPC as recovered
  */
-export function markPrimaryRecovered(): void {
+export function markPrimaryRecovered(config: RpcConfig): void {
+  const state = stateFor(config);
   if (!state.primaryHealthy) {
     state.primaryHealthy = true;
     console.log('✅ Primary RPC recovered');
@@ -105,17 +118,15 @@ export function markPrimaryRecovered(): void {
 /**
  * Get current RPC state for debugging/monitoring
  */
-export function getRpcState(): Readonly<RpcState> {
-  return { ...state };
+export function getRpcState(config: RpcConfig): Readonly<RpcState> {
+  return { ...stateFor(config) };
 }
 
 /**
  * Reset RPC state (useful for testing)
  */
 export function resetRpcState(): void {
-  state.primaryHealthy = true;
-  state.lastFailoverTime = null;
-  state.failoverCount = 0;
+  states.clear();
 }
 
 /**
@@ -147,6 +158,7 @@ export async function withFailover<T>(
   const maxRetries = config.maxRetries ?? 3;
   const retryDelayMs = config.retryDelayMs ?? 1000;
 
+  const state = stateFor(config);
   let lastError: Error | null = null;
   let attempts = 0;
 
@@ -157,18 +169,20 @@ export async function withFailover<T>(
 
     try {
       const result = await operation(connection);
+      rpcRequests.inc({ operation: operationName, result: 'ok' });
 
       // If we were on backup and this succeeded, check if we should try primary
       if (!state.primaryHealthy && state.lastFailoverTime) {
         const timeSinceFailover = Date.now() - state.lastFailoverTime;
         if (timeSinceFailover > PRIMARY_RECOVERY_INTERVAL_MS) {
-          markPrimaryRecovered();
+          markPrimaryRecovered(config);
         }
       }
 
       return result;
     } catch (error: any) {
       lastError = error;
+      rpcRequests.inc({ operation: operationName, result: 'error' });
 
       // Check if this is a connection/network error that warrants failover
       if (isFailoverableError(error)) {
@@ -105,7 +105,7 @@ async function testFailoverSimulation() {
 
   // Reset state
   resetRpcState();
-  console.log('Initial state:', getRpcState());
+  console.log('Initial state:', getRpcState(config));
 
   const rpc = new FailoverConnection(config);
   console.log(`Active RPC: ${rpc.getCurrentUrl()}`);
@@ -114,7 +114,7 @@ async function testFailoverSimulation() {
   console.log('\nSimulating primary RPC failure...');
   markPrimaryFailed(config);
 
-  console.log('State after failure:', getRpcState());
+  console.log('State after failure:', getRpcState(config));
   console.log(`Active RPC now: ${rpc.getCurrentUrl()}`);
 
   // Verify we can still make calls (should use backup)
@@ -165,7 +165,7 @@ async function testInvalidUrlFailover() {
       'getSlotWithInvalidPrimary'
     );
     console.log(`  ✅ Got slot from backup: ${slot}`);
-    console.log('\nFinal state:', getRpcState());
+    console.log('\nFinal state:', getRpcState(testConfig));
   } catch (e: any) {
     console.log(`  ❌ Failed: ${e.message}`);
   }
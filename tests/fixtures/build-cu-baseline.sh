@@ -0,0 +1,21 @@
+#!/bin/sh
+# Build the program as it was before Distribution became zero-copy, for the
+# claim compute-unit comparison in tests/merkle-distributor.ts
+#
+# Usage: npm run build:cu-baseline   (writes tests/fixtures/merkle_distributor_borsh.so)
+
+set -e
+
+root=$(git rev-parse --show-toplevel)
+zero_copy=$(git -C "$root" log -1 --format=%H --grep='Convert Distribution to a zero-copy account')
+if [ -z "$zero_copy" ]; then
+  echo "zero-copy commit not found in history" >&2
+  exit 1
+fi
+
+worktree=$(mktemp -d)
+trap 'git -C "$root" worktree remove --force "$worktree"' EXIT
+
+git -C "$root" worktree add --detach "$worktree" "$zero_copy^"
+(cd "$worktree" && anchor build -p merkle_distributor)
+cp "$worktree/target/deploy/merkle_distributor.so" "$root/tests/fixtures/merkle_distributor_borsh.so"
@@ -0,0 +1,703 @@
+// tests/merkle-distributor.ts
+// Program tests for the Merkle distributor, run in-process with bankrun
+//
+// Usage:
+//   anchor test   (builds target/deploy/merkle_distributor.so first)
+//
+// Claims are built with the same helpers the relayer uses, so these tests
+// also cover the TypeScript encoders.
+
+import assert from 'assert';
+import { createHash, randomBytes } from 'crypto';
+import { existsSync } from 'fs';
+
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js';
+import {
+  AccountLayout,
+  ExtensionType,
+  MINT_SIZE,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountInstruction,
+  createInitializeMint2Instruction,
+  createInitializeTransferHookInstruction,
+  createMintToInstruction,
+  createTransferCheckedInstruction,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+} from '@solana/spl-token';
+import { BanksClient, ProgramTestContext, start, startAnchor } from 'solana-bankrun';
+
+import {
+  buildClaimBatchInstruction,
+  buildClaimInstruction,
+  getClaimPda,
+  getConfigPda,
+  getDistributionPda,
+  getRegistryEntryPda,
+  getRegistryPda,
+  getVaultPda,
+} from '../src/merkle/relayer';
+import { MerkleTree, buildMerkleData } from '../src/merkle/tree';
+import {
+  HASH_KIND_KECCAK,
+  MERKLE_DISTRIBUTOR_PROGRAM_ID,
+  MerkleProof,
+  PROOF_MODE_SORTED,
+  hashSchemeIndex,
+} from '../src/merkle/types';
+
+const PROGRAM_ID = MERKLE_DISTRIBUTOR_PROGRAM_ID;
+const DECIMALS = 6;
+
+const AMOUNTS = [100n, 200n, 300n, 400n, 500n, 600n];
+
+// Program built before Distribution became zero-copy (npm run build:cu-baseline)
+const BORSH_BASELINE = 'merkle_distributor_borsh';
+
+/**
+ * Anchor discriminator: first 8 bytes of sha256("global:<name>")
+ */
+function discriminator(name: string): Buffer {
+  return createHash('sha256').update(`global:${name}`).digest().subarray(0, 8);
+}
+
+/**
+ * [discriminator (8)] [index (8)] [amount (8)] [proof_len (4)] [proof (32 * len)]
+ */
+function encodeIndexAmountProof(name: string, index: number, amount: bigint, proof: string[]): Buffer {
+  const data = Buffer.alloc(8 + 8 + 8 + 4 + proof.length * 32);
+  discriminator(name).copy(data, 0);
+  data.writeBigUInt64LE(BigInt(index), 8);
+  data.writeBigUInt64LE(amount, 16);
+  data.writeUInt32LE(proof.length, 24);
+  proof.forEach((node, i) => Buffer.from(node, 'hex').copy(data, 28 + i * 32));
+  return data;
+}
+
+function encodeU64(name: string, value: bigint): Buffer {
+  const data = Buffer.alloc(8 + 8);
+  discriminator(name).copy(data, 0);
+  data.writeBigUInt64LE(value, 8);
+  return data;
+}
+
+/**
+ * [discriminator (8)] [distribution_id (32)] [merkle_root (32)] [total_amount (8)]
+ * [num_recipients (8)] [clawback_destination (32)] [require_recipient_signature (1)]
+ * [hash_scheme (1)] [hash_kind (1)] [proof_mode (1)] [id_epoch (1, None)]
+ */
+function buildInitializeInstruction(
+  authority: PublicKey,
+  mint: PublicKey,
+  distributionId: Buffer,
+  merkleRoot: Buffer,
+  totalAmount: bigint,
+  numRecipients: number,
+  clawbackDestination: PublicKey,
+  registryIndex: bigint = 0n,
+  tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+): TransactionInstruction {
+  const data = Buffer.alloc(8 + 32 + 32 + 8 + 8 + 32 + 1 + 1 + 1 + 1 + 1);
+  discriminator('initialize').copy(data, 0);
+  distributionId.copy(data, 8);
+  merkleRoot.copy(data, 40);
+  data.writeBigUInt64LE(totalAmount, 72);
+  data.writeBigUInt64LE(BigInt(numRecipients), 80);
+  clawbackDestination.toBuffer().copy(data, 88);
+  data.writeUInt8(0, 120);
+  data.writeUInt8(hashSchemeIndex('v2'), 121);
+  data.writeUInt8(HASH_KIND_KECCAK, 122);
+  data.writeUInt8(PROOF_MODE_SORTED, 123);
+  data.writeUInt8(0, 124);
+
+  return new TransactionInstruction({
+    programId: PROGRAM_ID,
+    keys: [
+      { pubkey: authority, isSigner: true, isWritable: true },
+      { pubkey: getDistributionPda(PROGRAM_ID, distributionId)[0], isSigner: false, isWritable: true },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: getVaultPda(PROGRAM_ID, distributionId)[0], isSigner: false, isWritable: true },
+      { pubkey: getRegistryPda(PROGRAM_ID)[0], isSigner: false, isWritable: true },
+      { pubkey: getRegistryEntryPda(PROGRAM_ID, registryIndex)[0], isSigner: false, isWritable: true },
+      { pubkey: getConfigPda(PROGRAM_ID)[0], isSigner: false, isWritable: false },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+    ],
+    data,
+  });
+}
+
+function encodePubkey(name: string, value: PublicKey): Buffer {
+  return Buffer.concat([discriminator(name), value.toBuffer()]);
+}
+
+function getEscrowPda(distribution: PublicKey, recipient: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('escrow'), distribution.toBuffer(), recipient.toBuffer()],
+    PROGRAM_ID
+  )[0];
+}
+
+/**
+ * Claim record flags and escrow balance
+ * Layout: [discriminator (8)] [distribution (32)] [index (8)] [recipient (32)]
+ * [amount (8)] [claimed_at (8)] [memo_len (4)] [memo] [escrowed (1)]
+ * [renounced (1)] [escrowed_amount (8)] [bump (1)]
+ */
+function decodeClaimRecord(data: Uint8Array): { escrowed: boolean; renounced: boolean; escrowedAmount: bigint } {
+  const buffer = Buffer.from(data);
+  const offset = 96 + 4 + buffer.readUInt32LE(96);
+  return {
+    escrowed: buffer[offset] === 1,
+    renounced: buffer[offset + 1] === 1,
+    escrowedAmount: buffer.readBigUInt64LE(offset + 2),
+  };
+}
+
+type Outcome = { error: string | null; logs: string[]; computeUnits: bigint };
+
+async function sendWith(
+  client: BanksClient,
+  authority: Keypair,
+  instructions: TransactionInstruction[],
+  signers: Keypair[] = []
+): Promise<Outcome> {
+  const tx = new Transaction().add(...instructions);
+  const [blockhash] = (await client.getLatestBlockhash())!;
+  tx.recentBlockhash = blockhash;
+  tx.feePayer = signers[0]?.publicKey ?? authority.publicKey;
+  tx.sign(...(signers.length > 0 ? signers : [authority]));
+
+  const { result, meta } = await client.tryProcessTransaction(tx);
+  return {
+    error: result,
+    logs: meta?.logMessages ?? [],
+    computeUnits: meta?.computeUnitsConsumed ?? 0n,
+  };
+}
+
+async function sendOkWith(
+  client: BanksClient,
+  authority: Keypair,
+  instructions: TransactionInstruction[],
+  signers: Keypair[] = []
+): Promise<Outcome> {
+  const outcome = await sendWith(client, authority, instructions, signers);
+  assert.strictEqual(outcome.error, null, outcome.logs.join('\n'));
+  return outcome;
+}
+
+interface Deployment {
+  mint: PublicKey;
+  sourceAta: PublicKey;
+  recipientAtas: PublicKey[];
+  distribution: PublicKey;
+  vault: PublicKey;
+  tree: MerkleTree;
+  proofs: MerkleProof[];
+}
+
+/**
+ * Mint, recipient ATAs and a distribution over AMOUNTS, funded from the
+ * authority's ATA
+ *
+ * Passing the same mint and distribution id gives the same addresses (and
+ * PDA bumps) in different banks.
+ */
+async function deployDistribution(
+  client: BanksClient,
+  authority: Keypair,
+  recipients: Keypair[],
+  mintKeypair: Keypair = Keypair.generate(),
+  distributionId: Buffer = randomBytes(32)
+): Promise<Deployment> {
+  const mint = mintKeypair.publicKey;
+  const rent = await client.getRent();
+  const sourceAta = getAssociatedTokenAddressSync(mint, authority.publicKey);
+  const total = AMOUNTS.reduce((sum, amount) => sum + amount, 0n);
+
+  await sendOkWith(
+    client,
+    authority,
+    [
+      SystemProgram.createAccount({
+        fromPubkey: authority.publicKey,
+        newAccountPubkey: mint,
+        lamports: Number(rent.minimumBalance(BigInt(MINT_SIZE))),
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(mint, DECIMALS, authority.publicKey, null),
+      createAssociatedTokenAccountInstruction(authority.publicKey, sourceAta, authority.publicKey, mint),
+      createMintToInstruction(mint, sourceAta, authority.publicKey, total),
+    ],
+    [authority, mintKeypair]
+  );
+
+  const recipientAtas: PublicKey[] = [];
+  for (const recipient of recipients) {
+    const ata = getAssociatedTokenAddressSync(mint, recipient.publicKey);
+    recipientAtas.push(ata);
+    await sendOkWith(client, authority, [
+      createAssociatedTokenAccountInstruction(authority.publicKey, ata, authority.publicKey, mint),
+    ]);
+  }
+
+  const merkle = buildMerkleData(
+    distributionId.toString('hex'),
+    recipients.map((recipient, i) => ({ wallet: recipient.publicKey.toBase58(), amount: AMOUNTS[i] })),
+    'v2'
+  );
+  const distribution = getDistributionPda(PROGRAM_ID, distributionId)[0];
+  const vault = getVaultPda(PROGRAM_ID, distributionId)[0];
+
+  await sendOkWith(client, authority, [
+    buildInitializeInstruction(
+      authority.publicKey,
+      mint,
+      distributionId,
+      Buffer.from(merkle.root, 'hex'),
+      total,
+      recipients.length,
+      sourceAta
+    ),
+    createTransferCheckedInstruction(sourceAta, mint, vault, authority.publicKey, total, DECIMALS),
+  ]);
+
+  return { mint, sourceAta, recipientAtas, distribution, vault, tree: merkle.tree, proofs: merkle.proofs };
+}
+
+function claimInstruction(
+  deployment: Deployment,
+  authority: Keypair,
+  recipients: Keypair[],
+  index: number,
+  payer: PublicKey = authority.publicKey
+): TransactionInstruction {
+  const { distribution } = deployment;
+  return buildClaimInstruction(
+    PROGRAM_ID,
+    distribution,
+    getClaimPda(PROGRAM_ID, distribution, index)[0],
+    deployment.vault,
+    deployment.mint,
+    recipients[index].publicKey,
+    deployment.recipientAtas[index],
+    payer,
+    authority.publicKey,
+    index,
+    AMOUNTS[index],
+    deployment.proofs[index].proof
+  );
+}
+
+describe('merkle-distributor', () => {
+  let context: ProgramTestContext;
+  let client: BanksClient;
+  let authority: Keypair;
+  let deployment: Deployment;
+  let mint: PublicKey;
+  let sourceAta: PublicKey;
+  let distribution: PublicKey;
+  let vault: PublicKey;
+  let tree: MerkleTree;
+  let proofs: MerkleProof[];
+  let recipientAtas: PublicKey[];
+  const recipients = AMOUNTS.map(() => Keypair.generate());
+
+  function send(instructions: TransactionInstruction[], signers: Keypair[] = []): Promise<Outcome> {
+    return sendWith(client, authority, instructions, signers);
+  }
+
+  function sendOk(instructions: TransactionInstruction[], signers: Keypair[] = []): Promise<Outcome> {
+    return sendOkWith(client, authority, instructions, signers);
+  }
+
+  async function tokenBalance(account: PublicKey): Promise<bigint> {
+    const info = await client.getAccount(account);
+    assert.ok(info, `token account ${account.toBase58()} missing`);
+    return AccountLayout.decode(Buffer.from(info.data)).amount;
+  }
+
+  function fund(keypair: Keypair) {
+    context.setAccount(keypair.publicKey, {
+      lamports: 10 * LAMPORTS_PER_SOL,
+      data: Buffer.alloc(0),
+      owner: SystemProgram.programId,
+      executable: false,
+    });
+  }
+
+  function claimIx(index: number, payer: PublicKey = authority.publicKey): TransactionInstruction {
+    return claimInstruction(deployment, authority, recipients, index, payer);
+  }
+
+  before(async () => {
+    context = await startAnchor('.', [], []);
+    client = context.banksClient;
+    authority = context.payer;
+
+    deployment = await deployDistribution(client, authority, recipients);
+    ({ mint, sourceAta, recipientAtas, distribution, vault, tree, proofs } = deployment);
+    assert.strictEqual(await tokenBalance(vault), AMOUNTS.reduce((sum, amount) => sum + amount, 0n));
+  });
+
+  it('initialize and set_operator run before the global config exists', async () => {
+    const config = getConfigPda(PROGRAM_ID)[0];
+    assert.strictEqual(await client.getAccount(config), null);
+
+    // Distribution layout: [discriminator (8)] [authority (32)] [operator (32)] ...
+    const operator = async () =>
+      new PublicKey(Buffer.from((await client.getAccount(distribution))!.data).subarray(40, 72));
+    assert.ok((await operator()).equals(authority.publicKey));
+
+    const setOperatorIx = (newOperator: PublicKey) =>
+      new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: distribution, isSigner: false, isWritable: true },
+          { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+          { pubkey: config, isSigner: false, isWritable: false },
+        ],
+        data: encodePubkey('set_operator', newOperator),
+      });
+
+    const relayer = Keypair.generate().publicKey;
+    await sendOk([setOperatorIx(relayer)]);
+    assert.ok((await operator()).equals(relayer));
+    await sendOk([setOperatorIx(authority.publicKey)]);
+    assert.ok((await operator()).equals(authority.publicKey));
+  });
+
+  it('claim pays the recipient once', async () => {
+    await sendOk([claimIx(0)]);
+    assert.strictEqual(await tokenBalance(recipientAtas[0]), AMOUNTS[0]);
+
+    // Replayed by another payer (distinct signature): the claim record exists
+    const relayer = Keypair.generate();
+    fund(relayer);
+    const replay = await send([claimIx(0, relayer.publicKey)], [relayer]);
+    assert.notStrictEqual(replay.error, null);
+    assert.ok(replay.logs.some((line) => line.includes('already in use')), replay.logs.join('\n'));
+    assert.strictEqual(await tokenBalance(recipientAtas[0]), AMOUNTS[0]);
+  });
+
+  it('check_claim reports claim status without writing', async () => {
+    const check = async (index: number, amount: bigint) => {
+      const tx = new Transaction().add(
+        new TransactionInstruction({
+          programId: PROGRAM_ID,
+          keys: [
+            { pubkey: distribution, isSigner: false, isWritable: false },
+            { pubkey: getClaimPda(PROGRAM_ID, distribution, index)[0], isSigner: false, isWritable: false },
+            { pubkey: recipients[index].publicKey, isSigner: false, isWritable: false },
+          ],
+          data: encodeIndexAmountProof('check_claim', index, amount, proofs[index].proof),
+        })
+      );
+      tx.recentBlockhash = (await client.getLatestBlockhash())![0];
+      tx.feePayer = authority.publicKey;
+      tx.sign(authority);
+
+      const { result, meta } = await client.simulateTransaction(tx);
+      assert.strictEqual(result, null, meta?.logMessages.join('\n'));
+      // ClaimCheck: proof_valid, index_in_range, claimed, paused, recipient_signed, claimable
+      const [proofValid, indexInRange, claimed, paused, recipientSigned, claimable] = Array.from(
+        meta!.returnData!.data,
+        (byte) => byte === 1
+      );
+      return { proofValid, indexInRange, claimed, paused, recipientSigned, claimable };
+    };
+
+    assert.deepStrictEqual(await check(1, AMOUNTS[1]), {
+      proofValid: true,
+      indexInRange: true,
+      claimed: false,
+      paused: false,
+      recipientSigned: true,
+      claimable: true,
+    });
+    assert.deepStrictEqual(await check(0, AMOUNTS[0]), {
+      proofValid: true,
+      indexInRange: true,
+      claimed: true,
+      paused: false,
+      recipientSigned: true,
+      claimable: false,
+    });
+    // An invalid claim is reported, not rejected
+    const tampered = await check(1, AMOUNTS[1] + 1n);
+    assert.strictEqual(tampered.proofValid, false);
+    assert.strictEqual(tampered.claimable, false);
+  });
+
+  it('claim costs fewer compute units than with the Borsh Distribution', async function () {
+    if (!existsSync(`tests/fixtures/${BORSH_BASELINE}.so`)) {
+      this.skip();
+    }
+
+    // Same recipients, mint and distribution id in both banks, so both
+    // claims hash the same proof and derive the same PDAs
+    const bankRecipients = AMOUNTS.map(() => Keypair.generate());
+    const mintKeypair = Keypair.generate();
+    const distributionId = randomBytes(32);
+
+    const claimComputeUnits = async (bank: ProgramTestContext): Promise<bigint> => {
+      const bankDeployment = await deployDistribution(
+        bank.banksClient,
+        bank.payer,
+        bankRecipients,
+        mintKeypair,
+        distributionId
+      );
+      const outcome = await sendOkWith(bank.banksClient, bank.payer, [
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+        claimInstruction(bankDeployment, bank.payer, bankRecipients, 0),
+      ]);
+      return outcome.computeUnits;
+    };
+
+    const borsh = await claimComputeUnits(await start([{ name: BORSH_BASELINE, programId: PROGRAM_ID }], []));
+    const zeroCopy = await claimComputeUnits(await startAnchor('.', [], []));
+    assert.ok(zeroCopy < borsh, `claim used ${zeroCopy} CU zero-copy vs ${borsh} CU Borsh`);
+  });
+
+  it('claim rejects an amount outside the tree', async () => {
+    const ix = claimIx(1);
+    ix.data.writeBigUInt64LE(AMOUNTS[1] + 1n, 16);
+    const outcome = await send([ix]);
+    assert.notStrictEqual(outcome.error, null);
+    assert.strictEqual(await tokenBalance(recipientAtas[1]), 0n);
+
+    await sendOk([claimIx(1)]);
+    assert.strictEqual(await tokenBalance(recipientAtas[1]), AMOUNTS[1]);
+  });
+
+  it('claim_batch pays every leaf of a multiproof', async () => {
+    const multiProof = tree.getMultiProof([2, 3]);
+    const claims = multiProof.indices.map((index) => ({
+      index,
+      amount: AMOUNTS[index],
+      recipient: recipients[index].publicKey,
+      recipientAta: recipientAtas[index],
+    }));
+
+    await sendOk([
+      buildClaimBatchInstruction(
+        PROGRAM_ID,
+        distribution,
+        vault,
+        mint,
+        authority.publicKey,
+        authority.publicKey,
+        claims,
+        multiProof
+      ),
+    ]);
+    assert.strictEqual(await tokenBalance(recipientAtas[2]), AMOUNTS[2]);
+    assert.strictEqual(await tokenBalance(recipientAtas[3]), AMOUNTS[3]);
+  });
+
+  it('claim_batch rejects mints with a transfer hook', async () => {
+    // Token-2022 mint with a hook program that is never invoked: the batch
+    // is rejected before any transfer, so the vault stays unfunded
+    const hookMintKeypair = Keypair.generate();
+    const hookMint = hookMintKeypair.publicKey;
+    const space = getMintLen([ExtensionType.TransferHook]);
+    const rent = await client.getRent();
+    await sendOk(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: authority.publicKey,
+          newAccountPubkey: hookMint,
+          lamports: Number(rent.minimumBalance(BigInt(space))),
+          space,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          hookMint,
+          authority.publicKey,
+          Keypair.generate().publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMint2Instruction(hookMint, DECIMALS, authority.publicKey, null, TOKEN_2022_PROGRAM_ID),
+      ],
+      [authority, hookMintKeypair]
+    );
+
+    // Second distribution in the bank: registry entry 1
+    const distributionId = randomBytes(32);
+    await sendOk([
+      buildInitializeInstruction(
+        authority.publicKey,
+        hookMint,
+        distributionId,
+        tree.getRoot(),
+        AMOUNTS.reduce((sum, amount) => sum + amount, 0n),
+        recipients.length,
+        authority.publicKey,
+        1n,
+        TOKEN_2022_PROGRAM_ID
+      ),
+    ]);
+
+    const multiProof = tree.getMultiProof([2, 3]);
+    const outcome = await send([
+      buildClaimBatchInstruction(
+        PROGRAM_ID,
+        getDistributionPda(PROGRAM_ID, distributionId)[0],
+        getVaultPda(PROGRAM_ID, distributionId)[0],
+        hookMint,
+        authority.publicKey,
+        authority.publicKey,
+        multiProof.indices.map((index) => ({
+          index,
+          amount: AMOUNTS[index],
+          recipient: recipients[index].publicKey,
+          recipientAta: getAssociatedTokenAddressSync(
+            hookMint,
+            recipients[index].publicKey,
+            false,
+            TOKEN_2022_PROGRAM_ID
+          ),
+        })),
+        multiProof,
+        TOKEN_2022_PROGRAM_ID
+      ),
+    ]);
+    assert.notStrictEqual(outcome.error, null);
+    assert.ok(
+      outcome.logs.some((line) => line.includes('TransferHookUnsupported')),
+      outcome.logs.join('\n')
+    );
+  });
+
+  it('claim_to_escrow parks the tokens until release_escrow', async () => {
+    const index = 4;
+    const recipient = recipients[index].publicKey;
+    const claimRecord = getClaimPda(PROGRAM_ID, distribution, index)[0];
+    const escrow = getEscrowPda(distribution, recipient);
+
+    await sendOk([
+      new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: distribution, isSigner: false, isWritable: true },
+          { pubkey: claimRecord, isSigner: false, isWritable: true },
+          { pubkey: vault, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: recipient, isSigner: false, isWritable: false },
+          { pubkey: escrow, isSigner: false, isWritable: true },
+          { pubkey: authority.publicKey, isSigner: true, isWritable: true },
+          { pubkey: authority.publicKey, isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: encodeIndexAmountProof('claim_to_escrow', index, AMOUNTS[index], proofs[index].proof),
+      }),
+    ]);
+    assert.strictEqual(await tokenBalance(escrow), AMOUNTS[index]);
+    assert.strictEqual(await tokenBalance(recipientAtas[index]), 0n);
+    assert.deepStrictEqual(decodeClaimRecord((await client.getAccount(claimRecord))!.data), {
+      escrowed: true,
+      renounced: false,
+      escrowedAmount: AMOUNTS[index],
+    });
+
+    // Tokens sent to the escrow directly are not released against the claim
+    const stray = 7n;
+    await sendOk([createMintToInstruction(mint, escrow, authority.publicKey, stray)]);
+
+    const releaseIx = () =>
+      new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: distribution, isSigner: false, isWritable: true },
+          { pubkey: claimRecord, isSigner: false, isWritable: true },
+          { pubkey: escrow, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: recipient, isSigner: false, isWritable: false },
+          { pubkey: recipientAtas[index], isSigner: false, isWritable: true },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: encodeU64('release_escrow', BigInt(index)),
+      });
+
+    await sendOk([releaseIx()]);
+    assert.strictEqual(await tokenBalance(recipientAtas[index]), AMOUNTS[index]);
+    assert.strictEqual(await tokenBalance(escrow), stray);
+    assert.deepStrictEqual(decodeClaimRecord((await client.getAccount(claimRecord))!.data), {
+      escrowed: true,
+      renounced: false,
+      escrowedAmount: 0n,
+    });
+
+    // Released once: a second release finds nothing left (distinct fee payer)
+    const relayer = Keypair.generate();
+    fund(relayer);
+    const again = await send([releaseIx()], [relayer]);
+    assert.notStrictEqual(again.error, null);
+    assert.ok(again.logs.some((line) => line.includes('EscrowEmpty')), again.logs.join('\n'));
+    assert.strictEqual(await tokenBalance(escrow), stray);
+  });
+
+  it('renounce_claim leaves the allocation to clawback', async () => {
+    const index = 5;
+    const recipient = recipients[index];
+    const claimRecord = getClaimPda(PROGRAM_ID, distribution, index)[0];
+    fund(recipient);
+
+    await sendOk(
+      [
+        new TransactionInstruction({
+          programId: PROGRAM_ID,
+          keys: [
+            { pubkey: distribution, isSigner: false, isWritable: true },
+            { pubkey: claimRecord, isSigner: false, isWritable: true },
+            { pubkey: recipient.publicKey, isSigner: true, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          ],
+          data: encodeIndexAmountProof('renounce_claim', index, AMOUNTS[index], proofs[index].proof),
+        }),
+      ],
+      [recipient]
+    );
+    const record = decodeClaimRecord((await client.getAccount(claimRecord))!.data);
+    assert.deepStrictEqual(record, { escrowed: false, renounced: true, escrowedAmount: 0n });
+    assert.strictEqual(await tokenBalance(recipientAtas[index]), 0n);
+
+    // The leaf is consumed: the recipient can no longer claim it
+    const claim = await send([claimIx(index)]);
+    assert.notStrictEqual(claim.error, null);
+
+    // The renounced amount can be clawed back (to the funding account)
+    const vaultBefore = await tokenBalance(vault);
+    await sendOk([
+      new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: distribution, isSigner: false, isWritable: true },
+          { pubkey: vault, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: sourceAta, isSigner: false, isWritable: true },
+          { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: encodeU64('clawback', AMOUNTS[index]),
+      }),
+    ]);
+    assert.strictEqual(await tokenBalance(vault), vaultBefore - AMOUNTS[index]);
+    assert.strictEqual(await tokenBalance(sourceAta), AMOUNTS[index]);
+  });
+});
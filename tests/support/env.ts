@@ -0,0 +1,8 @@
+// tests/support/env.ts
+// Loaded before the program tests (mocha --require)
+//
+// The instruction builders live next to the relayer, which imports the
+// database pool; the pool only needs a connection string to be created and
+// never connects unless queried.
+
+process.env.DATABASE_URL ??= 'postgres://localhost/unused';